@@ -552,6 +552,7 @@ run_wizard_smoke() {
             fi
         else
             run_bin_cmd "wizard build" "$BIN_GREENTIC_COMPONENT" build --manifest "$wizard_manifest" --no-flow
+            run_bin_cmd "wizard validate" "$BIN_GREENTIC_COMPONENT" validate --manifest "$wizard_manifest"
             local wizard_wasm
             wizard_wasm=$(jq -r '.artifacts.component_wasm' "$wizard_manifest")
             local wizard_wasm_path="$wizard_root/$wizard_wasm"
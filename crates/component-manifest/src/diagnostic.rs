@@ -0,0 +1,406 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::presets::CAPABILITY_PRESETS;
+use crate::role::ComponentRole;
+use crate::types::ManifestError;
+
+/// One manifest problem in a form editors and CI can act on: a stable code,
+/// the JSON pointer of the offending field and, when there is an obvious
+/// fix, a suggestion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ManifestDiagnostic {
+    /// Stable identifier, e.g. `manifest.operation.duplicate`.
+    pub code: String,
+    /// JSON pointer into the manifest (RFC 6901); empty for the document.
+    pub pointer: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    /// 1-based position of `pointer` in the manifest text, once located.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+impl ManifestDiagnostic {
+    pub fn new(
+        code: impl Into<String>,
+        pointer: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            code: code.into(),
+            pointer: pointer.into(),
+            message: message.into(),
+            suggestion: None,
+            line: None,
+            column: None,
+        }
+    }
+
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+
+    /// Fills `line`/`column` from the manifest source, falling back to the
+    /// nearest enclosing field that exists.
+    pub fn locate(mut self, text: &str) -> Self {
+        let mut pointer = self.pointer.as_str();
+        loop {
+            if let Some((line, column)) = locate_pointer(text, pointer) {
+                self.line = Some(line);
+                self.column = Some(column);
+                return self;
+            }
+            match pointer.rfind('/') {
+                Some(idx) => pointer = &pointer[..idx],
+                None => return self,
+            }
+        }
+    }
+}
+
+impl ManifestError {
+    /// Stable code for this error, independent of its message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ManifestError::Json(_) => "manifest.json",
+            ManifestError::ConfigSchemaNotObject => "manifest.config_schema.not_object",
+            ManifestError::InvalidConfigSchema(_) => "manifest.config_schema.invalid",
+            ManifestError::InvalidExportSchema { .. } => "manifest.export.schema_invalid",
+            ManifestError::MissingCapabilities => "manifest.capabilities.missing",
+            ManifestError::MissingExports => "manifest.exports.missing",
+            ManifestError::DuplicateCapability(_) => "manifest.capability.duplicate",
+            ManifestError::DuplicateSecret(_) => "manifest.secret.duplicate",
+            ManifestError::DuplicateOperation(_) => "manifest.operation.duplicate",
+            ManifestError::DuplicateDependency(_) => "manifest.dependency.duplicate",
+            ManifestError::InvalidDependency { .. } => "manifest.dependency.invalid",
+            ManifestError::InvalidDeprecation { .. } => "manifest.deprecation.invalid",
+            ManifestError::InvalidSecret(_) => "manifest.secret.invalid_key",
+            ManifestError::InvalidSecretRequirement { .. } => "manifest.secret.invalid",
+            ManifestError::InvalidCapability(_) => "manifest.capability.invalid",
+            ManifestError::InvalidNetCapability(_) => "manifest.capabilities.net.invalid",
+            ManifestError::InvalidToolsCapability(_) => "manifest.capabilities.tools.invalid",
+            ManifestError::UnknownRole(_) => "manifest.role.unknown",
+            ManifestError::UnknownCapabilityPreset(_) => "manifest.capabilities.preset.unknown",
+            ManifestError::InvalidOperation(_) => "manifest.operation.invalid",
+            ManifestError::InvalidWitPackage { .. } => "manifest.wit_compat.package",
+            ManifestError::InvalidVersionReq { .. } => "manifest.wit_compat.version_req",
+            ManifestError::EmptyField(_) => "manifest.field.empty",
+            ManifestError::NonOverridableField(_) => "manifest.overlay.non_overridable",
+            ManifestError::InvalidOverlay(_) => "manifest.overlay.invalid",
+            ManifestError::InvalidEnvironment(_) => "manifest.overlay.env_invalid",
+            ManifestError::OverlayIo { .. } => "manifest.overlay.io",
+        }
+    }
+
+    /// The error as a diagnostic. Named entries (operations, secrets,
+    /// dependencies) are resolved to their index in `manifest` when given.
+    pub fn diagnostic(&self, manifest: Option<&Value>) -> ManifestDiagnostic {
+        let named = |array: &str, key: &str, name: &str| match manifest {
+            Some(manifest) => index_pointer(manifest, array, key, name),
+            None => format!("/{array}"),
+        };
+        let (pointer, suggestion): (String, Option<String>) = match self {
+            ManifestError::Json(_) => (String::new(), None),
+            ManifestError::ConfigSchemaNotObject => (
+                "/config_schema".into(),
+                Some("use an object schema, e.g. {\"type\": \"object\"}".into()),
+            ),
+            ManifestError::InvalidConfigSchema(reason) => (
+                format!(
+                    "/config_schema{}",
+                    reason_pointer(reason).unwrap_or_default()
+                ),
+                None,
+            ),
+            ManifestError::InvalidExportSchema { operation, reason } => {
+                let export = named("exports", "operation", operation);
+                let field = ["input_schema", "output_schema"]
+                    .into_iter()
+                    .find(|field| reason.starts_with(field));
+                let pointer = match field {
+                    Some(field) => format!(
+                        "{export}/{field}{}",
+                        reason_pointer(reason).unwrap_or_default()
+                    ),
+                    None => export,
+                };
+                (pointer, None)
+            }
+            ManifestError::MissingCapabilities => (
+                "/capabilities".into(),
+                Some("declare at least one capability".into()),
+            ),
+            ManifestError::MissingExports => (
+                "/exports".into(),
+                Some("declare at least one export".into()),
+            ),
+            ManifestError::DuplicateCapability(name) => (
+                last_string_pointer(manifest, "capabilities", name),
+                Some("remove the repeated entry".into()),
+            ),
+            ManifestError::DuplicateSecret(key) => (
+                last_index_pointer(manifest, "secret_requirements", "key", key),
+                Some("remove the repeated entry".into()),
+            ),
+            ManifestError::DuplicateOperation(name) => (
+                last_index_pointer(manifest, "exports", "operation", name),
+                Some("rename or remove the repeated operation".into()),
+            ),
+            ManifestError::DuplicateDependency(id) => (
+                last_index_pointer(manifest, "dependencies", "id", id),
+                Some("remove the repeated entry".into()),
+            ),
+            ManifestError::InvalidDependency { id, .. } => (named("dependencies", "id", id), None),
+            ManifestError::InvalidDeprecation { subject, .. } => {
+                (named("exports", "operation", subject), None)
+            }
+            ManifestError::InvalidSecret(key)
+            | ManifestError::InvalidSecretRequirement { key, .. } => (
+                format!("{}/key", named("secret_requirements", "key", key)),
+                None,
+            ),
+            ManifestError::InvalidCapability(name) => (
+                last_string_pointer(manifest, "capabilities", name),
+                Some("capability names match ^[a-z][a-z0-9_.:-]*$".into()),
+            ),
+            ManifestError::InvalidNetCapability(_) => ("/capabilities/net".into(), None),
+            ManifestError::InvalidToolsCapability(_) => ("/capabilities/tools/allow".into(), None),
+            ManifestError::UnknownRole(_) => (
+                "/role".into(),
+                Some(format!(
+                    "use one of {}",
+                    ComponentRole::ALL.map(ComponentRole::as_str).join(", ")
+                )),
+            ),
+            ManifestError::UnknownCapabilityPreset(_) => (
+                "/capabilities/preset".into(),
+                Some(format!("use one of {}", CAPABILITY_PRESETS.join(", "))),
+            ),
+            ManifestError::InvalidOperation(name) => (
+                format!("{}/operation", named("exports", "operation", name)),
+                Some("operation names match ^[a-z][a-z0-9_.:-]*$".into()),
+            ),
+            ManifestError::InvalidWitPackage { .. } => (
+                "/wit_compat/package".into(),
+                Some("set it to `greentic:component`".into()),
+            ),
+            ManifestError::InvalidVersionReq { field, .. } => (
+                format!("/{}", field.replace('.', "/")),
+                Some("use a semver requirement such as `>=0.4.0`".into()),
+            ),
+            ManifestError::EmptyField(field) => (format!("/{field}"), None),
+            ManifestError::NonOverridableField(field) => (
+                format!("/{}", field.replace('.', "/")),
+                Some(format!(
+                    "overlays may only patch {}",
+                    crate::OVERRIDABLE_FIELDS.join(", ")
+                )),
+            ),
+            ManifestError::InvalidOverlay(_)
+            | ManifestError::InvalidEnvironment(_)
+            | ManifestError::OverlayIo { .. } => (String::new(), None),
+        };
+        let mut diagnostic = ManifestDiagnostic::new(self.code(), pointer, self.to_string());
+        diagnostic.suggestion = suggestion;
+        if let ManifestError::Json(err) = self
+            && err.line() > 0
+        {
+            diagnostic.line = Some(err.line());
+            diagnostic.column = Some(err.column());
+        }
+        diagnostic
+    }
+}
+
+/// Escapes one reference token for a JSON pointer.
+pub fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// `/array/<i>` for the first entry of `manifest[array]` whose `key` equals
+/// `name`, or `/array` when there is none.
+pub fn index_pointer(manifest: &Value, array: &str, key: &str, name: &str) -> String {
+    manifest
+        .get(array)
+        .and_then(Value::as_array)
+        .and_then(|entries| {
+            entries
+                .iter()
+                .position(|entry| entry.get(key).and_then(Value::as_str) == Some(name))
+        })
+        .map(|index| format!("/{array}/{index}"))
+        .unwrap_or_else(|| format!("/{array}"))
+}
+
+/// Like [`index_pointer`] but for the last match, which is the one a
+/// duplicate check trips over.
+fn last_index_pointer(manifest: Option<&Value>, array: &str, key: &str, name: &str) -> String {
+    manifest
+        .and_then(|manifest| manifest.get(array))
+        .and_then(Value::as_array)
+        .and_then(|entries| {
+            entries
+                .iter()
+                .rposition(|entry| entry.get(key).and_then(Value::as_str) == Some(name))
+        })
+        .map(|index| format!("/{array}/{index}"))
+        .unwrap_or_else(|| format!("/{array}"))
+}
+
+fn last_string_pointer(manifest: Option<&Value>, array: &str, name: &str) -> String {
+    manifest
+        .and_then(|manifest| manifest.get(array))
+        .and_then(Value::as_array)
+        .and_then(|entries| {
+            entries
+                .iter()
+                .rposition(|entry| entry.as_str() == Some(name))
+        })
+        .map(|index| format!("/{array}/{index}"))
+        .unwrap_or_else(|| format!("/{array}"))
+}
+
+/// The schema-relative pointer in a reason produced by
+/// [`ValidationOptions::compile`](crate::ValidationOptions::compile),
+/// which starts with ``at `<pointer>`:``.
+pub fn reason_pointer(reason: &str) -> Option<&str> {
+    let start = reason.find("at `")? + 4;
+    let len = reason[start..].find('`')?;
+    let pointer = &reason[start..start + len];
+    match pointer {
+        "/" => Some(""),
+        pointer if pointer.starts_with('/') => Some(pointer),
+        _ => None,
+    }
+}
+
+/// 1-based line and column of the value `pointer` names in the JSON `text`;
+/// for object members, the position of the member's key.
+pub fn locate_pointer(text: &str, pointer: &str) -> Option<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut pos = skip_ws(bytes, 0);
+    let mut report = pos;
+    if !pointer.is_empty() {
+        for token in pointer.strip_prefix('/')?.split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            match bytes.get(pos)? {
+                b'{' => {
+                    let (key_pos, value_pos) = find_member(text, pos, &token)?;
+                    report = key_pos;
+                    pos = value_pos;
+                }
+                b'[' => {
+                    pos = find_element(bytes, pos, token.parse().ok()?)?;
+                    report = pos;
+                }
+                _ => return None,
+            }
+        }
+    }
+    let before = &text[..report];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    Some((line, column))
+}
+
+fn find_member(text: &str, open: usize, name: &str) -> Option<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut pos = skip_ws(bytes, open + 1);
+    while bytes.get(pos)? != &b'}' {
+        let key_end = skip_string(bytes, pos)?;
+        let key: String = serde_json::from_str(&text[pos..key_end]).ok()?;
+        let colon = skip_ws(bytes, key_end);
+        if bytes.get(colon)? != &b':' {
+            return None;
+        }
+        let value = skip_ws(bytes, colon + 1);
+        if key == name {
+            return Some((pos, value));
+        }
+        pos = skip_ws(bytes, skip_value(bytes, value)?);
+        if bytes.get(pos)? == &b',' {
+            pos = skip_ws(bytes, pos + 1);
+        }
+    }
+    None
+}
+
+fn find_element(bytes: &[u8], open: usize, index: usize) -> Option<usize> {
+    let mut pos = skip_ws(bytes, open + 1);
+    let mut current = 0;
+    while bytes.get(pos)? != &b']' {
+        if current == index {
+            return Some(pos);
+        }
+        pos = skip_ws(bytes, skip_value(bytes, pos)?);
+        if bytes.get(pos)? == &b',' {
+            pos = skip_ws(bytes, pos + 1);
+        }
+        current += 1;
+    }
+    None
+}
+
+fn skip_ws(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+        pos += 1;
+    }
+    pos
+}
+
+/// End of the string literal starting at `pos` (exclusive).
+fn skip_string(bytes: &[u8], pos: usize) -> Option<usize> {
+    if bytes.get(pos)? != &b'"' {
+        return None;
+    }
+    let mut idx = pos + 1;
+    loop {
+        match bytes.get(idx)? {
+            b'\\' => idx += 2,
+            b'"' => return Some(idx + 1),
+            _ => idx += 1,
+        }
+    }
+}
+
+/// End of the value starting at `pos` (exclusive).
+fn skip_value(bytes: &[u8], pos: usize) -> Option<usize> {
+    match bytes.get(pos)? {
+        b'"' => skip_string(bytes, pos),
+        b'{' | b'[' => {
+            let mut depth = 0usize;
+            let mut idx = pos;
+            loop {
+                match bytes.get(idx)? {
+                    b'"' => {
+                        idx = skip_string(bytes, idx)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(idx + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                idx += 1;
+            }
+        }
+        _ => {
+            let mut idx = pos;
+            while bytes.get(idx).is_some_and(|byte| {
+                !matches!(byte, b',' | b'}' | b']') && !byte.is_ascii_whitespace()
+            }) {
+                idx += 1;
+            }
+            Some(idx)
+        }
+    }
+}
@@ -1,8 +1,22 @@
+pub mod diagnostic;
+pub mod net;
+pub mod overlay;
+pub mod presets;
+pub mod role;
 pub mod schema;
+pub mod tools;
 pub mod types;
+pub mod validation;
 
-pub use schema::{ManifestValidator, validate_config_schema};
+pub use diagnostic::{ManifestDiagnostic, locate_pointer};
+pub use net::NetCaps;
+pub use overlay::{OVERRIDABLE_FIELDS, apply_env_overlay, apply_overlay, overlay_path};
+pub use presets::{CAPABILITY_PRESETS, capability_preset, expand_capability_preset};
+pub use role::ComponentRole;
+pub use schema::{ManifestValidator, validate_config_schema, validate_config_schema_with};
+pub use tools::ToolsCaps;
 pub use types::{
-    CapabilityRef, CompiledExportSchema, ComponentExport, ComponentInfo, ComponentManifest,
-    ManifestError, WitCompat,
+    CapabilityRef, CompiledExportSchema, ComponentDependency, ComponentExport, ComponentInfo,
+    ComponentManifest, Deprecation, ManifestError, WitCompat, deprecated_properties,
 };
+pub use validation::{SchemaDraft, ValidationOptions};
@@ -0,0 +1,76 @@
+use std::net::{IpAddr, ToSocketAddrs};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::ManifestError;
+
+/// Raw socket access declared under `capabilities.net`.
+///
+/// Sockets stay closed unless `tcp` or `udp` is set, and even then only the
+/// addresses that `hosts` resolve to may be connected to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetCaps {
+    #[serde(default)]
+    pub tcp: bool,
+    #[serde(default)]
+    pub udp: bool,
+    /// Host names or IP literals the component may connect to.
+    #[serde(default)]
+    pub hosts: Vec<String>,
+}
+
+impl NetCaps {
+    pub fn is_enabled(&self) -> bool {
+        self.tcp || self.udp
+    }
+
+    pub fn validate(&self) -> Result<(), ManifestError> {
+        if self.is_enabled() && self.hosts.is_empty() {
+            return Err(ManifestError::InvalidNetCapability(
+                "net.hosts must list at least one host when tcp or udp is enabled".into(),
+            ));
+        }
+        for host in &self.hosts {
+            let plain = !host.trim().is_empty()
+                && !host.contains("://")
+                && !host.contains('/')
+                && (host.parse::<IpAddr>().is_ok() || !host.contains(':'));
+            if !plain {
+                return Err(ManifestError::InvalidNetCapability(format!(
+                    "net.hosts entry `{host}` must be a bare host name or IP address"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolves `hosts` to the addresses a connect may target. Entries that
+    /// do not resolve are skipped, so connecting to them is denied.
+    pub fn resolve_hosts(&self) -> Vec<IpAddr> {
+        let mut addrs = Vec::new();
+        for host in &self.hosts {
+            if let Ok(ip) = host.parse::<IpAddr>() {
+                addrs.push(ip);
+                continue;
+            }
+            if let Ok(resolved) = (host.as_str(), 0).to_socket_addrs() {
+                addrs.extend(resolved.map(|addr| addr.ip()));
+            }
+        }
+        addrs.sort();
+        addrs.dedup();
+        addrs
+    }
+
+    /// Reads `net` from a manifest `capabilities` block.
+    pub fn from_capabilities(
+        capabilities: &serde_json::Value,
+    ) -> Result<Option<Self>, ManifestError> {
+        let Some(net) = capabilities.get("net") else {
+            return Ok(None);
+        };
+        let net: NetCaps = serde_json::from_value(net.clone())?;
+        net.validate()?;
+        Ok(Some(net))
+    }
+}
@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::types::ManifestError;
+
+/// Top-level manifest fields an environment overlay may touch.
+pub const OVERRIDABLE_FIELDS: &[&str] = &["limits", "capabilities", "telemetry"];
+
+/// Capability domains an overlay may patch under `capabilities`.
+const OVERRIDABLE_CAPABILITY_DOMAINS: &[&str] = &["wasi", "host", "net"];
+
+/// Location of the `<env>` overlay for a manifest, e.g.
+/// `component.manifest.json` -> `component.manifest.override.prod.json`.
+///
+/// `env` must be a plain name (ASCII letters, digits, `-`, `_`) so it cannot
+/// point the overlay outside the manifest directory.
+pub fn overlay_path(manifest_path: &Path, env: &str) -> Result<PathBuf, ManifestError> {
+    let valid = !env.is_empty()
+        && env
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !valid {
+        return Err(ManifestError::InvalidEnvironment(env.to_string()));
+    }
+    let file_name = manifest_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("component.manifest.json");
+    let stem = file_name.strip_suffix(".json").unwrap_or(file_name);
+    Ok(manifest_path.with_file_name(format!("{stem}.override.{env}.json")))
+}
+
+/// Deep-merges an environment overlay over the base manifest in place.
+///
+/// Only [`OVERRIDABLE_FIELDS`] may appear in the overlay; `null` removes an
+/// entry (JSON merge-patch semantics). The merged value still needs schema
+/// validation.
+pub fn apply_overlay(manifest: &mut Value, overlay: &Value) -> Result<(), ManifestError> {
+    let Some(fields) = overlay.as_object() else {
+        return Err(ManifestError::InvalidOverlay(
+            "overlay must be a JSON object".into(),
+        ));
+    };
+    for (field, patch) in fields {
+        if field == "$schema" {
+            continue;
+        }
+        if !OVERRIDABLE_FIELDS.contains(&field.as_str()) {
+            return Err(ManifestError::NonOverridableField(field.clone()));
+        }
+        if field == "capabilities" {
+            let Some(domains) = patch.as_object() else {
+                return Err(ManifestError::InvalidOverlay(
+                    "capabilities overlay must be an object".into(),
+                ));
+            };
+            if let Some(domain) = domains
+                .keys()
+                .find(|domain| !OVERRIDABLE_CAPABILITY_DOMAINS.contains(&domain.as_str()))
+            {
+                return Err(ManifestError::NonOverridableField(format!(
+                    "capabilities.{domain}"
+                )));
+            }
+        }
+    }
+
+    let Some(root) = manifest.as_object_mut() else {
+        return Err(ManifestError::InvalidOverlay(
+            "base manifest must be a JSON object".into(),
+        ));
+    };
+    for field in OVERRIDABLE_FIELDS {
+        let Some(patch) = fields.get(*field) else {
+            continue;
+        };
+        if patch.is_null() {
+            root.remove(*field);
+            continue;
+        }
+        let base = root
+            .entry(field.to_string())
+            .or_insert(Value::Object(Default::default()));
+        merge_patch(base, patch);
+    }
+    Ok(())
+}
+
+/// Applies the `<env>` overlay next to `manifest_path` when the file exists,
+/// returning its path. A missing overlay leaves `manifest` untouched.
+pub fn apply_env_overlay(
+    manifest: &mut Value,
+    manifest_path: &Path,
+    env: &str,
+) -> Result<Option<PathBuf>, ManifestError> {
+    let path = overlay_path(manifest_path, env)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let raw = fs::read_to_string(&path).map_err(|source| ManifestError::OverlayIo {
+        path: path.clone(),
+        source,
+    })?;
+    let overlay: Value = serde_json::from_str(&raw)?;
+    apply_overlay(manifest, &overlay)?;
+    Ok(Some(path))
+}
+
+/// RFC 7396 JSON merge patch.
+pub fn merge_patch(base: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *base = patch.clone();
+        return;
+    };
+    if !base.is_object() {
+        *base = Value::Object(Default::default());
+    }
+    let base = base.as_object_mut().expect("base coerced to object above");
+    for (key, value) in patch {
+        if value.is_null() {
+            base.remove(key);
+        } else {
+            merge_patch(
+                base.entry(key.clone())
+                    .or_insert(Value::Object(Default::default())),
+                value,
+            );
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use serde_json::{Map, Value, json};
+
+use crate::types::ManifestError;
+
+/// Names of the vetted capability presets, in documentation order.
+pub const CAPABILITY_PRESETS: &[&str] = &["http-tool", "stateless-transform", "stateful-messaging"];
+
+/// Returns the `{wasi, host}` capability block a preset expands to.
+pub fn capability_preset(name: &str) -> Option<Value> {
+    let block = match name {
+        // Calls outbound HTTP APIs; no state, no inbound traffic.
+        "http-tool" => json!({
+            "wasi": { "random": true, "clocks": true },
+            "host": {
+                "http": { "client": true, "server": false },
+                "telemetry": { "scope": "node" }
+            }
+        }),
+        // Pure input -> output mapping.
+        "stateless-transform" => json!({
+            "wasi": { "clocks": true },
+            "host": { "telemetry": { "scope": "node" } }
+        }),
+        // Conversational components that keep per-session state.
+        "stateful-messaging" => json!({
+            "wasi": { "random": true, "clocks": true },
+            "host": {
+                "state": { "read": true, "write": true },
+                "messaging": { "inbound": true, "outbound": true },
+                "telemetry": { "scope": "tenant" }
+            }
+        }),
+        _ => return None,
+    };
+    Some(block)
+}
+
+/// Expands `capabilities.preset` in place.
+///
+/// Explicit `wasi`/`host` entries next to the preset are deep-merged over the
+/// preset block, so authors can extend or tighten it; a `null` entry removes
+/// the corresponding preset capability.
+pub fn expand_capability_preset(capabilities: &mut Value) -> Result<(), ManifestError> {
+    let Some(map) = capabilities.as_object_mut() else {
+        return Ok(());
+    };
+    let Some(preset) = map.remove("preset") else {
+        return Ok(());
+    };
+    let name = preset
+        .as_str()
+        .ok_or_else(|| ManifestError::UnknownCapabilityPreset(preset.to_string()))?;
+    let mut expanded = capability_preset(name)
+        .ok_or_else(|| ManifestError::UnknownCapabilityPreset(name.to_string()))?;
+    let overrides = std::mem::take(map);
+    merge_over(&mut expanded, overrides);
+    *capabilities = expanded;
+    Ok(())
+}
+
+fn merge_over(base: &mut Value, overrides: Map<String, Value>) {
+    let Some(base) = base.as_object_mut() else {
+        return;
+    };
+    for (key, value) in overrides {
+        match value {
+            Value::Null => {
+                base.remove(&key);
+            }
+            Value::Object(nested) if base.get(&key).is_some_and(Value::is_object) => {
+                merge_over(base.get_mut(&key).expect("checked above"), nested);
+            }
+            value => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
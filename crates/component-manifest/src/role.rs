@@ -0,0 +1,53 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::ManifestError;
+
+/// What a component is for, from the manifest's `role` and the describe
+/// payload's `info.role`. Flow tooling picks node kinds from it and hosts can
+/// refuse roles they do not run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentRole {
+    /// Called for a result within a flow step.
+    Tool,
+    /// Bridges an external system (a channel, queue or API) into flows.
+    Adapter,
+    /// Runs a longer-lived unit of work that flows hand off to.
+    Process,
+}
+
+impl ComponentRole {
+    pub const ALL: [ComponentRole; 3] = [
+        ComponentRole::Tool,
+        ComponentRole::Adapter,
+        ComponentRole::Process,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ComponentRole::Tool => "tool",
+            ComponentRole::Adapter => "adapter",
+            ComponentRole::Process => "process",
+        }
+    }
+}
+
+impl fmt::Display for ComponentRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ComponentRole {
+    type Err = ManifestError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|role| role.as_str() == value)
+            .ok_or_else(|| ManifestError::UnknownRole(value.to_string()))
+    }
+}
@@ -1,17 +1,18 @@
-use jsonschema::validator_for;
 use regex::Regex;
 use semver::VersionReq;
 use serde_json::Value;
 
 use crate::types::{
-    CompiledExportSchema, ComponentExport, ComponentInfo, ComponentManifest, ManifestError,
-    WitCompat,
+    CompiledExportSchema, ComponentDependency, ComponentExport, ComponentInfo, ComponentManifest,
+    ManifestError, WitCompat, deprecated_properties,
 };
+use crate::validation::ValidationOptions;
 use greentic_types::{SecretKey, SecretRequirement};
 
 pub struct ManifestValidator {
     capability_pattern: Regex,
     operation_pattern: Regex,
+    options: ValidationOptions,
 }
 
 impl Default for ManifestValidator {
@@ -25,9 +26,23 @@ impl ManifestValidator {
         Self {
             capability_pattern: Regex::new(r"^[a-z][a-z0-9_.:-]*$").expect("valid regex"),
             operation_pattern: Regex::new(r"^[a-z][a-z0-9_.:-]*$").expect("valid regex"),
+            options: ValidationOptions::default(),
         }
     }
 
+    /// Compiles config and export schemas with `options` instead of the
+    /// defaults.
+    pub fn with_options(options: ValidationOptions) -> Self {
+        Self {
+            options,
+            ..Self::new()
+        }
+    }
+
+    pub fn options(&self) -> &ValidationOptions {
+        &self.options
+    }
+
     pub fn validate_value(&self, manifest_json: Value) -> Result<ComponentInfo, ManifestError> {
         prevalidate_secret_keys(&manifest_json)?;
         let manifest = ComponentManifest::from_value(manifest_json.clone())?;
@@ -64,37 +79,66 @@ impl ManifestValidator {
         }
 
         validate_wit_compat(&manifest.wit_compat)?;
+        self.validate_dependencies(&manifest.dependencies)?;
 
         if !manifest.secret_requirements.is_empty() {
             validate_secret_requirements(&manifest.secret_requirements)?;
         }
 
-        let config_schema = validate_config_schema(&manifest.config_schema)?;
+        let config_schema = validate_config_schema_with(&manifest.config_schema, &self.options)?;
         let compiled_exports = manifest
             .exports
             .iter()
-            .map(compile_export_schema)
+            .map(|export| compile_export_schema(export, &self.options))
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(ComponentInfo {
             name: manifest.name,
             description: manifest.description,
+            role: manifest.role,
             capabilities: manifest.capabilities,
             exports: compiled_exports,
             config_schema,
             secret_requirements: manifest.secret_requirements,
             wit_compat: manifest.wit_compat,
+            dependencies: manifest.dependencies,
             metadata: manifest.metadata,
             raw,
         })
     }
+
+    pub fn validate_dependencies(
+        &self,
+        dependencies: &[ComponentDependency],
+    ) -> Result<(), ManifestError> {
+        crate::types::ensure_unique(
+            dependencies.iter().map(|dep| dep.id.clone()),
+            ManifestError::DuplicateDependency,
+        )?;
+        for dependency in dependencies {
+            dependency.validate(&self.operation_pattern)?;
+        }
+        Ok(())
+    }
 }
 
 pub fn validate_config_schema(schema: &Value) -> Result<Value, ManifestError> {
+    validate_config_schema_with(schema, &ValidationOptions::default())
+}
+
+pub fn validate_config_schema_with(
+    schema: &Value,
+    options: &ValidationOptions,
+) -> Result<Value, ManifestError> {
     if !schema.is_object() {
         return Err(ManifestError::ConfigSchemaNotObject);
     }
-    validator_for(schema).map_err(|err| ManifestError::InvalidConfigSchema(err.to_string()))?;
+    options
+        .compile(schema)
+        .map_err(ManifestError::InvalidConfigSchema)?;
+    for (property, deprecation) in deprecated_properties(schema) {
+        deprecation.validate(&property)?;
+    }
     Ok(schema.clone())
 }
 
@@ -117,16 +161,19 @@ fn prevalidate_secret_keys(manifest: &Value) -> Result<(), ManifestError> {
     Ok(())
 }
 
-fn compile_export_schema(export: &ComponentExport) -> Result<CompiledExportSchema, ManifestError> {
+fn compile_export_schema(
+    export: &ComponentExport,
+    options: &ValidationOptions,
+) -> Result<CompiledExportSchema, ManifestError> {
     let input_schema = export
         .input_schema
         .as_ref()
-        .map(|schema| parse_schema(schema, export, "input_schema"))
+        .map(|schema| parse_schema(schema, export, "input_schema", options))
         .transpose()?;
     let output_schema = export
         .output_schema
         .as_ref()
-        .map(|schema| parse_schema(schema, export, "output_schema"))
+        .map(|schema| parse_schema(schema, export, "output_schema", options))
         .transpose()?;
 
     Ok(CompiledExportSchema {
@@ -134,6 +181,7 @@ fn compile_export_schema(export: &ComponentExport) -> Result<CompiledExportSchem
         description: export.description.clone(),
         input_schema,
         output_schema,
+        deprecated: export.deprecated.clone(),
     })
 }
 
@@ -141,6 +189,7 @@ fn parse_schema(
     schema: &Value,
     export: &ComponentExport,
     field: &str,
+    options: &ValidationOptions,
 ) -> Result<Value, ManifestError> {
     if !schema.is_object() {
         return Err(ManifestError::InvalidExportSchema {
@@ -148,10 +197,12 @@ fn parse_schema(
             reason: format!("{field} must be an object"),
         });
     }
-    validator_for(schema).map_err(|err| ManifestError::InvalidExportSchema {
-        operation: export.operation.clone(),
-        reason: err.to_string(),
-    })?;
+    options
+        .compile(schema)
+        .map_err(|reason| ManifestError::InvalidExportSchema {
+            operation: export.operation.clone(),
+            reason: format!("{field} {reason}"),
+        })?;
     Ok(schema.clone())
 }
 
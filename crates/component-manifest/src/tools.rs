@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::ManifestError;
+
+/// Other components a component may invoke through the host, declared under
+/// `capabilities.tools`.
+///
+/// Names refer to tools the host has registered; anything not listed in
+/// `allow` is refused, so an empty list grants nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolsCaps {
+    #[serde(default)]
+    pub allow: Vec<String>,
+}
+
+impl ToolsCaps {
+    pub fn allows(&self, name: &str) -> bool {
+        self.allow.iter().any(|allowed| allowed == name)
+    }
+
+    pub fn validate(&self) -> Result<(), ManifestError> {
+        for (index, name) in self.allow.iter().enumerate() {
+            if name.trim().is_empty() {
+                return Err(ManifestError::InvalidToolsCapability(
+                    "tools.allow entries must not be empty".into(),
+                ));
+            }
+            if self.allow[..index].contains(name) {
+                return Err(ManifestError::InvalidToolsCapability(format!(
+                    "tools.allow lists `{name}` more than once"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads `tools` from a manifest `capabilities` block.
+    pub fn from_capabilities(
+        capabilities: &serde_json::Value,
+    ) -> Result<Option<Self>, ManifestError> {
+        let Some(tools) = capabilities.get("tools") else {
+            return Ok(None);
+        };
+        let tools: ToolsCaps = serde_json::from_value(tools.clone())?;
+        tools.validate()?;
+        Ok(Some(tools))
+    }
+}
@@ -1,11 +1,15 @@
 use std::collections::HashSet;
+use std::path::PathBuf;
 
 use greentic_types::SecretRequirement;
 use regex::Regex;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use thiserror::Error;
 
+use crate::role::ComponentRole;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(transparent)]
 pub struct CapabilityRef(pub String);
@@ -35,6 +39,8 @@ pub struct ComponentExport {
     pub input_schema: Option<Value>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub output_schema: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<Deprecation>,
 }
 
 impl ComponentExport {
@@ -45,8 +51,153 @@ impl ComponentExport {
         if !pattern.is_match(&self.operation) {
             return Err(ManifestError::InvalidOperation(self.operation.clone()));
         }
+        if let Some(deprecated) = &self.deprecated {
+            deprecated.validate(&self.operation)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deprecation notice attached to an operation or a config schema property.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Deprecation {
+    /// Component version that introduced the deprecation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// Operation or property to use instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl Deprecation {
+    /// Reads a JSON Schema `deprecated` keyword, accepting both the standard
+    /// boolean form and the `{since, replacement, message}` object form.
+    pub fn from_schema(schema: &Value) -> Option<Self> {
+        match schema.get("deprecated")? {
+            Value::Bool(true) => Some(Self::default()),
+            value @ Value::Object(_) => serde_json::from_value(value.clone()).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn validate(&self, subject: &str) -> Result<(), ManifestError> {
+        if let Some(since) = &self.since {
+            Version::parse(since).map_err(|source| ManifestError::InvalidDeprecation {
+                subject: subject.to_string(),
+                reason: format!("since `{since}` is not semver: {source}"),
+            })?;
+        }
+        if self
+            .replacement
+            .as_deref()
+            .is_some_and(|replacement| replacement.trim().is_empty() || replacement == subject)
+        {
+            return Err(ManifestError::InvalidDeprecation {
+                subject: subject.to_string(),
+                reason: "replacement must name a different operation or property".into(),
+            });
+        }
         Ok(())
     }
+
+    /// Human readable notice for `subject`, suitable for lint output and logs.
+    pub fn notice(&self, subject: &str) -> String {
+        let mut notice = format!("`{subject}` is deprecated");
+        if let Some(since) = &self.since {
+            notice.push_str(&format!(" since {since}"));
+        }
+        if let Some(replacement) = &self.replacement {
+            notice.push_str(&format!("; use `{replacement}` instead"));
+        }
+        if let Some(message) = &self.message {
+            notice.push_str(&format!(" ({message})"));
+        }
+        notice
+    }
+}
+
+/// Returns the deprecated top-level properties of an object schema, sorted by name.
+pub fn deprecated_properties(schema: &Value) -> Vec<(String, Deprecation)> {
+    let mut deprecated = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| {
+            properties
+                .iter()
+                .filter_map(|(name, property)| {
+                    Deprecation::from_schema(property).map(|notice| (name.clone(), notice))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    deprecated.sort_by(|a, b| a.0.cmp(&b.0));
+    deprecated
+}
+
+/// Another component whose exports this component composes against.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ComponentDependency {
+    pub id: String,
+    /// Semver range the resolved component version must satisfy.
+    pub version: String,
+    /// Optional locator (path, oci://, https://, ...) used when the host has
+    /// no explicit source registered for `id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locator: Option<String>,
+    /// Operations of the dependency this component calls.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exports: Vec<String>,
+}
+
+impl ComponentDependency {
+    pub fn validate(&self, pattern: &Regex) -> Result<(), ManifestError> {
+        if self.id.trim().is_empty() {
+            return Err(ManifestError::EmptyField("dependencies.id"));
+        }
+        self.version_req()?;
+        if let Some(locator) = &self.locator
+            && locator.trim().is_empty()
+        {
+            return Err(ManifestError::InvalidDependency {
+                id: self.id.clone(),
+                reason: "locator must not be empty when provided".into(),
+            });
+        }
+        for export in &self.exports {
+            if !pattern.is_match(export) {
+                return Err(ManifestError::InvalidDependency {
+                    id: self.id.clone(),
+                    reason: format!("export `{export}` is not a valid operation name"),
+                });
+            }
+        }
+        ensure_unique(self.exports.iter().cloned(), |export| {
+            ManifestError::InvalidDependency {
+                id: self.id.clone(),
+                reason: format!("export `{export}` is listed twice"),
+            }
+        })
+    }
+
+    pub fn version_req(&self) -> Result<VersionReq, ManifestError> {
+        VersionReq::parse(&self.version).map_err(|source| ManifestError::InvalidVersionReq {
+            field: "dependencies.version",
+            source,
+        })
+    }
+
+    /// Returns whether `version` satisfies the declared range.
+    pub fn accepts(&self, version: &str) -> Result<bool, ManifestError> {
+        let req = self.version_req()?;
+        let version =
+            Version::parse(version).map_err(|source| ManifestError::InvalidDependency {
+                id: self.id.clone(),
+                reason: format!("resolved version `{version}` is not semver: {source}"),
+            })?;
+        Ok(req.matches(&version))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +213,8 @@ pub struct ComponentManifest {
     pub name: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<ComponentRole>,
     #[serde(default)]
     pub capabilities: Vec<CapabilityRef>,
     #[serde(default)]
@@ -70,6 +223,8 @@ pub struct ComponentManifest {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub secret_requirements: Vec<SecretRequirement>,
     pub wit_compat: WitCompat,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<ComponentDependency>,
     #[serde(default, skip_serializing_if = "Map::is_empty")]
     pub metadata: Map<String, Value>,
 }
@@ -86,17 +241,20 @@ pub struct CompiledExportSchema {
     pub description: Option<String>,
     pub input_schema: Option<Value>,
     pub output_schema: Option<Value>,
+    pub deprecated: Option<Deprecation>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ComponentInfo {
     pub name: Option<String>,
     pub description: Option<String>,
+    pub role: Option<ComponentRole>,
     pub capabilities: Vec<CapabilityRef>,
     pub exports: Vec<CompiledExportSchema>,
     pub config_schema: Value,
     pub secret_requirements: Vec<SecretRequirement>,
     pub wit_compat: WitCompat,
+    pub dependencies: Vec<ComponentDependency>,
     pub metadata: Map<String, Value>,
     pub raw: Value,
 }
@@ -121,12 +279,26 @@ pub enum ManifestError {
     DuplicateSecret(String),
     #[error("duplicate operation `{0}` detected")]
     DuplicateOperation(String),
+    #[error("duplicate dependency `{0}` detected")]
+    DuplicateDependency(String),
+    #[error("dependency `{id}` is invalid: {reason}")]
+    InvalidDependency { id: String, reason: String },
+    #[error("deprecation of `{subject}` is invalid: {reason}")]
+    InvalidDeprecation { subject: String, reason: String },
     #[error("secret name `{0}` is invalid")]
     InvalidSecret(String),
     #[error("secret requirement `{key}` is invalid: {reason}")]
     InvalidSecretRequirement { key: String, reason: String },
     #[error("capability `{0}` is invalid")]
     InvalidCapability(String),
+    #[error("net capability is invalid: {0}")]
+    InvalidNetCapability(String),
+    #[error("tools capability is invalid: {0}")]
+    InvalidToolsCapability(String),
+    #[error("unknown component role `{0}`")]
+    UnknownRole(String),
+    #[error("unknown capability preset `{0}`")]
+    UnknownCapabilityPreset(String),
     #[error("operation `{0}` is invalid")]
     InvalidOperation(String),
     #[error("wit package must be `greentic:component`, found `{found}`")]
@@ -139,6 +311,18 @@ pub enum ManifestError {
     },
     #[error("field `{0}` is required and cannot be empty")]
     EmptyField(&'static str),
+    #[error("field `{0}` cannot be overridden by an environment overlay")]
+    NonOverridableField(String),
+    #[error("environment overlay is invalid: {0}")]
+    InvalidOverlay(String),
+    #[error("environment name `{0}` must only contain ASCII letters, digits, `-` and `_`")]
+    InvalidEnvironment(String),
+    #[error("failed to read environment overlay {}: {source}", path.display())]
+    OverlayIo {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
 }
 
 pub(crate) fn ensure_unique<T, F>(
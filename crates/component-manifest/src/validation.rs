@@ -0,0 +1,208 @@
+use jsonschema::{Draft, Validator};
+use serde_json::Value;
+
+/// JSON Schema drafts a manifest schema can be compiled as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SchemaDraft {
+    Draft4,
+    Draft6,
+    Draft7,
+    Draft201909,
+    Draft202012,
+}
+
+impl SchemaDraft {
+    /// The draft a `$schema` URI names, if it is one of the known ones.
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        let uri = uri.trim_end_matches('#');
+        let uri = uri
+            .strip_prefix("https://")
+            .or_else(|| uri.strip_prefix("http://"))
+            .unwrap_or(uri);
+        match uri {
+            "json-schema.org/draft-04/schema" => Some(Self::Draft4),
+            "json-schema.org/draft-06/schema" => Some(Self::Draft6),
+            "json-schema.org/draft-07/schema" => Some(Self::Draft7),
+            "json-schema.org/draft/2019-09/schema" => Some(Self::Draft201909),
+            "json-schema.org/draft/2020-12/schema" => Some(Self::Draft202012),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Draft4 => "draft-04",
+            Self::Draft6 => "draft-06",
+            Self::Draft7 => "draft-07",
+            Self::Draft201909 => "2019-09",
+            Self::Draft202012 => "2020-12",
+        }
+    }
+
+    fn to_jsonschema(self) -> Draft {
+        match self {
+            Self::Draft4 => Draft::Draft4,
+            Self::Draft6 => Draft::Draft6,
+            Self::Draft7 => Draft::Draft7,
+            Self::Draft201909 => Draft::Draft201909,
+            Self::Draft202012 => Draft::Draft202012,
+        }
+    }
+}
+
+/// How config and operation schemas embedded in a manifest are compiled.
+///
+/// The default keeps the historical behaviour: the draft is detected from
+/// each schema's `$schema` and `format` is an annotation only.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ValidationOptions {
+    /// Compile every schema as this draft; a `$schema` naming another draft
+    /// is rejected.
+    pub draft: Option<SchemaDraft>,
+    /// Treat `format` (`uri`, `date-time`, `regex`, ...) as an assertion, and
+    /// check each `default` against its schema with formats asserted.
+    pub assert_formats: bool,
+}
+
+impl ValidationOptions {
+    pub fn with_draft(mut self, draft: SchemaDraft) -> Self {
+        self.draft = Some(draft);
+        self
+    }
+
+    pub fn assert_formats(mut self, assert: bool) -> Self {
+        self.assert_formats = assert;
+        self
+    }
+
+    /// Compiles `schema`. Errors name the JSON pointer inside the schema
+    /// that is at fault.
+    pub fn compile(&self, schema: &Value) -> Result<Validator, String> {
+        if let Some(draft) = self.draft
+            && let Some(declared) = schema.get("$schema").and_then(Value::as_str)
+            && let Some(declared_draft) = SchemaDraft::from_uri(declared)
+            && declared_draft != draft
+        {
+            return Err(format!(
+                "at `/$schema`: declares {} but validation is pinned to {}",
+                declared_draft.as_str(),
+                draft.as_str()
+            ));
+        }
+        let validator = self.build(schema)?;
+        if self.assert_formats {
+            check_defaults(self, schema, &mut String::new())?;
+        }
+        Ok(validator)
+    }
+
+    fn build(&self, schema: &Value) -> Result<Validator, String> {
+        let mut options = jsonschema::options().should_validate_formats(self.assert_formats);
+        if let Some(draft) = self.draft {
+            options = options.with_draft(draft.to_jsonschema());
+        }
+        options.build(schema).map_err(|err| {
+            format!(
+                "at `{}`: {err}",
+                pointer_or_root(&err.instance_path().to_string())
+            )
+        })
+    }
+}
+
+/// Keywords whose value is a single subschema.
+const SUBSCHEMA_KEYWORDS: &[&str] = &[
+    "items",
+    "additionalItems",
+    "additionalProperties",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+    "contains",
+    "propertyNames",
+    "not",
+    "if",
+    "then",
+    "else",
+    "contentSchema",
+];
+
+/// Keywords whose value is an array of subschemas.
+const SUBSCHEMA_LIST_KEYWORDS: &[&str] = &["allOf", "anyOf", "oneOf", "prefixItems", "items"];
+
+/// Keywords whose value maps names to subschemas.
+const SUBSCHEMA_MAP_KEYWORDS: &[&str] = &[
+    "properties",
+    "patternProperties",
+    "dependentSchemas",
+    "dependencies",
+    "$defs",
+    "definitions",
+];
+
+/// Validates every `default` against the schema object that declares it,
+/// following only the keywords that hold subschemas, so property names and
+/// annotation values are never mistaken for schemas. Subschemas that only
+/// compile in the context of the root (`$ref` to its definitions) are
+/// skipped.
+fn check_defaults(
+    options: &ValidationOptions,
+    schema: &Value,
+    pointer: &mut String,
+) -> Result<(), String> {
+    let Value::Object(map) = schema else {
+        return Ok(());
+    };
+    if let Some(default) = map.get("default")
+        && let Ok(validator) = options.build(schema)
+        && let Err(err) = validator.validate(default)
+    {
+        return Err(format!(
+            "at `{pointer}/default`: default does not match its schema: {err}"
+        ));
+    }
+    for (key, child) in map {
+        let keyword = key.as_str();
+        match child {
+            Value::Object(_) if SUBSCHEMA_KEYWORDS.contains(&keyword) => {
+                check_subschema(options, child, pointer, &[keyword])?;
+            }
+            Value::Array(items) if SUBSCHEMA_LIST_KEYWORDS.contains(&keyword) => {
+                for (index, item) in items.iter().enumerate() {
+                    check_subschema(
+                        options,
+                        item,
+                        pointer,
+                        &[keyword, index.to_string().as_str()],
+                    )?;
+                }
+            }
+            Value::Object(entries) if SUBSCHEMA_MAP_KEYWORDS.contains(&keyword) => {
+                for (name, entry) in entries {
+                    check_subschema(options, entry, pointer, &[keyword, name.as_str()])?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn check_subschema(
+    options: &ValidationOptions,
+    schema: &Value,
+    pointer: &mut String,
+    segments: &[&str],
+) -> Result<(), String> {
+    let len = pointer.len();
+    for segment in segments {
+        pointer.push('/');
+        pointer.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    }
+    let result = check_defaults(options, schema, pointer);
+    pointer.truncate(len);
+    result
+}
+
+fn pointer_or_root(pointer: &str) -> &str {
+    if pointer.is_empty() { "/" } else { pointer }
+}
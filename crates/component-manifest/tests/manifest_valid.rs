@@ -1,4 +1,7 @@
-use component_manifest::{ManifestError, ManifestValidator};
+use component_manifest::{
+    CAPABILITY_PRESETS, ComponentRole, ManifestError, ManifestValidator, capability_preset,
+    deprecated_properties, expand_capability_preset,
+};
 use serde_json::json;
 
 fn good_manifest() -> serde_json::Value {
@@ -108,3 +111,265 @@ fn reject_invalid_scope() {
         .then_some(())
         .expect("expected invalid secret requirement error");
 }
+
+#[test]
+fn accepts_dependencies_with_semver_ranges() {
+    let mut manifest = good_manifest();
+    manifest["dependencies"] = json!([{
+        "id": "ai.greentic.templates",
+        "version": "^0.4",
+        "exports": ["render"]
+    }]);
+    let info = ManifestValidator::new()
+        .validate_value(manifest)
+        .expect("dependencies should be valid");
+    let dependency = &info.dependencies[0];
+    assert_eq!(dependency.id, "ai.greentic.templates");
+    assert!(dependency.accepts("0.4.7").unwrap());
+    assert!(!dependency.accepts("0.5.0").unwrap());
+}
+
+#[test]
+fn reject_duplicate_dependency() {
+    let mut manifest = good_manifest();
+    manifest["dependencies"] = json!([
+        { "id": "ai.greentic.templates", "version": "^0.4" },
+        { "id": "ai.greentic.templates", "version": "^0.5" }
+    ]);
+    let err = ManifestValidator::new()
+        .validate_value(manifest)
+        .expect_err("duplicate dependency should be rejected");
+    matches!(err, ManifestError::DuplicateDependency(_))
+        .then_some(())
+        .expect("expected duplicate dependency error");
+}
+
+#[test]
+fn reject_invalid_dependency_range() {
+    let mut manifest = good_manifest();
+    manifest["dependencies"] = json!([{ "id": "ai.greentic.templates", "version": "latest" }]);
+    let err = ManifestValidator::new()
+        .validate_value(manifest)
+        .expect_err("invalid version range should be rejected");
+    matches!(err, ManifestError::InvalidVersionReq { .. })
+        .then_some(())
+        .expect("expected invalid version requirement error");
+}
+
+#[test]
+fn carries_deprecation_metadata() {
+    let mut manifest = good_manifest();
+    manifest["exports"][0]["deprecated"] =
+        json!({ "since": "1.2.0", "replacement": "process_v2", "message": "slow path" });
+    manifest["config_schema"]["properties"]["legacy_mode"] =
+        json!({ "type": "boolean", "deprecated": true });
+
+    let info = ManifestValidator::new()
+        .validate_value(manifest)
+        .expect("manifest should be valid");
+    let deprecated = info.exports[0]
+        .deprecated
+        .as_ref()
+        .expect("deprecation kept");
+    assert_eq!(
+        deprecated.notice("process"),
+        "`process` is deprecated since 1.2.0; use `process_v2` instead (slow path)"
+    );
+    let properties = deprecated_properties(&info.config_schema);
+    assert_eq!(properties.len(), 1);
+    assert_eq!(properties[0].0, "legacy_mode");
+}
+
+#[test]
+fn reject_invalid_deprecation() {
+    let mut manifest = good_manifest();
+    manifest["exports"][0]["deprecated"] = json!({ "since": "soon" });
+    let err = ManifestValidator::new()
+        .validate_value(manifest)
+        .expect_err("since must be semver");
+    assert!(matches!(err, ManifestError::InvalidDeprecation { .. }));
+}
+
+#[test]
+fn every_capability_preset_declares_wasi_and_host() {
+    for name in CAPABILITY_PRESETS {
+        let block = capability_preset(name).expect("listed preset exists");
+        assert!(block["wasi"].is_object(), "{name} wasi");
+        assert!(block["host"].is_object(), "{name} host");
+    }
+    assert!(capability_preset("unknown").is_none());
+}
+
+#[test]
+fn reject_unknown_capability_preset() {
+    let mut capabilities = json!({ "preset": "everything" });
+    let err = expand_capability_preset(&mut capabilities).expect_err("unknown preset");
+    assert!(matches!(err, ManifestError::UnknownCapabilityPreset(name) if name == "everything"));
+}
+
+#[test]
+fn roles_parse_from_the_manifest_and_reject_unknown_values() {
+    let validator = ManifestValidator::new();
+    let info = validator
+        .validate_value(good_manifest())
+        .expect("manifest should be valid");
+    assert_eq!(info.role, None);
+
+    let mut manifest = good_manifest();
+    manifest["role"] = json!("adapter");
+    let info = validator
+        .validate_value(manifest.clone())
+        .expect("manifest should be valid");
+    assert_eq!(info.role, Some(ComponentRole::Adapter));
+
+    manifest["role"] = json!("daemon");
+    assert!(validator.validate_value(manifest).is_err());
+    let err = "daemon".parse::<ComponentRole>().expect_err("unknown role");
+    assert_eq!(err.code(), "manifest.role.unknown");
+    assert_eq!(err.diagnostic(None).pointer, "/role");
+}
+
+#[test]
+fn net_caps_require_plain_hosts_when_enabled() {
+    let caps = json!({
+        "net": { "tcp": true, "hosts": ["smtp.example.com", "10.0.0.5"] }
+    });
+    let net = component_manifest::NetCaps::from_capabilities(&caps)
+        .expect("valid net caps")
+        .expect("net declared");
+    assert!(net.tcp && !net.udp);
+    assert!(net.resolve_hosts().contains(&"10.0.0.5".parse().unwrap()));
+
+    for bad in [
+        json!({ "net": { "tcp": true } }),
+        json!({ "net": { "udp": true, "hosts": ["smtp://mail.example.com"] } }),
+        json!({ "net": { "tcp": true, "hosts": ["mail.example.com:25"] } }),
+    ] {
+        let err = component_manifest::NetCaps::from_capabilities(&bad).unwrap_err();
+        assert!(
+            matches!(err, ManifestError::InvalidNetCapability(_)),
+            "{err}"
+        );
+    }
+    assert!(
+        component_manifest::NetCaps::from_capabilities(&json!({}))
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn tools_caps_reject_blank_and_duplicate_names() {
+    let tools = component_manifest::ToolsCaps::from_capabilities(&json!({
+        "tools": { "allow": ["translate", "summarize"] }
+    }))
+    .expect("valid tools caps")
+    .expect("tools declared");
+    assert!(tools.allows("translate") && !tools.allows("delete-everything"));
+
+    for bad in [
+        json!({ "tools": { "allow": [" "] } }),
+        json!({ "tools": { "allow": ["translate", "translate"] } }),
+    ] {
+        let err = component_manifest::ToolsCaps::from_capabilities(&bad).unwrap_err();
+        assert!(
+            matches!(err, ManifestError::InvalidToolsCapability(_)),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+fn validation_options_pin_the_draft_and_assert_formats() {
+    use component_manifest::{SchemaDraft, ValidationOptions, validate_config_schema_with};
+
+    let mut manifest = good_manifest();
+    manifest["config_schema"]["properties"]["endpoint"] =
+        json!({ "type": "string", "format": "uri", "default": "not a uri" });
+
+    ManifestValidator::new()
+        .validate_value(manifest.clone())
+        .expect("format is an annotation by default");
+
+    let strict = ManifestValidator::with_options(ValidationOptions::default().assert_formats(true));
+    let err = strict.validate_value(manifest.clone()).unwrap_err();
+    assert!(
+        matches!(&err, ManifestError::InvalidConfigSchema(reason)
+            if reason.contains("/properties/endpoint/default")),
+        "{err}"
+    );
+
+    let pinned = ManifestValidator::with_options(
+        ValidationOptions::default().with_draft(SchemaDraft::Draft7),
+    );
+    let err = pinned.validate_value(good_manifest()).unwrap_err();
+    assert!(err.to_string().contains("pinned to draft-07"), "{err}");
+
+    let options = ValidationOptions::default().assert_formats(true);
+    validate_config_schema_with(
+        &json!({
+            "type": "object",
+            "properties": {
+                "since": { "type": "string", "format": "date-time", "default": "2024-01-01T00:00:00Z" },
+                "pattern": { "type": "string", "format": "regex", "default": "^[a-z]+$" }
+            }
+        }),
+        &options,
+    )
+    .expect("valid defaults pass");
+    validate_config_schema_with(
+        &json!({
+            "type": "object",
+            "properties": {
+                "default": { "type": "integer" },
+                "not": { "type": "object" }
+            },
+            "x-ui": { "widget": { "type": "string", "default": 5 } }
+        }),
+        &options,
+    )
+    .expect("property names and annotations are not schemas");
+    assert!(
+        validate_config_schema_with(
+            &json!({ "properties": { "pattern": { "format": "regex", "default": "([" } } }),
+            &options,
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn errors_become_located_diagnostics() {
+    let mut manifest = good_manifest();
+    manifest["exports"]
+        .as_array_mut()
+        .unwrap()
+        .push(manifest["exports"][0].clone());
+    let text = serde_json::to_string_pretty(&manifest).unwrap();
+    let err = ManifestValidator::new()
+        .validate_value(manifest.clone())
+        .unwrap_err();
+    let diagnostic = err.diagnostic(Some(&manifest)).locate(&text);
+    assert_eq!(diagnostic.code, "manifest.operation.duplicate");
+    assert_eq!(diagnostic.pointer, "/exports/1");
+    assert!(diagnostic.suggestion.is_some());
+    let line = diagnostic.line.expect("located");
+    assert!(
+        text.lines()
+            .nth(line - 1)
+            .unwrap()
+            .trim_start()
+            .starts_with('{')
+    );
+
+    let broken = "{\n  \"name\": \"x\",\n  \"exports\": [\n";
+    let err = ManifestError::from(serde_json::from_str::<serde_json::Value>(broken).unwrap_err());
+    let diagnostic = err.diagnostic(None);
+    assert_eq!(diagnostic.code, "manifest.json");
+    assert!(diagnostic.line.is_some());
+
+    assert_eq!(
+        component_manifest::locate_pointer("{\n  \"a\": [1, {\"b\": 2}]\n}", "/a/1/b"),
+        Some((2, 13))
+    );
+}
@@ -0,0 +1,15 @@
+#![no_main]
+
+//! Feeds arbitrary bytes through the paths guest `describe` and `invoke`
+//! payloads take on the host. None of them may panic.
+
+use greentic_component_runtime::{
+    decode_describe, decode_output, strip_self_describe_tag, validate_canonical_cbor,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = validate_canonical_cbor(data);
+    let _ = decode_describe(data);
+    let _ = decode_output(strip_self_describe_tag(data));
+});
@@ -0,0 +1,134 @@
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditKind {
+    Http,
+    Secret,
+    StateRead,
+    StateWrite,
+    StateDelete,
+    /// Key listing under a prefix, recorded with the prefix as target.
+    StateList,
+    /// A WASI handle opened or closed, recorded as `handle:<slot>`.
+    Fs,
+    /// Raw socket connect or bind, recorded as `ip:port`.
+    Net,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Ok,
+    /// The call succeeded but found nothing (missing secret or state key).
+    Miss,
+    Denied,
+    Error,
+}
+
+/// One host-import call, in the same shape the CLI harness writes with
+/// `--audit-out`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp_ms: u64,
+    pub kind: AuditKind,
+    /// What was touched: `METHOD url`, secret key, state key, WASI handle,
+    /// or socket address.
+    pub target: String,
+    pub outcome: AuditOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Shared sink for host-import calls. Attach one to
+/// [`HostPolicy::audit`](crate::HostPolicy::audit) to record what components
+/// actually do; only keys and targets are kept, never payloads.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    entries: Arc<Mutex<Vec<AuditEntry>>>,
+}
+
+impl AuditLog {
+    pub fn record(
+        &self,
+        kind: AuditKind,
+        target: impl Into<String>,
+        outcome: AuditOutcome,
+        detail: Option<String>,
+    ) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis().try_into().unwrap_or(u64::MAX))
+            .unwrap_or_default();
+        self.entries
+            .lock()
+            .expect("audit log mutex poisoned")
+            .push(AuditEntry {
+                timestamp_ms,
+                kind,
+                target: target.into(),
+                outcome,
+                detail,
+            });
+    }
+
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.entries
+            .lock()
+            .expect("audit log mutex poisoned")
+            .clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("audit log mutex poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Entries recorded after the first `start`, e.g. during one invoke.
+    pub fn entries_since(&self, start: usize) -> Vec<AuditEntry> {
+        self.entries
+            .lock()
+            .expect("audit log mutex poisoned")
+            .get(start..)
+            .map(<[AuditEntry]>::to_vec)
+            .unwrap_or_default()
+    }
+
+    /// Removes and returns everything recorded so far.
+    pub fn drain(&self) -> Vec<AuditEntry> {
+        std::mem::take(&mut *self.entries.lock().expect("audit log mutex poisoned"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_are_shared_between_clones() {
+        let log = AuditLog::default();
+        let host_view = log.clone();
+        host_view.record(AuditKind::Secret, "API_TOKEN", AuditOutcome::Miss, None);
+        host_view.record(
+            AuditKind::Http,
+            "GET https://example.com/",
+            AuditOutcome::Denied,
+            Some("http disabled".into()),
+        );
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].target, "API_TOKEN");
+        assert_eq!(log.entries_since(1), entries[1..].to_vec());
+        assert_eq!(entries[1].outcome, AuditOutcome::Denied);
+        let json = serde_json::to_value(&entries[1]).expect("serialize");
+        assert_eq!(json["kind"], "http");
+        assert_eq!(json["detail"], "http disabled");
+    }
+}
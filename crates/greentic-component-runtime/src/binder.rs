@@ -1,10 +1,12 @@
 use std::collections::{HashMap, HashSet};
+use std::time::Instant;
 
 use jsonschema::Validator;
 use serde_json::Value;
 
 use crate::error::CompError;
 use crate::loader::{ComponentHandle, TenantBinding};
+use crate::policy::{PolicyAction, PolicyRequest};
 use greentic_types::TenantCtx;
 
 #[derive(Debug, Clone)]
@@ -31,19 +33,65 @@ impl Binder {
         secret_resolver: &mut dyn FnMut(&str, &TenantCtx) -> Result<String, CompError>,
     ) -> Result<(), CompError> {
         let inner = &handle.inner;
-        let binding = resolve_binding(
+        inner.host_policy.authorize(&PolicyRequest {
+            action: PolicyAction::Bind,
+            component: &inner.cref.name,
+            tenant: Some(tenant),
+            operation: None,
+            capabilities: &inner.info.capabilities,
+        })?;
+        let mut binding = resolve_binding(
             &inner.info,
             inner.config_schema.as_ref(),
             bindings,
             tenant,
             secret_resolver,
         )?;
+        binding.expires_at = secret_expiry(handle, binding.resolved_at);
 
         let key = binding_key(tenant);
         let mut guard = inner.bindings.lock().expect("binding mutex poisoned");
         guard.insert(key, binding);
         Ok(())
     }
+
+    /// Resolves the secrets of an existing binding again, keeping its config,
+    /// so rotated credentials take effect without reloading the component.
+    /// The resolver is called without holding the binding lock.
+    pub fn rebind_secrets(
+        &self,
+        handle: &ComponentHandle,
+        tenant: &TenantCtx,
+        secret_resolver: &mut dyn FnMut(&str, &TenantCtx) -> Result<String, CompError>,
+    ) -> Result<(), CompError> {
+        let inner = &handle.inner;
+        inner.host_policy.authorize(&PolicyRequest {
+            action: PolicyAction::Bind,
+            component: &inner.cref.name,
+            tenant: Some(tenant),
+            operation: None,
+            capabilities: &inner.info.capabilities,
+        })?;
+        let key = binding_key(tenant);
+        let current = {
+            let guard = inner.bindings.lock().expect("binding mutex poisoned");
+            guard
+                .get(&key)
+                .cloned()
+                .ok_or_else(|| CompError::BindingNotFound(key.clone()))?
+        };
+        let mut refreshed = refresh_secrets(&current, tenant, secret_resolver)?;
+        refreshed.expires_at = secret_expiry(handle, refreshed.resolved_at);
+
+        let mut guard = inner.bindings.lock().expect("binding mutex poisoned");
+        guard.insert(key, refreshed);
+        Ok(())
+    }
+}
+
+pub(crate) fn secret_expiry(handle: &ComponentHandle, resolved_at: Instant) -> Option<Instant> {
+    let ttl = handle.inner.host_policy.secret_rotation.as_ref()?.ttl?;
+    resolved_at.checked_add(ttl)
 }
 
 pub(crate) fn binding_key(ctx: &TenantCtx) -> String {
@@ -81,6 +129,30 @@ pub(crate) fn resolve_binding(
     Ok(TenantBinding {
         config: bindings.config.clone(),
         secrets: secret_values,
+        resolved_at: Instant::now(),
+        expires_at: None,
+        stale_reported: false,
+    })
+}
+
+/// Copy of `binding` with every bound secret resolved again.
+pub(crate) fn refresh_secrets(
+    binding: &TenantBinding,
+    tenant: &TenantCtx,
+    secret_resolver: &mut dyn FnMut(&str, &TenantCtx) -> Result<String, CompError>,
+) -> Result<TenantBinding, CompError> {
+    let mut secrets = HashMap::with_capacity(binding.secrets.len());
+    for key in binding.secrets.keys() {
+        let value = secret_resolver(key, tenant)
+            .map_err(|err| CompError::secret_resolution(key.clone(), err))?;
+        secrets.insert(key.clone(), value.into_bytes());
+    }
+    Ok(TenantBinding {
+        config: binding.config.clone(),
+        secrets,
+        resolved_at: Instant::now(),
+        expires_at: None,
+        stale_reported: false,
     })
 }
 
@@ -146,12 +218,14 @@ mod tests {
         let info = ComponentInfo {
             name: Some("fixture".into()),
             description: None,
+            role: None,
             capabilities: vec![CapabilityRef("telemetry".into())],
             exports: vec![component_manifest::CompiledExportSchema {
                 operation: "noop".into(),
                 description: None,
                 input_schema: None,
                 output_schema: None,
+                deprecated: None,
             }],
             config_schema: config_schema_json,
             secret_requirements: vec![secret_requirement],
@@ -160,6 +234,7 @@ mod tests {
                 min: "0.4.0".into(),
                 max: Some("0.4.x".into()),
             },
+            dependencies: Vec::new(),
             metadata: Map::new(),
             raw: manifest_json,
         };
@@ -219,4 +294,92 @@ mod tests {
         let err = resolve_binding(&info, &schema, &bindings, &tenant, &mut resolver).unwrap_err();
         assert!(matches!(err, CompError::SchemaValidation(_)));
     }
+
+    #[test]
+    fn refreshes_rotated_secrets_and_keeps_config() {
+        let (info, schema) = component_fixture();
+        let tenant = tenant_ctx();
+        let bindings = Bindings {
+            config: json!({"enabled": true}),
+            secrets: vec!["API_TOKEN".into()],
+        };
+        let mut initial =
+            |_key: &str, _ctx: &TenantCtx| -> Result<String, CompError> { Ok("old".into()) };
+        let mut binding =
+            resolve_binding(&info, &schema, &bindings, &tenant, &mut initial).unwrap();
+        binding.stale_reported = true;
+
+        let mut rotated =
+            |_key: &str, _ctx: &TenantCtx| -> Result<String, CompError> { Ok("new".into()) };
+        let refreshed = refresh_secrets(&binding, &tenant, &mut rotated).unwrap();
+        assert_eq!(refreshed.secrets.get("API_TOKEN").unwrap(), b"new");
+        assert_eq!(refreshed.config, binding.config);
+        assert!(!refreshed.stale_reported);
+        assert!(refreshed.resolved_at >= binding.resolved_at);
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordingHook(std::sync::Mutex<Vec<Vec<String>>>);
+
+    impl crate::SecretAgeHook for RecordingHook {
+        fn on_stale_secrets(&self, event: &crate::StaleSecrets<'_>) {
+            let secrets = event.secrets.iter().map(|key| key.to_string()).collect();
+            self.0.lock().unwrap().push(secrets);
+        }
+    }
+
+    #[test]
+    fn rotation_reports_stale_secrets_once_then_expires() {
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let (info, schema) = component_fixture();
+        let tenant = tenant_ctx();
+        let bindings = Bindings {
+            config: json!({"enabled": true}),
+            secrets: vec!["API_TOKEN".into()],
+        };
+        let mut resolver =
+            |_key: &str, _ctx: &TenantCtx| -> Result<String, CompError> { Ok("secret".into()) };
+        let mut binding =
+            resolve_binding(&info, &schema, &bindings, &tenant, &mut resolver).unwrap();
+        let start = binding.resolved_at;
+        binding.expires_at = Some(start + Duration::from_secs(120));
+
+        let hook = Arc::new(RecordingHook::default());
+        let rotation =
+            crate::SecretRotation::default().with_max_age(Duration::from_secs(60), hook.clone());
+
+        rotation
+            .check(
+                "demo",
+                &tenant,
+                &mut binding,
+                start + Duration::from_secs(30),
+            )
+            .unwrap();
+        assert!(hook.0.lock().unwrap().is_empty());
+
+        for _ in 0..2 {
+            rotation
+                .check(
+                    "demo",
+                    &tenant,
+                    &mut binding,
+                    start + Duration::from_secs(90),
+                )
+                .unwrap();
+        }
+        assert_eq!(*hook.0.lock().unwrap(), vec![vec!["API_TOKEN".to_string()]]);
+
+        let err = rotation
+            .check(
+                "demo",
+                &tenant,
+                &mut binding,
+                start + Duration::from_secs(120),
+            )
+            .unwrap_err();
+        assert!(matches!(err, CompError::SecretsExpired(_)));
+    }
 }
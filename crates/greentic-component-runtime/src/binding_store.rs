@@ -0,0 +1,286 @@
+use std::fmt;
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use greentic_types::{EnvId, TenantCtx, TenantId};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::binder::{Binder, Bindings, binding_key, resolve_binding, secret_expiry};
+use crate::error::CompError;
+use crate::loader::ComponentHandle;
+use crate::policy::{PolicyAction, PolicyRequest};
+
+const MAGIC: &[u8; 5] = b"GCBS\x01";
+const NONCE_LEN: usize = 12;
+
+/// Symmetric key held by the host and used to seal binding snapshots.
+#[derive(Clone)]
+pub struct HostKey(Key);
+
+impl HostKey {
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes.into())
+    }
+
+    pub fn generate() -> Self {
+        Self(ChaCha20Poly1305::generate_key(&mut OsRng))
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.into()
+    }
+}
+
+impl fmt::Debug for HostKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HostKey(..)")
+    }
+}
+
+/// Outcome of [`Binder::restore`].
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    /// Binding keys (`env::tenant`) restored from the snapshot.
+    pub restored: Vec<String>,
+    /// Bindings already present on the handle, which are left untouched.
+    pub skipped: Vec<String>,
+    /// Bindings that no longer validate or bind under the current policy.
+    pub rejected: Vec<(String, CompError)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotBody {
+    component: String,
+    version: String,
+    bindings: Vec<SnapshotEntry>,
+}
+
+/// Secret values are never written out; only the keys are kept so they can
+/// be resolved again on restore.
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotEntry {
+    env: String,
+    tenant: String,
+    config: Value,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    secrets: Vec<String>,
+}
+
+impl Binder {
+    /// Seals the handle's current config bindings with `key`. The component
+    /// name is bound into the ciphertext, so a snapshot only opens for the
+    /// component it was taken from.
+    pub fn snapshot(&self, handle: &ComponentHandle, key: &HostKey) -> Result<Vec<u8>, CompError> {
+        let inner = &handle.inner;
+        let mut bindings = {
+            let guard = inner.bindings.lock().expect("binding mutex poisoned");
+            guard
+                .iter()
+                .map(|(binding_key, binding)| {
+                    let (env, tenant) = binding_key.split_once("::").ok_or_else(|| {
+                        CompError::Snapshot(format!("malformed binding key `{binding_key}`"))
+                    })?;
+                    let mut secrets = binding.secrets.keys().cloned().collect::<Vec<_>>();
+                    secrets.sort_unstable();
+                    Ok(SnapshotEntry {
+                        env: env.to_string(),
+                        tenant: tenant.to_string(),
+                        config: binding.config.clone(),
+                        secrets,
+                    })
+                })
+                .collect::<Result<Vec<_>, CompError>>()?
+        };
+        bindings.sort_by(|a, b| (&a.env, &a.tenant).cmp(&(&b.env, &b.tenant)));
+        let body = SnapshotBody {
+            component: inner.cref.name.clone(),
+            version: inner.version.clone(),
+            bindings,
+        };
+        seal(&body, key)
+    }
+
+    /// Restores bindings from a sealed snapshot without contacting the
+    /// control plane. Each entry is authorized and validated against the
+    /// loaded component as if it were passed to [`Binder::bind`]; secrets
+    /// are resolved again through `secret_resolver`.
+    pub fn restore(
+        &self,
+        handle: &ComponentHandle,
+        sealed: &[u8],
+        key: &HostKey,
+        secret_resolver: &mut dyn FnMut(&str, &TenantCtx) -> Result<String, CompError>,
+    ) -> Result<RestoreReport, CompError> {
+        let inner = &handle.inner;
+        let body = open(sealed, key, &inner.cref.name)?;
+        if body.component != inner.cref.name {
+            return Err(CompError::Snapshot(format!(
+                "snapshot belongs to `{}`, not `{}`",
+                body.component, inner.cref.name
+            )));
+        }
+        if body.version != inner.version {
+            tracing::info!(
+                component = %inner.cref.name,
+                snapshot_version = %body.version,
+                version = %inner.version,
+                "restoring bindings taken from another component version"
+            );
+        }
+
+        let mut report = RestoreReport::default();
+        let mut resolved = Vec::with_capacity(body.bindings.len());
+        for entry in body.bindings {
+            let tenant = TenantCtx::new(EnvId(entry.env), TenantId(entry.tenant));
+            let key = binding_key(&tenant);
+            let bindings = Bindings::new(entry.config, entry.secrets);
+            let outcome = inner
+                .host_policy
+                .authorize(&PolicyRequest {
+                    action: PolicyAction::Bind,
+                    component: &inner.cref.name,
+                    tenant: Some(&tenant),
+                    operation: None,
+                    capabilities: &inner.info.capabilities,
+                })
+                .and_then(|_| {
+                    resolve_binding(
+                        &inner.info,
+                        inner.config_schema.as_ref(),
+                        &bindings,
+                        &tenant,
+                        secret_resolver,
+                    )
+                });
+            match outcome {
+                Ok(mut binding) => {
+                    binding.expires_at = secret_expiry(handle, binding.resolved_at);
+                    resolved.push((key, binding));
+                }
+                Err(err) => report.rejected.push((key, err)),
+            }
+        }
+
+        let mut guard = inner.bindings.lock().expect("binding mutex poisoned");
+        for (key, binding) in resolved {
+            if guard.contains_key(&key) {
+                report.skipped.push(key);
+            } else {
+                guard.insert(key.clone(), binding);
+                report.restored.push(key);
+            }
+        }
+        Ok(report)
+    }
+}
+
+fn seal(body: &SnapshotBody, key: &HostKey) -> Result<Vec<u8>, CompError> {
+    let plaintext = serde_json::to_vec(body)?;
+    let cipher = ChaCha20Poly1305::new(&key.0);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: &plaintext,
+                aad: body.component.as_bytes(),
+            },
+        )
+        .map_err(|_| CompError::Snapshot("encryption failed".into()))?;
+
+    let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(MAGIC);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+fn open(sealed: &[u8], key: &HostKey, component: &str) -> Result<SnapshotBody, CompError> {
+    let rest = sealed
+        .strip_prefix(MAGIC.as_slice())
+        .ok_or_else(|| CompError::Snapshot("not a binding snapshot".into()))?;
+    if rest.len() < NONCE_LEN {
+        return Err(CompError::Snapshot("snapshot is truncated".into()));
+    }
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let cipher = ChaCha20Poly1305::new(&key.0);
+    let plaintext = cipher
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: component.as_bytes(),
+            },
+        )
+        .map_err(|_| {
+            CompError::Snapshot(format!(
+                "cannot open snapshot for `{component}` (wrong key, component or corrupted data)"
+            ))
+        })?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn body() -> SnapshotBody {
+        SnapshotBody {
+            component: "demo".into(),
+            version: "1.0.0".into(),
+            bindings: vec![SnapshotEntry {
+                env: "dev".into(),
+                tenant: "acme".into(),
+                config: json!({"enabled": true}),
+                secrets: vec!["API_TOKEN".into()],
+            }],
+        }
+    }
+
+    #[test]
+    fn sealed_snapshot_round_trips() {
+        let key = HostKey::generate();
+        let sealed = seal(&body(), &key).unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        assert!(
+            !sealed
+                .windows(b"API_TOKEN".len())
+                .any(|window| window == b"API_TOKEN")
+        );
+
+        let opened = open(&sealed, &key, "demo").unwrap();
+        assert_eq!(opened.version, "1.0.0");
+        assert_eq!(opened.bindings.len(), 1);
+        assert_eq!(opened.bindings[0].tenant, "acme");
+        assert_eq!(opened.bindings[0].secrets, vec!["API_TOKEN".to_string()]);
+    }
+
+    #[test]
+    fn rejects_wrong_key_component_or_tampering() {
+        let key = HostKey::from_bytes([7; 32]);
+        let sealed = seal(&body(), &key).unwrap();
+
+        let other_key = HostKey::from_bytes([8; 32]);
+        assert!(matches!(
+            open(&sealed, &other_key, "demo"),
+            Err(CompError::Snapshot(_))
+        ));
+        assert!(matches!(
+            open(&sealed, &key, "other"),
+            Err(CompError::Snapshot(_))
+        ));
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 0x01;
+        assert!(matches!(
+            open(&tampered, &key, "demo"),
+            Err(CompError::Snapshot(_))
+        ));
+        assert!(matches!(
+            open(b"nope", &key, "demo"),
+            Err(CompError::Snapshot(_))
+        ));
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use greentic_types::TenantCtx;
+use wasmtime::StoreContextMut;
+use wasmtime::component::Linker;
+
+use crate::error::CompError;
+
+/// Interface guests import for blob access:
+///
+/// ```wit
+/// put: func(bytes: list<u8>) -> result<string, string>;
+/// append: func(handle: string, bytes: list<u8>) -> result<u64, string>;
+/// get: func(handle: string) -> result<list<u8>, string>;
+/// read: func(handle: string, offset: u64, len: u32) -> result<list<u8>, string>;
+/// size: func(handle: string) -> result<u64, string>;
+/// ```
+pub const BLOB_INTERFACE: &str = "greentic:component/blob@0.6.0";
+/// Capability a component must declare before touching blobs. Undeclared
+/// calls trap.
+pub const BLOB_CAPABILITY: &str = "blob";
+
+/// Storage behind the blob interface. `scope` isolates tenants: a handle is
+/// only valid in the scope that created it.
+pub trait BlobBackend: fmt::Debug + Send + Sync {
+    /// Stores `bytes` as a new blob and returns its handle.
+    fn put(&self, scope: &str, bytes: &[u8]) -> Result<String, CompError>;
+    /// Appends to an existing blob, returning its new size.
+    fn append(&self, scope: &str, handle: &str, bytes: &[u8]) -> Result<u64, CompError>;
+    /// Up to `len` bytes starting at `offset`; empty past the end.
+    fn read(&self, scope: &str, handle: &str, offset: u64, len: u64) -> Result<Vec<u8>, CompError>;
+    fn size(&self, scope: &str, handle: &str) -> Result<u64, CompError>;
+}
+
+/// Per-invocation caps on blob traffic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlobLimits {
+    /// Largest single blob, and largest single `get`/`read`.
+    pub max_blob_bytes: u64,
+    /// Total bytes one invocation may write across all blobs.
+    pub max_written_bytes: u64,
+}
+
+impl Default for BlobLimits {
+    fn default() -> Self {
+        Self {
+            max_blob_bytes: 64 * 1024 * 1024,
+            max_written_bytes: 256 * 1024 * 1024,
+        }
+    }
+}
+
+/// Scope the runtime uses for `tenant`'s blobs; hosts pass it to the backend
+/// to hand blobs in or read a component's results.
+pub fn blob_scope(tenant: &TenantCtx) -> String {
+    crate::binder::binding_key(tenant)
+}
+
+fn next_handle(counter: &AtomicU64) -> String {
+    format!("blob-{:016x}", counter.fetch_add(1, Ordering::Relaxed))
+}
+
+fn not_found(handle: &str) -> CompError {
+    CompError::Blob(format!("blob `{handle}` not found"))
+}
+
+/// Process-local backend; the runtime default.
+#[derive(Debug, Default)]
+pub struct InMemoryBlobs {
+    next: AtomicU64,
+    blobs: Mutex<HashMap<(String, String), Vec<u8>>>,
+}
+
+impl BlobBackend for InMemoryBlobs {
+    fn put(&self, scope: &str, bytes: &[u8]) -> Result<String, CompError> {
+        let handle = next_handle(&self.next);
+        self.blobs
+            .lock()
+            .expect("blob mutex poisoned")
+            .insert((scope.to_string(), handle.clone()), bytes.to_vec());
+        Ok(handle)
+    }
+
+    fn append(&self, scope: &str, handle: &str, bytes: &[u8]) -> Result<u64, CompError> {
+        let mut blobs = self.blobs.lock().expect("blob mutex poisoned");
+        let blob = blobs
+            .get_mut(&(scope.to_string(), handle.to_string()))
+            .ok_or_else(|| not_found(handle))?;
+        blob.extend_from_slice(bytes);
+        Ok(blob.len() as u64)
+    }
+
+    fn read(&self, scope: &str, handle: &str, offset: u64, len: u64) -> Result<Vec<u8>, CompError> {
+        let blobs = self.blobs.lock().expect("blob mutex poisoned");
+        let blob = blobs
+            .get(&(scope.to_string(), handle.to_string()))
+            .ok_or_else(|| not_found(handle))?;
+        let start = usize::try_from(offset)
+            .unwrap_or(usize::MAX)
+            .min(blob.len());
+        let end = start.saturating_add(usize::try_from(len).unwrap_or(usize::MAX));
+        Ok(blob[start..end.min(blob.len())].to_vec())
+    }
+
+    fn size(&self, scope: &str, handle: &str) -> Result<u64, CompError> {
+        self.blobs
+            .lock()
+            .expect("blob mutex poisoned")
+            .get(&(scope.to_string(), handle.to_string()))
+            .map(|blob| blob.len() as u64)
+            .ok_or_else(|| not_found(handle))
+    }
+}
+
+/// Stores each blob as a file under `root/<hex scope>/<handle>`.
+#[derive(Debug)]
+pub struct DirBlobs {
+    root: PathBuf,
+    next: AtomicU64,
+}
+
+impl DirBlobs {
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self, CompError> {
+        let root = root.into();
+        fs::create_dir_all(&root)
+            .map_err(|err| CompError::Blob(format!("create blob dir {}: {err}", root.display())))?;
+        Ok(Self {
+            root,
+            next: AtomicU64::new(0),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, scope: &str, handle: &str) -> Result<PathBuf, CompError> {
+        // Handles come from the guest; only accept the shape `put` hands out.
+        let valid = handle
+            .strip_prefix("blob-")
+            .is_some_and(|rest| !rest.is_empty() && rest.bytes().all(|b| b.is_ascii_hexdigit()));
+        if !valid {
+            return Err(not_found(handle));
+        }
+        let scope = scope
+            .bytes()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>();
+        Ok(self.root.join(scope).join(handle))
+    }
+
+    fn open(&self, scope: &str, handle: &str, options: &OpenOptions) -> Result<File, CompError> {
+        options
+            .open(self.path(scope, handle)?)
+            .map_err(|_| not_found(handle))
+    }
+}
+
+fn io_error(handle: &str, err: std::io::Error) -> CompError {
+    CompError::Blob(format!("blob `{handle}`: {err}"))
+}
+
+impl BlobBackend for DirBlobs {
+    fn put(&self, scope: &str, bytes: &[u8]) -> Result<String, CompError> {
+        let handle = next_handle(&self.next);
+        let path = self.path(scope, &handle)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| io_error(&handle, err))?;
+        }
+        fs::write(&path, bytes).map_err(|err| io_error(&handle, err))?;
+        Ok(handle)
+    }
+
+    fn append(&self, scope: &str, handle: &str, bytes: &[u8]) -> Result<u64, CompError> {
+        let mut file = self.open(scope, handle, OpenOptions::new().append(true))?;
+        file.write_all(bytes).map_err(|err| io_error(handle, err))?;
+        file.metadata()
+            .map(|meta| meta.len())
+            .map_err(|err| io_error(handle, err))
+    }
+
+    fn read(&self, scope: &str, handle: &str, offset: u64, len: u64) -> Result<Vec<u8>, CompError> {
+        let mut file = self.open(scope, handle, OpenOptions::new().read(true))?;
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|err| io_error(handle, err))?;
+        let mut bytes = Vec::new();
+        file.take(len)
+            .read_to_end(&mut bytes)
+            .map_err(|err| io_error(handle, err))?;
+        Ok(bytes)
+    }
+
+    fn size(&self, scope: &str, handle: &str) -> Result<u64, CompError> {
+        fs::metadata(self.path(scope, handle)?)
+            .map(|meta| meta.len())
+            .map_err(|_| not_found(handle))
+    }
+}
+
+/// Host side of the blob import for one invocation.
+#[derive(Debug, Clone)]
+pub struct BlobHost {
+    granted: bool,
+    scope: String,
+    backend: Arc<dyn BlobBackend>,
+    limits: BlobLimits,
+    written: u64,
+}
+
+impl BlobHost {
+    pub fn new(
+        granted: bool,
+        scope: impl Into<String>,
+        backend: Arc<dyn BlobBackend>,
+        limits: BlobLimits,
+    ) -> Self {
+        Self {
+            granted,
+            scope: scope.into(),
+            backend,
+            limits,
+            written: 0,
+        }
+    }
+
+    /// Host without the capability; every call traps.
+    pub fn denied() -> Self {
+        Self::new(
+            false,
+            "",
+            Arc::new(InMemoryBlobs::default()),
+            BlobLimits::default(),
+        )
+    }
+
+    fn check_granted(&self) -> wasmtime::Result<()> {
+        if self.granted {
+            Ok(())
+        } else {
+            Err(wasmtime::Error::new(CompError::HostFeatureDenied(
+                BLOB_CAPABILITY,
+            )))
+        }
+    }
+
+    fn charge(&mut self, bytes: usize, blob_size: u64) -> Result<(), CompError> {
+        let bytes = bytes as u64;
+        if blob_size > self.limits.max_blob_bytes {
+            return Err(CompError::Blob(format!(
+                "blob would be {blob_size} bytes, limit is {}",
+                self.limits.max_blob_bytes
+            )));
+        }
+        if self.written.saturating_add(bytes) > self.limits.max_written_bytes {
+            return Err(CompError::Blob(format!(
+                "invocation would write more than {} blob bytes",
+                self.limits.max_written_bytes
+            )));
+        }
+        self.written += bytes;
+        Ok(())
+    }
+
+    pub fn put(&mut self, bytes: &[u8]) -> Result<String, CompError> {
+        self.charge(bytes.len(), bytes.len() as u64)?;
+        self.backend.put(&self.scope, bytes)
+    }
+
+    pub fn append(&mut self, handle: &str, bytes: &[u8]) -> Result<u64, CompError> {
+        let size = self.backend.size(&self.scope, handle)?;
+        self.charge(bytes.len(), size.saturating_add(bytes.len() as u64))?;
+        self.backend.append(&self.scope, handle, bytes)
+    }
+
+    pub fn get(&self, handle: &str) -> Result<Vec<u8>, CompError> {
+        let size = self.backend.size(&self.scope, handle)?;
+        if size > self.limits.max_blob_bytes {
+            return Err(CompError::Blob(format!(
+                "blob `{handle}` is {size} bytes; use read to stream it"
+            )));
+        }
+        self.backend.read(&self.scope, handle, 0, size)
+    }
+
+    pub fn read(&self, handle: &str, offset: u64, len: u32) -> Result<Vec<u8>, CompError> {
+        let len = u64::from(len).min(self.limits.max_blob_bytes);
+        self.backend.read(&self.scope, handle, offset, len)
+    }
+
+    pub fn size(&self, handle: &str) -> Result<u64, CompError> {
+        self.backend.size(&self.scope, handle)
+    }
+}
+
+/// Defines [`BLOB_INTERFACE`] on `linker`. Backend failures and limit hits
+/// are returned to the guest as `err(message)`.
+pub fn add_blob_to_linker<T>(
+    linker: &mut Linker<T>,
+    get_host: impl Fn(&mut T) -> &mut BlobHost + Send + Sync + Copy + 'static,
+) -> wasmtime::Result<()>
+where
+    T: Send + 'static,
+{
+    let mut inst = linker.instance(BLOB_INTERFACE)?;
+    inst.func_wrap(
+        "put",
+        move |mut caller: StoreContextMut<'_, T>, (bytes,): (Vec<u8>,)| {
+            let host = get_host(caller.data_mut());
+            host.check_granted()?;
+            Ok((host.put(&bytes).map_err(|err| err.to_string()),))
+        },
+    )?;
+    inst.func_wrap(
+        "append",
+        move |mut caller: StoreContextMut<'_, T>, (handle, bytes): (String, Vec<u8>)| {
+            let host = get_host(caller.data_mut());
+            host.check_granted()?;
+            Ok((host.append(&handle, &bytes).map_err(|err| err.to_string()),))
+        },
+    )?;
+    inst.func_wrap(
+        "get",
+        move |mut caller: StoreContextMut<'_, T>, (handle,): (String,)| {
+            let host = get_host(caller.data_mut());
+            host.check_granted()?;
+            Ok((host.get(&handle).map_err(|err| err.to_string()),))
+        },
+    )?;
+    inst.func_wrap(
+        "read",
+        move |mut caller: StoreContextMut<'_, T>, (handle, offset, len): (String, u64, u32)| {
+            let host = get_host(caller.data_mut());
+            host.check_granted()?;
+            Ok((host
+                .read(&handle, offset, len)
+                .map_err(|err| err.to_string()),))
+        },
+    )?;
+    inst.func_wrap(
+        "size",
+        move |mut caller: StoreContextMut<'_, T>, (handle,): (String,)| {
+            let host = get_host(caller.data_mut());
+            host.check_granted()?;
+            Ok((host.size(&handle).map_err(|err| err.to_string()),))
+        },
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(backend: Arc<dyn BlobBackend>) {
+        let mut host = BlobHost::new(
+            true,
+            "dev::acme",
+            Arc::clone(&backend),
+            BlobLimits {
+                max_blob_bytes: 8,
+                max_written_bytes: 12,
+            },
+        );
+        let handle = host.put(b"abcd").unwrap();
+        assert_eq!(host.append(&handle, b"efg").unwrap(), 7);
+        assert_eq!(host.get(&handle).unwrap(), b"abcdefg");
+        assert_eq!(host.read(&handle, 2, 3).unwrap(), b"cde");
+        assert!(host.read(&handle, 10, 3).unwrap().is_empty());
+
+        // Single blob limit, then the per-invocation write budget.
+        assert!(host.append(&handle, b"hi").is_err());
+        host.put(b"12345").unwrap();
+        assert!(host.put(b"x").is_err());
+
+        // Other tenants cannot see the handle.
+        let other = BlobHost::new(false, "dev::other", backend, BlobLimits::default());
+        assert!(other.size(&handle).is_err());
+        assert!(other.check_granted().is_err());
+    }
+
+    #[test]
+    fn in_memory_backend_enforces_limits_and_scopes() {
+        roundtrip(Arc::new(InMemoryBlobs::default()));
+    }
+
+    #[test]
+    fn dir_backend_enforces_limits_and_scopes() {
+        let root = std::env::temp_dir().join(format!("greentic-blobs-{}", std::process::id()));
+        let backend = DirBlobs::new(&root).unwrap();
+        assert!(backend.size("dev::acme", "../escape").is_err());
+        roundtrip(Arc::new(backend));
+        let _ = fs::remove_dir_all(root);
+    }
+}
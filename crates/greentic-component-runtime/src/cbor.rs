@@ -0,0 +1,114 @@
+use greentic_types::cbor::canonical;
+use greentic_types::schemas::component::v0_6_0::ComponentDescribe;
+use serde_json::Value;
+
+use crate::error::CompError;
+
+/// CBOR self-describe tag (55799) that guests may prefix payloads with.
+pub const SELF_DESCRIBE_TAG: [u8; 3] = [0xd9, 0xd9, 0xf7];
+
+/// Why [`validate_canonical_cbor`] rejected a payload.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CanonicalCborError {
+    #[error("payload is empty")]
+    Empty,
+    #[error("payload is not valid CBOR: {0}")]
+    Malformed(String),
+    /// Decodes, but map keys are out of order, lengths are not minimal or
+    /// lengths are indefinite.
+    #[error("payload is not canonical CBOR (first difference at byte {offset})")]
+    NotCanonical { offset: usize },
+}
+
+/// Drops a leading self-describe tag, if any.
+pub fn strip_self_describe_tag(bytes: &[u8]) -> &[u8] {
+    bytes.strip_prefix(&SELF_DESCRIBE_TAG).unwrap_or(bytes)
+}
+
+/// Checks that `bytes` is what the host expects from `describe` and
+/// `invoke`: one CBOR item in canonical form, optionally self-describe
+/// tagged. Floats are allowed. Component authors can run this on their own
+/// outputs before shipping.
+pub fn validate_canonical_cbor(bytes: &[u8]) -> Result<(), CanonicalCborError> {
+    let payload = strip_self_describe_tag(bytes);
+    if payload.is_empty() {
+        return Err(CanonicalCborError::Empty);
+    }
+    let canonical = canonical::canonicalize_allow_floats(payload)
+        .map_err(|err| CanonicalCborError::Malformed(err.to_string()))?;
+    if canonical == payload {
+        return Ok(());
+    }
+    let offset = payload
+        .iter()
+        .zip(&canonical)
+        .position(|(ours, theirs)| ours != theirs)
+        .unwrap_or_else(|| payload.len().min(canonical.len()));
+    Err(CanonicalCborError::NotCanonical {
+        offset: bytes.len() - payload.len() + offset,
+    })
+}
+
+/// Decodes a `describe` payload the way [`Loader`](crate::Loader) does.
+pub fn decode_describe(bytes: &[u8]) -> Result<ComponentDescribe, CompError> {
+    canonical::from_cbor(strip_self_describe_tag(bytes))
+        .map_err(|err| CompError::SchemaValidation(err.to_string()))
+}
+
+/// Decodes an `invoke` output the way the invoker does.
+pub fn decode_output(bytes: &[u8]) -> Result<Value, CompError> {
+    canonical::from_cbor(bytes)
+        .map_err(|err| CompError::Runtime(format!("decode invoke output failed: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn strips_self_describe_tag_only_when_present() {
+        let tagged = [SELF_DESCRIBE_TAG.as_slice(), &[1_u8, 2, 3]].concat();
+        assert_eq!(strip_self_describe_tag(&tagged), &[1_u8, 2, 3]);
+        assert_eq!(strip_self_describe_tag(&[7_u8, 8, 9]), &[7_u8, 8, 9]);
+    }
+
+    #[test]
+    fn accepts_canonical_and_reports_where_it_diverges() {
+        let canonical_bytes =
+            canonical::to_canonical_cbor_allow_floats(&json!({ "a": 1, "bb": [1.5, "x"] }))
+                .expect("encode");
+        assert_eq!(validate_canonical_cbor(&canonical_bytes), Ok(()));
+        let tagged = [SELF_DESCRIBE_TAG.as_slice(), &canonical_bytes].concat();
+        assert_eq!(validate_canonical_cbor(&tagged), Ok(()));
+
+        // {"bb": 1, "a": 2}: keys out of canonical order.
+        let unordered = [0xa2, 0x62, b'b', b'b', 0x01, 0x61, b'a', 0x02];
+        assert_eq!(
+            validate_canonical_cbor(&unordered),
+            Err(CanonicalCborError::NotCanonical { offset: 1 })
+        );
+        // 1 encoded with a one-byte length argument instead of inline.
+        assert_eq!(
+            validate_canonical_cbor(&[0x18, 0x01]),
+            Err(CanonicalCborError::NotCanonical { offset: 0 })
+        );
+
+        assert_eq!(
+            validate_canonical_cbor(&SELF_DESCRIBE_TAG),
+            Err(CanonicalCborError::Empty)
+        );
+        assert!(matches!(
+            validate_canonical_cbor(&[0xa1, 0x61]),
+            Err(CanonicalCborError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn decoders_reject_garbage_without_panicking() {
+        for bytes in [&[][..], &[0xff], &[0x9f, 0x01], &SELF_DESCRIBE_TAG] {
+            assert!(decode_describe(bytes).is_err());
+            let _ = decode_output(bytes);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Admission control for invocations on one component handle. Once draining
+/// starts no new invocations are admitted, and callers can wait for the ones
+/// already running.
+#[derive(Debug, Default)]
+pub(crate) struct InFlight {
+    state: Mutex<InFlightState>,
+    idle: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct InFlightState {
+    active: usize,
+    draining: bool,
+}
+
+impl InFlight {
+    /// Registers an invocation; `None` once the handle is draining.
+    pub(crate) fn enter(&self) -> Option<InFlightGuard<'_>> {
+        let mut state = self.state.lock().expect("in-flight mutex poisoned");
+        if state.draining {
+            return None;
+        }
+        state.active += 1;
+        Some(InFlightGuard(self))
+    }
+
+    pub(crate) fn begin_drain(&self) {
+        self.state
+            .lock()
+            .expect("in-flight mutex poisoned")
+            .draining = true;
+    }
+
+    pub(crate) fn is_draining(&self) -> bool {
+        self.state
+            .lock()
+            .expect("in-flight mutex poisoned")
+            .draining
+    }
+
+    /// Blocks until no invocation is running or `timeout` passes, returning
+    /// how many are still running.
+    pub(crate) fn wait_idle(&self, timeout: Duration) -> usize {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.lock().expect("in-flight mutex poisoned");
+        while state.active > 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            state = self
+                .idle
+                .wait_timeout(state, deadline - now)
+                .expect("in-flight mutex poisoned")
+                .0;
+        }
+        state.active
+    }
+}
+
+pub(crate) struct InFlightGuard<'a>(&'a InFlight);
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock().expect("in-flight mutex poisoned");
+        state.active -= 1;
+        if state.active == 0 {
+            self.0.idle.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn draining_rejects_new_calls_and_waits_for_running_ones() {
+        let in_flight = Arc::new(InFlight::default());
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let worker = {
+            let in_flight = Arc::clone(&in_flight);
+            thread::spawn(move || {
+                let _guard = in_flight.enter().expect("admitted");
+                started_tx.send(()).unwrap();
+                thread::sleep(Duration::from_millis(50));
+            })
+        };
+        started_rx.recv().unwrap();
+
+        in_flight.begin_drain();
+        assert!(in_flight.is_draining());
+        assert!(in_flight.enter().is_none());
+        assert_eq!(in_flight.wait_idle(Duration::from_secs(5)), 0);
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn wait_idle_reports_calls_still_running_at_timeout() {
+        let in_flight = InFlight::default();
+        let _guard = in_flight.enter().expect("admitted");
+        in_flight.begin_drain();
+        assert_eq!(in_flight.wait_idle(Duration::from_millis(10)), 1);
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use wasmtime::{Config, Engine};
+
+use crate::epoch::EpochTicker;
+use crate::error::CompError;
+use crate::isolation::{IsolationProfile, PoolingConfig};
+
+/// Extra engine settings applied after the isolation profile, such as a
+/// compilation cache or profiling strategy.
+pub type EngineConfigurer = Arc<dyn Fn(&mut Config) + Send + Sync>;
+
+static GLOBAL: OnceLock<EngineProvider> = OnceLock::new();
+
+/// Hands out one wasmtime [`Engine`] per distinct engine configuration, so
+/// every load in the process shares compiled-code caches and, when pooling,
+/// a single instance pool. [`load`](crate::load) and [`Loader`](crate::Loader)
+/// use [`EngineProvider::global`]; call [`install`](Self::install) before the
+/// first load to configure it. Engines are kept for the provider's lifetime.
+pub struct EngineProvider {
+    configure: Option<EngineConfigurer>,
+    engines: Mutex<HashMap<EngineKey, SharedEngine>>,
+    created: AtomicU64,
+    reused: AtomicU64,
+}
+
+/// Counters for an [`EngineProvider`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EngineStats {
+    /// Engines currently held.
+    pub engines: usize,
+    /// Loads that had to build a new engine.
+    pub created: u64,
+    /// Loads served by an engine that already existed.
+    pub reused: u64,
+}
+
+/// Settings that must match for two loads to share an engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct EngineKey {
+    pub(crate) isolation: IsolationProfile,
+    pub(crate) pooling: Option<PoolingConfig>,
+    pub(crate) mode: EngineMode,
+}
+
+/// Both modes use epoch interruption, so loads with and without
+/// [`LoadPolicy::with_invoke_timeout`](crate::LoadPolicy::with_invoke_timeout)
+/// share one engine and, when pooling, one instance pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum EngineMode {
+    Blocking,
+    /// Async, suspending to the executor every `interval`.
+    Yielding {
+        interval: Duration,
+    },
+}
+
+/// An engine and the ticker advancing its epoch.
+#[derive(Clone)]
+pub(crate) struct SharedEngine {
+    pub(crate) engine: Engine,
+    pub(crate) ticker: EpochTicker,
+}
+
+impl EngineProvider {
+    pub fn new() -> Self {
+        Self {
+            configure: None,
+            engines: Mutex::new(HashMap::new()),
+            created: AtomicU64::new(0),
+            reused: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_config(mut self, configure: EngineConfigurer) -> Self {
+        self.configure = Some(configure);
+        self
+    }
+
+    /// Makes this the process-wide provider. Fails once the global provider
+    /// exists, whether from an earlier `install` or from the first load.
+    pub fn install(self) -> Result<(), CompError> {
+        GLOBAL.set(self).map_err(|_| {
+            CompError::Runtime(
+                "engine provider is already configured; install it before the first load".into(),
+            )
+        })
+    }
+
+    /// The process-wide provider, created with defaults on first use.
+    pub fn global() -> &'static EngineProvider {
+        GLOBAL.get_or_init(EngineProvider::new)
+    }
+
+    pub fn stats(&self) -> EngineStats {
+        EngineStats {
+            engines: self.engines.lock().expect("engine provider poisoned").len(),
+            created: self.created.load(Ordering::Relaxed),
+            reused: self.reused.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The engine for `key`, building it on first request. The flag is
+    /// `true` when the engine was built by this call.
+    pub(crate) fn engine(&self, key: EngineKey) -> Result<(SharedEngine, bool), CompError> {
+        let mut engines = self.engines.lock().expect("engine provider poisoned");
+        if let Some(shared) = engines.get(&key) {
+            self.reused.fetch_add(1, Ordering::Relaxed);
+            return Ok((shared.clone(), false));
+        }
+        let shared = self.build(key)?;
+        engines.insert(key, shared.clone());
+        self.created.fetch_add(1, Ordering::Relaxed);
+        Ok((shared, true))
+    }
+
+    fn build(&self, key: EngineKey) -> Result<SharedEngine, CompError> {
+        let mut config = Config::new();
+        config.wasm_component_model(true);
+        config.wasm_backtrace_details(wasmtime::WasmBacktraceDetails::Enable);
+        config.epoch_interruption(true);
+        if let EngineMode::Yielding { .. } = key.mode {
+            config.async_support(true);
+        }
+        key.isolation.configure(&mut config, key.pooling);
+        if let Some(configure) = &self.configure {
+            configure(&mut config);
+        }
+        let engine = Engine::new(&config).map_err(|err| CompError::Runtime(err.to_string()))?;
+        let resolution = match key.mode {
+            EngineMode::Blocking => EpochTicker::DEFAULT_RESOLUTION,
+            EngineMode::Yielding { interval } => interval,
+        };
+        let ticker = EpochTicker::new(engine.clone(), resolution);
+        Ok(SharedEngine { engine, ticker })
+    }
+}
+
+impl Default for EngineProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for EngineProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EngineProvider")
+            .field("configure", &self.configure.is_some())
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(isolation: IsolationProfile, mode: EngineMode) -> EngineKey {
+        EngineKey {
+            isolation,
+            pooling: None,
+            mode,
+        }
+    }
+
+    #[test]
+    fn engines_are_shared_per_configuration() {
+        let provider = EngineProvider::new();
+        let balanced = key(IsolationProfile::Balanced, EngineMode::Blocking);
+
+        let (first, created) = provider.engine(balanced).unwrap();
+        assert!(created);
+        assert_eq!(first.ticker.resolution(), EpochTicker::DEFAULT_RESOLUTION);
+        let (again, created) = provider.engine(balanced).unwrap();
+        assert!(!created);
+        assert!(Engine::same(&first.engine, &again.engine));
+
+        let interval = Duration::from_millis(5);
+        let (yielding, _) = provider
+            .engine(key(
+                IsolationProfile::Balanced,
+                EngineMode::Yielding { interval },
+            ))
+            .unwrap();
+        assert!(!Engine::same(&first.engine, &yielding.engine));
+        assert_eq!(yielding.ticker.resolution(), interval);
+
+        assert_eq!(
+            provider.stats(),
+            EngineStats {
+                engines: 2,
+                created: 2,
+                reused: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use wasmtime::{Engine, Store};
+
+/// Deadline for stores that must never be interrupted; far enough that the
+/// epoch cannot reach it, near enough that adding the current epoch does not
+/// overflow.
+const UNBOUNDED_TICKS: u64 = u64::MAX / 2;
+
+/// One background thread per [`Engine`] that advances its epoch while any
+/// invocation is armed, so timeouts and async yields do not need a thread
+/// per call. The thread sleeps while nothing is armed and exits when the
+/// last ticker handle is dropped.
+#[derive(Clone)]
+pub struct EpochTicker {
+    owner: Arc<Owner>,
+}
+
+/// Keeps the epoch advancing until dropped.
+#[must_use = "the ticker stops advancing once the guard is dropped"]
+pub struct EpochGuard {
+    shared: Arc<Shared>,
+}
+
+struct Owner {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    resolution: Duration,
+    state: Mutex<TickerState>,
+    wake: Condvar,
+}
+
+#[derive(Default)]
+struct TickerState {
+    armed: usize,
+    stopped: bool,
+}
+
+impl EpochTicker {
+    /// Tick length used when callers have no reason to pick their own.
+    pub const DEFAULT_RESOLUTION: Duration = Duration::from_millis(10);
+
+    /// Starts the ticker thread for `engine`, which must have been created
+    /// with `epoch_interruption(true)`.
+    pub fn new(engine: Engine, resolution: Duration) -> Self {
+        let shared = Arc::new(Shared {
+            resolution: resolution.max(Duration::from_millis(1)),
+            state: Mutex::new(TickerState::default()),
+            wake: Condvar::new(),
+        });
+        let ticking = Arc::clone(&shared);
+        thread::Builder::new()
+            .name("greentic-epoch".into())
+            .spawn(move || ticking.run(&engine))
+            .expect("spawn epoch ticker thread");
+        Self {
+            owner: Arc::new(Owner { shared }),
+        }
+    }
+
+    pub fn resolution(&self) -> Duration {
+        self.owner.shared.resolution
+    }
+
+    /// Keeps the epoch advancing every [`resolution`](Self::resolution)
+    /// until the guard drops, without touching any store's deadline.
+    pub fn arm(&self) -> EpochGuard {
+        let shared = &self.owner.shared;
+        shared.state.lock().expect("epoch ticker poisoned").armed += 1;
+        shared.wake.notify_one();
+        EpochGuard {
+            shared: Arc::clone(shared),
+        }
+    }
+
+    /// Sets `store` to trap with [`wasmtime::Trap::Interrupt`] once `timeout`
+    /// has passed (within one tick) and keeps the epoch advancing for it.
+    pub fn deadline<T>(&self, store: &mut Store<T>, timeout: Duration) -> EpochGuard {
+        store.set_epoch_deadline(self.ticks(timeout));
+        self.arm()
+    }
+
+    /// [`deadline`](Self::deadline) when `timeout` is set. Otherwise moves
+    /// `store`'s deadline out of reach, since other invocations on the same
+    /// engine still advance the epoch.
+    pub fn limit<T>(&self, store: &mut Store<T>, timeout: Option<Duration>) -> Option<EpochGuard> {
+        match timeout {
+            Some(timeout) => Some(self.deadline(store, timeout)),
+            None => {
+                store.set_epoch_deadline(UNBOUNDED_TICKS);
+                None
+            }
+        }
+    }
+
+    /// Ticks to wait so that at least `timeout` passes: the first tick may
+    /// land anywhere in the current period, hence the extra one.
+    pub fn ticks(&self, timeout: Duration) -> u64 {
+        let resolution = self.resolution().as_nanos();
+        let whole = timeout.as_nanos().div_ceil(resolution);
+        u64::try_from(whole).unwrap_or(u64::MAX - 1) + 1
+    }
+}
+
+impl Shared {
+    fn run(&self, engine: &Engine) {
+        let mut state = self.state.lock().expect("epoch ticker poisoned");
+        let mut next_tick: Option<Instant> = None;
+        loop {
+            if state.stopped {
+                return;
+            }
+            if state.armed == 0 {
+                next_tick = None;
+                state = self.wake.wait(state).expect("epoch ticker poisoned");
+                continue;
+            }
+            let due = *next_tick.get_or_insert_with(|| Instant::now() + self.resolution);
+            let now = Instant::now();
+            if now >= due {
+                engine.increment_epoch();
+                // After a stall, tick again one period from now rather than
+                // firing the missed ticks back to back.
+                next_tick = Some((due + self.resolution).max(now));
+                continue;
+            }
+            state = self
+                .wake
+                .wait_timeout(state, due - now)
+                .expect("epoch ticker poisoned")
+                .0;
+        }
+    }
+}
+
+impl Drop for Owner {
+    fn drop(&mut self) {
+        self.shared
+            .state
+            .lock()
+            .expect("epoch ticker poisoned")
+            .stopped = true;
+        self.shared.wake.notify_one();
+    }
+}
+
+impl Drop for EpochGuard {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().expect("epoch ticker poisoned");
+        state.armed = state.armed.saturating_sub(1);
+    }
+}
+
+impl std::fmt::Debug for EpochTicker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self
+            .owner
+            .shared
+            .state
+            .lock()
+            .expect("epoch ticker poisoned");
+        f.debug_struct("EpochTicker")
+            .field("resolution", &self.owner.shared.resolution)
+            .field("armed", &state.armed)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn epoch_engine() -> Engine {
+        let mut config = wasmtime::Config::new();
+        config.epoch_interruption(true);
+        Engine::new(&config).expect("engine")
+    }
+
+    #[test]
+    fn deadlines_round_up_to_whole_ticks() {
+        let ticker = EpochTicker::new(epoch_engine(), Duration::from_millis(10));
+        assert_eq!(ticker.ticks(Duration::from_millis(0)), 1);
+        assert_eq!(ticker.ticks(Duration::from_millis(10)), 2);
+        assert_eq!(ticker.ticks(Duration::from_millis(25)), 4);
+    }
+
+    #[test]
+    fn armed_ticker_interrupts_stores_past_their_deadline() {
+        // (module (func (export "spin") (loop (br 0))))
+        const SPIN: &[u8] = &[
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x07, 0x08, 0x01, 0x04, b's', b'p', b'i', b'n', 0x00, 0x00,
+            0x0a, 0x09, 0x01, 0x07, 0x00, 0x03, 0x40, 0x0c, 0x00, 0x0b, 0x0b,
+        ];
+        let engine = epoch_engine();
+        let ticker = EpochTicker::new(engine.clone(), Duration::from_millis(1));
+        let module = wasmtime::Module::new(&engine, SPIN).expect("module");
+        let mut store = Store::new(&engine, ());
+        let started = Instant::now();
+        let _guard = ticker.deadline(&mut store, Duration::from_millis(20));
+        let instance = wasmtime::Instance::new(&mut store, &module, &[]).expect("instance");
+        let spin = instance
+            .get_typed_func::<(), ()>(&mut store, "spin")
+            .expect("spin export");
+        let err = spin.call(&mut store, ()).expect_err("spin never returns");
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert_eq!(
+            err.downcast_ref::<wasmtime::Trap>(),
+            Some(&wasmtime::Trap::Interrupt)
+        );
+    }
+}
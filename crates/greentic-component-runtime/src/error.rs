@@ -1,9 +1,15 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
 use component_manifest::ManifestError;
 use greentic_component_store::StoreError;
 use jsonschema::ValidationError;
 use thiserror::Error;
 use wasmtime::Error as WasmtimeError;
 
+use crate::node_error::ErrorDetails;
+use crate::policy::PolicyAction;
+
 #[derive(Debug, Error)]
 pub enum CompError {
     #[error(transparent)]
@@ -18,6 +24,8 @@ pub enum CompError {
     SchemaValidation(String),
     #[error("binding not found for tenant {0}")]
     BindingNotFound(String),
+    #[error("secrets bound for tenant {0} have expired; rebind them")]
+    SecretsExpired(String),
     #[error("secret `{0}` is not declared by the component")]
     SecretNotDeclared(String),
     #[error("secret `{key}` resolution failed: {source}")]
@@ -28,10 +36,84 @@ pub enum CompError {
     },
     #[error("operation `{0}` is not exported by the component")]
     OperationNotFound(String),
+    #[error("dependency `{0}` has no locator in the manifest or load policy")]
+    DependencyUnresolved(String),
+    #[error("dependency `{id}` is incompatible: {reason}")]
+    DependencyMismatch { id: String, reason: String },
+    #[error("catalog entry `{name}` requires version {required}, component reports {version}")]
+    CatalogVersionMismatch {
+        name: String,
+        required: String,
+        version: String,
+    },
+    #[error("{action} of `{component}` denied by policy: {reason}")]
+    PolicyDenied {
+        action: PolicyAction,
+        component: String,
+        reason: String,
+    },
     #[error("host feature `{0}` is denied by policy")]
     HostFeatureDenied(&'static str),
+    #[error(
+        "rate limit exceeded for tenant `{tenant}` on `{component}.{operation}`; retry in {backoff_ms}ms"
+    )]
+    RateLimited {
+        tenant: String,
+        component: String,
+        operation: String,
+        backoff_ms: u64,
+    },
+    #[error("open handle limit exceeded ({open} open, max {max})")]
+    OpenHandleLimit { open: usize, max: usize },
+    #[error("component `{0}` is draining and no longer accepts invocations")]
+    Draining(String),
+    #[error("drain of `{component}` timed out with {in_flight} invocation(s) still running")]
+    DrainTimeout { component: String, in_flight: usize },
+    #[error("invocation of `{component}` exceeded its {timeout_ms}ms timeout")]
+    InvokeTimeout { component: String, timeout_ms: u64 },
+    #[error("component `{component}` has role `{role}`, which this host does not run")]
+    RoleNotAllowed { component: String, role: String },
+    #[error("output of `{component}` is {size} bytes, over the {limit}-byte limit")]
+    OutputTooLarge {
+        component: String,
+        size: usize,
+        limit: usize,
+    },
+    #[error(
+        "component `{0}` was not loaded for async invocation; use LoadPolicy::with_async_yield"
+    )]
+    AsyncUnavailable(String),
+    #[error("blob error: {0}")]
+    Blob(String),
+    #[error("events error: {0}")]
+    Events(String),
+    #[error("messaging error: {0}")]
+    Messaging(String),
+    #[error("telemetry error: {0}")]
+    Telemetry(String),
+    #[error("tool call failed: {0}")]
+    Tool(String),
+    #[error("sql error: {0}")]
+    Sql(String),
+    #[error("binding snapshot rejected: {0}")]
+    Snapshot(String),
+    #[error("component error {code}: {message}")]
+    Component {
+        code: String,
+        message: String,
+        retryable: bool,
+        backoff_ms: Option<u64>,
+        /// The guest's `details`, decoded from CBOR.
+        details: Option<serde_json::Value>,
+    },
     #[error("invalid manifest: {0}")]
     InvalidManifest(&'static str),
+    #[error("failed to read manifest {}: {source}", path.display())]
+    ManifestIo {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
     #[error("runtime error: {0}")]
     Runtime(String),
 }
@@ -49,4 +131,120 @@ impl CompError {
             source: Box::new(source),
         }
     }
+
+    /// The component's own code for [`CompError::Component`], otherwise the
+    /// error kind.
+    pub fn code(&self) -> &str {
+        match self {
+            CompError::Component { code, .. } => code,
+            CompError::Store(_) => "store",
+            CompError::Manifest(_)
+            | CompError::InvalidManifest(_)
+            | CompError::ManifestIo { .. } => "manifest",
+            CompError::Json(_) => "json",
+            CompError::Wasmtime(_) => "trap",
+            CompError::SchemaValidation(_) => "schema_validation",
+            CompError::BindingNotFound(_) => "binding_not_found",
+            CompError::SecretsExpired(_) => "secrets_expired",
+            CompError::SecretNotDeclared(_) | CompError::SecretResolution { .. } => "secrets",
+            CompError::OperationNotFound(_) => "operation_not_found",
+            CompError::DependencyUnresolved(_) | CompError::DependencyMismatch { .. } => {
+                "dependency"
+            }
+            CompError::PolicyDenied { .. } | CompError::HostFeatureDenied(_) => "policy_denied",
+            CompError::RateLimited { .. } => "rate_limited",
+            CompError::OpenHandleLimit { .. } => "open_handle_limit",
+            CompError::Blob(_) => "blob",
+            CompError::Sql(_) => "sql",
+            CompError::Messaging(_) => "messaging",
+            CompError::Events(_) => "events",
+            CompError::Tool(_) => "tool",
+            CompError::Telemetry(_) => "telemetry",
+            CompError::Snapshot(_) => "snapshot",
+            CompError::Draining(_) => "draining",
+            CompError::AsyncUnavailable(_) => "async_unavailable",
+            CompError::DrainTimeout { .. } => "drain_timeout",
+            CompError::InvokeTimeout { .. } => "timeout",
+            CompError::OutputTooLarge { .. } => "output_too_large",
+            CompError::RoleNotAllowed { .. } => "role_not_allowed",
+            CompError::Runtime(_) => "runtime",
+        }
+    }
+
+    /// Bounded label for metrics: the error kind, with every error the
+    /// component returned reported as `component` since its codes are
+    /// component-defined.
+    pub fn category(&self) -> &str {
+        match self {
+            CompError::Component { .. } => "component",
+            other => other.code(),
+        }
+    }
+
+    /// Whether the same call may succeed if retried later.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            CompError::RateLimited { .. } => true,
+            CompError::Component { retryable, .. } => *retryable,
+            _ => false,
+        }
+    }
+
+    /// Suggested delay before retrying, for retryable errors. A component
+    /// error without `backoff_ms` falls back to its details'
+    /// `retry_after_ms`.
+    pub fn backoff_ms(&self) -> Option<u64> {
+        match self {
+            CompError::RateLimited { backoff_ms, .. } => Some(*backoff_ms),
+            CompError::Component {
+                retryable: true,
+                backoff_ms,
+                ..
+            } => backoff_ms.or_else(|| self.error_details()?.retry_after_ms),
+            _ => None,
+        }
+    }
+
+    /// How long to wait before retrying, or `None` when retrying will not
+    /// help. Retryable errors without a suggested delay retry immediately.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.is_retryable()
+            .then(|| Duration::from_millis(self.backoff_ms().unwrap_or(0)))
+    }
+
+    /// A component error's details, when they follow the documented shape.
+    pub fn error_details(&self) -> Option<ErrorDetails> {
+        match self {
+            CompError::Component {
+                details: Some(details),
+                ..
+            } => ErrorDetails::from_value(details),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn component_codes_share_one_metric_category() {
+        let err = CompError::Component {
+            code: "billing.card_declined.4111".into(),
+            message: "declined".into(),
+            retryable: false,
+            backoff_ms: None,
+            details: None,
+        };
+        assert_eq!(err.code(), "billing.card_declined.4111");
+        assert_eq!(err.category(), "component");
+        let limited = CompError::RateLimited {
+            tenant: "acme".into(),
+            component: "echo".into(),
+            operation: "run".into(),
+            backoff_ms: 10,
+        };
+        assert_eq!(limited.category(), "rate_limited");
+    }
 }
@@ -0,0 +1,147 @@
+use std::fmt;
+use std::sync::Arc;
+
+use greentic_types::TenantCtx;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasmtime::StoreContextMut;
+use wasmtime::component::Linker;
+
+use crate::error::CompError;
+
+/// Interface guests import to publish events or webhooks:
+///
+/// ```wit
+/// emit-event: func(topic: string, payload: string) -> result<_, string>;
+/// ```
+///
+/// `payload` must be JSON.
+pub const EVENTS_INTERFACE: &str = "greentic:component/events@0.6.0";
+/// Capability a component must declare in `describe` to emit events; the
+/// manifest counterpart is `capabilities.host.events.outbound`.
+pub const EVENTS_CAPABILITY: &str = "events";
+
+/// An event emitted by a component.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmittedEvent {
+    pub topic: String,
+    pub payload: Value,
+}
+
+/// Receives emitted events, e.g. a broker publisher or webhook dispatcher.
+/// `scope` identifies the tenant, see [`events_scope`].
+pub trait EventSink: fmt::Debug + Send + Sync {
+    fn emit(&self, scope: &str, event: &EmittedEvent) -> Result<(), CompError>;
+}
+
+/// Scope the runtime passes to [`EventSink::emit`] for `tenant`.
+pub fn events_scope(tenant: &TenantCtx) -> String {
+    crate::binder::binding_key(tenant)
+}
+
+/// Host side of the events import for one invocation.
+#[derive(Debug, Clone, Default)]
+pub struct EventsHost {
+    scope: String,
+    sink: Option<Arc<dyn EventSink>>,
+}
+
+impl EventsHost {
+    /// `sink` is `None` when the component may not emit; calls then trap.
+    pub fn new(scope: impl Into<String>, sink: Option<Arc<dyn EventSink>>) -> Self {
+        Self {
+            scope: scope.into(),
+            sink,
+        }
+    }
+
+    pub fn emit(&self, topic: &str, payload_json: &str) -> Result<(), CompError> {
+        let sink = self
+            .sink
+            .as_ref()
+            .ok_or(CompError::HostFeatureDenied(EVENTS_CAPABILITY))?;
+        if topic.trim().is_empty() {
+            return Err(CompError::Events("topic must not be empty".into()));
+        }
+        let payload = serde_json::from_str(payload_json)
+            .map_err(|err| CompError::Events(format!("payload is not JSON: {err}")))?;
+        sink.emit(
+            &self.scope,
+            &EmittedEvent {
+                topic: topic.to_string(),
+                payload,
+            },
+        )
+    }
+}
+
+/// Defines [`EVENTS_INTERFACE`] on `linker`.
+pub fn add_events_to_linker<T>(
+    linker: &mut Linker<T>,
+    get_host: impl Fn(&mut T) -> &mut EventsHost + Send + Sync + Copy + 'static,
+) -> wasmtime::Result<()>
+where
+    T: Send + 'static,
+{
+    let mut inst = linker.instance(EVENTS_INTERFACE)?;
+    inst.func_wrap(
+        "emit-event",
+        move |mut caller: StoreContextMut<'_, T>, (topic, payload): (String, String)| {
+            let result = match get_host(caller.data_mut()).emit(&topic, &payload) {
+                Err(denied @ CompError::HostFeatureDenied(_)) => {
+                    return Err(wasmtime::Error::new(denied));
+                }
+                other => other.map_err(|err| err.to_string()),
+            };
+            Ok((result,))
+        },
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct Collect(Mutex<Vec<(String, EmittedEvent)>>);
+
+    impl EventSink for Collect {
+        fn emit(&self, scope: &str, event: &EmittedEvent) -> Result<(), CompError> {
+            self.0
+                .lock()
+                .unwrap()
+                .push((scope.to_string(), event.clone()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn forwards_json_events_to_the_sink() {
+        let sink = Arc::new(Collect::default());
+        let host = EventsHost::new("dev::acme", Some(sink.clone()));
+
+        host.emit("orders.created", r#"{"id":7}"#).unwrap();
+        assert!(matches!(
+            host.emit("orders.created", "not json"),
+            Err(CompError::Events(_))
+        ));
+        assert!(host.emit("", "{}").is_err());
+        assert_eq!(
+            *sink.0.lock().unwrap(),
+            vec![(
+                "dev::acme".to_string(),
+                EmittedEvent {
+                    topic: "orders.created".into(),
+                    payload: json!({"id": 7}),
+                }
+            )]
+        );
+        assert!(matches!(
+            EventsHost::default().emit("orders.created", "{}"),
+            Err(CompError::HostFeatureDenied("events"))
+        ));
+    }
+}
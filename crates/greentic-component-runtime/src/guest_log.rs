@@ -0,0 +1,230 @@
+use std::fmt;
+use std::sync::Arc;
+
+use greentic_types::TenantCtx;
+use tracing::Level;
+use tracing::level_filters::LevelFilter;
+use wasmtime::StoreContextMut;
+use wasmtime::component::{ComponentType, Lift, Linker, Lower};
+
+/// Interface guests import to call
+/// `log(level, target: string, message: string, fields: list<tuple<string, string>>)`.
+pub const LOG_INTERFACE: &str = "greentic:component/log@0.6.0";
+
+/// The WIT `level` enum.
+#[derive(ComponentType, Lift, Lower, Debug, Clone, Copy, PartialEq, Eq)]
+#[component(enum)]
+#[repr(u8)]
+pub enum GuestLogLevel {
+    #[component(name = "trace")]
+    Trace,
+    #[component(name = "debug")]
+    Debug,
+    #[component(name = "info")]
+    Info,
+    #[component(name = "warn")]
+    Warn,
+    #[component(name = "error")]
+    Error,
+}
+
+impl GuestLogLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GuestLogLevel::Trace => "trace",
+            GuestLogLevel::Debug => "debug",
+            GuestLogLevel::Info => "info",
+            GuestLogLevel::Warn => "warn",
+            GuestLogLevel::Error => "error",
+        }
+    }
+}
+
+impl From<GuestLogLevel> for Level {
+    fn from(level: GuestLogLevel) -> Self {
+        match level {
+            GuestLogLevel::Trace => Level::TRACE,
+            GuestLogLevel::Debug => Level::DEBUG,
+            GuestLogLevel::Info => Level::INFO,
+            GuestLogLevel::Warn => Level::WARN,
+            GuestLogLevel::Error => Level::ERROR,
+        }
+    }
+}
+
+/// A guest log call that passed the level filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuestLogRecord {
+    pub level: GuestLogLevel,
+    pub target: String,
+    pub message: String,
+    pub fields: Vec<(String, String)>,
+    pub component: String,
+    pub tenant: Option<String>,
+}
+
+/// Extra destination for guest records, next to the tracing event.
+pub type GuestLogSink = Arc<dyn Fn(&GuestLogRecord) + Send + Sync>;
+
+/// Host side of the log import. Records at or below `max_level` become
+/// `tracing` events on the `greentic_guest` target, carrying the component,
+/// tenant and the guest's own target as fields.
+#[derive(Clone)]
+pub struct GuestLogger {
+    component: String,
+    tenant: Option<String>,
+    max_level: LevelFilter,
+    sink: Option<GuestLogSink>,
+}
+
+impl fmt::Debug for GuestLogger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GuestLogger")
+            .field("component", &self.component)
+            .field("tenant", &self.tenant)
+            .field("max_level", &self.max_level)
+            .field("sink", &self.sink.is_some())
+            .finish()
+    }
+}
+
+impl GuestLogger {
+    pub fn new(
+        component: impl Into<String>,
+        tenant: Option<&TenantCtx>,
+        max_level: LevelFilter,
+    ) -> Self {
+        Self {
+            component: component.into(),
+            tenant: tenant.map(|ctx| ctx.tenant.as_str().to_string()),
+            max_level,
+            sink: None,
+        }
+    }
+
+    pub fn with_sink(mut self, sink: GuestLogSink) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    pub fn enabled(&self, level: GuestLogLevel) -> bool {
+        Level::from(level) <= self.max_level
+    }
+
+    pub fn log(
+        &self,
+        level: GuestLogLevel,
+        target: String,
+        message: String,
+        fields: Vec<(String, String)>,
+    ) {
+        if !self.enabled(level) {
+            return;
+        }
+        let record = GuestLogRecord {
+            level,
+            target,
+            message,
+            fields,
+            component: self.component.clone(),
+            tenant: self.tenant.clone(),
+        };
+        emit(&record);
+        if let Some(sink) = &self.sink {
+            sink(&record);
+        }
+    }
+}
+
+impl fmt::Display for GuestLogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        for (key, value) in &self.fields {
+            write!(f, " {key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+fn emit(record: &GuestLogRecord) {
+    let tenant = record.tenant.as_deref().unwrap_or("-");
+    // `tracing` needs the level at compile time, hence one arm per level.
+    macro_rules! event {
+        ($level:expr) => {
+            tracing::event!(
+                target: "greentic_guest",
+                $level,
+                component = %record.component,
+                tenant,
+                guest_target = %record.target,
+                "{record}"
+            )
+        };
+    }
+    match record.level {
+        GuestLogLevel::Trace => event!(Level::TRACE),
+        GuestLogLevel::Debug => event!(Level::DEBUG),
+        GuestLogLevel::Info => event!(Level::INFO),
+        GuestLogLevel::Warn => event!(Level::WARN),
+        GuestLogLevel::Error => event!(Level::ERROR),
+    }
+}
+
+/// Defines [`LOG_INTERFACE`] on `linker`.
+pub fn add_log_to_linker<T>(
+    linker: &mut Linker<T>,
+    get: impl Fn(&mut T) -> &mut GuestLogger + Send + Sync + Copy + 'static,
+) -> wasmtime::Result<()>
+where
+    T: Send + 'static,
+{
+    let mut inst = linker.instance(LOG_INTERFACE)?;
+    inst.func_wrap(
+        "log",
+        move |mut caller: StoreContextMut<'_, T>,
+              (level, target, message, fields): (
+            GuestLogLevel,
+            String,
+            String,
+            Vec<(String, String)>,
+        )| {
+            get(caller.data_mut()).log(level, target, message, fields);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn filters_below_max_level_and_forwards_to_sink() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink_seen = Arc::clone(&seen);
+        let logger = GuestLogger::new("importer", None, LevelFilter::INFO).with_sink(Arc::new(
+            move |record: &GuestLogRecord| sink_seen.lock().unwrap().push(record.to_string()),
+        ));
+
+        logger.log(
+            GuestLogLevel::Debug,
+            "db".into(),
+            "skipped".into(),
+            Vec::new(),
+        );
+        logger.log(
+            GuestLogLevel::Warn,
+            "db".into(),
+            "slow batch".into(),
+            vec![("rows".into(), "500".into())],
+        );
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec!["slow batch rows=500".to_string()]
+        );
+        assert!(!GuestLogger::new("x", None, LevelFilter::OFF).enabled(GuestLogLevel::Error));
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::BTreeSet;
+
+use wasmtime::component::ResourceTable;
+
+use crate::audit::{AuditKind, AuditLog, AuditOutcome};
+
+/// Empty slots probed past the highest occupied one before the counter
+/// assumes the table ends there.
+const TAIL_PROBE: u32 = 16;
+
+/// Counts the live entries (files, directories, streams, sockets) in a
+/// store's WASI [`ResourceTable`].
+///
+/// The table does not expose its size, so the counter remembers which slots
+/// it has seen occupied and rechecks them whenever a host call returns. New
+/// handles either reuse a freed slot below the highest one seen or are
+/// appended right after it, which the tail probe picks up. A handle opened
+/// and closed within the same host call is never seen.
+#[derive(Debug, Clone, Default)]
+pub struct OpenHandles {
+    live: BTreeSet<u32>,
+    opened: Vec<u32>,
+    closed: Vec<u32>,
+    peak: usize,
+    /// One past the highest slot seen occupied.
+    end: u32,
+}
+
+impl OpenHandles {
+    /// Recounts `table` and returns the number of live handles.
+    pub fn update(&mut self, table: &mut ResourceTable) -> usize {
+        let mut live = BTreeSet::new();
+        let mut rep = 0;
+        let mut gap = 0;
+        while rep < self.end || gap < TAIL_PROBE {
+            if table.get_any_mut(rep).is_ok() {
+                live.insert(rep);
+                gap = 0;
+                self.end = self.end.max(rep + 1);
+            } else if rep >= self.end {
+                gap += 1;
+            }
+            rep += 1;
+        }
+        self.opened = live.difference(&self.live).copied().collect();
+        self.closed = self.live.difference(&live).copied().collect();
+        self.live = live;
+        self.peak = self.peak.max(self.live.len());
+        self.live.len()
+    }
+
+    pub fn open(&self) -> usize {
+        self.live.len()
+    }
+
+    /// Highest count seen by [`OpenHandles::update`].
+    pub fn peak(&self) -> usize {
+        self.peak
+    }
+
+    /// Records the handles opened and closed since the previous update as
+    /// [`AuditKind::Fs`] entries targeting `handle:<slot>`.
+    pub fn audit(&self, log: &AuditLog) {
+        for (reps, detail) in [(&self.opened, "opened"), (&self.closed, "closed")] {
+            for rep in reps {
+                log.record(
+                    AuditKind::Fs,
+                    format!("handle:{rep}"),
+                    AuditOutcome::Ok,
+                    Some(detail.to_string()),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_reused_and_appended_slots() {
+        let mut table = ResourceTable::new();
+        let mut handles = OpenHandles::default();
+        assert_eq!(handles.update(&mut table), 0);
+
+        let first = table.push(()).unwrap();
+        let second = table.push(()).unwrap();
+        assert_eq!(handles.update(&mut table), 2);
+
+        table.delete(first).unwrap();
+        assert_eq!(handles.update(&mut table), 1);
+        let _reused = table.push(()).unwrap();
+        let _appended = table.push(()).unwrap();
+        assert_eq!(handles.update(&mut table), 3);
+
+        table.delete(second).unwrap();
+        assert_eq!(handles.update(&mut table), 2);
+        assert_eq!(handles.peak(), 3);
+    }
+
+    #[test]
+    fn opened_and_closed_handles_are_audited() {
+        let mut table = ResourceTable::new();
+        let mut handles = OpenHandles::default();
+        let log = AuditLog::default();
+
+        let file = table.push(()).unwrap();
+        handles.update(&mut table);
+        handles.audit(&log);
+        table.delete(file).unwrap();
+        handles.update(&mut table);
+        handles.audit(&log);
+        handles.update(&mut table);
+        handles.audit(&log);
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|entry| entry.kind == AuditKind::Fs));
+        assert_eq!(entries[0].detail.as_deref(), Some("opened"));
+        assert_eq!(entries[1].detail.as_deref(), Some("closed"));
+    }
+}
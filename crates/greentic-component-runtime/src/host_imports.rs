@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::sync::{Arc, Mutex};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, OnceLock};
 
 use greentic_interfaces::runner_host_v1::{self, RunnerHost};
 use greentic_interfaces_host::component::v0_6::exports::greentic::component::node;
@@ -13,14 +14,28 @@ use greentic_types::cbor::canonical;
 use reqwest::blocking::Client as HttpClient;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde_json::Value;
+use tracing::level_filters::LevelFilter;
 use wasmtime::StoreContextMut;
-use wasmtime::component::{Linker, ResourceTable};
-use wasmtime::{Engine, Result as WasmtimeResult};
+use wasmtime::component::types::ComponentItem;
+use wasmtime::component::{Component as WasmComponent, Linker, ResourceTable};
+use wasmtime::{Engine, ResourceLimiter, Result as WasmtimeResult};
+use wasmtime_wasi::sockets::SocketAddrUse;
 use wasmtime_wasi::{WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView, p2};
 
+use crate::audit::{AuditKind, AuditOutcome};
+use crate::blob::{BlobHost, add_blob_to_linker};
 use crate::error::CompError;
+use crate::events::{EventsHost, add_events_to_linker};
+use crate::guest_log::{GuestLogger, add_log_to_linker};
+use crate::handles::OpenHandles;
 use crate::loader::ComponentRef;
+use crate::messaging::{MessagingHost, add_messaging_to_linker};
 use crate::policy::HostPolicy;
+use crate::progress::{ProgressReporter, add_progress_to_linker};
+use crate::sql::{SqlHost, add_sql_to_linker};
+use crate::telemetry::{TelemetryHost, add_telemetry_to_linker};
+use crate::tools::{ToolsHost, add_tools_to_linker, add_tools_to_linker_async};
+use crate::yielding::{YieldNow, offload};
 
 pub struct HostState {
     _tenant: Option<TenantCtx>,
@@ -30,15 +45,31 @@ pub struct HostState {
     wasi_table: ResourceTable,
     policy: HostPolicy,
     state_store: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    /// Pending changes under [`HostPolicy::transactional_state`]; `None`
+    /// values are deletes.
+    state_txn: Option<HashMap<String, Option<Vec<u8>>>>,
     runner: RunnerHostImpl,
     control: ControlHostImpl,
+    progress: ProgressReporter,
+    guest_log: GuestLogger,
+    blobs: BlobHost,
+    sql: SqlHost,
+    messaging: MessagingHost,
+    events: EventsHost,
+    tools: ToolsHost,
+    telemetry: TelemetryHost,
+    handles: OpenHandles,
+    peak_memory_bytes: usize,
+    table_growth: u64,
+    host_call_count: u64,
 }
 
 impl HostState {
     pub fn empty(policy: HostPolicy) -> Self {
-        let (wasi_ctx, wasi_table) = build_wasi_state();
+        let (wasi_ctx, wasi_table) = build_wasi_state(&policy);
         let runner_policy = policy.clone();
         let state_store = policy.state_store.clone();
+        let state_txn = policy.transactional_state.then(HashMap::new);
         Self {
             _tenant: None,
             _config: Value::Null,
@@ -46,9 +77,22 @@ impl HostState {
             wasi_ctx,
             wasi_table,
             state_store,
+            state_txn,
             policy,
             runner: RunnerHostImpl::new(runner_policy),
             control: ControlHostImpl,
+            progress: ProgressReporter::default(),
+            guest_log: GuestLogger::new("", None, LevelFilter::OFF),
+            blobs: BlobHost::denied(),
+            sql: SqlHost::default(),
+            messaging: MessagingHost::default(),
+            events: EventsHost::default(),
+            tools: ToolsHost::default(),
+            telemetry: TelemetryHost::default(),
+            handles: OpenHandles::default(),
+            peak_memory_bytes: 0,
+            table_growth: 0,
+            host_call_count: 0,
         }
     }
 
@@ -58,9 +102,10 @@ impl HostState {
         secrets: HashMap<String, Vec<u8>>,
         policy: HostPolicy,
     ) -> Self {
-        let (wasi_ctx, wasi_table) = build_wasi_state();
+        let (wasi_ctx, wasi_table) = build_wasi_state(&policy);
         let runner_policy = policy.clone();
         let state_store = policy.state_store.clone();
+        let state_txn = policy.transactional_state.then(HashMap::new);
         Self {
             _tenant: Some(tenant),
             _config: config,
@@ -68,35 +113,204 @@ impl HostState {
             wasi_ctx,
             wasi_table,
             state_store,
+            state_txn,
             policy,
             runner: RunnerHostImpl::new(runner_policy),
             control: ControlHostImpl,
+            progress: ProgressReporter::default(),
+            guest_log: GuestLogger::new("", None, LevelFilter::OFF),
+            blobs: BlobHost::denied(),
+            sql: SqlHost::default(),
+            messaging: MessagingHost::default(),
+            events: EventsHost::default(),
+            tools: ToolsHost::default(),
+            telemetry: TelemetryHost::default(),
+            handles: OpenHandles::default(),
+            peak_memory_bytes: 0,
+            table_growth: 0,
+            host_call_count: 0,
         }
     }
 }
 
-fn build_wasi_state() -> (WasiCtx, ResourceTable) {
+impl HostState {
+    pub(crate) fn with_progress(mut self, progress: ProgressReporter) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub(crate) fn with_guest_log(mut self, guest_log: GuestLogger) -> Self {
+        self.guest_log = guest_log;
+        self
+    }
+
+    pub(crate) fn with_blobs(mut self, blobs: BlobHost) -> Self {
+        self.blobs = blobs;
+        self
+    }
+
+    pub(crate) fn with_sql(mut self, sql: SqlHost) -> Self {
+        self.sql = sql;
+        self
+    }
+
+    pub(crate) fn with_messaging(mut self, messaging: MessagingHost) -> Self {
+        self.messaging = messaging;
+        self
+    }
+
+    pub(crate) fn with_events(mut self, events: EventsHost) -> Self {
+        self.events = events;
+        self
+    }
+
+    pub(crate) fn with_tools(mut self, tools: ToolsHost) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    pub(crate) fn with_telemetry(mut self, telemetry: TelemetryHost) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Enforces [`HostPolicy::max_open_handles`] against the live entries in
+    /// the WASI resource table; invoked whenever a host call returns.
+    pub(crate) fn check_open_handles(&mut self) -> Result<(), CompError> {
+        let open = self.handles.update(&mut self.wasi_table);
+        if let Some(audit) = &self.policy.audit {
+            self.handles.audit(audit);
+        }
+        match self.policy.max_open_handles {
+            Some(max) if open > max => Err(CompError::OpenHandleLimit { open, max }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Applies the state changes buffered in transactional mode.
+    pub(crate) fn commit_state(&mut self) {
+        let Some(pending) = self.state_txn.take() else {
+            return;
+        };
+        let mut guard = self.state_store.lock().expect("state store mutex poisoned");
+        for (key, value) in pending {
+            match value {
+                Some(bytes) => guard.insert(key, bytes),
+                None => guard.remove(&key),
+            };
+        }
+    }
+
+    pub(crate) fn peak_open_handles(&self) -> usize {
+        self.handles.peak()
+    }
+
+    pub(crate) fn peak_memory_bytes(&self) -> usize {
+        self.peak_memory_bytes
+    }
+
+    pub(crate) fn table_growth(&self) -> u64 {
+        self.table_growth
+    }
+
+    pub(crate) fn count_host_call(&mut self) {
+        self.host_call_count += 1;
+    }
+
+    pub(crate) fn host_call_count(&self) -> u64 {
+        self.host_call_count
+    }
+}
+
+/// Never denies growth; only records the high-water mark for metrics.
+impl ResourceLimiter for HostState {
+    fn memory_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> WasmtimeResult<bool> {
+        self.peak_memory_bytes = self.peak_memory_bytes.max(current).max(desired);
+        Ok(true)
+    }
+
+    fn table_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> WasmtimeResult<bool> {
+        self.table_growth += desired.saturating_sub(current) as u64;
+        Ok(true)
+    }
+}
+
+fn build_wasi_state(policy: &HostPolicy) -> (WasiCtx, ResourceTable) {
     let mut wasi_builder = WasiCtxBuilder::new();
+    for (key, value) in policy.guest_env() {
+        wasi_builder.env(key, value);
+    }
+    match policy.net.as_ref().filter(|net| net.is_enabled()) {
+        Some(net) => {
+            wasi_builder.allow_tcp(net.tcp);
+            wasi_builder.allow_udp(net.udp);
+            wasi_builder.allow_ip_name_lookup(true);
+            let allowed = Arc::new(net.resolve_hosts());
+            let policy = policy.clone();
+            wasi_builder.socket_addr_check(move |addr: SocketAddr, addr_use: SocketAddrUse| {
+                // Listening is never granted; UDP needs a local bind to send.
+                let permitted = match addr_use {
+                    SocketAddrUse::TcpBind => false,
+                    SocketAddrUse::UdpBind => true,
+                    SocketAddrUse::TcpConnect
+                    | SocketAddrUse::UdpConnect
+                    | SocketAddrUse::UdpOutgoingDatagram => allowed.contains(&addr.ip()),
+                };
+                if !permitted {
+                    tracing::warn!(%addr, ?addr_use, "socket denied by net policy");
+                }
+                policy.audit(
+                    AuditKind::Net,
+                    addr.to_string(),
+                    if permitted {
+                        AuditOutcome::Ok
+                    } else {
+                        AuditOutcome::Denied
+                    },
+                    Some(format!("{addr_use:?}")),
+                );
+                Box::pin(async move { permitted })
+            });
+        }
+        None => {
+            wasi_builder.allow_tcp(false);
+            wasi_builder.allow_udp(false);
+        }
+    }
     (wasi_builder.build(), ResourceTable::new())
 }
 
+/// The client behind `http-request`, built on first use. A blocking client
+/// may be neither built nor dropped on an async executor thread, so it is
+/// created inside the call, which async invocations run off the fiber, and
+/// never dropped.
+fn http_client() -> &'static HttpClient {
+    static CLIENT: OnceLock<HttpClient> = OnceLock::new();
+    CLIENT.get_or_init(HttpClient::new)
+}
+
+#[derive(Clone)]
 struct RunnerHostImpl {
     policy: HostPolicy,
-    http_client: HttpClient,
 }
 
 impl RunnerHostImpl {
     fn new(policy: HostPolicy) -> Self {
-        Self {
-            policy,
-            http_client: HttpClient::new(),
-        }
+        Self { policy }
     }
-}
 
-impl RunnerHost for RunnerHostImpl {
-    fn http_request(
-        &mut self,
+    fn send_http(
+        &self,
         method: String,
         url: String,
         headers: Vec<String>,
@@ -111,8 +325,19 @@ impl RunnerHost for RunnerHostImpl {
         let url = url
             .parse::<reqwest::Url>()
             .map_err(|err| CompError::Runtime(err.to_string()))?;
+        let domains = &self.policy.http_domains;
+        if !domains.is_empty()
+            && !url
+                .host_str()
+                .is_some_and(|host| domains.iter().any(|domain| domain == host))
+        {
+            return Ok(Err(format!(
+                "http host `{}` is not in capabilities.host.http.domains",
+                url.host_str().unwrap_or_default()
+            )));
+        }
 
-        let mut builder = self.http_client.request(method, url);
+        let mut builder = http_client().request(method, url);
 
         if !headers.is_empty() {
             let mut header_map = HeaderMap::new();
@@ -142,39 +367,90 @@ impl RunnerHost for RunnerHostImpl {
         Ok(Ok(bytes.to_vec()))
     }
 
-    fn kv_get(&mut self, _ns: String, _key: String) -> WasmtimeResult<Option<String>> {
-        // Legacy runner-host surface; routed to the state store for compatibility.
-        let key = format!("{_ns}:{_key}");
+    /// [`send_http`](Self::send_http), recorded as an [`AuditKind::Http`]
+    /// entry.
+    fn fetch(
+        &self,
+        method: String,
+        url: String,
+        headers: Vec<String>,
+        body: Option<Vec<u8>>,
+    ) -> WasmtimeResult<Result<Vec<u8>, String>> {
+        let target = format!("{method} {url}");
+        let result = self.send_http(method, url, headers, body);
+        let (outcome, detail) = match &result {
+            Ok(Ok(_)) => (AuditOutcome::Ok, None),
+            Ok(Err(_)) => (AuditOutcome::Denied, None),
+            Err(err) => (AuditOutcome::Error, Some(err.to_string())),
+        };
+        self.policy.audit(AuditKind::Http, target, outcome, detail);
+        result
+    }
+}
+
+/// The legacy runner-host surface. `http-request` goes through
+/// [`RunnerHostImpl`]; the kv calls share the state store, and the
+/// transaction buffer, with `state-store`.
+impl RunnerHost for HostState {
+    fn http_request(
+        &mut self,
+        method: String,
+        url: String,
+        headers: Vec<String>,
+        body: Option<Vec<u8>>,
+    ) -> WasmtimeResult<Result<Vec<u8>, String>> {
+        self.runner.fetch(method, url, headers, body)
+    }
+
+    fn kv_get(&mut self, ns: String, key: String) -> WasmtimeResult<Option<String>> {
+        let key = format!("{ns}:{key}");
         if !self.policy.allow_state_read {
+            self.policy
+                .audit(AuditKind::StateRead, key, AuditOutcome::Denied, None);
             return Ok(None);
         }
-        let guard = self
-            .policy
-            .state_store
-            .lock()
-            .expect("state store mutex poisoned");
-        match guard.get(&key) {
+        let value = match self.state_txn.as_ref().and_then(|txn| txn.get(&key)) {
+            Some(pending) => pending.clone(),
+            None => self
+                .state_store
+                .lock()
+                .expect("state store mutex poisoned")
+                .get(&key)
+                .cloned(),
+        };
+        let outcome = if value.is_some() {
+            AuditOutcome::Ok
+        } else {
+            AuditOutcome::Miss
+        };
+        self.policy.audit(AuditKind::StateRead, key, outcome, None);
+        match value {
             Some(bytes) => Ok(Some(
-                String::from_utf8(bytes.clone())
-                    .map_err(|err| CompError::Runtime(err.to_string()))?,
+                String::from_utf8(bytes).map_err(|err| CompError::Runtime(err.to_string()))?,
             )),
             None => Ok(None),
         }
     }
 
-    fn kv_put(&mut self, _ns: String, _key: String, _val: String) -> WasmtimeResult<()> {
-        // Legacy runner-host surface; routed to the state store for compatibility.
+    fn kv_put(&mut self, ns: String, key: String, val: String) -> WasmtimeResult<()> {
+        let key = format!("{ns}:{key}");
         if !self.policy.allow_state_write {
+            self.policy
+                .audit(AuditKind::StateWrite, key, AuditOutcome::Denied, None);
             return Ok(());
         }
-        let key = format!("{_ns}:{_key}");
-        let mut guard = self
-            .policy
-            .state_store
-            .lock()
-            .expect("state store mutex poisoned");
-        let bytes = _val.into_bytes();
-        guard.insert(key, canonicalize_cbor_or_passthrough(&bytes));
+        let bytes = canonicalize_cbor_or_passthrough(val.as_bytes());
+        match &mut self.state_txn {
+            Some(txn) => {
+                txn.insert(key.clone(), Some(bytes));
+            }
+            None => {
+                let mut guard = self.state_store.lock().expect("state store mutex poisoned");
+                guard.insert(key.clone(), bytes);
+            }
+        }
+        self.policy
+            .audit(AuditKind::StateWrite, key, AuditOutcome::Ok, None);
         Ok(())
     }
 }
@@ -186,18 +462,103 @@ impl ControlHost for ControlHostImpl {
         false
     }
 
-    fn yield_now(&mut self) {}
+    /// Blocking invocations cannot suspend the guest, so this only gives
+    /// up the OS thread's time slice.
+    fn yield_now(&mut self) {
+        std::thread::yield_now();
+    }
 }
 
 pub fn build_linker(engine: &Engine, _policy: &HostPolicy) -> Result<Linker<HostState>, CompError> {
     let mut linker = Linker::<HostState>::new(engine);
-    runner_host_v1::add_to_linker(&mut linker, |state: &mut HostState| &mut state.runner)?;
+    runner_host_v1::add_to_linker(&mut linker, |state: &mut HostState| state)?;
     add_control_to_linker_v0_6(&mut linker, |state: &mut HostState| &mut state.control)?;
     add_state_store_to_linker(&mut linker, |state: &mut HostState| state)?;
+    add_progress_to_linker(&mut linker, |state: &mut HostState| &mut state.progress)?;
+    add_log_to_linker(&mut linker, |state: &mut HostState| &mut state.guest_log)?;
+    add_blob_to_linker(&mut linker, |state: &mut HostState| &mut state.blobs)?;
+    add_sql_to_linker(&mut linker, |state: &mut HostState| &mut state.sql)?;
+    add_messaging_to_linker(&mut linker, |state: &mut HostState| &mut state.messaging)?;
+    add_events_to_linker(&mut linker, |state: &mut HostState| &mut state.events)?;
+    add_tools_to_linker(&mut linker, |state: &mut HostState| &mut state.tools)?;
+    add_telemetry_to_linker(&mut linker, |state: &mut HostState| &mut state.telemetry)?;
     p2::add_to_linker_sync(&mut linker)?;
     Ok(linker)
 }
 
+/// Linker for engines with async support, used for components loaded with
+/// [`LoadPolicy::with_async_yield`](crate::LoadPolicy::with_async_yield).
+/// `control.yield-now` suspends the fiber, and the host calls that block
+/// (runner-host `http-request`, `tools.call-tool`) run off it through
+/// [`offload`], so they do not stall the executor thread.
+pub(crate) fn build_async_linker(
+    engine: &Engine,
+    component: &WasmComponent,
+) -> Result<Linker<HostState>, CompError> {
+    let mut linker = Linker::<HostState>::new(engine);
+    runner_host_v1::add_to_linker(&mut linker, |state: &mut HostState| state)?;
+    // The generated runner-host bindings are sync only, so `http-request`
+    // is redefined on whichever imported interface carries it.
+    linker.allow_shadowing(true);
+    for interface in imports_exporting(engine, component, "http-request") {
+        linker.instance(&interface)?.func_wrap_async(
+            "http-request",
+            |caller: StoreContextMut<'_, HostState>,
+             (method, url, headers, body): (String, String, Vec<String>, Option<Vec<u8>>)| {
+                let runner = caller.data().runner.clone();
+                Box::new(async move {
+                    offload(move || runner.fetch(method, url, headers, body))
+                        .await
+                        .map(|result| (result,))
+                })
+            },
+        )?;
+    }
+    linker.allow_shadowing(false);
+    let mut control = linker.instance("greentic:component/control@0.6.0")?;
+    control.func_wrap(
+        "should-cancel",
+        |mut caller: StoreContextMut<'_, HostState>, (): ()| {
+            Ok((caller.data_mut().control.should_cancel(),))
+        },
+    )?;
+    control.func_wrap_async(
+        "yield-now",
+        |_caller: StoreContextMut<'_, HostState>, (): ()| {
+            Box::new(async {
+                YieldNow::default().await;
+                Ok(())
+            })
+        },
+    )?;
+    add_state_store_to_linker(&mut linker, |state: &mut HostState| state)?;
+    add_progress_to_linker(&mut linker, |state: &mut HostState| &mut state.progress)?;
+    add_log_to_linker(&mut linker, |state: &mut HostState| &mut state.guest_log)?;
+    add_blob_to_linker(&mut linker, |state: &mut HostState| &mut state.blobs)?;
+    add_sql_to_linker(&mut linker, |state: &mut HostState| &mut state.sql)?;
+    add_messaging_to_linker(&mut linker, |state: &mut HostState| &mut state.messaging)?;
+    add_events_to_linker(&mut linker, |state: &mut HostState| &mut state.events)?;
+    add_tools_to_linker_async(&mut linker, |state: &mut HostState| &mut state.tools)?;
+    add_telemetry_to_linker(&mut linker, |state: &mut HostState| &mut state.telemetry)?;
+    p2::add_to_linker_async(&mut linker)?;
+    Ok(linker)
+}
+
+/// Names of the instances `component` imports that export `func`.
+fn imports_exporting(engine: &Engine, component: &WasmComponent, func: &str) -> Vec<String> {
+    component
+        .component_type()
+        .imports(engine)
+        .filter_map(|(name, item)| match item {
+            ComponentItem::ComponentInstance(instance) => instance
+                .exports(engine)
+                .any(|(export, _)| export == func)
+                .then(|| name.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
 fn add_control_to_linker_v0_6<T>(
     linker: &mut Linker<T>,
     get_host: impl Fn(&mut T) -> &mut (dyn ControlHost + Send + Sync + 'static)
@@ -248,14 +609,31 @@ impl StateStoreHost for HostState {
         _ctx: Option<WitTenantCtx>,
     ) -> Result<Vec<u8>, StateStoreError> {
         if !self.policy.allow_state_read {
+            self.policy
+                .audit(AuditKind::StateRead, key, AuditOutcome::Denied, None);
             return Err(StateStoreError {
                 code: "state.read.denied".into(),
                 message: "state store reads are disabled by policy".into(),
             });
         }
-        let guard = self.state_store.lock().expect("state store mutex poisoned");
-        match guard.get(&key) {
-            Some(bytes) => Ok(bytes.clone()),
+        let value = match self.state_txn.as_ref().and_then(|txn| txn.get(&key)) {
+            Some(pending) => pending.clone(),
+            None => self
+                .state_store
+                .lock()
+                .expect("state store mutex poisoned")
+                .get(&key)
+                .cloned(),
+        };
+        let outcome = if value.is_some() {
+            AuditOutcome::Ok
+        } else {
+            AuditOutcome::Miss
+        };
+        self.policy
+            .audit(AuditKind::StateRead, key.clone(), outcome, None);
+        match value {
+            Some(bytes) => Ok(bytes),
             None => Err(StateStoreError {
                 code: "state.read.miss".into(),
                 message: format!("state key `{key}` not found"),
@@ -270,13 +648,25 @@ impl StateStoreHost for HostState {
         _ctx: Option<WitTenantCtx>,
     ) -> Result<OpAck, StateStoreError> {
         if !self.policy.allow_state_write {
+            self.policy
+                .audit(AuditKind::StateWrite, key, AuditOutcome::Denied, None);
             return Err(StateStoreError {
                 code: "state.write.denied".into(),
                 message: "state store writes are disabled by policy".into(),
             });
         }
-        let mut guard = self.state_store.lock().expect("state store mutex poisoned");
-        guard.insert(key, canonicalize_cbor_or_passthrough(&bytes));
+        let bytes = canonicalize_cbor_or_passthrough(&bytes);
+        match &mut self.state_txn {
+            Some(txn) => {
+                txn.insert(key.clone(), Some(bytes));
+            }
+            None => {
+                let mut guard = self.state_store.lock().expect("state store mutex poisoned");
+                guard.insert(key.clone(), bytes);
+            }
+        }
+        self.policy
+            .audit(AuditKind::StateWrite, key, AuditOutcome::Ok, None);
         Ok(OpAck::Ok)
     }
 
@@ -286,13 +676,24 @@ impl StateStoreHost for HostState {
         _ctx: Option<WitTenantCtx>,
     ) -> Result<OpAck, StateStoreError> {
         if !self.policy.allow_state_delete {
+            self.policy
+                .audit(AuditKind::StateDelete, key, AuditOutcome::Denied, None);
             return Err(StateStoreError {
                 code: "state.delete.denied".into(),
                 message: "state store deletes are disabled by policy".into(),
             });
         }
-        let mut guard = self.state_store.lock().expect("state store mutex poisoned");
-        guard.remove(&key);
+        match &mut self.state_txn {
+            Some(txn) => {
+                txn.insert(key.clone(), None);
+            }
+            None => {
+                let mut guard = self.state_store.lock().expect("state store mutex poisoned");
+                guard.remove(&key);
+            }
+        }
+        self.policy
+            .audit(AuditKind::StateDelete, key, AuditOutcome::Ok, None);
         Ok(OpAck::Ok)
     }
 }
@@ -354,6 +755,7 @@ pub fn make_component_tenant_ctx(tenant: &TenantCtx) -> node::TenantCtx {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audit::AuditLog;
     use std::io::{ErrorKind, Read, Write};
     use std::net::TcpListener;
     use std::thread;
@@ -393,14 +795,27 @@ mod tests {
             allow_state_write,
             allow_state_delete,
             state_store: state_store.clone(),
+            ..HostPolicy::default()
         };
         HostState::empty(policy)
     }
+    #[test]
+    fn limiter_tracks_peak_memory_without_denying() {
+        let mut host = host_state(false, false, false, false);
+        assert!(ResourceLimiter::memory_growing(&mut host, 65_536, 131_072, None).unwrap());
+        assert!(ResourceLimiter::memory_growing(&mut host, 0, 65_536, None).unwrap());
+        assert_eq!(host.peak_memory_bytes(), 131_072);
+
+        assert!(ResourceLimiter::table_growing(&mut host, 0, 4, None).unwrap());
+        assert!(ResourceLimiter::table_growing(&mut host, 4, 10, None).unwrap());
+        assert_eq!(host.table_growth(), 10);
+    }
+
     #[test]
     fn http_fetch_denied_by_policy() {
         let mut host = host_state(false, false, false, false);
         let result = RunnerHost::http_request(
-            &mut host.runner,
+            &mut host,
             "GET".into(),
             "http://localhost".into(),
             vec![],
@@ -420,7 +835,7 @@ mod tests {
             Err(err) => panic!("bind http listener: {err}"),
         };
         let mut host = host_state(true, false, false, false);
-        let response = RunnerHost::http_request(&mut host.runner, "GET".into(), url, vec![], None)
+        let response = RunnerHost::http_request(&mut host, "GET".into(), url, vec![], None)
             .expect("http fetch");
         let body = response.expect("http ok");
         assert_eq!(body, b"hello");
@@ -449,6 +864,78 @@ mod tests {
         assert!(matches!(missing, Err(err) if err.code == "state.read.miss"));
     }
 
+    #[test]
+    fn transactional_state_waits_for_commit() {
+        let mut host = host_state(false, true, true, true);
+        host.state_txn = Some(HashMap::new());
+        StateStoreHost::write(&mut host, "demo".into(), b"data".to_vec(), None).unwrap();
+        let read = StateStoreHost::read(&mut host, "demo".into(), None).expect("state read");
+        assert_eq!(read, b"data");
+        assert!(host.state_store.lock().unwrap().is_empty());
+
+        host.commit_state();
+        assert_eq!(
+            host.state_store.lock().unwrap().get("demo"),
+            Some(&b"data".to_vec())
+        );
+    }
+
+    #[test]
+    fn runner_kv_put_is_discarded_when_the_invocation_fails() {
+        let mut host = host_state(false, true, true, false);
+        host.state_txn = Some(HashMap::new());
+        host.state_store
+            .lock()
+            .unwrap()
+            .insert("ns:demo".into(), b"before".to_vec());
+
+        RunnerHost::kv_put(&mut host, "ns".into(), "demo".into(), "after".into()).unwrap();
+        let read = RunnerHost::kv_get(&mut host, "ns".into(), "demo".into()).unwrap();
+        assert_eq!(read.as_deref(), Some("after"));
+
+        // A trap or component error drops the store without committing.
+        let store = host.state_store.clone();
+        drop(host);
+        assert_eq!(
+            store.lock().unwrap().get("ns:demo"),
+            Some(&b"before".to_vec())
+        );
+    }
+
+    #[test]
+    fn state_calls_are_audited() {
+        let mut host = host_state(false, true, false, false);
+        let audit = AuditLog::default();
+        host.policy.audit = Some(audit.clone());
+
+        let _ = StateStoreHost::read(&mut host, "demo".into(), None);
+        let _ = StateStoreHost::write(&mut host, "demo".into(), b"data".to_vec(), None);
+
+        let entries = audit.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, AuditKind::StateRead);
+        assert_eq!(entries[0].outcome, AuditOutcome::Miss);
+        assert_eq!(entries[1].kind, AuditKind::StateWrite);
+        assert_eq!(entries[1].outcome, AuditOutcome::Denied);
+        assert_eq!(entries[1].target, "demo");
+    }
+
+    #[test]
+    fn open_handles_are_capped_by_policy() {
+        let mut host = host_state(false, false, false, false);
+        host.policy.max_open_handles = Some(1);
+        host.wasi_table.push(()).expect("push handle");
+        assert!(host.check_open_handles().is_ok());
+
+        host.wasi_table.push(()).expect("push handle");
+        let err = host.check_open_handles().expect_err("limit exceeded");
+        assert!(matches!(
+            err,
+            CompError::OpenHandleLimit { open: 2, max: 1 }
+        ));
+        assert_eq!(host.peak_open_handles(), 2);
+    }
+
     #[test]
     fn state_store_write_canonicalizes_cbor_payload() {
         let mut host = host_state(false, true, true, false);
@@ -1,12 +1,64 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use greentic_interfaces_host::component::v0_6::exports::greentic::component::node;
 use greentic_types::TenantCtx;
 use greentic_types::cbor::canonical;
 use serde_json::Value;
-use wasmtime::Store;
+use wasmtime::{CallHook, Store};
 
 use crate::binder::binding_key;
+use crate::blob::{BLOB_CAPABILITY, BlobHost};
+use crate::cbor::decode_output;
+use crate::drain::InFlightGuard;
 use crate::error::CompError;
+use crate::events::{EVENTS_CAPABILITY, EventsHost};
+use crate::guest_log::GuestLogger;
 use crate::host_imports::{HostState, make_invocation_envelope};
-use crate::loader::ComponentHandle;
+use crate::loader::{Compiled, ComponentHandle};
+use crate::messaging::{MESSAGING_CAPABILITY, MessagingHost};
+use crate::metrics::metric;
+use crate::policy::{PolicyAction, PolicyRequest};
+use crate::progress::{PROGRESS_CAPABILITY, ProgressCallback, ProgressReporter};
+use crate::sql::{SQL_CAPABILITY, SqlHost};
+use crate::telemetry::{TELEMETRY_CAPABILITY, TelemetryHost};
+use crate::tools::{TOOLS_CAPABILITY, ToolsHost};
+use crate::yielding::yield_every_tick;
+
+pub(crate) const NODE_INTERFACE: &str = "greentic:component/node@0.6.0";
+
+/// Resources one invocation used, from instantiation until the guest
+/// returned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InvokeUsage {
+    /// Linear memory high-water mark in bytes.
+    pub peak_memory_bytes: usize,
+    /// Table elements allocated, initial table sizes included.
+    pub table_growth: u64,
+    /// `None` unless the engine meters fuel.
+    pub fuel_consumed: Option<u64>,
+    /// Calls from the guest into host imports.
+    pub host_call_count: u64,
+    pub peak_open_handles: usize,
+}
+
+/// The output of [`invoke_with_usage`] and what producing it cost.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    pub output: Value,
+    pub usage: InvokeUsage,
+}
+
+/// Usage of the guest run, if it got as far as instantiation.
+type Usage = Option<InvokeUsage>;
+
+/// An invocation that passed policy, throttling and binding checks.
+struct Admitted<'a> {
+    _in_flight: InFlightGuard<'a>,
+    compiled: Arc<Compiled>,
+    host_state: HostState,
+    envelope: node::InvocationEnvelope,
+}
 
 pub fn invoke(
     handle: &ComponentHandle,
@@ -14,47 +66,433 @@ pub fn invoke(
     input_json: &Value,
     tenant: &TenantCtx,
 ) -> Result<Value, CompError> {
+    invoke_with_usage(handle, operation, input_json, tenant).map(|invocation| invocation.output)
+}
+
+/// Like [`invoke`], also returning the resources the guest used.
+pub fn invoke_with_usage(
+    handle: &ComponentHandle,
+    operation: &str,
+    input_json: &Value,
+    tenant: &TenantCtx,
+) -> Result<Invocation, CompError> {
+    let started = Instant::now();
+    let mut usage = Usage::default();
+    let result = admit(handle, operation, input_json, tenant, None).and_then(|admitted| {
+        if admitted.compiled.yielding {
+            futures::executor::block_on(run_yielding(handle, operation, admitted, &mut usage))
+        } else {
+            run_blocking(handle, operation, admitted, &mut usage)
+        }
+    });
+    record_metrics(handle, operation, &result, started.elapsed(), &usage);
+    result.map(|output| Invocation {
+        output,
+        usage: usage.unwrap_or_default(),
+    })
+}
+
+/// Runs the invocation on a wasmtime fiber that yields to the async executor
+/// every epoch tick and whenever the guest calls `control.yield-now`, so
+/// long-running guests share executor threads instead of pinning one.
+pub async fn invoke_async(
+    handle: &ComponentHandle,
+    operation: &str,
+    input_json: &Value,
+    tenant: &TenantCtx,
+    on_progress: Option<ProgressCallback>,
+) -> Result<Value, CompError> {
+    let started = Instant::now();
+    let mut usage = Usage::default();
+    let result = match admit(handle, operation, input_json, tenant, on_progress) {
+        Ok(admitted) => run_yielding(handle, operation, admitted, &mut usage).await,
+        Err(err) => Err(err),
+    };
+    record_metrics(handle, operation, &result, started.elapsed(), &usage);
+    result
+}
+
+fn admit<'a>(
+    handle: &'a ComponentHandle,
+    operation: &str,
+    input_json: &Value,
+    tenant: &TenantCtx,
+    on_progress: Option<ProgressCallback>,
+) -> Result<Admitted<'a>, CompError> {
     let inner = &handle.inner;
 
-    if !inner
+    let Some(export) = inner
         .info
         .exports
         .iter()
-        .any(|export| export.operation == operation)
-    {
+        .find(|export| export.operation == operation)
+    else {
         return Err(CompError::OperationNotFound(operation.to_string()));
+    };
+    if let Some(deprecated) = &export.deprecated {
+        tracing::warn!(
+            component = %inner.cref.name,
+            "{}",
+            deprecated.notice(operation)
+        );
     }
 
+    inner.host_policy.authorize(&PolicyRequest {
+        action: PolicyAction::Invoke,
+        component: &inner.cref.name,
+        tenant: Some(tenant),
+        operation: Some(operation),
+        capabilities: &inner.info.capabilities,
+    })?;
+
+    let in_flight = inner
+        .in_flight
+        .enter()
+        .ok_or_else(|| CompError::Draining(inner.cref.name.clone()))?;
+    let compiled = inner.compiled()?;
+
     let key = binding_key(tenant);
+    inner
+        .host_policy
+        .throttle(&key, &inner.cref.name, operation)
+        .inspect_err(|err| tracing::warn!(component = %inner.cref.name, "{err}"))?;
     let binding = {
-        let guard = inner.bindings.lock().expect("binding mutex poisoned");
-        guard
-            .get(&key)
-            .cloned()
-            .ok_or_else(|| CompError::BindingNotFound(key.clone()))?
+        let mut guard = inner.bindings.lock().expect("binding mutex poisoned");
+        let binding = guard
+            .get_mut(&key)
+            .ok_or_else(|| CompError::BindingNotFound(key.clone()))?;
+        if let Some(rotation) = &inner.host_policy.secret_rotation {
+            rotation.check(&inner.cref.name, tenant, binding, Instant::now())?;
+        }
+        binding.clone()
     };
 
     let host_state = HostState::from_binding(
         tenant.clone(),
-        binding.config.clone(),
-        binding.secrets.clone(),
+        binding.config,
+        binding.secrets,
         inner.host_policy.clone(),
-    );
-    let mut store = Store::new(&inner.engine, host_state);
-    let instance = inner.instance_pre.instantiate(&mut store)?;
-    let exports = inner.guest_indices.load(&mut store, &instance)?;
-
+    )
+    .with_progress(ProgressReporter::new(
+        declares(handle, PROGRESS_CAPABILITY),
+        on_progress,
+    ))
+    .with_guest_log(GuestLogger::new(
+        inner.cref.name.as_str(),
+        Some(tenant),
+        inner.host_policy.guest_log_level,
+    ))
+    .with_telemetry(TelemetryHost::new(
+        inner.cref.name.as_str(),
+        Some(tenant),
+        (inner.host_policy.allow_telemetry && declares(handle, TELEMETRY_CAPABILITY))
+            .then(|| inner.host_policy.telemetry_scope.clone()),
+    ))
+    .with_blobs(BlobHost::new(
+        declares(handle, BLOB_CAPABILITY),
+        key.clone(),
+        inner.host_policy.blobs.clone(),
+        inner.host_policy.blob_limits,
+    ))
+    .with_tools(ToolsHost::new(
+        tenant.clone(),
+        inner
+            .host_policy
+            .tools
+            .clone()
+            .filter(|_| declares(handle, TOOLS_CAPABILITY)),
+    ))
+    .with_events(EventsHost::new(
+        key.clone(),
+        inner
+            .host_policy
+            .events
+            .clone()
+            .filter(|_| declares(handle, EVENTS_CAPABILITY)),
+    ))
+    .with_messaging(MessagingHost::new(
+        key.clone(),
+        inner.host_policy.messaging.clone(),
+        declares(handle, MESSAGING_CAPABILITY),
+        declares(handle, MESSAGING_CAPABILITY),
+    ))
+    .with_sql(SqlHost::new(
+        key,
+        inner
+            .host_policy
+            .sql
+            .clone()
+            .filter(|_| declares(handle, SQL_CAPABILITY)),
+    ));
     let payload_cbor = canonical::to_canonical_cbor_allow_floats(input_json)
         .map_err(|err| CompError::Runtime(format!("encode invoke payload failed: {err}")))?;
     let envelope = make_invocation_envelope(&inner.cref, tenant, operation, payload_cbor);
-    let result = exports.call_invoke(&mut store, operation, &envelope)?;
-
-    match result {
-        Ok(output) => canonical::from_cbor(&output.output_cbor)
-            .map_err(|err| CompError::Runtime(format!("decode invoke output failed: {err}"))),
-        Err(err) => Err(CompError::Runtime(format!(
-            "component error {}: {}",
-            err.code, err.message
-        ))),
+    Ok(Admitted {
+        _in_flight: in_flight,
+        compiled,
+        host_state,
+        envelope,
+    })
+}
+
+fn declares(handle: &ComponentHandle, capability: &str) -> bool {
+    handle
+        .inner
+        .info
+        .capabilities
+        .iter()
+        .any(|declared| declared.0 == capability)
+}
+
+fn new_store(engine: &wasmtime::Engine, host_state: HostState) -> Store<HostState> {
+    let mut store = Store::new(engine, host_state);
+    store.limiter(|state| state);
+    store.call_hook(|mut store, hook| {
+        match hook {
+            CallHook::CallingHost => store.data_mut().count_host_call(),
+            CallHook::ReturningFromHost => store.data_mut().check_open_handles()?,
+            _ => {}
+        }
+        Ok(())
+    });
+    store
+}
+
+fn store_usage(store: &Store<HostState>, fuel_before: Option<u64>) -> InvokeUsage {
+    let state = store.data();
+    InvokeUsage {
+        peak_memory_bytes: state.peak_memory_bytes(),
+        table_growth: state.table_growth(),
+        fuel_consumed: fuel_before
+            .zip(store.get_fuel().ok())
+            .map(|(before, after)| before.saturating_sub(after)),
+        host_call_count: state.host_call_count(),
+        peak_open_handles: state.peak_open_handles(),
+    }
+}
+
+fn run_blocking(
+    handle: &ComponentHandle,
+    operation: &str,
+    admitted: Admitted<'_>,
+    usage: &mut Usage,
+) -> Result<Value, CompError> {
+    let compiled = &admitted.compiled;
+    let mut store = new_store(&compiled.engine, admitted.host_state);
+    let _deadline = compiled.ticker.limit(&mut store, compiled.timeout);
+    let surface = |err| surface_error(handle, compiled, err);
+    let instance = compiled
+        .instance_pre
+        .instantiate(&mut store)
+        .map_err(surface)?;
+    let exports = compiled.guest_indices.load(&mut store, &instance)?;
+
+    let fuel_before = store.get_fuel().ok();
+    let result = exports.call_invoke(&mut store, operation, &admitted.envelope);
+    *usage = Some(store_usage(&store, fuel_before));
+    let result = result.map_err(surface)?;
+    finish(handle, operation, &mut store, result)
+}
+
+async fn run_yielding(
+    handle: &ComponentHandle,
+    operation: &str,
+    admitted: Admitted<'_>,
+    usage: &mut Usage,
+) -> Result<Value, CompError> {
+    let compiled = &admitted.compiled;
+    if !compiled.yielding {
+        return Err(CompError::AsyncUnavailable(handle.inner.cref.name.clone()));
+    }
+    let mut store = new_store(&compiled.engine, admitted.host_state);
+    let _ticking = yield_every_tick(&mut store, &compiled.ticker, compiled.timeout);
+    let surface = |err| surface_error(handle, compiled, err);
+
+    let instance = compiled
+        .instance_pre
+        .instantiate_async(&mut store)
+        .await
+        .map_err(surface)?;
+    let node_index = instance
+        .get_export_index(&mut store, None, NODE_INTERFACE)
+        .ok_or_else(|| CompError::Runtime(format!("component does not export {NODE_INTERFACE}")))?;
+    let invoke_index = instance
+        .get_export_index(&mut store, Some(&node_index), "invoke")
+        .ok_or_else(|| CompError::Runtime(format!("{NODE_INTERFACE} has no invoke export")))?;
+    let invoke = instance
+        .get_typed_func::<(&str, &node::InvocationEnvelope), (Result<node::InvocationResult, node::NodeError>,)>(
+            &mut store,
+            invoke_index,
+        )?;
+
+    let fuel_before = store.get_fuel().ok();
+    let result = invoke
+        .call_async(&mut store, (operation, &admitted.envelope))
+        .await;
+    *usage = Some(store_usage(&store, fuel_before));
+    let (result,) = result.map_err(surface)?;
+    invoke.post_return_async(&mut store).await?;
+    finish(handle, operation, &mut store, result)
+}
+
+fn finish(
+    handle: &ComponentHandle,
+    operation: &str,
+    store: &mut Store<HostState>,
+    result: Result<node::InvocationResult, node::NodeError>,
+) -> Result<Value, CompError> {
+    tracing::debug!(
+        component = %handle.inner.cref.name,
+        operation,
+        open_handles = store.data().peak_open_handles(),
+        "invocation finished"
+    );
+
+    let limit = handle.inner.host_policy.max_output_bytes;
+    let output = match result {
+        Ok(output) if output.output_cbor.len() > limit => Err(CompError::OutputTooLarge {
+            component: handle.inner.cref.name.clone(),
+            size: output.output_cbor.len(),
+            limit,
+        }),
+        Ok(output) => decode_output(&output.output_cbor),
+        Err(err) => Err(CompError::Component {
+            code: err.code,
+            message: err.message,
+            retryable: err.retryable,
+            backoff_ms: err.backoff_ms,
+            details: err
+                .details
+                .and_then(|bytes| canonical::from_cbor::<Value>(&bytes).ok()),
+        }),
+    };
+    if output.is_ok() {
+        store.data_mut().commit_state();
+    }
+    output
+}
+
+fn record_metrics(
+    handle: &ComponentHandle,
+    operation: &str,
+    result: &Result<Value, CompError>,
+    elapsed: Duration,
+    usage: &Usage,
+) {
+    let inner = &handle.inner;
+    let metrics = &inner.host_policy.metrics;
+    let component = inner.cref.name.as_str();
+    let labels = [("component", component), ("operation", operation)];
+    let outcome = if result.is_ok() { "ok" } else { "error" };
+    metrics.counter(
+        metric::INVOCATIONS,
+        1,
+        &[
+            ("component", component),
+            ("operation", operation),
+            ("outcome", outcome),
+        ],
+    );
+    metrics.histogram(metric::INVOKE_DURATION, elapsed.as_secs_f64(), &labels);
+    if let Err(err) = result {
+        metrics.counter(
+            metric::ERRORS,
+            1,
+            &[
+                ("component", component),
+                ("operation", operation),
+                ("category", err.category()),
+            ],
+        );
+    }
+    if let Some(usage) = usage {
+        metrics.gauge(metric::MEMORY_PEAK, usage.peak_memory_bytes as f64, &labels);
+        metrics.histogram(metric::HOST_CALLS, usage.host_call_count as f64, &labels);
+        if let Some(fuel) = usage.fuel_consumed {
+            metrics.histogram(metric::FUEL, fuel as f64, &labels);
+        }
+    }
+}
+
+fn is_interrupt(err: &wasmtime::Error) -> bool {
+    err.chain()
+        .find_map(|source| source.downcast_ref::<wasmtime::Trap>())
+        .is_some_and(|trap| matches!(trap, wasmtime::Trap::Interrupt))
+}
+
+/// Reports epoch interrupts as the invocation timeout they stand for.
+fn surface_error(handle: &ComponentHandle, compiled: &Compiled, err: wasmtime::Error) -> CompError {
+    match &compiled.timeout {
+        Some(timeout) if is_interrupt(&err) => CompError::InvokeTimeout {
+            component: handle.inner.cref.name.clone(),
+            timeout_ms: timeout.as_millis().try_into().unwrap_or(u64::MAX),
+        },
+        _ => surface_limit_error(err),
+    }
+}
+
+/// Unwraps a handle-limit failure raised by the call hook so callers get the
+/// typed error rather than a generic wasmtime trap.
+fn surface_limit_error(err: wasmtime::Error) -> CompError {
+    match err.downcast::<CompError>() {
+        Ok(limit @ CompError::OpenHandleLimit { .. }) => {
+            tracing::warn!("{limit}");
+            limit
+        }
+        Ok(other) => other,
+        Err(err) => CompError::Wasmtime(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use greentic_component_store::ComponentStore;
+    use greentic_types::{EnvId, TenantId};
+    use serde_json::json;
+
+    use crate::binder::{Binder, Bindings};
+    use crate::loader::{ComponentRef, Loader};
+    use crate::policy::{HostPolicy, LoadPolicy};
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn invoke_async_runs_on_a_tokio_runtime() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../greentic-component/tests/contract/fixtures/component_v0_6_0");
+        let cache = tempfile::tempdir().expect("cache dir");
+        let policy = LoadPolicy::new(Arc::new(ComponentStore::new(cache.path()).expect("store")))
+            .with_host_policy(HostPolicy {
+                allow_http_fetch: true,
+                ..HostPolicy::default()
+            })
+            .with_async_yield(Duration::from_millis(1));
+        let cref = ComponentRef {
+            name: "fixture".into(),
+            locator: fixture.join("component.wasm").display().to_string(),
+            manifest: None,
+        };
+        let handle = tokio::task::block_in_place(|| Loader.load(&cref, &policy)).expect("load");
+        let tenant = TenantCtx::new(EnvId("dev".into()), TenantId("tenant".into()));
+        Binder
+            .bind(
+                &handle,
+                &tenant,
+                &Bindings::new(json!({}), Vec::new()),
+                &mut |name, _| Err(CompError::SecretNotDeclared(name.to_string())),
+            )
+            .expect("bind");
+
+        let output = invoke_async(
+            &handle,
+            "handle_message",
+            &json!({"input": "hello"}),
+            &tenant,
+            None,
+        )
+        .await;
+        assert!(output.is_ok(), "{output:?}");
     }
 }
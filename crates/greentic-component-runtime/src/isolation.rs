@@ -0,0 +1,236 @@
+use std::fmt;
+use std::str::FromStr;
+
+use wasmtime::{Config, InstanceAllocationStrategy, PoolingAllocationConfig};
+
+use crate::policy::HostPolicy;
+
+/// Named bundle of wasmtime engine settings and host defaults.
+///
+/// `Balanced` matches the runtime's historical behaviour. `Strict` is meant
+/// for untrusted third-party components, `Trusted` for first-party code that
+/// needs every engine feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum IsolationProfile {
+    Strict,
+    #[default]
+    Balanced,
+    Trusted,
+}
+
+/// Engine knobs selected by an [`IsolationProfile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IsolationSettings {
+    /// Use the pooling instance allocator with a fixed instance budget.
+    pub pooling: bool,
+    /// Concurrent component instances per engine when pooling.
+    pub max_instances: u32,
+    /// Maximum wasm stack in bytes.
+    pub max_wasm_stack: usize,
+    /// Canonicalize NaNs so float results are deterministic across hosts.
+    pub nan_canonicalization: bool,
+    pub simd: bool,
+    pub threads: bool,
+    /// Open WASI handle cap applied to the host policy when it has none.
+    pub max_open_handles: Option<usize>,
+}
+
+/// Size of a wasm linear memory page.
+const WASM_PAGE_BYTES: u64 = 64 * 1024;
+
+/// Pooling instance allocator sizing. Slots are reserved up front, so memory
+/// use is bounded and instantiation skips most allocation; instances beyond
+/// the budget, or memories larger than `max_memory_pages`, fail to
+/// instantiate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PoolingConfig {
+    /// Concurrent component instances per engine.
+    pub max_instances: u32,
+    /// Largest linear memory a pooled instance may grow to, in 64 KiB pages.
+    pub max_memory_pages: u64,
+    /// Tables across all pooled instances.
+    pub max_tables: u32,
+}
+
+impl PoolingConfig {
+    /// Pool for `max_instances` instances with 4 GiB memories and four tables
+    /// per instance.
+    pub fn new(max_instances: u32) -> Self {
+        Self {
+            max_instances,
+            max_memory_pages: 65536,
+            max_tables: max_instances.saturating_mul(4),
+        }
+    }
+
+    pub fn with_max_memory_pages(mut self, pages: u64) -> Self {
+        self.max_memory_pages = pages;
+        self
+    }
+
+    pub fn with_max_tables(mut self, tables: u32) -> Self {
+        self.max_tables = tables;
+        self
+    }
+
+    /// Switches `config` to the pooling allocator.
+    pub fn configure(&self, config: &mut Config) {
+        let mut pooling = PoolingAllocationConfig::default();
+        pooling.total_component_instances(self.max_instances);
+        // Size the core pools to the instance budget so the allocator does
+        // not reserve address space for the default thousand instances.
+        pooling.total_core_instances(self.max_instances.saturating_mul(8));
+        pooling.total_memories(self.max_instances.saturating_mul(2));
+        pooling.total_tables(self.max_tables);
+        let max_memory_bytes = self.max_memory_pages.saturating_mul(WASM_PAGE_BYTES);
+        pooling.max_memory_size(usize::try_from(max_memory_bytes).unwrap_or(usize::MAX));
+        config.allocation_strategy(InstanceAllocationStrategy::Pooling(pooling));
+    }
+}
+
+impl IsolationProfile {
+    pub const ALL: [IsolationProfile; 3] = [
+        IsolationProfile::Strict,
+        IsolationProfile::Balanced,
+        IsolationProfile::Trusted,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IsolationProfile::Strict => "strict",
+            IsolationProfile::Balanced => "balanced",
+            IsolationProfile::Trusted => "trusted",
+        }
+    }
+
+    pub fn settings(self) -> IsolationSettings {
+        match self {
+            IsolationProfile::Strict => IsolationSettings {
+                pooling: true,
+                max_instances: 16,
+                max_wasm_stack: 256 * 1024,
+                nan_canonicalization: true,
+                simd: false,
+                threads: false,
+                max_open_handles: Some(32),
+            },
+            IsolationProfile::Balanced => IsolationSettings {
+                pooling: false,
+                max_instances: 1000,
+                max_wasm_stack: 512 * 1024,
+                nan_canonicalization: false,
+                simd: true,
+                threads: false,
+                max_open_handles: None,
+            },
+            IsolationProfile::Trusted => IsolationSettings {
+                pooling: false,
+                max_instances: 1000,
+                max_wasm_stack: 2 * 1024 * 1024,
+                nan_canonicalization: false,
+                simd: true,
+                threads: true,
+                max_open_handles: None,
+            },
+        }
+    }
+
+    /// Applies the profile's engine settings; an explicit `pooling` replaces
+    /// the profile's own pool, and turns pooling on for profiles without one.
+    pub(crate) fn configure(self, config: &mut Config, pooling: Option<PoolingConfig>) {
+        let settings = self.settings();
+        match pooling {
+            Some(pooling) => pooling.configure(config),
+            None if settings.pooling => {
+                PoolingConfig::new(settings.max_instances).configure(config)
+            }
+            None => {}
+        }
+        config.max_wasm_stack(settings.max_wasm_stack);
+        config.cranelift_nan_canonicalization(settings.nan_canonicalization);
+        config.wasm_simd(settings.simd);
+        if !settings.simd {
+            // Relaxed SIMD depends on SIMD and would make engine creation fail.
+            config.wasm_relaxed_simd(false);
+        }
+        config.wasm_threads(settings.threads);
+    }
+
+    /// Applies the profile's host capability defaults. `Strict` revokes
+    /// HTTP, state writes and sockets; `Trusted` grants HTTP and state access.
+    /// Explicit settings such as hooks, audit logs and rate limits are kept.
+    pub fn apply_host_defaults(self, host: &mut HostPolicy) {
+        match self {
+            IsolationProfile::Strict => {
+                host.allow_http_fetch = false;
+                host.allow_state_write = false;
+                host.allow_state_delete = false;
+                host.net = None;
+            }
+            IsolationProfile::Balanced => {}
+            IsolationProfile::Trusted => {
+                host.allow_http_fetch = true;
+                host.allow_state_read = true;
+                host.allow_state_write = true;
+                host.allow_state_delete = true;
+            }
+        }
+        if host.max_open_handles.is_none() {
+            host.max_open_handles = self.settings().max_open_handles;
+        }
+    }
+}
+
+impl fmt::Display for IsolationProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for IsolationProfile {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        IsolationProfile::ALL
+            .into_iter()
+            .find(|profile| profile.name() == value)
+            .ok_or_else(|| {
+                format!(
+                    "unknown isolation profile `{value}` (expected strict, balanced or trusted)"
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_build_engines_and_adjust_host_defaults() {
+        for profile in IsolationProfile::ALL {
+            let mut config = Config::new();
+            config.wasm_component_model(true);
+            profile.configure(&mut config, None);
+            wasmtime::Engine::new(&config).unwrap_or_else(|err| panic!("{profile} engine: {err}"));
+            assert_eq!(profile.name().parse::<IsolationProfile>(), Ok(profile));
+        }
+
+        let mut config = Config::new();
+        config.wasm_component_model(true);
+        let pooling = PoolingConfig::new(4)
+            .with_max_memory_pages(160)
+            .with_max_tables(8);
+        IsolationProfile::Balanced.configure(&mut config, Some(pooling));
+        wasmtime::Engine::new(&config).expect("pooled balanced engine");
+
+        let mut host = HostPolicy {
+            allow_http_fetch: true,
+            ..HostPolicy::default()
+        };
+        IsolationProfile::Strict.apply_host_defaults(&mut host);
+        assert!(!host.allow_http_fetch);
+        assert_eq!(host.max_open_handles, Some(32));
+        assert!("paranoid".parse::<IsolationProfile>().is_err());
+    }
+}
@@ -1,23 +1,124 @@
+mod audit;
 mod binder;
+#[cfg(feature = "persistence")]
+mod binding_store;
+mod blob;
+mod cbor;
+mod drain;
+mod engine;
+mod epoch;
 mod error;
+mod events;
+mod guest_log;
+mod handles;
 mod host_imports;
 mod invoker;
+mod isolation;
 mod loader;
+mod manifest;
+mod messaging;
+mod metrics;
+mod node_error;
+mod opa;
 mod policy;
+mod preload;
+mod progress;
+mod rate_limit;
+mod secret_rotation;
+mod sql;
+mod telemetry;
+mod tools;
+mod yielding;
 
 use greentic_types::TenantCtx;
 use serde_json::Value;
 
+pub use audit::{AuditEntry, AuditKind, AuditLog, AuditOutcome};
 pub use binder::{Binder, Bindings};
+#[cfg(feature = "persistence")]
+pub use binding_store::{HostKey, RestoreReport};
+pub use blob::{
+    BLOB_CAPABILITY, BLOB_INTERFACE, BlobBackend, BlobHost, BlobLimits, DirBlobs, InMemoryBlobs,
+    add_blob_to_linker, blob_scope,
+};
+pub use cbor::{
+    CanonicalCborError, SELF_DESCRIBE_TAG, decode_describe, decode_output, strip_self_describe_tag,
+    validate_canonical_cbor,
+};
+pub use component_manifest::{ComponentDependency, ComponentRole};
+pub use engine::{EngineConfigurer, EngineProvider, EngineStats};
+pub use epoch::{EpochGuard, EpochTicker};
 pub use error::CompError;
+pub use events::{
+    EVENTS_CAPABILITY, EVENTS_INTERFACE, EmittedEvent, EventSink, EventsHost, add_events_to_linker,
+    events_scope,
+};
+pub use greentic_component_store::{
+    CATALOG_FILE, Catalog, CatalogEntry, CatalogRegistry, CatalogSignature,
+};
+pub use guest_log::{
+    GuestLogLevel, GuestLogRecord, GuestLogSink, GuestLogger, LOG_INTERFACE, add_log_to_linker,
+};
+pub use handles::OpenHandles;
+pub use invoker::{Invocation, InvokeUsage};
+pub use isolation::{IsolationProfile, IsolationSettings, PoolingConfig};
 pub use loader::{ComponentHandle, ComponentRef, Loader};
-pub use policy::{HostPolicy, LoadPolicy};
+pub use messaging::{
+    MESSAGING_CAPABILITY, MESSAGING_INTERFACE, MessagingBackend, MessagingHost, OutboundMessage,
+    add_messaging_to_linker, messaging_scope,
+};
+#[cfg(feature = "prometheus")]
+pub use metrics::PrometheusSink;
+pub use metrics::{MetricsSink, NoopMetrics, metric};
+pub use node_error::{ErrorDetails, FieldError, UpstreamError};
+pub use opa::OpaPolicyHook;
+pub use policy::{
+    DEFAULT_MAX_OUTPUT_BYTES, HostPolicy, LoadPolicy, PolicyAction, PolicyDecision, PolicyHook,
+    PolicyRequest,
+};
+pub use preload::{
+    PreloadCallback, PreloadComponent, PreloadOutcome, PreloadPlan, PreloadProgress,
+    PreloadSecretResolver, PreloadStage, PreloadTenant,
+};
+pub use progress::{
+    PROGRESS_CAPABILITY, PROGRESS_INTERFACE, Progress, ProgressCallback, ProgressReporter,
+    add_progress_to_linker, progress_channel,
+};
+pub use rate_limit::{RateLimit, RateLimiter};
+pub use secret_rotation::{SecretAgeHook, SecretRotation, StaleSecrets};
+pub use sql::{
+    SQL_CAPABILITY, SQL_INTERFACE, SqlAccess, SqlBackend, SqlHost, SqlResult, add_sql_to_linker,
+    sql_scope,
+};
+pub use telemetry::{
+    TELEMETRY_CAPABILITY, TELEMETRY_INTERFACE, TelemetryHost, TelemetryRecord, TelemetrySink,
+    add_telemetry_to_linker, attribute_scope,
+};
+pub use tools::{
+    MAX_TOOL_DEPTH, TOOLS_CAPABILITY, TOOLS_INTERFACE, ToolAccess, ToolDirectory, ToolsHost,
+    add_tools_to_linker,
+};
 
 pub fn load(cref: &ComponentRef, policy: &LoadPolicy) -> Result<ComponentHandle, CompError> {
     let loader = Loader;
     loader.load(cref, policy)
 }
 
+pub fn load_dependencies(
+    dependencies: &[ComponentDependency],
+    policy: &LoadPolicy,
+) -> Result<Vec<ComponentHandle>, CompError> {
+    let loader = Loader;
+    loader.load_dependencies(dependencies, policy)
+}
+
+/// Loads and binds every component in `plan` concurrently; see
+/// [`Loader::preload`].
+pub fn preload(plan: &PreloadPlan) -> Vec<PreloadOutcome> {
+    let loader = Loader;
+    loader.preload(plan)
+}
+
 pub fn describe(handle: &ComponentHandle) -> Result<ComponentManifestInfo, CompError> {
     let loader = Loader;
     loader.describe(handle)
@@ -33,6 +134,32 @@ pub fn bind(
     binder.bind(handle, tenant, bindings, secret_resolver)
 }
 
+pub fn rebind_secrets(
+    handle: &ComponentHandle,
+    tenant: &TenantCtx,
+    secret_resolver: &mut dyn FnMut(&str, &TenantCtx) -> Result<String, CompError>,
+) -> Result<(), CompError> {
+    let binder = Binder;
+    binder.rebind_secrets(handle, tenant, secret_resolver)
+}
+
+#[cfg(feature = "persistence")]
+pub fn snapshot_bindings(handle: &ComponentHandle, key: &HostKey) -> Result<Vec<u8>, CompError> {
+    let binder = Binder;
+    binder.snapshot(handle, key)
+}
+
+#[cfg(feature = "persistence")]
+pub fn restore_bindings(
+    handle: &ComponentHandle,
+    sealed: &[u8],
+    key: &HostKey,
+    secret_resolver: &mut dyn FnMut(&str, &TenantCtx) -> Result<String, CompError>,
+) -> Result<RestoreReport, CompError> {
+    let binder = Binder;
+    binder.restore(handle, sealed, key, secret_resolver)
+}
+
 pub fn invoke(
     handle: &ComponentHandle,
     operation: &str,
@@ -42,4 +169,36 @@ pub fn invoke(
     invoker::invoke(handle, operation, input_json, tenant)
 }
 
+/// Like [`invoke`], also returning peak memory, table growth, fuel and
+/// host-call counts for the invocation.
+pub fn invoke_with_usage(
+    handle: &ComponentHandle,
+    operation: &str,
+    input_json: &Value,
+    tenant: &TenantCtx,
+) -> Result<Invocation, CompError> {
+    invoker::invoke_with_usage(handle, operation, input_json, tenant)
+}
+
+pub async fn invoke_async(
+    handle: &ComponentHandle,
+    operation: &str,
+    input_json: &Value,
+    tenant: &TenantCtx,
+) -> Result<Value, CompError> {
+    invoker::invoke_async(handle, operation, input_json, tenant, None).await
+}
+
+/// Like [`invoke_async`], passing every `report-progress` call from the guest
+/// to `on_progress` while the invocation runs.
+pub async fn invoke_async_with_progress(
+    handle: &ComponentHandle,
+    operation: &str,
+    input_json: &Value,
+    tenant: &TenantCtx,
+    on_progress: ProgressCallback,
+) -> Result<Value, CompError> {
+    invoker::invoke_async(handle, operation, input_json, tenant, Some(on_progress)).await
+}
+
 pub type ComponentManifestInfo = component_manifest::ComponentInfo;
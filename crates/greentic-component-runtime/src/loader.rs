@@ -1,27 +1,62 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use component_manifest::{CapabilityRef, CompiledExportSchema, ComponentInfo, WitCompat};
+use component_manifest::{
+    CapabilityRef, CompiledExportSchema, ComponentDependency, ComponentInfo, ComponentRole,
+    WitCompat,
+};
+use greentic_component_store::{StoreError, VerificationPolicy};
 use greentic_interfaces_host::component::v0_6::exports::greentic::component::node::{
     ComponentDescriptor, GuestIndices,
 };
-use greentic_types::cbor::canonical;
 use greentic_types::schemas::component::v0_6_0::ComponentDescribe;
 use jsonschema::{Validator, validator_for};
+use semver::{Version, VersionReq};
 use serde_json::{Map, Value, json};
+use wasmtime::Engine;
 use wasmtime::component::{Component as WasmComponent, Func, InstancePre, Val};
-use wasmtime::{Config, Engine};
 
+use crate::cbor::decode_describe;
+use crate::drain::InFlight;
+use crate::engine::{EngineKey, EngineMode, EngineProvider, SharedEngine};
+use crate::epoch::EpochTicker;
 use crate::error::CompError;
-use crate::host_imports::{HostState, build_linker};
-use crate::policy::LoadPolicy;
-
-const SELF_DESCRIBE_TAG: [u8; 3] = [0xd9, 0xd9, 0xf7];
+use crate::guest_log::GuestLogger;
+use crate::host_imports::{HostState, build_async_linker, build_linker};
+use crate::invoker::NODE_INTERFACE;
+use crate::manifest::{manifest_capabilities, manifest_host_policy, read_manifest};
+use crate::metrics::metric;
+use crate::policy::{LoadPolicy, PolicyAction, PolicyRequest};
+use crate::yielding::yield_every_tick;
 
 #[derive(Debug, Clone)]
 pub struct ComponentRef {
     pub name: String,
     pub locator: String,
+    /// The component's `component.manifest.json`. When set, the loader reads
+    /// it (with the load policy's environment overlay) and applies its
+    /// limits and capabilities.
+    pub manifest: Option<PathBuf>,
+}
+
+impl ComponentRef {
+    /// Refers to a component by its catalog name; the load policy's
+    /// [`Catalog`](greentic_component_store::Catalog) supplies the locator.
+    pub fn named(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            locator: name.clone(),
+            name,
+            manifest: None,
+        }
+    }
+
+    pub fn with_manifest(mut self, path: impl Into<PathBuf>) -> Self {
+        self.manifest = Some(path.into());
+        self
+    }
 }
 
 pub struct Loader;
@@ -38,37 +73,142 @@ impl Loader {
         cref: &ComponentRef,
         policy: &LoadPolicy,
     ) -> Result<ComponentHandle, CompError> {
-        let artifact = policy
-            .store
-            .fetch_from_str(&cref.locator, &policy.verification)?;
-
-        let engine = create_engine()?;
+        let manifest = cref
+            .manifest
+            .as_deref()
+            .map(|path| read_manifest(path, policy.env.as_deref()))
+            .transpose()?;
+        let host = match &manifest {
+            Some(manifest) => manifest_host_policy(&policy.host, policy.isolation, manifest)?,
+            None => policy.host.clone(),
+        };
+        let declared = match &manifest {
+            Some(manifest) => manifest_capabilities(manifest)?,
+            None => Vec::new(),
+        };
+        policy.host.authorize(&PolicyRequest {
+            action: PolicyAction::Load,
+            component: &cref.name,
+            tenant: None,
+            operation: None,
+            capabilities: &declared,
+        })?;
+        let entry = policy
+            .catalog
+            .as_ref()
+            .and_then(|catalog| catalog.lookup(&cref.locator));
+        let artifact = match entry {
+            Some(entry) => {
+                let pinned = entry.verification_policy();
+                let verification = VerificationPolicy {
+                    digest: pinned.digest.or_else(|| policy.verification.digest.clone()),
+                    signature: pinned
+                        .signature
+                        .or_else(|| policy.verification.signature.clone()),
+                    digest_sidecars: policy.verification.digest_sidecars,
+                };
+                policy.store.fetch_from_str(&entry.locator, &verification)?
+            }
+            None => policy
+                .store
+                .fetch_from_str(&cref.locator, &policy.verification)?,
+        };
+        policy.host.metrics.counter(
+            metric::CACHE,
+            1,
+            &[
+                ("component", cref.name.as_str()),
+                ("result", if artifact.from_cache { "hit" } else { "miss" }),
+            ],
+        );
+
+        // Async support is fixed per engine, so a component that may be
+        // invoked asynchronously is compiled for the async engine only and
+        // blocking invocations drive it to completion on the calling thread.
+        let mode = match policy.async_yield {
+            Some(interval) => EngineMode::Yielding { interval },
+            None => EngineMode::Blocking,
+        };
+        let SharedEngine { engine, ticker } = shared_engine(cref, policy, mode)?;
+        let yielding = policy.async_yield.is_some();
+        let timeout = policy.invoke_timeout;
         let component = WasmComponent::from_binary(&engine, &artifact.bytes)?;
 
-        let linker = build_linker(&engine, &policy.host)?;
+        let linker = if yielding {
+            build_async_linker(&engine, &component)?
+        } else {
+            build_linker(&engine, &host)?
+        };
         let instance_pre = linker.instantiate_pre(&component)?;
         let guest_indices = GuestIndices::new(&instance_pre)?;
-        let host_state = HostState::empty(policy.host.clone());
+        let host_state = HostState::empty(host.clone()).with_guest_log(GuestLogger::new(
+            cref.name.as_str(),
+            None,
+            host.guest_log_level,
+        ));
         let mut store = wasmtime::Store::new(&engine, host_state);
-
-        let instance = instance_pre.instantiate(&mut store)?;
-        let guest = guest_indices.load(&mut store, &instance)?;
-        let descriptor = guest.call_describe(&mut store)?;
-        let config_schema_value =
-            load_config_schema_from_describe(&instance, &mut store)?.unwrap_or_else(|| json!({}));
-        let info = component_info_from_descriptor(&descriptor, config_schema_value.clone());
+        let (descriptor, describe) = if yielding {
+            futures::executor::block_on(describe_async(
+                &instance_pre,
+                &mut store,
+                &ticker,
+                timeout,
+            ))?
+        } else {
+            // Other components' invocations advance the shared epoch, so the
+            // describe call needs a deadline of its own, if only an
+            // unreachable one.
+            let _deadline = ticker.limit(&mut store, timeout);
+            let instance = instance_pre.instantiate(&mut store)?;
+            let guest = guest_indices.load(&mut store, &instance)?;
+            let descriptor = guest.call_describe(&mut store)?;
+            let describe = describe_func(&instance, &mut store)?
+                .map(|func| {
+                    call_component_func(&mut store, &func, &[]).and_then(decode_describe_values)
+                })
+                .transpose()?;
+            (descriptor, describe)
+        };
+        if let Some(required) = entry.and_then(|entry| entry.version.as_deref()) {
+            check_catalog_version(&cref.locator, required, &descriptor.version)?;
+        }
+        let declared_role = describe
+            .as_ref()
+            .map(|describe| describe.info.role.trim())
+            .filter(|role| !role.is_empty());
+        policy.admit_role(&cref.name, declared_role)?;
+        let config_schema_value = match &describe {
+            Some(describe) => serde_json::to_value(&describe.config_schema)?,
+            None => json!({}),
+        };
+        let role = declared_role.and_then(|role| role.parse().ok());
+        let info = component_info_from_descriptor(&descriptor, config_schema_value.clone(), role);
+        policy.host.authorize(&PolicyRequest {
+            action: PolicyAction::Load,
+            component: &cref.name,
+            tenant: None,
+            operation: None,
+            capabilities: &info.capabilities,
+        })?;
         let config_schema = validator_for(&config_schema_value)
             .map_err(|err| CompError::SchemaValidation(err.to_string()))?;
-
         Ok(ComponentHandle {
             inner: Arc::new(ComponentInner {
                 cref: cref.clone(),
+                manifest,
+                version: descriptor.version.clone(),
                 info,
                 config_schema: Arc::new(config_schema),
-                engine,
-                instance_pre,
-                guest_indices,
-                host_policy: policy.host.clone(),
+                compiled: Mutex::new(Some(Arc::new(Compiled {
+                    engine,
+                    instance_pre,
+                    guest_indices,
+                    ticker,
+                    timeout,
+                    yielding,
+                }))),
+                in_flight: InFlight::default(),
+                host_policy: host,
                 bindings: Mutex::new(HashMap::new()),
             }),
         })
@@ -77,11 +217,95 @@ impl Loader {
     pub fn describe(&self, handle: &ComponentHandle) -> Result<ComponentInfo, CompError> {
         Ok(handle.inner.info.clone())
     }
+
+    /// Loads every manifest dependency and checks it against the declared
+    /// semver range and the exports the dependent component composes against.
+    pub fn load_dependencies(
+        &self,
+        dependencies: &[ComponentDependency],
+        policy: &LoadPolicy,
+    ) -> Result<Vec<ComponentHandle>, CompError> {
+        dependencies
+            .iter()
+            .map(|dependency| {
+                let locator = policy
+                    .dependency_locators
+                    .get(&dependency.id)
+                    .or(dependency.locator.as_ref())
+                    .or_else(|| {
+                        policy
+                            .catalog
+                            .as_ref()
+                            .filter(|catalog| catalog.get(&dependency.id).is_some())
+                            .map(|_| &dependency.id)
+                    })
+                    .ok_or_else(|| CompError::DependencyUnresolved(dependency.id.clone()))?;
+                let cref = ComponentRef {
+                    name: dependency.id.clone(),
+                    locator: locator.clone(),
+                    manifest: None,
+                };
+                let handle = self.load(&cref, policy)?;
+                check_dependency(dependency, &handle)?;
+                Ok(handle)
+            })
+            .collect()
+    }
+}
+
+fn check_catalog_version(name: &str, required: &str, version: &str) -> Result<(), CompError> {
+    let mismatch = || CompError::CatalogVersionMismatch {
+        name: name.to_string(),
+        required: required.to_string(),
+        version: version.to_string(),
+    };
+    let req = VersionReq::parse(required).map_err(|err| {
+        CompError::Store(StoreError::InvalidCatalog(format!(
+            "`{name}` version `{required}`: {err}"
+        )))
+    })?;
+    let version = Version::parse(version).map_err(|_| mismatch())?;
+    if req.matches(&version) {
+        Ok(())
+    } else {
+        Err(mismatch())
+    }
+}
+
+fn check_dependency(
+    dependency: &ComponentDependency,
+    handle: &ComponentHandle,
+) -> Result<(), CompError> {
+    if !dependency.accepts(handle.version())? {
+        return Err(CompError::DependencyMismatch {
+            id: dependency.id.clone(),
+            reason: format!(
+                "version {} does not satisfy {}",
+                handle.version(),
+                dependency.version
+            ),
+        });
+    }
+    for export in &dependency.exports {
+        if !handle
+            .info()
+            .exports
+            .iter()
+            .any(|candidate| candidate.operation == *export)
+        {
+            return Err(CompError::DependencyMismatch {
+                id: dependency.id.clone(),
+                reason: format!("operation `{export}` is not exported"),
+            });
+        }
+    }
+    Ok(())
 }
 
 fn component_info_from_descriptor(
     descriptor: &ComponentDescriptor,
     config_schema: Value,
+    role: Option<ComponentRole>,
 ) -> ComponentInfo {
     let capabilities = descriptor
         .capabilities
@@ -97,6 +321,7 @@ fn component_info_from_descriptor(
             description: op.summary.clone(),
             input_schema: None,
             output_schema: None,
+            deprecated: None,
         })
         .collect();
 
@@ -116,6 +341,7 @@ fn component_info_from_descriptor(
     ComponentInfo {
         name: Some(descriptor.name.clone()),
         description: descriptor.summary.clone(),
+        role,
         capabilities,
         exports,
         config_schema,
@@ -125,15 +351,54 @@ fn component_info_from_descriptor(
             min: "0.6.0".to_string(),
             max: None,
         },
+        dependencies: Vec::new(),
         metadata: Map::new(),
         raw,
     }
 }
 
-fn load_config_schema_from_describe(
+/// `node.describe` and, when exported, `component-descriptor.describe` on a
+/// fresh instance from an async engine's `instance_pre`.
+async fn describe_async(
+    instance_pre: &InstancePre<HostState>,
+    store: &mut wasmtime::Store<HostState>,
+    ticker: &EpochTicker,
+    timeout: Option<Duration>,
+) -> Result<(ComponentDescriptor, Option<ComponentDescribe>), CompError> {
+    let _ticking = yield_every_tick(&mut *store, ticker, timeout);
+    let instance = instance_pre.instantiate_async(&mut *store).await?;
+    let node_index = instance
+        .get_export_index(&mut *store, None, NODE_INTERFACE)
+        .ok_or_else(|| CompError::Runtime(format!("component does not export {NODE_INTERFACE}")))?;
+    let describe_index = instance
+        .get_export_index(&mut *store, Some(&node_index), "describe")
+        .ok_or_else(|| CompError::Runtime(format!("{NODE_INTERFACE} has no describe export")))?;
+    let node_describe =
+        instance.get_typed_func::<(), (ComponentDescriptor,)>(&mut *store, describe_index)?;
+    let (descriptor,) = node_describe.call_async(&mut *store, ()).await?;
+    node_describe.post_return_async(&mut *store).await?;
+
+    let describe = match describe_func(&instance, store)? {
+        Some(func) => {
+            let mut results = vec![Val::Bool(false); func.ty(&mut *store).results().len()];
+            func.call_async(&mut *store, &[], &mut results)
+                .await
+                .map_err(|err| CompError::Runtime(format!("call failed: {err}")))?;
+            func.post_return_async(&mut *store)
+                .await
+                .map_err(|err| CompError::Runtime(format!("post-return failed: {err}")))?;
+            Some(decode_describe_values(results)?)
+        }
+        None => None,
+    };
+    Ok((descriptor, describe))
+}
+
+/// `component-descriptor.describe`, when the component exports it.
+fn describe_func(
     instance: &wasmtime::component::Instance,
     store: &mut wasmtime::Store<HostState>,
-) -> Result<Option<Value>, CompError> {
+) -> Result<Option<Func>, CompError> {
     let Some(interface_index) = resolve_interface_index(instance, store, "component-descriptor")
     else {
         return Ok(None);
@@ -143,21 +408,18 @@ fn load_config_schema_from_describe(
     else {
         return Ok(None);
     };
-    let func = instance.get_func(&mut *store, func_index).ok_or_else(|| {
-        CompError::Runtime("component-descriptor.describe is not callable".into())
-    })?;
-    let describe_bytes = call_component_func(store, &func, &[]).and_then(|values| {
-        values
-            .first()
-            .ok_or_else(|| CompError::Runtime("describe returned no values".into()))
-            .and_then(val_to_bytes)
-    })?;
-    let payload = strip_self_describe_tag(&describe_bytes);
-    let describe: ComponentDescribe = canonical::from_cbor(payload)
-        .map_err(|err| CompError::SchemaValidation(err.to_string()))?;
-    serde_json::to_value(describe.config_schema)
+    instance
+        .get_func(&mut *store, func_index)
         .map(Some)
-        .map_err(CompError::from)
+        .ok_or_else(|| CompError::Runtime("component-descriptor.describe is not callable".into()))
+}
+
+fn decode_describe_values(values: Vec<Val>) -> Result<ComponentDescribe, CompError> {
+    let describe_bytes = values
+        .first()
+        .ok_or_else(|| CompError::Runtime("describe returned no values".into()))
+        .and_then(val_to_bytes)?;
+    decode_describe(&describe_bytes)
 }
 
 fn resolve_interface_index(
@@ -217,19 +479,36 @@ fn val_to_bytes(val: &Val) -> Result<Vec<u8>, CompError> {
     }
 }
 
-fn strip_self_describe_tag(bytes: &[u8]) -> &[u8] {
-    if bytes.starts_with(&SELF_DESCRIBE_TAG) {
-        &bytes[SELF_DESCRIBE_TAG.len()..]
-    } else {
-        bytes
-    }
-}
-
-fn create_engine() -> Result<Engine, CompError> {
-    let mut config = Config::new();
-    config.wasm_component_model(true);
-    config.wasm_backtrace_details(wasmtime::WasmBacktraceDetails::Enable);
-    Engine::new(&config).map_err(|err| CompError::Runtime(err.to_string()))
+/// The process-wide engine for `policy` in `mode`, reporting whether it was
+/// reused to the policy's metrics sink.
+fn shared_engine(
+    cref: &ComponentRef,
+    policy: &LoadPolicy,
+    mode: EngineMode,
+) -> Result<SharedEngine, CompError> {
+    let provider = EngineProvider::global();
+    let (shared, created) = provider.engine(EngineKey {
+        isolation: policy.isolation,
+        pooling: policy.pooling,
+        mode,
+    })?;
+    let metrics = &policy.host.metrics;
+    metrics.counter(
+        metric::ENGINE,
+        1,
+        &[
+            ("isolation", policy.isolation.name()),
+            ("result", if created { "created" } else { "reused" }),
+        ],
+    );
+    metrics.gauge(metric::ENGINES, provider.stats().engines as f64, &[]);
+    tracing::debug!(
+        component = %cref.name,
+        isolation = %policy.isolation,
+        created,
+        "resolved shared engine"
+    );
+    Ok(shared)
 }
 
 pub struct ComponentHandle {
@@ -238,19 +517,53 @@ pub struct ComponentHandle {
 
 pub(crate) struct ComponentInner {
     pub(crate) cref: ComponentRef,
+    /// Set when [`ComponentRef::manifest`] named one; overlay applied.
+    pub(crate) manifest: Option<Value>,
+    pub(crate) version: String,
     pub(crate) info: ComponentInfo,
     pub(crate) config_schema: Arc<Validator>,
+    /// Taken out by [`ComponentHandle::drain`].
+    pub(crate) compiled: Mutex<Option<Arc<Compiled>>>,
+    pub(crate) in_flight: InFlight,
+    pub(crate) host_policy: crate::policy::HostPolicy,
+    pub(crate) bindings: Mutex<HashMap<String, TenantBinding>>,
+}
+
+/// Wasmtime state needed to instantiate the component.
+pub(crate) struct Compiled {
     pub(crate) engine: Engine,
     pub(crate) instance_pre: InstancePre<HostState>,
     pub(crate) guest_indices: GuestIndices,
-    pub(crate) host_policy: crate::policy::HostPolicy,
-    pub(crate) bindings: Mutex<HashMap<String, TenantBinding>>,
+    /// Advances `engine`'s epoch while invocations are running.
+    pub(crate) ticker: EpochTicker,
+    /// Set when loaded with [`LoadPolicy::with_invoke_timeout`].
+    pub(crate) timeout: Option<Duration>,
+    /// Set when loaded with [`LoadPolicy::with_async_yield`]: `engine` is
+    /// then async and every invocation runs on a fiber yielding on each tick
+    /// of `ticker`.
+    pub(crate) yielding: bool,
+}
+
+impl ComponentInner {
+    pub(crate) fn compiled(&self) -> Result<Arc<Compiled>, CompError> {
+        self.compiled
+            .lock()
+            .expect("compiled mutex poisoned")
+            .clone()
+            .ok_or_else(|| CompError::Draining(self.cref.name.clone()))
+    }
 }
 
 #[derive(Debug, Clone)]
 pub(crate) struct TenantBinding {
     pub config: Value,
     pub secrets: HashMap<String, Vec<u8>>,
+    /// When `secrets` were last resolved.
+    pub resolved_at: Instant,
+    /// Set from [`SecretRotation::ttl`](crate::SecretRotation::ttl) at bind time.
+    pub expires_at: Option<Instant>,
+    /// Whether the max-age hook already fired for these secrets.
+    pub stale_reported: bool,
 }
 
 impl ComponentHandle {
@@ -261,6 +574,56 @@ impl ComponentHandle {
     pub fn cref(&self) -> &ComponentRef {
         &self.inner.cref
     }
+
+    /// The manifest the component was loaded with, after the environment
+    /// overlay; `None` when the [`ComponentRef`] named no manifest.
+    pub fn manifest(&self) -> Option<&Value> {
+        self.inner.manifest.as_ref()
+    }
+
+    /// Component version reported by `describe`.
+    pub fn version(&self) -> &str {
+        &self.inner.version
+    }
+
+    /// Stops admitting invocations, waits up to `timeout` for running ones
+    /// to finish, then releases the compiled component and tenant bindings.
+    /// Resources are released even when the wait times out; calls still
+    /// running keep their instance alive until they return.
+    pub fn drain(&self, timeout: Duration) -> Result<(), CompError> {
+        let inner = &self.inner;
+        inner.in_flight.begin_drain();
+        let in_flight = inner.in_flight.wait_idle(timeout);
+        let compiled = inner
+            .compiled
+            .lock()
+            .expect("compiled mutex poisoned")
+            .take();
+        inner
+            .bindings
+            .lock()
+            .expect("binding mutex poisoned")
+            .clear();
+        drop(compiled);
+        if in_flight > 0 {
+            tracing::warn!(
+                component = %inner.cref.name,
+                in_flight,
+                "drain timed out with invocations still running"
+            );
+            return Err(CompError::DrainTimeout {
+                component: inner.cref.name.clone(),
+                in_flight,
+            });
+        }
+        tracing::debug!(component = %inner.cref.name, "component drained");
+        Ok(())
+    }
+
+    /// Whether [`ComponentHandle::drain`] has been called.
+    pub fn is_draining(&self) -> bool {
+        self.inner.in_flight.is_draining()
+    }
 }
 
 impl Clone for ComponentHandle {
@@ -291,17 +654,11 @@ mod tests {
     #[test]
     fn descriptor_maps_to_component_info() {
         let config_schema = json!({"type":"object"});
-        let info = component_info_from_descriptor(&descriptor_fixture(), config_schema.clone());
+        let info =
+            component_info_from_descriptor(&descriptor_fixture(), config_schema.clone(), None);
         assert_eq!(info.wit_compat.package, "greentic:component");
         assert_eq!(info.wit_compat.min, "0.6.0");
         assert_eq!(info.config_schema, config_schema);
         assert_eq!(info.capabilities.len(), 1);
     }
-
-    #[test]
-    fn strips_self_describe_tag_only_when_present() {
-        let tagged = [SELF_DESCRIBE_TAG.as_slice(), &[1_u8, 2, 3]].concat();
-        assert_eq!(strip_self_describe_tag(&tagged), &[1_u8, 2, 3]);
-        assert_eq!(strip_self_describe_tag(&[7_u8, 8, 9]), &[7_u8, 8, 9]);
-    }
 }
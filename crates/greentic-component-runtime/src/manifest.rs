@@ -0,0 +1,192 @@
+use std::fs;
+use std::path::Path;
+
+use component_manifest::{CapabilityRef, NetCaps, apply_env_overlay, expand_capability_preset};
+use serde_json::Value;
+
+use crate::error::CompError;
+use crate::isolation::IsolationProfile;
+use crate::policy::HostPolicy;
+
+/// Reads the component manifest at `path`, deep-merging the
+/// `component.manifest.override.<env>.json` overlay next to it when `env` is
+/// set and the overlay exists.
+pub(crate) fn read_manifest(path: &Path, env: Option<&str>) -> Result<Value, CompError> {
+    let raw = fs::read_to_string(path).map_err(|source| CompError::ManifestIo {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mut manifest: Value = serde_json::from_str(&raw)?;
+    if let Some(env) = env {
+        apply_env_overlay(&mut manifest, path, env)?;
+    }
+    Ok(manifest)
+}
+
+/// `host` adjusted to the manifest: `limits.files` caps the open WASI
+/// handles and `limits.max_output_bytes` the invoke output, while
+/// `capabilities.net`, `capabilities.wasi.env.allow` and
+/// `capabilities.host.http` replace the host policy's socket access,
+/// environment allow-list and HTTP client access. [`IsolationProfile::Strict`]
+/// keeps network access closed whatever the manifest declares.
+pub(crate) fn manifest_host_policy(
+    host: &HostPolicy,
+    isolation: IsolationProfile,
+    manifest: &Value,
+) -> Result<HostPolicy, CompError> {
+    let mut host = host.clone();
+    let limits = &manifest["limits"];
+    if let Some(files) = limits["files"].as_u64() {
+        let files = usize::try_from(files).unwrap_or(usize::MAX);
+        host.max_open_handles = Some(host.max_open_handles.map_or(files, |max| max.min(files)));
+    }
+    if let Some(bytes) = limits["max_output_bytes"].as_u64() {
+        let bytes = usize::try_from(bytes).unwrap_or(usize::MAX);
+        host.max_output_bytes = host.max_output_bytes.min(bytes);
+    }
+
+    let mut capabilities = manifest
+        .get("capabilities")
+        .cloned()
+        .unwrap_or_else(|| Value::Object(Default::default()));
+    expand_capability_preset(&mut capabilities)?;
+    host.net = NetCaps::from_capabilities(&capabilities)?;
+    host.env_allow = capabilities["wasi"]["env"]["allow"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect();
+    let http = &capabilities["host"]["http"];
+    host.allow_http_fetch = http["client"].as_bool().unwrap_or(false);
+    host.http_domains = http["domains"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect();
+    if isolation == IsolationProfile::Strict {
+        host.allow_http_fetch = false;
+        host.net = None;
+    }
+    Ok(host)
+}
+
+/// What the manifest's `capabilities` block turns on, as dotted paths such
+/// as `host.messaging.inbound` or `wasi.filesystem.mode=sandbox`. The loader
+/// hands these to the policy hook before it fetches the artifact.
+pub(crate) fn manifest_capabilities(manifest: &Value) -> Result<Vec<CapabilityRef>, CompError> {
+    let mut capabilities = manifest
+        .get("capabilities")
+        .cloned()
+        .unwrap_or_else(|| Value::Object(Default::default()));
+    expand_capability_preset(&mut capabilities)?;
+    let mut paths = Vec::new();
+    capability_paths(&capabilities, "", &mut paths);
+    Ok(paths.into_iter().map(CapabilityRef).collect())
+}
+
+fn capability_paths(value: &Value, prefix: &str, out: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                capability_paths(child, &path, out);
+            }
+        }
+        Value::Bool(true) => out.push(prefix.to_string()),
+        Value::Array(items) if !items.is_empty() => out.push(prefix.to_string()),
+        Value::String(text) if text != "none" => out.push(format!("{prefix}={text}")),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn env_overlay_is_merged_and_env_names_are_checked() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("component.manifest.json");
+        fs::write(
+            &path,
+            json!({ "id": "acme.echo", "limits": { "memory_mb": 128, "files": 8 } }).to_string(),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("component.manifest.override.prod.json"),
+            json!({ "limits": { "files": 2 } }).to_string(),
+        )
+        .unwrap();
+
+        let prod = read_manifest(&path, Some("prod")).unwrap();
+        assert_eq!(prod["limits"], json!({ "memory_mb": 128, "files": 2 }));
+        let base = read_manifest(&path, Some("dev")).unwrap();
+        assert_eq!(base["limits"]["files"], 8);
+        assert!(matches!(
+            read_manifest(&path, Some("../prod")),
+            Err(CompError::Manifest(
+                component_manifest::ManifestError::InvalidEnvironment(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn manifest_limits_narrow_the_host_policy() {
+        let manifest = json!({ "limits": { "files": 4, "max_output_bytes": 1024 } });
+        let balanced = IsolationProfile::Balanced;
+        let host = manifest_host_policy(&HostPolicy::default(), balanced, &manifest).unwrap();
+        assert_eq!(host.max_open_handles, Some(4));
+        assert_eq!(host.max_output_bytes, 1024);
+
+        let strict = HostPolicy {
+            max_open_handles: Some(2),
+            ..HostPolicy::default()
+        };
+        let host = manifest_host_policy(&strict, balanced, &manifest).unwrap();
+        assert_eq!(host.max_open_handles, Some(2));
+        let host = manifest_host_policy(&HostPolicy::default(), balanced, &json!({})).unwrap();
+        assert_eq!(host.max_open_handles, None);
+    }
+
+    #[test]
+    fn manifest_capabilities_replace_network_and_env_access() {
+        let manifest = json!({
+            "capabilities": {
+                "net": { "tcp": true, "hosts": ["db.internal"] },
+                "wasi": { "env": { "allow": ["LOG_LEVEL"] } },
+                "host": { "http": { "client": true, "domains": ["api.example.com"] } }
+            }
+        });
+        let permissive = HostPolicy {
+            allow_http_fetch: true,
+            env_allow: vec!["HOME".into()],
+            ..HostPolicy::default()
+        };
+
+        let host =
+            manifest_host_policy(&permissive, IsolationProfile::Balanced, &manifest).unwrap();
+        assert!(host.allow_http_fetch);
+        assert_eq!(host.http_domains, vec!["api.example.com".to_string()]);
+        assert_eq!(host.env_allow, vec!["LOG_LEVEL".to_string()]);
+        assert_eq!(host.net.as_ref().map(|net| net.tcp), Some(true));
+
+        let host =
+            manifest_host_policy(&permissive, IsolationProfile::Balanced, &json!({})).unwrap();
+        assert!(!host.allow_http_fetch);
+        assert!(host.env_allow.is_empty());
+        assert!(host.net.is_none());
+
+        let host = manifest_host_policy(&permissive, IsolationProfile::Strict, &manifest).unwrap();
+        assert!(!host.allow_http_fetch);
+        assert!(host.net.is_none());
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt;
+use std::sync::Arc;
+
+use greentic_types::TenantCtx;
+use serde::{Deserialize, Serialize};
+use wasmtime::StoreContextMut;
+use wasmtime::component::Linker;
+
+use crate::error::CompError;
+
+/// Interface backing the manifest's `host.messaging` capability:
+///
+/// ```wit
+/// send: func(channel: string, body: string, metadata: list<tuple<string, string>>) -> result<string, string>;
+/// ack: func(message-id: string) -> result<_, string>;
+/// ```
+///
+/// `send` returns the id the backend assigned; `ack` confirms an inbound
+/// message the component finished handling.
+pub const MESSAGING_INTERFACE: &str = "greentic:component/messaging@0.6.0";
+/// Capability a component must declare in `describe` to send or ack.
+pub const MESSAGING_CAPABILITY: &str = "messaging";
+
+/// A message handed to the backend by `send`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboundMessage {
+    pub channel: String,
+    pub body: String,
+    /// Channel-specific headers such as a thread id or content type.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metadata: Vec<(String, String)>,
+}
+
+/// Delivers outbound messages and acknowledgements. `scope` identifies the
+/// tenant, see [`messaging_scope`].
+pub trait MessagingBackend: fmt::Debug + Send + Sync {
+    fn send(&self, scope: &str, message: &OutboundMessage) -> Result<String, CompError>;
+    fn ack(&self, scope: &str, message_id: &str) -> Result<(), CompError>;
+}
+
+/// Scope the runtime passes to [`MessagingBackend`] for `tenant`.
+pub fn messaging_scope(tenant: &TenantCtx) -> String {
+    crate::binder::binding_key(tenant)
+}
+
+/// Host side of the messaging import for one invocation. Sending needs
+/// outbound access and acking needs inbound access; denied calls trap.
+#[derive(Debug, Clone, Default)]
+pub struct MessagingHost {
+    scope: String,
+    backend: Option<Arc<dyn MessagingBackend>>,
+    outbound: bool,
+    inbound: bool,
+}
+
+impl MessagingHost {
+    pub fn new(
+        scope: impl Into<String>,
+        backend: Option<Arc<dyn MessagingBackend>>,
+        outbound: bool,
+        inbound: bool,
+    ) -> Self {
+        Self {
+            scope: scope.into(),
+            backend,
+            outbound,
+            inbound,
+        }
+    }
+
+    fn backend(&self, allowed: bool) -> Result<&Arc<dyn MessagingBackend>, CompError> {
+        self.backend
+            .as_ref()
+            .filter(|_| allowed)
+            .ok_or(CompError::HostFeatureDenied(MESSAGING_CAPABILITY))
+    }
+
+    pub fn send(&self, message: &OutboundMessage) -> Result<String, CompError> {
+        if message.channel.trim().is_empty() {
+            return Err(CompError::Messaging("channel must not be empty".into()));
+        }
+        self.backend(self.outbound)?.send(&self.scope, message)
+    }
+
+    pub fn ack(&self, message_id: &str) -> Result<(), CompError> {
+        self.backend(self.inbound)?.ack(&self.scope, message_id)
+    }
+}
+
+fn guest_result<T>(result: Result<T, CompError>) -> wasmtime::Result<Result<T, String>> {
+    match result {
+        Err(denied @ CompError::HostFeatureDenied(_)) => Err(wasmtime::Error::new(denied)),
+        other => Ok(other.map_err(|err| err.to_string())),
+    }
+}
+
+/// Defines [`MESSAGING_INTERFACE`] on `linker`.
+pub fn add_messaging_to_linker<T>(
+    linker: &mut Linker<T>,
+    get_host: impl Fn(&mut T) -> &mut MessagingHost + Send + Sync + Copy + 'static,
+) -> wasmtime::Result<()>
+where
+    T: Send + 'static,
+{
+    let mut inst = linker.instance(MESSAGING_INTERFACE)?;
+    inst.func_wrap(
+        "send",
+        move |mut caller: StoreContextMut<'_, T>,
+              (channel, body, metadata): (String, String, Vec<(String, String)>)| {
+            let message = OutboundMessage {
+                channel,
+                body,
+                metadata,
+            };
+            Ok((guest_result(get_host(caller.data_mut()).send(&message))?,))
+        },
+    )?;
+    inst.func_wrap(
+        "ack",
+        move |mut caller: StoreContextMut<'_, T>, (message_id,): (String,)| {
+            Ok((guest_result(get_host(caller.data_mut()).ack(&message_id))?,))
+        },
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct Outbox(Mutex<Vec<(String, OutboundMessage)>>);
+
+    impl MessagingBackend for Outbox {
+        fn send(&self, scope: &str, message: &OutboundMessage) -> Result<String, CompError> {
+            let mut sent = self.0.lock().unwrap();
+            sent.push((scope.to_string(), message.clone()));
+            Ok(format!("msg-{}", sent.len()))
+        }
+
+        fn ack(&self, _scope: &str, message_id: &str) -> Result<(), CompError> {
+            Err(CompError::Messaging(format!(
+                "unknown message {message_id}"
+            )))
+        }
+    }
+
+    #[test]
+    fn send_and_ack_follow_direction_grants() {
+        let outbox = Arc::new(Outbox::default());
+        let host = MessagingHost::new("dev::acme", Some(outbox.clone()), true, false);
+        let message = OutboundMessage {
+            channel: "slack:#ops".into(),
+            body: "deploy finished".into(),
+            metadata: vec![("thread".into(), "42".into())],
+        };
+
+        assert_eq!(host.send(&message).unwrap(), "msg-1");
+        assert_eq!(outbox.0.lock().unwrap()[0].0, "dev::acme");
+        assert!(matches!(
+            host.ack("msg-1"),
+            Err(CompError::HostFeatureDenied("messaging"))
+        ));
+        assert!(
+            host.send(&OutboundMessage {
+                channel: " ".into(),
+                ..message
+            })
+            .is_err()
+        );
+        assert!(
+            MessagingHost::default()
+                .send(&OutboundMessage {
+                    channel: "email".into(),
+                    body: String::new(),
+                    metadata: Vec::new(),
+                })
+                .is_err()
+        );
+    }
+}
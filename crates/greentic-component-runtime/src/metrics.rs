@@ -0,0 +1,254 @@
+use std::fmt;
+
+/// Receives runtime measurements. Labels are `(name, value)` pairs; a given
+/// metric name is always reported with the same label names.
+///
+/// Sinks run on the invoke path and must not block.
+pub trait MetricsSink: fmt::Debug + Send + Sync {
+    fn counter(&self, name: &str, value: u64, labels: &[(&str, &str)]);
+    fn gauge(&self, name: &str, value: f64, labels: &[(&str, &str)]);
+    fn histogram(&self, name: &str, value: f64, labels: &[(&str, &str)]);
+}
+
+/// Discards every measurement; the default sink.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopMetrics;
+
+impl MetricsSink for NoopMetrics {
+    fn counter(&self, _name: &str, _value: u64, _labels: &[(&str, &str)]) {}
+    fn gauge(&self, _name: &str, _value: f64, _labels: &[(&str, &str)]) {}
+    fn histogram(&self, _name: &str, _value: f64, _labels: &[(&str, &str)]) {}
+}
+
+/// Metric names reported by the runtime and the `greentic-component` test
+/// harness.
+pub mod metric {
+    /// Counter labelled `component`, `operation`, `outcome` (`ok`/`error`).
+    pub const INVOCATIONS: &str = "greentic_component_invocations_total";
+    /// Histogram in seconds labelled `component`, `operation`.
+    pub const INVOKE_DURATION: &str = "greentic_component_invoke_duration_seconds";
+    /// Counter labelled `component`, `operation`, `category`: the host error
+    /// kind, or `component` for any error the component returned.
+    pub const ERRORS: &str = "greentic_component_errors_total";
+    /// Histogram labelled `component`, `operation`; only reported when the
+    /// engine meters fuel.
+    pub const FUEL: &str = "greentic_component_fuel_consumed";
+    /// Gauge labelled `component`, `operation`: linear memory high-water mark
+    /// of the last invocation.
+    pub const MEMORY_PEAK: &str = "greentic_component_memory_peak_bytes";
+    /// Counter labelled `component`, `result` (`hit`/`miss`) for artifact
+    /// cache lookups on load.
+    pub const CACHE: &str = "greentic_component_cache_total";
+    /// Histogram labelled `component`, `operation`: host import calls per
+    /// invocation.
+    pub const HOST_CALLS: &str = "greentic_component_host_calls";
+    /// Counter labelled `isolation`, `result` (`created`/`reused`) for
+    /// engine lookups on load.
+    pub const ENGINE: &str = "greentic_component_engine_total";
+    /// Gauge, unlabelled: engines held by the process-wide
+    /// [`EngineProvider`](crate::EngineProvider).
+    pub const ENGINES: &str = "greentic_component_engines";
+
+    pub(crate) fn help(name: &str) -> &'static str {
+        match name {
+            INVOCATIONS => "Component invocations by outcome",
+            INVOKE_DURATION => "Wall-clock invoke latency in seconds",
+            ERRORS => "Failed invocations by error category",
+            FUEL => "Fuel consumed per invocation",
+            MEMORY_PEAK => "Peak linear memory of the last invocation in bytes",
+            CACHE => "Component artifact cache lookups",
+            HOST_CALLS => "Host import calls per invocation",
+            ENGINE => "Wasmtime engine lookups on load",
+            ENGINES => "Wasmtime engines shared across loads",
+            _ => "Greentic component metric",
+        }
+    }
+}
+
+#[cfg(feature = "prometheus")]
+pub use prometheus_sink::PrometheusSink;
+
+#[cfg(feature = "prometheus")]
+mod prometheus_sink {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    use prometheus::{
+        Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+    };
+
+    use super::{MetricsSink, metric};
+
+    enum Family {
+        Counter(IntCounterVec),
+        Gauge(GaugeVec),
+        Histogram(HistogramVec),
+    }
+
+    /// Registers one metric family per name on first use and serves them
+    /// from a [`prometheus::Registry`].
+    #[derive(Clone)]
+    pub struct PrometheusSink {
+        registry: Registry,
+        families: Arc<Mutex<HashMap<String, Family>>>,
+    }
+
+    impl Default for PrometheusSink {
+        fn default() -> Self {
+            Self::with_registry(Registry::new())
+        }
+    }
+
+    impl fmt::Debug for PrometheusSink {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let families = self.families.lock().map(|f| f.len()).unwrap_or_default();
+            f.debug_struct("PrometheusSink")
+                .field("families", &families)
+                .finish()
+        }
+    }
+
+    impl PrometheusSink {
+        /// Shares `registry` with metrics the host already exports.
+        pub fn with_registry(registry: Registry) -> Self {
+            Self {
+                registry,
+                families: Arc::new(Mutex::new(HashMap::new())),
+            }
+        }
+
+        pub fn registry(&self) -> &Registry {
+            &self.registry
+        }
+
+        /// Text exposition format, ready to serve on `/metrics`.
+        pub fn render(&self) -> String {
+            let mut buffer = Vec::new();
+            if let Err(err) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+                tracing::warn!("failed to encode prometheus metrics: {err}");
+            }
+            String::from_utf8(buffer).unwrap_or_default()
+        }
+
+        fn with_family(
+            &self,
+            name: &str,
+            labels: &[(&str, &str)],
+            create: impl FnOnce(&[&str]) -> prometheus::Result<Family>,
+            record: impl FnOnce(&Family, &[&str]) -> prometheus::Result<()>,
+        ) {
+            let names = labels.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+            let values = labels.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+            let mut families = self.families.lock().expect("metrics mutex poisoned");
+            if !families.contains_key(name) {
+                let family = create(&names).and_then(|family| {
+                    let collector: Box<dyn prometheus::core::Collector> = match &family {
+                        Family::Counter(vec) => Box::new(vec.clone()),
+                        Family::Gauge(vec) => Box::new(vec.clone()),
+                        Family::Histogram(vec) => Box::new(vec.clone()),
+                    };
+                    self.registry.register(collector)?;
+                    Ok(family)
+                });
+                match family {
+                    Ok(family) => {
+                        families.insert(name.to_string(), family);
+                    }
+                    Err(err) => {
+                        tracing::warn!(metric = name, "cannot register metric: {err}");
+                        return;
+                    }
+                }
+            }
+            if let Err(err) = record(&families[name], &values) {
+                tracing::warn!(metric = name, "dropping sample: {err}");
+            }
+        }
+    }
+
+    impl MetricsSink for PrometheusSink {
+        fn counter(&self, name: &str, value: u64, labels: &[(&str, &str)]) {
+            self.with_family(
+                name,
+                labels,
+                |names| {
+                    IntCounterVec::new(Opts::new(name, metric::help(name)), names)
+                        .map(Family::Counter)
+                },
+                |family, values| match family {
+                    Family::Counter(vec) => {
+                        vec.get_metric_with_label_values(values)?.inc_by(value);
+                        Ok(())
+                    }
+                    _ => Err(prometheus::Error::Msg(format!("{name} is not a counter"))),
+                },
+            );
+        }
+
+        fn gauge(&self, name: &str, value: f64, labels: &[(&str, &str)]) {
+            self.with_family(
+                name,
+                labels,
+                |names| {
+                    GaugeVec::new(Opts::new(name, metric::help(name)), names).map(Family::Gauge)
+                },
+                |family, values| match family {
+                    Family::Gauge(vec) => {
+                        vec.get_metric_with_label_values(values)?.set(value);
+                        Ok(())
+                    }
+                    _ => Err(prometheus::Error::Msg(format!("{name} is not a gauge"))),
+                },
+            );
+        }
+
+        fn histogram(&self, name: &str, value: f64, labels: &[(&str, &str)]) {
+            self.with_family(
+                name,
+                labels,
+                |names| {
+                    HistogramVec::new(HistogramOpts::new(name, metric::help(name)), names)
+                        .map(Family::Histogram)
+                },
+                |family, values| match family {
+                    Family::Histogram(vec) => {
+                        vec.get_metric_with_label_values(values)?.observe(value);
+                        Ok(())
+                    }
+                    _ => Err(prometheus::Error::Msg(format!("{name} is not a histogram"))),
+                },
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn renders_registered_families() {
+            let sink = PrometheusSink::default();
+            let labels = [
+                ("component", "echo"),
+                ("operation", "run"),
+                ("outcome", "ok"),
+            ];
+            sink.counter(metric::INVOCATIONS, 1, &labels);
+            sink.counter(metric::INVOCATIONS, 2, &labels);
+            sink.histogram(
+                metric::INVOKE_DURATION,
+                0.25,
+                &[("component", "echo"), ("operation", "run")],
+            );
+            // A second label set for the same name is dropped, not a panic.
+            sink.counter(metric::INVOCATIONS, 1, &[("component", "echo")]);
+
+            let text = sink.render();
+            assert!(text.contains(
+                "greentic_component_invocations_total{component=\"echo\",operation=\"run\",outcome=\"ok\"} 3"
+            ));
+            assert!(text.contains("greentic_component_invoke_duration_seconds_count"));
+        }
+    }
+}
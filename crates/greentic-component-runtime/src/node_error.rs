@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// The `details` of a node error in the documented shape
+/// (`schemas/v1/error-details.schema.json` in `greentic-component`).
+/// Details in any other shape stay available as raw JSON on
+/// [`CompError::Component`](crate::CompError::Component).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ErrorDetails {
+    /// Input problems, one per offending field.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+    /// Earliest useful retry, when the component knows it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<UpstreamError>,
+    /// Component-specific data with no fixed shape.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub context: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldError {
+    /// JSON pointer into the operation input.
+    pub pointer: String,
+    pub message: String,
+}
+
+/// The external service whose failure caused the error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpstreamError {
+    pub service: String,
+    /// HTTP status, when the upstream speaks HTTP.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl ErrorDetails {
+    /// `None` unless `value` is an object in the documented shape.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        value
+            .is_object()
+            .then(|| Self::deserialize(value).ok())
+            .flatten()
+    }
+
+    /// Parses details JSON as a guest returned it; see [`Self::from_value`].
+    pub fn from_json(raw: &str) -> Option<Self> {
+        serde_json::from_str::<Value>(raw)
+            .ok()
+            .and_then(|value| Self::from_value(&value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::CompError;
+    use serde_json::json;
+
+    #[test]
+    fn only_documented_shapes_parse() {
+        let details = ErrorDetails::from_value(&json!({
+            "fields": [{ "pointer": "/email", "message": "not an address" }],
+            "retry_after_ms": 1500,
+            "upstream": { "service": "crm", "status": 503 },
+            "context": { "request_id": "r-1" }
+        }))
+        .expect("documented shape");
+        assert_eq!(details.fields[0].pointer, "/email");
+        assert_eq!(details.retry_after_ms, Some(1500));
+        assert_eq!(details.upstream.unwrap().status, Some(503));
+
+        assert_eq!(ErrorDetails::from_json("{}"), Some(ErrorDetails::default()));
+        assert!(ErrorDetails::from_json(r#"{"hint":"free form"}"#).is_none());
+        assert!(ErrorDetails::from_json(r#""just text""#).is_none());
+        assert!(ErrorDetails::from_json("not json").is_none());
+    }
+
+    #[test]
+    fn component_errors_expose_retry_hints() {
+        let transient = CompError::Component {
+            code: "upstream.unavailable".into(),
+            message: "crm is down".into(),
+            retryable: true,
+            backoff_ms: None,
+            details: Some(json!({ "retry_after_ms": 250 })),
+        };
+        assert_eq!(transient.retry_after(), Some(Duration::from_millis(250)));
+        assert_eq!(transient.error_details().unwrap().retry_after_ms, Some(250));
+
+        let permanent = CompError::Component {
+            code: "input.invalid".into(),
+            message: "bad email".into(),
+            retryable: false,
+            backoff_ms: Some(100),
+            details: None,
+        };
+        assert_eq!(permanent.backoff_ms(), None);
+        assert_eq!(permanent.retry_after(), None);
+    }
+}
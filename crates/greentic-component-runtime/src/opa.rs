@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use reqwest::blocking::Client as HttpClient;
+use serde_json::{Value, json};
+
+use crate::policy::{PolicyDecision, PolicyHook, PolicyRequest};
+
+/// [`PolicyHook`] backed by an Open Policy Agent decision endpoint.
+///
+/// Each request is POSTed as `{"input": {...}}` to `endpoint` (for example
+/// `http://localhost:8181/v1/data/greentic/component/allow`). The rule may
+/// evaluate to a boolean or to `{"allow": bool, "reason": string}`. Transport
+/// errors and undefined decisions deny.
+#[derive(Debug, Clone)]
+pub struct OpaPolicyHook {
+    endpoint: String,
+    client: HttpClient,
+}
+
+impl OpaPolicyHook {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            client: HttpClient::builder()
+                .timeout(Duration::from_secs(2))
+                .build()
+                .unwrap_or_else(|_| HttpClient::new()),
+        }
+    }
+
+    fn query(&self, request: &PolicyRequest<'_>) -> Result<Value, String> {
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .json(&json!({ "input": request_input(request) }))
+            .send()
+            .map_err(|err| format!("opa request failed: {err}"))?;
+        if !response.status().is_success() {
+            return Err(format!("opa returned status {}", response.status()));
+        }
+        response
+            .json::<Value>()
+            .map_err(|err| format!("opa response is not JSON: {err}"))
+    }
+}
+
+impl PolicyHook for OpaPolicyHook {
+    fn authorize(&self, request: &PolicyRequest<'_>) -> PolicyDecision {
+        match self.query(request) {
+            Ok(body) => decision_from_result(body.get("result")),
+            Err(reason) => PolicyDecision::Deny(reason),
+        }
+    }
+}
+
+fn request_input(request: &PolicyRequest<'_>) -> Value {
+    json!({
+        "action": request.action.to_string(),
+        "component": request.component,
+        "tenant": request.tenant.map(|tenant| json!({
+            "env": tenant.env.as_str(),
+            "tenant": tenant.tenant_id.as_str(),
+            "team": tenant.team_id.as_ref().map(|team| team.as_str()),
+        })),
+        "operation": request.operation,
+        "capabilities": request
+            .capabilities
+            .iter()
+            .map(|capability| capability.as_str())
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn decision_from_result(result: Option<&Value>) -> PolicyDecision {
+    match result {
+        Some(Value::Bool(true)) => PolicyDecision::Allow,
+        Some(Value::Bool(false)) => PolicyDecision::Deny("denied by opa policy".into()),
+        Some(Value::Object(decision)) => {
+            if decision.get("allow").and_then(Value::as_bool) == Some(true) {
+                PolicyDecision::Allow
+            } else {
+                let reason = decision
+                    .get("reason")
+                    .and_then(Value::as_str)
+                    .unwrap_or("denied by opa policy");
+                PolicyDecision::Deny(reason.to_string())
+            }
+        }
+        _ => PolicyDecision::Deny("opa decision is undefined".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decisions_accept_bool_and_object_results() {
+        assert_eq!(
+            decision_from_result(Some(&json!(true))),
+            PolicyDecision::Allow
+        );
+        assert_eq!(
+            decision_from_result(Some(&json!({"allow": false, "reason": "tenant suspended"}))),
+            PolicyDecision::Deny("tenant suspended".into())
+        );
+        assert!(matches!(
+            decision_from_result(None),
+            PolicyDecision::Deny(_)
+        ));
+    }
+}
@@ -1,28 +1,321 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use greentic_component_store::ComponentStore;
+use component_manifest::{CapabilityRef, ComponentRole, NetCaps};
 use greentic_component_store::VerificationPolicy;
+use greentic_component_store::{Catalog, ComponentStore};
+use greentic_types::TenantCtx;
+use greentic_types::component::TelemetryScope;
+use tracing::level_filters::LevelFilter;
+
+use crate::audit::{AuditKind, AuditLog, AuditOutcome};
+use crate::blob::{BlobBackend, BlobLimits, InMemoryBlobs};
+use crate::error::CompError;
+use crate::events::EventSink;
+use crate::isolation::{IsolationProfile, PoolingConfig};
+use crate::messaging::MessagingBackend;
+use crate::metrics::{MetricsSink, NoopMetrics};
+use crate::rate_limit::{RateLimit, RateLimiter};
+use crate::secret_rotation::SecretRotation;
+use crate::sql::SqlAccess;
+use crate::tools::ToolAccess;
+
+/// Lifecycle step a [`PolicyHook`] is asked about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyAction {
+    /// Asked before the artifact is fetched, with the manifest's
+    /// capabilities, and again after describe with the ones the component
+    /// reports.
+    Load,
+    Bind,
+    Invoke,
+}
+
+impl fmt::Display for PolicyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PolicyAction::Load => "load",
+            PolicyAction::Bind => "bind",
+            PolicyAction::Invoke => "invoke",
+        })
+    }
+}
+
+/// What the runtime is about to do, handed to a [`PolicyHook`].
+#[derive(Debug, Clone, Copy)]
+pub struct PolicyRequest<'a> {
+    pub action: PolicyAction,
+    /// Component name from the [`ComponentRef`](crate::ComponentRef).
+    pub component: &'a str,
+    /// `None` for [`PolicyAction::Load`], which is tenant independent.
+    pub tenant: Option<&'a TenantCtx>,
+    /// Set for [`PolicyAction::Invoke`].
+    pub operation: Option<&'a str>,
+    /// Capabilities the component declares: dotted manifest paths for the
+    /// first [`PolicyAction::Load`] check, the describe list otherwise.
+    pub capabilities: &'a [CapabilityRef],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    Allow,
+    Deny(String),
+}
+
+/// Centralized authorization for platform operators. Implementations must be
+/// cheap enough to run on every invocation.
+pub trait PolicyHook: fmt::Debug + Send + Sync {
+    fn authorize(&self, request: &PolicyRequest<'_>) -> PolicyDecision;
+}
+
+/// Default for [`HostPolicy::max_output_bytes`].
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 16 * 1024 * 1024;
 
 #[derive(Debug, Clone)]
 pub struct HostPolicy {
     pub allow_http_fetch: bool,
+    /// Hosts `http-request` may reach, usually the manifest's
+    /// `capabilities.host.http.domains`; empty allows any host.
+    pub http_domains: Vec<String>,
     pub allow_telemetry: bool,
+    /// Finest attribute scope guests may use, usually the manifest's
+    /// `capabilities.host.telemetry.scope`.
+    pub telemetry_scope: TelemetryScope,
     pub allow_state_read: bool,
     pub allow_state_write: bool,
     pub allow_state_delete: bool,
     pub state_store: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    /// Buffer each invocation's state writes and deletes and apply them only
+    /// when it returns successfully; traps and component errors discard them.
+    pub transactional_state: bool,
+    /// Cap on simultaneously open WASI handles per invocation. The loader
+    /// lowers it to the manifest's `limits.files`; `None` leaves handles
+    /// unbounded.
+    pub max_open_handles: Option<usize>,
+    /// Largest CBOR output an invocation may return; bigger results fail
+    /// with [`CompError::OutputTooLarge`] instead of being decoded. The
+    /// loader lowers it to the manifest's `limits.max_output_bytes`.
+    pub max_output_bytes: usize,
+    /// Raw socket access; the loader replaces it with the manifest's
+    /// `capabilities.net`. `None` keeps WASI sockets closed.
+    pub net: Option<NetCaps>,
+    /// Variable names visible to the guest; the loader replaces it with the
+    /// manifest's `capabilities.wasi.env.allow`.
+    pub env_allow: Vec<String>,
+    /// Values for allow-listed variables. Names missing here stay unset; the
+    /// host process environment is never passed through.
+    pub env_vars: HashMap<String, String>,
+    /// Records every host-import call when set.
+    pub audit: Option<AuditLog>,
+    /// Central authorization hook consulted before load, bind and invoke.
+    pub hook: Option<Arc<dyn PolicyHook>>,
+    /// Per `(tenant, component, operation)` invocation budget.
+    pub rate_limiter: Option<RateLimiter>,
+    /// Expiry and staleness rules for bound secrets.
+    pub secret_rotation: Option<SecretRotation>,
+    /// Receives load and invoke measurements.
+    pub metrics: Arc<dyn MetricsSink>,
+    /// Most verbose guest `log` level forwarded to `tracing`.
+    pub guest_log_level: LevelFilter,
+    /// Storage for the blob interface, shared by every component using this
+    /// policy so hosts can hand blobs in and read results out.
+    pub blobs: Arc<dyn BlobBackend>,
+    pub blob_limits: BlobLimits,
+    /// Database access for components declaring the `sql` capability.
+    pub sql: Option<SqlAccess>,
+    /// Delivery for components declaring the `messaging` capability.
+    pub messaging: Option<Arc<dyn MessagingBackend>>,
+    /// Where `emit-event` calls go; without a sink emitting traps even for
+    /// components declaring `events`.
+    pub events: Option<Arc<dyn EventSink>>,
+    /// Components reachable through `call-tool`, for components declaring
+    /// `tools`.
+    pub tools: Option<ToolAccess>,
 }
 
 impl Default for HostPolicy {
     fn default() -> Self {
         Self {
             allow_http_fetch: false,
+            http_domains: Vec::new(),
             allow_telemetry: true,
+            telemetry_scope: TelemetryScope::Tenant,
             allow_state_read: false,
             allow_state_write: false,
             allow_state_delete: false,
             state_store: Arc::new(Mutex::new(HashMap::new())),
+            transactional_state: false,
+            max_open_handles: None,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            net: None,
+            env_allow: Vec::new(),
+            env_vars: HashMap::new(),
+            audit: None,
+            hook: None,
+            rate_limiter: None,
+            secret_rotation: None,
+            metrics: Arc::new(NoopMetrics),
+            guest_log_level: LevelFilter::INFO,
+            blobs: Arc::new(InMemoryBlobs::default()),
+            blob_limits: BlobLimits::default(),
+            sql: None,
+            messaging: None,
+            events: None,
+            tools: None,
+        }
+    }
+}
+
+impl HostPolicy {
+    pub fn with_hook(mut self, hook: Arc<dyn PolicyHook>) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    pub fn with_net(mut self, net: NetCaps) -> Self {
+        self.net = Some(net);
+        self
+    }
+
+    pub fn with_max_output_bytes(mut self, bytes: usize) -> Self {
+        self.max_output_bytes = bytes;
+        self
+    }
+
+    pub fn with_env_allow<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.env_allow = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_env_var(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env_vars.insert(key.into(), value.into());
+        self
+    }
+
+    /// Resolves the environment exposed to the guest, dropping anything that
+    /// is not allow-listed.
+    pub(crate) fn guest_env(&self) -> Vec<(String, String)> {
+        for key in self.env_vars.keys() {
+            if !self.env_allow.contains(key) {
+                tracing::warn!(%key, "environment variable is not in wasi.env.allow; withheld");
+            }
+        }
+        self.env_allow
+            .iter()
+            .filter_map(|key| {
+                let value = self.env_vars.get(key)?;
+                Some((key.clone(), value.clone()))
+            })
+            .collect()
+    }
+
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limiter = Some(RateLimiter::new(limit));
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<dyn MetricsSink>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn with_secret_rotation(mut self, rotation: SecretRotation) -> Self {
+        self.secret_rotation = Some(rotation);
+        self
+    }
+
+    pub fn with_guest_log_level(mut self, level: LevelFilter) -> Self {
+        self.guest_log_level = level;
+        self
+    }
+
+    pub fn with_telemetry_scope(mut self, scope: TelemetryScope) -> Self {
+        self.telemetry_scope = scope;
+        self
+    }
+
+    pub fn with_blob_backend(mut self, backend: Arc<dyn BlobBackend>) -> Self {
+        self.blobs = backend;
+        self
+    }
+
+    pub fn with_blob_limits(mut self, limits: BlobLimits) -> Self {
+        self.blob_limits = limits;
+        self
+    }
+
+    pub fn with_sql(mut self, access: SqlAccess) -> Self {
+        self.sql = Some(access);
+        self
+    }
+
+    pub fn with_messaging(mut self, backend: Arc<dyn MessagingBackend>) -> Self {
+        self.messaging = Some(backend);
+        self
+    }
+
+    pub fn with_event_sink(mut self, sink: Arc<dyn EventSink>) -> Self {
+        self.events = Some(sink);
+        self
+    }
+
+    pub fn with_tools(mut self, access: ToolAccess) -> Self {
+        self.tools = Some(access);
+        self
+    }
+
+    /// Charges one invocation against the tenant's bucket for
+    /// `component.operation`.
+    pub(crate) fn throttle(
+        &self,
+        tenant: &str,
+        component: &str,
+        operation: &str,
+    ) -> Result<(), CompError> {
+        let Some(limiter) = &self.rate_limiter else {
+            return Ok(());
+        };
+        limiter
+            .try_acquire(tenant, component, operation)
+            .map_err(|backoff_ms| CompError::RateLimited {
+                tenant: tenant.to_string(),
+                component: component.to_string(),
+                operation: operation.to_string(),
+                backoff_ms,
+            })
+    }
+
+    /// Asks the configured [`PolicyHook`], if any, whether `request` may
+    /// proceed.
+    pub(crate) fn authorize(&self, request: &PolicyRequest<'_>) -> Result<(), CompError> {
+        let Some(hook) = &self.hook else {
+            return Ok(());
+        };
+        match hook.authorize(request) {
+            PolicyDecision::Allow => Ok(()),
+            PolicyDecision::Deny(reason) => Err(CompError::PolicyDenied {
+                action: request.action,
+                component: request.component.to_string(),
+                reason,
+            }),
+        }
+    }
+
+    pub(crate) fn audit(
+        &self,
+        kind: AuditKind,
+        target: impl Into<String>,
+        outcome: AuditOutcome,
+        detail: Option<String>,
+    ) {
+        if let Some(audit) = &self.audit {
+            audit.record(kind, target, outcome, detail);
         }
     }
 }
@@ -32,6 +325,26 @@ pub struct LoadPolicy {
     pub store: Arc<ComponentStore>,
     pub verification: VerificationPolicy,
     pub host: HostPolicy,
+    /// Engine settings every component loaded with this policy runs under.
+    pub isolation: IsolationProfile,
+    /// Pooling allocator sizing; overrides the isolation profile's pool.
+    pub pooling: Option<PoolingConfig>,
+    /// Locators for manifest dependencies, keyed by dependency id. These take
+    /// precedence over the `locator` declared in the manifest.
+    pub dependency_locators: HashMap<String, String>,
+    /// Epoch tick for `invoke_async`; `None` leaves async invocation off.
+    pub async_yield: Option<Duration>,
+    /// Wall-clock limit for one blocking `invoke`; `None` runs unbounded.
+    pub invoke_timeout: Option<Duration>,
+    /// Named sources: a locator matching a catalog name is replaced by the
+    /// entry's locator, verification and version requirement.
+    pub catalog: Option<Arc<Catalog>>,
+    /// Roles this host runs, checked against the component's describe
+    /// `info.role`; `None` accepts any role, including none.
+    pub allowed_roles: Option<Vec<ComponentRole>>,
+    /// Environment whose `component.manifest.override.<env>.json` overlay is
+    /// merged over manifests named by a [`ComponentRef`](crate::ComponentRef).
+    pub env: Option<String>,
 }
 
 impl LoadPolicy {
@@ -40,6 +353,14 @@ impl LoadPolicy {
             store,
             verification: VerificationPolicy::default(),
             host: HostPolicy::default(),
+            isolation: IsolationProfile::default(),
+            pooling: None,
+            dependency_locators: HashMap::new(),
+            async_yield: None,
+            invoke_timeout: None,
+            catalog: None,
+            allowed_roles: None,
+            env: None,
         }
     }
 
@@ -52,4 +373,217 @@ impl LoadPolicy {
         self.host = host;
         self
     }
+
+    /// Compiles components for `invoke_async` instead, which suspends the
+    /// guest back to the executor every `interval` and on
+    /// `control.yield-now`. The blocking `invoke` still works on them.
+    pub fn with_async_yield(mut self, interval: Duration) -> Self {
+        self.async_yield = Some(interval);
+        self
+    }
+
+    /// Interrupts a blocking `invoke` that runs longer than `timeout` with
+    /// [`CompError::InvokeTimeout`]. Deadlines are enforced by one
+    /// [`EpochTicker`](crate::EpochTicker) per loaded component.
+    pub fn with_invoke_timeout(mut self, timeout: Duration) -> Self {
+        self.invoke_timeout = Some(timeout);
+        self
+    }
+
+    /// Selects the isolation profile and applies its host capability defaults
+    /// to the current host policy; later `with_host_policy` calls replace them.
+    pub fn with_isolation(mut self, profile: IsolationProfile) -> Self {
+        self.isolation = profile;
+        profile.apply_host_defaults(&mut self.host);
+        self
+    }
+
+    /// Loads components with the pooling instance allocator sized by
+    /// `pooling`, for predictable memory use and faster instantiation.
+    pub fn with_pooling(mut self, pooling: PoolingConfig) -> Self {
+        self.pooling = Some(pooling);
+        self
+    }
+
+    /// Installs `hook` on the host policy so it also governs bind and invoke
+    /// for every component loaded with this policy.
+    pub fn with_policy_hook(mut self, hook: Arc<dyn PolicyHook>) -> Self {
+        self.host.hook = Some(hook);
+        self
+    }
+
+    pub fn with_catalog(mut self, catalog: Arc<Catalog>) -> Self {
+        self.catalog = Some(catalog);
+        self
+    }
+
+    /// Refuses to load components whose role is not in `roles`, or that
+    /// declare no recognised role at all.
+    pub fn with_allowed_roles(mut self, roles: impl IntoIterator<Item = ComponentRole>) -> Self {
+        self.allowed_roles = Some(roles.into_iter().collect());
+        self
+    }
+
+    /// Checks a component's declared role (as its describe payload spells
+    /// it) against [`allowed_roles`](Self::allowed_roles).
+    pub(crate) fn admit_role(&self, component: &str, role: Option<&str>) -> Result<(), CompError> {
+        let Some(allowed) = &self.allowed_roles else {
+            return Ok(());
+        };
+        let parsed = role.and_then(|role| role.parse::<ComponentRole>().ok());
+        if parsed.is_some_and(|role| allowed.contains(&role)) {
+            return Ok(());
+        }
+        Err(CompError::RoleNotAllowed {
+            component: component.to_string(),
+            role: role.unwrap_or("unspecified").to_string(),
+        })
+    }
+
+    pub fn with_env(mut self, env: impl Into<String>) -> Self {
+        self.env = Some(env.into());
+        self
+    }
+
+    pub fn with_dependency_locator(
+        mut self,
+        id: impl Into<String>,
+        locator: impl Into<String>,
+    ) -> Self {
+        self.dependency_locators.insert(id.into(), locator.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct DenyInvoke;
+
+    impl PolicyHook for DenyInvoke {
+        fn authorize(&self, request: &PolicyRequest<'_>) -> PolicyDecision {
+            match request.action {
+                PolicyAction::Invoke => PolicyDecision::Deny("invocations are frozen".into()),
+                _ => PolicyDecision::Allow,
+            }
+        }
+    }
+
+    fn request(action: PolicyAction) -> PolicyRequest<'static> {
+        PolicyRequest {
+            action,
+            component: "demo",
+            tenant: None,
+            operation: None,
+            capabilities: &[],
+        }
+    }
+
+    #[test]
+    fn hook_denials_surface_as_policy_errors() {
+        let policy = HostPolicy::default().with_hook(Arc::new(DenyInvoke));
+
+        policy
+            .authorize(&request(PolicyAction::Load))
+            .expect("load allowed");
+        let err = policy
+            .authorize(&request(PolicyAction::Invoke))
+            .expect_err("invoke denied");
+        assert!(matches!(
+            err,
+            CompError::PolicyDenied { action: PolicyAction::Invoke, ref reason, .. }
+                if reason == "invocations are frozen"
+        ));
+        HostPolicy::default()
+            .authorize(&request(PolicyAction::Invoke))
+            .expect("no hook allows everything");
+    }
+
+    #[derive(Debug)]
+    struct DenyLoad(Arc<Mutex<Vec<Vec<String>>>>);
+
+    impl PolicyHook for DenyLoad {
+        fn authorize(&self, request: &PolicyRequest<'_>) -> PolicyDecision {
+            self.0.lock().unwrap().push(
+                request
+                    .capabilities
+                    .iter()
+                    .map(|capability| capability.0.clone())
+                    .collect(),
+            );
+            PolicyDecision::Deny("not on the allow list".into())
+        }
+    }
+
+    #[test]
+    fn load_is_authorized_before_the_artifact_is_fetched() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let manifest = dir.path().join("component.manifest.json");
+        std::fs::write(
+            &manifest,
+            r#"{"capabilities":{"host":{"http":{"client":true}},"wasi":{"clocks":false}}}"#,
+        )
+        .expect("write manifest");
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let policy = LoadPolicy::new(Arc::new(
+            ComponentStore::new(dir.path().join("cache")).expect("store"),
+        ))
+        .with_host_policy(HostPolicy::default().with_hook(Arc::new(DenyLoad(asked.clone()))));
+        let cref = crate::ComponentRef {
+            name: "demo".into(),
+            locator: dir.path().join("missing.wasm").display().to_string(),
+            manifest: Some(manifest),
+        };
+
+        let err = crate::Loader
+            .load(&cref, &policy)
+            .err()
+            .expect("load denied");
+        assert!(matches!(
+            err,
+            CompError::PolicyDenied {
+                action: PolicyAction::Load,
+                ..
+            }
+        ));
+        assert_eq!(*asked.lock().unwrap(), vec![vec!["host.http.client"]]);
+    }
+
+    #[test]
+    fn allowed_roles_gate_loads() {
+        let cache = std::env::temp_dir().join(format!("greentic-roles-{}", std::process::id()));
+        let policy = LoadPolicy::new(Arc::new(ComponentStore::new(&cache).expect("store")));
+        policy.admit_role("demo", None).expect("no restriction");
+
+        let policy = policy.with_allowed_roles([ComponentRole::Tool, ComponentRole::Adapter]);
+        policy.admit_role("demo", Some("adapter")).expect("allowed");
+        let err = policy
+            .admit_role("demo", Some("process"))
+            .expect_err("process refused");
+        assert_eq!(err.code(), "role_not_allowed");
+        assert!(matches!(
+            policy.admit_role("demo", None),
+            Err(CompError::RoleNotAllowed { ref role, .. }) if role == "unspecified"
+        ));
+        assert!(policy.admit_role("demo", Some("daemon")).is_err());
+        let _ = std::fs::remove_dir_all(&cache);
+    }
+
+    #[test]
+    fn guest_env_only_exposes_allow_listed_names() {
+        let policy = HostPolicy::default()
+            .with_env_allow(["GREENTIC_TEST_LEVEL"])
+            .with_env_var("GREENTIC_TEST_LEVEL", "debug")
+            .with_env_var("GREENTIC_TEST_SECRET", "hunter2");
+
+        assert_eq!(
+            policy.guest_env(),
+            vec![("GREENTIC_TEST_LEVEL".to_string(), "debug".to_string())]
+        );
+        assert!(HostPolicy::default().guest_env().is_empty());
+        let unset = HostPolicy::default().with_env_allow(["PATH"]);
+        assert!(unset.guest_env().is_empty(), "host environment leaked");
+    }
 }
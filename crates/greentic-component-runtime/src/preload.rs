@@ -0,0 +1,307 @@
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use greentic_types::TenantCtx;
+
+use crate::binder::{Binder, Bindings};
+use crate::error::CompError;
+use crate::loader::{ComponentHandle, ComponentRef, Loader};
+use crate::policy::LoadPolicy;
+
+/// Resolves a secret for a tenant binding. Shared by every preload worker.
+pub type PreloadSecretResolver =
+    Arc<dyn Fn(&str, &TenantCtx) -> Result<String, CompError> + Send + Sync>;
+
+/// Called from worker threads as components move through the plan; keep it
+/// cheap.
+pub type PreloadCallback = Arc<dyn Fn(&PreloadProgress) + Send + Sync>;
+
+/// Components a node should have compiled, and optionally bound, before it
+/// takes traffic.
+#[derive(Clone, Default)]
+pub struct PreloadPlan {
+    pub components: Vec<PreloadComponent>,
+    /// Loads running at once; defaults to the available parallelism.
+    pub concurrency: Option<NonZeroUsize>,
+    pub secret_resolver: Option<PreloadSecretResolver>,
+    pub on_progress: Option<PreloadCallback>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PreloadComponent {
+    pub cref: ComponentRef,
+    pub policy: LoadPolicy,
+    /// Tenants to bind once the component is loaded; empty loads only.
+    pub tenants: Vec<PreloadTenant>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PreloadTenant {
+    pub tenant: TenantCtx,
+    pub bindings: Bindings,
+}
+
+/// Where one component is in the plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreloadStage {
+    Loading,
+    /// Fetched and compiled; tenants are bound next.
+    Loaded {
+        elapsed: Duration,
+    },
+    Bound {
+        tenant: String,
+    },
+    /// Loaded and every tenant bound.
+    Ready {
+        elapsed: Duration,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreloadProgress {
+    pub component: String,
+    pub stage: PreloadStage,
+    /// Components finished so far, failed ones included.
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// The result for one [`PreloadComponent`], in plan order.
+pub struct PreloadOutcome {
+    pub name: String,
+    /// The loaded handle, or the error that stopped loading or binding it.
+    pub result: Result<ComponentHandle, CompError>,
+    /// Fetch, compile and bind time.
+    pub elapsed: Duration,
+}
+
+impl fmt::Debug for PreloadPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreloadPlan")
+            .field("components", &self.components)
+            .field("concurrency", &self.concurrency)
+            .field("secret_resolver", &self.secret_resolver.is_some())
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+impl fmt::Debug for PreloadOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreloadOutcome")
+            .field("name", &self.name)
+            .field("result", &self.result.as_ref().map(|_| "loaded"))
+            .field("elapsed", &self.elapsed)
+            .finish()
+    }
+}
+
+impl PreloadPlan {
+    pub fn new(components: Vec<PreloadComponent>) -> Self {
+        Self {
+            components,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_concurrency(mut self, concurrency: NonZeroUsize) -> Self {
+        self.concurrency = Some(concurrency);
+        self
+    }
+
+    pub fn with_secret_resolver(mut self, resolver: PreloadSecretResolver) -> Self {
+        self.secret_resolver = Some(resolver);
+        self
+    }
+
+    pub fn with_progress(mut self, callback: PreloadCallback) -> Self {
+        self.on_progress = Some(callback);
+        self
+    }
+}
+
+impl PreloadComponent {
+    pub fn new(cref: ComponentRef, policy: LoadPolicy) -> Self {
+        Self {
+            cref,
+            policy,
+            tenants: Vec::new(),
+        }
+    }
+
+    pub fn bind(mut self, tenant: TenantCtx, bindings: Bindings) -> Self {
+        self.tenants.push(PreloadTenant { tenant, bindings });
+        self
+    }
+}
+
+impl Loader {
+    /// Fetches, compiles and binds every component in `plan` on a pool of
+    /// worker threads. One component failing does not stop the others;
+    /// check each outcome.
+    pub fn preload(&self, plan: &PreloadPlan) -> Vec<PreloadOutcome> {
+        let total = plan.components.len();
+        let workers = plan
+            .concurrency
+            .or_else(|| std::thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get)
+            .min(total.max(1));
+        let next = AtomicUsize::new(0);
+        let completed = AtomicUsize::new(0);
+        let outcomes: Mutex<Vec<Option<PreloadOutcome>>> =
+            Mutex::new((0..total).map(|_| None).collect());
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(component) = plan.components.get(index) else {
+                            break;
+                        };
+                        let outcome = self.preload_one(plan, component, &completed, total);
+                        outcomes.lock().expect("preload outcomes poisoned")[index] = Some(outcome);
+                    }
+                });
+            }
+        });
+
+        outcomes
+            .into_inner()
+            .expect("preload outcomes poisoned")
+            .into_iter()
+            .map(|outcome| outcome.expect("every planned component is preloaded"))
+            .collect()
+    }
+
+    fn preload_one(
+        &self,
+        plan: &PreloadPlan,
+        component: &PreloadComponent,
+        completed: &AtomicUsize,
+        total: usize,
+    ) -> PreloadOutcome {
+        let name = component.cref.name.clone();
+        let report = |stage: PreloadStage, completed: usize| {
+            if let Some(callback) = &plan.on_progress {
+                callback(&PreloadProgress {
+                    component: name.clone(),
+                    stage,
+                    completed,
+                    total,
+                });
+            }
+        };
+        let started = Instant::now();
+        report(PreloadStage::Loading, completed.load(Ordering::Relaxed));
+
+        let result = self
+            .load(&component.cref, &component.policy)
+            .and_then(|handle| {
+                report(
+                    PreloadStage::Loaded {
+                        elapsed: started.elapsed(),
+                    },
+                    completed.load(Ordering::Relaxed),
+                );
+                let mut resolver = |key: &str, tenant: &TenantCtx| match &plan.secret_resolver {
+                    Some(resolver) => resolver(key, tenant),
+                    None => Err(CompError::Runtime(
+                        "preload plan has no secret resolver".into(),
+                    )),
+                };
+                for tenant in &component.tenants {
+                    Binder.bind(&handle, &tenant.tenant, &tenant.bindings, &mut resolver)?;
+                    report(
+                        PreloadStage::Bound {
+                            tenant: tenant.tenant.tenant.as_str().to_string(),
+                        },
+                        completed.load(Ordering::Relaxed),
+                    );
+                }
+                Ok(handle)
+            });
+
+        let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Err(err) = &result {
+            report(
+                PreloadStage::Failed {
+                    error: err.to_string(),
+                },
+                done,
+            );
+        } else {
+            report(
+                PreloadStage::Ready {
+                    elapsed: started.elapsed(),
+                },
+                done,
+            );
+        }
+        PreloadOutcome {
+            name,
+            result,
+            elapsed: started.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use greentic_component_store::ComponentStore;
+
+    #[test]
+    fn failures_are_reported_per_component_in_plan_order() {
+        let cache = std::env::temp_dir().join(format!("greentic-preload-{}", std::process::id()));
+        let policy = LoadPolicy::new(Arc::new(ComponentStore::new(&cache).expect("store")));
+        let components = ["a", "b", "c"]
+            .into_iter()
+            .map(|name| {
+                PreloadComponent::new(
+                    ComponentRef {
+                        name: name.to_string(),
+                        locator: cache.join(format!("{name}.wasm")).display().to_string(),
+                        manifest: None,
+                    },
+                    policy.clone(),
+                )
+            })
+            .collect();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let plan = PreloadPlan::new(components)
+            .with_concurrency(NonZeroUsize::new(2).unwrap())
+            .with_progress(Arc::new(move |progress: &PreloadProgress| {
+                sink.lock().unwrap().push(progress.clone());
+            }));
+
+        let outcomes = Loader.preload(&plan);
+        assert_eq!(
+            outcomes
+                .iter()
+                .map(|outcome| outcome.name.as_str())
+                .collect::<Vec<_>>(),
+            ["a", "b", "c"]
+        );
+        assert!(outcomes.iter().all(|outcome| outcome.result.is_err()));
+
+        let events = events.lock().unwrap();
+        let failed = events
+            .iter()
+            .filter(|event| matches!(event.stage, PreloadStage::Failed { .. }))
+            .map(|event| event.completed)
+            .collect::<Vec<_>>();
+        assert_eq!(failed.len(), 3);
+        assert_eq!(failed.iter().max(), Some(&3));
+        assert!(events.iter().all(|event| event.total == 3));
+        let _ = std::fs::remove_dir_all(&cache);
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+use std::sync::Arc;
+
+use futures::channel::mpsc;
+use wasmtime::StoreContextMut;
+use wasmtime::component::Linker;
+
+use crate::error::CompError;
+
+/// Interface guests import to call `report-progress(percent: u8, message: string)`.
+pub const PROGRESS_INTERFACE: &str = "greentic:component/progress@0.6.0";
+/// Capability a component must declare in `describe` before it may report
+/// progress. Undeclared calls trap.
+pub const PROGRESS_CAPABILITY: &str = "progress";
+
+/// One `report-progress` call from the guest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Progress {
+    /// Clamped to `0..=100`.
+    pub percent: u8,
+    pub message: String,
+}
+
+/// Called on the invoking thread for every progress report; keep it cheap.
+pub type ProgressCallback = Arc<dyn Fn(&Progress) + Send + Sync>;
+
+/// A callback that forwards reports into a channel, for callers that would
+/// rather poll a stream next to the invoke future.
+pub fn progress_channel() -> (ProgressCallback, mpsc::UnboundedReceiver<Progress>) {
+    let (tx, rx) = mpsc::unbounded();
+    let callback: ProgressCallback = Arc::new(move |progress: &Progress| {
+        // The receiver going away only means nobody is listening anymore.
+        let _ = tx.unbounded_send(progress.clone());
+    });
+    (callback, rx)
+}
+
+/// Host side of the progress import for one invocation.
+#[derive(Clone, Default)]
+pub struct ProgressReporter {
+    granted: bool,
+    callback: Option<ProgressCallback>,
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("granted", &self.granted)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+impl ProgressReporter {
+    /// `granted` reflects the component's declared capability; reports from
+    /// a granted component with no callback are dropped.
+    pub fn new(granted: bool, callback: Option<ProgressCallback>) -> Self {
+        Self { granted, callback }
+    }
+
+    pub fn report(&mut self, percent: u8, message: String) -> Result<(), CompError> {
+        if !self.granted {
+            return Err(CompError::HostFeatureDenied(PROGRESS_CAPABILITY));
+        }
+        if let Some(callback) = &self.callback {
+            callback(&Progress {
+                percent: percent.min(100),
+                message,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Defines [`PROGRESS_INTERFACE`] on `linker`. Works for both sync and
+/// async engines since reporting never blocks on the guest.
+pub fn add_progress_to_linker<T>(
+    linker: &mut Linker<T>,
+    get: impl Fn(&mut T) -> &mut ProgressReporter + Send + Sync + Copy + 'static,
+) -> wasmtime::Result<()>
+where
+    T: Send + 'static,
+{
+    let mut inst = linker.instance(PROGRESS_INTERFACE)?;
+    inst.func_wrap(
+        "report-progress",
+        move |mut caller: StoreContextMut<'_, T>, (percent, message): (u8, String)| {
+            get(caller.data_mut())
+                .report(percent, message)
+                .map_err(wasmtime::Error::new)
+        },
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[test]
+    fn reports_reach_the_channel_clamped() {
+        let (callback, mut rx) = progress_channel();
+        let mut reporter = ProgressReporter::new(true, Some(callback));
+        reporter.report(40, "importing".into()).unwrap();
+        reporter.report(250, "done".into()).unwrap();
+        drop(reporter);
+
+        let seen = futures::executor::block_on(rx.by_ref().collect::<Vec<_>>());
+        assert_eq!(
+            seen,
+            vec![
+                Progress {
+                    percent: 40,
+                    message: "importing".into()
+                },
+                Progress {
+                    percent: 100,
+                    message: "done".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn undeclared_capability_is_denied() {
+        let mut reporter = ProgressReporter::new(false, None);
+        let err = reporter.report(10, "nope".into()).unwrap_err();
+        assert!(matches!(err, CompError::HostFeatureDenied("progress")));
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Token-bucket settings applied independently to every
+/// `(tenant, component, operation)` triple.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Largest burst a tenant may issue before being throttled.
+    pub burst: u32,
+    /// Tokens restored per second. Zero, negative and NaN rates never
+    /// refill, so the burst is all a triple ever gets.
+    pub per_second: f64,
+}
+
+impl RateLimit {
+    pub fn new(burst: u32, per_second: f64) -> Self {
+        Self { burst, per_second }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BucketKey {
+    tenant: String,
+    component: String,
+    operation: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Shared limiter state. Cloning is cheap and every clone draws from the same
+/// buckets, so one limiter can sit on a [`HostPolicy`](crate::HostPolicy)
+/// used by many components.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    buckets: Arc<Mutex<HashMap<BucketKey, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Takes one token for the triple, or returns how long to wait (in
+    /// milliseconds) until one is available.
+    pub fn try_acquire(&self, tenant: &str, component: &str, operation: &str) -> Result<(), u64> {
+        self.try_acquire_at(tenant, component, operation, Instant::now())
+    }
+
+    fn try_acquire_at(
+        &self,
+        tenant: &str,
+        component: &str,
+        operation: &str,
+        now: Instant,
+    ) -> Result<(), u64> {
+        let burst = f64::from(self.limit.burst);
+        let key = BucketKey {
+            tenant: tenant.to_string(),
+            component: component.to_string(),
+            operation: operation.to_string(),
+        };
+        let mut buckets = self.buckets.lock().expect("rate limiter mutex poisoned");
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: burst,
+            refilled_at: now,
+        });
+        let refills = self.limit.per_second > 0.0;
+        if refills {
+            let elapsed = now.saturating_duration_since(bucket.refilled_at);
+            bucket.tokens =
+                (bucket.tokens + elapsed.as_secs_f64() * self.limit.per_second).min(burst);
+        }
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        if !refills {
+            return Err(u64::MAX);
+        }
+        // Rates so small the wait overflows a `Duration` wait forever.
+        let wait = Duration::try_from_secs_f64((1.0 - bucket.tokens) / self.limit.per_second)
+            .unwrap_or(Duration::MAX);
+        Err(wait.as_millis().try_into().unwrap_or(u64::MAX).max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_are_isolated_and_refill() {
+        let limiter = RateLimiter::new(RateLimit::new(2, 10.0));
+        let start = Instant::now();
+
+        assert!(
+            limiter
+                .try_acquire_at("noisy", "demo", "run", start)
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .try_acquire_at("noisy", "demo", "run", start)
+                .is_ok()
+        );
+        let backoff = limiter
+            .try_acquire_at("noisy", "demo", "run", start)
+            .expect_err("bucket drained");
+        assert_eq!(backoff, 100);
+
+        // Other tenants and operations keep their own budget.
+        assert!(
+            limiter
+                .try_acquire_at("quiet", "demo", "run", start)
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .try_acquire_at("noisy", "demo", "list", start)
+                .is_ok()
+        );
+
+        let later = start + Duration::from_millis(100);
+        assert!(
+            limiter
+                .try_acquire_at("noisy", "demo", "run", later)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn nan_rate_never_refills() {
+        let limiter = RateLimiter::new(RateLimit::new(1, f64::NAN));
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire_at("t", "demo", "run", start).is_ok());
+        let later = start + Duration::from_secs(3600);
+        assert_eq!(
+            limiter.try_acquire_at("t", "demo", "run", later),
+            Err(u64::MAX)
+        );
+        let empty = RateLimiter::new(RateLimit::new(0, f64::NAN));
+        assert_eq!(
+            empty.try_acquire_at("t", "demo", "run", start),
+            Err(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn tiny_rate_waits_saturate() {
+        let limiter = RateLimiter::new(RateLimit::new(1, 1e-20));
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire_at("t", "demo", "run", start).is_ok());
+        assert_eq!(
+            limiter.try_acquire_at("t", "demo", "run", start),
+            Err(u64::MAX)
+        );
+    }
+}
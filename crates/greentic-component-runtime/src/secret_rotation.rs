@@ -0,0 +1,102 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use greentic_types::TenantCtx;
+
+use crate::binder::binding_key;
+use crate::error::CompError;
+use crate::loader::TenantBinding;
+
+/// Lifetime rules for secrets resolved by [`Binder::bind`](crate::Binder::bind).
+///
+/// `ttl` is a hard limit: once it passes, invocations fail with
+/// [`CompError::SecretsExpired`] until
+/// [`Binder::rebind_secrets`](crate::Binder::rebind_secrets) refreshes them.
+/// `max_age` is a soft limit that only notifies the hook, once per binding.
+#[derive(Debug, Clone, Default)]
+pub struct SecretRotation {
+    pub ttl: Option<Duration>,
+    pub max_age: Option<Duration>,
+    pub hook: Option<Arc<dyn SecretAgeHook>>,
+}
+
+impl SecretRotation {
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_max_age(mut self, max_age: Duration, hook: Arc<dyn SecretAgeHook>) -> Self {
+        self.max_age = Some(max_age);
+        self.hook = Some(hook);
+        self
+    }
+
+    /// Enforces the binding's expiry and reports stale secrets. Bindings
+    /// without secrets never expire.
+    pub(crate) fn check(
+        &self,
+        component: &str,
+        tenant: &TenantCtx,
+        binding: &mut TenantBinding,
+        now: Instant,
+    ) -> Result<(), CompError> {
+        if binding.secrets.is_empty() {
+            return Ok(());
+        }
+        if binding
+            .expires_at
+            .is_some_and(|expires_at| now >= expires_at)
+        {
+            return Err(CompError::SecretsExpired(binding_key(tenant)));
+        }
+        let age = now.saturating_duration_since(binding.resolved_at);
+        let Some(max_age) = self.max_age.filter(|max_age| age > *max_age) else {
+            return Ok(());
+        };
+        if binding.stale_reported {
+            return Ok(());
+        }
+        binding.stale_reported = true;
+        let mut secrets = binding
+            .secrets
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        secrets.sort_unstable();
+        tracing::warn!(
+            component,
+            tenant = %binding_key(tenant),
+            age_secs = age.as_secs(),
+            "bound secrets are older than the configured max age"
+        );
+        if let Some(hook) = &self.hook {
+            hook.on_stale_secrets(&StaleSecrets {
+                component,
+                tenant,
+                secrets: &secrets,
+                age,
+                max_age,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Bound secrets that outlived [`SecretRotation::max_age`].
+#[derive(Debug, Clone, Copy)]
+pub struct StaleSecrets<'a> {
+    pub component: &'a str,
+    pub tenant: &'a TenantCtx,
+    /// Secret keys held by the binding, sorted.
+    pub secrets: &'a [&'a str],
+    pub age: Duration,
+    pub max_age: Duration,
+}
+
+/// Notified when a binding's secrets should be rotated. Runs on the invoke
+/// path, so implementations should hand work off rather than rebind inline.
+pub trait SecretAgeHook: fmt::Debug + Send + Sync {
+    fn on_stale_secrets(&self, event: &StaleSecrets<'_>);
+}
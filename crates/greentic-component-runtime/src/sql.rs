@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use greentic_types::TenantCtx;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use wasmtime::StoreContextMut;
+use wasmtime::component::Linker;
+
+use crate::error::CompError;
+
+/// Interface guests import to run allowlisted statements:
+///
+/// ```wit
+/// query: func(connection: string, statement: string, params-json: string) -> result<string, string>;
+/// ```
+///
+/// `statement` names an entry of the manifest's `capabilities.host.sql.statements`;
+/// `params-json` is a JSON array bound positionally. The result is a JSON
+/// [`SqlResult`].
+pub const SQL_INTERFACE: &str = "greentic:component/sql@0.6.0";
+/// Capability a component must declare before querying. Undeclared calls trap.
+pub const SQL_CAPABILITY: &str = "sql";
+
+/// Scope the runtime passes to [`SqlBackend::query`] for `tenant`.
+pub fn sql_scope(tenant: &TenantCtx) -> String {
+    crate::binder::binding_key(tenant)
+}
+
+/// Rows returned by a statement, or the number of rows it changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SqlResult {
+    pub rows: Vec<Map<String, Value>>,
+    pub rows_affected: u64,
+}
+
+/// Operator-provided database access. `scope` identifies the tenant, and
+/// `connection` is the logical name the component asked for; backends map the
+/// pair to a real connection and must refuse names they do not know.
+pub trait SqlBackend: fmt::Debug + Send + Sync {
+    fn query(
+        &self,
+        scope: &str,
+        connection: &str,
+        sql: &str,
+        params: &[Value],
+    ) -> Result<SqlResult, CompError>;
+}
+
+/// Backend plus the statements a component may run, keyed by name. The
+/// statements usually come from the manifest's `capabilities.host.sql`.
+#[derive(Debug, Clone)]
+pub struct SqlAccess {
+    pub backend: Arc<dyn SqlBackend>,
+    pub statements: BTreeMap<String, String>,
+}
+
+impl SqlAccess {
+    pub fn new(backend: Arc<dyn SqlBackend>, statements: BTreeMap<String, String>) -> Self {
+        Self {
+            backend,
+            statements,
+        }
+    }
+}
+
+/// Host side of the SQL import for one invocation.
+#[derive(Debug, Clone, Default)]
+pub struct SqlHost {
+    scope: String,
+    access: Option<SqlAccess>,
+}
+
+impl SqlHost {
+    /// `access` is `None` when the component lacks the capability or the
+    /// operator configured no database.
+    pub fn new(scope: impl Into<String>, access: Option<SqlAccess>) -> Self {
+        Self {
+            scope: scope.into(),
+            access,
+        }
+    }
+
+    pub fn query(
+        &self,
+        connection: &str,
+        statement: &str,
+        params_json: &str,
+    ) -> Result<SqlResult, CompError> {
+        let access = self
+            .access
+            .as_ref()
+            .ok_or(CompError::HostFeatureDenied(SQL_CAPABILITY))?;
+        let sql = access
+            .statements
+            .get(statement)
+            .ok_or_else(|| CompError::Sql(format!("statement `{statement}` is not allowlisted")))?;
+        let params = match serde_json::from_str(params_json)? {
+            Value::Array(params) => params,
+            Value::Null => Vec::new(),
+            _ => return Err(CompError::Sql("params must be a JSON array".into())),
+        };
+        access.backend.query(&self.scope, connection, sql, &params)
+    }
+}
+
+/// Defines [`SQL_INTERFACE`] on `linker`. Statement and database errors are
+/// returned to the guest as `err(message)`.
+pub fn add_sql_to_linker<T>(
+    linker: &mut Linker<T>,
+    get_host: impl Fn(&mut T) -> &mut SqlHost + Send + Sync + Copy + 'static,
+) -> wasmtime::Result<()>
+where
+    T: Send + 'static,
+{
+    let mut inst = linker.instance(SQL_INTERFACE)?;
+    inst.func_wrap(
+        "query",
+        move |mut caller: StoreContextMut<'_, T>,
+              (connection, statement, params_json): (String, String, String)| {
+            let host = get_host(caller.data_mut());
+            let result = match host.query(&connection, &statement, &params_json) {
+                Err(denied @ CompError::HostFeatureDenied(_)) => {
+                    return Err(wasmtime::Error::new(denied));
+                }
+                Err(err) => Err(err.to_string()),
+                Ok(result) => serde_json::to_string(&result).map_err(|err| err.to_string()),
+            };
+            Ok((result,))
+        },
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct Recording(Mutex<Vec<(String, String, String, Vec<Value>)>>);
+
+    impl SqlBackend for Recording {
+        fn query(
+            &self,
+            scope: &str,
+            connection: &str,
+            sql: &str,
+            params: &[Value],
+        ) -> Result<SqlResult, CompError> {
+            self.0.lock().unwrap().push((
+                scope.into(),
+                connection.into(),
+                sql.into(),
+                params.to_vec(),
+            ));
+            Ok(SqlResult {
+                rows_affected: 1,
+                ..SqlResult::default()
+            })
+        }
+    }
+
+    #[test]
+    fn only_allowlisted_statements_reach_the_backend() {
+        let backend = Arc::new(Recording::default());
+        let statements = BTreeMap::from([(
+            "touch".to_string(),
+            "UPDATE users SET seen = 1 WHERE id = ?1".to_string(),
+        )]);
+        let host = SqlHost::new(
+            "dev::acme",
+            Some(SqlAccess::new(backend.clone(), statements)),
+        );
+
+        assert_eq!(host.query("main", "touch", "[7]").unwrap().rows_affected, 1);
+        assert!(matches!(
+            host.query("main", "DROP TABLE users", "[]"),
+            Err(CompError::Sql(_))
+        ));
+        assert!(host.query("main", "touch", "{}").is_err());
+        assert_eq!(
+            *backend.0.lock().unwrap(),
+            vec![(
+                "dev::acme".to_string(),
+                "main".to_string(),
+                "UPDATE users SET seen = 1 WHERE id = ?1".to_string(),
+                vec![json!(7)],
+            )]
+        );
+
+        let denied = SqlHost::new("dev::acme", None);
+        assert!(matches!(
+            denied.query("main", "touch", "[]"),
+            Err(CompError::HostFeatureDenied("sql"))
+        ));
+    }
+}
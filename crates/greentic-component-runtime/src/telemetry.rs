@@ -0,0 +1,234 @@
+use std::fmt;
+use std::sync::Arc;
+
+use greentic_types::TenantCtx;
+use greentic_types::component::TelemetryScope;
+use serde::Serialize;
+use wasmtime::StoreContextMut;
+use wasmtime::component::Linker;
+
+use crate::error::CompError;
+
+/// Interface guests import to enrich the invocation span:
+///
+/// ```wit
+/// add-span-event: func(name: string, attributes: list<tuple<string, string>>) -> result<_, string>;
+/// set-span-attribute: func(key: string, value: string) -> result<_, string>;
+/// ```
+///
+/// Failures are returned to the guest rather than trapping, so components
+/// that trace unconditionally keep working where telemetry is not granted.
+pub const TELEMETRY_INTERFACE: &str = "greentic:component/telemetry@0.6.0";
+/// Capability a component must declare in `describe` for its telemetry to be
+/// kept.
+pub const TELEMETRY_CAPABILITY: &str = "telemetry";
+
+/// Telemetry accepted from a guest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TelemetryRecord {
+    SpanEvent {
+        name: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        attributes: Vec<(String, String)>,
+    },
+    SpanAttribute {
+        key: String,
+        value: String,
+    },
+}
+
+/// Extra destination for accepted records, next to the tracing event.
+pub type TelemetrySink = Arc<dyn Fn(&TelemetryRecord) + Send + Sync>;
+
+/// Scope an attribute key needs: `node.*` keys need [`TelemetryScope::Node`],
+/// `pack.*` keys need at least [`TelemetryScope::Pack`], anything else is
+/// tenant level.
+pub fn attribute_scope(key: &str) -> TelemetryScope {
+    if key.starts_with("node.") {
+        TelemetryScope::Node
+    } else if key.starts_with("pack.") {
+        TelemetryScope::Pack
+    } else {
+        TelemetryScope::Tenant
+    }
+}
+
+fn rank(scope: &TelemetryScope) -> u8 {
+    match scope {
+        TelemetryScope::Tenant => 0,
+        TelemetryScope::Pack => 1,
+        TelemetryScope::Node => 2,
+    }
+}
+
+/// Host side of the telemetry import. Accepted records become `tracing`
+/// events on the `greentic_telemetry` target.
+#[derive(Clone, Default)]
+pub struct TelemetryHost {
+    component: String,
+    tenant: Option<String>,
+    /// `None` when the capability was not granted; records are dropped.
+    scope: Option<TelemetryScope>,
+    sink: Option<TelemetrySink>,
+}
+
+impl fmt::Debug for TelemetryHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TelemetryHost")
+            .field("component", &self.component)
+            .field("tenant", &self.tenant)
+            .field("scope", &self.scope)
+            .field("sink", &self.sink.is_some())
+            .finish()
+    }
+}
+
+impl TelemetryHost {
+    pub fn new(
+        component: impl Into<String>,
+        tenant: Option<&TenantCtx>,
+        scope: Option<TelemetryScope>,
+    ) -> Self {
+        Self {
+            component: component.into(),
+            tenant: tenant.map(|ctx| ctx.tenant.as_str().to_string()),
+            scope,
+            sink: None,
+        }
+    }
+
+    pub fn with_sink(mut self, sink: TelemetrySink) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    pub fn add_span_event(
+        &self,
+        name: String,
+        attributes: Vec<(String, String)>,
+    ) -> Result<(), CompError> {
+        if name.trim().is_empty() {
+            return Err(CompError::Telemetry(
+                "span event name must not be empty".into(),
+            ));
+        }
+        for (key, _) in &attributes {
+            self.check_key(key)?;
+        }
+        self.accept(TelemetryRecord::SpanEvent { name, attributes })
+    }
+
+    pub fn set_span_attribute(&self, key: String, value: String) -> Result<(), CompError> {
+        self.check_key(&key)?;
+        self.accept(TelemetryRecord::SpanAttribute { key, value })
+    }
+
+    fn check_key(&self, key: &str) -> Result<(), CompError> {
+        let granted = self
+            .scope
+            .as_ref()
+            .ok_or(CompError::HostFeatureDenied(TELEMETRY_CAPABILITY))?;
+        if key.trim().is_empty() {
+            return Err(CompError::Telemetry(
+                "attribute key must not be empty".into(),
+            ));
+        }
+        let needed = attribute_scope(key);
+        if rank(&needed) > rank(granted) {
+            return Err(CompError::Telemetry(format!(
+                "attribute `{key}` needs {needed:?} scope but the component declares {granted:?}"
+            )));
+        }
+        Ok(())
+    }
+
+    fn accept(&self, record: TelemetryRecord) -> Result<(), CompError> {
+        if self.scope.is_none() {
+            return Err(CompError::HostFeatureDenied(TELEMETRY_CAPABILITY));
+        }
+        let tenant = self.tenant.as_deref().unwrap_or("-");
+        match &record {
+            TelemetryRecord::SpanEvent { name, attributes } => tracing::info!(
+                target: "greentic_telemetry",
+                component = %self.component,
+                tenant,
+                attributes = ?attributes,
+                "span event {name}"
+            ),
+            TelemetryRecord::SpanAttribute { key, value } => tracing::info!(
+                target: "greentic_telemetry",
+                component = %self.component,
+                tenant,
+                "span attribute {key}={value}"
+            ),
+        }
+        if let Some(sink) = &self.sink {
+            sink(&record);
+        }
+        Ok(())
+    }
+}
+
+/// Defines [`TELEMETRY_INTERFACE`] on `linker`.
+pub fn add_telemetry_to_linker<T>(
+    linker: &mut Linker<T>,
+    get_host: impl Fn(&mut T) -> &mut TelemetryHost + Send + Sync + Copy + 'static,
+) -> wasmtime::Result<()>
+where
+    T: Send + 'static,
+{
+    let mut inst = linker.instance(TELEMETRY_INTERFACE)?;
+    inst.func_wrap(
+        "add-span-event",
+        move |mut caller: StoreContextMut<'_, T>,
+              (name, attributes): (String, Vec<(String, String)>)| {
+            let result = get_host(caller.data_mut()).add_span_event(name, attributes);
+            Ok((result.map_err(|err| err.to_string()),))
+        },
+    )?;
+    inst.func_wrap(
+        "set-span-attribute",
+        move |mut caller: StoreContextMut<'_, T>, (key, value): (String, String)| {
+            let result = get_host(caller.data_mut()).set_span_attribute(key, value);
+            Ok((result.map_err(|err| err.to_string()),))
+        },
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn enforces_declared_scope_and_drops_without_capability() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink_seen = Arc::clone(&seen);
+        let host =
+            TelemetryHost::new("importer", None, Some(TelemetryScope::Pack)).with_sink(Arc::new(
+                move |record: &TelemetryRecord| sink_seen.lock().unwrap().push(record.clone()),
+            ));
+
+        host.set_span_attribute("pack.id".into(), "billing".into())
+            .unwrap();
+        host.add_span_event("batch.done".into(), vec![("rows".into(), "500".into())])
+            .unwrap();
+        assert!(matches!(
+            host.set_span_attribute("node.id".into(), "n1".into()),
+            Err(CompError::Telemetry(_))
+        ));
+        assert!(
+            host.add_span_event("retry".into(), vec![("node.attempt".into(), "2".into())])
+                .is_err()
+        );
+        assert_eq!(seen.lock().unwrap().len(), 2);
+
+        let denied = TelemetryHost::new("importer", None, None);
+        assert!(matches!(
+            denied.set_span_attribute("rows".into(), "1".into()),
+            Err(CompError::HostFeatureDenied("telemetry"))
+        ));
+    }
+}
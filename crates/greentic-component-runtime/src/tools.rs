@@ -0,0 +1,263 @@
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use component_manifest::ToolsCaps;
+use greentic_types::TenantCtx;
+use serde_json::Value;
+use wasmtime::StoreContextMut;
+use wasmtime::component::Linker;
+
+use crate::error::CompError;
+use crate::loader::{ComponentHandle, ComponentRef, Loader};
+use crate::policy::LoadPolicy;
+use crate::yielding::offload;
+
+/// Interface guests import to invoke another registered component:
+///
+/// ```wit
+/// call-tool: func(name: string, operation: string, payload: string) -> result<string, string>;
+/// ```
+///
+/// `payload` and the returned string are JSON.
+pub const TOOLS_INTERFACE: &str = "greentic:component/tools@0.6.0";
+/// Capability a component must declare in `describe` to call tools.
+pub const TOOLS_CAPABILITY: &str = "tools";
+/// How deep tool calls may nest before the innermost call is refused, so two
+/// components calling each other cannot recurse forever.
+pub const MAX_TOOL_DEPTH: usize = 8;
+
+thread_local! {
+    static TOOL_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Components callable as tools, by name. Callees are invoked with the
+/// caller's tenant, so they must already be bound for it.
+#[derive(Default)]
+pub struct ToolDirectory {
+    tools: RwLock<BTreeMap<String, ComponentHandle>>,
+}
+
+impl fmt::Debug for ToolDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tools = self.tools.read().expect("tool directory lock poisoned");
+        f.debug_set().entries(tools.keys()).finish()
+    }
+}
+
+impl ToolDirectory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an already loaded component under `name`.
+    pub fn insert(&self, name: impl Into<String>, handle: ComponentHandle) {
+        self.tools
+            .write()
+            .expect("tool directory lock poisoned")
+            .insert(name.into(), handle);
+    }
+
+    /// Loads `cref` with the runtime [`Loader`] and registers it under `name`.
+    pub fn load(
+        &self,
+        name: impl Into<String>,
+        cref: &ComponentRef,
+        policy: &LoadPolicy,
+    ) -> Result<ComponentHandle, CompError> {
+        let handle = Loader.load(cref, policy)?;
+        self.insert(name, handle.clone());
+        Ok(handle)
+    }
+
+    pub fn get(&self, name: &str) -> Option<ComponentHandle> {
+        self.tools
+            .read()
+            .expect("tool directory lock poisoned")
+            .get(name)
+            .cloned()
+    }
+}
+
+/// Registered tools plus the names one component may call, usually the
+/// manifest's `capabilities.tools`.
+#[derive(Debug, Clone)]
+pub struct ToolAccess {
+    pub directory: Arc<ToolDirectory>,
+    pub allow: ToolsCaps,
+}
+
+impl ToolAccess {
+    pub fn new(directory: Arc<ToolDirectory>, allow: ToolsCaps) -> Self {
+        Self { directory, allow }
+    }
+}
+
+/// Host side of the tools import for one invocation.
+#[derive(Debug, Clone, Default)]
+pub struct ToolsHost {
+    tenant: Option<TenantCtx>,
+    access: Option<ToolAccess>,
+}
+
+impl ToolsHost {
+    /// `access` is `None` when the component may not call tools; calls then
+    /// trap.
+    pub fn new(tenant: TenantCtx, access: Option<ToolAccess>) -> Self {
+        Self {
+            tenant: Some(tenant),
+            access,
+        }
+    }
+
+    pub fn call(
+        &self,
+        name: &str,
+        operation: &str,
+        payload_json: &str,
+    ) -> Result<Value, CompError> {
+        self.prepare(name, payload_json)?.run(operation)
+    }
+
+    /// Checks access and the nesting depth and resolves the callee, leaving
+    /// only the invocation itself, which may then run on another thread.
+    fn prepare(&self, name: &str, payload_json: &str) -> Result<ToolCall, CompError> {
+        let (Some(access), Some(tenant)) = (&self.access, &self.tenant) else {
+            return Err(CompError::HostFeatureDenied(TOOLS_CAPABILITY));
+        };
+        if !access.allow.allows(name) {
+            return Err(CompError::Tool(format!(
+                "`{name}` is not in capabilities.tools.allow"
+            )));
+        }
+        let handle = access
+            .directory
+            .get(name)
+            .ok_or_else(|| CompError::Tool(format!("no tool named `{name}` is registered")))?;
+        let payload: Value = serde_json::from_str(payload_json)?;
+        let depth = TOOL_DEPTH.get();
+        if depth >= MAX_TOOL_DEPTH {
+            return Err(CompError::Tool(format!(
+                "tool calls nested deeper than {MAX_TOOL_DEPTH}"
+            )));
+        }
+        Ok(ToolCall {
+            handle,
+            tenant: tenant.clone(),
+            payload,
+            depth,
+        })
+    }
+}
+
+/// A tool call that passed [`ToolsHost::prepare`].
+struct ToolCall {
+    handle: ComponentHandle,
+    tenant: TenantCtx,
+    payload: Value,
+    /// Nesting depth of the caller.
+    depth: usize,
+}
+
+impl ToolCall {
+    fn run(self, operation: &str) -> Result<Value, CompError> {
+        let previous = TOOL_DEPTH.replace(self.depth + 1);
+        let result = crate::invoker::invoke(&self.handle, operation, &self.payload, &self.tenant);
+        TOOL_DEPTH.set(previous);
+        result
+    }
+}
+
+/// Defines [`TOOLS_INTERFACE`] on `linker`. Callee failures, including its
+/// own traps, come back to the caller as `err(message)`.
+pub fn add_tools_to_linker<T>(
+    linker: &mut Linker<T>,
+    get_host: impl Fn(&mut T) -> &mut ToolsHost + Send + Sync + Copy + 'static,
+) -> wasmtime::Result<()>
+where
+    T: Send + 'static,
+{
+    let mut inst = linker.instance(TOOLS_INTERFACE)?;
+    inst.func_wrap(
+        "call-tool",
+        move |mut caller: StoreContextMut<'_, T>,
+              (name, operation, payload): (String, String, String)| {
+            let host = get_host(caller.data_mut());
+            tool_result(host.call(&name, &operation, &payload))
+        },
+    )?;
+    Ok(())
+}
+
+/// Like [`add_tools_to_linker`], for async engines: the callee runs off the
+/// caller's fiber, so it cannot stall the caller's executor thread.
+pub(crate) fn add_tools_to_linker_async<T>(
+    linker: &mut Linker<T>,
+    get_host: impl Fn(&mut T) -> &mut ToolsHost + Send + Sync + Copy + 'static,
+) -> wasmtime::Result<()>
+where
+    T: Send + 'static,
+{
+    let mut inst = linker.instance(TOOLS_INTERFACE)?;
+    inst.func_wrap_async(
+        "call-tool",
+        move |mut caller: StoreContextMut<'_, T>,
+              (name, operation, payload): (String, String, String)| {
+            let call = get_host(caller.data_mut()).prepare(&name, &payload);
+            Box::new(async move {
+                let result = match call {
+                    Ok(call) => offload(move || call.run(&operation)).await,
+                    Err(err) => Err(err),
+                };
+                tool_result(result)
+            })
+        },
+    )?;
+    Ok(())
+}
+
+/// Denied calls trap; any other failure is returned to the guest.
+fn tool_result(result: Result<Value, CompError>) -> wasmtime::Result<(Result<String, String>,)> {
+    let result = match result {
+        Err(denied @ CompError::HostFeatureDenied(TOOLS_CAPABILITY)) => {
+            return Err(wasmtime::Error::new(denied));
+        }
+        Err(err) => Err(err.to_string()),
+        Ok(output) => Ok(output.to_string()),
+    };
+    Ok((result,))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use greentic_types::{EnvId, TenantId};
+
+    fn tenant() -> TenantCtx {
+        TenantCtx::new(EnvId("dev".into()), TenantId("acme".into()))
+    }
+
+    #[test]
+    fn only_allowlisted_registered_tools_are_reachable() {
+        let access = ToolAccess::new(
+            Arc::new(ToolDirectory::new()),
+            ToolsCaps {
+                allow: vec!["translate".into()],
+            },
+        );
+        let host = ToolsHost::new(tenant(), Some(access));
+
+        let err = host.call("summarize", "run", "{}").unwrap_err();
+        assert!(
+            err.to_string().contains("capabilities.tools.allow"),
+            "{err}"
+        );
+        let err = host.call("translate", "run", "{}").unwrap_err();
+        assert!(err.to_string().contains("no tool named"), "{err}");
+        assert!(matches!(
+            ToolsHost::new(tenant(), None).call("translate", "run", "{}"),
+            Err(CompError::HostFeatureDenied("tools"))
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use wasmtime::{Store, Trap, UpdateDeadline};
+
+use crate::epoch::{EpochGuard, EpochTicker};
+
+/// Makes `store`, on an async engine, suspend to the executor on every tick
+/// of `ticker` and trap with [`Trap::Interrupt`] once `timeout` has passed.
+pub(crate) fn yield_every_tick<T>(
+    store: &mut Store<T>,
+    ticker: &EpochTicker,
+    timeout: Option<Duration>,
+) -> EpochGuard {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    store.epoch_deadline_callback(move |_| match deadline {
+        Some(deadline) if Instant::now() >= deadline => Err(Trap::Interrupt.into()),
+        _ => Ok(UpdateDeadline::Yield(1)),
+    });
+    store.set_epoch_deadline(1);
+    ticker.arm()
+}
+
+/// Runs a blocking host call off the guest's fiber: on Tokio's blocking
+/// pool when the invocation is driven from a Tokio runtime, otherwise on a
+/// thread of its own.
+pub(crate) async fn offload<R>(work: impl FnOnce() -> R + Send + 'static) -> R
+where
+    R: Send + 'static,
+{
+    if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+        return runtime
+            .spawn_blocking(work)
+            .await
+            .expect("blocking host call panicked");
+    }
+    let (tx, rx) = futures::channel::oneshot::channel();
+    thread::Builder::new()
+        .name("greentic-host-call".into())
+        .spawn(move || {
+            let _ = tx.send(work());
+        })
+        .expect("spawn host call thread");
+    rx.await.expect("blocking host call panicked")
+}
+
+/// Returns `Pending` once so the executor can run other tasks, then
+/// completes. Used for the guest's `control.yield-now`.
+#[derive(Debug, Default)]
+pub(crate) struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yield_now_is_pending_exactly_once() {
+        let mut future = YieldNow::default();
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+        assert!(Pin::new(&mut future).poll(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn offloaded_work_runs_on_another_thread() {
+        let caller = thread::current().id();
+        let worker = futures::executor::block_on(offload(|| thread::current().id()));
+        assert_ne!(worker, caller);
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::BTreeMap;
+use std::fs as std_fs;
+use std::path::{Component, Path, PathBuf};
+use std::thread;
+
+use serde_json::Value;
+use tracing::debug;
+
+use crate::{
+    ComponentStore, DigestAlgorithm, DigestPolicy, FetchDecision, StoreError, StoreLocator,
+    VerificationPolicy, VerificationReport, VerifiedDigest, hash_locator,
+};
+
+/// Software bill of materials looked up next to a bundle's manifest.
+pub const BUNDLE_SBOM: &str = "sbom.cdx.json";
+
+/// Which optional members [`ComponentStore::fetch_bundle_with`] looks for,
+/// and the digests they must match.
+#[derive(Debug, Clone)]
+pub struct BundleOptions {
+    /// Locales fetched from `assets/i18n/<locale>.json`; missing ones are
+    /// skipped.
+    pub locales: Vec<String>,
+    pub sbom: bool,
+    /// Digests declared by whatever references the bundle, keyed by member
+    /// name (`component.manifest.json`, `sbom.cdx.json`,
+    /// `assets/i18n/en.json`). A member with a declared digest must be
+    /// published and match it. The wasm is checked against the policy and
+    /// `hashes.component_wasm` instead.
+    pub digests: BTreeMap<String, DigestPolicy>,
+}
+
+impl Default for BundleOptions {
+    fn default() -> Self {
+        Self {
+            locales: vec!["en".to_string()],
+            sbom: true,
+            digests: BTreeMap::new(),
+        }
+    }
+}
+
+/// A component manifest together with the artifacts it travels with, all
+/// verified and committed to the cache together.
+#[derive(Debug)]
+pub struct Bundle {
+    /// Where the manifest was fetched from.
+    pub locator: StoreLocator,
+    /// Cache directory holding every member under its bundle-relative path.
+    pub path: PathBuf,
+    pub manifest: Value,
+    pub manifest_member: BundleMember,
+    pub wasm: BundleMember,
+    pub sbom: Option<BundleMember>,
+    /// Translations keyed by locale.
+    pub i18n: BTreeMap<String, BundleMember>,
+}
+
+#[derive(Debug)]
+pub struct BundleMember {
+    /// Path relative to the manifest, e.g. `assets/i18n/en.json`.
+    pub name: String,
+    /// Location inside the bundle's cache directory.
+    pub path: PathBuf,
+    pub bytes: Vec<u8>,
+    pub verification: VerificationReport,
+}
+
+impl Bundle {
+    /// Every member with its verification report, manifest first.
+    pub fn members(&self) -> impl Iterator<Item = &BundleMember> {
+        [&self.manifest_member, &self.wasm]
+            .into_iter()
+            .chain(self.sbom.iter())
+            .chain(self.i18n.values())
+    }
+}
+
+impl ComponentStore {
+    /// Fetches the manifest at `locator` (a file path or HTTP(S) URL), its
+    /// wasm, an SBOM and the English translations.
+    pub fn fetch_bundle(
+        &self,
+        locator: &str,
+        policy: &VerificationPolicy,
+    ) -> Result<Bundle, StoreError> {
+        self.fetch_bundle_with(locator, policy, &BundleOptions::default())
+    }
+
+    /// Like [`ComponentStore::fetch_bundle`] with explicit optional members.
+    ///
+    /// `policy` governs the wasm; without an expected digest there the
+    /// manifest's `hashes.component_wasm` is enforced. The other members are
+    /// checked against [`BundleOptions::digests`]. The members are fetched
+    /// concurrently and nothing is cached unless all of them verify.
+    pub fn fetch_bundle_with(
+        &self,
+        locator: &str,
+        policy: &VerificationPolicy,
+        options: &BundleOptions,
+    ) -> Result<Bundle, StoreError> {
+        let locator = StoreLocator::parse(locator)?;
+        let result = self.fetch_bundle_observed(&locator, policy, options);
+        for observer in &self.observers {
+            match &result {
+                Ok(bundle) => {
+                    observer.on_fetch_verified(&locator, &bundle.wasm.verification);
+                    observer.on_fetch_cached(&locator, &bundle.path, false);
+                }
+                Err(err) => observer.on_fetch_error(&locator, err),
+            }
+        }
+        result
+    }
+
+    fn fetch_bundle_observed(
+        &self,
+        locator: &StoreLocator,
+        policy: &VerificationPolicy,
+        options: &BundleOptions,
+    ) -> Result<Bundle, StoreError> {
+        for observer in &self.observers {
+            if let FetchDecision::Deny(reason) = observer.on_fetch_start(locator) {
+                return Err(StoreError::FetchDenied {
+                    locator: locator.as_cache_key(),
+                    reason,
+                });
+            }
+        }
+
+        let manifest_bytes = self.fetch_bytes(locator, &mut |_, _| {})?;
+        let manifest_name = manifest_name(locator);
+        let manifest_report = member_report(options, &manifest_name, &manifest_bytes)?;
+        let manifest: Value =
+            serde_json::from_slice(&manifest_bytes).map_err(|err| invalid(locator, err))?;
+        let wasm_name = manifest
+            .pointer("/artifacts/component_wasm")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid(locator, "manifest has no artifacts.component_wasm"))?
+            .to_string();
+        let wasm_policy = if policy.digest.as_ref().and_then(|d| d.expected()).is_some() {
+            policy.clone()
+        } else {
+            VerificationPolicy {
+                digest: manifest_wasm_digest(&manifest).map_err(|err| invalid(locator, err))?,
+                signature: policy.signature.clone(),
+                digest_sidecars: false,
+            }
+        };
+
+        let optional = options
+            .sbom
+            .then(|| BUNDLE_SBOM.to_string())
+            .into_iter()
+            .chain(
+                options
+                    .locales
+                    .iter()
+                    .map(|locale| format!("assets/i18n/{locale}.json")),
+            )
+            .collect::<Vec<_>>();
+        let (wasm, optional) = thread::scope(|scope| {
+            let wasm = scope.spawn(|| self.fetch_member(locator, &wasm_name));
+            let optional = optional
+                .iter()
+                .map(|name| (name, scope.spawn(move || self.fetch_member(locator, name))))
+                .collect::<Vec<_>>();
+            let wasm = joined(wasm, locator);
+            let optional = optional
+                .into_iter()
+                .map(|(name, handle)| (name.clone(), joined(handle, locator)))
+                .collect::<Vec<_>>();
+            (wasm, optional)
+        });
+
+        let wasm_bytes = wasm?;
+        let wasm_report = wasm_policy.verify(&wasm_bytes)?;
+        let mut members = vec![
+            (manifest_name, manifest_bytes, manifest_report),
+            (wasm_name, wasm_bytes, wasm_report),
+        ];
+        for (name, fetched) in optional {
+            match fetched {
+                Ok(bytes) => {
+                    let report = member_report(options, &name, &bytes)?;
+                    members.push((name, bytes, report));
+                }
+                Err(err) if is_not_found(&err) && !options.digests.contains_key(&name) => {
+                    debug!("bundle member {name} not published")
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        let path = self.commit_bundle(locator, &members)?;
+        let mut members = members
+            .into_iter()
+            .map(|(name, bytes, verification)| BundleMember {
+                path: path.join(&name),
+                name,
+                bytes,
+                verification,
+            });
+        let manifest_member = members.next().expect("manifest member");
+        let wasm = members.next().expect("wasm member");
+        let mut sbom = None;
+        let mut i18n = BTreeMap::new();
+        for member in members {
+            let locale = member
+                .name
+                .strip_prefix("assets/i18n/")
+                .and_then(|file| file.strip_suffix(".json"))
+                .map(str::to_string);
+            match locale {
+                Some(locale) => {
+                    i18n.insert(locale, member);
+                }
+                None => sbom = Some(member),
+            }
+        }
+        Ok(Bundle {
+            locator: locator.clone(),
+            path,
+            manifest,
+            manifest_member,
+            wasm,
+            sbom,
+            i18n,
+        })
+    }
+
+    fn fetch_member(&self, manifest: &StoreLocator, name: &str) -> Result<Vec<u8>, StoreError> {
+        let member = member_locator(manifest, name)?;
+        let mut progress = |received: u64, total: Option<u64>| {
+            for observer in &self.observers {
+                observer.on_fetch_progress(&member, received, total);
+            }
+        };
+        self.fetch_bytes(&member, &mut progress)
+    }
+
+    /// Writes the members into a staging directory and swaps it into place,
+    /// so the cache never holds half a bundle.
+    fn commit_bundle(
+        &self,
+        locator: &StoreLocator,
+        members: &[(String, Vec<u8>, VerificationReport)],
+    ) -> Result<PathBuf, StoreError> {
+        let bundles = self.cache_root.join("bundles");
+        let key = hash_locator(locator);
+        let target = bundles.join(&key);
+        std_fs::create_dir_all(&bundles)?;
+        // Removed on drop unless renamed into place below; concurrent fetches
+        // of the same bundle each stage in their own directory.
+        let staging = tempfile::Builder::new()
+            .prefix(&format!(".{key}."))
+            .tempdir_in(&bundles)?;
+        for (name, bytes, _) in members {
+            let path = staging.path().join(name);
+            if let Some(parent) = path.parent() {
+                std_fs::create_dir_all(parent)?;
+            }
+            std_fs::write(path, bytes)?;
+        }
+        if target.exists() {
+            std_fs::remove_dir_all(&target)?;
+        }
+        std_fs::rename(staging.path(), &target)?;
+        debug!("cached bundle {:?} at {}", locator, target.display());
+        Ok(target)
+    }
+}
+
+fn member_locator(manifest: &StoreLocator, name: &str) -> Result<StoreLocator, StoreError> {
+    let relative = Path::new(name);
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(invalid(
+            manifest,
+            format!("member `{name}` must be a relative path inside the bundle"),
+        ));
+    }
+    match manifest {
+        StoreLocator::Fs { path, .. } => {
+            let member = path
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join(relative);
+            Ok(StoreLocator::Fs {
+                locator: member.display().to_string(),
+                path: member,
+            })
+        }
+        StoreLocator::Http(url) | StoreLocator::Https(url) => {
+            let member = url.join(name).map_err(|err| invalid(manifest, err))?;
+            Ok(if member.scheme() == "https" {
+                StoreLocator::Https(member)
+            } else {
+                StoreLocator::Http(member)
+            })
+        }
+        StoreLocator::Oci(_) | StoreLocator::Warg(_) => Err(invalid(
+            manifest,
+            "bundles need a file or HTTP(S) manifest locator",
+        )),
+    }
+}
+
+fn joined<T>(
+    handle: thread::ScopedJoinHandle<'_, Result<T, StoreError>>,
+    locator: &StoreLocator,
+) -> Result<T, StoreError> {
+    handle
+        .join()
+        .unwrap_or_else(|_| Err(invalid(locator, "bundle fetch thread panicked")))
+}
+
+fn manifest_name(locator: &StoreLocator) -> String {
+    let name = match locator {
+        StoreLocator::Fs { path, .. } => path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+        StoreLocator::Http(url) | StoreLocator::Https(url) => url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back().map(str::to_string)),
+        StoreLocator::Oci(_) | StoreLocator::Warg(_) => None,
+    };
+    name.filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "component.manifest.json".to_string())
+}
+
+/// `hashes.component_wasm` (`blake3:<hex>`) as a required digest, if declared.
+fn manifest_wasm_digest(manifest: &Value) -> Result<Option<DigestPolicy>, String> {
+    let Some(declared) = manifest
+        .pointer("/hashes/component_wasm")
+        .and_then(Value::as_str)
+    else {
+        return Ok(None);
+    };
+    let hex = declared
+        .strip_prefix("blake3:")
+        .ok_or_else(|| format!("unsupported hashes.component_wasm `{declared}`"))?;
+    Ok(Some(DigestPolicy::blake3(Some(hex.to_string()), true)))
+}
+
+/// Checks a non-wasm member against its declared digest, or records its
+/// sha256 when none is declared.
+fn member_report(
+    options: &BundleOptions,
+    name: &str,
+    bytes: &[u8],
+) -> Result<VerificationReport, StoreError> {
+    let digest = match options.digests.get(name) {
+        Some(declared) => declared.verify(bytes)?,
+        None => VerifiedDigest::compute(DigestAlgorithm::Sha256, bytes),
+    };
+    Ok(VerificationReport {
+        digest: Some(digest),
+        signature: None,
+    })
+}
+
+fn is_not_found(err: &StoreError) -> bool {
+    match err {
+        StoreError::Io(err) => err.kind() == std::io::ErrorKind::NotFound,
+        #[cfg(feature = "http")]
+        StoreError::Http(err) => err.status() == Some(reqwest::StatusCode::NOT_FOUND),
+        _ => false,
+    }
+}
+
+fn invalid(locator: &StoreLocator, reason: impl ToString) -> StoreError {
+    StoreError::InvalidLocator {
+        locator: locator.as_cache_key(),
+        reason: reason.to_string(),
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::fs as std_fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{DigestPolicy, SignaturePolicy, StoreError, VerificationPolicy};
+
+/// Conventional catalog file name, looked up next to manifests and in the
+/// working directory.
+pub const CATALOG_FILE: &str = "components.toml";
+
+/// Logical component names mapped to locators, loaded from a
+/// `components.toml`:
+///
+/// ```toml
+/// [components.slack-adapter]
+/// locator = "oci://ghcr.io/greentic/slack-adapter:1.4.2"
+/// version = "^1.4"
+/// digest = "sha256:9f2c..."
+/// signature = "required"
+/// ```
+///
+/// Only `locator` is required. Names let flows and hosts refer to
+/// `slack-adapter` while the catalog decides where it comes from.
+///
+/// Registries whose published index should be searched are listed
+/// alongside:
+///
+/// ```toml
+/// [registries.acme]
+/// index = "https://components.acme.dev/index.json"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Catalog {
+    #[serde(default)]
+    components: BTreeMap<String, CatalogEntry>,
+    #[serde(default)]
+    registries: BTreeMap<String, CatalogRegistry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CatalogEntry {
+    pub locator: String,
+    /// Semver requirement the component's `describe` version must satisfy.
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Expected artifact digest, `sha256:<hex>` or bare hex.
+    #[serde(default)]
+    pub digest: Option<String>,
+    #[serde(default)]
+    pub signature: CatalogSignature,
+}
+
+/// A registry publishing an `index.json` catalog of its components.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CatalogRegistry {
+    /// Index location: a local path or an `http(s)://` URL.
+    pub index: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CatalogSignature {
+    #[default]
+    Disabled,
+    Optional,
+    Required,
+}
+
+impl Catalog {
+    pub fn from_path(path: &Path) -> Result<Self, StoreError> {
+        let raw = std_fs::read_to_string(path)?;
+        Self::from_toml_str(&raw).map_err(|err| match err {
+            StoreError::InvalidCatalog(reason) => {
+                StoreError::InvalidCatalog(format!("{}: {reason}", path.display()))
+            }
+            other => other,
+        })
+    }
+
+    pub fn from_toml_str(raw: &str) -> Result<Self, StoreError> {
+        let catalog: Catalog =
+            toml::from_str(raw).map_err(|err| StoreError::InvalidCatalog(err.to_string()))?;
+        for (name, entry) in &catalog.components {
+            if name.contains("://") || name.contains('/') {
+                return Err(StoreError::InvalidCatalog(format!(
+                    "component name `{name}` looks like a locator"
+                )));
+            }
+            if entry.locator.trim().is_empty() {
+                return Err(StoreError::InvalidCatalog(format!(
+                    "component `{name}` has an empty locator"
+                )));
+            }
+        }
+        for (name, registry) in &catalog.registries {
+            if registry.index.trim().is_empty() {
+                return Err(StoreError::InvalidCatalog(format!(
+                    "registry `{name}` has an empty index"
+                )));
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// Loads `dir/components.toml` when it exists.
+    pub fn discover(dir: &Path) -> Result<Option<Self>, StoreError> {
+        let path = dir.join(CATALOG_FILE);
+        if path.is_file() {
+            Self::from_path(&path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CatalogEntry> {
+        self.components.get(name)
+    }
+
+    /// Entry for `name_or_locator` when it is a catalog name; raw locators
+    /// and unknown names return `None` and are used as-is.
+    pub fn lookup(&self, name_or_locator: &str) -> Option<&CatalogEntry> {
+        if name_or_locator.contains("://") {
+            return None;
+        }
+        self.get(name_or_locator)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.components.keys().map(String::as_str)
+    }
+
+    pub fn registries(&self) -> impl Iterator<Item = (&str, &CatalogRegistry)> {
+        self.registries
+            .iter()
+            .map(|(name, registry)| (name.as_str(), registry))
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, entry: CatalogEntry) {
+        self.components.insert(name.into(), entry);
+    }
+}
+
+impl CatalogEntry {
+    pub fn new(locator: impl Into<String>) -> Self {
+        Self {
+            locator: locator.into(),
+            version: None,
+            digest: None,
+            signature: CatalogSignature::Disabled,
+        }
+    }
+
+    /// Digest without the `sha256:` prefix, as the store compares it.
+    pub fn digest_hex(&self) -> Option<&str> {
+        self.digest
+            .as_deref()
+            .map(|digest| digest.strip_prefix("sha256:").unwrap_or(digest))
+    }
+
+    /// Verification the entry asks for: its digest must match when set, and
+    /// its signature setting applies.
+    pub fn verification_policy(&self) -> VerificationPolicy {
+        VerificationPolicy {
+            digest: self
+                .digest_hex()
+                .map(|digest| DigestPolicy::sha256(Some(digest.to_string()), true)),
+            signature: match self.signature {
+                CatalogSignature::Disabled => None,
+                CatalogSignature::Optional => Some(SignaturePolicy::cosign_optional()),
+                CatalogSignature::Required => Some(SignaturePolicy::cosign_required()),
+            },
+            digest_sidecars: false,
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs as std_fs;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use serde::{Deserialize, Serialize};
+
+use crate::StoreError;
+
+/// Index in each cache directory recording the nonce every encrypted file
+/// was sealed with.
+pub const CACHE_INDEX_FILE: &str = "store-index.json";
+const NONCE_LEN: usize = 12;
+
+/// AES-256-GCM key that seals cached artifacts at rest. Keep it outside the
+/// cache root. It uses the same format and variable as the CLI cache key,
+/// so one key serves both caches.
+#[derive(Clone)]
+pub struct CacheEncryptionKey(Key<Aes256Gcm>);
+
+impl CacheEncryptionKey {
+    /// Environment variable [`load`](Self::load) reads: 64 hex characters.
+    pub const ENV_VAR: &'static str = "GREENTIC_CACHE_ENCRYPTION_KEY";
+    /// Keyring service and account [`load`](Self::load) falls back to.
+    pub const KEYRING_SERVICE: &'static str = "greentic-component";
+    pub const KEYRING_ACCOUNT: &'static str = "cache-encryption-key";
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes.into())
+    }
+
+    pub fn from_hex(raw: &str) -> Result<Self, StoreError> {
+        let bytes = hex::decode(raw.trim())
+            .map_err(|_| StoreError::Encryption("cache encryption key must be hex".into()))?;
+        let bytes: [u8; 32] = bytes.try_into().map_err(|bytes: Vec<u8>| {
+            StoreError::Encryption(format!(
+                "cache encryption key must be 32 bytes, got {}",
+                bytes.len()
+            ))
+        })?;
+        Ok(Self::from_bytes(bytes))
+    }
+
+    /// The key from [`ENV_VAR`](Self::ENV_VAR), else, with the `keyring`
+    /// feature, from the OS keyring. `None` when neither holds one; a
+    /// keyring that cannot be reached is logged and treated as empty.
+    pub fn load() -> Result<Option<Self>, StoreError> {
+        if let Some(raw) = std::env::var(Self::ENV_VAR)
+            .ok()
+            .filter(|raw| !raw.trim().is_empty())
+        {
+            return Self::from_hex(&raw).map(Some);
+        }
+        #[cfg(feature = "keyring")]
+        {
+            let entry = keyring::Entry::new(Self::KEYRING_SERVICE, Self::KEYRING_ACCOUNT)
+                .and_then(|entry| entry.get_password());
+            match entry {
+                Ok(raw) => Self::from_hex(&raw).map(Some),
+                Err(keyring::Error::NoEntry) => Ok(None),
+                Err(err) => {
+                    tracing::warn!(
+                        "cache encryption disabled: keyring entry {}/{} unavailable: {err}",
+                        Self::KEYRING_SERVICE,
+                        Self::KEYRING_ACCOUNT
+                    );
+                    Ok(None)
+                }
+            }
+        }
+        #[cfg(not(feature = "keyring"))]
+        Ok(None)
+    }
+
+    /// `plaintext` sealed under a fresh nonce and bound to the cache file
+    /// `name`, with the hex nonce to record in the index.
+    pub(crate) fn seal(
+        &self,
+        name: &str,
+        plaintext: &[u8],
+    ) -> Result<(Vec<u8>, String), StoreError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(&self.0)
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| StoreError::Encryption(format!("encrypting {name} failed")))?;
+        Ok((ciphertext, hex::encode(nonce)))
+    }
+
+    /// The cache file `name` sealed with `nonce`; the error says why it did
+    /// not open.
+    pub(crate) fn open(
+        &self,
+        name: &str,
+        nonce: &str,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let nonce = hex::decode(nonce)
+            .ok()
+            .filter(|nonce| nonce.len() == NONCE_LEN)
+            .ok_or_else(|| "index entry has a malformed nonce".to_string())?;
+        Aes256Gcm::new(&self.0)
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| "bytes do not decrypt (wrong key or modified ciphertext)".to_string())
+    }
+}
+
+impl fmt::Debug for CacheEncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CacheEncryptionKey(..)")
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct CacheIndex {
+    /// Keyed by file name within the cache directory.
+    #[serde(default)]
+    pub entries: BTreeMap<String, IndexEntry>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct IndexEntry {
+    /// Hex AES-GCM nonce; `None` for files stored in plaintext.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+impl CacheIndex {
+    pub fn read(dir: &Path) -> Result<Self, StoreError> {
+        let path = dir.join(CACHE_INDEX_FILE);
+        match std_fs::read(&path) {
+            Ok(raw) => serde_json::from_slice(&raw).map_err(|err| {
+                StoreError::Encryption(format!("corrupt cache index {}: {err}", path.display()))
+            }),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn write(&self, dir: &Path) -> Result<(), StoreError> {
+        let path = dir.join(CACHE_INDEX_FILE);
+        let raw = serde_json::to_vec_pretty(self).map_err(|err| {
+            StoreError::Encryption(format!("serialize cache index {}: {err}", path.display()))
+        })?;
+        let mut staged = tempfile::NamedTempFile::new_in(dir)?;
+        staged.write_all(&raw)?;
+        staged.persist(&path).map_err(|err| err.error)?;
+        Ok(())
+    }
+
+    pub fn nonce(&self, name: &str) -> Option<&str> {
+        self.entries.get(name)?.nonce.as_deref()
+    }
+}
@@ -1,32 +1,245 @@
+#[cfg(feature = "http")]
+use std::fs as std_fs;
+#[cfg(feature = "http")]
+use std::io::Read;
+#[cfg(feature = "http")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "http")]
+use std::time::Duration;
+
 #[cfg(feature = "http")]
 use reqwest::blocking::Client;
 #[cfg(feature = "http")]
 use reqwest::header::{ACCEPT, USER_AGENT};
 #[cfg(feature = "http")]
+use reqwest::{Certificate, Identity, NoProxy, Proxy, StatusCode};
+#[cfg(feature = "http")]
+use tracing::debug;
+#[cfg(feature = "http")]
 use url::Url;
 
 use crate::StoreError;
+#[cfg(feature = "http")]
+use crate::{DigestAlgorithm, DigestPolicy};
+
+/// Query parameter carrying an expected digest, e.g.
+/// `https://host/c.wasm?digest=sha256:<hex>`.
+pub const DIGEST_QUERY_PARAM: &str = "digest";
+
+/// Largest buffer reserved up front from an announced `Content-Length`.
+#[cfg(feature = "http")]
+const PREALLOC_LIMIT: u64 = 64 * 1024 * 1024;
 
 #[cfg(feature = "http")]
 const USER_AGENT_VALUE: &str = concat!("greentic-component/", env!("CARGO_PKG_VERSION"));
 
+/// Network settings for the store's HTTP client. The default matches plain
+/// `reqwest`: `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY` are honoured and the
+/// built-in web PKI roots are trusted.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone, Default)]
+pub struct HttpConfig {
+    /// Proxy URL for every request; replaces the environment proxies.
+    pub proxy: Option<String>,
+    /// Hosts that bypass `proxy`, in `NO_PROXY` syntax.
+    pub no_proxy: Option<String>,
+    /// Ignore the proxy environment variables.
+    pub ignore_env_proxy: bool,
+    /// PEM files with extra root certificates; bundles may hold several.
+    pub root_certificates: Vec<PathBuf>,
+    /// Trust only `root_certificates`, e.g. behind a TLS-intercepting proxy.
+    pub disable_builtin_roots: bool,
+    /// PEM file holding the client certificate chain and private key (mTLS).
+    pub client_identity: Option<PathBuf>,
+    /// Whole-request timeout.
+    pub timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+}
+
+#[cfg(feature = "http")]
+impl HttpConfig {
+    pub fn with_proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    pub fn with_no_proxy(mut self, hosts: impl Into<String>) -> Self {
+        self.no_proxy = Some(hosts.into());
+        self
+    }
+
+    pub fn without_env_proxy(mut self) -> Self {
+        self.ignore_env_proxy = true;
+        self
+    }
+
+    pub fn with_root_certificate(mut self, pem: impl Into<PathBuf>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    pub fn without_builtin_roots(mut self) -> Self {
+        self.disable_builtin_roots = true;
+        self
+    }
+
+    pub fn with_client_identity(mut self, pem: impl Into<PathBuf>) -> Self {
+        self.client_identity = Some(pem.into());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+}
+
 #[cfg(feature = "http")]
 pub fn build_client() -> Result<Client, StoreError> {
-    Client::builder()
-        .user_agent(USER_AGENT_VALUE)
-        .build()
-        .map_err(StoreError::from)
+    build_client_with(&HttpConfig::default())
+}
+
+#[cfg(feature = "http")]
+pub fn build_client_with(config: &HttpConfig) -> Result<Client, StoreError> {
+    let mut builder = Client::builder().user_agent(USER_AGENT_VALUE);
+    if config.ignore_env_proxy || config.proxy.is_some() {
+        builder = builder.no_proxy();
+    }
+    if let Some(proxy) = &config.proxy {
+        let proxy = Proxy::all(proxy.as_str())
+            .map_err(|err| StoreError::HttpConfig(format!("proxy `{proxy}`: {err}")))?
+            .no_proxy(config.no_proxy.as_deref().and_then(NoProxy::from_string));
+        builder = builder.proxy(proxy);
+    }
+    for path in &config.root_certificates {
+        let pem = read_pem(path)?;
+        let certificates = Certificate::from_pem_bundle(&pem)
+            .map_err(|err| StoreError::HttpConfig(format!("{}: {err}", path.display())))?;
+        if certificates.is_empty() {
+            return Err(StoreError::HttpConfig(format!(
+                "{}: no PEM certificates found",
+                path.display()
+            )));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    if config.disable_builtin_roots {
+        if config.root_certificates.is_empty() {
+            return Err(StoreError::HttpConfig(
+                "built-in roots disabled but no root certificates configured".into(),
+            ));
+        }
+        builder = builder.tls_built_in_root_certs(false);
+    }
+    if let Some(path) = &config.client_identity {
+        let identity = Identity::from_pem(&read_pem(path)?)
+            .map_err(|err| StoreError::HttpConfig(format!("{}: {err}", path.display())))?;
+        builder = builder.identity(identity);
+    }
+    if let Some(timeout) = config.timeout {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(timeout) = config.connect_timeout {
+        builder = builder.connect_timeout(timeout);
+    }
+    builder.build().map_err(StoreError::from)
 }
 
 #[cfg(feature = "http")]
-pub fn fetch(client: &Client, url: &Url) -> Result<Vec<u8>, StoreError> {
+fn read_pem(path: &Path) -> Result<Vec<u8>, StoreError> {
+    std_fs::read(path).map_err(|err| StoreError::HttpConfig(format!("{}: {err}", path.display())))
+}
+
+#[cfg(feature = "http")]
+pub fn fetch(
+    client: &Client,
+    url: &Url,
+    progress: &mut dyn FnMut(u64, Option<u64>),
+) -> Result<Vec<u8>, StoreError> {
     let response = client
-        .get(url.clone())
+        .get(without_digest_param(url))
         .header(USER_AGENT, USER_AGENT_VALUE)
         .header(ACCEPT, "application/wasm,application/octet-stream")
         .send()?;
-    let response = response.error_for_status()?;
-    Ok(response.bytes()?.to_vec())
+    let mut response = response.error_for_status()?;
+    let total = response.content_length();
+    let mut bytes = Vec::with_capacity(total.unwrap_or_default().min(PREALLOC_LIMIT) as usize);
+    let mut chunk = vec![0u8; 64 * 1024];
+    loop {
+        let read = response.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        bytes.extend_from_slice(&chunk[..read]);
+        progress(bytes.len() as u64, total);
+    }
+    Ok(bytes)
+}
+
+/// The digest the publisher advertises for `url`: the `digest` query
+/// parameter when present, otherwise, when `sidecars` is set, a
+/// `<url>.blake3` or `<url>.sha256` sidecar. Sidecars that cannot be reached
+/// are treated as absent, so a caller that must have a digest should mark
+/// its policy as required.
+#[cfg(feature = "http")]
+pub fn published_digest(
+    client: &Client,
+    url: &Url,
+    sidecars: bool,
+) -> Result<Option<DigestPolicy>, StoreError> {
+    if let Some((_, value)) = url.query_pairs().find(|(key, _)| key == DIGEST_QUERY_PARAM) {
+        return Ok(Some(DigestPolicy::parse(&value, DigestAlgorithm::Sha256)?));
+    }
+    if !sidecars {
+        return Ok(None);
+    }
+    let base = without_digest_param(url);
+    for algorithm in [DigestAlgorithm::Blake3, DigestAlgorithm::Sha256] {
+        let mut sidecar = base.clone();
+        sidecar.set_path(&format!("{}.{}", base.path(), algorithm.name()));
+        let response = match client
+            .get(sidecar.clone())
+            .header(USER_AGENT, USER_AGENT_VALUE)
+            .send()
+        {
+            Ok(response) => response,
+            Err(err) => {
+                debug!("digest sidecar {sidecar} unavailable: {err}");
+                continue;
+            }
+        };
+        if response.status() == StatusCode::NOT_FOUND {
+            continue;
+        }
+        let body = response.error_for_status()?.text()?;
+        // `sha256sum`/`b3sum` output: `<hex>  <file name>`.
+        let value = body.split_whitespace().next().unwrap_or_default();
+        return Ok(Some(DigestPolicy::parse(value, algorithm)?));
+    }
+    Ok(None)
+}
+
+#[cfg(feature = "http")]
+fn without_digest_param(url: &Url) -> Url {
+    let mut stripped = url.clone();
+    let pairs = url
+        .query_pairs()
+        .filter(|(key, _)| key != DIGEST_QUERY_PARAM)
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+    if pairs.is_empty() {
+        stripped.set_query(None);
+    } else {
+        stripped.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    stripped
 }
 
 #[cfg(not(feature = "http"))]
@@ -35,6 +248,10 @@ pub fn build_client() -> Result<(), StoreError> {
 }
 
 #[cfg(not(feature = "http"))]
-pub fn fetch(_client: &(), _url: &url::Url) -> Result<Vec<u8>, StoreError> {
+pub fn fetch(
+    _client: &(),
+    _url: &url::Url,
+    _progress: &mut dyn FnMut(u64, Option<u64>),
+) -> Result<Vec<u8>, StoreError> {
     Err(StoreError::UnsupportedScheme("http".into()))
 }
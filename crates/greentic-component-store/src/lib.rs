@@ -1,29 +1,49 @@
 use std::fs as std_fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use percent_encoding::percent_decode_str;
 use sha2::{Digest as _, Sha256};
 use thiserror::Error;
-use tracing::debug;
+use tracing::{debug, warn};
 use url::Url;
 
+use crate::encryption::CacheIndex;
+
+pub mod bundle;
+pub mod catalog;
+pub mod encryption;
 pub mod fs;
 #[cfg(feature = "http")]
 pub mod http;
+pub mod observer;
 pub mod oci;
 pub mod verify;
 pub mod warg;
 
+pub use bundle::{BUNDLE_SBOM, Bundle, BundleMember, BundleOptions};
+pub use catalog::{CATALOG_FILE, Catalog, CatalogEntry, CatalogRegistry, CatalogSignature};
+pub use encryption::{CACHE_INDEX_FILE, CacheEncryptionKey};
+#[cfg(feature = "http")]
+pub use http::HttpConfig;
+pub use observer::{FetchDecision, FetchObserver};
 pub use verify::{
     DigestAlgorithm, DigestPolicy, SignaturePolicy, VerificationError, VerificationPolicy,
     VerificationReport, VerifiedDigest, VerifiedSignature,
 };
 
+/// Environment variable [`ComponentStore::with_default_cache`] reads the
+/// read-only system cache directory from.
+pub const SYSTEM_CACHE_ENV: &str = "GREENTIC_COMPONENT_SYSTEM_CACHE";
+
 #[derive(Debug, Clone)]
 pub struct ComponentStore {
     cache_root: PathBuf,
+    system_cache: Option<PathBuf>,
+    encryption: Option<CacheEncryptionKey>,
     #[cfg(feature = "http")]
     http_client: reqwest::blocking::Client,
+    observers: Vec<Arc<dyn FetchObserver>>,
 }
 
 impl ComponentStore {
@@ -32,20 +52,131 @@ impl ComponentStore {
         std_fs::create_dir_all(&cache_root)?;
         Ok(Self {
             cache_root,
+            system_cache: None,
+            encryption: None,
             #[cfg(feature = "http")]
             http_client: http::build_client()?,
+            observers: Vec::new(),
+        })
+    }
+
+    /// Like [`ComponentStore::new`], with proxy, TLS and timeout settings for
+    /// HTTP(S) locators.
+    #[cfg(feature = "http")]
+    pub fn with_http_config(
+        cache_root: impl AsRef<Path>,
+        config: &HttpConfig,
+    ) -> Result<Self, StoreError> {
+        let cache_root = cache_root.as_ref().to_path_buf();
+        std_fs::create_dir_all(&cache_root)?;
+        Ok(Self {
+            cache_root,
+            system_cache: None,
+            encryption: None,
+            http_client: http::build_client_with(config)?,
+            observers: Vec::new(),
         })
     }
 
+    /// A store caching in the temp directory, with the system cache named by
+    /// [`SYSTEM_CACHE_ENV`] underneath it when that is set and encryption
+    /// under [`CacheEncryptionKey::load`]'s key when there is one.
     pub fn with_default_cache() -> Result<Self, StoreError> {
         let default = default_cache_dir();
-        Self::new(default)
+        let mut store = Self::new(default)?;
+        store.encryption = CacheEncryptionKey::load()?;
+        Ok(match std::env::var_os(SYSTEM_CACHE_ENV) {
+            Some(dir) if !dir.is_empty() => store.with_system_cache(dir),
+            _ => store,
+        })
+    }
+
+    /// Stores artifacts encrypted with `key` under a fresh nonce per file,
+    /// recorded in each cache directory's [`CACHE_INDEX_FILE`], and decrypts
+    /// them transparently on read. Plaintext entries are fetched again and
+    /// stored encrypted; encrypted entries fail to load without the key.
+    /// [`StoreArtifact::path`] then names the sealed file, so read
+    /// [`StoreArtifact::bytes`] instead. Bundle directories are written as
+    /// published.
+    pub fn with_encryption_key(mut self, key: CacheEncryptionKey) -> Self {
+        self.encryption = Some(key);
+        self
     }
 
+    /// Looks artifacts up in `dir` when the cache root misses. The directory
+    /// is only read, so it can be provisioned into an immutable image; new
+    /// fetches are written to the cache root.
+    pub fn with_system_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.system_cache = Some(dir.into());
+        self
+    }
+
+    /// The writable cache that new downloads are stored in. Artifacts found
+    /// in the [system cache](Self::system_cache) are served from there and
+    /// not copied here.
     pub fn cache_root(&self) -> &Path {
         &self.cache_root
     }
 
+    /// The read-only cache consulted when the cache root misses, if any.
+    pub fn system_cache(&self) -> Option<&Path> {
+        self.system_cache.as_deref()
+    }
+
+    /// `file_name` in the cache root, else in the system cache.
+    fn cached(&self, file_name: &str) -> Option<PathBuf> {
+        std::iter::once(self.cache_root.as_path())
+            .chain(self.system_cache.as_deref())
+            .map(|dir| dir.join(file_name))
+            .find(|path| path.exists())
+    }
+
+    /// The contents of `file_name` from the cache root, else the system
+    /// cache, decrypted when its directory's index records a nonce. `None`
+    /// when neither holds it, or when a plaintext or undecryptable copy
+    /// should be fetched again.
+    fn read_cached(&self, file_name: &str) -> Result<Option<(PathBuf, Vec<u8>)>, StoreError> {
+        let Some(path) = self.cached(file_name) else {
+            return Ok(None);
+        };
+        let stored = std_fs::read(&path)?;
+        let index = CacheIndex::read(path.parent().unwrap_or(&self.cache_root))?;
+        let bytes = match (&self.encryption, index.nonce(file_name)) {
+            (None, None) => stored,
+            (None, Some(_)) => {
+                return Err(StoreError::Encryption(format!(
+                    "cached {} is encrypted; configure the cache encryption key to read it",
+                    path.display()
+                )));
+            }
+            (Some(_), None) => {
+                warn!(
+                    "cached {} is not encrypted; fetching it again",
+                    path.display()
+                );
+                return Ok(None);
+            }
+            (Some(key), Some(nonce)) => match key.open(file_name, nonce, &stored) {
+                Ok(plaintext) => plaintext,
+                Err(reason) => {
+                    warn!(
+                        "ignoring cached {}: {reason}; fetching it again",
+                        path.display()
+                    );
+                    return Ok(None);
+                }
+            },
+        };
+        Ok(Some((path, bytes)))
+    }
+
+    /// Reports every fetch made through this store to `observer`, which may
+    /// also veto fetches that would reach a source.
+    pub fn with_observer(mut self, observer: Arc<dyn FetchObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
     pub fn fetch_from_str(
         &self,
         locator: &str,
@@ -60,17 +191,42 @@ impl ComponentStore {
         locator: &StoreLocator,
         policy: &VerificationPolicy,
     ) -> Result<StoreArtifact, StoreError> {
+        let result = self.fetch_observed(locator, policy);
+        for observer in &self.observers {
+            match &result {
+                Ok(artifact) => {
+                    observer.on_fetch_verified(locator, &artifact.verification);
+                    observer.on_fetch_cached(locator, &artifact.path, artifact.from_cache);
+                }
+                Err(err) => observer.on_fetch_error(locator, err),
+            }
+        }
+        result
+    }
+
+    fn fetch_observed(
+        &self,
+        locator: &StoreLocator,
+        policy: &VerificationPolicy,
+    ) -> Result<StoreArtifact, StoreError> {
+        let pinned;
+        let policy = match locator.pinned_digest()? {
+            Some(pin) => {
+                pinned = with_pinned_digest(locator, policy, pin)?;
+                &pinned
+            }
+            None => policy,
+        };
         if let Some(expected) = policy.digest.as_ref().and_then(|d| d.expected()) {
-            let cache_path = self.cache_root.join(format!("{expected}.wasm"));
-            if cache_path.exists() {
+            if let Some((cache_path, bytes)) = self.read_cached(&format!("{expected}.wasm"))? {
                 debug!("cache hit for digest {expected}");
-                let bytes = std_fs::read(&cache_path)?;
                 let report = policy.verify(&bytes)?;
                 return Ok(StoreArtifact {
                     locator: locator.clone(),
                     path: cache_path,
                     bytes,
                     verification: report,
+                    from_cache: true,
                 });
             }
         }
@@ -79,7 +235,32 @@ impl ComponentStore {
             return Ok(artifact);
         }
 
-        let bytes = self.fetch_bytes(locator)?;
+        for observer in &self.observers {
+            if let FetchDecision::Deny(reason) = observer.on_fetch_start(locator) {
+                return Err(StoreError::FetchDenied {
+                    locator: locator.as_cache_key(),
+                    reason,
+                });
+            }
+        }
+        let mut progress = |received: u64, total: Option<u64>| {
+            for observer in &self.observers {
+                observer.on_fetch_progress(locator, received, total);
+            }
+        };
+        let bytes = self.fetch_bytes(locator, &mut progress)?;
+        let published;
+        let policy = match self.published_digest(locator, policy)? {
+            Some(digest) => {
+                published = VerificationPolicy {
+                    digest: Some(digest),
+                    signature: policy.signature.clone(),
+                    digest_sidecars: policy.digest_sidecars,
+                };
+                &published
+            }
+            None => policy,
+        };
         let report = policy.verify(&bytes)?;
         let digest = report
             .digest
@@ -94,6 +275,7 @@ impl ComponentStore {
                 digest: Some(digest),
                 signature: report.signature,
             },
+            from_cache: false,
         })
     }
 
@@ -103,18 +285,22 @@ impl ComponentStore {
         policy: &VerificationPolicy,
     ) -> Result<Option<StoreArtifact>, StoreError> {
         let cache_key = self.compute_cache_key(locator);
-        let cache_path = self.cache_root.join(format!("{cache_key}.wasm"));
-        if !cache_path.exists() {
+        let Some((cache_path, bytes)) = self.read_cached(&format!("{cache_key}.wasm"))? else {
             return Ok(None);
-        }
+        };
 
-        let bytes = std_fs::read(&cache_path)?;
         let report = policy.verify(&bytes)?;
         let digest = report
             .digest
             .clone()
             .unwrap_or_else(|| VerifiedDigest::compute(DigestAlgorithm::Sha256, &bytes));
-        let digest_path = self.persist(locator, &bytes, &digest)?;
+        let digest_path = if cache_path.parent() == Some(self.cache_root.as_path()) {
+            self.persist(locator, &bytes, &digest)?
+        } else {
+            // Served from the system cache, which is never written to.
+            self.cached(&format!("{}.wasm", digest.value))
+                .unwrap_or(cache_path)
+        };
         Ok(Some(StoreArtifact {
             locator: locator.clone(),
             path: digest_path,
@@ -123,16 +309,42 @@ impl ComponentStore {
                 digest: Some(digest),
                 signature: report.signature,
             },
+            from_cache: true,
         }))
     }
 
-    fn fetch_bytes(&self, locator: &StoreLocator) -> Result<Vec<u8>, StoreError> {
+    /// For HTTP(S) locators whose policy pins no digest, the digest published
+    /// with the artifact: the `digest` query parameter, or the sidecars when
+    /// [`VerificationPolicy::digest_sidecars`] allows requesting them (see
+    /// [`http::published_digest`]).
+    fn published_digest(
+        &self,
+        locator: &StoreLocator,
+        policy: &VerificationPolicy,
+    ) -> Result<Option<DigestPolicy>, StoreError> {
+        if policy.digest.as_ref().and_then(|d| d.expected()).is_some() {
+            return Ok(None);
+        }
         match locator {
+            #[cfg(feature = "http")]
+            StoreLocator::Http(url) | StoreLocator::Https(url) => {
+                http::published_digest(&self.http_client, url, policy.digest_sidecars)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn fetch_bytes(
+        &self,
+        locator: &StoreLocator,
+        progress: &mut dyn FnMut(u64, Option<u64>),
+    ) -> Result<Vec<u8>, StoreError> {
+        let bytes = match locator {
             StoreLocator::Fs { path, .. } => crate::fs::fetch(path),
             StoreLocator::Http(url) => {
                 #[cfg(feature = "http")]
                 {
-                    http::fetch(&self.http_client, url)
+                    return http::fetch(&self.http_client, url, progress);
                 }
                 #[cfg(not(feature = "http"))]
                 {
@@ -143,7 +355,7 @@ impl ComponentStore {
             StoreLocator::Https(url) => {
                 #[cfg(feature = "http")]
                 {
-                    http::fetch(&self.http_client, url)
+                    return http::fetch(&self.http_client, url, progress);
                 }
                 #[cfg(not(feature = "http"))]
                 {
@@ -153,7 +365,9 @@ impl ComponentStore {
             }
             StoreLocator::Oci(reference) => oci::fetch(reference),
             StoreLocator::Warg(reference) => warg::fetch(reference),
-        }
+        }?;
+        progress(bytes.len() as u64, Some(bytes.len() as u64));
+        Ok(bytes)
     }
 
     fn persist(
@@ -162,27 +376,72 @@ impl ComponentStore {
         bytes: &[u8],
         digest: &VerifiedDigest,
     ) -> Result<PathBuf, StoreError> {
-        let mut file_name = digest.value.clone();
-        file_name.push_str(".wasm");
+        let file_name = format!("{}.wasm", digest.value);
         let path = self.cache_root.join(&file_name);
-        std_fs::write(&path, bytes)?;
+        let mut written = vec![(
+            file_name.clone(),
+            self.write_cached(&path, &file_name, bytes)?,
+        )];
 
-        let locator_cache = self
-            .cache_root
-            .join(format!("{}.wasm", self.compute_cache_key(locator)));
-        if locator_cache != path
-            && let Err(err) = std_fs::write(&locator_cache, bytes)
-        {
-            debug!(
-                "failed to update locator cache at {}: {}",
-                locator_cache.display(),
-                err
-            );
+        let locator_name = format!("{}.wasm", self.compute_cache_key(locator));
+        if locator_name != file_name {
+            let locator_cache = self.cache_root.join(&locator_name);
+            match self.write_cached(&locator_cache, &locator_name, bytes) {
+                Ok(nonce) => written.push((locator_name, nonce)),
+                Err(err) => debug!(
+                    "failed to update locator cache at {}: {}",
+                    locator_cache.display(),
+                    err
+                ),
+            }
         }
+        self.record_nonces(written)?;
 
         debug!("cached artifact {:?} at {}", locator, path.display());
         Ok(path)
     }
+
+    /// Writes `bytes` to `path`, sealed when the store has a key, and returns
+    /// the nonce used.
+    fn write_cached(
+        &self,
+        path: &Path,
+        file_name: &str,
+        bytes: &[u8],
+    ) -> Result<Option<String>, StoreError> {
+        match &self.encryption {
+            Some(key) => {
+                let (ciphertext, nonce) = key.seal(file_name, bytes)?;
+                std_fs::write(path, ciphertext)?;
+                Ok(Some(nonce))
+            }
+            None => {
+                std_fs::write(path, bytes)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Records the nonces of freshly written cache files. A store without a
+    /// key only touches an existing index, to forget nonces of files it
+    /// overwrote in plaintext.
+    fn record_nonces(&self, written: Vec<(String, Option<String>)>) -> Result<(), StoreError> {
+        if self.encryption.is_none() && !self.cache_root.join(CACHE_INDEX_FILE).exists() {
+            return Ok(());
+        }
+        let mut index = CacheIndex::read(&self.cache_root)?;
+        for (name, nonce) in written {
+            match nonce {
+                Some(nonce) => {
+                    index.entries.entry(name).or_default().nonce = Some(nonce);
+                }
+                None => {
+                    index.entries.remove(&name);
+                }
+            }
+        }
+        index.write(&self.cache_root)
+    }
 }
 
 fn default_cache_dir() -> PathBuf {
@@ -199,7 +458,16 @@ pub enum StoreLocator {
 }
 
 impl StoreLocator {
+    /// Parses a path or `fs`, `file`, `http(s)`, `oci` or `warg` URL. A digest
+    /// pinned in the locator (see [`pinned_digest`](Self::pinned_digest)) is
+    /// checked for well-formedness here and enforced on fetch.
     pub fn parse(raw: &str) -> Result<Self, StoreError> {
+        let locator = Self::parse_unpinned(raw)?;
+        locator.pinned_digest()?;
+        Ok(locator)
+    }
+
+    fn parse_unpinned(raw: &str) -> Result<Self, StoreError> {
         if raw.contains("://") {
             let url = Url::parse(raw).map_err(|err| StoreError::InvalidLocator {
                 locator: raw.to_string(),
@@ -239,6 +507,48 @@ impl StoreLocator {
         }
     }
 
+    /// The digest this locator pins inline, as a required [`DigestPolicy`]:
+    /// an OCI reference ending in `@sha256:<hex>`, or a `#sha256=<hex>` /
+    /// `#blake3=<hex>` fragment on any URL locator.
+    pub fn pinned_digest(&self) -> Result<Option<DigestPolicy>, StoreError> {
+        let invalid = |reason: String| StoreError::InvalidLocator {
+            locator: self.as_cache_key(),
+            reason,
+        };
+        if let StoreLocator::Oci(reference) = self
+            && let Some((_, digest)) = reference
+                .rsplit('/')
+                .next()
+                .and_then(|name| name.split_once('@'))
+        {
+            let digest = digest.split('#').next().unwrap_or(digest);
+            return DigestPolicy::parse(digest, DigestAlgorithm::Sha256)
+                .map(Some)
+                .map_err(|err| invalid(err.to_string()));
+        }
+        let fragment = match self {
+            StoreLocator::Fs { locator, .. } if locator.contains("://") => Url::parse(locator)
+                .ok()
+                .and_then(|url| url.fragment().map(str::to_string)),
+            StoreLocator::Fs { .. } => None,
+            StoreLocator::Http(url) | StoreLocator::Https(url) => {
+                url.fragment().map(str::to_string)
+            }
+            StoreLocator::Oci(reference) | StoreLocator::Warg(reference) => Url::parse(reference)
+                .ok()
+                .and_then(|url| url.fragment().map(str::to_string)),
+        };
+        let Some(fragment) = fragment.filter(|fragment| !fragment.is_empty()) else {
+            return Ok(None);
+        };
+        let (algorithm, hex) = fragment
+            .split_once('=')
+            .ok_or_else(|| invalid(format!("pin `#{fragment}` must be `#<algorithm>=<hex>`")))?;
+        DigestPolicy::parse(&format!("{algorithm}:{hex}"), DigestAlgorithm::Sha256)
+            .map(Some)
+            .map_err(|err| invalid(err.to_string()))
+    }
+
     pub fn as_cache_key(&self) -> String {
         match self {
             StoreLocator::Fs { locator, .. } => locator.clone(),
@@ -254,6 +564,8 @@ pub struct StoreArtifact {
     pub path: PathBuf,
     pub bytes: Vec<u8>,
     pub verification: VerificationReport,
+    /// Whether the bytes came from the local cache rather than the source.
+    pub from_cache: bool,
 }
 
 #[derive(Debug, Error)]
@@ -267,8 +579,50 @@ pub enum StoreError {
     #[cfg(feature = "http")]
     #[error(transparent)]
     Http(#[from] reqwest::Error),
+    #[cfg(feature = "http")]
+    #[error("invalid HTTP client configuration: {0}")]
+    HttpConfig(String),
     #[error(transparent)]
     Verification(#[from] VerificationError),
+    #[error("fetch of `{locator}` denied: {reason}")]
+    FetchDenied { locator: String, reason: String },
+    #[error("invalid component catalog: {0}")]
+    InvalidCatalog(String),
+    #[error("cache encryption: {0}")]
+    Encryption(String),
+}
+
+/// `policy` with its digest check replaced by the locator's pin. A policy
+/// that already expects a digest must expect the pinned one.
+fn with_pinned_digest(
+    locator: &StoreLocator,
+    policy: &VerificationPolicy,
+    pin: DigestPolicy,
+) -> Result<VerificationPolicy, StoreError> {
+    if let Some(digest) = &policy.digest
+        && let Some(expected) = digest.expected()
+    {
+        let agrees = digest.algorithm() == pin.algorithm()
+            && pin
+                .expected()
+                .is_some_and(|pinned| pinned.eq_ignore_ascii_case(expected));
+        if !agrees {
+            return Err(StoreError::InvalidLocator {
+                locator: locator.as_cache_key(),
+                reason: format!(
+                    "pinned digest {}:{} conflicts with the policy's {}:{expected}",
+                    pin.algorithm().name(),
+                    pin.expected().unwrap_or_default(),
+                    digest.algorithm().name()
+                ),
+            });
+        }
+    }
+    Ok(VerificationPolicy {
+        digest: Some(pin),
+        signature: policy.signature.clone(),
+        digest_sidecars: policy.digest_sidecars,
+    })
 }
 
 fn decode_fs_path(url: &Url) -> Result<PathBuf, StoreError> {
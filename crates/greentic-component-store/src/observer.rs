@@ -0,0 +1,34 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::{StoreError, StoreLocator, VerificationReport};
+
+/// Whether a fetch may go to its source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchDecision {
+    Allow,
+    Deny(String),
+}
+
+/// Follows [`ComponentStore`](crate::ComponentStore) fetches, e.g. to drive
+/// progress bars or audit logs. Every method defaults to a no-op.
+///
+/// Cache hits skip `on_fetch_start` and `on_fetch_progress`; they still report
+/// `on_fetch_verified` and `on_fetch_cached` with `from_cache` set.
+pub trait FetchObserver: fmt::Debug + Send + Sync {
+    /// Called before the source is contacted. Returning
+    /// [`FetchDecision::Deny`] aborts the fetch with
+    /// [`StoreError::FetchDenied`], e.g. during a change freeze.
+    fn on_fetch_start(&self, _locator: &StoreLocator) -> FetchDecision {
+        FetchDecision::Allow
+    }
+
+    /// Bytes received so far and, when the source announces it, the total.
+    fn on_fetch_progress(&self, _locator: &StoreLocator, _received: u64, _total: Option<u64>) {}
+
+    fn on_fetch_verified(&self, _locator: &StoreLocator, _report: &VerificationReport) {}
+
+    fn on_fetch_cached(&self, _locator: &StoreLocator, _path: &Path, _from_cache: bool) {}
+
+    fn on_fetch_error(&self, _locator: &StoreLocator, _error: &StoreError) {}
+}
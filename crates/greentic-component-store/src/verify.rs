@@ -4,6 +4,17 @@ use thiserror::Error;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DigestAlgorithm {
     Sha256,
+    Blake3,
+}
+
+impl DigestAlgorithm {
+    /// Prefix used in `<algorithm>:<hex>` digests and sidecar extensions.
+    pub fn name(self) -> &'static str {
+        match self {
+            DigestAlgorithm::Sha256 => "sha256",
+            DigestAlgorithm::Blake3 => "blake3",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -22,20 +33,54 @@ impl DigestPolicy {
         }
     }
 
+    pub fn blake3(expected: Option<String>, required: bool) -> Self {
+        Self {
+            algorithm: DigestAlgorithm::Blake3,
+            expected,
+            required,
+        }
+    }
+
+    /// Parses a required digest written `<algorithm>:<hex>`, or bare hex in
+    /// the `default` algorithm.
+    pub fn parse(value: &str, default: DigestAlgorithm) -> Result<Self, VerificationError> {
+        let (algorithm, hex) = match value.split_once(':') {
+            Some(("sha256", hex)) => (DigestAlgorithm::Sha256, hex),
+            Some(("blake3", hex)) => (DigestAlgorithm::Blake3, hex),
+            Some((other, _)) => {
+                return Err(VerificationError::InvalidDigest(format!(
+                    "unsupported digest algorithm `{other}`"
+                )));
+            }
+            None => (default, value),
+        };
+        if hex.len() != 64 || !hex.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            return Err(VerificationError::InvalidDigest(format!(
+                "`{value}` is not a 64-character hex {} digest",
+                algorithm.name()
+            )));
+        }
+        let expected = Some(hex.to_ascii_lowercase());
+        Ok(match algorithm {
+            DigestAlgorithm::Sha256 => DigestPolicy::sha256(expected, true),
+            DigestAlgorithm::Blake3 => DigestPolicy::blake3(expected, true),
+        })
+    }
+
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        self.algorithm
+    }
+
     pub fn expected(&self) -> Option<&str> {
         self.expected.as_deref()
     }
 
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
     pub fn verify(&self, bytes: &[u8]) -> Result<VerifiedDigest, VerificationError> {
-        let computed = match self.algorithm {
-            DigestAlgorithm::Sha256 => {
-                let digest = Sha256::digest(bytes);
-                VerifiedDigest {
-                    algorithm: DigestAlgorithm::Sha256,
-                    value: hex::encode(digest),
-                }
-            }
-        };
+        let computed = VerifiedDigest::compute(self.algorithm, bytes);
 
         if let Some(expected) = &self.expected {
             if !equal_digest(expected, &computed.value) {
@@ -87,9 +132,18 @@ impl SignaturePolicy {
 pub struct VerificationPolicy {
     pub digest: Option<DigestPolicy>,
     pub signature: Option<SignaturePolicy>,
+    /// For HTTP(S) locators whose policy pins no digest, request the
+    /// `<url>.blake3` and `<url>.sha256` sidecars. Off by default because
+    /// every such fetch then sends extra requests to the host.
+    pub digest_sidecars: bool,
 }
 
 impl VerificationPolicy {
+    pub fn with_digest_sidecars(mut self) -> Self {
+        self.digest_sidecars = true;
+        self
+    }
+
     pub fn verify(&self, bytes: &[u8]) -> Result<VerificationReport, VerificationError> {
         let digest = match &self.digest {
             Some(policy) => Some(policy.verify(bytes)?),
@@ -125,6 +179,10 @@ impl VerifiedDigest {
                     value: hex::encode(digest),
                 }
             }
+            DigestAlgorithm::Blake3 => Self {
+                algorithm,
+                value: blake3::hash(bytes).to_hex().to_string(),
+            },
         }
     }
 }
@@ -140,6 +198,8 @@ pub enum VerificationError {
     DigestMissing,
     #[error("digest mismatch (expected {expected}, actual {actual})")]
     DigestMismatch { expected: String, actual: String },
+    #[error("invalid published digest: {0}")]
+    InvalidDigest(String),
     #[error("signature verification not implemented: {0}")]
     SignatureNotImplemented(String),
 }
@@ -0,0 +1,203 @@
+use std::fs;
+
+use greentic_component_store::{
+    BUNDLE_SBOM, BundleOptions, ComponentStore, DigestAlgorithm, DigestPolicy, StoreError,
+    VerificationError, VerificationPolicy,
+};
+use serde_json::json;
+
+fn write_bundle(dir: &std::path::Path, wasm: &[u8], declared: &[u8], wasm_name: &str) {
+    fs::write(dir.join("component.wasm"), wasm).unwrap();
+    fs::create_dir_all(dir.join("assets/i18n")).unwrap();
+    fs::write(dir.join("assets/i18n/en.json"), br#"{"hello":"Hello"}"#).unwrap();
+    let manifest = json!({
+        "artifacts": { "component_wasm": wasm_name },
+        "hashes": { "component_wasm": format!("blake3:{}", blake3::hash(declared).to_hex()) }
+    });
+    fs::write(
+        dir.join("component.manifest.json"),
+        serde_json::to_vec(&manifest).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn fetch_bundle_verifies_members_and_commits_together() {
+    let source = tempfile::tempdir().expect("source");
+    let cache = tempfile::tempdir().expect("cache");
+    write_bundle(
+        source.path(),
+        b"bundle-wasm",
+        b"bundle-wasm",
+        "component.wasm",
+    );
+    let store = ComponentStore::new(cache.path()).expect("store");
+    let manifest = source.path().join("component.manifest.json");
+
+    let bundle = store
+        .fetch_bundle(
+            &manifest.display().to_string(),
+            &VerificationPolicy::default(),
+        )
+        .expect("bundle");
+    assert_eq!(bundle.wasm.bytes, b"bundle-wasm");
+    let digest = bundle.wasm.verification.digest.as_ref().expect("digest");
+    assert_eq!(digest.algorithm, DigestAlgorithm::Blake3);
+    assert!(bundle.sbom.is_none(), "no sbom published");
+    assert_eq!(bundle.i18n.keys().collect::<Vec<_>>(), ["en"]);
+    assert_eq!(
+        fs::read(bundle.path.join("assets/i18n/en.json")).unwrap(),
+        bundle.i18n["en"].bytes
+    );
+    assert!(bundle.path.join("component.manifest.json").is_file());
+    assert_eq!(bundle.members().count(), 3);
+
+    let options = BundleOptions {
+        locales: vec!["en".into(), "fr".into()],
+        sbom: false,
+        ..Default::default()
+    };
+    let bundle = store
+        .fetch_bundle_with(
+            &manifest.display().to_string(),
+            &VerificationPolicy::default(),
+            &options,
+        )
+        .expect("missing locales are skipped");
+    assert_eq!(bundle.i18n.len(), 1);
+}
+
+#[test]
+fn fetch_bundle_caches_nothing_when_a_member_fails() {
+    let source = tempfile::tempdir().expect("source");
+    let cache = tempfile::tempdir().expect("cache");
+    write_bundle(source.path(), b"tampered", b"bundle-wasm", "component.wasm");
+    let store = ComponentStore::new(cache.path()).expect("store");
+    let manifest = source.path().join("component.manifest.json");
+
+    let err = store
+        .fetch_bundle(
+            &manifest.display().to_string(),
+            &VerificationPolicy::default(),
+        )
+        .expect_err("wasm does not match the manifest hash");
+    assert!(
+        matches!(
+            err,
+            StoreError::Verification(VerificationError::DigestMismatch { .. })
+        ),
+        "{err}"
+    );
+    assert!(!cache.path().join("bundles").exists());
+
+    write_bundle(
+        source.path(),
+        b"bundle-wasm",
+        b"bundle-wasm",
+        "../escape.wasm",
+    );
+    let err = store
+        .fetch_bundle(
+            &manifest.display().to_string(),
+            &VerificationPolicy::default(),
+        )
+        .expect_err("members must stay inside the bundle");
+    assert!(err.to_string().contains("relative path inside"), "{err}");
+}
+
+#[test]
+fn fetch_bundle_checks_declared_member_digests() {
+    let source = tempfile::tempdir().expect("source");
+    let cache = tempfile::tempdir().expect("cache");
+    write_bundle(
+        source.path(),
+        b"bundle-wasm",
+        b"bundle-wasm",
+        "component.wasm",
+    );
+    let store = ComponentStore::new(cache.path()).expect("store");
+    let manifest = source
+        .path()
+        .join("component.manifest.json")
+        .display()
+        .to_string();
+    let en = blake3::hash(br#"{"hello":"Hello"}"#).to_hex().to_string();
+    let fetch = |digests: Vec<(&str, DigestPolicy)>| {
+        let options = BundleOptions {
+            digests: digests
+                .into_iter()
+                .map(|(name, digest)| (name.to_string(), digest))
+                .collect(),
+            ..Default::default()
+        };
+        store.fetch_bundle_with(&manifest, &VerificationPolicy::default(), &options)
+    };
+
+    let bundle = fetch(vec![(
+        "assets/i18n/en.json",
+        DigestPolicy::blake3(Some(en), true),
+    )])
+    .expect("declared digest matches");
+    let digest = bundle.i18n["en"].verification.digest.as_ref().unwrap();
+    assert_eq!(digest.algorithm, DigestAlgorithm::Blake3);
+
+    let err = fetch(vec![(
+        "component.manifest.json",
+        DigestPolicy::sha256(Some("0".repeat(64)), true),
+    )])
+    .expect_err("manifest does not match");
+    assert!(
+        matches!(
+            err,
+            StoreError::Verification(VerificationError::DigestMismatch { .. })
+        ),
+        "{err}"
+    );
+
+    fetch(vec![(
+        BUNDLE_SBOM,
+        DigestPolicy::sha256(Some("0".repeat(64)), true),
+    )])
+    .expect_err("a declared member must be published");
+    assert!(!cache.path().join("bundles").exists());
+}
+
+#[test]
+fn concurrent_fetches_of_one_bundle_stage_separately() {
+    let source = tempfile::tempdir().expect("source");
+    let cache = tempfile::tempdir().expect("cache");
+    write_bundle(
+        source.path(),
+        b"bundle-wasm",
+        b"bundle-wasm",
+        "component.wasm",
+    );
+    let store = ComponentStore::new(cache.path()).expect("store");
+    let manifest = source
+        .path()
+        .join("component.manifest.json")
+        .display()
+        .to_string();
+
+    std::thread::scope(|scope| {
+        let fetches = (0..4)
+            .map(|_| scope.spawn(|| store.fetch_bundle(&manifest, &VerificationPolicy::default())))
+            .collect::<Vec<_>>();
+        let mut committed = 0;
+        for fetch in fetches {
+            // A rename may lose to another thread's; staging never collides.
+            match fetch.join().unwrap() {
+                Ok(_) => committed += 1,
+                Err(err) => assert!(matches!(err, StoreError::Io(_)), "{err}"),
+            }
+        }
+        assert!(committed > 0);
+    });
+    assert!(cache.path().join("bundles").read_dir().unwrap().count() >= 1);
+    let leftovers = fs::read_dir(cache.path().join("bundles"))
+        .unwrap()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with('.'))
+        .count();
+    assert_eq!(leftovers, 0, "staging directories are cleaned up");
+}
@@ -0,0 +1,114 @@
+use std::fs;
+
+use greentic_component_store::{
+    Catalog, CatalogEntry, CatalogSignature, ComponentStore, DigestAlgorithm, StoreError,
+    VerificationError, VerifiedDigest,
+};
+
+#[test]
+fn parses_entries_and_builds_verification() {
+    let catalog = Catalog::from_toml_str(
+        r#"
+[components.slack-adapter]
+locator = "oci://ghcr.io/greentic/slack-adapter:1.4.2"
+version = "^1.4"
+digest = "sha256:abcd"
+signature = "optional"
+
+[components.local]
+locator = "./build/local.wasm"
+
+[registries.acme]
+index = "https://components.acme.dev/index.json"
+"#,
+    )
+    .expect("catalog");
+    assert_eq!(
+        catalog
+            .registries()
+            .map(|(name, registry)| (name, registry.index.as_str()))
+            .collect::<Vec<_>>(),
+        [("acme", "https://components.acme.dev/index.json")]
+    );
+    assert_eq!(
+        catalog.names().collect::<Vec<_>>(),
+        ["local", "slack-adapter"]
+    );
+
+    let slack = catalog.lookup("slack-adapter").expect("slack entry");
+    assert_eq!(slack.version.as_deref(), Some("^1.4"));
+    assert_eq!(slack.digest_hex(), Some("abcd"));
+    assert_eq!(slack.signature, CatalogSignature::Optional);
+    let policy = slack.verification_policy();
+    assert_eq!(
+        policy.digest.as_ref().and_then(|d| d.expected()),
+        Some("abcd")
+    );
+    assert!(policy.signature.is_some());
+
+    assert_eq!(
+        catalog
+            .get("local")
+            .map(|entry| entry.verification_policy().digest.is_none()),
+        Some(true)
+    );
+    assert!(
+        catalog
+            .lookup("oci://ghcr.io/greentic/slack-adapter:1.4.2")
+            .is_none()
+    );
+    assert!(catalog.lookup("unknown").is_none());
+}
+
+#[test]
+fn rejects_malformed_catalogs() {
+    let err = Catalog::from_toml_str("[components.\"a/b\"]\nlocator = \"a.wasm\"\n")
+        .expect_err("names must not be locators");
+    assert!(err.to_string().contains("looks like a locator"), "{err}");
+
+    let err = Catalog::from_toml_str("[components.x]\nurl = \"a.wasm\"\n")
+        .expect_err("unknown keys are rejected");
+    assert!(matches!(err, StoreError::InvalidCatalog(_)), "{err}");
+
+    let err =
+        Catalog::from_toml_str("[components.x]\nlocator = \" \"\n").expect_err("empty locator");
+    assert!(err.to_string().contains("empty locator"), "{err}");
+
+    let err = Catalog::from_toml_str("[registries.acme]\nindex = \"\"\n").expect_err("empty index");
+    assert!(err.to_string().contains("empty index"), "{err}");
+}
+
+#[test]
+fn fetch_by_name_enforces_catalog_digest() {
+    let temp_dir = tempfile::tempdir().expect("component dir");
+    let cache_dir = tempfile::tempdir().expect("cache dir");
+    let wasm = temp_dir.path().join("adapter.wasm");
+    fs::write(&wasm, b"adapter").expect("write wasm");
+    let digest = VerifiedDigest::compute(DigestAlgorithm::Sha256, b"adapter").value;
+
+    let mut catalog = Catalog::default();
+    let mut entry = CatalogEntry::new(wasm.display().to_string());
+    entry.digest = Some(format!("sha256:{digest}"));
+    catalog.insert("adapter", entry.clone());
+    entry.digest = Some("sha256:00".to_string());
+    catalog.insert("tampered", entry);
+
+    let store = ComponentStore::new(cache_dir.path()).expect("store");
+    let adapter = catalog.lookup("adapter").expect("adapter");
+    let artifact = store
+        .fetch_from_str(&adapter.locator, &adapter.verification_policy())
+        .expect("digest matches");
+    assert_eq!(artifact.bytes, b"adapter");
+
+    let tampered = catalog.lookup("tampered").expect("tampered");
+    let err = store
+        .fetch_from_str(&tampered.locator, &tampered.verification_policy())
+        .expect_err("digest mismatch");
+    assert!(
+        matches!(
+            err,
+            StoreError::Verification(VerificationError::DigestMismatch { .. })
+        ),
+        "{err}"
+    );
+}
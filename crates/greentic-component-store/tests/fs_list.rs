@@ -3,7 +3,10 @@ use std::io::Write;
 use std::path::PathBuf;
 
 use greentic_component_store::fs as store_fs;
-use greentic_component_store::{ComponentStore, DigestPolicy, VerificationPolicy};
+use greentic_component_store::{
+    CACHE_INDEX_FILE, CacheEncryptionKey, ComponentStore, DigestAlgorithm, DigestPolicy,
+    StoreError, StoreLocator, VerificationError, VerificationPolicy,
+};
 
 fn write_file(dir: &tempfile::TempDir, name: &str, contents: &[u8]) -> PathBuf {
     let path = dir.path().join(name);
@@ -33,7 +36,7 @@ fn fetch_fs_component_and_cache() {
     let locator = format!("fs://{}", file_path.display());
     let policy = VerificationPolicy {
         digest: Some(DigestPolicy::sha256(None, false)),
-        signature: None,
+        ..Default::default()
     };
 
     let artifact = store
@@ -51,3 +54,159 @@ fn fetch_fs_component_and_cache() {
     assert_eq!(artifact_cached.bytes, b"hello world");
     assert!(artifact_cached.path.exists());
 }
+
+#[test]
+fn encrypted_cache_opens_transparently() {
+    let temp_dir = tempfile::tempdir().expect("component dir");
+    let cache_dir = tempfile::tempdir().expect("cache dir");
+    let file_path = write_file(&temp_dir, "component.wasm", b"hello world");
+    let locator = format!("fs://{}", file_path.display());
+    let policy = VerificationPolicy {
+        digest: Some(DigestPolicy::sha256(None, false)),
+        ..Default::default()
+    };
+    let key = CacheEncryptionKey::from_bytes([7; 32]);
+    let store = ComponentStore::new(cache_dir.path())
+        .expect("store")
+        .with_encryption_key(key);
+
+    let artifact = store.fetch_from_str(&locator, &policy).expect("fetch");
+    assert_eq!(artifact.bytes, b"hello world");
+    let on_disk = fs::read(&artifact.path).expect("cached file");
+    assert!(!on_disk.windows(5).any(|window| window == b"hello"));
+    let index = fs::read_to_string(cache_dir.path().join(CACHE_INDEX_FILE)).expect("index");
+    assert!(index.contains("nonce"));
+
+    fs::remove_file(&file_path).expect("remove source file");
+    let cached = store
+        .fetch_from_str(&locator, &policy)
+        .expect("cached fetch");
+    assert!(cached.from_cache);
+    assert_eq!(cached.bytes, b"hello world");
+
+    let err = ComponentStore::new(cache_dir.path())
+        .expect("store without key")
+        .fetch_from_str(&locator, &policy)
+        .expect_err("sealed entries need the key");
+    assert!(matches!(err, StoreError::Encryption(_)));
+}
+
+#[test]
+fn system_cache_is_read_but_never_written() {
+    let temp_dir = tempfile::tempdir().expect("component dir");
+    let system_dir = tempfile::tempdir().expect("system cache");
+    let user_dir = tempfile::tempdir().expect("user cache");
+    let baked = write_file(&temp_dir, "baked.wasm", b"baked into the image");
+    let fresh = write_file(&temp_dir, "fresh.wasm", b"fetched at runtime");
+    let policy = VerificationPolicy {
+        digest: Some(DigestPolicy::sha256(None, false)),
+        ..Default::default()
+    };
+    let baked_locator = format!("fs://{}", baked.display());
+    let fresh_locator = format!("fs://{}", fresh.display());
+
+    // The operator provisions the system cache ahead of time.
+    ComponentStore::new(system_dir.path())
+        .expect("provisioning store")
+        .fetch_from_str(&baked_locator, &policy)
+        .expect("provision");
+    fs::remove_file(&baked).expect("remove source file");
+    let listing = |dir: &std::path::Path| {
+        let mut names = fs::read_dir(dir)
+            .expect("read dir")
+            .map(|entry| entry.expect("entry").file_name())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+    let provisioned = listing(system_dir.path());
+
+    let store = ComponentStore::new(user_dir.path())
+        .expect("store")
+        .with_system_cache(system_dir.path());
+    let hit = store
+        .fetch_from_str(&baked_locator, &policy)
+        .expect("served from the system cache");
+    assert!(hit.from_cache);
+    assert_eq!(hit.bytes, b"baked into the image");
+    assert!(hit.path.starts_with(system_dir.path()));
+
+    let fetched = store
+        .fetch_from_str(&fresh_locator, &policy)
+        .expect("fetch into the user cache");
+    assert!(!fetched.from_cache);
+    assert!(fetched.path.starts_with(user_dir.path()));
+
+    assert_eq!(listing(system_dir.path()), provisioned);
+    assert_eq!(listing(user_dir.path()).len(), 2);
+}
+
+#[test]
+fn digests_pinned_in_locators_are_enforced() {
+    let temp_dir = tempfile::tempdir().expect("component dir");
+    let cache_dir = tempfile::tempdir().expect("cache dir");
+    let file_path = write_file(&temp_dir, "component.wasm", b"hello world");
+    let store = ComponentStore::new(cache_dir.path()).expect("store");
+    let blake3 = blake3::hash(b"hello world").to_hex().to_string();
+
+    let pinned = format!("fs://{}#blake3={blake3}", file_path.display());
+    let artifact = store
+        .fetch_from_str(&pinned, &VerificationPolicy::default())
+        .expect("pinned fetch");
+    let digest = artifact.verification.digest.expect("digest checked");
+    assert_eq!(digest.algorithm, DigestAlgorithm::Blake3);
+    assert_eq!(digest.value, blake3);
+
+    let wrong = format!("fs://{}#sha256={}", file_path.display(), "0".repeat(64));
+    let err = store
+        .fetch_from_str(&wrong, &VerificationPolicy::default())
+        .expect_err("pin does not match");
+    assert!(matches!(
+        err,
+        StoreError::Verification(VerificationError::DigestMismatch { .. })
+    ));
+
+    // An explicit policy digest has to agree with the pin.
+    let policy = VerificationPolicy {
+        digest: Some(DigestPolicy::sha256(Some("1".repeat(64)), true)),
+        ..Default::default()
+    };
+    let err = store
+        .fetch_from_str(&pinned, &policy)
+        .expect_err("conflicting digests");
+    assert!(matches!(err, StoreError::InvalidLocator { .. }), "{err}");
+
+    let oci = StoreLocator::parse(&format!("oci://ghcr.io/org/comp@sha256:{}", "a".repeat(64)))
+        .expect("oci locator");
+    let pin = oci.pinned_digest().expect("valid pin").expect("pinned");
+    assert_eq!(pin.algorithm(), DigestAlgorithm::Sha256);
+    assert_eq!(pin.expected(), Some("a".repeat(64).as_str()));
+    assert!(pin.is_required());
+    let https = StoreLocator::parse(&format!("https://host/c.wasm#blake3={}", "B".repeat(64)))
+        .expect("https locator");
+    assert_eq!(
+        https.pinned_digest().unwrap().unwrap().expected(),
+        Some("b".repeat(64).as_str())
+    );
+    assert!(
+        StoreLocator::parse("https://host/c.wasm")
+            .unwrap()
+            .pinned_digest()
+            .unwrap()
+            .is_none()
+    );
+
+    for bad in [
+        "https://host/c.wasm#md5=abc",
+        "https://host/c.wasm#sha256",
+        "oci://ghcr.io/org/comp@sha256:short",
+    ] {
+        assert!(
+            matches!(
+                StoreLocator::parse(bad),
+                Err(StoreError::InvalidLocator { .. })
+            ),
+            "{bad}"
+        );
+    }
+}
@@ -1,8 +1,12 @@
 use std::io::{ErrorKind, Read, Write};
 use std::net::TcpListener;
 use std::thread;
+use std::time::Duration;
 
-use greentic_component_store::{ComponentStore, DigestPolicy, VerificationPolicy};
+use greentic_component_store::{
+    ComponentStore, DigestAlgorithm, DigestPolicy, HttpConfig, StoreError, VerificationError,
+    VerificationPolicy,
+};
 
 fn spawn_http_server(body: &'static [u8]) -> std::io::Result<String> {
     let listener = TcpListener::bind("127.0.0.1:0")?;
@@ -44,7 +48,7 @@ fn fetch_http_component() {
     let store = ComponentStore::new(cache_dir.path()).expect("store");
     let policy = VerificationPolicy {
         digest: Some(DigestPolicy::sha256(None, false)),
-        signature: None,
+        ..Default::default()
     };
 
     let artifact = store
@@ -53,3 +57,191 @@ fn fetch_http_component() {
     assert_eq!(artifact.bytes, payload);
     assert!(artifact.verification.digest.is_some());
 }
+
+/// Serves `routes` (path -> body) until the test ends; other paths get 404.
+fn spawn_routing_server(routes: Vec<(String, Vec<u8>)>) -> std::io::Result<String> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut buffer = [0u8; 1024];
+            let read = stream.read(&mut buffer).unwrap_or(0);
+            let request = String::from_utf8_lossy(&buffer[..read]);
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+            let path = path.split('?').next().unwrap_or(path);
+            let response = match routes.iter().find(|(route, _)| route == path) {
+                Some((_, body)) => {
+                    let mut response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    )
+                    .into_bytes();
+                    response.extend_from_slice(body);
+                    response
+                }
+                None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_vec(),
+            };
+            let _ = stream.write_all(&response);
+        }
+    });
+
+    Ok(format!("http://{}:{}", addr.ip(), addr.port()))
+}
+
+#[test]
+fn fetch_http_component_checks_published_digests() {
+    let payload = b"wasm!".to_vec();
+    let blake3 = blake3::hash(&payload).to_hex().to_string();
+    let wrong = "0".repeat(64);
+    let base = match spawn_routing_server(vec![
+        ("/good.wasm".into(), payload.clone()),
+        (
+            "/good.wasm.blake3".into(),
+            format!("{blake3}  good.wasm\n").into_bytes(),
+        ),
+        ("/bad.wasm".into(), payload.clone()),
+        ("/bad.wasm.sha256".into(), wrong.clone().into_bytes()),
+        ("/plain.wasm".into(), payload.clone()),
+    ]) {
+        Ok(base) => base,
+        Err(err) if err.kind() == ErrorKind::PermissionDenied => {
+            eprintln!("skipping fetch_http_component_checks_published_digests: {err}");
+            return;
+        }
+        Err(err) => panic!("bind http listener: {err}"),
+    };
+
+    let fetch = |path: &str, policy: &VerificationPolicy| {
+        let cache_dir = tempfile::tempdir().expect("cache dir");
+        let store = ComponentStore::new(cache_dir.path()).expect("store");
+        store.fetch_from_str(&format!("{base}{path}"), policy)
+    };
+
+    let sidecars = VerificationPolicy::default().with_digest_sidecars();
+    let artifact = fetch("/good.wasm", &sidecars).expect("sidecar matches");
+    let digest = artifact.verification.digest.expect("digest");
+    assert_eq!(digest.algorithm, DigestAlgorithm::Blake3);
+    assert_eq!(digest.value, blake3);
+
+    // Without the flag no sidecar is requested, so the bad one goes unseen.
+    fetch("/bad.wasm", &VerificationPolicy::default()).expect("sidecars not probed");
+    let err = fetch("/bad.wasm", &sidecars).expect_err("sidecar mismatch");
+    assert!(
+        matches!(
+            err,
+            StoreError::Verification(VerificationError::DigestMismatch { .. })
+        ),
+        "{err}"
+    );
+
+    let err = fetch(
+        &format!("/plain.wasm?digest=sha256:{wrong}"),
+        &VerificationPolicy::default(),
+    )
+    .expect_err("query digest mismatch");
+    assert!(err.to_string().contains("digest mismatch"), "{err}");
+    let artifact = fetch(
+        &format!("/plain.wasm?digest=blake3:{blake3}"),
+        &VerificationPolicy::default(),
+    )
+    .expect("query digest matches");
+    assert_eq!(artifact.bytes, payload);
+
+    let required = VerificationPolicy {
+        digest: Some(DigestPolicy::sha256(None, true)),
+        ..Default::default()
+    };
+    let err = fetch("/plain.wasm", &required).expect_err("no digest published");
+    assert!(
+        matches!(
+            err,
+            StoreError::Verification(VerificationError::DigestMissing)
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn http_config_routes_through_explicit_proxy() {
+    let listener = match TcpListener::bind("127.0.0.1:0") {
+        Ok(listener) => listener,
+        Err(err) if err.kind() == ErrorKind::PermissionDenied => {
+            eprintln!("skipping http_config_routes_through_explicit_proxy: {err}");
+            return;
+        }
+        Err(err) => panic!("bind proxy listener: {err}"),
+    };
+    let proxy = format!("http://{}", listener.local_addr().unwrap());
+    let request_line = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("proxied request");
+        let mut buffer = [0u8; 1024];
+        let read = stream.read(&mut buffer).unwrap_or(0);
+        let body = b"proxied";
+        let header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        let _ = stream.write_all(header.as_bytes());
+        let _ = stream.write_all(body);
+        String::from_utf8_lossy(&buffer[..read])
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    });
+
+    let cache_dir = tempfile::tempdir().expect("cache dir");
+    let config = HttpConfig::default()
+        .with_proxy(proxy)
+        .with_timeout(Duration::from_secs(10));
+    let store = ComponentStore::with_http_config(cache_dir.path(), &config).expect("store");
+    let policy = VerificationPolicy {
+        digest: Some(DigestPolicy::sha256(None, false)),
+        ..Default::default()
+    };
+    let artifact = store
+        .fetch_from_str("http://components.invalid/component.wasm", &policy)
+        .expect("fetch through proxy");
+    assert_eq!(artifact.bytes, b"proxied");
+    assert_eq!(
+        request_line.join().unwrap(),
+        "GET http://components.invalid/component.wasm HTTP/1.1"
+    );
+}
+
+#[test]
+fn http_config_reports_unusable_tls_material() {
+    let dir = tempfile::tempdir().expect("dir");
+    let missing = dir.path().join("missing.pem");
+    let err = ComponentStore::with_http_config(
+        dir.path(),
+        &HttpConfig::default().with_root_certificate(&missing),
+    )
+    .expect_err("missing bundle");
+    assert!(
+        matches!(&err, StoreError::HttpConfig(message) if message.contains("missing.pem")),
+        "{err}"
+    );
+
+    let empty = dir.path().join("empty.pem");
+    std::fs::write(&empty, b"not a certificate").unwrap();
+    let err = ComponentStore::with_http_config(
+        dir.path(),
+        &HttpConfig::default().with_root_certificate(&empty),
+    )
+    .expect_err("no certificates");
+    assert!(
+        matches!(&err, StoreError::HttpConfig(message) if message.contains("empty.pem")),
+        "{err}"
+    );
+
+    let err = ComponentStore::with_http_config(
+        dir.path(),
+        &HttpConfig::default().without_builtin_roots(),
+    )
+    .expect_err("no roots at all");
+    assert!(err.to_string().contains("no root certificates"), "{err}");
+}
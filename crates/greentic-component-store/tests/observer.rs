@@ -0,0 +1,103 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use greentic_component_store::{
+    ComponentStore, FetchDecision, FetchObserver, StoreError, StoreLocator, VerificationPolicy,
+    VerificationReport,
+};
+
+#[derive(Debug, Default)]
+struct Recorder {
+    events: Mutex<Vec<String>>,
+    freeze: bool,
+}
+
+impl Recorder {
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut self.events.lock().unwrap())
+    }
+
+    fn push(&self, event: String) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+impl FetchObserver for Recorder {
+    fn on_fetch_start(&self, _locator: &StoreLocator) -> FetchDecision {
+        self.push("start".into());
+        if self.freeze {
+            FetchDecision::Deny("change freeze".into())
+        } else {
+            FetchDecision::Allow
+        }
+    }
+
+    fn on_fetch_progress(&self, _locator: &StoreLocator, received: u64, total: Option<u64>) {
+        self.push(format!("progress {received}/{total:?}"));
+    }
+
+    fn on_fetch_verified(&self, _locator: &StoreLocator, report: &VerificationReport) {
+        self.push(format!("verified digest={}", report.digest.is_some()));
+    }
+
+    fn on_fetch_cached(&self, _locator: &StoreLocator, _path: &Path, from_cache: bool) {
+        self.push(format!("cached from_cache={from_cache}"));
+    }
+
+    fn on_fetch_error(&self, _locator: &StoreLocator, error: &StoreError) {
+        self.push(format!("error {error}"));
+    }
+}
+
+#[test]
+fn observers_follow_fetch_lifecycle_and_gate_sources() {
+    let source_dir = tempfile::tempdir().expect("source dir");
+    let cache_dir = tempfile::tempdir().expect("cache dir");
+    let wasm = source_dir.path().join("component.wasm");
+    std::fs::write(&wasm, b"observed").expect("write wasm");
+    let locator = wasm.display().to_string();
+
+    let recorder = Arc::new(Recorder::default());
+    let store = ComponentStore::new(cache_dir.path())
+        .expect("store")
+        .with_observer(recorder.clone());
+    let policy = VerificationPolicy::default();
+
+    store
+        .fetch_from_str(&locator, &policy)
+        .expect("first fetch");
+    assert_eq!(
+        recorder.take(),
+        [
+            "start",
+            "progress 8/Some(8)",
+            "verified digest=true",
+            "cached from_cache=false"
+        ]
+    );
+
+    store.fetch_from_str(&locator, &policy).expect("cache hit");
+    assert_eq!(
+        recorder.take(),
+        ["verified digest=true", "cached from_cache=true"]
+    );
+
+    let frozen = Arc::new(Recorder {
+        freeze: true,
+        ..Recorder::default()
+    });
+    let fresh_cache = tempfile::tempdir().expect("fresh cache");
+    let store = ComponentStore::new(fresh_cache.path())
+        .expect("store")
+        .with_observer(frozen.clone());
+    let err = store
+        .fetch_from_str(&locator, &policy)
+        .expect_err("freeze denies the fetch");
+    assert!(
+        matches!(&err, StoreError::FetchDenied { reason, .. } if reason == "change freeze"),
+        "{err}"
+    );
+    let events = frozen.take();
+    assert_eq!(events.len(), 2, "{events:?}");
+    assert!(events[1].starts_with("error fetch of"), "{events:?}");
+}
@@ -1,13 +1,22 @@
+//! Builds the contract fixture for one component ABI:
+//!
+//! ```text
+//! cargo run -p greentic-component --features abi --example gen_contract_fixture -- --abi 0.6
+//! ```
+//!
+//! The fixture lands in `tests/contract/fixtures/component_v<version>/`,
+//! where the contract registry picks it up without further changes.
+
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use anyhow::{Context, Result, bail};
 use blake3::Hasher;
-use serde_json::json;
+use serde_json::{Value, json};
 use tempfile::TempDir;
 
-const FIXTURE_CARGO_TOML: &str = r#"[package]
+const V0_5_CARGO_TOML: &str = r#"[package]
 name = "contract_fixture"
 version = "0.1.0"
 edition = "2021"
@@ -20,7 +29,7 @@ greentic-interfaces-guest = { version = "0.4", default-features = false, feature
 serde_json = "1"
 "#;
 
-const FIXTURE_LIB_RS: &str = r#"use greentic_interfaces_guest::component::node::{InvokeResult, NodeError};
+const V0_5_LIB_RS: &str = r#"use greentic_interfaces_guest::component::node::{InvokeResult, NodeError};
 use greentic_interfaces_guest::component_entrypoint;
 use serde_json::Value;
 
@@ -73,16 +82,157 @@ component_entrypoint!({
 });
 "#;
 
+const V0_6_CARGO_TOML: &str = r#"[package]
+name = "contract_fixture"
+version = "0.1.0"
+edition = "2024"
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+serde_json = "1"
+greentic-interfaces-guest = { version = "0.4", default-features = false, features = ["component-v0-6"] }
+greentic-types = "0.4"
+"#;
+
+const V0_6_LIB_RS: &str = r#"use std::collections::BTreeMap;
+
+use greentic_interfaces_guest::component_v0_6::node;
+use greentic_types::cbor::canonical;
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use serde_json::Value;
+
+#[cfg(target_arch = "wasm32")]
+#[used]
+#[unsafe(link_section = ".greentic.wasi")]
+static WASI_TARGET_MARKER: [u8; 13] = *b"wasm32-wasip2";
+
+struct Component;
+
+impl node::Guest for Component {
+    fn describe() -> node::ComponentDescriptor {
+        node::ComponentDescriptor {
+            name: "contract-fixture".to_string(),
+            version: "0.1.0".to_string(),
+            summary: Some("Contract fixture".to_string()),
+            capabilities: Vec::new(),
+            ops: vec![node::Op {
+                name: "handle_message".to_string(),
+                summary: None,
+                input: io_schema(string_object("input")),
+                output: io_schema(string_object("message")),
+                examples: Vec::new(),
+            }],
+            schemas: Vec::new(),
+            setup: None,
+        }
+    }
+
+    fn invoke(
+        _operation: String,
+        envelope: node::InvocationEnvelope,
+    ) -> Result<node::InvocationResult, node::NodeError> {
+        let input: Value = canonical::from_cbor(&envelope.payload_cbor).unwrap_or(Value::Null);
+        let Some(text) = input.get("input").and_then(Value::as_str) else {
+            return Err(node::NodeError {
+                code: "INVALID_INPUT".to_string(),
+                message: "expected object with input".to_string(),
+                retryable: false,
+                backoff_ms: None,
+                details: None,
+            });
+        };
+        let output = serde_json::json!({ "message": format!("handled {text}") });
+        Ok(node::InvocationResult {
+            ok: true,
+            output_cbor: canonical::to_canonical_cbor_allow_floats(&output).expect("encode cbor"),
+            output_metadata_cbor: None,
+        })
+    }
+}
+
+fn string_object(field: &str) -> SchemaIr {
+    SchemaIr::Object {
+        properties: BTreeMap::from([(
+            field.to_string(),
+            SchemaIr::String {
+                min_len: Some(0),
+                max_len: None,
+                regex: None,
+                format: None,
+            },
+        )]),
+        required: vec![field.to_string()],
+        additional: AdditionalProperties::Allow,
+    }
+}
+
+fn io_schema(schema: SchemaIr) -> node::IoSchema {
+    node::IoSchema {
+        schema: node::SchemaSource::InlineCbor(
+            canonical::to_canonical_cbor_allow_floats(&schema).expect("encode cbor"),
+        ),
+        content_type: "application/cbor".to_string(),
+        schema_version: None,
+    }
+}
+
+greentic_interfaces_guest::export_component_v060!(Component);
+"#;
+
+/// One supported component ABI: the guest crate to build and the inputs its
+/// contract suite starts from.
+struct AbiFixture {
+    version: &'static str,
+    cargo_toml: &'static str,
+    lib_rs: &'static str,
+    input_schema: fn() -> Value,
+    output_schema: fn() -> Value,
+    valid_input: fn() -> Value,
+}
+
+const ABIS: &[AbiFixture] = &[
+    AbiFixture {
+        version: "0.5.0",
+        cargo_toml: V0_5_CARGO_TOML,
+        lib_rs: V0_5_LIB_RS,
+        input_schema: || json!({}),
+        output_schema: || json!({}),
+        valid_input: || json!({ "message": "ok" }),
+    },
+    AbiFixture {
+        version: "0.6.0",
+        cargo_toml: V0_6_CARGO_TOML,
+        lib_rs: V0_6_LIB_RS,
+        input_schema: || string_object_schema("input"),
+        output_schema: || string_object_schema("message"),
+        valid_input: || json!({ "input": "hello" }),
+    },
+];
+
 fn main() -> Result<()> {
-    let fixture_dir =
-        PathBuf::from("crates/greentic-component/tests/contract/fixtures/component_v0_5_0");
+    let abi = parse_abi(std::env::args().skip(1))?;
+    let fixture = ABIS
+        .iter()
+        .find(|fixture| fixture.version == abi)
+        .with_context(|| {
+            let known = ABIS
+                .iter()
+                .map(|fixture| fixture.version)
+                .collect::<Vec<_>>();
+            format!("unknown ABI `{abi}`; known: {}", known.join(", "))
+        })?;
+    let fixture_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/contract/fixtures")
+        .join(format!("component_v{}", fixture.version.replace('.', "_")));
     fs::create_dir_all(&fixture_dir)?;
 
     let temp = TempDir::new().context("create temp dir")?;
     let temp_path = temp.path();
-    fs::write(temp_path.join("Cargo.toml"), FIXTURE_CARGO_TOML)?;
+    fs::write(temp_path.join("Cargo.toml"), fixture.cargo_toml)?;
     fs::create_dir_all(temp_path.join("src"))?;
-    fs::write(temp_path.join("src/lib.rs"), FIXTURE_LIB_RS)?;
+    fs::write(temp_path.join("src/lib.rs"), fixture.lib_rs)?;
 
     let cargo_bin = std::env::var_os("CARGO")
         .map(PathBuf::from)
@@ -106,14 +256,19 @@ fn main() -> Result<()> {
     fs::write(fixture_dir.join("component.wasm"), &wasm_bytes)?;
 
     let hash = blake3_hash(&wasm_bytes);
+    let world = format!("greentic:component/component@{}", fixture.version);
     let manifest = json!({
-        "id": "com.greentic.contract.fixture",
-        "name": "Contract Fixture",
+        "id": format!("com.greentic.contract.fixture.v{}", fixture.version.replace('.', "_")),
+        "name": format!("Contract Fixture v{}", fixture.version),
         "version": "0.1.0",
-        "world": "greentic:component/component@0.5.0",
+        "world": world,
         "describe_export": "describe",
         "operations": [
-            { "name": "handle_message", "input_schema": {}, "output_schema": {} }
+            {
+                "name": "handle_message",
+                "input_schema": (fixture.input_schema)(),
+                "output_schema": (fixture.output_schema)()
+            }
         ],
         "default_operation": "handle_message",
         "supports": ["messaging"],
@@ -187,10 +342,74 @@ fn main() -> Result<()> {
         serde_json::to_string_pretty(&manifest)?,
     )?;
 
+    write_seed_inputs(&fixture_dir, fixture, &world)?;
+
     println!("wrote contract fixture to {}", fixture_dir.display());
     Ok(())
 }
 
+/// `--abi 0.6` or `--abi 0.6.0`; defaults to the newest ABI.
+fn parse_abi(mut args: impl Iterator<Item = String>) -> Result<String> {
+    let newest = ABIS.last().expect("at least one ABI").version;
+    let Some(flag) = args.next() else {
+        return Ok(newest.to_string());
+    };
+    let value = match flag.strip_prefix("--abi=") {
+        Some(value) => value.to_string(),
+        None if flag == "--abi" => args.next().context("--abi needs a version")?,
+        None => bail!("usage: gen_contract_fixture [--abi <version>]"),
+    };
+    Ok(match value.split('.').count() {
+        2 => format!("{value}.0"),
+        _ => value,
+    })
+}
+
+/// Writes the README and one valid and one invalid input, leaving existing
+/// inputs alone so hand-written cases survive a rebuild.
+fn write_seed_inputs(fixture_dir: &Path, fixture: &AbiFixture, world: &str) -> Result<()> {
+    let newest = ABIS
+        .last()
+        .is_some_and(|newest| newest.version == fixture.version);
+    let status = if newest {
+        "Canonical contract target."
+    } else {
+        "Legacy world, kept for compatibility coverage only."
+    };
+    fs::write(
+        fixture_dir.join("README.md"),
+        format!(
+            "Contract fixture for `{world}`. {status}\n\n\
+             Regenerate with `cargo run -p greentic-component --features abi --example gen_contract_fixture -- --abi {}`.\n\n\
+             - `component.wasm` and `component.manifest.json` are rebuilt by the generator.\n\
+             - `valid_inputs/` and `invalid_inputs/` drive `contract_tests`; add cases freely.\n",
+            fixture.version
+        ),
+    )?;
+    for (dir, input) in [
+        ("valid_inputs", (fixture.valid_input)()),
+        ("invalid_inputs", Value::Null),
+    ] {
+        let dir = fixture_dir.join(dir);
+        if dir.exists() {
+            continue;
+        }
+        fs::create_dir_all(&dir)?;
+        let name = if input.is_null() { "null" } else { "empty" };
+        fs::write(dir.join(format!("{name}.json")), input.to_string())?;
+    }
+    Ok(())
+}
+
+fn string_object_schema(field: &str) -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": { field: { "type": "string" } },
+        "required": [field]
+    })
+}
+
 fn blake3_hash(bytes: &[u8]) -> String {
     let mut hasher = Hasher::new();
     hasher.update(bytes);
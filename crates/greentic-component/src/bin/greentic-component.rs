@@ -2,6 +2,8 @@
 
 use std::process;
 
+#[cfg(feature = "cli")]
+use greentic_component::cmd::exit::{self, ExitCode};
 #[cfg(feature = "cli")]
 use greentic_component::cmd::test::TestCommandError;
 #[cfg(feature = "cli")]
@@ -16,9 +18,15 @@ fn main() {
 #[cfg(feature = "cli")]
 fn main() {
     if let Err(err) = greentic_component::cli::main() {
+        let code = ExitCode::of(&err);
         if let Some(test_error) = err.downcast_ref::<TestCommandError>() {
             println!("{}", test_error.render_json());
-            process::exit(1);
+            process::exit(code.code());
+        }
+        // stdout may already hold the command's own JSON report.
+        if exit::json_requested(std::env::args().skip(1)) {
+            eprintln!("{}", exit::error_json(&err));
+            process::exit(code.code());
         }
         match err.downcast::<ValidationError>() {
             Ok(diag) => {
@@ -28,6 +36,6 @@ fn main() {
                 eprintln!("greentic-component: {other:?}");
             }
         }
-        process::exit(1);
+        process::exit(code.code());
     }
 }
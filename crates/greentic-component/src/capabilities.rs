@@ -1,3 +1,4 @@
+pub use component_manifest::{NetCaps, ToolsCaps};
 use greentic_types::SecretKey;
 pub use greentic_types::component::{
     ComponentCapabilities as Capabilities, ComponentConfigurators, ComponentProfiles,
@@ -85,6 +86,15 @@ fn validate_host(host: &HostCapabilities) -> Result<(), CapabilityError> {
             "state capability must enable read and/or write",
         ));
     }
+    if let Some(events) = &host.events
+        && !events.inbound
+        && !events.outbound
+    {
+        return Err(CapabilityError::invalid(
+            "host.events",
+            "events capability must enable inbound and/or outbound",
+        ));
+    }
     if let Some(telemetry) = &host.telemetry {
         validate_telemetry(telemetry)?;
     }
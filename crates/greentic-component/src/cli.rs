@@ -4,9 +4,15 @@ use clap::{Parser, Subcommand};
 #[cfg(feature = "store")]
 use crate::cmd::store::StoreCommand;
 use crate::cmd::{
-    self, build::BuildArgs, doctor::DoctorArgs, flow::FlowCommand, hash::HashArgs,
-    inspect::InspectArgs, new::NewArgs, templates::TemplatesArgs, test::TestArgs,
-    wizard::WizardCommand,
+    self, bench::BenchArgs, build::BuildArgs, cache::CacheCommand, caps::CapsCommand,
+    client::ClientCommand, compat::CompatArgs, configurator::ConfiguratorCommand,
+    conform::ConformArgs, conformance::ConformanceArgs, doctor::DoctorArgs, errors::ErrorsCommand,
+    fixtures::FixturesCommand, flow::FlowCommand, graph::GraphArgs, hash::HashArgs,
+    index::IndexCommand, inspect::InspectArgs, lsp::LspArgs, manifest::ManifestCommand,
+    new::NewArgs, op::OpCommand, progress::LogFormat, sdk::SdkCommand, search::SearchArgs,
+    secret::SecretCommand, sync::SyncArgs, templates::TemplatesArgs, test::TestArgs,
+    upgrade_test::UpgradeTestArgs, validate::ValidateArgs, verify_build::VerifyBuildArgs,
+    wit::WitCommand, wizard::WizardCommand,
 };
 use crate::scaffold::engine::ScaffoldEngine;
 
@@ -18,6 +24,10 @@ use crate::scaffold::engine::ScaffoldEngine;
     arg_required_else_help = true
 )]
 pub struct Cli {
+    /// Progress output: `json` adds NDJSON start/finish events on stderr
+    /// (build, store fetch)
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
     #[command(subcommand)]
     command: Commands,
 }
@@ -37,17 +47,77 @@ enum Commands {
     Inspect(InspectArgs),
     /// Recompute manifest hashes
     Hash(HashArgs),
+    /// Check drop-in compatibility between two component versions
+    Compat(CompatArgs),
     /// Build component wasm + update config flows
     Build(BuildArgs),
+    /// Compare manifest operations/config_schema with the wasm's describe()
+    Sync(SyncArgs),
+    /// Rebuild from a clean checkout and compare the wasm with the recorded hash
+    VerifyBuild(VerifyBuildArgs),
+    /// Add operations to a wizard-generated component
+    #[command(subcommand)]
+    Op(OpCommand),
+    /// Validate a component manifest and report located diagnostics
+    Validate(ValidateArgs),
+    /// Language server for component manifests and `.ygtc` flows (stdio)
+    Lsp(LspArgs),
+    /// Upgrade component manifests written against older schema shapes
+    #[command(subcommand)]
+    Manifest(ManifestCommand),
+    /// Declare secret requirements in a component manifest
+    #[command(subcommand)]
+    Secret(SecretCommand),
+    /// Capability tooling (least-privilege suggestions)
+    #[command(subcommand)]
+    Caps(CapsCommand),
+    /// Typed client generation for host applications
+    #[command(subcommand)]
+    Client(ClientCommand),
+    /// Error code catalog tooling
+    #[command(subcommand)]
+    Errors(ErrorsCommand),
     /// Invoke a component locally with an in-memory state/secrets harness
     #[command(
         long_about = "Invoke a component locally with in-memory state/secrets. \
 See docs/component-developer-guide.md for a walkthrough."
     )]
     Test(Box<TestArgs>),
+    /// Check a host implementation against the harness's host-import semantics
+    Conformance(ConformanceArgs),
+    /// Check a built component against the component ABI (exports, CBOR
+    /// round-trips, error envelopes)
+    Conform(ConformArgs),
+    /// Compare invocation latency with and without the pooling allocator
+    Bench(BenchArgs),
+    /// Run a deployed and a candidate build back to back: state and config
+    /// produced by one must keep working in the other
+    UpgradeTest(UpgradeTestArgs),
+    /// Generate test cases from operation schemas
+    #[command(subcommand)]
+    Fixtures(FixturesCommand),
     /// Flow utilities (config flow regeneration)
     #[command(subcommand)]
     Flow(FlowCommand),
+    /// Run the configurator flows a manifest declares
+    #[command(subcommand)]
+    Configurator(ConfiguratorCommand),
+    /// Inspect, verify and purge the local component cache
+    #[command(subcommand)]
+    Cache(CacheCommand),
+    /// Build and verify signed catalog indexes for component registries
+    #[command(subcommand)]
+    Index(IndexCommand),
+    /// Render the transitive component graph and check it resolves
+    Graph(GraphArgs),
+    /// Search configured registry indexes and the local catalog for components
+    Search(SearchArgs),
+    /// Vendor and check the canonical WIT packages for an ABI version
+    #[command(subcommand)]
+    Wit(WitCommand),
+    /// Generate typed Rust helpers over the host imports for an ABI version
+    #[command(subcommand)]
+    Sdk(SdkCommand),
     /// Interact with the component store
     #[cfg(feature = "store")]
     #[command(subcommand)]
@@ -56,6 +126,7 @@ See docs/component-developer-guide.md for a walkthrough."
 
 pub fn main() -> Result<()> {
     let cli = Cli::parse();
+    cmd::progress::set_format(cli.log_format);
     let engine = ScaffoldEngine::new();
     match cli.command {
         Commands::New(args) => cmd::new::run(args, &engine),
@@ -74,9 +145,32 @@ pub fn main() -> Result<()> {
             Ok(())
         }
         Commands::Hash(args) => cmd::hash::run(args),
+        Commands::Compat(args) => cmd::compat::run(&args).map(|_| ()),
         Commands::Build(args) => cmd::build::run(args),
+        Commands::Sync(args) => cmd::sync::run(&args).map(|_| ()),
+        Commands::VerifyBuild(args) => cmd::verify_build::run(&args).map(|_| ()),
+        Commands::Op(op_cmd) => cmd::op::run(op_cmd),
+        Commands::Validate(args) => cmd::validate::run(args),
+        Commands::Lsp(args) => cmd::lsp::run(args),
+        Commands::Manifest(manifest_cmd) => cmd::manifest::run(manifest_cmd),
+        Commands::Secret(secret_cmd) => cmd::secret::run(secret_cmd),
+        Commands::Caps(caps_cmd) => cmd::caps::run(caps_cmd),
+        Commands::Client(client_cmd) => cmd::client::run(client_cmd),
+        Commands::Errors(errors_cmd) => cmd::errors::run(errors_cmd),
         Commands::Test(args) => cmd::test::run(*args),
+        Commands::Conformance(args) => cmd::conformance::run(&args).map(|_| ()),
+        Commands::Conform(args) => cmd::conform::run(&args).map(|_| ()),
+        Commands::Bench(args) => cmd::bench::run(&args).map(|_| ()),
+        Commands::UpgradeTest(args) => cmd::upgrade_test::run(&args).map(|_| ()),
+        Commands::Fixtures(fixtures_cmd) => cmd::fixtures::run(fixtures_cmd),
         Commands::Flow(flow_cmd) => cmd::flow::run(flow_cmd),
+        Commands::Configurator(configurator_cmd) => cmd::configurator::run(configurator_cmd),
+        Commands::Cache(cache_cmd) => cmd::cache::run(cache_cmd),
+        Commands::Index(index_cmd) => cmd::index::run(index_cmd),
+        Commands::Search(args) => cmd::search::run(args),
+        Commands::Graph(args) => cmd::graph::run(args),
+        Commands::Wit(wit_cmd) => cmd::wit::run(wit_cmd),
+        Commands::Sdk(sdk_cmd) => cmd::sdk::run(sdk_cmd),
         #[cfg(feature = "store")]
         Commands::Store(store_cmd) => cmd::store::run(store_cmd),
     }
@@ -122,4 +216,12 @@ mod tests {
             _ => panic!("expected wizard args"),
         }
     }
+
+    #[test]
+    fn log_format_is_accepted_after_the_subcommand() {
+        let cli = Cli::try_parse_from(["greentic-component", "build", "--log-format", "json"])
+            .expect("expected CLI to parse");
+        assert_eq!(cli.log_format, LogFormat::Json);
+        assert!(matches!(cli.command, Commands::Build(_)));
+    }
 }
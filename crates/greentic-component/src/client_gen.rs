@@ -0,0 +1,531 @@
+//! Typed client generation from a manifest's operation schemas.
+//!
+//! The generated Rust client wraps `greentic_component_runtime::invoke`; the
+//! TypeScript client takes the host's invoke transport as a function, since
+//! there is no direct binding to the runtime from JavaScript.
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+use serde_json::{Map, Value};
+
+use crate::manifest::ComponentManifest;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ClientLang {
+    Rust,
+    Ts,
+}
+
+impl ClientLang {
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            ClientLang::Rust => "rs",
+            ClientLang::Ts => "ts",
+        }
+    }
+}
+
+/// Renders a client for every operation in `manifest`.
+pub fn generate_client(manifest: &ComponentManifest, lang: ClientLang) -> String {
+    let mut types = TypeCollector::default();
+    let operations = manifest
+        .operations
+        .iter()
+        .map(|operation| {
+            let base = pascal_case(&operation.name);
+            OperationDef {
+                name: operation.name.clone(),
+                input: types.root(&format!("{base}Input"), &operation.input_schema),
+                output: types.root(&format!("{base}Output"), &operation.output_schema),
+            }
+        })
+        .collect::<Vec<_>>();
+    let client = Client {
+        component: manifest.id.as_str(),
+        version: manifest.version.to_string(),
+        name: format!("{}Client", pascal_case(&manifest.name)),
+        types: types.defs,
+        operations,
+    };
+    match lang {
+        ClientLang::Rust => render_rust(&client),
+        ClientLang::Ts => render_ts(&client),
+    }
+}
+
+struct Client<'a> {
+    component: &'a str,
+    version: String,
+    name: String,
+    types: Vec<TypeDef>,
+    operations: Vec<OperationDef>,
+}
+
+struct OperationDef {
+    name: String,
+    input: Ty,
+    output: Ty,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Ty {
+    String,
+    Integer,
+    Number,
+    Bool,
+    Any,
+    Array(Box<Ty>),
+    Map(Box<Ty>),
+    Nullable(Box<Ty>),
+    Named(String),
+}
+
+struct TypeDef {
+    name: String,
+    description: Option<String>,
+    kind: TypeKind,
+}
+
+enum TypeKind {
+    Struct(Vec<FieldDef>),
+    Enum(Vec<String>),
+    Alias(Ty),
+}
+
+struct FieldDef {
+    name: String,
+    description: Option<String>,
+    ty: Ty,
+    required: bool,
+}
+
+#[derive(Default)]
+struct TypeCollector {
+    defs: Vec<TypeDef>,
+    names: BTreeSet<String>,
+}
+
+impl TypeCollector {
+    /// Operation inputs and outputs always get a named type, even when the
+    /// schema is a scalar, so method signatures stay stable.
+    fn root(&mut self, name: &str, schema: &Value) -> Ty {
+        match self.ty(name, schema) {
+            Ty::Named(name) => Ty::Named(name),
+            other => {
+                let name = self.claim(name);
+                self.defs.push(TypeDef {
+                    name: name.clone(),
+                    description: description(schema),
+                    kind: TypeKind::Alias(other),
+                });
+                Ty::Named(name)
+            }
+        }
+    }
+
+    fn ty(&mut self, name: &str, schema: &Value) -> Ty {
+        let Some(object) = schema.as_object() else {
+            return Ty::Any;
+        };
+        if let Some(Value::Array(types)) = object.get("type") {
+            let non_null = types
+                .iter()
+                .filter(|ty| ty.as_str() != Some("null"))
+                .collect::<Vec<_>>();
+            if let [single] = non_null.as_slice() {
+                let mut narrowed = object.clone();
+                narrowed.insert("type".into(), (*single).clone());
+                let inner = self.ty(name, &Value::Object(narrowed));
+                return if non_null.len() < types.len() {
+                    Ty::Nullable(Box::new(inner))
+                } else {
+                    inner
+                };
+            }
+            return Ty::Any;
+        }
+        if let Some(values) = object.get("enum").and_then(Value::as_array) {
+            let variants = values
+                .iter()
+                .map(|value| value.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>();
+            return match variants {
+                Some(variants) if !variants.is_empty() => {
+                    let name = self.claim(name);
+                    self.defs.push(TypeDef {
+                        name: name.clone(),
+                        description: description(schema),
+                        kind: TypeKind::Enum(variants),
+                    });
+                    Ty::Named(name)
+                }
+                _ => Ty::Any,
+            };
+        }
+        match object.get("type").and_then(Value::as_str) {
+            Some("string") => Ty::String,
+            Some("integer") => Ty::Integer,
+            Some("number") => Ty::Number,
+            Some("boolean") => Ty::Bool,
+            Some("array") => {
+                let item = object
+                    .get("items")
+                    .map(|items| self.ty(&format!("{name}Item"), items))
+                    .unwrap_or(Ty::Any);
+                Ty::Array(Box::new(item))
+            }
+            Some("object") => self.object(name, object),
+            _ if object.contains_key("properties") => self.object(name, object),
+            _ => Ty::Any,
+        }
+    }
+
+    fn object(&mut self, name: &str, object: &Map<String, Value>) -> Ty {
+        let properties = object.get("properties").and_then(Value::as_object);
+        let Some(properties) = properties.filter(|properties| !properties.is_empty()) else {
+            return match object.get("additionalProperties") {
+                Some(Value::Object(values)) => {
+                    let value = self.ty(&format!("{name}Value"), &Value::Object(values.clone()));
+                    Ty::Map(Box::new(value))
+                }
+                _ => Ty::Map(Box::new(Ty::Any)),
+            };
+        };
+        let required = object
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| {
+                required
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<BTreeSet<_>>()
+            })
+            .unwrap_or_default();
+        // Claim the name before recursing so nested types cannot take it.
+        let name = self.claim(name);
+        let fields = properties
+            .iter()
+            .map(|(field, schema)| FieldDef {
+                name: field.clone(),
+                description: description(schema),
+                ty: self.ty(&format!("{name}{}", pascal_case(field)), schema),
+                required: required.contains(field.as_str()),
+            })
+            .collect();
+        self.defs.push(TypeDef {
+            name: name.clone(),
+            description: object
+                .get("description")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            kind: TypeKind::Struct(fields),
+        });
+        Ty::Named(name)
+    }
+
+    fn claim(&mut self, name: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut suffix = 2;
+        while !self.names.insert(candidate.clone()) {
+            candidate = format!("{name}{suffix}");
+            suffix += 1;
+        }
+        candidate
+    }
+}
+
+fn description(schema: &Value) -> Option<String> {
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+fn render_rust(client: &Client<'_>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Generated by `greentic-component client gen` for {}@{}. Do not edit.",
+        client.component, client.version
+    );
+    out.push_str(
+        "\nuse greentic_component_runtime::{CompError, ComponentHandle};\nuse greentic_types::TenantCtx;\nuse serde::{Deserialize, Serialize};\n",
+    );
+
+    for def in &client.types {
+        out.push('\n');
+        rust_doc(&mut out, "", def.description.as_deref());
+        match &def.kind {
+            TypeKind::Struct(fields) => {
+                out.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+                let _ = writeln!(out, "pub struct {} {{", def.name);
+                for field in fields {
+                    rust_doc(&mut out, "    ", field.description.as_deref());
+                    let ident = rust_ident(&snake_case(&field.name));
+                    if ident.trim_start_matches("r#") != field.name {
+                        let _ = writeln!(out, "    #[serde(rename = {:?})]", field.name);
+                    }
+                    let ty = rust_type(&field.ty);
+                    if field.required {
+                        let _ = writeln!(out, "    pub {ident}: {ty},");
+                    } else {
+                        out.push_str(
+                            "    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n",
+                        );
+                        let _ = writeln!(out, "    pub {ident}: Option<{ty}>,");
+                    }
+                }
+                out.push_str("}\n");
+            }
+            TypeKind::Enum(variants) => {
+                out.push_str(
+                    "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\n",
+                );
+                let _ = writeln!(out, "pub enum {} {{", def.name);
+                let mut seen = BTreeSet::new();
+                for variant in variants {
+                    let ident = unique(&mut seen, &rust_variant(variant));
+                    let _ = writeln!(out, "    #[serde(rename = {variant:?})]");
+                    let _ = writeln!(out, "    {ident},");
+                }
+                out.push_str("}\n");
+            }
+            TypeKind::Alias(ty) => {
+                let _ = writeln!(out, "pub type {} = {};", def.name, rust_type(ty));
+            }
+        }
+    }
+
+    let _ = write!(
+        out,
+        "\n/// Typed wrapper around `greentic_component_runtime::invoke` for `{component}`.\n\
+         pub struct {name}<'a> {{\n    handle: &'a ComponentHandle,\n    tenant: &'a TenantCtx,\n}}\n\n\
+         impl<'a> {name}<'a> {{\n    pub fn new(handle: &'a ComponentHandle, tenant: &'a TenantCtx) -> Self {{\n        Self {{ handle, tenant }}\n    }}\n",
+        component = client.component,
+        name = client.name,
+    );
+    for operation in &client.operations {
+        let _ = write!(
+            out,
+            "\n    pub fn {method}(&self, input: &{input}) -> Result<{output}, CompError> {{\n        \
+             let input = serde_json::to_value(input)?;\n        \
+             let output = greentic_component_runtime::invoke(self.handle, {op:?}, &input, self.tenant)?;\n        \
+             Ok(serde_json::from_value(output)?)\n    }}\n",
+            method = rust_ident(&snake_case(&operation.name)),
+            input = rust_type(&operation.input),
+            output = rust_type(&operation.output),
+            op = operation.name,
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn rust_doc(out: &mut String, indent: &str, description: Option<&str>) {
+    for line in description.into_iter().flat_map(str::lines) {
+        let _ = writeln!(out, "{indent}/// {line}");
+    }
+}
+
+fn rust_type(ty: &Ty) -> String {
+    match ty {
+        Ty::String => "String".into(),
+        Ty::Integer => "i64".into(),
+        Ty::Number => "f64".into(),
+        Ty::Bool => "bool".into(),
+        Ty::Any => "serde_json::Value".into(),
+        Ty::Array(item) => format!("Vec<{}>", rust_type(item)),
+        Ty::Map(value) => format!("std::collections::BTreeMap<String, {}>", rust_type(value)),
+        Ty::Nullable(inner) => format!("Option<{}>", rust_type(inner)),
+        Ty::Named(name) => name.clone(),
+    }
+}
+
+fn rust_variant(value: &str) -> String {
+    let ident = pascal_case(value);
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("V{ident}")
+    } else {
+        ident
+    }
+}
+
+fn rust_ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod",
+        "move", "mut", "pub", "ref", "return", "static", "struct", "super", "trait", "true",
+        "type", "unsafe", "use", "where", "while", "yield",
+    ];
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else if matches!(name, "self" | "Self") {
+        format!("{name}_")
+    } else if KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+fn render_ts(client: &Client<'_>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Generated by `greentic-component client gen` for {}@{}. Do not edit.",
+        client.component, client.version
+    );
+
+    for def in &client.types {
+        out.push('\n');
+        ts_doc(&mut out, "", def.description.as_deref());
+        match &def.kind {
+            TypeKind::Struct(fields) => {
+                let _ = writeln!(out, "export interface {} {{", def.name);
+                for field in fields {
+                    ts_doc(&mut out, "  ", field.description.as_deref());
+                    let optional = if field.required { "" } else { "?" };
+                    let _ = writeln!(
+                        out,
+                        "  {}{optional}: {};",
+                        ts_property(&field.name),
+                        ts_type(&field.ty)
+                    );
+                }
+                out.push_str("}\n");
+            }
+            TypeKind::Enum(variants) => {
+                let union = variants
+                    .iter()
+                    .map(|variant| format!("{variant:?}"))
+                    .collect::<Vec<_>>()
+                    .join(" | ");
+                let _ = writeln!(out, "export type {} = {union};", def.name);
+            }
+            TypeKind::Alias(ty) => {
+                let _ = writeln!(out, "export type {} = {};", def.name, ts_type(ty));
+            }
+        }
+    }
+
+    let _ = write!(
+        out,
+        "\n/** Sends one invocation to the host runtime and resolves with its JSON output. */\n\
+         export type InvokeFn = (operation: string, input: unknown) => Promise<unknown>;\n\n\
+         /** Typed client for `{component}`. */\nexport class {name} {{\n  constructor(private readonly invoke: InvokeFn) {{}}\n",
+        component = client.component,
+        name = client.name,
+    );
+    for operation in &client.operations {
+        let _ = write!(
+            out,
+            "\n  async {method}(input: {input}): Promise<{output}> {{\n    \
+             return (await this.invoke({op:?}, input)) as {output};\n  }}\n",
+            method = camel_case(&operation.name),
+            input = ts_type(&operation.input),
+            output = ts_type(&operation.output),
+            op = operation.name,
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn ts_doc(out: &mut String, indent: &str, description: Option<&str>) {
+    if let Some(description) = description {
+        let _ = writeln!(
+            out,
+            "{indent}/** {} */",
+            description.replace("*/", "*\\/").replace('\n', " ")
+        );
+    }
+}
+
+fn ts_type(ty: &Ty) -> String {
+    match ty {
+        Ty::String => "string".into(),
+        Ty::Integer | Ty::Number => "number".into(),
+        Ty::Bool => "boolean".into(),
+        Ty::Any => "unknown".into(),
+        Ty::Array(item) => match item.as_ref() {
+            Ty::Nullable(_) => format!("Array<{}>", ts_type(item)),
+            _ => format!("{}[]", ts_type(item)),
+        },
+        Ty::Map(value) => format!("Record<string, {}>", ts_type(value)),
+        Ty::Nullable(inner) => format!("{} | null", ts_type(inner)),
+        Ty::Named(name) => name.clone(),
+    }
+}
+
+fn ts_property(name: &str) -> String {
+    let plain = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        name.to_string()
+    } else {
+        format!("{name:?}")
+    }
+}
+
+fn unique(seen: &mut BTreeSet<String>, ident: &str) -> String {
+    let mut candidate = ident.to_string();
+    let mut suffix = 2;
+    while !seen.insert(candidate.clone()) {
+        candidate = format!("{ident}{suffix}");
+        suffix += 1;
+    }
+    candidate
+}
+
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && prev_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn pascal_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn camel_case(name: &str) -> String {
+    let pascal = pascal_case(name);
+    let mut chars = pascal.chars();
+    chars
+        .next()
+        .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+fn snake_case(name: &str) -> String {
+    words(name).join("_")
+}
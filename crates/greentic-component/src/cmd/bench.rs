@@ -0,0 +1,234 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{Context, Result, anyhow, bail};
+use clap::Args;
+use greentic_component_runtime::PoolingConfig;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::test::{harness_for_manifest, select_profile};
+use crate::config::resolve_manifest_path;
+use crate::manifest::{parse_manifest_value, resolve_schema_refs};
+use crate::test_harness::{AuditLog, InvokeOutcome};
+
+#[derive(Args, Debug, Clone)]
+pub struct BenchArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+    /// Component wasm to benchmark (defaults to the manifest's wasm artifact)
+    #[arg(long, value_name = "PATH")]
+    pub wasm: Option<PathBuf>,
+    /// Operation to invoke (defaults to the manifest's default operation)
+    #[arg(long)]
+    pub op: Option<String>,
+    /// Invocation input as inline JSON
+    #[arg(long = "input-json", value_name = "JSON", default_value = "{}")]
+    pub input_json: String,
+    /// Timed invocations per allocator, after one warm-up call
+    #[arg(long, default_value_t = 50)]
+    pub iterations: u32,
+    /// Instance slots reserved by the pooling allocator
+    #[arg(long, default_value_t = 16)]
+    pub pool_instances: u32,
+    /// Largest pooled linear memory in 64 KiB pages (default: the harness's
+    /// 256 MiB memory cap)
+    #[arg(long, default_value_t = 4096)]
+    pub pool_memory_pages: u64,
+    /// Tables across all pooled instances (default: four per instance)
+    #[arg(long)]
+    pub pool_tables: Option<u32>,
+    /// Manifest profile to run under (defaults to `profiles.default`)
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+    /// Emit the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub operation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    pub iterations: u32,
+    pub pooling_off: BenchTimings,
+    pub pooling_on: BenchTimings,
+    /// Measured on the on-demand allocator; the guest does the same work
+    /// under pooling.
+    pub usage: BenchUsage,
+}
+
+/// Wall time of whole invocations (instantiate + run), in microseconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchTimings {
+    pub mean_us: u64,
+    pub p50_us: u64,
+    pub p95_us: u64,
+    pub max_us: u64,
+}
+
+/// Resource use per invocation across the timed iterations.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BenchUsage {
+    pub peak_memory_bytes: usize,
+    pub table_growth: u64,
+    pub mean_host_call_count: u64,
+    /// `None` unless the engine meters fuel.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_fuel_consumed: Option<u64>,
+}
+
+pub fn run(args: &BenchArgs) -> Result<BenchReport> {
+    if args.iterations == 0 {
+        bail!("--iterations must be at least 1");
+    }
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let mut manifest_value: Value =
+        serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    let manifest_dir = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    resolve_schema_refs(&mut manifest_value, manifest_dir)
+        .context("resolve manifest schema references")?;
+    let manifest = parse_manifest_value(manifest_value.clone()).context("parse manifest")?;
+
+    let wasm = args
+        .wasm
+        .clone()
+        .unwrap_or_else(|| manifest.wasm_artifact_path(manifest_dir));
+    let wasm_bytes = fs::read(&wasm).with_context(|| format!("read wasm {}", wasm.display()))?;
+    let op = args
+        .op
+        .clone()
+        .or_else(|| manifest.default_operation.clone())
+        .or_else(|| match manifest.operations.as_slice() {
+            [only] => Some(only.name.clone()),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("pass --op; the manifest has no default operation"))?;
+    let input: Value = serde_json::from_str(&args.input_json).context("--input-json")?;
+    let profile = select_profile(&manifest, args.profile.as_deref())?;
+
+    let mut pooling =
+        PoolingConfig::new(args.pool_instances).with_max_memory_pages(args.pool_memory_pages);
+    if let Some(tables) = args.pool_tables {
+        pooling = pooling.with_max_tables(tables);
+    }
+
+    let measure = |pooling: Option<PoolingConfig>| -> Result<(BenchTimings, BenchUsage)> {
+        let (harness, _sandbox) = harness_for_manifest(
+            wasm_bytes.clone(),
+            &manifest_value,
+            &manifest,
+            None,
+            AuditLog::default(),
+            pooling,
+            profile.as_deref(),
+        )?;
+        harness
+            .invoke(&op, &input)
+            .with_context(|| format!("warm-up invoke `{op}`"))?;
+        let mut samples = Vec::with_capacity(args.iterations as usize);
+        let mut outcomes = Vec::with_capacity(args.iterations as usize);
+        for _ in 0..args.iterations {
+            let started = Instant::now();
+            let outcome = harness
+                .invoke(&op, &input)
+                .with_context(|| format!("invoke `{op}`"))?;
+            samples.push(started.elapsed().as_micros() as u64);
+            outcomes.push(outcome);
+        }
+        Ok((timings(samples), usage(&outcomes)))
+    };
+    let (pooling_off, usage) = measure(None)?;
+    let (pooling_on, _) = measure(Some(pooling))?;
+    let report = BenchReport {
+        pooling_off,
+        pooling_on,
+        usage,
+        operation: op,
+        profile,
+        iterations: args.iterations,
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        let profile = report
+            .profile
+            .as_deref()
+            .map(|profile| format!(", profile {profile}"))
+            .unwrap_or_default();
+        println!(
+            "bench `{}` ({} iterations{profile}, instantiate + run)",
+            report.operation, report.iterations
+        );
+        println!(
+            "{:<12} {:>10} {:>10} {:>10} {:>10}",
+            "allocator", "mean", "p50", "p95", "max"
+        );
+        for (label, timings) in [
+            ("on-demand", &report.pooling_off),
+            ("pooling", &report.pooling_on),
+        ] {
+            println!(
+                "{label:<12} {:>8}us {:>8}us {:>8}us {:>8}us",
+                timings.mean_us, timings.p50_us, timings.p95_us, timings.max_us
+            );
+        }
+        let usage = &report.usage;
+        let fuel = usage
+            .mean_fuel_consumed
+            .map_or_else(|| "not metered".to_string(), |fuel| fuel.to_string());
+        println!(
+            "usage: peak memory {} bytes, {} table elements, {} host calls, fuel {fuel}",
+            usage.peak_memory_bytes, usage.table_growth, usage.mean_host_call_count
+        );
+    }
+    Ok(report)
+}
+
+fn timings(mut samples: Vec<u64>) -> BenchTimings {
+    samples.sort_unstable();
+    let percentile = |pct: usize| samples[(samples.len() - 1) * pct / 100];
+    BenchTimings {
+        mean_us: samples.iter().sum::<u64>() / samples.len() as u64,
+        p50_us: percentile(50),
+        p95_us: percentile(95),
+        max_us: percentile(100),
+    }
+}
+
+fn usage(outcomes: &[InvokeOutcome]) -> BenchUsage {
+    let count = outcomes.len().max(1) as u64;
+    let fuel: Option<Vec<u64>> = outcomes
+        .iter()
+        .map(|outcome| outcome.fuel_consumed)
+        .collect();
+    BenchUsage {
+        peak_memory_bytes: outcomes
+            .iter()
+            .map(|outcome| outcome.peak_memory_bytes)
+            .max()
+            .unwrap_or_default(),
+        table_growth: outcomes
+            .iter()
+            .map(|outcome| outcome.table_growth)
+            .max()
+            .unwrap_or_default(),
+        mean_host_call_count: outcomes
+            .iter()
+            .map(|outcome| outcome.host_call_count)
+            .sum::<u64>()
+            / count,
+        mean_fuel_consumed: fuel
+            .filter(|fuel| !fuel.is_empty())
+            .map(|fuel| fuel.iter().sum::<u64>() / count),
+    }
+}
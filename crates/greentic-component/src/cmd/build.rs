@@ -7,7 +7,9 @@ use std::process::Command;
 
 use anyhow::{Context, Result, anyhow, bail};
 use clap::Args;
-use serde_json::Value as JsonValue;
+use serde_json::{Value as JsonValue, json};
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
 use wasmtime::component::{Component, Linker, Val};
 use wasmtime::{Engine, Store};
 use wasmtime_wasi::{ResourceTable, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
@@ -17,11 +19,18 @@ use crate::cmd::component_world::{canonical_component_world, is_fallback_world};
 use crate::cmd::flow::{
     FlowUpdateResult, manifest_component_id, resolve_operation, update_with_manifest,
 };
+use crate::cmd::progress;
 use crate::config::{
     ConfigInferenceOptions, ConfigSchemaSource, load_manifest_with_schema, resolve_manifest_path,
 };
+use crate::manifest::{
+    BuildCommand, apply_build_profile, build_command, parse_manifest_for_profile,
+    resolve_schema_refs,
+};
+use crate::manifest_sync::{check_manifest_sync, verify_schema_hashes};
 use crate::parse_manifest;
 use crate::path_safety::normalize_under_root;
+use crate::provenance::Provenance;
 use crate::schema_quality::{SchemaQualityMode, validate_operation_schemas};
 use greentic_types::cbor::canonical;
 use greentic_types::schemas::component::v0_6_0::ComponentDescribe;
@@ -57,6 +66,18 @@ pub struct BuildArgs {
     /// Allow empty operation schemas (warnings only)
     #[arg(long)]
     pub permissive: bool,
+    /// Treat build warnings as errors, reported with stable `E_*` codes
+    #[arg(long, conflicts_with = "permissive")]
+    pub strict: bool,
+    /// Build profile from `build.profiles` to apply (e.g. dev, staging, prod)
+    #[arg(long = "profile", value_name = "NAME")]
+    pub profile: Option<String>,
+    /// Record build provenance in the manifest (rewrites it on every build)
+    #[arg(long = "provenance")]
+    pub provenance: bool,
+    /// Emit an in-toto/SLSA provenance attestation into dist/
+    #[arg(long = "attestation")]
+    pub attestation: bool,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -68,6 +89,14 @@ struct BuildSummary {
     schema_written: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     flows: Option<FlowUpdateResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile_manifest: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provenance: Option<Provenance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attestation: Option<PathBuf>,
 }
 
 pub fn run(args: BuildArgs) -> Result<()> {
@@ -98,27 +127,45 @@ pub fn run(args: BuildArgs) -> Result<()> {
         cargo_bin.display()
     );
 
-    let config = load_manifest_with_schema(&manifest_path, &inference_opts)?;
     let mode = if args.permissive {
         SchemaQualityMode::Permissive
     } else {
         SchemaQualityMode::Strict
     };
-    let manifest_component = parse_manifest(
-        &serde_json::to_string(&config.manifest)
-            .context("failed to serialize manifest for schema validation")?,
-    )
-    .context("failed to parse manifest for schema validation")?;
+    let manifest_dir = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+    let (config, resolved_manifest, manifest_component) =
+        progress::phase("build", "manifest", || {
+            let config = load_manifest_with_schema(&manifest_path, &inference_opts)?;
+            // Validate against inlined schemas; the source manifest keeps its `$ref`s.
+            let mut resolved_manifest = config.manifest.clone();
+            resolve_schema_refs(&mut resolved_manifest, manifest_dir)
+                .context("failed to resolve manifest schema references")?;
+            let manifest_raw = serde_json::to_string(&resolved_manifest)
+                .context("failed to serialize manifest for schema validation")?;
+            let manifest_component = match &args.profile {
+                Some(profile) => parse_manifest_for_profile(&manifest_raw, profile)
+                    .with_context(|| format!("failed to apply build profile `{profile}`"))?,
+                None => parse_manifest(&manifest_raw)
+                    .context("failed to parse manifest for schema validation")?,
+            };
+            Ok((config, resolved_manifest, manifest_component))
+        })?;
+    let mut diagnostics = BuildDiagnostics::new(args.strict);
     let schema_warnings = validate_operation_schemas(&manifest_component, mode)?;
     for warning in schema_warnings {
-        eprintln!("warning[W_OP_SCHEMA_EMPTY]: {}", warning.message);
+        diagnostics.warn("OP_SCHEMA_EMPTY", &warning.message);
+    }
+    for missing in missing_descriptions(&resolved_manifest) {
+        diagnostics.warn("MISSING_DESCRIPTION", &missing);
     }
     let component_id = manifest_component_id(&config.manifest)?;
     let _operation = resolve_operation(&config.manifest, component_id)?;
     let flow_outcome = if args.no_flow {
         None
     } else {
-        Some(update_with_manifest(&config)?)
+        Some(progress::phase("build", "flows", || {
+            update_with_manifest(&config)
+        })?)
     };
 
     let mut manifest_to_write = flow_outcome
@@ -126,9 +173,27 @@ pub fn run(args: BuildArgs) -> Result<()> {
         .map(|outcome| outcome.manifest.clone())
         .unwrap_or_else(|| config.manifest.clone());
 
-    let manifest_dir = manifest_path.parent().unwrap_or_else(|| Path::new("."));
-    build_wasm(manifest_dir, &cargo_bin, &manifest_to_write)?;
-    check_canonical_world_export(manifest_dir, &manifest_to_write)?;
+    let provenance = if args.provenance || args.attestation {
+        progress::phase("build", "provenance", || {
+            collect_provenance(manifest_dir, &cargo_bin)
+        })?
+    } else {
+        None
+    };
+    if args.attestation && provenance.is_none() {
+        bail!("--attestation requires build provenance; run the build from inside a git checkout");
+    }
+    let backend = BuildBackend::resolve(manifest_dir, &manifest_to_write, &cargo_bin)?;
+    progress::phase("build", "compile", || {
+        build_wasm(manifest_dir, &backend, &manifest_to_write)
+    })?;
+    check_canonical_world_export(manifest_dir, &manifest_to_write, &mut diagnostics)?;
+    if args.provenance
+        && let Some(provenance) = &provenance
+    {
+        manifest_to_write["provenance"] =
+            serde_json::to_value(provenance).context("failed to serialize provenance")?;
+    }
 
     if !config.persist_schema {
         manifest_to_write
@@ -136,8 +201,54 @@ pub fn run(args: BuildArgs) -> Result<()> {
             .map(|obj| obj.remove("config_schema"));
     }
     let (wasm_path, wasm_hash) = update_manifest_hashes(manifest_dir, &mut manifest_to_write)?;
-    emit_describe_artifacts(manifest_dir, &manifest_to_write, &wasm_path)?;
-    write_manifest(&manifest_path, &manifest_to_write)?;
+    progress::phase("build", "describe", || {
+        if let Some(describe) = emit_describe_artifacts(
+            manifest_dir,
+            &manifest_to_write,
+            &wasm_path,
+            &wasm_hash,
+            &mut diagnostics,
+        )? {
+            warn_describe_drift(
+                manifest_dir,
+                &manifest_to_write,
+                &describe,
+                &mut diagnostics,
+            )?;
+            check_schema_hashes(
+                manifest_dir,
+                &manifest_to_write,
+                &describe,
+                args.permissive,
+                &mut diagnostics,
+            )?;
+        }
+        Ok(())
+    })?;
+    // Strict builds stop here, before the manifest or attestation is written.
+    diagnostics.finish()?;
+    let attestation = match (&provenance, args.attestation) {
+        (Some(provenance), true) => Some(emit_attestation(
+            manifest_dir,
+            &manifest_path,
+            &manifest_to_write,
+            &wasm_path,
+            provenance,
+        )?),
+        _ => None,
+    };
+    progress::phase("build", "write", || {
+        write_manifest(&manifest_path, &manifest_to_write)
+    })?;
+    let profile_manifest = match &args.profile {
+        Some(profile) => Some(emit_profile_manifest(
+            manifest_dir,
+            &manifest_to_write,
+            &wasm_path,
+            profile,
+        )?),
+        None => None,
+    };
 
     if args.json {
         let payload = BuildSummary {
@@ -147,6 +258,10 @@ pub fn run(args: BuildArgs) -> Result<()> {
             config_source: config.source,
             schema_written: config.schema_written && config.persist_schema,
             flows: flow_outcome.as_ref().map(|outcome| outcome.result),
+            profile: args.profile.clone(),
+            profile_manifest,
+            provenance,
+            attestation,
         };
         serde_json::to_writer_pretty(std::io::stdout(), &payload)?;
         println!();
@@ -160,6 +275,25 @@ pub fn run(args: BuildArgs) -> Result<()> {
                 config.source
             );
         }
+        if let (Some(profile), Some(path)) = (&args.profile, &profile_manifest) {
+            println!("Wrote `{profile}` profile manifest to {}", path.display());
+        }
+        match &provenance {
+            Some(provenance) if args.provenance => println!(
+                "Recorded provenance (commit {}{})",
+                provenance.git_commit,
+                if provenance.git_dirty == Some(true) {
+                    ", dirty"
+                } else {
+                    ""
+                }
+            ),
+            None if args.provenance => println!("Provenance skipped (not a git checkout)"),
+            _ => {}
+        }
+        if let Some(path) = &attestation {
+            println!("Wrote provenance attestation to {}", path.display());
+        }
         if let Some(outcome) = flow_outcome {
             let flows = outcome.result;
             println!(
@@ -174,78 +308,273 @@ pub fn run(args: BuildArgs) -> Result<()> {
     Ok(())
 }
 
-fn build_wasm(manifest_dir: &Path, cargo_bin: &Path, manifest: &JsonValue) -> Result<()> {
+fn build_wasm(manifest_dir: &Path, backend: &BuildBackend, manifest: &JsonValue) -> Result<()> {
     let resolved_world = manifest.get("world").and_then(|v| v.as_str()).unwrap_or("");
     if resolved_world.is_empty() {
         println!("Resolved manifest world: <missing>");
     } else {
         println!("Resolved manifest world: {resolved_world}");
     }
-    let require_component = resolved_world.contains("component@0.6.0");
-
-    if require_component {
-        if cargo_component_available(cargo_bin) {
-            println!(
-                "Running cargo component build via {} in {}",
-                cargo_bin.display(),
-                manifest_dir.display()
+    let mut cmd = backend.command(manifest_dir, manifest)?;
+    let invocation = backend.invocation(&cmd);
+    match backend {
+        BuildBackend::Cargo(cargo_bin) => println!(
+            "Running {invocation} via {} in {}",
+            cargo_bin.display(),
+            manifest_dir.display()
+        ),
+        BuildBackend::Custom(_) => println!(
+            "Running build command `{invocation}` in {}",
+            manifest_dir.display()
+        ),
+    }
+    let status = cmd
+        .status()
+        .with_context(|| format!("failed to run {invocation}"))?;
+    if !status.success() {
+        bail!("{invocation} failed with status {status}");
+    }
+    if let BuildBackend::Custom(_) = backend {
+        let wasm_path = resolve_wasm_path(manifest_dir, manifest)?;
+        if !wasm_path.is_file() {
+            bail!(
+                "build command `{invocation}` succeeded but left no wasm at {}; point artifacts.component_wasm at its output",
+                wasm_path.display()
             );
-            let mut cmd = Command::new(cargo_bin);
-            if let Some(flags) = resolved_wasm_rustflags() {
-                cmd.env("RUSTFLAGS", sanitize_wasm_rustflags(&flags));
-            }
-            let status = cmd
-                .arg("component")
-                .arg("build")
-                .arg("--target")
-                .arg("wasm32-wasip2")
-                .arg("--release")
-                .current_dir(manifest_dir)
-                .status()
-                .with_context(|| {
-                    format!(
-                        "failed to run cargo component build via {}",
-                        cargo_bin.display()
-                    )
-                })?;
-            if !status.success() {
-                bail!(
-                    "cargo component build --target wasm32-wasip2 --release failed with status {}",
-                    status
-                );
+        }
+    }
+    Ok(())
+}
+
+/// The compile step `build` runs before hashing, describe extraction, flow
+/// regeneration and dist packaging.
+#[derive(Debug, Clone)]
+pub(crate) enum BuildBackend {
+    /// `cargo build`, or `cargo component build` for component@0.6.0 worlds.
+    Cargo(PathBuf),
+    /// `build.command` from the manifest, or the build table in the project's
+    /// package metadata (see [`package_build_command`]).
+    Custom(BuildCommand),
+}
+
+impl BuildBackend {
+    /// The manifest's `build.command` wins over package metadata; without
+    /// either, cargo builds the component.
+    pub(crate) fn resolve(
+        manifest_dir: &Path,
+        manifest: &JsonValue,
+        cargo_bin: &Path,
+    ) -> Result<Self> {
+        if let Some(command) = build_command(manifest).context("invalid build.command")? {
+            return Ok(Self::Custom(command));
+        }
+        if let Some(command) = package_build_command(manifest_dir)? {
+            return Ok(Self::Custom(command));
+        }
+        Ok(Self::Cargo(cargo_bin.to_path_buf()))
+    }
+
+    pub(crate) fn command(&self, manifest_dir: &Path, manifest: &JsonValue) -> Result<Command> {
+        match self {
+            Self::Cargo(cargo_bin) => wasm_build_command(manifest_dir, cargo_bin, manifest),
+            Self::Custom(spec) => {
+                let (program, args) = spec
+                    .run
+                    .split_first()
+                    .ok_or_else(|| anyhow!("build.command.run must name a program"))?;
+                if manifest
+                    .get("artifacts")
+                    .and_then(|artifacts| artifacts.get("component_wasm"))
+                    .is_none()
+                {
+                    bail!("build.command requires artifacts.component_wasm to locate its output");
+                }
+                let mut cmd = Command::new(program);
+                cmd.args(args).envs(&spec.env).current_dir(manifest_dir);
+                Ok(cmd)
             }
-            return Ok(());
         }
+    }
+
+    /// How `cmd` is shown in progress and error messages.
+    pub(crate) fn invocation(&self, cmd: &Command) -> String {
+        match self {
+            Self::Cargo(_) => cargo_invocation(cmd),
+            Self::Custom(spec) => spec.run.join(" "),
+        }
+    }
+}
+
+/// TOML package files that may carry a build command, with the table it
+/// lives under.
+const TOML_BUILD_TABLES: &[(&str, &[&str])] = &[
+    ("Cargo.toml", &["package", "metadata", "greentic", "build"]),
+    ("pyproject.toml", &["tool", "greentic", "build"]),
+];
+
+/// Reads the build command from the project's package metadata:
+/// `[package.metadata.greentic.build]` in Cargo.toml,
+/// `[tool.greentic.build]` in pyproject.toml or `greentic.build` in
+/// package.json, first match wins.
+fn package_build_command(manifest_dir: &Path) -> Result<Option<BuildCommand>> {
+    for (file, keys) in TOML_BUILD_TABLES {
+        let Ok(contents) = fs::read_to_string(manifest_dir.join(file)) else {
+            continue;
+        };
+        let Ok(doc) = toml::from_str::<toml::Value>(&contents) else {
+            continue;
+        };
+        if let Some(build) = keys.iter().try_fold(&doc, |value, key| value.get(*key)) {
+            return build
+                .clone()
+                .try_into::<BuildCommand>()
+                .map(Some)
+                .with_context(|| format!("invalid [{}] in {file}", keys.join(".")));
+        }
+    }
+    if let Ok(contents) = fs::read_to_string(manifest_dir.join("package.json"))
+        && let Ok(doc) = serde_json::from_str::<JsonValue>(&contents)
+        && let Some(build) = doc.pointer("/greentic/build")
+    {
+        return serde_json::from_value(build.clone())
+            .map(Some)
+            .context("invalid greentic.build in package.json");
+    }
+    Ok(None)
+}
+
+/// The cargo command `build` runs inside `manifest_dir` to produce the
+/// component wasm; component@0.6.0 worlds go through cargo-component.
+fn wasm_build_command(
+    manifest_dir: &Path,
+    cargo_bin: &Path,
+    manifest: &JsonValue,
+) -> Result<Command> {
+    let args = wasm_build_args(manifest);
+    if args.first() == Some(&"component") && !cargo_component_available(cargo_bin) {
         bail!(
             "component@0.6.0 manifests require cargo-component; install it with `cargo install cargo-component --locked`"
         );
     }
-
-    println!(
-        "Running cargo build via {} in {}",
-        cargo_bin.display(),
-        manifest_dir.display()
-    );
     let mut cmd = Command::new(cargo_bin);
     if let Some(flags) = resolved_wasm_rustflags() {
         cmd.env("RUSTFLAGS", sanitize_wasm_rustflags(&flags));
     }
-    let status = cmd
-        .arg("build")
-        .arg("--target")
-        .arg("wasm32-wasip2")
-        .arg("--release")
-        .current_dir(manifest_dir)
-        .status()
-        .with_context(|| format!("failed to run cargo build via {}", cargo_bin.display()))?;
+    cmd.args(args).current_dir(manifest_dir);
+    Ok(cmd)
+}
 
-    if !status.success() {
-        bail!(
-            "cargo build --target wasm32-wasip2 --release failed with status {}",
-            status
-        );
+/// Cargo arguments that build the component wasm for `manifest`'s world.
+pub(crate) fn wasm_build_args(manifest: &JsonValue) -> Vec<&'static str> {
+    let world = manifest.get("world").and_then(|v| v.as_str()).unwrap_or("");
+    let mut args = Vec::new();
+    if world.contains("component@0.6.0") {
+        args.push("component");
     }
-    Ok(())
+    args.extend(["build", "--target", "wasm32-wasip2", "--release"]);
+    args
+}
+
+/// Renders `cmd` as `cargo <args>` for progress and error messages.
+fn cargo_invocation(cmd: &Command) -> String {
+    let mut words = vec!["cargo".to_string()];
+    words.extend(cmd.get_args().map(|arg| arg.to_string_lossy().into_owned()));
+    words.join(" ")
+}
+
+/// Gathers build provenance for the manifest. Returns `None` when the component
+/// does not live in a git checkout, since `git_commit` is mandatory.
+fn collect_provenance(manifest_dir: &Path, cargo_bin: &Path) -> Result<Option<Provenance>> {
+    let Some(git_commit) = command_stdout(
+        Command::new("git")
+            .arg("rev-parse")
+            .arg("HEAD")
+            .current_dir(manifest_dir),
+    ) else {
+        return Ok(None);
+    };
+    let git_dirty = command_stdout(
+        Command::new("git")
+            .arg("status")
+            .arg("--porcelain")
+            .current_dir(manifest_dir),
+    )
+    .map(|status| !status.is_empty());
+    let rustc = env::var_os("RUSTC")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("rustc"));
+    let toolchain = command_stdout(
+        Command::new(&rustc)
+            .arg("--version")
+            .current_dir(manifest_dir),
+    )
+    .unwrap_or_else(|| "unknown".to_string());
+    let cargo_component = command_stdout(
+        Command::new(cargo_bin)
+            .arg("component")
+            .arg("--version")
+            .current_dir(manifest_dir),
+    )
+    .filter(|version| !version.is_empty());
+    let provenance = Provenance {
+        builder: format!("greentic-component@{}", env!("CARGO_PKG_VERSION")),
+        git_commit,
+        git_dirty,
+        toolchain,
+        cargo_component,
+        built_at_utc: build_timestamp()?,
+    };
+    provenance
+        .validate()
+        .context("collected build provenance is invalid")?;
+    Ok(Some(provenance))
+}
+
+/// The `origin` remote of the checkout with any credentials stripped, for the
+/// attestation's source dependency.
+fn source_remote(manifest_dir: &Path) -> Option<String> {
+    let url = command_stdout(
+        Command::new("git")
+            .args(["remote", "get-url", "origin"])
+            .current_dir(manifest_dir),
+    )
+    .filter(|url| !url.is_empty())?;
+    Some(match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+            let host = authority
+                .rsplit_once('@')
+                .map_or(authority, |(_, host)| host);
+            format!("{scheme}://{host}{path}")
+        }
+        None => url,
+    })
+}
+
+/// Honors `SOURCE_DATE_EPOCH` so reproducible builds record a stable timestamp.
+fn build_timestamp() -> Result<OffsetDateTime> {
+    match env::var("SOURCE_DATE_EPOCH") {
+        Ok(raw) => {
+            let secs: i64 = raw
+                .trim()
+                .parse()
+                .with_context(|| format!("SOURCE_DATE_EPOCH `{raw}` is not an integer"))?;
+            OffsetDateTime::from_unix_timestamp(secs)
+                .with_context(|| format!("SOURCE_DATE_EPOCH `{raw}` is out of range"))
+        }
+        Err(_) => Ok(OffsetDateTime::now_utc()
+            .replace_nanosecond(0)
+            .expect("zero nanoseconds is always valid")),
+    }
+}
+
+pub(crate) fn command_stdout(cmd: &mut Command) -> Option<String> {
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    Some(stdout.trim().to_string())
 }
 
 fn cargo_component_available(cargo_bin: &Path) -> bool {
@@ -258,14 +587,14 @@ fn cargo_component_available(cargo_bin: &Path) -> bool {
 }
 
 /// Reads the wasm-specific rustflags that CI exports for wasm builds.
-fn resolved_wasm_rustflags() -> Option<String> {
+pub(crate) fn resolved_wasm_rustflags() -> Option<String> {
     env::var("WASM_RUSTFLAGS")
         .ok()
         .or_else(|| env::var("RUSTFLAGS").ok())
 }
 
 /// Drops linker arguments that `wasm-component-ld` rejects and normalizes whitespace.
-fn sanitize_wasm_rustflags(flags: &str) -> String {
+pub(crate) fn sanitize_wasm_rustflags(flags: &str) -> String {
     flags
         .replace("-Wl,", "")
         .replace("-C link-arg=--no-keep-memory", "")
@@ -275,7 +604,11 @@ fn sanitize_wasm_rustflags(flags: &str) -> String {
         .join(" ")
 }
 
-fn check_canonical_world_export(manifest_dir: &Path, manifest: &JsonValue) -> Result<()> {
+fn check_canonical_world_export(
+    manifest_dir: &Path,
+    manifest: &JsonValue,
+    diagnostics: &mut BuildDiagnostics,
+) -> Result<()> {
     if env::var_os("GREENTIC_SKIP_NODE_EXPORT_CHECK").is_some() {
         println!("World export check skipped (GREENTIC_SKIP_NODE_EXPORT_CHECK=1)");
         return Ok(());
@@ -287,6 +620,10 @@ fn check_canonical_world_export(manifest_dir: &Path, manifest: &JsonValue) -> Re
         Err(err) => match err {
             AbiError::WorldMismatch { expected, found } if is_fallback_world(&found) => {
                 println!("Exported world: fallback {found} (expected {expected})");
+                diagnostics.warn(
+                    "FALLBACK_WORLD",
+                    &format!("component exports fallback world {found} instead of {expected}"),
+                );
             }
             err => {
                 return Err(err)
@@ -379,37 +716,202 @@ fn resolve_wasm_path(manifest_dir: &Path, manifest: &JsonValue) -> Result<PathBu
     Ok(normalized)
 }
 
+/// Build warnings keyed by stable codes. They print as `warning[W_<CODE>]`;
+/// under `--strict` they print as `error[E_<CODE>]` and fail the build once
+/// every check has run, so one pass reports all of them.
+struct BuildDiagnostics {
+    strict: bool,
+    errors: Vec<String>,
+}
+
+impl BuildDiagnostics {
+    fn new(strict: bool) -> Self {
+        Self {
+            strict,
+            errors: Vec::new(),
+        }
+    }
+
+    fn warn(&mut self, code: &str, message: &str) {
+        if self.strict {
+            eprintln!("error[E_{code}]: {message}");
+            progress::diagnostic("build", "error", &format!("E_{code}"), message);
+            self.errors.push(format!("E_{code}: {message}"));
+        } else {
+            eprintln!("warning[W_{code}]: {message}");
+            progress::diagnostic("build", "warning", &format!("W_{code}"), message);
+        }
+    }
+
+    fn finish(&self) -> Result<()> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        bail!(
+            "--strict: {} warning(s) treated as errors:\n  {}",
+            self.errors.len(),
+            self.errors.join("\n  ")
+        )
+    }
+}
+
+/// Top-level properties of the config and operation schemas that carry no
+/// `description`, one entry per schema.
+fn missing_descriptions(manifest: &JsonValue) -> Vec<String> {
+    let mut schemas = Vec::new();
+    if let Some(schema) = manifest.get("config_schema") {
+        schemas.push(("config_schema".to_string(), schema));
+    }
+    for operation in manifest
+        .get("operations")
+        .and_then(JsonValue::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let name = operation
+            .get("name")
+            .and_then(JsonValue::as_str)
+            .unwrap_or("?");
+        for field in ["input_schema", "output_schema"] {
+            if let Some(schema) = operation.get(field) {
+                schemas.push((format!("operations.{name}.{field}"), schema));
+            }
+        }
+    }
+    schemas
+        .into_iter()
+        .filter_map(|(path, schema)| {
+            let properties = schema.get("properties")?.as_object()?;
+            let missing = properties
+                .iter()
+                .filter(|(_, property)| {
+                    property
+                        .get("description")
+                        .and_then(JsonValue::as_str)
+                        .is_none_or(|text| text.trim().is_empty())
+                })
+                .map(|(name, _)| format!("`{name}`"))
+                .collect::<Vec<_>>();
+            (!missing.is_empty()).then(|| {
+                format!(
+                    "{path}: properties without a description: {}",
+                    missing.join(", ")
+                )
+            })
+        })
+        .collect()
+}
+
 fn write_manifest(manifest_path: &Path, manifest: &JsonValue) -> Result<()> {
     let formatted = serde_json::to_string_pretty(manifest)?;
     fs::write(manifest_path, formatted + "\n")
         .with_context(|| format!("failed to write {}", manifest_path.display()))
 }
 
+/// Warns when the manifest no longer matches what the freshly built
+/// component reports through describe().
+fn warn_describe_drift(
+    manifest_dir: &Path,
+    manifest: &JsonValue,
+    describe: &ComponentDescribe,
+    diagnostics: &mut BuildDiagnostics,
+) -> Result<()> {
+    let mut resolved = manifest.clone();
+    resolve_schema_refs(&mut resolved, manifest_dir)
+        .context("resolve manifest schema references")?;
+    let report = check_manifest_sync(&resolved, describe);
+    for drift in &report.drift {
+        diagnostics.warn(
+            "DESCRIBE_DRIFT",
+            &format!("{}: {}", drift.path, drift.message),
+        );
+    }
+    if !report.in_sync() {
+        eprintln!(
+            "hint: run `greentic-component sync --write` to update the manifest from describe()"
+        );
+    }
+    Ok(())
+}
+
+/// Fails the build when an operation's `schema_hash` does not match the
+/// schemas it was computed from; `--permissive` downgrades this to warnings.
+fn check_schema_hashes(
+    manifest_dir: &Path,
+    manifest: &JsonValue,
+    describe: &ComponentDescribe,
+    permissive: bool,
+    diagnostics: &mut BuildDiagnostics,
+) -> Result<()> {
+    let mut resolved = manifest.clone();
+    resolve_schema_refs(&mut resolved, manifest_dir)
+        .context("resolve manifest schema references")?;
+    let mismatches = verify_schema_hashes(&resolved, describe);
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    let lines = mismatches
+        .iter()
+        .map(|mismatch| {
+            format!(
+                "operation `{}` ({}): {}",
+                mismatch.operation, mismatch.path, mismatch.message
+            )
+        })
+        .collect::<Vec<_>>();
+    if permissive {
+        for line in &lines {
+            diagnostics.warn("SCHEMA_HASH", line);
+        }
+        return Ok(());
+    }
+    bail!(
+        "schema_hash verification failed:\n  {}\nhint: rebuild so describe() hashes its current schemas, or run `greentic-component sync --write` if the manifest is stale",
+        lines.join("\n  ")
+    )
+}
+
 fn emit_describe_artifacts(
     manifest_dir: &Path,
     manifest: &JsonValue,
     wasm_path: &Path,
-) -> Result<()> {
+    wasm_hash: &str,
+    diagnostics: &mut BuildDiagnostics,
+) -> Result<Option<ComponentDescribe>> {
     let abi_version = read_abi_version(manifest_dir);
     let require_describe = abi_version.as_deref() == Some("0.6.0");
 
-    let describe_bytes = match call_describe(wasm_path) {
-        Ok(bytes) => bytes,
-        Err(err) => {
-            if require_describe {
-                return Err(anyhow!("describe failed: {err}"));
+    let cache_path = describe_cache_path(manifest_dir, wasm_hash);
+    let cached = cache_path
+        .as_deref()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| decode_describe(&bytes).ok());
+    let (canonical_bytes, describe) = match cached {
+        Some(hit) => hit,
+        None => {
+            let describe_bytes = match call_describe(wasm_path) {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    if require_describe {
+                        return Err(anyhow!("describe failed: {err}"));
+                    }
+                    diagnostics.warn(
+                        "DESCRIBE_SKIPPED",
+                        &format!("skipping describe artifacts ({err})"),
+                    );
+                    return Ok(None);
+                }
+            };
+            let decoded = decode_describe(&describe_bytes)?;
+            if let Some(path) = &cache_path {
+                // Best effort: without the entry the next build just calls describe again.
+                let _ = fs::create_dir_all(path.parent().unwrap_or(manifest_dir))
+                    .and_then(|_| fs::write(path, &decoded.0));
             }
-            eprintln!("warning: skipping describe artifacts ({err})");
-            return Ok(());
+            decoded
         }
     };
 
-    let payload = strip_self_describe_tag(&describe_bytes);
-    let canonical_bytes = canonical::canonicalize_allow_floats(payload)
-        .map_err(|err| anyhow!("describe canonicalization failed: {err}"))?;
-    let describe: ComponentDescribe = canonical::from_cbor(&canonical_bytes)
-        .map_err(|err| anyhow!("describe decode failed: {err}"))?;
-
     let dist_dir = manifest_dir.join("dist");
     fs::create_dir_all(&dist_dir)
         .with_context(|| format!("failed to create {}", dist_dir.display()))?;
@@ -430,10 +932,104 @@ fn emit_describe_artifacts(
         let _ = fs::copy(wasm_path, &wasm_out);
     }
 
-    Ok(())
+    Ok(Some(describe))
+}
+
+/// Where the describe payload of the wasm hashing to `wasm_hash`
+/// (`blake3:<hex>`) is cached: `<target>/greentic/describe/<hex>.cbor`,
+/// honouring `CARGO_TARGET_DIR`. The payload only depends on the wasm bytes,
+/// so an unchanged build can skip instantiating the component.
+fn describe_cache_path(manifest_dir: &Path, wasm_hash: &str) -> Option<PathBuf> {
+    let hex = wasm_hash.strip_prefix("blake3:")?;
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let target = env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| manifest_dir.join("target"));
+    let target = if target.is_relative() {
+        manifest_dir.join(target)
+    } else {
+        target
+    };
+    Some(target.join("greentic/describe").join(format!("{hex}.cbor")))
+}
+
+fn emit_attestation(
+    manifest_dir: &Path,
+    manifest_path: &Path,
+    manifest: &JsonValue,
+    wasm_path: &Path,
+    provenance: &Provenance,
+) -> Result<PathBuf> {
+    let wasm_bytes = fs::read(wasm_path)
+        .with_context(|| format!("failed to read wasm at {}", wasm_path.display()))?;
+    let sha256 = hex::encode(Sha256::digest(&wasm_bytes));
+
+    let abi_version = read_abi_version(manifest_dir);
+    let (name, abi_underscore) = artifact_basename(manifest, wasm_path, abi_version.as_deref());
+    let base = format!("{name}__{abi_underscore}");
+    let external_parameters = json!({
+        "manifest": path_string_relative(manifest_dir, manifest_path)?,
+        "component_id": manifest.get("id").cloned().unwrap_or(JsonValue::Null),
+        "version": manifest.get("version").cloned().unwrap_or(JsonValue::Null),
+        "world": manifest.get("world").cloned().unwrap_or(JsonValue::Null),
+    });
+    let statement = provenance.slsa_statement(
+        &format!("{base}.wasm"),
+        &sha256,
+        source_remote(manifest_dir).as_deref(),
+        external_parameters,
+    );
+
+    let dist_dir = manifest_dir.join("dist");
+    fs::create_dir_all(&dist_dir)
+        .with_context(|| format!("failed to create {}", dist_dir.display()))?;
+    let attestation_path = dist_dir.join(format!("{base}.intoto.json"));
+    let formatted = serde_json::to_string_pretty(&statement)?;
+    fs::write(&attestation_path, formatted + "\n")
+        .with_context(|| format!("failed to write {}", attestation_path.display()))?;
+    Ok(attestation_path)
+}
+
+/// Writes the manifest with `build.profiles.<profile>` applied into dist/, next
+/// to the wasm copy it points at.
+fn emit_profile_manifest(
+    manifest_dir: &Path,
+    manifest: &JsonValue,
+    wasm_path: &Path,
+    profile: &str,
+) -> Result<PathBuf> {
+    let mut effective = manifest.clone();
+    apply_build_profile(&mut effective, profile)
+        .with_context(|| format!("failed to apply build profile `{profile}`"))?;
+    if let Some(obj) = effective.as_object_mut() {
+        obj.remove("build");
+    }
+    // dist/ manifests travel without the source tree, so bundle schema files.
+    resolve_schema_refs(&mut effective, manifest_dir)
+        .context("failed to bundle manifest schema references")?;
+
+    let abi_version = read_abi_version(manifest_dir);
+    let (name, abi_underscore) = artifact_basename(manifest, wasm_path, abi_version.as_deref());
+    let base = format!("{name}__{abi_underscore}");
+    let dist_dir = manifest_dir.join("dist");
+    fs::create_dir_all(&dist_dir)
+        .with_context(|| format!("failed to create {}", dist_dir.display()))?;
+    let wasm_out = dist_dir.join(format!("{base}.wasm"));
+    if wasm_out != wasm_path {
+        fs::copy(wasm_path, &wasm_out)
+            .with_context(|| format!("failed to copy wasm to {}", wasm_out.display()))?;
+    }
+    effective["artifacts"]["component_wasm"] = JsonValue::String(format!("{base}.wasm"));
+
+    let profile_path = dist_dir.join(format!("{base}.{profile}.manifest.json"));
+    write_manifest(&profile_path, &effective)?;
+    Ok(profile_path)
 }
 
-fn read_abi_version(manifest_dir: &Path) -> Option<String> {
+/// `[package.metadata.greentic] abi_version` from the project's Cargo.toml.
+pub(crate) fn read_abi_version(manifest_dir: &Path) -> Option<String> {
     let cargo_path = manifest_dir.join("Cargo.toml");
     let contents = fs::read_to_string(cargo_path).ok()?;
     let doc: toml::Value = toml::from_str(&contents).ok()?;
@@ -474,6 +1070,16 @@ fn sanitize_name(raw: &str) -> String {
         .to_string()
 }
 
+/// Canonical CBOR and decoded form of a describe payload.
+fn decode_describe(bytes: &[u8]) -> Result<(Vec<u8>, ComponentDescribe)> {
+    let payload = strip_self_describe_tag(bytes);
+    let canonical_bytes = canonical::canonicalize_allow_floats(payload)
+        .map_err(|err| anyhow!("describe canonicalization failed: {err}"))?;
+    let describe: ComponentDescribe = canonical::from_cbor(&canonical_bytes)
+        .map_err(|err| anyhow!("describe decode failed: {err}"))?;
+    Ok((canonical_bytes, describe))
+}
+
 fn call_describe(wasm_path: &Path) -> Result<Vec<u8>> {
     let mut config = wasmtime::Config::new();
     config.wasm_component_model(true);
@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Subcommand};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::store::{
+    Cache, CacheEntry, CacheKey, CacheStatus, CompatPolicy, ComponentLocator, ComponentStore,
+    TamperPolicy,
+};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CacheCommand {
+    /// List cached components
+    Ls(CacheLsArgs),
+    /// Show the indexed metadata of one cached component
+    Show(CacheShowArgs),
+    /// Re-hash cached bytes and report corrupt, missing, unsigned or
+    /// undecryptable entries
+    Verify(CacheVerifyArgs),
+    /// Forget a locator and delete bytes nothing else refers to
+    Purge(CachePurgeArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CacheDirArgs {
+    /// Component cache directory (defaults to ~/.greentic/components)
+    #[arg(long, value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CacheLsArgs {
+    #[command(flatten)]
+    pub cache: CacheDirArgs,
+    /// Emit entries as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CacheShowArgs {
+    #[command(flatten)]
+    pub cache: CacheDirArgs,
+    /// `sha256:<hex>`, bare hex, or an unambiguous prefix
+    #[arg(value_name = "DIGEST")]
+    pub digest: String,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CacheVerifyArgs {
+    #[command(flatten)]
+    pub cache: CacheDirArgs,
+    /// Host key the cache index was signed with; also checks entry signatures
+    #[arg(long, value_name = "FILE")]
+    pub key_file: Option<PathBuf>,
+    /// Emit results as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CachePurgeArgs {
+    #[command(flatten)]
+    pub cache: CacheDirArgs,
+    /// Locator as it was fetched (`oci://...`, `file://...` or a path)
+    #[arg(value_name = "LOCATOR")]
+    pub locator: String,
+}
+
+pub fn run(command: CacheCommand) -> Result<()> {
+    let rt = tokio::runtime::Runtime::new().context("failed to create async runtime")?;
+    rt.block_on(async {
+        match command {
+            CacheCommand::Ls(args) => ls(&args).await,
+            CacheCommand::Show(args) => show(&args).await,
+            CacheCommand::Verify(args) => verify(&args).await,
+            CacheCommand::Purge(args) => purge(&args).await,
+        }
+    })
+}
+
+/// The cache as the CLI's [`ComponentStore`] sees it, encryption key
+/// included.
+fn open(args: &CacheDirArgs) -> Result<Cache> {
+    let store = ComponentStore::from_env(args.cache_dir.clone(), CompatPolicy::default())?;
+    Ok(store.cache().clone())
+}
+
+async fn ls(args: &CacheLsArgs) -> Result<()> {
+    let entries = open(&args.cache)?.entries().await?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("cache is empty");
+        return Ok(());
+    }
+    for entry in &entries {
+        let locators = entry
+            .locators
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "{}  {:>10}  {}  {}",
+            short_digest(&entry.id.0),
+            entry.size,
+            stored_at(entry),
+            locators
+        );
+    }
+    Ok(())
+}
+
+async fn show(args: &CacheShowArgs) -> Result<()> {
+    let Some(entry) = open(&args.cache)?.entry(&args.digest).await? else {
+        bail!("no cache entry matches `{}`", args.digest);
+    };
+    println!("{}", serde_json::to_string_pretty(&entry)?);
+    Ok(())
+}
+
+async fn verify(args: &CacheVerifyArgs) -> Result<()> {
+    let mut cache = open(&args.cache)?;
+    if let Some(path) = &args.key_file {
+        cache = cache.with_signing_key(CacheKey::from_file(path)?, TamperPolicy::Fail);
+    }
+    let checks = cache.verify().await?;
+    let bad = checks
+        .iter()
+        .filter(|check| check.status != CacheStatus::Ok)
+        .count();
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&checks)?);
+    } else {
+        for check in &checks {
+            match &check.status {
+                CacheStatus::Ok => println!("ok       {}", check.id.0),
+                CacheStatus::Missing => println!("missing  {}", check.id.0),
+                CacheStatus::Corrupt { actual } => {
+                    println!("corrupt  {} (bytes hash to {})", check.id.0, actual.0)
+                }
+                CacheStatus::Unsigned => println!("unsigned {}", check.id.0),
+                CacheStatus::Unreadable => println!("sealed   {} (cannot decrypt)", check.id.0),
+                CacheStatus::Unencrypted => println!("plain    {} (not encrypted)", check.id.0),
+            }
+        }
+    }
+    if bad > 0 {
+        bail!(
+            "{bad} of {} cache entr{} failed verification; purge and refetch them",
+            checks.len(),
+            if checks.len() == 1 { "y" } else { "ies" }
+        );
+    }
+    Ok(())
+}
+
+async fn purge(args: &CachePurgeArgs) -> Result<()> {
+    let locator = ComponentLocator::parse(&args.locator)?;
+    match open(&args.cache)?.purge(&locator).await? {
+        Some(id) => println!("purged {locator} ({})", id.0),
+        None => println!("{locator} is not cached"),
+    }
+    Ok(())
+}
+
+fn short_digest(id: &str) -> &str {
+    let hex = id.strip_prefix("sha256:").unwrap_or(id);
+    &hex[..hex.len().min(12)]
+}
+
+fn stored_at(entry: &CacheEntry) -> String {
+    OffsetDateTime::from_unix_timestamp(entry.stored_at as i64)
+        .ok()
+        .and_then(|at| at.format(&Rfc3339).ok())
+        .unwrap_or_else(|| entry.stored_at.to_string())
+}
@@ -0,0 +1,375 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{Args, Subcommand};
+use jsonschema::Validator;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::test::harness_for_manifest;
+use crate::config::resolve_manifest_path;
+use crate::manifest::{ComponentManifest, parse_manifest_value, resolve_schema_refs};
+use crate::test_harness::{AuditEntry, AuditKind, AuditLog};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CapsCommand {
+    /// Suggest a least-privilege capabilities block from observed host usage
+    Suggest(CapsSuggestArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CapsSuggestArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+    /// Component wasm to exercise (defaults to the manifest's wasm artifact)
+    #[arg(long, value_name = "PATH")]
+    pub wasm: Option<PathBuf>,
+    /// Directory of test cases to run, searched recursively; each `*.json`
+    /// file holds `{"op": ..., "input": ..., "expect": "ok"|"error"}`
+    #[arg(long, value_name = "DIR")]
+    pub cases: Option<PathBuf>,
+    /// Audit log written by `greentic-component test --audit-out` (repeatable)
+    #[arg(long = "audit", value_name = "PATH")]
+    pub audit: Vec<PathBuf>,
+    /// Emit the suggestion as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(command: CapsCommand) -> Result<()> {
+    match command {
+        CapsCommand::Suggest(args) => suggest(&args).map(|_| ()),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapsSuggestion {
+    /// Minimized `capabilities` block for the manifest.
+    pub capabilities: Value,
+    /// Hosts the component contacted over HTTP.
+    pub http_domains: Vec<String>,
+    /// Declared capabilities that were never exercised.
+    pub unused: Vec<String>,
+    /// Capabilities the component tried to use without declaring them.
+    pub undeclared: Vec<String>,
+}
+
+pub fn suggest(args: &CapsSuggestArgs) -> Result<CapsSuggestion> {
+    if args.cases.is_none() && args.audit.is_empty() {
+        bail!("pass --cases <dir> and/or --audit <file> so there is host usage to observe");
+    }
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let mut manifest_value: Value =
+        serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    let manifest_dir = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    resolve_schema_refs(&mut manifest_value, manifest_dir)
+        .context("resolve manifest schema references")?;
+    let manifest = parse_manifest_value(manifest_value.clone()).context("parse manifest")?;
+
+    let mut entries = Vec::new();
+    for path in &args.audit {
+        entries.extend(read_audit_entries(path)?);
+    }
+    if let Some(cases) = &args.cases {
+        let wasm = args
+            .wasm
+            .clone()
+            .unwrap_or_else(|| manifest.wasm_artifact_path(manifest_dir));
+        entries.extend(run_cases(&wasm, cases, &manifest_value, &manifest)?);
+    }
+
+    let mut declared = manifest_value
+        .get("capabilities")
+        .cloned()
+        .unwrap_or_else(|| Value::Object(Map::new()));
+    component_manifest::expand_capability_preset(&mut declared)?;
+    let suggestion = suggest_capabilities(&declared, &entries)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&suggestion)?);
+    } else {
+        emit_human(&suggestion)?;
+    }
+    Ok(suggestion)
+}
+
+/// Narrows a declared `capabilities` block (presets already expanded) to what
+/// the audit entries show was used.
+///
+/// Only HTTP client access, secrets and state are observable through the
+/// harness; everything else (WASI, messaging, events, telemetry, ...) is kept
+/// as declared.
+pub fn suggest_capabilities(declared: &Value, entries: &[AuditEntry]) -> Result<CapsSuggestion> {
+    let mut capabilities = declared.clone();
+    let host = capabilities
+        .get_mut("host")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| anyhow!("capabilities.host must be an object"))?;
+    let mut unused = Vec::new();
+    let mut undeclared = Vec::new();
+    let used = |kind: AuditKind| entries.iter().any(|entry| entry.kind == kind);
+
+    let http_domains: BTreeSet<String> = entries
+        .iter()
+        .filter(|entry| entry.kind == AuditKind::Http)
+        .filter_map(|entry| entry.target.split_once(' '))
+        .filter_map(|(_, url)| reqwest::Url::parse(url).ok())
+        .filter_map(|url| url.host_str().map(str::to_string))
+        .collect();
+    narrow_flags(
+        host,
+        "http",
+        &[("client", used(AuditKind::Http))],
+        &mut unused,
+        &mut undeclared,
+    );
+    if let Some(http) = host.get_mut("http").and_then(Value::as_object_mut) {
+        http.insert(
+            "domains".to_string(),
+            Value::Array(http_domains.iter().cloned().map(Value::String).collect()),
+        );
+    }
+
+    let read_keys: BTreeSet<&str> = entries
+        .iter()
+        .filter(|entry| entry.kind == AuditKind::Secret)
+        .map(|entry| entry.target.as_str())
+        .collect();
+    let mut declared_keys = BTreeSet::new();
+    if let Some(required) = host
+        .get_mut("secrets")
+        .and_then(|secrets| secrets.get_mut("required"))
+        .and_then(Value::as_array_mut)
+    {
+        required.retain(|requirement| {
+            let Some(key) = requirement.get("key").and_then(Value::as_str) else {
+                return true;
+            };
+            declared_keys.insert(key.to_string());
+            if read_keys.contains(key) {
+                true
+            } else {
+                unused.push(format!("host.secrets.required[{key}]"));
+                false
+            }
+        });
+        if required.is_empty() {
+            host.remove("secrets");
+        }
+    }
+    for key in read_keys {
+        if !declared_keys.contains(key) {
+            undeclared.push(format!("host.secrets.required[{key}]"));
+        }
+    }
+
+    let delete = used(AuditKind::StateDelete);
+    narrow_flags(
+        host,
+        "state",
+        &[
+            (
+                "read",
+                used(AuditKind::StateRead) || used(AuditKind::StateList),
+            ),
+            ("write", used(AuditKind::StateWrite) || delete),
+            ("delete", delete),
+        ],
+        &mut unused,
+        &mut undeclared,
+    );
+
+    Ok(CapsSuggestion {
+        capabilities,
+        http_domains: http_domains.into_iter().collect(),
+        unused,
+        undeclared,
+    })
+}
+
+/// Sets each observable boolean flag of `host.<domain>` to whether it was
+/// used, dropping the domain once no flag remains enabled.
+fn narrow_flags(
+    host: &mut Map<String, Value>,
+    domain: &str,
+    flags: &[(&str, bool)],
+    unused: &mut Vec<String>,
+    undeclared: &mut Vec<String>,
+) {
+    let section = host
+        .entry(domain.to_string())
+        .or_insert_with(|| Value::Object(Map::new()));
+    let Some(section) = section.as_object_mut() else {
+        return;
+    };
+    for (flag, used) in flags {
+        let declared = section.get(*flag).and_then(Value::as_bool).unwrap_or(false);
+        match (declared, *used) {
+            (true, false) => unused.push(format!("host.{domain}.{flag}")),
+            (false, true) => undeclared.push(format!("host.{domain}.{flag}")),
+            _ => {}
+        }
+        if *used || section.contains_key(*flag) {
+            section.insert(flag.to_string(), Value::Bool(*used));
+        }
+    }
+    if !section.values().any(|value| value.as_bool() == Some(true)) {
+        host.remove(domain);
+    }
+}
+
+fn read_audit_entries(path: &Path) -> Result<Vec<AuditEntry>> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("read audit log {}", path.display()))?;
+    let mut report: Value = serde_json::from_str(&raw)
+        .with_context(|| format!("audit log {} must be valid JSON", path.display()))?;
+    let entries = report
+        .get_mut("entries")
+        .map(Value::take)
+        .ok_or_else(|| anyhow!("audit log {} has no `entries`", path.display()))?;
+    serde_json::from_value(entries)
+        .with_context(|| format!("audit log {} has malformed entries", path.display()))
+}
+
+/// Runs every case under `cases_dir` (including the `<op>/` directories
+/// `fixtures gen` writes) and returns the host calls they made.
+///
+/// An input the operation's `input_schema` rejects is not invoked and counts
+/// as an error. Cases with an `expect` must end the way it says; the run
+/// fails after all cases have been tried if any did not.
+fn run_cases(
+    wasm: &Path,
+    cases_dir: &Path,
+    manifest_value: &Value,
+    manifest: &ComponentManifest,
+) -> Result<Vec<AuditEntry>> {
+    let mut cases = Vec::new();
+    collect_cases(cases_dir, &mut cases)?;
+    cases.sort();
+    if cases.is_empty() {
+        bail!("no *.json test cases found in {}", cases_dir.display());
+    }
+    let validators = input_validators(manifest_value)?;
+
+    let wasm_bytes = fs::read(wasm).with_context(|| format!("read wasm {}", wasm.display()))?;
+    let audit = AuditLog::default();
+    let (harness, _sandbox) = harness_for_manifest(
+        wasm_bytes,
+        manifest_value,
+        manifest,
+        None,
+        audit.clone(),
+        None,
+        None,
+    )?;
+    let mut unmet = Vec::new();
+    for path in cases {
+        let raw =
+            fs::read_to_string(&path).with_context(|| format!("read case {}", path.display()))?;
+        let case: Value = serde_json::from_str(&raw)
+            .with_context(|| format!("case {} must be valid JSON", path.display()))?;
+        let op = case
+            .get("op")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| manifest.default_operation.clone())
+            .or_else(|| match manifest.operations.as_slice() {
+                [only] => Some(only.name.clone()),
+                _ => None,
+            })
+            .ok_or_else(|| anyhow!("case {} does not name an `op`", path.display()))?;
+        let input = case.get("input").cloned().unwrap_or(Value::Null);
+        let outcome = match validators.get(&op) {
+            Some(validator) if !validator.is_valid(&input) => {
+                Err(anyhow!("input rejected by the `{op}` input_schema"))
+            }
+            // A failing case still tells us which host calls it attempted.
+            _ => harness.invoke(&op, &input).map(|_| ()),
+        };
+        match (case.get("expect").and_then(Value::as_str), outcome) {
+            (None, Err(err)) => eprintln!("warning: case {} failed: {err:#}", path.display()),
+            (Some("ok"), Err(err)) => unmet.push(format!(
+                "{}: expected ok, got error: {err:#}",
+                path.display()
+            )),
+            (Some("error"), Ok(())) => unmet.push(format!(
+                "{}: expected an error, but the case succeeded",
+                path.display()
+            )),
+            (None | Some("ok" | "error"), _) => {}
+            (Some(other), _) => bail!(
+                "case {} has unknown expect `{other}`; use `ok` or `error`",
+                path.display()
+            ),
+        }
+    }
+    if !unmet.is_empty() {
+        bail!(
+            "{} case(s) did not end as expected:\n  {}",
+            unmet.len(),
+            unmet.join("\n  ")
+        );
+    }
+    Ok(audit.entries())
+}
+
+fn collect_cases(dir: &Path, cases: &mut Vec<PathBuf>) -> Result<()> {
+    let entries =
+        fs::read_dir(dir).with_context(|| format!("read cases directory {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_cases(&path, cases)?;
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            cases.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Compiled `input_schema` of every operation that declares one.
+fn input_validators(manifest_value: &Value) -> Result<BTreeMap<String, Validator>> {
+    let mut validators = BTreeMap::new();
+    for operation in manifest_value
+        .get("operations")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let (Some(name), Some(schema)) = (
+            operation.get("name").and_then(Value::as_str),
+            operation.get("input_schema"),
+        ) else {
+            continue;
+        };
+        let validator = jsonschema::validator_for(schema)
+            .map_err(|err| anyhow!("input_schema of `{name}` does not compile: {err}"))?;
+        validators.insert(name.to_string(), validator);
+    }
+    Ok(validators)
+}
+
+fn emit_human(suggestion: &CapsSuggestion) -> Result<()> {
+    println!("suggested capabilities:");
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&suggestion.capabilities)?
+    );
+    if !suggestion.http_domains.is_empty() {
+        println!("http domains: {}", suggestion.http_domains.join(", "));
+    }
+    for capability in &suggestion.unused {
+        println!("unused: {capability} is declared but was never exercised");
+    }
+    for capability in &suggestion.undeclared {
+        println!("undeclared: {capability} was used but is not declared");
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use serde_json::Value;
+
+use crate::client_gen::{ClientLang, generate_client};
+use crate::config::resolve_manifest_path;
+use crate::manifest::{parse_manifest_value, resolve_schema_refs};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ClientCommand {
+    /// Generate a typed client from the manifest's operation schemas
+    Gen(ClientGenArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ClientGenArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+    /// Target language
+    #[arg(long, value_enum, default_value = "rust")]
+    pub lang: ClientLang,
+    /// Write the client to this file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    pub out: Option<PathBuf>,
+}
+
+pub fn run(command: ClientCommand) -> Result<()> {
+    match command {
+        ClientCommand::Gen(args) => generate(&args).map(|_| ()),
+    }
+}
+
+pub fn generate(args: &ClientGenArgs) -> Result<String> {
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let mut manifest_value: Value =
+        serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    let manifest_dir = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    resolve_schema_refs(&mut manifest_value, manifest_dir)
+        .context("resolve manifest schema references")?;
+    let manifest = parse_manifest_value(manifest_value).context("parse manifest")?;
+
+    let source = generate_client(&manifest, args.lang);
+    match &args.out {
+        Some(out) => {
+            if let Some(parent) = out.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("create {}", parent.display()))?;
+            }
+            fs::write(out, &source).with_context(|| format!("write {}", out.display()))?;
+            println!(
+                "wrote {} client for {} operation(s) to {}",
+                args.lang.file_extension(),
+                manifest.operations.len(),
+                out.display()
+            );
+        }
+        None => print!("{source}"),
+    }
+    Ok(source)
+}
@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow, bail};
+use clap::{Args, Parser};
+
+use super::inspect::load_describe;
+use crate::compat::{ChangeKind, CompatReport, check_compat};
+
+#[derive(Args, Debug, Clone)]
+#[command(about = "Check whether a new component build is a drop-in replacement for an old one")]
+pub struct CompatArgs {
+    /// Previous component (wasm or describe CBOR)
+    pub old: PathBuf,
+    /// Candidate component (wasm or describe CBOR)
+    pub new: PathBuf,
+    /// Emit the report as JSON
+    #[arg(long)]
+    pub json: bool,
+    /// Also fail when the version number does not reflect the changes
+    #[arg(long)]
+    pub require_version_bump: bool,
+}
+
+#[derive(Parser, Debug)]
+struct CompatCli {
+    #[command(flatten)]
+    args: CompatArgs,
+}
+
+pub fn parse_from_cli() -> CompatArgs {
+    CompatCli::parse().args
+}
+
+pub fn run(args: &CompatArgs) -> Result<CompatReport> {
+    let old = load_describe(&args.old).map_err(|err| anyhow!(err))?;
+    let new = load_describe(&args.new).map_err(|err| anyhow!(err))?;
+    let report = check_compat(&old, &new);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        emit_human(&report);
+    }
+
+    if !report.is_compatible() {
+        bail!(
+            "compat: {} breaking change(s) between {} and {}",
+            report.breaking().count(),
+            report.old_version,
+            report.new_version
+        );
+    }
+    if args.require_version_bump && !report.version_ok {
+        bail!(
+            "compat: {} -> {} needs at least a {:?} version bump",
+            report.old_version,
+            report.new_version,
+            report.required_bump
+        );
+    }
+    Ok(report)
+}
+
+fn emit_human(report: &CompatReport) {
+    println!("compat: {} -> {}", report.old_version, report.new_version);
+    for change in &report.changes {
+        let label = match change.kind {
+            ChangeKind::Breaking => "breaking",
+            ChangeKind::Compatible => "compatible",
+        };
+        println!("  {label:<10} {}: {}", change.path, change.message);
+    }
+    println!(
+        "  verdict: {} (required bump: {:?}, version ok: {})",
+        if report.is_compatible() {
+            "drop-in compatible"
+        } else {
+            "incompatible"
+        },
+        report.required_bump,
+        report.version_ok
+    );
+}
@@ -0,0 +1,91 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use clap::{Args, Subcommand};
+use greentic_types::schemas::component::v0_6_0::QaMode;
+use serde_json::Value;
+
+use super::flow::{
+    FlowSimulation, SimulationOptions, load_flow_graph, print_simulation, simulate_graph,
+};
+use crate::config::resolve_manifest_path;
+use crate::manifest::{ConfiguratorMode, Configurators};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfiguratorCommand {
+    /// Run the configurator flow declared for a lifecycle step
+    Run(ConfiguratorRunArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ConfiguratorRunArgs {
+    /// Lifecycle step: setup, upgrade or remove
+    #[arg(value_name = "MODE")]
+    pub mode: ConfiguratorMode,
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+    /// JSON object mapping question ids to answers
+    #[arg(long, value_name = "PATH")]
+    pub answers: Option<PathBuf>,
+    /// Component wasm to invoke (defaults to the manifest's wasm artifact)
+    #[arg(long, value_name = "PATH")]
+    pub wasm: Option<PathBuf>,
+    /// Stop after emitting the node; do not invoke the component
+    #[arg(long)]
+    pub no_invoke: bool,
+    /// Emit the simulation report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(command: ConfiguratorCommand) -> Result<()> {
+    match command {
+        ConfiguratorCommand::Run(args) => run_configurator(&args).map(|_| ()),
+    }
+}
+
+/// Looks up the flow `configurators.<mode>` names and simulates it the way
+/// `flow test` does.
+pub fn run_configurator(args: &ConfiguratorRunArgs) -> Result<FlowSimulation> {
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let manifest_value: Value =
+        serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    let manifest_dir = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let configurators: Configurators = match manifest_value.get("configurators") {
+        Some(value) => serde_json::from_value(value.clone()).context("configurators")?,
+        None => Configurators::default(),
+    };
+    configurators.validate()?;
+
+    let mode = args.mode;
+    let flow = configurators
+        .flow(mode)
+        .ok_or_else(|| anyhow!("manifest declares no configurators.{mode} flow"))?;
+    let graph = load_flow_graph(&manifest_value, manifest_dir, flow)
+        .with_context(|| format!("configurators.{mode}"))?;
+    let simulation = simulate_graph(
+        &manifest_path,
+        &manifest_value,
+        flow,
+        &graph,
+        &SimulationOptions {
+            answers: args.answers.clone(),
+            wasm: args.wasm.clone(),
+            no_invoke: args.no_invoke,
+            qa_mode: match mode {
+                ConfiguratorMode::Setup => QaMode::Setup,
+                ConfiguratorMode::Upgrade => QaMode::Update,
+                ConfiguratorMode::Remove => QaMode::Remove,
+            },
+        },
+    )?;
+    print_simulation(&simulation, args.json)?;
+    Ok(simulation)
+}
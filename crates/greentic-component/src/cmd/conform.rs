@@ -0,0 +1,326 @@
+//! `greentic-component conform`: checks a built component against the
+//! `component@0.6.0` ABI without its sources, so registries can gate
+//! submissions on it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use super::doctor::error_diagnostics;
+use super::test::harness_for_manifest;
+use crate::manifest::{ComponentManifest, parse_manifest_value, resolve_schema_refs};
+use crate::test_harness::{AuditLog, ComponentInvokeError};
+
+const DEFAULT_MAX_ERROR_BYTES: usize = 64 * 1024;
+
+const ERROR_DETAILS_SCHEMA: &str = include_str!("../../schemas/v1/error-details.schema.json");
+
+#[derive(Args, Debug, Clone)]
+pub struct ConformArgs {
+    /// Component wasm to check
+    pub wasm: PathBuf,
+    /// Path to component.manifest.json (defaults to the one next to the wasm)
+    #[arg(long, value_name = "PATH")]
+    pub manifest: Option<PathBuf>,
+    /// Largest serialized error envelope accepted, in bytes
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_ERROR_BYTES)]
+    pub max_error_bytes: usize,
+    /// Emit the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConformReport {
+    pub wasm: String,
+    pub checks: Vec<ConformCheck>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConformCheck {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<String>,
+}
+
+impl ConformReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    pub fn failed(&self) -> usize {
+        self.checks.iter().filter(|check| !check.passed).count()
+    }
+}
+
+impl ConformCheck {
+    fn new(id: &'static str, description: &'static str, failures: Vec<String>) -> Self {
+        Self {
+            id,
+            description,
+            passed: failures.is_empty(),
+            failures,
+        }
+    }
+}
+
+pub fn run(args: &ConformArgs) -> Result<ConformReport> {
+    if !args.wasm.is_file() {
+        bail!("wasm not found: {}", args.wasm.display());
+    }
+    let manifest_path = args.manifest.clone().unwrap_or_else(|| {
+        args.wasm
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("component.manifest.json")
+    });
+    let manifest = if manifest_path.is_file() {
+        Some(load_manifest(&manifest_path)?)
+    } else {
+        None
+    };
+
+    let diagnostics = error_diagnostics(&args.wasm, manifest.as_ref().map(|(_, parsed)| parsed))
+        .map_err(anyhow::Error::msg)?;
+    let mut exports = Vec::new();
+    let mut round_trips = Vec::new();
+    let mut run_empty_state = Vec::new();
+    for diag in diagnostics {
+        let line = format!("[{}] {}: {}", diag.code, diag.path, diag.message);
+        if diag.path == "component-runtime.run" {
+            run_empty_state.push(line);
+        } else if diag.code.starts_with("doctor.export.") || diag.code.starts_with("doctor.world.")
+        {
+            exports.push(line);
+        } else {
+            round_trips.push(line);
+        }
+    }
+    let envelopes = match &manifest {
+        Some((value, parsed)) => error_envelope_failures(args, value, parsed)?,
+        None => vec![format!(
+            "no manifest at {}; pass --manifest so operations can be invoked",
+            manifest_path.display()
+        )],
+    };
+
+    let report = ConformReport {
+        wasm: args.wasm.display().to_string(),
+        checks: vec![
+            ConformCheck::new(
+                "exports",
+                "component@0.6.0 world and required exports are present",
+                exports,
+            ),
+            ConformCheck::new(
+                "round_trips",
+                "describe, info, qa-spec, i18n-keys and apply-answers decode as canonical CBOR",
+                round_trips,
+            ),
+            ConformCheck::new(
+                "run_empty_state",
+                "run() accepts an empty input and empty state",
+                run_empty_state,
+            ),
+            ConformCheck::new(
+                "error_envelopes",
+                "malformed inputs yield spec error envelopes within the size limit, not traps",
+                envelopes,
+            ),
+        ],
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for check in &report.checks {
+            let status = if check.passed { "ok" } else { "FAIL" };
+            println!("{status:<4} {} - {}", check.id, check.description);
+            for failure in &check.failures {
+                println!("     {failure}");
+            }
+        }
+    }
+    if !report.passed() {
+        bail!(
+            "{} of {} conform check(s) failed",
+            report.failed(),
+            report.checks.len()
+        );
+    }
+    Ok(report)
+}
+
+fn load_manifest(path: &Path) -> Result<(Value, ComponentManifest)> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("read manifest {}", path.display()))?;
+    let mut value: Value = serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    let manifest_dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    resolve_schema_refs(&mut value, manifest_dir).context("resolve manifest schema references")?;
+    let manifest = parse_manifest_value(value.clone()).context("parse manifest")?;
+    Ok((value, manifest))
+}
+
+/// Invokes every operation with inputs no schema should accept. Returning
+/// output is tolerated; failing any other way than a well-formed component
+/// error is not.
+fn error_envelope_failures(
+    args: &ConformArgs,
+    manifest_value: &Value,
+    manifest: &ComponentManifest,
+) -> Result<Vec<String>> {
+    let wasm_bytes =
+        fs::read(&args.wasm).with_context(|| format!("read wasm {}", args.wasm.display()))?;
+    let (harness, _sandbox) = harness_for_manifest(
+        wasm_bytes,
+        manifest_value,
+        manifest,
+        None,
+        AuditLog::default(),
+        None,
+        None,
+    )?;
+    let probes = [
+        ("null", Value::Null),
+        ("array", json!([])),
+        ("string", json!("")),
+    ];
+    let mut failures = Vec::new();
+    for operation in &manifest.operations {
+        for (label, input) in &probes {
+            let context = format!("`{}` with {label} input", operation.name);
+            let Err(err) = harness.invoke(&operation.name, input) else {
+                continue;
+            };
+            match err
+                .chain()
+                .find_map(|source| source.downcast_ref::<ComponentInvokeError>())
+            {
+                Some(envelope) => failures.extend(
+                    envelope_issues(envelope, args.max_error_bytes)
+                        .into_iter()
+                        .map(|issue| format!("{context}: {issue}")),
+                ),
+                None => failures.push(format!("{context}: {err:#}")),
+            }
+        }
+    }
+    Ok(failures)
+}
+
+/// What is wrong with a component error envelope, if anything.
+fn envelope_issues(err: &ComponentInvokeError, max_bytes: usize) -> Vec<String> {
+    let mut issues = Vec::new();
+    if err.code.is_empty() {
+        issues.push("error code is empty".to_string());
+    } else if !err
+        .code
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '.' | '-'))
+    {
+        issues.push(format!(
+            "error code `{}` has characters outside [A-Za-z0-9_.-]",
+            err.code
+        ));
+    }
+    if err.message.trim().is_empty() {
+        issues.push(format!("error `{}` has an empty message", err.code));
+    }
+    match err.details.as_deref().map(serde_json::from_str::<Value>) {
+        Some(Err(_)) => issues.push(format!("error `{}` details are not JSON", err.code)),
+        // Objects are held to the documented shape; other JSON predates it.
+        Some(Ok(details)) if details.is_object() => {
+            let schema: Value =
+                serde_json::from_str(ERROR_DETAILS_SCHEMA).expect("error details schema is JSON");
+            let validator =
+                jsonschema::validator_for(&schema).expect("error details schema compiles");
+            issues.extend(validator.iter_errors(&details).map(|error| {
+                format!(
+                    "error `{}` details do not match the error-details schema at `{}`: {error}",
+                    err.code,
+                    error.instance_path()
+                )
+            }));
+        }
+        _ => {}
+    }
+    if err.backoff_ms.is_some() && !err.retryable {
+        issues.push(format!(
+            "error `{}` sets backoff_ms but is not retryable",
+            err.code
+        ));
+    }
+    let size = json!({
+        "code": err.code,
+        "message": err.message,
+        "retryable": err.retryable,
+        "backoff_ms": err.backoff_ms,
+        "details": err.details,
+    })
+    .to_string()
+    .len();
+    if size > max_bytes {
+        issues.push(format!(
+            "error `{}` envelope is {size} bytes, over the {max_bytes}-byte limit",
+            err.code
+        ));
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(code: &str, details: Option<&str>) -> ComponentInvokeError {
+        ComponentInvokeError {
+            code: code.to_string(),
+            message: "bad input".to_string(),
+            retryable: false,
+            backoff_ms: None,
+            details: details.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn envelope_issues_flag_spec_violations() {
+        assert!(envelope_issues(&envelope("input.invalid", Some("{}")), 1024).is_empty());
+        assert!(envelope_issues(&envelope("INVALID_INPUT", None), 1024).is_empty());
+
+        let issues = envelope_issues(&envelope("bad code", Some("not json")), 1024);
+        assert_eq!(issues.len(), 2, "{issues:?}");
+
+        let large = "x".repeat(2048);
+        let issues = envelope_issues(
+            &envelope("input.invalid", Some(&format!("\"{large}\""))),
+            1024,
+        );
+        assert!(issues[0].contains("over the 1024-byte limit"), "{issues:?}");
+
+        let documented = r#"{"fields":[{"pointer":"/email","message":"not an address"}],"upstream":{"service":"crm","status":503}}"#;
+        assert!(envelope_issues(&envelope("input.invalid", Some(documented)), 1024).is_empty());
+        let issues = envelope_issues(
+            &envelope("input.invalid", Some(r#"{"upstream":{"status":503}}"#)),
+            1024,
+        );
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        assert!(
+            issues[0].contains("error-details schema at `/upstream`"),
+            "{issues:?}"
+        );
+        assert!(
+            envelope("input.invalid", Some(documented))
+                .error_details()
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::{Result, bail};
+use clap::Args;
+
+use crate::test_harness::conformance::{
+    CommandHost, ConformanceReport, HostUnderTest, ReferenceHost, run_conformance,
+};
+
+#[derive(Args, Debug, Clone)]
+pub struct ConformanceArgs {
+    /// Host adapter to test; speaks JSON lines on stdin/stdout (defaults to
+    /// the built-in reference host)
+    #[arg(long, value_name = "CMD")]
+    pub host_cmd: Option<String>,
+    /// Argument passed to the host adapter (repeatable)
+    #[arg(long = "host-arg", value_name = "ARG", requires = "host_cmd")]
+    pub host_args: Vec<String>,
+    /// Only run the named case
+    #[arg(long, value_name = "ID")]
+    pub case: Option<String>,
+    /// Emit the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(args: &ConformanceArgs) -> Result<ConformanceReport> {
+    let mut host: Box<dyn HostUnderTest> = match &args.host_cmd {
+        Some(program) => Box::new(CommandHost::spawn(program, &args.host_args)?),
+        None => Box::new(ReferenceHost::default()),
+    };
+    let report = run_conformance(host.as_mut(), args.case.as_deref())?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for case in &report.cases {
+            let status = if case.passed { "ok" } else { "FAIL" };
+            println!("{status:<4} {} - {}", case.id, case.description);
+            for failure in &case.failures {
+                println!("     {failure}");
+            }
+        }
+    }
+    if !report.passed() {
+        bail!(
+            "{} of {} conformance case(s) failed",
+            report.failed(),
+            report.cases.len()
+        );
+    }
+    Ok(report)
+}
@@ -11,6 +11,8 @@ use wasmtime_wasi::{ResourceTable, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiVie
 
 use super::path::strip_file_scheme;
 use crate::cmd::component_world::is_fallback_world;
+use crate::manifest::{ComponentManifest, ComponentRole};
+use crate::manifest_sync::verify_schema_hashes;
 use crate::{ComponentError, abi, loader};
 
 use greentic_types::cbor::canonical;
@@ -54,10 +56,12 @@ pub fn parse_from_cli() -> DoctorArgs {
 
 pub fn run(args: DoctorArgs) -> Result<(), ComponentError> {
     let target_path = strip_file_scheme(Path::new(&args.target));
-    let wasm_path = resolve_wasm_path(&args.target, &target_path, args.manifest.as_deref())
-        .map_err(ComponentError::Doctor)?;
+    let (wasm_path, manifest) =
+        resolve_wasm_path(&args.target, &target_path, args.manifest.as_deref())
+            .map_err(ComponentError::Doctor)?;
 
-    let report = DoctorReport::from_wasm(&wasm_path).map_err(ComponentError::Doctor)?;
+    let report =
+        DoctorReport::from_wasm(&wasm_path, manifest.as_ref()).map_err(ComponentError::Doctor)?;
     match args.format {
         DoctorFormat::Human => report.emit_human(),
         DoctorFormat::Json => report.emit_json()?,
@@ -73,28 +77,28 @@ fn resolve_wasm_path(
     raw_target: &str,
     target_path: &Path,
     manifest: Option<&Path>,
-) -> Result<PathBuf, String> {
+) -> Result<(PathBuf, Option<ComponentManifest>), String> {
     if let Some(manifest_path) = manifest {
         let handle = loader::discover_with_manifest(raw_target, Some(manifest_path))
             .map_err(|err| format!("failed to load manifest: {err}"))?;
-        return Ok(handle.wasm_path);
+        return Ok((handle.wasm_path, Some(handle.manifest)));
     }
 
     if target_path.is_file() {
         if target_path.extension().and_then(|ext| ext.to_str()) == Some("wasm") {
-            return Ok(target_path.to_path_buf());
+            return Ok((target_path.to_path_buf(), None));
         }
         if target_path.extension().and_then(|ext| ext.to_str()) == Some("json") {
             let handle = loader::discover_with_manifest(raw_target, Some(target_path))
                 .map_err(|err| format!("failed to load manifest: {err}"))?;
-            return Ok(handle.wasm_path);
+            return Ok((handle.wasm_path, Some(handle.manifest)));
         }
     }
 
     if target_path.is_dir()
         && let Some(found) = find_wasm_in_dir(target_path)?
     {
-        return Ok(found);
+        return Ok((found, None));
     }
 
     Err(format!(
@@ -142,18 +146,35 @@ fn collect_wasm_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String>
     Ok(())
 }
 
+/// The error-level doctor findings for `wasm_path`, for commands that gate
+/// on the same checks.
+pub(crate) fn error_diagnostics(
+    wasm_path: &Path,
+    manifest: Option<&ComponentManifest>,
+) -> Result<Vec<DoctorDiagnostic>, String> {
+    let report = DoctorReport::from_wasm(wasm_path, manifest)?;
+    Ok(report
+        .diagnostics
+        .into_iter()
+        .filter(|diag| diag.severity == Severity::Error)
+        .collect())
+}
+
 #[derive(Default, Serialize)]
 struct DoctorReport {
     diagnostics: Vec<DoctorDiagnostic>,
 }
 
 impl DoctorReport {
-    fn from_wasm(wasm_path: &Path) -> Result<Self, String> {
+    fn from_wasm(wasm_path: &Path, manifest: Option<&ComponentManifest>) -> Result<Self, String> {
         let mut report = DoctorReport::default();
         report.validate_world(wasm_path);
 
         let mut caller = ComponentCaller::new(wasm_path)
             .map_err(|err| format!("doctor: failed to load component: {err}"))?;
+        if let Some(manifest) = manifest {
+            report.validate_env_access(&caller.imports, manifest);
+        }
 
         let info_bytes = report.require_export_bytes(
             &mut caller,
@@ -226,6 +247,10 @@ impl DoctorReport {
                 Ok(describe) => {
                     report.validate_info(&describe.info, "describe");
                     report.validate_describe(&describe, &bytes);
+                    if let Some(manifest) = manifest {
+                        report.validate_manifest_schema_hashes(&describe, manifest);
+                        report.validate_role(&describe.info, manifest);
+                    }
                     report.validate_i18n(&i18n_keys, &qa_specs);
                     report.validate_apply_answers(&mut caller, &describe, &bytes);
                 }
@@ -256,6 +281,54 @@ impl DoctorReport {
         }
     }
 
+    /// Env access is only detectable at import granularity: a component that
+    /// imports `wasi:cli/environment` without declaring `wasi.env` would see
+    /// an empty environment at runtime.
+    fn validate_env_access(&mut self, imports: &[String], manifest: &ComponentManifest) {
+        let reads_env = imports
+            .iter()
+            .any(|name| name.starts_with("wasi:cli/environment"));
+        let declared = manifest
+            .capabilities
+            .wasi
+            .env
+            .as_ref()
+            .is_some_and(|env| !env.allow.is_empty());
+        if reads_env && !declared {
+            self.warn(
+                "doctor.capabilities.env_undeclared",
+                "component imports wasi:cli/environment but declares no wasi.env.allow entries",
+                "capabilities.wasi.env",
+                Some(
+                    "list the variables the component reads in capabilities.wasi.env.allow".into(),
+                ),
+            );
+        }
+    }
+
+    /// `validate_describe` checks describe's hashes against its own SchemaIR;
+    /// this catches a manifest whose schemas no longer hash to them.
+    fn validate_manifest_schema_hashes(
+        &mut self,
+        describe: &ComponentDescribe,
+        manifest: &ComponentManifest,
+    ) {
+        let Ok(manifest) = serde_json::to_value(manifest) else {
+            return;
+        };
+        for mismatch in verify_schema_hashes(&manifest, describe) {
+            if mismatch.path.starts_with("describe.") {
+                continue;
+            }
+            self.error(
+                "doctor.manifest.schema_hash.mismatch",
+                format!("operation `{}`: {}", mismatch.operation, mismatch.message),
+                mismatch.path,
+                Some("rebuild the component or run `greentic-component sync --write`".into()),
+            );
+        }
+    }
+
     fn validate_info(&mut self, info: &ComponentInfo, source: &str) {
         if info.id.trim().is_empty() {
             self.error(
@@ -280,6 +353,32 @@ impl DoctorReport {
                 "info.role",
                 None,
             );
+        } else if info.role.parse::<ComponentRole>().is_err() {
+            self.error(
+                "doctor.describe.info.role_unknown",
+                format!("{source} info.role `{}` is not a known role", info.role),
+                "info.role",
+                Some("use `tool`, `adapter` or `process`".into()),
+            );
+        }
+    }
+
+    /// The manifest's `role` (default `tool`) must match what `describe`
+    /// reports, or flows and hosts would treat the component differently.
+    fn validate_role(&mut self, info: &ComponentInfo, manifest: &ComponentManifest) {
+        let Ok(described) = info.role.parse::<ComponentRole>() else {
+            return;
+        };
+        let declared = manifest.role.unwrap_or(ComponentRole::Tool);
+        if described != declared {
+            self.error(
+                "doctor.manifest.role.mismatch",
+                format!(
+                    "describe reports role `{described}` but the manifest declares `{declared}`"
+                ),
+                "info.role",
+                Some("set `role` in component.manifest.json to match describe".into()),
+            );
         }
     }
 
@@ -568,6 +667,22 @@ impl DoctorReport {
         });
     }
 
+    fn warn(
+        &mut self,
+        code: impl Into<String>,
+        message: impl Into<String>,
+        path: impl Into<String>,
+        hint: Option<String>,
+    ) {
+        self.diagnostics.push(DoctorDiagnostic {
+            severity: Severity::Warning,
+            code: code.into(),
+            message: message.into(),
+            path: path.into(),
+            hint,
+        });
+    }
+
     fn finalize(&mut self) {
         self.diagnostics
             .sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.code.cmp(&b.code)));
@@ -613,22 +728,25 @@ impl DoctorReport {
 #[serde(rename_all = "lowercase")]
 enum Severity {
     Error,
+    Warning,
 }
 
 impl std::fmt::Display for Severity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize)]
-struct DoctorDiagnostic {
+pub(crate) struct DoctorDiagnostic {
     severity: Severity,
-    code: String,
-    message: String,
-    path: String,
+    pub(crate) code: String,
+    pub(crate) message: String,
+    /// Export (`interface.func`) or describe path the finding is about.
+    pub(crate) path: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     hint: Option<String>,
 }
@@ -636,6 +754,8 @@ struct DoctorDiagnostic {
 struct ComponentCaller {
     store: Store<DoctorWasi>,
     instance: wasmtime::component::Instance,
+    /// Names of the interfaces the component imports.
+    imports: Vec<String>,
 }
 
 impl ComponentCaller {
@@ -645,13 +765,22 @@ impl ComponentCaller {
         let engine = Engine::new(&config)?;
 
         let component = Component::from_file(&engine, wasm_path)?;
+        let imports = component
+            .component_type()
+            .imports(&engine)
+            .map(|(name, _)| name.to_string())
+            .collect();
         let mut linker = Linker::new(&engine);
         wasmtime_wasi::p2::add_to_linker_sync(&mut linker)?;
 
         let wasi = DoctorWasi::new()?;
         let mut store = Store::new(&engine, wasi);
         let instance = linker.instantiate(&mut store, &component)?;
-        Ok(Self { store, instance })
+        Ok(Self {
+            store,
+            instance,
+            imports,
+        })
     }
 
     fn call(&mut self, interface: &str, func: &str, params: &[Val]) -> Result<Vec<Val>, String> {
@@ -668,6 +797,10 @@ impl ComponentCaller {
 
         call_component_func(&mut self.store, &func, params)
     }
+
+    fn exports(&mut self, interface: &str) -> bool {
+        resolve_interface_index(&self.instance, &mut self.store, interface).is_some()
+    }
 }
 
 fn resolve_interface_index(
@@ -691,6 +824,65 @@ fn interface_candidates(interface: &str) -> [String; 3] {
     ]
 }
 
+/// Calls `component-qa.apply-answers` on the component at `wasm_path` and
+/// decodes the config it returns.
+pub(crate) fn apply_answers(
+    wasm_path: &Path,
+    mode: QaMode,
+    current_config: &JsonValue,
+    answers: &JsonValue,
+) -> Result<JsonValue, String> {
+    let mut caller = ComponentCaller::new(wasm_path)
+        .map_err(|err| format!("instantiate {}: {err}", wasm_path.display()))?;
+    call_apply_answers(&mut caller, mode, current_config, answers)
+}
+
+/// [`apply_answers`] for components that may not export `component-qa`;
+/// `None` when they do not.
+pub(crate) fn apply_answers_if_exported(
+    wasm_path: &Path,
+    mode: QaMode,
+    current_config: &JsonValue,
+    answers: &JsonValue,
+) -> Result<Option<JsonValue>, String> {
+    let mut caller = ComponentCaller::new(wasm_path)
+        .map_err(|err| format!("instantiate {}: {err}", wasm_path.display()))?;
+    if !caller.exports("component-qa") {
+        return Ok(None);
+    }
+    call_apply_answers(&mut caller, mode, current_config, answers).map(Some)
+}
+
+fn call_apply_answers(
+    caller: &mut ComponentCaller,
+    mode: QaMode,
+    current_config: &JsonValue,
+    answers: &JsonValue,
+) -> Result<JsonValue, String> {
+    let mode_name = qa_modes()
+        .into_iter()
+        .find_map(|(candidate, name)| (candidate == mode).then_some(name))
+        .ok_or_else(|| format!("unsupported QA mode {mode:?}"))?;
+    let encode = |value: &JsonValue| {
+        canonical::to_canonical_cbor_allow_floats(value)
+            .map_err(|err| format!("CBOR encode failed: {err}"))
+    };
+    let results = caller.call(
+        "component-qa",
+        "apply-answers",
+        &[
+            Val::Enum(mode_name.to_string()),
+            Val::List(bytes_to_vals(&encode(current_config)?)),
+            Val::List(bytes_to_vals(&encode(answers)?)),
+        ],
+    )?;
+    let bytes = results
+        .first()
+        .ok_or_else(|| "component-qa.apply-answers returned no value".to_string())
+        .and_then(val_to_bytes)?;
+    decode_cbor(&bytes)
+}
+
 fn call_component_func(
     store: &mut Store<DoctorWasi>,
     func: &Func,
@@ -1509,6 +1701,46 @@ mod tests {
         assert!(has_code(&report, "doctor.i18n.key_missing"));
     }
 
+    #[test]
+    fn doctor_warns_on_undeclared_env_access() {
+        let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/manifests/valid.component.json");
+        let raw = fs::read_to_string(&manifest_path).expect("manifest");
+        let mut manifest = crate::manifest::parse_manifest(&raw).expect("manifest parse");
+        let imports = vec!["wasi:cli/environment@0.2.0".to_string()];
+
+        let mut report = DoctorReport::default();
+        report.validate_env_access(&imports, &manifest);
+        assert!(report.diagnostics.is_empty(), "RUST_LOG is declared");
+
+        manifest.capabilities.wasi.env = None;
+        report.validate_env_access(&imports, &manifest);
+        assert!(has_code(&report, "doctor.capabilities.env_undeclared"));
+        assert!(!report.has_errors(), "undeclared env access is a warning");
+    }
+
+    #[test]
+    fn doctor_checks_role_against_the_manifest() {
+        let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/manifests/valid.component.json");
+        let raw = fs::read_to_string(&manifest_path).expect("manifest");
+        let mut manifest = crate::manifest::parse_manifest(&raw).expect("manifest parse");
+        let mut info = good_describe().info;
+
+        let mut report = DoctorReport::default();
+        report.validate_role(&info, &manifest);
+        assert!(report.diagnostics.is_empty(), "tool is the default role");
+
+        manifest.role = Some(ComponentRole::Process);
+        report.validate_role(&info, &manifest);
+        assert!(has_code(&report, "doctor.manifest.role.mismatch"));
+
+        info.role = "service".to_string();
+        let mut report = DoctorReport::default();
+        report.validate_info(&info, "describe");
+        assert!(has_code(&report, "doctor.describe.info.role_unknown"));
+    }
+
     #[test]
     fn validation_issues_include_field_paths_and_hash_context() {
         let describe = good_describe();
@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Subcommand};
+use serde_json::Value;
+
+use crate::config::resolve_manifest_path;
+use crate::manifest::{DeclaredError, parse_manifest_value, resolve_schema_refs};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ErrorsCommand {
+    /// List the error codes a component declares in its manifest
+    List(ErrorsListArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ErrorsListArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+    /// Only list codes the given operation can return
+    #[arg(long, value_name = "OP")]
+    pub operation: Option<String>,
+    /// Emit the catalog as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(command: ErrorsCommand) -> Result<()> {
+    match command {
+        ErrorsCommand::List(args) => list(&args).map(|_| ()),
+    }
+}
+
+pub fn list(args: &ErrorsListArgs) -> Result<Vec<DeclaredError>> {
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let mut manifest_value: Value =
+        serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    let manifest_dir = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    resolve_schema_refs(&mut manifest_value, manifest_dir)
+        .context("resolve manifest schema references")?;
+    let manifest = parse_manifest_value(manifest_value).context("parse manifest")?;
+
+    if let Some(operation) = &args.operation
+        && !manifest.operations.iter().any(|op| op.name == *operation)
+    {
+        bail!("operation `{operation}` not declared in manifest");
+    }
+    let errors = manifest
+        .errors
+        .into_iter()
+        .filter(|error| {
+            args.operation
+                .as_deref()
+                .is_none_or(|operation| error.applies_to(operation))
+        })
+        .collect::<Vec<_>>();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&errors)?);
+    } else if errors.is_empty() {
+        println!("{} declares no error codes", manifest_path.display());
+    } else {
+        for error in &errors {
+            let operations = if error.operations.is_empty() {
+                "all operations".to_string()
+            } else {
+                error.operations.join(", ")
+            };
+            let retryable = if error.retryable { " (retryable)" } else { "" };
+            println!("{}{retryable} [{operations}]", error.code);
+            if let Some(description) = &error.description {
+                println!("    {description}");
+            }
+        }
+    }
+    Ok(errors)
+}
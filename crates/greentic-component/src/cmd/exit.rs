@@ -0,0 +1,190 @@
+//! Process exit codes shared by every subcommand, so scripts can branch on
+//! the kind of failure without parsing messages.
+//!
+//! | code | name                | meaning                                            |
+//! |------|---------------------|----------------------------------------------------|
+//! | 0    | `ok`                | success                                            |
+//! | 1    | `failure`           | anything not covered below                         |
+//! | 2    | `usage`             | bad flags or inputs (clap exits with 2 as well)    |
+//! | 3    | `manifest_invalid`  | the component manifest failed to parse or validate |
+//! | 4    | `capability_denied` | a capability the run needs is not declared         |
+//! | 5    | `component_error`   | the component returned an error or trapped         |
+//! | 6    | `timeout`           | execution exceeded its time budget                 |
+//! | 7    | `limit`             | a memory, handle, output, size or quota limit      |
+
+use std::error::Error as StdError;
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::ComponentError;
+use crate::capabilities::CapabilityError;
+use crate::limits::LimitError;
+use crate::manifest::ManifestError;
+use crate::scaffold::validate::ValidationError;
+#[cfg(feature = "store")]
+use crate::store::CompatError;
+use crate::test_harness::{ComponentInvokeError, FsQuotaExceeded, GuestTrap, HarnessError};
+
+use super::test::TestCommandError;
+use super::validate::ValidationFailed;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitCode {
+    Ok,
+    Failure,
+    Usage,
+    ManifestInvalid,
+    CapabilityDenied,
+    ComponentError,
+    Timeout,
+    Limit,
+}
+
+impl ExitCode {
+    pub fn code(self) -> i32 {
+        match self {
+            ExitCode::Ok => 0,
+            ExitCode::Failure => 1,
+            ExitCode::Usage => 2,
+            ExitCode::ManifestInvalid => 3,
+            ExitCode::CapabilityDenied => 4,
+            ExitCode::ComponentError => 5,
+            ExitCode::Timeout => 6,
+            ExitCode::Limit => 7,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExitCode::Ok => "ok",
+            ExitCode::Failure => "failure",
+            ExitCode::Usage => "usage",
+            ExitCode::ManifestInvalid => "manifest_invalid",
+            ExitCode::CapabilityDenied => "capability_denied",
+            ExitCode::ComponentError => "component_error",
+            ExitCode::Timeout => "timeout",
+            ExitCode::Limit => "limit",
+        }
+    }
+
+    /// Classifies an error by the first typed cause in its chain.
+    pub fn of(err: &anyhow::Error) -> Self {
+        // A decoded trap is attached as context, which `chain` cannot
+        // downcast to.
+        if err.downcast_ref::<GuestTrap>().is_some() {
+            return ExitCode::ComponentError;
+        }
+        err.chain().find_map(classify).unwrap_or(ExitCode::Failure)
+    }
+}
+
+fn classify(cause: &(dyn StdError + 'static)) -> Option<ExitCode> {
+    if let Some(err) = cause.downcast_ref::<TestCommandError>() {
+        return Some(err.exit_code());
+    }
+    if cause.is::<clap::Error>() || cause.is::<ValidationError>() {
+        return Some(ExitCode::Usage);
+    }
+    if cause.is::<ManifestError>()
+        || cause.is::<CapabilityError>()
+        || cause.is::<ValidationFailed>()
+    {
+        return Some(ExitCode::ManifestInvalid);
+    }
+    if cause.is::<LimitError>() || cause.is::<FsQuotaExceeded>() {
+        return Some(ExitCode::Limit);
+    }
+    if cause.is::<ComponentInvokeError>() {
+        return Some(ExitCode::ComponentError);
+    }
+    if let Some(err) = cause.downcast_ref::<HarnessError>() {
+        return Some(match err {
+            HarnessError::Timeout { .. } => ExitCode::Timeout,
+            HarnessError::MemoryLimit { .. }
+            | HarnessError::OpenHandleLimit { .. }
+            | HarnessError::OutputTooLarge { .. } => ExitCode::Limit,
+        });
+    }
+    if let Some(err) = cause.downcast_ref::<ComponentError>() {
+        return match err {
+            ComponentError::Manifest(_) | ComponentError::Capability(_) => {
+                Some(ExitCode::ManifestInvalid)
+            }
+            ComponentError::Limits(_) => Some(ExitCode::Limit),
+            _ => None,
+        };
+    }
+    #[cfg(feature = "store")]
+    if let Some(err) = cause.downcast_ref::<CompatError>() {
+        return match err {
+            CompatError::MissingCapabilities(_) => Some(ExitCode::CapabilityDenied),
+            CompatError::TooLarge { .. } => Some(ExitCode::Limit),
+            _ => None,
+        };
+    }
+    None
+}
+
+/// Whether the command line asked for machine-readable output.
+pub fn json_requested<I, S>(args: I) -> bool
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut previous_was_format = false;
+    for arg in args {
+        let arg = arg.as_ref();
+        if arg == "--json" || arg == "--format=json" || (previous_was_format && arg == "json") {
+            return true;
+        }
+        previous_was_format = arg == "--format";
+    }
+    false
+}
+
+/// The `--json` error payload for commands without a richer one of their own.
+pub fn error_json(err: &anyhow::Error) -> Value {
+    let exit = ExitCode::of(err);
+    json!({
+        "status": "error",
+        "code": exit.name(),
+        "exit_code": exit.code(),
+        "message": err.to_string(),
+        "chain": err.chain().skip(1).map(|cause| cause.to_string()).collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn classifies_typed_causes_through_context() {
+        let timeout = anyhow::Error::new(HarnessError::Timeout { timeout_ms: 10 })
+            .context("invoke component");
+        assert_eq!(ExitCode::of(&timeout), ExitCode::Timeout);
+
+        let manifest: anyhow::Result<()> =
+            Err(ManifestError::MissingOperations).context("load component.manifest.json");
+        assert_eq!(
+            ExitCode::of(&manifest.unwrap_err()),
+            ExitCode::ManifestInvalid
+        );
+
+        let plain = anyhow::anyhow!("cargo build failed");
+        assert_eq!(ExitCode::of(&plain), ExitCode::Failure);
+        let payload = error_json(&plain);
+        assert_eq!(payload["code"], "failure");
+        assert_eq!(payload["exit_code"], 1);
+    }
+
+    #[test]
+    fn detects_json_flags() {
+        assert!(json_requested(["build", "--json"]));
+        assert!(json_requested(["doctor", "x.wasm", "--format", "json"]));
+        assert!(!json_requested(["doctor", "x.wasm", "--format", "human"]));
+    }
+}
@@ -0,0 +1,493 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use clap::{Args, Subcommand};
+use jsonschema::validator_for;
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+use crate::config::resolve_manifest_path;
+use crate::manifest::resolve_schema_refs;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum FixturesCommand {
+    /// Generate example inputs for every operation from its input schema
+    Gen(FixturesGenArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct FixturesGenArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+    /// Output directory, relative to the manifest
+    #[arg(long, value_name = "DIR", default_value = "tests/cases")]
+    pub out: PathBuf,
+    /// Only generate cases for this operation (repeatable)
+    #[arg(long = "op", value_name = "NAME")]
+    pub ops: Vec<String>,
+    /// Overwrite case files that already exist
+    #[arg(long)]
+    pub force: bool,
+    /// Emit the summary as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Whether a case's input should be accepted by the operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Expectation {
+    Ok,
+    Error,
+}
+
+/// One file under `tests/cases/<op>/<name>.json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Case {
+    #[serde(skip)]
+    pub name: String,
+    pub op: String,
+    pub description: String,
+    pub input: Value,
+    pub expect: Expectation,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct FixturesReport {
+    pub written: Vec<PathBuf>,
+    /// Existing files left in place (pass `--force` to overwrite).
+    pub kept: Vec<PathBuf>,
+    /// Operations no valid example could be derived for, with the reason.
+    pub unsupported: Vec<(String, String)>,
+}
+
+pub fn run(command: FixturesCommand) -> Result<()> {
+    match command {
+        FixturesCommand::Gen(args) => {
+            let report = generate(&args)?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                return Ok(());
+            }
+            for path in &report.written {
+                println!("wrote {}", path.display());
+            }
+            if !report.kept.is_empty() {
+                println!(
+                    "kept {} existing case(s); pass --force to regenerate",
+                    report.kept.len()
+                );
+            }
+            for (op, reason) in &report.unsupported {
+                eprintln!("warning: no cases for `{op}`: {reason}");
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn generate(args: &FixturesGenArgs) -> Result<FixturesReport> {
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let root = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let mut manifest: Value = serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    resolve_schema_refs(&mut manifest, root).context("resolve manifest schema references")?;
+    let operations = manifest
+        .get("operations")
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("{} has no operations array", manifest_path.display()))?;
+
+    let out = root.join(&args.out);
+    let mut report = FixturesReport::default();
+    for operation in operations {
+        let Some(op) = operation.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        if !args.ops.is_empty() && !args.ops.iter().any(|name| name == op) {
+            continue;
+        }
+        let schema = operation.get("input_schema").cloned().unwrap_or(json!({}));
+        let cases = match cases_for(op, &schema) {
+            Ok(cases) => cases,
+            Err(reason) => {
+                report.unsupported.push((op.to_string(), reason));
+                continue;
+            }
+        };
+        let dir = out.join(file_stem(op));
+        fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+        for case in cases {
+            let path = dir.join(format!("{}.json", case.name));
+            if path.exists() && !args.force {
+                report.kept.push(path);
+                continue;
+            }
+            fs::write(&path, serde_json::to_string_pretty(&case)? + "\n")
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            report.written.push(path);
+        }
+    }
+    Ok(report)
+}
+
+/// Builds the cases for one operation. Every case is checked against the
+/// schema, so an `ok` input is accepted and an `error` input is rejected;
+/// variants that fail that check are dropped rather than written wrong.
+pub fn cases_for(op: &str, schema: &Value) -> Result<Vec<Case>, String> {
+    let validator = validator_for(schema).map_err(|err| format!("invalid input schema: {err}"))?;
+    let minimal = example(schema, false)?;
+    if !validator.is_valid(&minimal) {
+        return Err("the derived example does not satisfy the schema; add `examples`".into());
+    }
+    let case = |name: String, description: String, input: Value, expect: Expectation| Case {
+        name,
+        op: op.to_string(),
+        description,
+        input,
+        expect,
+    };
+    let mut candidates = vec![case(
+        "valid-minimal".into(),
+        "required fields only".into(),
+        minimal.clone(),
+        Expectation::Ok,
+    )];
+    if let Ok(full) = example(schema, true)
+        && full != minimal
+    {
+        candidates.push(case(
+            "valid-full".into(),
+            "every declared field".into(),
+            full,
+            Expectation::Ok,
+        ));
+    }
+
+    if let (Some(properties), Value::Object(base)) = (
+        schema.get("properties").and_then(Value::as_object),
+        &minimal,
+    ) {
+        let required = required_names(schema);
+        for (name, property) in properties {
+            let stem = file_stem(name);
+            let with = |value: Value| {
+                let mut input = base.clone();
+                input.insert(name.clone(), value);
+                Value::Object(input)
+            };
+            for (label, value, expect) in boundaries(property) {
+                let prefix = match expect {
+                    Expectation::Ok => "boundary",
+                    Expectation::Error => "invalid",
+                };
+                candidates.push(case(
+                    format!("{prefix}-{stem}-{label}"),
+                    format!("`{name}` {}", label.replace('-', " ")),
+                    with(value),
+                    expect,
+                ));
+            }
+            if let Some(value) = wrong_type(property) {
+                candidates.push(case(
+                    format!("invalid-{stem}-wrong-type"),
+                    format!("`{name}` has the wrong type"),
+                    with(value),
+                    Expectation::Error,
+                ));
+            }
+            if required.contains(&name.as_str()) {
+                let mut input = base.clone();
+                input.remove(name);
+                candidates.push(case(
+                    format!("invalid-{stem}-missing"),
+                    format!("required `{name}` is missing"),
+                    Value::Object(input),
+                    Expectation::Error,
+                ));
+            }
+        }
+        if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+            let mut input = base.clone();
+            input.insert("__unexpected".into(), json!(true));
+            candidates.push(case(
+                "invalid-unknown-field".into(),
+                "an undeclared field".into(),
+                Value::Object(input),
+                Expectation::Error,
+            ));
+        }
+    }
+
+    Ok(candidates
+        .into_iter()
+        .filter(|case| validator.is_valid(&case.input) == (case.expect == Expectation::Ok))
+        .collect())
+}
+
+/// Derives a value accepted by `schema`, preferring the schema's own
+/// `const`, `default`, `examples` and `enum` entries. With `full`, optional
+/// object properties are filled in too.
+fn example(schema: &Value, full: bool) -> Result<Value, String> {
+    let Some(object) = schema.as_object() else {
+        return Ok(Value::Null);
+    };
+    if let Some(value) = object.get("const") {
+        return Ok(value.clone());
+    }
+    if let Some(value) = object.get("default") {
+        return Ok(value.clone());
+    }
+    if let Some(value) = object
+        .get("examples")
+        .and_then(Value::as_array)
+        .and_then(|examples| examples.first())
+    {
+        return Ok(value.clone());
+    }
+    if let Some(value) = object
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|values| values.first())
+    {
+        return Ok(value.clone());
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(first) = object
+            .get(key)
+            .and_then(Value::as_array)
+            .and_then(|variants| variants.first())
+        {
+            return example(first, full);
+        }
+    }
+    match schema_type(schema) {
+        Some("object") | None if object.contains_key("properties") => {
+            let required = required_names(schema);
+            let mut value = Map::new();
+            if let Some(properties) = object.get("properties").and_then(Value::as_object) {
+                for (name, property) in properties {
+                    if full || required.contains(&name.as_str()) {
+                        value.insert(name.clone(), example(property, full)?);
+                    }
+                }
+            }
+            Ok(Value::Object(value))
+        }
+        Some("object") => Ok(json!({})),
+        Some("array") => {
+            let count = object.get("minItems").and_then(Value::as_u64).unwrap_or(0);
+            let item = example(object.get("items").unwrap_or(&json!({})), full)?;
+            Ok(Value::Array(vec![item; count as usize]))
+        }
+        Some("string") => Ok(Value::String(string_example(object))),
+        Some("integer") => {
+            let min = object.get("minimum").and_then(Value::as_i64);
+            let max = object.get("maximum").and_then(Value::as_i64);
+            Ok(json!(min.unwrap_or(0).min(max.unwrap_or(i64::MAX))))
+        }
+        Some("number") => {
+            let min = object.get("minimum").and_then(Value::as_f64);
+            let max = object.get("maximum").and_then(Value::as_f64);
+            Ok(json!(min.unwrap_or(0.0).min(max.unwrap_or(f64::MAX))))
+        }
+        Some("boolean") => Ok(Value::Bool(false)),
+        Some("null") | None => Ok(Value::Null),
+        Some(other) => Err(format!("unsupported schema type `{other}`")),
+    }
+}
+
+fn string_example(schema: &Map<String, Value>) -> String {
+    let base = match schema.get("format").and_then(Value::as_str) {
+        Some("email") => "user@example.com",
+        Some("uri") | Some("url") => "https://example.com",
+        Some("date-time") => "2024-01-01T00:00:00Z",
+        Some("date") => "2024-01-01",
+        Some("uuid") => "00000000-0000-4000-8000-000000000000",
+        _ => "example",
+    };
+    let min = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0) as usize;
+    let max = schema
+        .get("maxLength")
+        .and_then(Value::as_u64)
+        .map(|max| max as usize);
+    let mut value = base.to_string();
+    if value.len() < min {
+        value.push_str(&"x".repeat(min - value.len()));
+    }
+    if let Some(max) = max {
+        value.truncate(max);
+    }
+    value
+}
+
+/// Values at and just past a property's declared limits.
+fn boundaries(schema: &Value) -> Vec<(&'static str, Value, Expectation)> {
+    let mut out = Vec::new();
+    match schema_type(schema) {
+        Some("string") => {
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                out.push((
+                    "min-length",
+                    json!("x".repeat(min as usize)),
+                    Expectation::Ok,
+                ));
+                if min > 0 {
+                    out.push((
+                        "below-min-length",
+                        json!("x".repeat(min as usize - 1)),
+                        Expectation::Error,
+                    ));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                out.push((
+                    "max-length",
+                    json!("x".repeat(max as usize)),
+                    Expectation::Ok,
+                ));
+                out.push((
+                    "above-max-length",
+                    json!("x".repeat(max as usize + 1)),
+                    Expectation::Error,
+                ));
+            }
+        }
+        Some("integer") | Some("number") => {
+            let step = if schema_type(schema) == Some("integer") {
+                json!(1)
+            } else {
+                json!(0.5)
+            };
+            let shifted = |value: &Value, up: bool| match (value.as_i64(), step.as_i64()) {
+                (Some(value), Some(step)) if up => json!(value + step),
+                (Some(value), Some(step)) => json!(value - step),
+                _ => {
+                    let value = value.as_f64().unwrap_or_default();
+                    let step = step.as_f64().unwrap_or(1.0);
+                    json!(if up { value + step } else { value - step })
+                }
+            };
+            if let Some(min) = schema.get("minimum") {
+                out.push(("minimum", min.clone(), Expectation::Ok));
+                out.push(("below-minimum", shifted(min, false), Expectation::Error));
+            }
+            if let Some(max) = schema.get("maximum") {
+                out.push(("maximum", max.clone(), Expectation::Ok));
+                out.push(("above-maximum", shifted(max, true), Expectation::Error));
+            }
+        }
+        Some("array") => {
+            let item =
+                example(schema.get("items").unwrap_or(&json!({})), false).unwrap_or(Value::Null);
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+                && min > 0
+            {
+                out.push((
+                    "below-min-items",
+                    Value::Array(vec![item.clone(); min as usize - 1]),
+                    Expectation::Error,
+                ));
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                out.push((
+                    "above-max-items",
+                    Value::Array(vec![item; max as usize + 1]),
+                    Expectation::Error,
+                ));
+            }
+        }
+        _ => {}
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array)
+        && values.iter().all(Value::is_string)
+    {
+        out.push(("not-in-enum", json!("__not_a_member"), Expectation::Error));
+    }
+    out
+}
+
+fn wrong_type(schema: &Value) -> Option<Value> {
+    match schema_type(schema)? {
+        "string" => Some(json!(42)),
+        "integer" | "number" | "boolean" | "null" | "object" => Some(json!("not-a-match")),
+        "array" => Some(json!({})),
+        _ => None,
+    }
+}
+
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type")? {
+        Value::String(ty) => Some(ty.as_str()),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|ty| *ty != "null"),
+        _ => None,
+    }
+}
+
+fn required_names(schema: &Value) -> Vec<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cases_respect_schema_limits() {
+        let schema = json!({
+            "type": "object",
+            "required": ["message"],
+            "properties": {
+                "message": { "type": "string", "minLength": 1, "maxLength": 4 },
+                "count": { "type": "integer", "minimum": 0 }
+            },
+            "additionalProperties": false
+        });
+        let cases = cases_for("run", &schema).expect("cases");
+        let names = cases
+            .iter()
+            .map(|case| case.name.as_str())
+            .collect::<Vec<_>>();
+        for expected in [
+            "valid-minimal",
+            "valid-full",
+            "boundary-message-max-length",
+            "invalid-message-above-max-length",
+            "invalid-message-below-min-length",
+            "invalid-message-missing",
+            "invalid-count-below-minimum",
+            "invalid-unknown-field",
+        ] {
+            assert!(names.contains(&expected), "missing {expected}: {names:?}");
+        }
+        let minimal = cases
+            .iter()
+            .find(|case| case.name == "valid-minimal")
+            .unwrap();
+        assert_eq!(minimal.input, json!({ "message": "exam" }));
+    }
+}
@@ -1,27 +1,69 @@
 #![cfg(feature = "cli")]
 
 use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow, bail};
-use clap::{Args, Subcommand};
-use component_manifest::validate_config_schema;
+use clap::{Args, Subcommand, ValueEnum};
+use component_manifest::{ComponentRole, Deprecation, validate_config_schema};
+use greentic_component_runtime::{CATALOG_FILE, Catalog};
+use greentic_types::schemas::component::v0_6_0::QaMode;
+use handlebars::{Handlebars, no_escape};
+use jsonschema::{Validator, validator_for};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::Serialize;
 use serde_json::{Map as JsonMap, Value as JsonValue, json};
 
+use super::doctor::apply_answers_if_exported;
+use super::test::harness_for_manifest;
 use crate::config::{
     ConfigInferenceOptions, ConfigOutcome, load_manifest_with_schema, resolve_manifest_path,
 };
+use crate::manifest::{Configurators, parse_manifest_value, resolve_schema_refs};
+use crate::test_harness::AuditLog;
 
 const DEFAULT_MANIFEST: &str = "component.manifest.json";
 const DEFAULT_KIND: &str = "component-config";
 pub(crate) const COMPONENT_EXEC_KIND: &str = "component.exec";
+const COMPONENT_ADAPTER_KIND: &str = "component.adapter";
+const COMPONENT_PROCESS_KIND: &str = "component.process";
+/// Directory, relative to the manifest, holding `<flow>.json.hbs` overrides.
+const FLOW_TEMPLATES_DIR: &str = "flows/templates";
+/// Node flag that keeps a hand-edited node across `flow update`.
+const PRESERVE_MARKER: &str = "x_flow_preserve";
+/// Routing target the generator leaves for `flow add-step` to fill in.
+const NEXT_NODE_PLACEHOLDER: &str = "NEXT_NODE_PLACEHOLDER";
+/// Directory, relative to the manifest, holding `<flow>.ygtc` flows that
+/// configurators may reference.
+pub(crate) const FLOWS_DIR: &str = "flows";
+
+/// Structural schema for `dev_flows` graphs: the shape [`update_with_manifest`]
+/// writes, not the upstream greentic FlowIR schema, so flows it accepts may
+/// still be rejected by the flow tooling.
+pub(crate) const FLOW_IR_SCHEMA_JSON: &str = include_str!("../../schemas/v1/flow-ir.schema.json");
+
+static FLOW_IR_SCHEMA: Lazy<Validator> = Lazy::new(|| {
+    let value: JsonValue =
+        serde_json::from_str(FLOW_IR_SCHEMA_JSON).expect("flow IR schema must be valid JSON");
+    validator_for(&value).expect("flow IR schema must compile")
+});
+
+static TEMPLATE_EXPR: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{[^}]*\}\}").expect("valid template expression regex"));
 
 #[derive(Subcommand, Debug, Clone)]
 pub enum FlowCommand {
     /// Regenerate config flows and embed them into component.manifest.json
     Update(FlowUpdateArgs),
+    /// Check dev_flows against the FlowIR schema and the manifest
+    Validate(FlowValidateArgs),
+    /// Render dev_flows as a graph and list the questions they ask
+    Preview(FlowPreviewArgs),
+    /// Answer a config flow's questions and invoke the component with the result
+    Test(FlowTestArgs),
 }
 
 #[derive(Args, Debug, Clone)]
@@ -41,6 +83,85 @@ pub struct FlowUpdateArgs {
     /// Skip schema validation
     #[arg(long = "no-validate")]
     pub no_validate: bool,
+    /// Only regenerate this flow; the other is left untouched
+    #[arg(long = "only", value_enum)]
+    pub only: Option<FlowKind>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowKind {
+    Default,
+    Custom,
+}
+
+impl FlowKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FlowKind::Default => "default",
+            FlowKind::Custom => "custom",
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct FlowValidateArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long = "manifest", value_name = "PATH", default_value = DEFAULT_MANIFEST)]
+    pub manifest: PathBuf,
+    /// Emit issues as JSON
+    #[arg(long = "json")]
+    pub json: bool,
+    /// Component catalog that named dependency locators must resolve in
+    /// (defaults to components.toml next to the manifest)
+    #[arg(long = "catalog", value_name = "PATH")]
+    pub catalog: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct FlowPreviewArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long = "manifest", value_name = "PATH", default_value = DEFAULT_MANIFEST)]
+    pub manifest: PathBuf,
+    /// Only preview this flow id (default: all)
+    #[arg(long = "flow", value_name = "ID")]
+    pub flow: Option<String>,
+    /// Graph rendering
+    #[arg(long = "format", value_enum, default_value = "ascii")]
+    pub format: PreviewFormat,
+    /// Locale used to resolve question prompts from assets/i18n/<locale>.json
+    #[arg(long = "locale", value_name = "LOCALE", default_value = "en")]
+    pub locale: String,
+    /// Preview the dev_flows stored in the manifest instead of regenerating them
+    #[arg(long = "stored")]
+    pub stored: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct FlowTestArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long = "manifest", value_name = "PATH", default_value = DEFAULT_MANIFEST)]
+    pub manifest: PathBuf,
+    /// JSON object mapping question ids to answers
+    #[arg(long = "answers", value_name = "PATH")]
+    pub answers: Option<PathBuf>,
+    /// Flow to simulate
+    #[arg(long = "flow", value_name = "ID", default_value = "custom")]
+    pub flow: String,
+    /// Component wasm to invoke (defaults to the manifest's wasm artifact)
+    #[arg(long = "wasm", value_name = "PATH")]
+    pub wasm: Option<PathBuf>,
+    /// Stop after emitting the node; do not invoke the component
+    #[arg(long = "no-invoke")]
+    pub no_invoke: bool,
+    /// Emit the simulation report as JSON
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewFormat {
+    Ascii,
+    Mermaid,
 }
 
 pub fn run(command: FlowCommand) -> Result<()> {
@@ -49,6 +170,12 @@ pub fn run(command: FlowCommand) -> Result<()> {
             update(args)?;
             Ok(())
         }
+        FlowCommand::Validate(args) => validate(args),
+        FlowCommand::Preview(args) => {
+            print!("{}", preview(&args)?);
+            Ok(())
+        }
+        FlowCommand::Test(args) => simulate(&args).map(|_| ()),
     }
 }
 
@@ -76,7 +203,7 @@ pub fn update(args: FlowUpdateArgs) -> Result<FlowUpdateResult> {
     let FlowUpdateOutcome {
         mut manifest,
         result,
-    } = update_with_manifest(&config)?;
+    } = update_selected(&config, args.only)?;
 
     if !config.persist_schema {
         manifest
@@ -104,9 +231,18 @@ pub fn update(args: FlowUpdateArgs) -> Result<FlowUpdateResult> {
 }
 
 pub fn update_with_manifest(config: &ConfigOutcome) -> Result<FlowUpdateOutcome> {
+    update_selected(config, None)
+}
+
+/// Regenerates the selected flows (both when `only` is `None`). Flows that are
+/// not selected are left exactly as they are in the manifest.
+pub fn update_selected(
+    config: &ConfigOutcome,
+    only: Option<FlowKind>,
+) -> Result<FlowUpdateOutcome> {
     let component_id = manifest_component_id(&config.manifest)?;
     let component_name = manifest_component_name(&config.manifest)?;
-    let _node_kind = resolve_node_kind(&config.manifest)?;
+    let node_kind = resolve_node_kind(&config.manifest)?;
     let operation = resolve_operation(&config.manifest, component_id)?;
     let input_schema = load_operation_input_schema(&config.manifest_path, &config.manifest)?;
 
@@ -114,9 +250,19 @@ pub fn update_with_manifest(config: &ConfigOutcome) -> Result<FlowUpdateOutcome>
         .map_err(|err| anyhow!("config_schema failed validation: {err}"))?;
 
     let fields = collect_fields(&input_schema)?;
-
-    let default_flow = render_default_flow(component_id, component_name, &operation, &fields)?;
-    let custom_flow = render_custom_flow(component_id, component_name, &operation, &fields)?;
+    let templates_dir = config
+        .manifest_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(FLOW_TEMPLATES_DIR);
+    let target = FlowTarget {
+        component_id,
+        component_name,
+        node_kind,
+        operation: &operation,
+        fields: &fields,
+        templates_dir: &templates_dir,
+    };
 
     let mut manifest = config.manifest.clone();
     let manifest_obj = manifest
@@ -131,34 +277,946 @@ pub fn update_with_manifest(config: &ConfigOutcome) -> Result<FlowUpdateOutcome>
 
     let mut merged = BTreeMap::new();
     for (key, value) in dev_flows.iter() {
-        if key != "custom" && key != "default" {
-            merged.insert(key.clone(), value.clone());
+        merged.insert(key.clone(), value.clone());
+    }
+    let mut result = FlowUpdateResult::default();
+    for kind in [FlowKind::Custom, FlowKind::Default] {
+        if only.is_some_and(|selected| selected != kind) {
+            continue;
+        }
+        let mut graph = match kind {
+            FlowKind::Default => render_default_flow(&target)?,
+            FlowKind::Custom => render_custom_flow(&target)?,
+        };
+        let previous = merged
+            .get(kind.as_str())
+            .and_then(|entry| entry.get("graph"));
+        preserve_marked_nodes(previous, &mut graph);
+        merged.insert(
+            kind.as_str().to_string(),
+            json!({
+                "format": "flow-ir-json",
+                "graph": graph,
+            }),
+        );
+        match kind {
+            FlowKind::Default => result.default_updated = true,
+            FlowKind::Custom => result.custom_updated = true,
         }
     }
-    merged.insert(
-        "custom".to_string(),
-        json!({
-            "format": "flow-ir-json",
-            "graph": custom_flow,
-        }),
-    );
-    merged.insert(
-        "default".to_string(),
-        json!({
-            "format": "flow-ir-json",
-            "graph": default_flow,
-        }),
-    );
 
     *dev_flows = merged.into_iter().collect();
 
-    Ok(FlowUpdateOutcome {
-        manifest,
-        result: FlowUpdateResult {
-            default_updated: true,
-            custom_updated: true,
+    Ok(FlowUpdateOutcome { manifest, result })
+}
+
+/// Copies nodes marked `"x_flow_preserve": true` in the previous graph over
+/// the freshly generated one, so hand edits survive regeneration.
+fn preserve_marked_nodes(previous: Option<&JsonValue>, generated: &mut JsonValue) {
+    let Some(previous) = previous else {
+        return;
+    };
+    let preserved = graph_nodes(previous)
+        .into_iter()
+        .filter(|(_, node)| node.get(PRESERVE_MARKER).and_then(|flag| flag.as_bool()) == Some(true))
+        .map(|(id, node)| (id, node.clone()))
+        .collect::<Vec<_>>();
+    match generated.get_mut("nodes") {
+        Some(JsonValue::Object(nodes)) => {
+            for (id, node) in preserved {
+                nodes.insert(id, node);
+            }
+        }
+        Some(JsonValue::Array(nodes)) => {
+            for (id, node) in preserved {
+                let existing = nodes.iter_mut().find(|candidate| {
+                    candidate.get("id").and_then(|v| v.as_str()) == Some(id.as_str())
+                });
+                match existing {
+                    Some(slot) => *slot = node,
+                    None => nodes.push(node),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A problem found in one of the manifest's dev_flows.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FlowIssue {
+    pub flow: String,
+    /// Location inside the flow entry, e.g. `graph.nodes.emit_config.template`.
+    pub path: String,
+    pub message: String,
+}
+
+pub fn validate(args: FlowValidateArgs) -> Result<()> {
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let text = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+    let manifest: JsonValue = serde_json::from_str(&text)
+        .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+    let dir = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let catalog = match &args.catalog {
+        Some(path) => Some(
+            Catalog::from_path(path)
+                .with_context(|| format!("failed to load catalog {}", path.display()))?,
+        ),
+        None => Catalog::discover(dir)
+            .with_context(|| format!("failed to load {}", dir.join(CATALOG_FILE).display()))?,
+    };
+    let mut issues = validate_dev_flows(&manifest)?;
+    issues.extend(validate_configurator_flows(&manifest, dir));
+    issues.extend(validate_component_refs(&manifest, catalog.as_ref()));
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&issues)?);
+    } else {
+        for issue in &issues {
+            eprintln!("{}: {}: {}", issue.flow, issue.path, issue.message);
+        }
+    }
+    if !issues.is_empty() {
+        bail!(
+            "{} issue(s) found in dev_flows of {}",
+            issues.len(),
+            manifest_path.display()
+        );
+    }
+    if !args.json {
+        println!("dev_flows in {} are valid", manifest_path.display());
+    }
+    Ok(())
+}
+
+/// Checks every `dev_flows` entry against the FlowIR schema, then verifies
+/// routing targets, that templates render to JSON, and that the component and
+/// operations a template references are declared by the manifest.
+pub fn validate_dev_flows(manifest: &JsonValue) -> Result<Vec<FlowIssue>> {
+    let component_id = manifest_component_id(manifest)?;
+    let component_name = manifest
+        .get("name")
+        .and_then(|value| value.as_str())
+        .unwrap_or(component_id);
+    let node_kind = resolve_node_kind(manifest).ok();
+    let operations = manifest
+        .get("operations")
+        .and_then(|value| value.as_array())
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|entry| entry.get("name").and_then(|name| name.as_str()))
+                .collect::<HashSet<_>>()
+        })
+        .unwrap_or_default();
+
+    let mut issues = Vec::new();
+    let Some(dev_flows) = manifest.get("dev_flows") else {
+        return Ok(issues);
+    };
+    let dev_flows = dev_flows
+        .as_object()
+        .ok_or_else(|| anyhow!("dev_flows must be an object"))?;
+
+    for (flow_id, entry) in dev_flows {
+        let mut issue = |path: &str, message: String| {
+            issues.push(FlowIssue {
+                flow: flow_id.clone(),
+                path: path.to_string(),
+                message,
+            })
+        };
+        if let Some(format) = entry.get("format")
+            && format.as_str() != Some("flow-ir-json")
+        {
+            issue("format", format!("unsupported flow format {format}"));
+        }
+        let Some(graph) = entry.get("graph") else {
+            issue("graph", "missing FlowIR graph".into());
+            continue;
+        };
+        for graph_issue in validate_flow_graph(flow_id, graph) {
+            match graph_issue.path.as_str() {
+                "" => issue("graph", graph_issue.message),
+                path => issue(&format!("graph.{path}"), graph_issue.message),
+            }
+        }
+        if !FLOW_IR_SCHEMA.is_valid(graph) {
+            continue;
+        }
+
+        let nodes = graph_nodes(graph);
+        let node_ids = nodes
+            .iter()
+            .map(|(id, _)| id.as_str())
+            .collect::<HashSet<_>>();
+
+        for (node_id, node) in &nodes {
+            let Some(template) = node.get("template").and_then(|value| value.as_str()) else {
+                continue;
+            };
+            let path = format!("graph.nodes.{node_id}.template");
+            let rendered = TEMPLATE_EXPR.replace_all(template, "0");
+            let payload: JsonValue = match serde_json::from_str(&rendered) {
+                Ok(payload) => payload,
+                Err(err) => {
+                    issue(&path, format!("template is not valid JSON: {err}"));
+                    continue;
+                }
+            };
+            if let Some(target) = payload.get("node_id").and_then(|value| value.as_str())
+                && target != component_id
+                && target != component_name
+            {
+                issue(
+                    &path,
+                    format!("node_id `{target}` does not match component `{component_id}`"),
+                );
+            }
+            if let Some(kind) = payload.get("node_kind").and_then(|value| value.as_str())
+                && let Some(expected) = node_kind
+                && kind != expected
+            {
+                issue(
+                    &path,
+                    format!("node_kind `{kind}` does not match the component role (`{expected}`)"),
+                );
+            }
+            let Some(exec) = payload.get("node").and_then(|value| value.as_object()) else {
+                continue;
+            };
+            for (key, value) in exec {
+                if key == "routing" {
+                    for target in routing_targets(&payload["node"]) {
+                        if target != NEXT_NODE_PLACEHOLDER && !node_ids.contains(target) {
+                            issue(&path, format!("references unknown node `{target}`"));
+                        }
+                    }
+                } else if !operations.contains(key.as_str()) {
+                    issue(
+                        &path,
+                        format!("operation `{key}` is not declared by the manifest"),
+                    );
+                } else if !value.is_object() {
+                    issue(&path, format!("operation `{key}` must map to an object"));
+                }
+            }
+        }
+    }
+    Ok(issues)
+}
+
+/// Checks one FlowIR graph on its own: the FlowIR schema, then edges and
+/// routing that point at missing nodes. Paths are relative to the graph
+/// (`edges[0].to`); schema errors use the empty path. Templates are left to
+/// [`validate_dev_flows`], which knows the component's operations.
+pub fn validate_flow_graph(flow: &str, graph: &JsonValue) -> Vec<FlowIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |path: String, message: String| {
+        issues.push(FlowIssue {
+            flow: flow.to_string(),
+            path,
+            message,
+        })
+    };
+    let schema_errors = FLOW_IR_SCHEMA
+        .iter_errors(graph)
+        .map(|err| err.to_string())
+        .collect::<Vec<_>>();
+    if !schema_errors.is_empty() {
+        for message in schema_errors {
+            issue(String::new(), message);
+        }
+        return issues;
+    }
+
+    let nodes = graph_nodes(graph);
+    let node_ids = nodes
+        .iter()
+        .map(|(id, _)| id.as_str())
+        .collect::<HashSet<_>>();
+    let mut check_target = |path: String, target: &str| {
+        if target != NEXT_NODE_PLACEHOLDER && !node_ids.contains(target) {
+            issue(path, format!("references unknown node `{target}`"));
+        }
+    };
+    if let Some(edges) = graph.get("edges").and_then(|value| value.as_array()) {
+        for (idx, edge) in edges.iter().enumerate() {
+            for end in ["from", "to"] {
+                if let Some(target) = edge.get(end).and_then(|value| value.as_str()) {
+                    check_target(format!("edges[{idx}].{end}"), target);
+                }
+            }
+        }
+    }
+    for (node_id, node) in &nodes {
+        for target in routing_targets(node) {
+            check_target(format!("nodes.{node_id}.routing"), target);
+        }
+    }
+    issues
+}
+
+/// Reports manifest dependencies whose locator is a bare component name
+/// (rather than a path or URL) that the catalog does not define.
+pub fn validate_component_refs(manifest: &JsonValue, catalog: Option<&Catalog>) -> Vec<FlowIssue> {
+    let Some(dependencies) = manifest
+        .get("dependencies")
+        .and_then(|value| value.as_array())
+    else {
+        return Vec::new();
+    };
+    let mut issues = Vec::new();
+    for (idx, dependency) in dependencies.iter().enumerate() {
+        let Some(locator) = dependency.get("locator").and_then(|value| value.as_str()) else {
+            continue;
+        };
+        if !is_component_name(locator) {
+            continue;
+        }
+        let message = match catalog {
+            Some(catalog) if catalog.get(locator).is_some() => continue,
+            Some(_) => format!("component `{locator}` is not defined in the catalog"),
+            None => {
+                format!("component `{locator}` is a catalog name but no {CATALOG_FILE} was found")
+            }
+        };
+        issues.push(FlowIssue {
+            flow: "dependencies".to_string(),
+            path: format!("dependencies[{idx}].locator"),
+            message,
+        });
+    }
+    issues
+}
+
+fn is_component_name(locator: &str) -> bool {
+    !locator.is_empty()
+        && !locator.ends_with(".wasm")
+        && locator
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+}
+
+/// Renders the default/custom flows `flow update` would generate (or the
+/// stored dev_flows with `--stored`) without writing anything.
+pub fn preview(args: &FlowPreviewArgs) -> Result<String> {
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let manifest = if args.stored {
+        let text = fs::read_to_string(&manifest_path)
+            .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+        serde_json::from_str(&text)
+            .with_context(|| format!("failed to parse {}", manifest_path.display()))?
+    } else {
+        let inference_opts = ConfigInferenceOptions {
+            write_schema: false,
+            ..ConfigInferenceOptions::default()
+        };
+        let config = load_manifest_with_schema(&manifest_path, &inference_opts)?;
+        update_with_manifest(&config)?.manifest
+    };
+    let bundle = load_i18n_bundle(&manifest_path, &args.locale)?;
+
+    let dev_flows = manifest
+        .get("dev_flows")
+        .and_then(|value| value.as_object())
+        .ok_or_else(|| {
+            anyhow!("manifest has no dev_flows; run `greentic-component flow update`")
+        })?;
+    let mut out = String::new();
+    let mut rendered = 0;
+    for (flow_id, entry) in dev_flows {
+        if args.flow.as_ref().is_some_and(|wanted| wanted != flow_id) {
+            continue;
+        }
+        let graph = entry
+            .get("graph")
+            .ok_or_else(|| anyhow!("dev_flows.{flow_id} has no graph"))?;
+        if rendered > 0 {
+            out.push('\n');
+        }
+        render_flow_preview(&mut out, flow_id, graph, args.format, &bundle);
+        rendered += 1;
+    }
+    if let Some(wanted) = &args.flow
+        && rendered == 0
+    {
+        bail!("dev_flows has no flow `{wanted}`");
+    }
+    Ok(out)
+}
+
+/// Loads `assets/i18n/<locale>.json` next to the manifest, layered over the
+/// `en` bundle so missing keys fall back to English.
+fn load_i18n_bundle(manifest_path: &Path, locale: &str) -> Result<BTreeMap<String, String>> {
+    let dir = manifest_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("assets/i18n");
+    let mut bundle = BTreeMap::new();
+    let mut locales = vec!["en"];
+    if locale != "en" {
+        locales.push(locale);
+    }
+    for locale in locales {
+        let path = dir.join(format!("{locale}.json"));
+        if !path.exists() {
+            continue;
+        }
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let entries: BTreeMap<String, String> = serde_json::from_str(&text)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        bundle.extend(entries);
+    }
+    Ok(bundle)
+}
+
+fn render_flow_preview(
+    out: &mut String,
+    flow_id: &str,
+    graph: &JsonValue,
+    format: PreviewFormat,
+    bundle: &BTreeMap<String, String>,
+) {
+    let nodes = graph_nodes(graph);
+    let mut edges = Vec::new();
+    let mut labels = Vec::new();
+    for (node_id, node) in &nodes {
+        let mut label = node_id.clone();
+        let mut targets = routing_targets(node)
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if let Some(fields) = question_fields(node) {
+            let _ = write!(label, " (asks {})", fields.len());
+        }
+        if let Some(payload) = node
+            .get("template")
+            .and_then(|value| value.as_str())
+            .and_then(|template| {
+                serde_json::from_str::<JsonValue>(&TEMPLATE_EXPR.replace_all(template, "0")).ok()
+            })
+            && let Some(exec) = payload.get("node").and_then(|value| value.as_object())
+        {
+            let ops = exec
+                .keys()
+                .filter(|key| *key != "routing")
+                .cloned()
+                .collect::<Vec<_>>();
+            if !ops.is_empty() {
+                let _ = write!(label, ": {}", ops.join(", "));
+            }
+            targets.extend(routing_targets(&payload["node"]).map(str::to_string));
+        }
+        for target in targets {
+            edges.push((node_id.clone(), target));
+        }
+        labels.push((node_id.clone(), label));
+    }
+    if let Some(list) = graph.get("edges").and_then(|value| value.as_array()) {
+        for edge in list {
+            if let (Some(from), Some(to)) = (
+                edge.get("from").and_then(|value| value.as_str()),
+                edge.get("to").and_then(|value| value.as_str()),
+            ) {
+                edges.push((from.to_string(), to.to_string()));
+            }
+        }
+    }
+
+    let title = graph
+        .get("id")
+        .and_then(|value| value.as_str())
+        .unwrap_or(flow_id);
+    match format {
+        PreviewFormat::Ascii => {
+            let _ = writeln!(out, "flow {flow_id} ({title})");
+            for (node_id, label) in &labels {
+                let _ = writeln!(out, "  [{label}]");
+                for (_, target) in edges.iter().filter(|(from, _)| from == node_id) {
+                    let target = if target == NEXT_NODE_PLACEHOLDER {
+                        "<next step>"
+                    } else {
+                        target
+                    };
+                    let _ = writeln!(out, "    -> {target}");
+                }
+            }
+        }
+        PreviewFormat::Mermaid => {
+            let _ = writeln!(out, "%% flow {flow_id} ({title})");
+            let _ = writeln!(out, "flowchart TD");
+            for (node_id, label) in &labels {
+                let _ = writeln!(
+                    out,
+                    "  {}[\"{}\"]",
+                    mermaid_id(node_id),
+                    label.replace('"', "'")
+                );
+            }
+            for (from, to) in &edges {
+                let to = if to == NEXT_NODE_PLACEHOLDER {
+                    "next_step((next step))".to_string()
+                } else {
+                    mermaid_id(to)
+                };
+                let _ = writeln!(out, "  {} --> {to}", mermaid_id(from));
+            }
+        }
+    }
+
+    let questions = nodes
+        .iter()
+        .filter_map(|(_, node)| question_fields(node))
+        .flatten()
+        .collect::<Vec<_>>();
+    if questions.is_empty() {
+        return;
+    }
+    let comment = if format == PreviewFormat::Mermaid {
+        "%% "
+    } else {
+        ""
+    };
+    let _ = writeln!(out, "{comment}questions:");
+    for (idx, field) in questions.iter().enumerate() {
+        let id = field
+            .get("id")
+            .and_then(|value| value.as_str())
+            .unwrap_or("?");
+        let prompt = field
+            .get("prompt")
+            .and_then(|value| value.as_str())
+            .map(|prompt| bundle.get(prompt).map(String::as_str).unwrap_or(prompt))
+            .unwrap_or(id);
+        let kind = field
+            .get("type")
+            .and_then(|value| value.as_str())
+            .unwrap_or("string");
+        let _ = write!(out, "{comment}  {}. {prompt} [{id}: {kind}", idx + 1);
+        if let Some(options) = field.get("options").and_then(|value| value.as_array()) {
+            let options = options
+                .iter()
+                .map(|option| {
+                    option
+                        .as_str()
+                        .map(str::to_string)
+                        .unwrap_or_else(|| option.to_string())
+                })
+                .collect::<Vec<_>>();
+            let _ = write!(out, "; one of {}", options.join(", "));
+        }
+        if let Some(default) = field.get("default") {
+            let _ = write!(out, "; default {default}");
+        }
+        let _ = writeln!(out, "]");
+    }
+}
+
+fn question_fields(node: &JsonValue) -> Option<&Vec<JsonValue>> {
+    node.get("questions")?.get("fields")?.as_array()
+}
+
+fn mermaid_id(node_id: &str) -> String {
+    node_id
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+        .collect()
+}
+
+/// Outcome of `flow test`: the state gathered from the answers, the node the
+/// flow emitted and, unless `--no-invoke`, the config `apply-answers` produced
+/// and what the component returned.
+#[derive(Debug, Clone, Serialize)]
+pub struct FlowSimulation {
+    pub flow: String,
+    /// Nodes visited, in order.
+    pub path: Vec<String>,
+    pub state: BTreeMap<String, JsonValue>,
+    pub emitted: JsonValue,
+    pub operation: String,
+    pub input: JsonValue,
+    /// Config returned by `component-qa.apply-answers`; absent when the
+    /// component does not export it and runs with the emitted input instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<JsonValue>,
+}
+
+pub fn simulate(args: &FlowTestArgs) -> Result<FlowSimulation> {
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let text = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+    let manifest_value: JsonValue = serde_json::from_str(&text)
+        .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+    let graph = manifest_value
+        .get("dev_flows")
+        .and_then(|flows| flows.get(&args.flow))
+        .and_then(|entry| entry.get("graph"))
+        .ok_or_else(|| {
+            anyhow!(
+                "dev_flows has no `{}` flow; run `greentic-component flow update`",
+                args.flow
+            )
+        })?;
+    let simulation = simulate_graph(
+        &manifest_path,
+        &manifest_value,
+        &args.flow,
+        graph,
+        &SimulationOptions {
+            answers: args.answers.clone(),
+            wasm: args.wasm.clone(),
+            no_invoke: args.no_invoke,
+            qa_mode: if args.flow == "default" {
+                QaMode::Default
+            } else {
+                QaMode::Setup
+            },
         },
-    })
+    )?;
+    print_simulation(&simulation, args.json)?;
+    Ok(simulation)
+}
+
+/// How [`simulate_graph`] answers questions and whether it invokes.
+#[derive(Debug, Clone)]
+pub(crate) struct SimulationOptions {
+    pub answers: Option<PathBuf>,
+    pub wasm: Option<PathBuf>,
+    pub no_invoke: bool,
+    /// Mode passed to `component-qa.apply-answers` before invoking.
+    pub qa_mode: QaMode,
+}
+
+/// Runs `graph` as `flow test` does: answers its questions, renders the node
+/// it emits and, unless `no_invoke`, applies the answers through
+/// `component-qa.apply-answers` and invokes the component with the emitted
+/// input under the resulting config.
+pub(crate) fn simulate_graph(
+    manifest_path: &Path,
+    manifest_value: &JsonValue,
+    flow: &str,
+    graph: &JsonValue,
+    options: &SimulationOptions,
+) -> Result<FlowSimulation> {
+    let answers = match &options.answers {
+        Some(path) => {
+            let raw = fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let value: JsonValue = serde_json::from_str(&raw)
+                .with_context(|| format!("answers {} must be valid JSON", path.display()))?;
+            value
+                .as_object()
+                .cloned()
+                .ok_or_else(|| anyhow!("answers {} must be a JSON object", path.display()))?
+        }
+        None => JsonMap::new(),
+    };
+
+    let (path, state, emitted) = run_flow(graph, &answers)?;
+    let exec = emitted
+        .get("node")
+        .and_then(|node| node.as_object())
+        .ok_or_else(|| anyhow!("emitted node has no `node` object"))?;
+    let (operation, body) = exec
+        .iter()
+        .find(|(key, _)| *key != "routing")
+        .ok_or_else(|| anyhow!("emitted node does not name an operation"))?;
+    let input = body.get("input").cloned().unwrap_or(JsonValue::Null);
+    let mut simulation = FlowSimulation {
+        flow: flow.to_string(),
+        path,
+        state,
+        emitted: emitted.clone(),
+        operation: operation.clone(),
+        input,
+        config: None,
+        output: None,
+    };
+
+    if !options.no_invoke {
+        let mut resolved = manifest_value.clone();
+        let manifest_dir = manifest_path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        resolve_schema_refs(&mut resolved, manifest_dir)
+            .context("resolve manifest schema references")?;
+        let manifest = parse_manifest_value(resolved.clone()).context("parse manifest")?;
+        let wasm = options
+            .wasm
+            .clone()
+            .unwrap_or_else(|| manifest.wasm_artifact_path(manifest_dir));
+        let wasm_bytes =
+            fs::read(&wasm).with_context(|| format!("read wasm {}", wasm.display()))?;
+        // The emitted input is the node's configuration; `apply-answers`
+        // turns it and the answers into the config the component runs with.
+        let answered = JsonValue::Object(simulation.state.clone().into_iter().collect());
+        simulation.config =
+            apply_answers_if_exported(&wasm, options.qa_mode, &simulation.input, &answered)
+                .map_err(|err| anyhow!("apply-answers on {}: {err}", wasm.display()))?;
+        let config = simulation
+            .config
+            .clone()
+            .unwrap_or_else(|| simulation.input.clone());
+        let (harness, _sandbox) = harness_for_manifest(
+            wasm_bytes,
+            &resolved,
+            &manifest,
+            Some(config),
+            AuditLog::default(),
+            None,
+            None,
+        )?;
+        let outcome = harness
+            .invoke(&simulation.operation, &simulation.input)
+            .with_context(|| format!("invoke `{}`", simulation.operation))?;
+        let output = serde_json::from_str(&outcome.output_json)
+            .unwrap_or(JsonValue::String(outcome.output_json));
+        simulation.output = Some(output);
+    }
+    Ok(simulation)
+}
+
+pub(crate) fn print_simulation(simulation: &FlowSimulation, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(simulation)?);
+    } else {
+        println!("path: {}", simulation.path.join(" -> "));
+        println!(
+            "emitted node:\n{}",
+            serde_json::to_string_pretty(&simulation.emitted)?
+        );
+        if let Some(config) = &simulation.config {
+            println!("applied config:\n{}", serde_json::to_string_pretty(config)?);
+        }
+        if let Some(output) = &simulation.output {
+            println!(
+                "{} output:\n{}",
+                simulation.operation,
+                serde_json::to_string_pretty(output)?
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The graph of flow `flow`: the `dev_flows` entry when there is one,
+/// otherwise `flows/<flow>.ygtc` next to the manifest.
+pub(crate) fn load_flow_graph(manifest: &JsonValue, root: &Path, flow: &str) -> Result<JsonValue> {
+    if let Some(graph) = manifest
+        .get("dev_flows")
+        .and_then(|flows| flows.get(flow))
+        .and_then(|entry| entry.get("graph"))
+    {
+        return Ok(graph.clone());
+    }
+    let path = flow_file_path(root, flow);
+    if !path.is_file() {
+        bail!(
+            "flow `{flow}` is neither in dev_flows nor at {}",
+            path.display()
+        );
+    }
+    let raw =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_norway::from_str(&raw).with_context(|| format!("{} is not valid YAML", path.display()))
+}
+
+fn flow_file_path(root: &Path, flow: &str) -> PathBuf {
+    root.join(FLOWS_DIR).join(format!("{flow}.ygtc"))
+}
+
+/// Reports `configurators` entries whose flow is missing from both
+/// `dev_flows` and `flows/`, and checks the graphs of those found under
+/// `flows/` (dev_flows are left to [`validate_dev_flows`]).
+pub fn validate_configurator_flows(manifest: &JsonValue, root: &Path) -> Vec<FlowIssue> {
+    let Some(configurators) = manifest
+        .get("configurators")
+        .and_then(|value| serde_json::from_value::<Configurators>(value.clone()).ok())
+        .filter(|configurators| configurators.validate().is_ok())
+    else {
+        return Vec::new();
+    };
+    let mut issues = Vec::new();
+    for (field, flow) in configurators.flows() {
+        let issue = |message: String| FlowIssue {
+            flow: "configurators".to_string(),
+            path: field.to_string(),
+            message,
+        };
+        if manifest
+            .get("dev_flows")
+            .and_then(|flows| flows.get(flow))
+            .is_some()
+        {
+            continue;
+        }
+        if !flow_file_path(root, flow).is_file() {
+            issues.push(issue(format!(
+                "flow `{flow}` is not in dev_flows or {FLOWS_DIR}/{flow}.ygtc"
+            )));
+            continue;
+        }
+        match load_flow_graph(manifest, root, flow) {
+            Ok(graph) => issues.extend(validate_flow_graph(flow, &graph).into_iter().map(
+                |graph_issue| {
+                    issue(format!(
+                        "{FLOWS_DIR}/{flow}.ygtc{}: {}",
+                        match graph_issue.path.as_str() {
+                            "" => String::new(),
+                            path => format!(" {path}"),
+                        },
+                        graph_issue.message
+                    ))
+                },
+            )),
+            Err(err) => issues.push(issue(format!("{err:#}"))),
+        }
+    }
+    issues
+}
+
+/// Walks the graph from its entry node, answering questions from `answers`
+/// (falling back to field defaults) and rendering the first template reached.
+fn run_flow(
+    graph: &JsonValue,
+    answers: &JsonMap<String, JsonValue>,
+) -> Result<(Vec<String>, BTreeMap<String, JsonValue>, JsonValue)> {
+    let nodes = graph_nodes(graph);
+    let targeted = nodes
+        .iter()
+        .flat_map(|(_, node)| routing_targets(node))
+        .collect::<HashSet<_>>();
+    let mut current = nodes
+        .iter()
+        .find(|(id, _)| !targeted.contains(id.as_str()))
+        .or_else(|| nodes.first())
+        .map(|(id, _)| id.clone())
+        .ok_or_else(|| anyhow!("flow has no nodes"))?;
+
+    let mut path = Vec::new();
+    let mut state = BTreeMap::new();
+    loop {
+        if path.contains(&current) {
+            bail!("flow loops back to node `{current}`");
+        }
+        let node = nodes
+            .iter()
+            .find(|(id, _)| *id == current)
+            .map(|(_, node)| *node)
+            .ok_or_else(|| anyhow!("flow routes to unknown node `{current}`"))?;
+        path.push(current.clone());
+
+        for field in question_fields(node).into_iter().flatten() {
+            let (id, value) = answer_question(field, answers)?;
+            state.insert(id, value);
+        }
+        if let Some(template) = node.get("template").and_then(|value| value.as_str()) {
+            let emitted = render_state_template(template, &state)
+                .with_context(|| format!("node `{current}` template"))?;
+            return Ok((path, state, emitted));
+        }
+        match routing_targets(node).next() {
+            Some(next) if next != NEXT_NODE_PLACEHOLDER => current = next.to_string(),
+            _ => bail!("flow ended at `{current}` without emitting a node"),
+        }
+    }
+}
+
+fn answer_question(
+    field: &JsonValue,
+    answers: &JsonMap<String, JsonValue>,
+) -> Result<(String, JsonValue)> {
+    let id = field
+        .get("id")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| anyhow!("question is missing an `id`"))?;
+    let value = answers
+        .get(id)
+        .or_else(|| field.get("default"))
+        .cloned()
+        .ok_or_else(|| anyhow!("no answer for question `{id}` and it has no default"))?;
+    let kind = field
+        .get("type")
+        .and_then(|value| value.as_str())
+        .unwrap_or("string");
+    let type_ok = match kind {
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "string" | "enum" => value.is_string(),
+        _ => true,
+    };
+    if !type_ok {
+        bail!("answer for `{id}` must be a {kind}, got {value}");
+    }
+    if let Some(options) = field.get("options").and_then(|value| value.as_array())
+        && !options.contains(&value)
+    {
+        bail!(
+            "answer for `{id}` must be one of {}",
+            JsonValue::from(options.clone())
+        );
+    }
+    Ok((id.to_string(), value))
+}
+
+/// Substitutes `{{state.<id>}}` expressions with JSON-encoded answers and
+/// parses the result. A quoted expression is replaced together with its
+/// quotes so string answers are escaped correctly.
+fn render_state_template(template: &str, state: &BTreeMap<String, JsonValue>) -> Result<JsonValue> {
+    static STATE_EXPR: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r#""\{\{\s*state\.([A-Za-z0-9_.-]+)\s*\}\}"|\{\{\s*state\.([A-Za-z0-9_.-]+)\s*\}\}"#,
+        )
+        .expect("valid state expression regex")
+    });
+    let mut missing = None;
+    let rendered = STATE_EXPR.replace_all(template, |caps: &regex::Captures<'_>| {
+        let key = caps
+            .get(1)
+            .or_else(|| caps.get(2))
+            .map_or("", |m| m.as_str());
+        match state.get(key) {
+            Some(value) => value.to_string(),
+            None => {
+                missing.get_or_insert_with(|| key.to_string());
+                "null".to_string()
+            }
+        }
+    });
+    if let Some(key) = missing {
+        bail!("template references `state.{key}` but no question sets it");
+    }
+    serde_json::from_str(&rendered).context("rendered template is not valid JSON")
+}
+
+fn graph_nodes(graph: &JsonValue) -> Vec<(String, &JsonValue)> {
+    match graph.get("nodes") {
+        Some(JsonValue::Object(map)) => map.iter().map(|(id, node)| (id.clone(), node)).collect(),
+        Some(JsonValue::Array(list)) => list
+            .iter()
+            .filter_map(|node| {
+                let id = node.get("id")?.as_str()?;
+                Some((id.to_string(), node))
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn routing_targets(node: &JsonValue) -> impl Iterator<Item = &str> {
+    node.get("routing")
+        .and_then(|value| value.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|route| route.get("to").and_then(|value| value.as_str()))
 }
 
 fn collect_fields(config_schema: &JsonValue) -> Result<Vec<ConfigField>> {
@@ -177,8 +1235,10 @@ fn collect_fields(config_schema: &JsonValue) -> Result<Vec<ConfigField>> {
         })
         .unwrap_or_default();
 
+    // Deprecated fields stay accepted but are not offered in scaffolded flows.
     let mut fields = properties
         .iter()
+        .filter(|(_, schema)| Deprecation::from_schema(schema).is_none())
         .map(|(name, schema)| ConfigField::from_schema(name, schema, required.contains(name)))
         .collect::<Vec<_>>();
     fields.sort_by(|a, b| a.name.cmp(&b.name));
@@ -316,15 +1376,22 @@ fn humanize(raw: &str) -> String {
     result
 }
 
-fn render_default_flow(
-    component_id: &str,
-    component_name: &str,
-    operation: &str,
-    fields: &[ConfigField],
-) -> Result<JsonValue> {
-    let field_values = compute_default_fields(fields)?;
+struct FlowTarget<'a> {
+    component_id: &'a str,
+    component_name: &'a str,
+    node_kind: &'static str,
+    operation: &'a str,
+    fields: &'a [ConfigField],
+    templates_dir: &'a Path,
+}
+
+fn render_default_flow(target: &FlowTarget<'_>) -> Result<JsonValue> {
+    let field_values = compute_default_fields(target.fields)?;
+    let emit_template = render_emit_template(target, field_values);
+    if let Some(graph) = render_user_template(target, FlowKind::Default, &emit_template, &[])? {
+        return Ok(graph);
+    }
 
-    let emit_template = render_emit_template(component_name, operation, field_values);
     let mut nodes = BTreeMap::new();
     nodes.insert(
         "emit_config".to_string(),
@@ -333,6 +1400,7 @@ fn render_default_flow(
         }),
     );
 
+    let component_id = target.component_id;
     let doc = FlowDocument {
         id: format!("{component_id}.default"),
         kind: DEFAULT_KIND.to_string(),
@@ -343,13 +1411,9 @@ fn render_default_flow(
     flow_to_value(&doc)
 }
 
-fn render_custom_flow(
-    component_id: &str,
-    component_name: &str,
-    operation: &str,
-    fields: &[ConfigField],
-) -> Result<JsonValue> {
-    let visible_fields = fields
+fn render_custom_flow(target: &FlowTarget<'_>) -> Result<JsonValue> {
+    let visible_fields = target
+        .fields
         .iter()
         .filter(|field| !field.hidden)
         .collect::<Vec<_>>();
@@ -381,16 +1445,6 @@ fn render_custom_flow(
         question_fields.push(JsonValue::Object(mapping));
     }
 
-    let mut questions_inner = JsonMap::new();
-    questions_inner.insert("fields".into(), JsonValue::Array(question_fields));
-
-    let mut ask_node = JsonMap::new();
-    ask_node.insert("questions".into(), JsonValue::Object(questions_inner));
-    ask_node.insert(
-        "routing".into(),
-        JsonValue::Array(vec![json!({ "to": "emit_config" })]),
-    );
-
     let emit_field_values = visible_fields
         .iter()
         .map(|field| EmitField {
@@ -402,7 +1456,22 @@ fn render_custom_flow(
             },
         })
         .collect::<Vec<_>>();
-    let emit_template = render_emit_template(component_name, operation, emit_field_values);
+    let emit_template = render_emit_template(target, emit_field_values);
+    if let Some(graph) =
+        render_user_template(target, FlowKind::Custom, &emit_template, &question_fields)?
+    {
+        return Ok(graph);
+    }
+
+    let mut questions_inner = JsonMap::new();
+    questions_inner.insert("fields".into(), JsonValue::Array(question_fields));
+
+    let mut ask_node = JsonMap::new();
+    ask_node.insert("questions".into(), JsonValue::Object(questions_inner));
+    ask_node.insert(
+        "routing".into(),
+        JsonValue::Array(vec![json!({ "to": "emit_config" })]),
+    );
 
     let mut nodes = BTreeMap::new();
     nodes.insert("ask_config".to_string(), JsonValue::Object(ask_node));
@@ -411,6 +1480,7 @@ fn render_custom_flow(
         json!({ "template": emit_template }),
     );
 
+    let component_id = target.component_id;
     let doc = FlowDocument {
         id: format!("{component_id}.custom"),
         kind: DEFAULT_KIND.to_string(),
@@ -421,12 +1491,71 @@ fn render_custom_flow(
     flow_to_value(&doc)
 }
 
-fn render_emit_template(component_name: &str, operation: &str, fields: Vec<EmitField>) -> String {
+/// Renders `flows/templates/<kind>.json.hbs` when the component ships one.
+///
+/// Besides the component identity and `fields`, the context carries the
+/// generated pieces pre-encoded as JSON (`emit_template_json`,
+/// `questions_json`) so templates can splice them in without escaping.
+fn render_user_template(
+    target: &FlowTarget<'_>,
+    kind: FlowKind,
+    emit_template: &str,
+    questions: &[JsonValue],
+) -> Result<Option<JsonValue>> {
+    let path = target
+        .templates_dir
+        .join(format!("{}.json.hbs", kind.as_str()));
+    if !path.exists() {
+        return Ok(None);
+    }
+    let source =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let fields = target
+        .fields
+        .iter()
+        .map(|field| {
+            json!({
+                "name": field.name,
+                "prompt": field.prompt(),
+                "type": field.question_type(),
+                "required": field.required,
+                "hidden": field.hidden,
+                "options": field.enum_options,
+                "default": field.default_value,
+            })
+        })
+        .collect::<Vec<_>>();
+    let context = json!({
+        "component_id": target.component_id,
+        "component_name": target.component_name,
+        "node_kind": target.node_kind,
+        "operation": target.operation,
+        "kind": DEFAULT_KIND,
+        "flow": kind.as_str(),
+        "fields": fields,
+        "emit_template": emit_template,
+        "emit_template_json": serde_json::to_string(emit_template)?,
+        "questions_json": serde_json::to_string(questions)?,
+    });
+
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(true);
+    handlebars.register_escape_fn(no_escape);
+    let rendered = handlebars
+        .render_template(&source, &context)
+        .with_context(|| format!("failed to render {}", path.display()))?;
+    let graph = serde_json::from_str(&rendered)
+        .with_context(|| format!("{} did not render to valid JSON", path.display()))?;
+    Ok(Some(graph))
+}
+
+fn render_emit_template(target: &FlowTarget<'_>, fields: Vec<EmitField>) -> String {
     let mut lines = Vec::new();
     lines.push("{".to_string());
-    lines.push(format!("  \"node_id\": \"{component_name}\","));
+    lines.push(format!("  \"node_id\": \"{}\",", target.component_name));
+    lines.push(format!("  \"node_kind\": \"{}\",", target.node_kind));
     lines.push("  \"node\": {".to_string());
-    lines.push(format!("    \"{operation}\": {{"));
+    lines.push(format!("    \"{}\": {{", target.operation));
     lines.push("      \"input\": {".to_string());
     for (idx, field) in fields.iter().enumerate() {
         let suffix = if idx + 1 == fields.len() { "" } else { "," };
@@ -461,21 +1590,35 @@ fn manifest_component_name(manifest: &JsonValue) -> Result<&str> {
         .ok_or_else(|| anyhow!("component.manifest.json must contain a string `name` field"))
 }
 
+/// The node kind config flows emit, picked from the manifest's `role`
+/// (`tool` when absent). The legacy `mode`/`kind` fields may only restate
+/// the default.
 fn resolve_node_kind(manifest: &JsonValue) -> Result<&'static str> {
     let requested = manifest
         .get("mode")
         .or_else(|| manifest.get("kind"))
         .and_then(|value| value.as_str());
-    let resolved = requested.unwrap_or(COMPONENT_EXEC_KIND);
-    if resolved == "tool" {
-        bail!("mode/kind `tool` is no longer supported for config flows");
-    }
-    if resolved != COMPONENT_EXEC_KIND {
-        bail!(
-            "unsupported config flow node kind `{resolved}`; allowed kinds: {COMPONENT_EXEC_KIND}"
-        );
+    if let Some(requested) = requested {
+        if requested == "tool" {
+            bail!("mode/kind `tool` is no longer supported for config flows; set `role` instead");
+        }
+        if requested != COMPONENT_EXEC_KIND {
+            bail!(
+                "unsupported config flow node kind `{requested}`; allowed kinds: {COMPONENT_EXEC_KIND}"
+            );
+        }
     }
-    Ok(COMPONENT_EXEC_KIND)
+    let role = match manifest.get("role").and_then(|value| value.as_str()) {
+        Some(role) => role.parse::<ComponentRole>().map_err(|_| {
+            anyhow!("unknown component role `{role}`; use tool, adapter or process")
+        })?,
+        None => ComponentRole::Tool,
+    };
+    Ok(match role {
+        ComponentRole::Tool => COMPONENT_EXEC_KIND,
+        ComponentRole::Adapter => COMPONENT_ADAPTER_KIND,
+        ComponentRole::Process => COMPONENT_PROCESS_KIND,
+    })
 }
 
 pub(crate) fn resolve_operation(manifest: &JsonValue, component_id: &str) -> Result<String> {
@@ -0,0 +1,576 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueEnum};
+use greentic_component_runtime::{CATALOG_FILE, Catalog};
+use semver::{Version, VersionReq};
+use serde_json::Value;
+
+use crate::config::resolve_manifest_path;
+use crate::manifest::{ComponentManifest, parse_manifest_value, resolve_schema_refs};
+use crate::store::{ComponentIndex, ComponentLocator};
+
+const MANIFEST_FILE: &str = "component.manifest.json";
+
+#[derive(Args, Debug, Clone)]
+pub struct GraphArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = MANIFEST_FILE)]
+    pub manifest: PathBuf,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+    pub format: GraphFormat,
+    /// Registry index to resolve dependency ids against (repeatable); the
+    /// newest version satisfying the range is used
+    #[arg(long = "index", value_name = "PATH")]
+    pub indexes: Vec<PathBuf>,
+    /// Catalog resolving component-name locators (defaults to the one next
+    /// to the manifest)
+    #[arg(long, value_name = "PATH")]
+    pub catalog: Option<PathBuf>,
+    /// Write the graph here instead of stdout
+    #[arg(long, value_name = "PATH")]
+    pub out: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+/// A component in the graph, keyed by id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphNode {
+    pub id: String,
+    /// `None` for components whose manifest could not be read.
+    pub version: Option<Version>,
+    /// Manifest path, the locator of a component that was not opened,
+    /// `host` for a host tool or `unresolved`.
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// An entry of `dependencies`.
+    Dependency,
+    /// A name in `capabilities.tools.allow`.
+    Tool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    pub requirement: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ComponentGraph {
+    pub nodes: BTreeMap<String, GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// Unresolvable dependencies, unsatisfied ranges, incompatible
+    /// components and cycles, in the order they were found.
+    pub problems: Vec<String>,
+}
+
+pub fn run(args: GraphArgs) -> Result<()> {
+    let graph = build(&args)?;
+    let rendered = match args.format {
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Mermaid => graph.to_mermaid(),
+    };
+    match &args.out {
+        Some(path) => {
+            fs::write(path, &rendered).with_context(|| format!("write {}", path.display()))?
+        }
+        None => print!("{rendered}"),
+    }
+    if !graph.problems.is_empty() {
+        for problem in &graph.problems {
+            eprintln!("error: {problem}");
+        }
+        bail!("component graph has {} problem(s)", graph.problems.len());
+    }
+    Ok(())
+}
+
+/// Walks `dependencies` and `capabilities.tools` from the root manifest,
+/// opening each component's manifest through an index, the catalog or the
+/// dependency's own locator.
+pub fn build(args: &GraphArgs) -> Result<ComponentGraph> {
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let root_dir = parent_dir(&manifest_path);
+    let (catalog, catalog_dir) = match &args.catalog {
+        Some(path) => (
+            Some(
+                Catalog::from_path(path)
+                    .with_context(|| format!("failed to load catalog {}", path.display()))?,
+            ),
+            parent_dir(path),
+        ),
+        None => (
+            Catalog::discover(&root_dir).with_context(|| {
+                format!("failed to load {}", root_dir.join(CATALOG_FILE).display())
+            })?,
+            root_dir.clone(),
+        ),
+    };
+    let mut indexes = Vec::new();
+    for path in &args.indexes {
+        indexes.push((ComponentIndex::load(path, None)?, parent_dir(path)));
+    }
+    let sources = Sources {
+        catalog: catalog.as_ref(),
+        catalog_dir: &catalog_dir,
+        indexes: &indexes,
+    };
+
+    let mut graph = ComponentGraph::default();
+    let root = load_manifest(&manifest_path)?;
+    let mut loaded: BTreeMap<String, ComponentManifest> = BTreeMap::new();
+    let mut queue = VecDeque::new();
+    graph.add_node(&root, &manifest_path);
+    queue.push_back((root.id.as_str().to_string(), root_dir));
+    loaded.insert(root.id.as_str().to_string(), root);
+
+    while let Some((id, dir)) = queue.pop_front() {
+        let manifest = loaded[&id].clone();
+        let requests = manifest
+            .dependencies
+            .iter()
+            .map(|dependency| {
+                (
+                    EdgeKind::Dependency,
+                    dependency.id.clone(),
+                    Some(dependency.version.clone()),
+                    dependency.locator.clone(),
+                )
+            })
+            .chain(
+                manifest
+                    .tools
+                    .iter()
+                    .flat_map(|tools| &tools.allow)
+                    .map(|name| (EdgeKind::Tool, name.clone(), None, None)),
+            )
+            .collect::<Vec<_>>();
+        for (kind, target, requirement, locator) in requests {
+            graph.edges.push(GraphEdge {
+                from: id.clone(),
+                to: target.clone(),
+                kind,
+                requirement: requirement.clone(),
+            });
+            if graph.nodes.contains_key(&target) {
+                continue;
+            }
+            let req = match requirement.as_deref().map(VersionReq::parse) {
+                Some(Ok(req)) => req,
+                Some(Err(err)) => {
+                    graph.problems.push(format!(
+                        "`{id}` depends on `{target}` with an invalid range: {err}"
+                    ));
+                    VersionReq::STAR
+                }
+                None => VersionReq::STAR,
+            };
+            match sources.locate(&target, &req, locator.as_deref(), &dir) {
+                Located::Manifest(path) => {
+                    let dependency = load_manifest(&path)
+                        .with_context(|| format!("load `{target}` from {}", path.display()))?;
+                    if dependency.id.as_str() != target {
+                        graph.problems.push(format!(
+                            "`{id}` expects `{target}` at {}, which holds `{}`",
+                            path.display(),
+                            dependency.id
+                        ));
+                    }
+                    graph.nodes.insert(
+                        target.clone(),
+                        GraphNode {
+                            id: target.clone(),
+                            version: Some(dependency.version.clone()),
+                            source: path.display().to_string(),
+                        },
+                    );
+                    queue.push_back((target.clone(), parent_dir(&path)));
+                    loaded.insert(target.clone(), dependency);
+                }
+                Located::External(locator) => {
+                    graph.nodes.insert(
+                        target.clone(),
+                        GraphNode {
+                            id: target.clone(),
+                            version: None,
+                            source: locator,
+                        },
+                    );
+                }
+                // Tool names the graph cannot place are host-registered tools.
+                Located::Missing if kind == EdgeKind::Tool => {
+                    graph.nodes.insert(
+                        target.clone(),
+                        GraphNode {
+                            id: target.clone(),
+                            version: None,
+                            source: "host".into(),
+                        },
+                    );
+                }
+                Located::Missing => {
+                    graph.nodes.insert(
+                        target.clone(),
+                        GraphNode {
+                            id: target.clone(),
+                            version: None,
+                            source: "unresolved".into(),
+                        },
+                    );
+                    graph.problems.push(format!(
+                        "`{id}` depends on `{target}`, which no index, catalog entry or locator provides"
+                    ));
+                }
+            }
+        }
+    }
+
+    for edge in &graph.edges {
+        let (Some(from), Some(to)) = (loaded.get(&edge.from), loaded.get(&edge.to)) else {
+            continue;
+        };
+        let mut problems = Vec::new();
+        check_edge(edge, from, to, &mut problems);
+        graph.problems.extend(problems);
+    }
+    graph.problems.extend(graph.cycles());
+    Ok(graph)
+}
+
+struct Sources<'a> {
+    catalog: Option<&'a Catalog>,
+    catalog_dir: &'a Path,
+    indexes: &'a [(ComponentIndex, PathBuf)],
+}
+
+enum Located {
+    Manifest(PathBuf),
+    /// A locator that is not on disk (`oci://...`); its manifest is not read.
+    External(String),
+    Missing,
+}
+
+impl Sources<'_> {
+    /// Indexes win over the catalog, which wins over the declared locator,
+    /// matching how hosts prefer registered sources.
+    fn locate(&self, id: &str, req: &VersionReq, locator: Option<&str>, dir: &Path) -> Located {
+        let newest = self
+            .indexes
+            .iter()
+            .flat_map(|(index, index_dir)| index.versions(id, index_dir))
+            .filter_map(|(entry, locator)| {
+                let version = Version::parse(&entry.version).ok()?;
+                req.matches(&version).then_some((version, entry, locator))
+            })
+            .max_by(|a, b| a.0.cmp(&b.0));
+        if let Some((_, entry, locator)) = newest {
+            return match locator {
+                Ok(ComponentLocator::Fs { path, .. }) => manifest_near(&path).map_or_else(
+                    || Located::External(path.display().to_string()),
+                    Located::Manifest,
+                ),
+                Ok(other) => Located::External(other.to_string()),
+                Err(_) => Located::External(entry.locator.clone()),
+            };
+        }
+        let catalog_entry = self.catalog.and_then(|catalog| {
+            catalog
+                .lookup(locator.unwrap_or(id))
+                .or_else(|| catalog.lookup(id))
+        });
+        let (locator, base) = match (catalog_entry, locator) {
+            (Some(entry), _) => (entry.locator.as_str(), self.catalog_dir),
+            (None, Some(locator)) => (locator, dir),
+            (None, None) => return Located::Missing,
+        };
+        match ComponentLocator::parse(locator) {
+            Ok(ComponentLocator::Fs { path, .. }) => {
+                let path = base.join(path);
+                manifest_near(&path).map_or(Located::Missing, Located::Manifest)
+            }
+            Ok(other) => Located::External(other.to_string()),
+            Err(_) => Located::External(locator.to_string()),
+        }
+    }
+}
+
+/// The manifest for a locator: the file itself, the one in a directory, or
+/// the nearest one above a wasm (`bin/component.wasm` sits below it).
+fn manifest_near(path: &Path) -> Option<PathBuf> {
+    if path.is_dir() {
+        let manifest = path.join(MANIFEST_FILE);
+        return manifest.is_file().then_some(manifest);
+    }
+    if path.extension().is_some_and(|ext| ext == "json") {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    path.ancestors()
+        .skip(1)
+        .take(3)
+        .map(|dir| dir.join(MANIFEST_FILE))
+        .find(|manifest| manifest.is_file())
+}
+
+fn check_edge(
+    edge: &GraphEdge,
+    from: &ComponentManifest,
+    to: &ComponentManifest,
+    problems: &mut Vec<String>,
+) {
+    let (from_id, to_id) = (&edge.from, &edge.to);
+    if let Some(requirement) = &edge.requirement
+        && let Ok(req) = VersionReq::parse(requirement)
+        && !req.matches(&to.version)
+    {
+        problems.push(format!(
+            "`{from_id}` needs `{to_id}` {requirement}, but {} resolved",
+            to.version
+        ));
+    }
+    if edge.kind == EdgeKind::Dependency
+        && let Some(dependency) = from.dependencies.iter().find(|dep| dep.id == *to_id)
+    {
+        for export in &dependency.exports {
+            if !to
+                .operations
+                .iter()
+                .any(|operation| operation.name == *export)
+            {
+                problems.push(format!(
+                    "`{from_id}` calls `{to_id}.{export}`, which `{to_id}` does not declare"
+                ));
+            }
+        }
+    }
+    if let (Some(from_abi), Some(to_abi)) = (world_version(from), world_version(to))
+        && !abi_compatible(&from_abi, &to_abi)
+    {
+        problems.push(format!(
+            "`{from_id}` targets world {from_abi} but `{to_id}` targets {to_abi}"
+        ));
+    }
+    if !from.supports.is_empty()
+        && !to.supports.is_empty()
+        && !from.supports.iter().any(|kind| to.supports.contains(kind))
+    {
+        problems.push(format!(
+            "`{to_id}` supports none of the flow kinds `{from_id}` runs in"
+        ));
+    }
+}
+
+/// `greentic:component/component@0.6.0` -> `0.6.0`.
+fn world_version(manifest: &ComponentManifest) -> Option<Version> {
+    let (_, version) = manifest.world.as_str().rsplit_once('@')?;
+    Version::parse(version).ok()
+}
+
+/// Same major, and for `0.x` the same minor: the range a caret requirement
+/// would accept in either direction.
+fn abi_compatible(a: &Version, b: &Version) -> bool {
+    a.major == b.major && (a.major > 0 || a.minor == b.minor)
+}
+
+impl ComponentGraph {
+    fn add_node(&mut self, manifest: &ComponentManifest, path: &Path) {
+        let id = manifest.id.as_str().to_string();
+        self.nodes.insert(
+            id.clone(),
+            GraphNode {
+                id,
+                version: Some(manifest.version.clone()),
+                source: path.display().to_string(),
+            },
+        );
+    }
+
+    /// Every cycle, each reported once from its smallest id.
+    fn cycles(&self) -> Vec<String> {
+        let mut adjacency: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for edge in &self.edges {
+            adjacency
+                .entry(edge.from.as_str())
+                .or_default()
+                .insert(edge.to.as_str());
+        }
+        let mut found = BTreeSet::new();
+        for start in adjacency.keys() {
+            let mut path = vec![*start];
+            walk(&adjacency, &mut path, &mut found);
+        }
+        found
+            .into_iter()
+            .map(|cycle: Vec<&str>| format!("dependency cycle: {}", cycle.join(" -> ")))
+            .collect()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph components {\n    rankdir=LR;\n");
+        for node in self.nodes.values() {
+            let style = if node.version.is_some() {
+                ""
+            } else {
+                ", style=dashed"
+            };
+            let _ = writeln!(
+                out,
+                "    \"{}\" [label=\"{}\"{style}];",
+                node.id,
+                node.label("\\n")
+            );
+        }
+        for edge in &self.edges {
+            let mut attrs = Vec::new();
+            if let Some(requirement) = &edge.requirement {
+                attrs.push(format!("label=\"{requirement}\""));
+            }
+            if edge.kind == EdgeKind::Tool {
+                attrs.push("style=dashed".into());
+            }
+            let attrs = if attrs.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attrs.join(", "))
+            };
+            let _ = writeln!(out, "    \"{}\" -> \"{}\"{attrs};", edge.from, edge.to);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let ids: BTreeMap<&str, String> = self
+            .nodes
+            .keys()
+            .enumerate()
+            .map(|(index, id)| (id.as_str(), format!("n{index}")))
+            .collect();
+        let mut out = String::from("graph LR\n");
+        for node in self.nodes.values() {
+            let _ = writeln!(
+                out,
+                "    {}[\"{}\"]",
+                ids[node.id.as_str()],
+                node.label("<br/>")
+            );
+        }
+        for edge in &self.edges {
+            let (Some(from), Some(to)) = (ids.get(edge.from.as_str()), ids.get(edge.to.as_str()))
+            else {
+                continue;
+            };
+            let arrow = match edge.kind {
+                EdgeKind::Dependency => "-->",
+                EdgeKind::Tool => "-.->",
+            };
+            match &edge.requirement {
+                Some(requirement) => {
+                    let _ = writeln!(out, "    {from} {arrow}|\"{requirement}\"| {to}");
+                }
+                None => {
+                    let _ = writeln!(out, "    {from} {arrow} {to}");
+                }
+            }
+        }
+        out
+    }
+}
+
+impl GraphNode {
+    fn label(&self, newline: &str) -> String {
+        match &self.version {
+            Some(version) => format!("{}{newline}{version}", self.id),
+            None => format!("{}{newline}{}", self.id, self.source),
+        }
+    }
+}
+
+fn walk<'a>(
+    adjacency: &BTreeMap<&'a str, BTreeSet<&'a str>>,
+    path: &mut Vec<&'a str>,
+    found: &mut BTreeSet<Vec<&'a str>>,
+) {
+    let current = *path.last().expect("path starts with a node");
+    for &next in adjacency.get(current).into_iter().flatten() {
+        if next == path[0] {
+            let mut cycle = path.clone();
+            cycle.push(next);
+            found.insert(cycle);
+        } else if next > path[0] && !path.contains(&next) {
+            path.push(next);
+            walk(adjacency, path, found);
+            path.pop();
+        }
+    }
+}
+
+fn load_manifest(path: &Path) -> Result<ComponentManifest> {
+    let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let mut value: Value =
+        serde_json::from_str(&text).with_context(|| format!("parse {}", path.display()))?;
+    resolve_schema_refs(&mut value, &parent_dir(path))?;
+    Ok(parse_manifest_value(value)?)
+}
+
+fn parent_dir(path: &Path) -> PathBuf {
+    path.parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(from: &str, to: &str) -> GraphEdge {
+        GraphEdge {
+            from: from.into(),
+            to: to.into(),
+            kind: EdgeKind::Dependency,
+            requirement: None,
+        }
+    }
+
+    #[test]
+    fn cycles_are_reported_once_from_their_smallest_id() {
+        let graph = ComponentGraph {
+            edges: vec![
+                edge("app", "b"),
+                edge("b", "c"),
+                edge("c", "b"),
+                edge("c", "app"),
+            ],
+            ..ComponentGraph::default()
+        };
+        assert_eq!(
+            graph.cycles(),
+            [
+                "dependency cycle: app -> b -> c -> app",
+                "dependency cycle: b -> c -> b",
+            ]
+        );
+        assert!(abi_compatible(
+            &Version::new(0, 6, 0),
+            &Version::new(0, 6, 3)
+        ));
+        assert!(!abi_compatible(
+            &Version::new(0, 5, 0),
+            &Version::new(0, 6, 0)
+        ));
+    }
+}
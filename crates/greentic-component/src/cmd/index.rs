@@ -0,0 +1,273 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Subcommand};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use walkdir::{DirEntry, WalkDir};
+
+use crate::manifest::{parse_manifest_value, resolve_schema_refs};
+use crate::store::{
+    ComponentIndex, INDEX_FILE, IndexEntry, IndexKey, IndexOperation, IndexSchemas,
+};
+
+const MANIFEST_FILE: &str = "component.manifest.json";
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum IndexCommand {
+    /// Write a catalog index for every component under a directory
+    Build(IndexBuildArgs),
+    /// Check an index's signature and that its artifacts still match
+    Verify(IndexVerifyArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct IndexBuildArgs {
+    /// Directory of component packs (each a component.manifest.json next to
+    /// its built wasm)
+    #[arg(value_name = "DIR")]
+    pub dir: PathBuf,
+    /// Where to write the index (defaults to <DIR>/index.json)
+    #[arg(long, value_name = "PATH")]
+    pub out: Option<PathBuf>,
+    /// Registry key to sign the index with (HMAC-SHA256)
+    #[arg(long, value_name = "FILE")]
+    pub key_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct IndexVerifyArgs {
+    /// Index to check
+    #[arg(value_name = "INDEX", default_value = INDEX_FILE)]
+    pub index: PathBuf,
+    /// Registry key the index was signed with
+    #[arg(long, value_name = "FILE")]
+    pub key_file: Option<PathBuf>,
+}
+
+pub fn run(command: IndexCommand) -> Result<()> {
+    match command {
+        IndexCommand::Build(args) => {
+            let (path, index) = build(&args)?;
+            println!(
+                "wrote {} ({} component version(s){})",
+                path.display(),
+                index.components.len(),
+                if index.signature.is_some() {
+                    ", signed"
+                } else {
+                    ""
+                }
+            );
+            Ok(())
+        }
+        IndexCommand::Verify(args) => verify(&args),
+    }
+}
+
+/// Scans `args.dir` for component manifests and writes the index next to
+/// it, with locators relative to the index file.
+pub fn build(args: &IndexBuildArgs) -> Result<(PathBuf, ComponentIndex)> {
+    let out = args
+        .out
+        .clone()
+        .unwrap_or_else(|| args.dir.join(INDEX_FILE));
+    let out_dir = match out.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        Some(dir) => dir.to_path_buf(),
+        None => PathBuf::from("."),
+    };
+    fs::create_dir_all(&out_dir)
+        .with_context(|| format!("create index directory {}", out_dir.display()))?;
+    let out_dir = out_dir
+        .canonicalize()
+        .with_context(|| format!("resolve {}", out_dir.display()))?;
+
+    let mut entries = Vec::new();
+    let walker = WalkDir::new(&args.dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !skipped(entry));
+    for entry in walker {
+        let entry = entry.with_context(|| format!("scan {}", args.dir.display()))?;
+        if entry.file_type().is_file() && entry.file_name() == MANIFEST_FILE {
+            let indexed = index_entry(entry.path(), &out_dir)
+                .with_context(|| format!("index {}", entry.path().display()))?;
+            entries.push(indexed);
+        }
+    }
+    if entries.is_empty() {
+        bail!("no {MANIFEST_FILE} found under {}", args.dir.display());
+    }
+    let mut seen = BTreeSet::new();
+    for entry in &entries {
+        if !seen.insert((entry.id.as_str(), entry.version.as_str())) {
+            bail!(
+                "{} {} is published more than once under {}",
+                entry.id,
+                entry.version,
+                args.dir.display()
+            );
+        }
+    }
+
+    let mut index = ComponentIndex::new(entries);
+    if let Some(key_file) = &args.key_file {
+        index.sign(&IndexKey::from_file(key_file)?);
+    }
+    let json = serde_json::to_string_pretty(&index)?;
+    fs::write(&out, json + "\n").with_context(|| format!("write {}", out.display()))?;
+    Ok((out, index))
+}
+
+fn verify(args: &IndexVerifyArgs) -> Result<()> {
+    let key = args
+        .key_file
+        .as_deref()
+        .map(IndexKey::from_file)
+        .transpose()?;
+    let index = ComponentIndex::load(&args.index, key.as_ref())?;
+    let dir = args
+        .index
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let mut failures = 0;
+    for entry in &index.components {
+        if entry.locator.contains("://") {
+            println!("{} {}: skipped (remote)", entry.id, entry.version);
+            continue;
+        }
+        let path = dir.join(&entry.locator);
+        let status = match fs::read(&path) {
+            Ok(bytes) if sha256_digest(&bytes) == entry.digest => "ok".to_string(),
+            Ok(bytes) => format!("digest mismatch (found {})", sha256_digest(&bytes)),
+            Err(err) => format!("missing ({err})"),
+        };
+        if status != "ok" {
+            failures += 1;
+        }
+        println!("{} {}: {status}", entry.id, entry.version);
+    }
+    if failures > 0 {
+        bail!(
+            "{failures} artifact(s) do not match {}",
+            args.index.display()
+        );
+    }
+    Ok(())
+}
+
+fn skipped(entry: &DirEntry) -> bool {
+    let name = entry.file_name().to_string_lossy();
+    entry.file_type().is_dir() && (name.starts_with('.') || name == "target")
+}
+
+fn index_entry(manifest_path: &Path, out_dir: &Path) -> Result<IndexEntry> {
+    let root = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+    let text = fs::read_to_string(manifest_path)?;
+    let mut value: Value = serde_json::from_str(&text)?;
+    resolve_schema_refs(&mut value, root)?;
+    let manifest = parse_manifest_value(value.clone())?;
+
+    let wasm_path = manifest.wasm_artifact_path(root);
+    let bytes =
+        fs::read(&wasm_path).with_context(|| format!("read wasm {}", wasm_path.display()))?;
+    let wasm_hash = format!("blake3:{}", blake3::hash(&bytes).to_hex());
+    if wasm_hash != manifest.hashes.component_wasm.as_str() {
+        bail!(
+            "{} does not match hashes.component_wasm; rebuild or run `greentic-component hash`",
+            wasm_path.display()
+        );
+    }
+    let wasm_path = wasm_path
+        .canonicalize()
+        .with_context(|| format!("resolve {}", wasm_path.display()))?;
+    let locator = pathdiff::diff_paths(&wasm_path, out_dir)
+        .unwrap_or_else(|| wasm_path.clone())
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let strings = |pointer: &str| {
+        value
+            .pointer(pointer)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    let mut capabilities = Vec::new();
+    if let Some(declared) = value.get("capabilities") {
+        capability_paths(declared, "", &mut capabilities);
+    }
+    let secrets = manifest
+        .secret_requirements
+        .iter()
+        .map(|requirement| requirement.key.as_str().to_string())
+        .collect();
+    let operations = value
+        .get("operations")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|operation| {
+            Some(IndexOperation {
+                name: operation.get("name")?.as_str()?.to_string(),
+                input: operation.get("input_schema").cloned().unwrap_or_default(),
+                output: operation.get("output_schema").cloned().unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    Ok(IndexEntry {
+        id: manifest.id.as_str().to_string(),
+        name: manifest.name.clone(),
+        version: manifest.version.to_string(),
+        world: value
+            .get("world")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        locator,
+        digest: sha256_digest(&bytes),
+        wasm_hash,
+        size: bytes.len() as u64,
+        supports: strings("/supports"),
+        capabilities,
+        secrets,
+        schemas: IndexSchemas {
+            config: value.get("config_schema").cloned().unwrap_or_default(),
+            operations,
+        },
+    })
+}
+
+fn sha256_digest(bytes: &[u8]) -> String {
+    format!("sha256:{}", hex::encode(Sha256::digest(bytes)))
+}
+
+/// Dotted paths of what a `capabilities` block turns on: `true` flags,
+/// non-empty lists, and `path=value` for strings such as a filesystem mode.
+fn capability_paths(value: &Value, prefix: &str, out: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                capability_paths(child, &path, out);
+            }
+        }
+        Value::Bool(true) => out.push(prefix.to_string()),
+        Value::Array(items) if !items.is_empty() => out.push(prefix.to_string()),
+        Value::String(text) if text != "none" => out.push(format!("{prefix}={text}")),
+        _ => {}
+    }
+}
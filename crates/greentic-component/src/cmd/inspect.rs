@@ -9,7 +9,10 @@ use wasmtime::{Engine, Store};
 use wasmtime_wasi::{ResourceTable, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
 
 use super::path::strip_file_scheme;
+use crate::describe::DescribePayload;
+use crate::manifest::ComponentManifest;
 use crate::{ComponentError, PreparedComponent, prepare_component_with_manifest};
+use component_manifest::deprecated_properties;
 use greentic_types::cbor::canonical;
 use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
 use greentic_types::schemas::component::v0_6_0::{ComponentDescribe, schema_hash};
@@ -63,6 +66,7 @@ pub fn run(args: &InspectArgs) -> Result<InspectResult, ComponentError> {
         .ok_or_else(|| ComponentError::Doctor("inspect target is required".to_string()))?;
     let manifest_override = args.manifest.as_deref().map(strip_file_scheme);
     let prepared = prepare_component_with_manifest(target, manifest_override.as_deref())?;
+    let warnings = deprecation_warnings(&prepared);
     if args.json {
         let json = serde_json::to_string_pretty(&build_report(&prepared))
             .expect("serializing inspect report");
@@ -118,7 +122,37 @@ pub fn run(args: &InspectArgs) -> Result<InspectResult, ComponentError> {
         println!("  redaction paths: {}", prepared.redaction_paths().len());
         println!("  defaults applied: {}", prepared.defaults_applied().len());
     }
-    Ok(InspectResult::default())
+    Ok(InspectResult { warnings })
+}
+
+/// Lints deprecated operations (from the manifest) and deprecated config
+/// fields (from the describe payload).
+pub fn deprecation_warnings(prepared: &PreparedComponent) -> Vec<String> {
+    lint_deprecations(&prepared.manifest, &prepared.describe)
+}
+
+fn lint_deprecations(manifest: &ComponentManifest, describe: &DescribePayload) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (operation, deprecated) in &manifest.deprecated_operations {
+        warnings.push(format!("operation {}", deprecated.notice(operation)));
+    }
+    if let Some(default_operation) = &manifest.default_operation
+        && manifest.operation_deprecation(default_operation).is_some()
+    {
+        warnings.push(format!(
+            "default_operation `{default_operation}` points at a deprecated operation"
+        ));
+    }
+    for version in &describe.versions {
+        for (field, deprecated) in deprecated_properties(&version.schema) {
+            warnings.push(format!(
+                "config field {} (describe {})",
+                deprecated.notice(&field),
+                version.version
+            ));
+        }
+    }
+    warnings
 }
 
 pub fn emit_warnings(warnings: &[String]) {
@@ -452,6 +486,23 @@ fn collect_wasm_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String>
     Ok(())
 }
 
+/// Decodes the describe payload of a wasm component, or of a describe
+/// CBOR file produced by `build`.
+pub(crate) fn load_describe(path: &Path) -> Result<ComponentDescribe, String> {
+    let path = strip_file_scheme(path);
+    let is_wasm = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wasm"));
+    let bytes = if is_wasm {
+        call_describe(&path)?
+    } else {
+        fs::read(&path).map_err(|err| format!("failed to read {}: {err}", path.display()))?
+    };
+    canonical::from_cbor(strip_self_describe_tag(&bytes))
+        .map_err(|err| format!("describe decode failed for {}: {err}", path.display()))
+}
+
 fn call_describe(wasm_path: &Path) -> Result<Vec<u8>, String> {
     let mut config = wasmtime::Config::new();
     config.wasm_component_model(true);
@@ -562,3 +613,45 @@ impl WasiView for InspectWasi {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::describe::DescribeVersion;
+    use crate::manifest::parse_manifest_value;
+    use serde_json::json;
+
+    #[test]
+    fn deprecations_are_linted_from_manifest_and_describe() {
+        let mut value: Value = serde_json::from_str(include_str!(
+            "../../tests/fixtures/manifests/valid.component.json"
+        ))
+        .expect("fixture");
+        value["operations"][0]["deprecated"] = json!({ "since": "0.3.0" });
+        let manifest = parse_manifest_value(value).expect("manifest");
+        let describe = DescribePayload {
+            name: "demo".into(),
+            versions: vec![DescribeVersion {
+                version: semver::Version::new(1, 0, 0),
+                schema: json!({
+                    "type": "object",
+                    "properties": { "old": { "type": "string", "deprecated": true } }
+                }),
+                defaults: None,
+            }],
+            schema_id: None,
+        };
+
+        let warnings = lint_deprecations(&manifest, &describe);
+        assert_eq!(warnings.len(), 3, "{warnings:?}");
+        assert!(
+            warnings[0].starts_with("operation `handle_message`"),
+            "{warnings:?}"
+        );
+        assert!(warnings[1].contains("default_operation"), "{warnings:?}");
+        assert!(warnings[2].contains("old"), "{warnings:?}");
+
+        let serialized = serde_json::to_value(&manifest).expect("serialize");
+        assert!(serialized.get("deprecated_operations").is_none());
+    }
+}
@@ -0,0 +1,951 @@
+//! A Language Server Protocol server on stdio for `component.manifest.json`
+//! and `.ygtc` flow files. Diagnostics come from the same passes as
+//! `validate`; hover and completion read the manifest and FlowIR JSON
+//! Schemas. Documents are synced in full, so positions only need mapping
+//! between UTF-16 columns and byte offsets.
+
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, BufRead, Read, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use serde_json::{Map, Value, json};
+
+use super::flow::{FLOW_IR_SCHEMA_JSON, validate_flow_graph};
+use super::validate::{
+    Severity, ValidateDiagnostic, ValidateOptions, flow_path_pointer, validate_source,
+};
+use crate::manifest::{self, ManifestDiagnostic};
+
+const SOURCE: &str = "greentic-component";
+
+#[derive(Args, Debug, Clone)]
+pub struct LspArgs {
+    /// Serve over stdin/stdout (the only transport; accepted because most
+    /// editors pass it)
+    #[arg(long)]
+    pub stdio: bool,
+}
+
+pub fn run(_args: LspArgs) -> Result<()> {
+    serve(&mut io::stdin().lock(), &mut io::stdout().lock())
+}
+
+/// Serves one session until `exit` or end of input. A body that is not
+/// JSON gets a `-32700` reply; only I/O and framing errors end the session.
+fn serve(input: &mut impl BufRead, output: &mut impl Write) -> Result<()> {
+    let mut server = Server::new();
+    while let Some(body) = read_message(input)? {
+        let message = match serde_json::from_slice(&body) {
+            Ok(message) => message,
+            Err(err) => {
+                write_message(
+                    output,
+                    &json!({
+                        "jsonrpc": "2.0",
+                        "id": null,
+                        "error": { "code": -32700, "message": format!("parse error: {err}") },
+                    }),
+                )?;
+                continue;
+            }
+        };
+        for reply in server.handle(message) {
+            write_message(output, &reply)?;
+        }
+        if server.exited {
+            if !server.shutdown {
+                bail!("exit received before shutdown");
+            }
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DocumentKind {
+    Manifest,
+    Flow,
+}
+
+fn document_kind(uri: &str) -> Option<DocumentKind> {
+    let name = uri.rsplit('/').next()?;
+    if name.ends_with(".ygtc") {
+        Some(DocumentKind::Flow)
+    } else if name.ends_with("component.manifest.json") || name.ends_with(".component.json") {
+        Some(DocumentKind::Manifest)
+    } else {
+        None
+    }
+}
+
+/// Editor session state: the open documents and the schemas hover and
+/// completion are answered from.
+pub struct Server {
+    documents: HashMap<String, String>,
+    manifest_schema: Value,
+    flow_schema: Value,
+    shutdown: bool,
+    exited: bool,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self {
+            documents: HashMap::new(),
+            manifest_schema: serde_json::from_str(manifest::schema())
+                .expect("component manifest schema must be valid JSON"),
+            flow_schema: serde_json::from_str(FLOW_IR_SCHEMA_JSON)
+                .expect("flow IR schema must be valid JSON"),
+            shutdown: false,
+            exited: false,
+        }
+    }
+
+    /// Handles one client message and returns the messages to send back:
+    /// the response for a request, `publishDiagnostics` for document
+    /// notifications.
+    pub fn handle(&mut self, message: Value) -> Vec<Value> {
+        // Responses to server-initiated requests; this server sends none.
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return Vec::new();
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let uri = params
+            .pointer("/textDocument/uri")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let result = match method {
+            "initialize" => Some(json!({
+                "capabilities": {
+                    "textDocumentSync": { "openClose": true, "change": 1, "save": true },
+                    "hoverProvider": true,
+                    "completionProvider": { "triggerCharacters": ["\"", ":"] },
+                },
+                "serverInfo": { "name": SOURCE, "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Some(Value::Null)
+            }
+            "exit" => {
+                self.exited = true;
+                None
+            }
+            "textDocument/didOpen" => {
+                let text = params.pointer("/textDocument/text").and_then(Value::as_str);
+                return match (uri, text) {
+                    (Some(uri), Some(text)) => {
+                        self.documents.insert(uri.clone(), text.to_string());
+                        vec![self.publish(&uri)]
+                    }
+                    _ => Vec::new(),
+                };
+            }
+            "textDocument/didChange" => {
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Value::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str);
+                return match (uri, text) {
+                    (Some(uri), Some(text)) => {
+                        self.documents.insert(uri.clone(), text.to_string());
+                        vec![self.publish(&uri)]
+                    }
+                    _ => Vec::new(),
+                };
+            }
+            // The wasm hash and `$ref`ed schemas are read from disk.
+            "textDocument/didSave" => {
+                return uri.map(|uri| vec![self.publish(&uri)]).unwrap_or_default();
+            }
+            "textDocument/didClose" => {
+                return match uri {
+                    Some(uri) => {
+                        self.documents.remove(&uri);
+                        vec![publish_diagnostics(&uri, Vec::new())]
+                    }
+                    None => Vec::new(),
+                };
+            }
+            "textDocument/hover" => Some(self.hover(&params).unwrap_or(Value::Null)),
+            "textDocument/completion" => Some(Value::Array(self.completion(&params))),
+            _ => None,
+        };
+        let Some(id) = message.get("id").cloned() else {
+            return Vec::new();
+        };
+        let reply = match result {
+            Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            None => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": format!("method not found: {method}") },
+            }),
+        };
+        vec![reply]
+    }
+
+    fn publish(&self, uri: &str) -> Value {
+        let text = self.documents.get(uri).map(String::as_str).unwrap_or("");
+        let diagnostics = match document_kind(uri) {
+            Some(DocumentKind::Manifest) => manifest_diagnostics(uri, text),
+            Some(DocumentKind::Flow) => flow_diagnostics(text),
+            None => Vec::new(),
+        };
+        let diagnostics = diagnostics
+            .iter()
+            .map(|diagnostic| lsp_diagnostic(text, diagnostic))
+            .collect();
+        publish_diagnostics(uri, diagnostics)
+    }
+
+    /// The open document at `params.textDocument`, with the byte offset of
+    /// `params.position` and the schema that describes it.
+    fn cursor(&self, params: &Value) -> Option<(&Value, Cursor)> {
+        let uri = params.pointer("/textDocument/uri")?.as_str()?;
+        let text = self.documents.get(uri)?;
+        let offset = offset_at(text, params.get("position")?)?;
+        Some(match document_kind(uri)? {
+            DocumentKind::Manifest => (&self.manifest_schema, json_cursor(text, offset)),
+            DocumentKind::Flow => {
+                let mut cursor = yaml_cursor(text, offset);
+                // A flow file may wrap its graph the way `dev_flows` entries do.
+                if cursor.container.first().map(String::as_str) == Some("graph") {
+                    cursor.container.remove(0);
+                }
+                (&self.flow_schema, cursor)
+            }
+        })
+    }
+
+    fn hover(&self, params: &Value) -> Option<Value> {
+        let (schema, cursor) = self.cursor(params)?;
+        let pointer = cursor.pointer();
+        let name = pointer.last()?;
+        let docs = describe(&schema_at(schema, &pointer))?;
+        Some(json!({
+            "contents": { "kind": "markdown", "value": format!("**`{name}`**\n\n{docs}") },
+        }))
+    }
+
+    fn completion(&self, params: &Value) -> Vec<Value> {
+        let Some((schema, cursor)) = self.cursor(params) else {
+            return Vec::new();
+        };
+        if cursor.in_key {
+            property_items(schema, &schema_at(schema, &cursor.container))
+        } else {
+            value_items(&schema_at(schema, &cursor.pointer()))
+        }
+    }
+}
+
+/// Reads the body of one `Content-Length` framed message; `None` at end of
+/// input.
+fn read_message(input: &mut impl BufRead) -> Result<Option<Vec<u8>>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = Some(
+                value
+                    .trim()
+                    .parse::<usize>()
+                    .context("invalid Content-Length")?,
+            );
+        }
+    }
+    let length = length.context("message without Content-Length header")?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> Result<()> {
+    let body = serde_json::to_string(message)?;
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    output.flush()?;
+    Ok(())
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+fn manifest_diagnostics(uri: &str, text: &str) -> Vec<ValidateDiagnostic> {
+    match validate_source(uri_to_path(uri), text, &ValidateOptions::default()) {
+        Ok((report, _)) => report.diagnostics,
+        // An unreadable catalog or wasm; report it on the first line.
+        Err(err) => vec![ValidateDiagnostic {
+            severity: Severity::Error,
+            diagnostic: ManifestDiagnostic::new("manifest.io", "", format!("{err:#}")),
+        }],
+    }
+}
+
+/// Checks a flow file as a FlowIR graph, either bare or under `graph`.
+fn flow_diagnostics(text: &str) -> Vec<ValidateDiagnostic> {
+    let document: Value = match serde_norway::from_str(text) {
+        Ok(document) => document,
+        Err(err) => {
+            let mut diagnostic = ManifestDiagnostic::new("flow.yaml", "", err.to_string());
+            if let Some(location) = err.location() {
+                diagnostic.line = Some(location.line());
+                diagnostic.column = Some(location.column());
+            }
+            return vec![ValidateDiagnostic {
+                severity: Severity::Error,
+                diagnostic,
+            }];
+        }
+    };
+    let (prefix, graph) = match document.get("graph") {
+        Some(graph) => ("/graph", graph),
+        None => ("", &document),
+    };
+    validate_flow_graph("flow", graph)
+        .into_iter()
+        .map(|issue| {
+            let pointer = format!("{prefix}{}", flow_path_pointer(&issue.path));
+            let mut diagnostic =
+                ManifestDiagnostic::new("flow.graph.invalid", pointer, issue.message);
+            if let Some((line, column)) = yaml_locate(text, &diagnostic.pointer) {
+                diagnostic.line = Some(line);
+                diagnostic.column = Some(column);
+            }
+            ValidateDiagnostic {
+                severity: Severity::Error,
+                diagnostic,
+            }
+        })
+        .collect()
+}
+
+/// Spans from the diagnostic's position to the end of its line.
+fn lsp_diagnostic(text: &str, entry: &ValidateDiagnostic) -> Value {
+    let diagnostic = &entry.diagnostic;
+    let line = diagnostic.line.unwrap_or(1).saturating_sub(1);
+    let column = diagnostic.column.unwrap_or(1).saturating_sub(1);
+    let source_line = text.lines().nth(line).unwrap_or("");
+    let start = utf16_len(source_line.chars().take(column));
+    let end = utf16_len(source_line.trim_end().trim_end_matches(',').chars()).max(start);
+    let mut message = diagnostic.message.clone();
+    if let Some(suggestion) = &diagnostic.suggestion {
+        message.push_str(&format!("\nhelp: {suggestion}"));
+    }
+    json!({
+        "range": {
+            "start": { "line": line, "character": start },
+            "end": { "line": line, "character": end },
+        },
+        "severity": match entry.severity {
+            Severity::Error => 1,
+            Severity::Warning => 2,
+        },
+        "code": diagnostic.code,
+        "source": SOURCE,
+        "message": message,
+    })
+}
+
+fn utf16_len(chars: impl Iterator<Item = char>) -> usize {
+    chars.map(char::len_utf16).sum()
+}
+
+/// Byte offset of an LSP position (UTF-16 `character`), clamped to the end
+/// of its line.
+fn offset_at(text: &str, position: &Value) -> Option<usize> {
+    let line = position.get("line")?.as_u64()? as usize;
+    let character = position.get("character")?.as_u64()? as usize;
+    let mut start = 0;
+    for _ in 0..line {
+        start += text[start..].find('\n')? + 1;
+    }
+    let rest = &text[start..];
+    let rest = &rest[..rest.find('\n').unwrap_or(rest.len())];
+    let mut units = 0;
+    for (index, ch) in rest.char_indices() {
+        if units >= character {
+            return Some(start + index);
+        }
+        units += ch.len_utf16();
+    }
+    Some(start + rest.len())
+}
+
+fn uri_to_path(uri: &str) -> PathBuf {
+    let Some(path) = uri.strip_prefix("file://") else {
+        return PathBuf::from("component.manifest.json");
+    };
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%'
+            && let Some(byte) = path
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    PathBuf::from(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+/// Where the cursor sits: pointer tokens of the innermost object or array,
+/// the member (or element index) it is on, and whether it is in key
+/// position.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Cursor {
+    container: Vec<String>,
+    member: Option<String>,
+    in_key: bool,
+}
+
+impl Cursor {
+    fn pointer(&self) -> Vec<String> {
+        let mut pointer = self.container.clone();
+        pointer.extend(self.member.clone());
+        pointer
+    }
+}
+
+enum Frame {
+    Object {
+        member: Option<String>,
+        expecting_key: bool,
+    },
+    Array {
+        index: usize,
+    },
+}
+
+/// Scans JSON up to `offset` without requiring the rest of the document to
+/// parse, since the buffer is usually mid-edit.
+fn json_cursor(text: &str, offset: usize) -> Cursor {
+    let bytes = text.as_bytes();
+    let offset = offset.min(bytes.len());
+    let mut stack = Vec::new();
+    let mut pos = 0;
+    while pos < offset {
+        match bytes[pos] {
+            b'"' => {
+                let (end, terminated) = string_end(bytes, pos);
+                // The cursor may sit just past an unterminated string being typed.
+                let inside = offset < end || (offset == end && !terminated);
+                if let Some(Frame::Object {
+                    member,
+                    expecting_key,
+                }) = stack.last_mut()
+                    && *expecting_key
+                {
+                    let key = &text[pos + 1..end];
+                    *member = Some(key.strip_suffix('"').unwrap_or(key).to_string());
+                    *expecting_key = false;
+                    if inside {
+                        return cursor(&stack, true);
+                    }
+                } else if inside {
+                    break;
+                }
+                pos = end;
+                continue;
+            }
+            b'{' => stack.push(Frame::Object {
+                member: None,
+                expecting_key: true,
+            }),
+            b'[' => stack.push(Frame::Array { index: 0 }),
+            b'}' | b']' => {
+                stack.pop();
+            }
+            b',' => match stack.last_mut() {
+                Some(Frame::Object {
+                    member,
+                    expecting_key,
+                }) => {
+                    *member = None;
+                    *expecting_key = true;
+                }
+                Some(Frame::Array { index }) => *index += 1,
+                None => {}
+            },
+            _ => {}
+        }
+        pos += 1;
+    }
+    let in_key = matches!(
+        stack.last(),
+        Some(Frame::Object {
+            expecting_key: true,
+            ..
+        })
+    );
+    cursor(&stack, in_key)
+}
+
+fn cursor(stack: &[Frame], in_key: bool) -> Cursor {
+    let token = |frame: &Frame| match frame {
+        Frame::Object { member, .. } => member.clone(),
+        Frame::Array { index } => Some(index.to_string()),
+    };
+    let Some((last, parents)) = stack.split_last() else {
+        return Cursor::default();
+    };
+    Cursor {
+        container: parents.iter().filter_map(token).collect(),
+        member: token(last),
+        in_key,
+    }
+}
+
+/// Byte just past the string opening at `start`, and whether it was closed;
+/// an unterminated string ends at its line.
+fn string_end(bytes: &[u8], start: usize) -> (usize, bool) {
+    let mut pos = start + 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            b'"' => return (pos + 1, true),
+            b'\n' => return (pos, false),
+            _ => pos += 1,
+        }
+    }
+    (bytes.len(), false)
+}
+
+/// One non-blank, non-comment YAML line.
+struct YamlLine<'a> {
+    indent: usize,
+    /// Starts a sequence entry (`- `).
+    item: bool,
+    /// Byte column of the key, after any `- `.
+    column: usize,
+    key: Option<&'a str>,
+}
+
+fn yaml_line(line: &str) -> Option<YamlLine<'_>> {
+    let content = line.trim_start();
+    if content.is_empty() || content.starts_with('#') {
+        return None;
+    }
+    let indent = line.len() - content.len();
+    let (item, body) = match content.strip_prefix('-') {
+        Some(rest) if rest.is_empty() || rest.starts_with(' ') => (true, rest.trim_start()),
+        _ => (false, content),
+    };
+    let key = body
+        .split_once(':')
+        .filter(|(_, after)| after.is_empty() || after.starts_with(' '))
+        .map(|(key, _)| key.trim().trim_matches(['"', '\'']))
+        .filter(|key| !key.is_empty());
+    Some(YamlLine {
+        indent,
+        item,
+        column: line.len() - body.len(),
+        key,
+    })
+}
+
+/// Pointer tokens of the mappings and sequences enclosing column `limit` on
+/// line `index`, found by walking up to less-indented `key:` and `- ` lines.
+/// `item` is set when the line starts a sequence entry, whose parent key may
+/// sit at the same indentation.
+fn yaml_parents(lines: &[&str], index: usize, mut limit: usize, mut item: bool) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for row in (0..index).rev() {
+        if limit == 0 && !item {
+            break;
+        }
+        let Some(line) = yaml_line(lines[row]) else {
+            continue;
+        };
+        if line.item && line.indent < limit {
+            if let Some(key) = line.key
+                && line.column < limit
+            {
+                tokens.push(key.to_string());
+            }
+            tokens.push(item_index(lines, row, line.indent).to_string());
+            limit = line.indent;
+            item = true;
+        } else if !line.item
+            && (line.indent < limit || (item && line.indent == limit))
+            && let Some(key) = line.key
+        {
+            tokens.push(key.to_string());
+            limit = line.indent;
+            item = false;
+        }
+    }
+    tokens.reverse();
+    tokens
+}
+
+/// Position of the sequence entry starting on line `index` among its
+/// siblings.
+fn item_index(lines: &[&str], index: usize, indent: usize) -> usize {
+    let mut count = 0;
+    for row in (0..index).rev() {
+        let Some(line) = yaml_line(lines[row]) else {
+            continue;
+        };
+        if line.indent < indent || (line.indent == indent && !line.item) {
+            break;
+        }
+        if line.indent == indent && line.item {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn yaml_cursor(text: &str, offset: usize) -> Cursor {
+    let offset = offset.min(text.len());
+    let lines = text.split('\n').collect::<Vec<_>>();
+    let index = text[..offset].matches('\n').count();
+    let column = offset - text[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+    let Some(line) = yaml_line(lines[index]) else {
+        return Cursor {
+            container: yaml_parents(&lines, index, column, false),
+            member: None,
+            in_key: true,
+        };
+    };
+    let mut container = yaml_parents(&lines, index, line.indent, line.item);
+    if line.item {
+        container.push(item_index(&lines, index, line.indent).to_string());
+    }
+    match line.key {
+        Some(key) => Cursor {
+            container,
+            member: Some(key.to_string()),
+            in_key: column <= line.column + key.len(),
+        },
+        None if line.item => {
+            let member = container.pop();
+            Cursor {
+                container,
+                member,
+                in_key: false,
+            }
+        }
+        None => Cursor {
+            container,
+            member: None,
+            in_key: true,
+        },
+    }
+}
+
+/// 1-based line and column of the YAML value `pointer` names, falling back
+/// to its closest ancestor.
+fn yaml_locate(text: &str, pointer: &str) -> Option<(usize, usize)> {
+    let lines = text.split('\n').collect::<Vec<_>>();
+    let mut target = pointer
+        .split('/')
+        .skip(1)
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect::<Vec<_>>();
+    while !target.is_empty() {
+        for (index, raw) in lines.iter().enumerate() {
+            let Some(line) = yaml_line(raw) else {
+                continue;
+            };
+            let mut tokens = yaml_parents(&lines, index, line.indent, line.item);
+            let mut column = line.indent;
+            if line.item {
+                tokens.push(item_index(&lines, index, line.indent).to_string());
+            }
+            if !tokens.starts_with(&target)
+                && let Some(key) = line.key
+            {
+                tokens.push(key.to_string());
+                column = line.column;
+            }
+            if tokens.starts_with(&target) {
+                return Some((index + 1, raw[..column].chars().count() + 1));
+            }
+        }
+        target.pop();
+    }
+    Some((1, 1))
+}
+
+/// Candidate subschemas for `tokens`, following `$ref`, `allOf`/`oneOf`/
+/// `anyOf`, `items` and `additionalProperties`.
+fn schema_at<'a>(root: &'a Value, tokens: &[String]) -> Vec<&'a Value> {
+    let mut current = Vec::new();
+    expand(root, root, &mut current, 0);
+    for token in tokens {
+        let mut next = Vec::new();
+        for schema in current {
+            if let Some(child) = schema
+                .get("properties")
+                .and_then(|properties| properties.get(token.as_str()))
+            {
+                expand(root, child, &mut next, 0);
+            } else if token.parse::<usize>().is_ok()
+                && let Some(items) = schema.get("items")
+            {
+                expand(root, items, &mut next, 0);
+            } else if let Some(additional) = schema
+                .get("additionalProperties")
+                .filter(|additional| additional.is_object())
+            {
+                expand(root, additional, &mut next, 0);
+            }
+        }
+        current = next;
+    }
+    current
+}
+
+fn expand<'a>(root: &'a Value, schema: &'a Value, out: &mut Vec<&'a Value>, depth: usize) {
+    if depth > 8 {
+        return;
+    }
+    if let Some(target) = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| root.pointer(pointer))
+    {
+        expand(root, target, out, depth + 1);
+    }
+    out.push(schema);
+    for keyword in ["allOf", "oneOf", "anyOf"] {
+        for branch in schema
+            .get(keyword)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            expand(root, branch, out, depth + 1);
+        }
+    }
+}
+
+/// Markdown for hover: description, type, allowed values and default.
+fn describe(schemas: &[&Value]) -> Option<String> {
+    let mut lines = Vec::new();
+    if let Some(description) = schemas
+        .iter()
+        .find_map(|schema| schema.get("description").and_then(Value::as_str))
+    {
+        lines.push(description.to_string());
+    }
+    let types = schemas
+        .iter()
+        .filter_map(|schema| schema.get("type"))
+        .flat_map(|kind| match kind {
+            Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+            kind => kind.as_str().into_iter().collect::<Vec<_>>(),
+        })
+        .collect::<BTreeSet<_>>();
+    if !types.is_empty() {
+        let types = types.into_iter().collect::<Vec<_>>().join(" | ");
+        lines.push(format!("type: `{types}`"));
+    }
+    if let Some(values) = schemas
+        .iter()
+        .find_map(|schema| schema.get("enum").and_then(Value::as_array))
+    {
+        let values = values
+            .iter()
+            .map(|value| format!("`{value}`"))
+            .collect::<Vec<_>>();
+        lines.push(format!("one of: {}", values.join(", ")));
+    }
+    if let Some(default) = schemas.iter().find_map(|schema| schema.get("default")) {
+        lines.push(format!("default: `{default}`"));
+    }
+    (!lines.is_empty()).then(|| lines.join("\n\n"))
+}
+
+fn property_items(root: &Value, schemas: &[&Value]) -> Vec<Value> {
+    let mut seen = BTreeSet::new();
+    let mut items = Vec::new();
+    for schema in schemas {
+        let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+            continue;
+        };
+        for (name, property) in properties {
+            if !seen.insert(name.as_str()) {
+                continue;
+            }
+            let mut item = Map::new();
+            item.insert("label".into(), json!(name));
+            item.insert("kind".into(), json!(10));
+            let mut expanded = Vec::new();
+            expand(root, property, &mut expanded, 0);
+            if let Some(docs) = describe(&expanded) {
+                item.insert(
+                    "documentation".into(),
+                    json!({ "kind": "markdown", "value": docs }),
+                );
+            }
+            items.push(Value::Object(item));
+        }
+    }
+    items
+}
+
+fn value_items(schemas: &[&Value]) -> Vec<Value> {
+    let mut values = Vec::new();
+    for schema in schemas {
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            values.extend(allowed.iter().cloned());
+        }
+        if let Some(constant) = schema.get("const") {
+            values.push(constant.clone());
+        }
+        if schema.get("type").and_then(Value::as_str) == Some("boolean") {
+            values.extend([json!(true), json!(false)]);
+        }
+    }
+    let mut seen = BTreeSet::new();
+    values
+        .into_iter()
+        .map(|value| match value {
+            Value::String(text) => text,
+            other => other.to_string(),
+        })
+        .filter(|label| seen.insert(label.clone()))
+        .map(|label| json!({ "label": label, "kind": 12 }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(tokens: &[&str]) -> Vec<String> {
+        tokens.iter().map(|token| token.to_string()).collect()
+    }
+
+    #[test]
+    fn json_cursor_tracks_keys_values_and_indices() {
+        let text = "{\n  \"operations\": [\n    { \"na\n";
+        let cursor = json_cursor(text, text.len() - 1);
+        assert_eq!(cursor.container, tokens(&["operations", "0"]));
+        assert!(cursor.in_key);
+
+        let text = "{ \"supports\": [\"messaging\", \"ev";
+        let cursor = json_cursor(text, text.len());
+        assert_eq!(cursor.pointer(), tokens(&["supports", "1"]));
+        assert!(!cursor.in_key);
+    }
+
+    #[test]
+    fn yaml_cursor_and_locate_agree_on_paths() {
+        let text = "nodes:\n  - id: start\n    routing:\n      - to: end\n  - id: end\nedges:\n  - from: start\n    to: missing\n";
+        let offset = text.find("to: end").unwrap();
+        let cursor = yaml_cursor(text, offset);
+        assert_eq!(cursor.container, tokens(&["nodes", "0", "routing", "0"]));
+        assert_eq!(cursor.member.as_deref(), Some("to"));
+        assert!(cursor.in_key);
+
+        assert_eq!(yaml_locate(text, "/edges/0/to"), Some((8, 5)));
+        assert_eq!(yaml_locate(text, "/nodes/1"), Some((5, 3)));
+    }
+
+    #[test]
+    fn server_publishes_diagnostics_and_answers_hover_and_completion() {
+        let mut server = Server::new();
+        let reply = server
+            .handle(json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }));
+        assert_eq!(reply[0]["result"]["capabilities"]["hoverProvider"], true);
+
+        let uri = "untitled:flow.ygtc";
+        let text = "nodes:\n  start:\n    routing:\n      - to: nowhere\n";
+        let published = server.handle(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": uri, "languageId": "yaml", "version": 1, "text": text } },
+        }));
+        let diagnostics = &published[0]["params"]["diagnostics"];
+        assert_eq!(diagnostics[0]["code"], "flow.graph.invalid");
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], 2);
+
+        let hover = server.handle(json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "textDocument/hover",
+            "params": { "textDocument": { "uri": uri }, "position": { "line": 0, "character": 1 } },
+        }));
+        assert!(
+            hover[0]["result"]["contents"]["value"]
+                .as_str()
+                .unwrap()
+                .contains("`nodes`")
+        );
+
+        let completion = server.handle(json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "textDocument/completion",
+            "params": { "textDocument": { "uri": uri }, "position": { "line": 4, "character": 0 } },
+        }));
+        let labels = completion[0]["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["label"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert!(labels.contains(&"edges"));
+    }
+
+    #[test]
+    fn malformed_bodies_are_answered_without_ending_the_session() {
+        let mut input = Vec::new();
+        for body in [
+            "{\"jsonrpc\": \"2.0\", \"id\": 1,".to_string(),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "shutdown" }).to_string(),
+            json!({ "jsonrpc": "2.0", "method": "exit" }).to_string(),
+        ] {
+            write!(input, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        }
+        let mut output = Vec::new();
+        serve(&mut input.as_slice(), &mut output).unwrap();
+
+        let mut output = output.as_slice();
+        let error: Value =
+            serde_json::from_slice(&read_message(&mut output).unwrap().unwrap()).unwrap();
+        assert_eq!(error["error"]["code"], -32700);
+        assert_eq!(error["id"], Value::Null);
+        let shutdown: Value =
+            serde_json::from_slice(&read_message(&mut output).unwrap().unwrap()).unwrap();
+        assert_eq!(shutdown["id"], 2);
+        assert_eq!(shutdown["result"], Value::Null);
+        assert!(read_message(&mut output).unwrap().is_none());
+    }
+}
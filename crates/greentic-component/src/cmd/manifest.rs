@@ -0,0 +1,568 @@
+//! Upgrades `component.manifest.json` files written against older shapes of
+//! the manifest schema. Every rewrite is a named rule; values the tool had to
+//! guess, and anything the current schema still rejects, are listed for
+//! review instead of being silently fixed.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+use crate::config::resolve_manifest_path;
+use crate::manifest::{parse_manifest_value, resolve_schema_refs, schema};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ManifestCommand {
+    /// Upgrade a manifest to the current schema
+    Migrate(ManifestMigrateArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ManifestMigrateArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+    /// Report the changes without writing the manifest
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Emit the migration report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct MigrationNote {
+    /// Rule that produced the note, e.g. `operations.shape`.
+    pub rule: &'static str,
+    /// Manifest location, e.g. `capabilities.host.secrets.required`.
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MigrationReport {
+    pub manifest: PathBuf,
+    pub changes: Vec<MigrationNote>,
+    /// Inferred values and remaining problems a person should look at.
+    pub review: Vec<MigrationNote>,
+    /// Whether the migrated manifest passes validation.
+    pub valid: bool,
+    pub written: bool,
+}
+
+pub fn run(command: ManifestCommand) -> Result<()> {
+    match command {
+        ManifestCommand::Migrate(args) => {
+            let report = migrate(&args)?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print_report(&report);
+            }
+            if !report.valid {
+                bail!(
+                    "{} still fails validation after migration; see the review items",
+                    report.manifest.display()
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn migrate(args: &ManifestMigrateArgs) -> Result<MigrationReport> {
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let root = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
+    let raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let mut manifest: Value = serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    let object = manifest
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("{} must contain a JSON object", manifest_path.display()))?;
+
+    let mut migration = Migration::default();
+    migration.apply(object, &root);
+
+    let mut check = manifest.clone();
+    let validation = resolve_schema_refs(&mut check, &root)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| parse_manifest_value(check).map_err(anyhow::Error::from));
+    if let Err(err) = &validation {
+        migration.review("validation", "", format!("{err:#}"));
+    }
+
+    let mut report = MigrationReport {
+        manifest: manifest_path.clone(),
+        changes: migration.changes,
+        review: migration.review,
+        valid: validation.is_ok(),
+        written: false,
+    };
+    if !args.dry_run && !report.changes.is_empty() {
+        fs::write(
+            &manifest_path,
+            serde_json::to_string_pretty(&manifest)? + "\n",
+        )
+        .with_context(|| format!("failed to write {}", manifest_path.display()))?;
+        report.written = true;
+    }
+    Ok(report)
+}
+
+fn print_report(report: &MigrationReport) {
+    if report.changes.is_empty() {
+        println!("{} is already current", report.manifest.display());
+    }
+    for change in &report.changes {
+        println!("migrated {:<28} {}", change.path, change.message);
+    }
+    for item in &report.review {
+        let path = if item.path.is_empty() {
+            "(manifest)"
+        } else {
+            item.path.as_str()
+        };
+        println!("review   {path:<28} {}", item.message);
+    }
+    if report.written {
+        println!("updated {}", report.manifest.display());
+    } else if !report.changes.is_empty() {
+        println!("dry run: {} left unchanged", report.manifest.display());
+    }
+}
+
+#[derive(Default)]
+struct Migration {
+    changes: Vec<MigrationNote>,
+    review: Vec<MigrationNote>,
+}
+
+impl Migration {
+    fn change(&mut self, rule: &'static str, path: &str, message: impl Into<String>) {
+        self.changes.push(MigrationNote {
+            rule,
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn review(&mut self, rule: &'static str, path: &str, message: impl Into<String>) {
+        self.review.push(MigrationNote {
+            rule,
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn apply(&mut self, object: &mut Map<String, Value>, root: &Path) {
+        self.schema_ref(object);
+        self.artifacts(object);
+        self.describe_export(object);
+        self.operations(object);
+        self.supports(object);
+        self.profiles(object);
+        self.config_schema(object);
+        self.capabilities(object);
+        self.secrets(object);
+        self.hashes(object, root);
+        self.unknown_fields(object);
+    }
+
+    fn schema_ref(&mut self, object: &mut Map<String, Value>) {
+        let Some(current) = current_schema_id() else {
+            return;
+        };
+        if let Some(Value::String(reference)) = object.get("$schema")
+            && reference != &current
+        {
+            self.change("schema.ref", "$schema", format!("points at {current}"));
+            object.insert("$schema".into(), Value::String(current));
+        }
+    }
+
+    /// `artifacts: "x.wasm"`, `artifacts.wasm` and a top-level `wasm` all
+    /// became `artifacts.component_wasm`.
+    fn artifacts(&mut self, object: &mut Map<String, Value>) {
+        if let Some(Value::String(wasm)) = object.get("artifacts").cloned() {
+            object.insert("artifacts".into(), json!({ "component_wasm": wasm }));
+            self.change(
+                "artifacts.shape",
+                "artifacts",
+                "string became `{ component_wasm }`",
+            );
+        }
+        if let Some(Value::Object(artifacts)) = object.get_mut("artifacts")
+            && !artifacts.contains_key("component_wasm")
+            && let Some(wasm) = artifacts.remove("wasm")
+        {
+            artifacts.insert("component_wasm".into(), wasm);
+            self.change(
+                "artifacts.shape",
+                "artifacts.wasm",
+                "renamed to `artifacts.component_wasm`",
+            );
+        }
+        if let Some(wasm) = object.get("wasm").cloned()
+            && wasm.is_string()
+            && object
+                .get("artifacts")
+                .and_then(|artifacts| artifacts.get("component_wasm"))
+                .is_none()
+        {
+            object.remove("wasm");
+            let artifacts = object.entry("artifacts").or_insert_with(|| json!({}));
+            if let Some(artifacts) = artifacts.as_object_mut() {
+                artifacts.insert("component_wasm".into(), wasm);
+                self.change(
+                    "artifacts.shape",
+                    "wasm",
+                    "moved to `artifacts.component_wasm`",
+                );
+            }
+        }
+    }
+
+    fn describe_export(&mut self, object: &mut Map<String, Value>) {
+        if object.contains_key("describe_export") {
+            return;
+        }
+        object.insert("describe_export".into(), json!("describe"));
+        self.change(
+            "describe_export.default",
+            "describe_export",
+            "set to `describe`",
+        );
+        self.review(
+            "describe_export.default",
+            "describe_export",
+            "inferred `describe`; confirm the component exports it",
+        );
+    }
+
+    /// Operations declared by name only gain empty schemas.
+    fn operations(&mut self, object: &mut Map<String, Value>) {
+        let Some(Value::Array(operations)) = object.get_mut("operations") else {
+            return;
+        };
+        let mut named = Vec::new();
+        for operation in operations.iter_mut() {
+            if let Value::String(name) = operation {
+                named.push(name.clone());
+                *operation = json!({
+                    "name": name,
+                    "input_schema": {},
+                    "output_schema": {},
+                });
+            }
+        }
+        if named.is_empty() {
+            return;
+        }
+        self.change(
+            "operations.shape",
+            "operations",
+            format!("{} name-only operation(s) became objects", named.len()),
+        );
+        for name in named {
+            self.review(
+                "operations.shape",
+                &format!("operations.{name}"),
+                "input_schema and output_schema are empty; doctor rejects them without --permissive",
+            );
+        }
+    }
+
+    fn supports(&mut self, object: &mut Map<String, Value>) {
+        let empty = match object.get("supports") {
+            None => true,
+            Some(Value::Array(kinds)) => kinds.is_empty(),
+            Some(_) => false,
+        };
+        if !empty {
+            return;
+        }
+        object.insert("supports".into(), json!(["messaging"]));
+        self.change("supports.default", "supports", "set to [\"messaging\"]");
+        self.review(
+            "supports.default",
+            "supports",
+            "inferred `messaging`; list the flow kinds the component actually handles",
+        );
+    }
+
+    /// A bare list of profiles became `{ default, supported }`.
+    fn profiles(&mut self, object: &mut Map<String, Value>) {
+        match object.get("profiles").cloned() {
+            Some(Value::Array(supported)) => {
+                let mut profiles = json!({ "supported": supported });
+                if let Some(first) = supported.first() {
+                    profiles["default"] = first.clone();
+                }
+                object.insert("profiles".into(), profiles);
+                self.change(
+                    "profiles.shape",
+                    "profiles",
+                    "list became `{ default, supported }` with the first entry as default",
+                );
+            }
+            None => {
+                object.insert(
+                    "profiles".into(),
+                    json!({ "default": "stateless", "supported": ["stateless"] }),
+                );
+                self.change("profiles.default", "profiles", "set to `stateless`");
+                self.review(
+                    "profiles.default",
+                    "profiles",
+                    "inferred the `stateless` profile",
+                );
+            }
+            Some(_) => {}
+        }
+    }
+
+    fn config_schema(&mut self, object: &mut Map<String, Value>) {
+        if object.contains_key("config_schema") {
+            return;
+        }
+        object.insert(
+            "config_schema".into(),
+            json!({
+                "type": "object",
+                "properties": {},
+                "required": [],
+                "additionalProperties": false,
+            }),
+        );
+        self.change(
+            "config_schema.default",
+            "config_schema",
+            "set to an empty object schema",
+        );
+    }
+
+    /// The schema wants `wasi` and `host` (or a `preset`) to be explicit.
+    fn capabilities(&mut self, object: &mut Map<String, Value>) {
+        let capabilities = object.entry("capabilities").or_insert_with(|| json!({}));
+        let Some(capabilities) = capabilities.as_object_mut() else {
+            return;
+        };
+        if capabilities.contains_key("preset") {
+            return;
+        }
+        for section in ["wasi", "host"] {
+            if !capabilities.contains_key(section) {
+                capabilities.insert(section.into(), json!({}));
+                self.change(
+                    "capabilities.sections",
+                    &format!("capabilities.{section}"),
+                    "added as an empty section",
+                );
+            }
+        }
+    }
+
+    /// String-only secrets became structured `SecretRequirement`s.
+    fn secrets(&mut self, object: &mut Map<String, Value>) {
+        if let Some(Value::Array(requirements)) = object.get_mut("secret_requirements") {
+            let upgraded = upgrade_secrets(requirements);
+            self.note_secrets("secret_requirements", upgraded);
+        }
+        let Some(Value::Object(host)) = object
+            .get_mut("capabilities")
+            .and_then(|capabilities| capabilities.get_mut("host"))
+        else {
+            return;
+        };
+        if let Some(Value::Array(keys)) = host.get("secrets").cloned() {
+            host.insert("secrets".into(), json!({ "required": keys }));
+            self.change(
+                "secrets.shape",
+                "capabilities.host.secrets",
+                "list became `{ required }`",
+            );
+        }
+        if let Some(Value::Array(required)) = host
+            .get_mut("secrets")
+            .and_then(|secrets| secrets.get_mut("required"))
+        {
+            let upgraded = upgrade_secrets(required);
+            self.note_secrets("capabilities.host.secrets.required", upgraded);
+        }
+    }
+
+    fn note_secrets(&mut self, path: &str, upgraded: Vec<String>) {
+        if upgraded.is_empty() {
+            return;
+        }
+        self.change(
+            "secrets.structured",
+            path,
+            format!("{} string secret(s) became requirements", upgraded.len()),
+        );
+        self.review(
+            "secrets.structured",
+            path,
+            format!(
+                "inferred scope dev/default and format text for {}",
+                upgraded.join(", ")
+            ),
+        );
+    }
+
+    /// Fills a missing wasm hash from the artifact when it is on disk.
+    fn hashes(&mut self, object: &mut Map<String, Value>, root: &Path) {
+        if object
+            .get("hashes")
+            .and_then(|hashes| hashes.get("component_wasm"))
+            .is_some()
+        {
+            return;
+        }
+        let wasm = object
+            .get("artifacts")
+            .and_then(|artifacts| artifacts.get("component_wasm"))
+            .and_then(Value::as_str)
+            .map(|relative| root.join(relative));
+        match wasm.as_deref().map(fs::read) {
+            Some(Ok(bytes)) => {
+                let digest = format!("blake3:{}", blake3::hash(&bytes).to_hex());
+                let hashes = object.entry("hashes").or_insert_with(|| json!({}));
+                if let Some(hashes) = hashes.as_object_mut() {
+                    hashes.insert("component_wasm".into(), Value::String(digest));
+                    self.change(
+                        "hashes.component_wasm",
+                        "hashes.component_wasm",
+                        "computed from the wasm artifact",
+                    );
+                }
+            }
+            _ => self.review(
+                "hashes.component_wasm",
+                "hashes.component_wasm",
+                "missing and the wasm artifact is not on disk; build, then run `greentic-component hash`",
+            ),
+        }
+    }
+
+    /// Fields the current schema does not know are kept, but flagged.
+    fn unknown_fields(&mut self, object: &Map<String, Value>) {
+        let known = known_fields();
+        if known.is_empty() {
+            return;
+        }
+        for key in object.keys().filter(|key| !known.contains(key.as_str())) {
+            self.review(
+                "fields.unknown",
+                key,
+                "not part of the current schema; remove it or move it under a known field",
+            );
+        }
+    }
+}
+
+/// Replaces string entries with text requirements; returns their keys.
+fn upgrade_secrets(entries: &mut [Value]) -> Vec<String> {
+    let mut upgraded = Vec::new();
+    for entry in entries {
+        if let Value::String(key) = entry {
+            upgraded.push(key.clone());
+            *entry = json!({
+                "key": key,
+                "required": true,
+                "scope": { "env": "dev", "tenant": "default" },
+                "format": "text",
+            });
+        }
+    }
+    upgraded
+}
+
+fn schema_document() -> Option<Value> {
+    serde_json::from_str(schema()).ok()
+}
+
+fn current_schema_id() -> Option<String> {
+    schema_document()?
+        .get("$id")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+fn known_fields() -> BTreeSet<String> {
+    schema_document()
+        .and_then(|doc| doc.get("properties").and_then(Value::as_object).cloned())
+        .map(|properties| properties.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_legacy_shapes_and_flags_guesses() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("component.wasm"), b"\0asm").unwrap();
+        let legacy = json!({
+            "$schema": "https://example.com/old/component.manifest.schema.json",
+            "id": "com.example.legacy",
+            "name": "Legacy",
+            "version": "0.1.0",
+            "world": "greentic:component/component@0.6.0",
+            "operations": ["handle_message"],
+            "profiles": ["stateless"],
+            "capabilities": {
+                "host": { "secrets": ["API_TOKEN"] }
+            },
+            "artifacts": "component.wasm",
+            "notes": "kept"
+        });
+        let manifest = dir.path().join("component.manifest.json");
+        fs::write(&manifest, legacy.to_string()).unwrap();
+
+        let args = ManifestMigrateArgs {
+            manifest: dir.path().to_path_buf(),
+            dry_run: false,
+            json: false,
+        };
+        let report = migrate(&args).unwrap();
+        assert!(report.written);
+        let rules = report
+            .review
+            .iter()
+            .map(|note| note.rule)
+            .collect::<BTreeSet<_>>();
+        assert!(rules.contains("describe_export.default"));
+        assert!(rules.contains("secrets.structured"));
+        assert!(rules.contains("fields.unknown"));
+
+        let migrated: Value =
+            serde_json::from_str(&fs::read_to_string(&manifest).unwrap()).unwrap();
+        assert_eq!(migrated["$schema"], json!(current_schema_id().unwrap()));
+        assert_eq!(migrated["artifacts"]["component_wasm"], "component.wasm");
+        assert_eq!(migrated["operations"][0]["name"], "handle_message");
+        assert_eq!(migrated["profiles"]["default"], "stateless");
+        assert_eq!(
+            migrated["capabilities"]["host"]["secrets"]["required"][0]["key"],
+            "API_TOKEN"
+        );
+        assert_eq!(
+            migrated["hashes"]["component_wasm"],
+            json!(format!("blake3:{}", blake3::hash(b"\0asm").to_hex()))
+        );
+        assert_eq!(migrated["notes"], "kept");
+
+        let again = migrate(&args).unwrap();
+        assert!(again.changes.is_empty());
+        assert!(!again.written);
+    }
+}
@@ -1,14 +1,39 @@
+pub mod bench;
 pub mod build;
+pub mod cache;
+pub mod caps;
+pub mod client;
+pub mod compat;
 pub mod component_world;
+pub mod configurator;
+pub mod conform;
+pub mod conformance;
 pub mod doctor;
+pub mod errors;
+pub mod exit;
+pub mod fixtures;
 pub mod flow;
+pub mod graph;
 pub mod hash;
+pub mod index;
 pub mod inspect;
+pub mod lsp;
+pub mod manifest;
 pub mod new;
+pub mod op;
 pub mod path;
 pub mod post;
+pub mod progress;
+pub mod sdk;
+pub mod search;
+pub mod secret;
 #[cfg(feature = "store")]
 pub mod store;
+pub mod sync;
 pub mod templates;
 pub mod test;
+pub mod upgrade_test;
+pub mod validate;
+pub mod verify_build;
+pub mod wit;
 pub mod wizard;
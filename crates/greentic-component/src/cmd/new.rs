@@ -11,11 +11,13 @@ use clap::Args;
 use serde::Serialize;
 use serde_json::json;
 
+use crate::cmd::exit::ExitCode;
 use crate::cmd::post::{self, GitInitStatus, PostInitReport};
 use crate::scaffold::deps::DependencyMode;
 use crate::scaffold::engine::{
     DEFAULT_WIT_WORLD, ScaffoldEngine, ScaffoldOutcome, ScaffoldRequest,
 };
+use crate::scaffold::hooks::{self, HookReport, HookStatus};
 use crate::scaffold::validate::{self, ComponentName, OrgNamespace, ValidationError};
 
 type ValidationResult<T> = std::result::Result<T, ValidationError>;
@@ -65,6 +67,12 @@ pub struct NewArgs {
     /// Skip git initialization after scaffolding
     #[arg(long = "no-git")]
     pub no_git: bool,
+    /// Skip the template's post-generation hooks
+    #[arg(long = "no-hooks")]
+    pub no_hooks: bool,
+    /// Run the template's post-generation hooks without asking
+    #[arg(long = "run-hooks", alias = "yes-hooks", conflicts_with = "no_hooks")]
+    pub run_hooks: bool,
     /// Emit JSON instead of human-readable output
     #[arg(long = "json")]
     pub json: bool,
@@ -95,6 +103,14 @@ pub fn run(args: NewArgs, engine: &ScaffoldEngine) -> Result<()> {
         stdout().flush().ok();
     }
     let post_started = Instant::now();
+    // Hooks run before git init so their changes land in the initial commit.
+    let approval = hooks::approve(
+        &outcome.hooks,
+        args.no_hooks,
+        args.run_hooks,
+        args.non_interactive,
+    );
+    let hook_reports = hooks::run_hooks(&outcome.path, &outcome.hooks, &approval);
     let skip_git = should_skip_git(&args);
     let post_init = post::run_post_init(&outcome, skip_git);
     if !args.json && !args.no_check {
@@ -109,12 +125,16 @@ pub fn run(args: NewArgs, engine: &ScaffoldEngine) -> Result<()> {
             scaffold: &outcome,
             compile_check: &compile_check,
             post_init: &post_init,
+            hooks: &hook_reports,
         };
         print_json(&payload)?;
     } else {
-        print_human(&outcome, &compile_check, &post_init);
+        print_human(&outcome, &compile_check, &post_init, &hook_reports);
         println!("post-init + checks in {:.2?}", post_started.elapsed());
     }
+    if let Some(failed) = hook_reports.iter().find(|report| report.is_blocking()) {
+        anyhow::bail!("post-generation hook `{}` failed", failed.name);
+    }
     if compile_check.ran && !compile_check.passed {
         anyhow::bail!("cargo check --target wasm32-wasip2 failed");
     }
@@ -152,12 +172,18 @@ fn print_json<T: Serialize>(value: &T) -> Result<()> {
     Ok(())
 }
 
-fn print_human(outcome: &ScaffoldOutcome, check: &CompileCheckReport, post: &PostInitReport) {
+fn print_human(
+    outcome: &ScaffoldOutcome,
+    check: &CompileCheckReport,
+    post: &PostInitReport,
+    hooks: &[HookReport],
+) {
     println!("{}", outcome.human_summary());
     print_template_metadata(outcome);
     for path in &outcome.created {
         println!("  - {path}");
     }
+    print_hook_summary(hooks);
     print_git_summary(&post.git);
     if !check.ran {
         println!("cargo check (wasm32-wasip2): skipped (--no-check)");
@@ -234,6 +260,21 @@ fn print_git_summary(report: &post::GitInitReport) {
     }
 }
 
+pub(crate) fn print_hook_summary(hooks: &[HookReport]) {
+    for hook in hooks {
+        let detail = hook
+            .message
+            .as_deref()
+            .map(|message| format!(" ({message})"))
+            .unwrap_or_default();
+        match hook.status {
+            HookStatus::Ok => println!("hook {}: ok", hook.command),
+            HookStatus::Skipped => println!("hook {}: skipped{detail}", hook.command),
+            HookStatus::Failed => println!("hook {}: FAILED{detail}", hook.command),
+        }
+    }
+}
+
 fn print_template_metadata(outcome: &ScaffoldOutcome) {
     match &outcome.template_description {
         Some(desc) => println!("Template: {} — {desc}", outcome.template),
@@ -300,10 +341,11 @@ fn emit_validation_failure(err: &ValidationError, json: bool) -> Result<()> {
                 "kind": "validation",
                 "code": err.code(),
                 "message": err.to_string()
-            }
+            },
+            "exit_code": ExitCode::Usage.code()
         });
         print_json(&payload)?;
-        process::exit(1);
+        process::exit(ExitCode::Usage.code());
     }
     Ok(())
 }
@@ -313,6 +355,7 @@ struct NewCliOutput<'a> {
     scaffold: &'a ScaffoldOutcome,
     compile_check: &'a CompileCheckReport,
     post_init: &'a PostInitReport,
+    hooks: &'a [HookReport],
 }
 
 #[derive(Debug, Serialize)]
@@ -364,6 +407,8 @@ mod tests {
             non_interactive: false,
             no_check: false,
             no_git: false,
+            no_hooks: false,
+            run_hooks: false,
             json: false,
         };
         let request = build_request(&args).unwrap();
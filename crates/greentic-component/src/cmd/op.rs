@@ -0,0 +1,426 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{Args, Subcommand};
+use serde_json::{Value, json};
+
+use crate::config::resolve_manifest_path;
+use crate::manifest::{parse_manifest_value, resolve_schema_refs};
+
+/// Marker comments in wizard scaffolds that `op add` inserts code above.
+const MARKER: &str = "// greentic-component op add: ";
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum OpCommand {
+    /// Add an operation to the manifest and the generated sources
+    Add(OpAddArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct OpAddArgs {
+    /// Operation name (snake_case)
+    #[arg(value_name = "NAME")]
+    pub name: String,
+    /// JSON Schema for the operation input
+    #[arg(long, value_name = "FILE")]
+    pub input: PathBuf,
+    /// JSON Schema for the operation output
+    #[arg(long, value_name = "FILE")]
+    pub output: PathBuf,
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+}
+
+pub fn run(command: OpCommand) -> Result<()> {
+    match command {
+        OpCommand::Add(args) => {
+            for path in add(&args)? {
+                println!("updated {}", path.display());
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Adds the operation everywhere it has to be declared and returns the files
+/// written. Nothing is written unless every file can be updated.
+pub fn add(args: &OpAddArgs) -> Result<Vec<PathBuf>> {
+    let name = args.name.trim();
+    if !is_operation_name(name) {
+        bail!("operation name `{name}` must be snake_case (a-z, 0-9, _) and start with a letter");
+    }
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let root = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
+    let raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let mut manifest: Value = serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    let operations = manifest
+        .get_mut("operations")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| anyhow!("{} has no operations array", manifest_path.display()))?;
+    if operations
+        .iter()
+        .any(|op| op.get("name").and_then(Value::as_str) == Some(name))
+    {
+        bail!(
+            "operation `{name}` already exists in {}",
+            manifest_path.display()
+        );
+    }
+
+    let input = read_schema(&args.input)?;
+    let output = read_schema(&args.output)?;
+    let input_ir = schema_ir_source(&input, 1).context("convert --input schema")?;
+    let output_ir = schema_ir_source(&output, 1).context("convert --output schema")?;
+    operations.push(json!({
+        "name": name,
+        "input_schema": input,
+        "output_schema": output,
+    }));
+    let mut check = manifest.clone();
+    resolve_schema_refs(&mut check, &root).context("resolve manifest schema references")?;
+    parse_manifest_value(check).context("manifest with the new operation failed validation")?;
+
+    let src = root.join("src");
+    let mut schema_rs = Source::load(src.join("schema.rs"), MARKER)?;
+    let mut descriptor_rs = Source::load(src.join("descriptor.rs"), MARKER)?;
+    let mut runtime_rs = Source::load(src.join("runtime.rs"), MARKER)?;
+    let mut lib_rs = Source::load(src.join("lib.rs"), MARKER)?;
+    if runtime_rs.text.contains(&format!("pub fn {name}(")) {
+        bail!("{} already defines `{name}`", runtime_rs.path.display());
+    }
+    let missing = [
+        (&descriptor_rs, "operations"),
+        (&lib_rs, "ops"),
+        (&lib_rs, "dispatch"),
+    ]
+    .iter()
+    .filter(|(source, marker)| !source.has_marker(marker))
+    .map(|(source, marker)| format!("`{MARKER}{marker}` in {}", source.path.display()))
+    .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        bail!(
+            "op add needs a wizard scaffold; missing {}",
+            missing.join(", ")
+        );
+    }
+
+    schema_rs.append(&schema_functions(name, &input_ir, &output_ir));
+    descriptor_rs.insert(
+        "operations",
+        &[format!("{name}_operation(&config_schema),")],
+    );
+    descriptor_rs.append(&descriptor_function(name));
+    lib_rs.insert("ops", &node_op(name));
+    lib_rs.insert(
+        "dispatch",
+        &[format!(
+            "\"{name}\" => runtime::{name}(envelope.payload_cbor, Vec::new()).0,"
+        )],
+    );
+    runtime_rs.append(&runtime_stub(name));
+
+    fs::write(
+        &manifest_path,
+        serde_json::to_string_pretty(&manifest)? + "\n",
+    )
+    .with_context(|| format!("failed to write {}", manifest_path.display()))?;
+    let mut written = vec![manifest_path];
+    for source in [schema_rs, descriptor_rs, lib_rs, runtime_rs] {
+        written.push(source.save()?);
+    }
+    Ok(written)
+}
+
+fn is_operation_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn read_schema(path: &Path) -> Result<Value> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let schema: Value = serde_json::from_str(&raw)
+        .with_context(|| format!("{} is not valid JSON", path.display()))?;
+    if !schema.is_object() {
+        bail!("{} must contain a JSON Schema object", path.display());
+    }
+    Ok(schema)
+}
+
+/// A generated source file edited in place at `{prefix}<marker>` comments.
+pub(crate) struct Source {
+    pub(crate) path: PathBuf,
+    pub(crate) text: String,
+    prefix: &'static str,
+}
+
+impl Source {
+    pub(crate) fn load(path: PathBuf, prefix: &'static str) -> Result<Self> {
+        let text = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+        Ok(Self { path, text, prefix })
+    }
+
+    pub(crate) fn has_marker(&self, marker: &str) -> bool {
+        self.text.contains(&format!("{}{marker}", self.prefix))
+    }
+
+    /// Inserts `lines` above the marker, at the marker's indentation.
+    pub(crate) fn insert(&mut self, marker: &str, lines: &[String]) {
+        let needle = format!("{}{marker}", self.prefix);
+        let Some(at) = self.text.find(&needle) else {
+            return;
+        };
+        let line_start = self.text[..at].rfind('\n').map_or(0, |idx| idx + 1);
+        let indent = &self.text[line_start..at];
+        let block = lines
+            .iter()
+            .map(|line| format!("{indent}{line}\n"))
+            .collect::<String>();
+        self.text.insert_str(line_start, &block);
+    }
+
+    pub(crate) fn append(&mut self, code: &str) {
+        if !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+        self.text.push('\n');
+        self.text.push_str(code);
+    }
+
+    pub(crate) fn save(self) -> Result<PathBuf> {
+        fs::write(&self.path, &self.text)
+            .with_context(|| format!("failed to write {}", self.path.display()))?;
+        Ok(self.path)
+    }
+}
+
+fn schema_functions(name: &str, input_ir: &str, output_ir: &str) -> String {
+    format!(
+        r#"pub fn {name}_input_schema() -> SchemaIr {{
+    {input_ir}
+}}
+
+pub fn {name}_output_schema() -> SchemaIr {{
+    {output_ir}
+}}
+
+pub fn {name}_input_schema_cbor() -> Vec<u8> {{
+    canonical::to_canonical_cbor_allow_floats(&{name}_input_schema()).unwrap_or_default()
+}}
+
+pub fn {name}_output_schema_cbor() -> Vec<u8> {{
+    canonical::to_canonical_cbor_allow_floats(&{name}_output_schema()).unwrap_or_default()
+}}
+"#
+    )
+}
+
+fn descriptor_function(name: &str) -> String {
+    format!(
+        r#"fn {name}_operation(
+    config_schema: &greentic_types::schemas::common::schema_ir::SchemaIr,
+) -> ComponentOperation {{
+    let input_schema = schema::{name}_input_schema();
+    let output_schema = schema::{name}_output_schema();
+    let op_hash = schema_hash(&input_schema, &output_schema, config_schema)
+        .expect("schema hash");
+    ComponentOperation {{
+        id: "{name}".to_string(),
+        display_name: None,
+        input: ComponentRunInput {{ schema: input_schema }},
+        output: ComponentRunOutput {{ schema: output_schema }},
+        defaults: BTreeMap::new(),
+        redactions: Vec::new(),
+        constraints: BTreeMap::new(),
+        schema_hash: op_hash,
+    }}
+}}
+"#
+    )
+}
+
+fn node_op(name: &str) -> Vec<String> {
+    format!(
+        r#"node::Op {{
+    name: "{name}".to_string(),
+    summary: None,
+    input: node::IoSchema {{
+        schema: node::SchemaSource::InlineCbor(schema::{name}_input_schema_cbor()),
+        content_type: "application/cbor".to_string(),
+        schema_version: None,
+    }},
+    output: node::IoSchema {{
+        schema: node::SchemaSource::InlineCbor(schema::{name}_output_schema_cbor()),
+        content_type: "application/cbor".to_string(),
+        schema_version: None,
+    }},
+    examples: Vec::new(),
+}},"#
+    )
+    .lines()
+    .map(str::to_string)
+    .collect()
+}
+
+fn runtime_stub(name: &str) -> String {
+    format!(
+        r#"/// Handles the `{name}` operation; the output must match
+/// `schema::{name}_output_schema()`.
+pub fn {name}(input: Vec<u8>, state: Vec<u8>) -> (Vec<u8>, Vec<u8>) {{
+    let _input_map = decode_map(&input);
+    let output = BTreeMap::<String, JsonValue>::new();
+    let output_cbor = canonical::to_canonical_cbor_allow_floats(&output).unwrap_or_default();
+    (output_cbor, canonicalize_or_empty(&state))
+}}
+
+#[cfg(test)]
+mod {name}_tests {{
+    use super::*;
+
+    #[test]
+    fn {name}_returns_a_cbor_map() {{
+        let input = canonical::to_canonical_cbor_allow_floats(&serde_json::json!({{}}))
+            .expect("encode input");
+        let (output, _state) = {name}(input, Vec::new());
+        let value: JsonValue = canonical::from_cbor(&output).expect("decode output");
+        assert!(value.is_object());
+    }}
+}}
+"#
+    )
+}
+
+/// Renders a JSON Schema as the `SchemaIr` expression building it, indented
+/// for a body `depth` levels deep.
+fn schema_ir_source(schema: &Value, depth: usize) -> Result<String> {
+    let pad = "    ".repeat(depth);
+    let inner = "    ".repeat(depth + 1);
+    if let Some(variants) = schema.get("oneOf").and_then(Value::as_array) {
+        let variants = variants
+            .iter()
+            .map(|variant| {
+                Ok(format!(
+                    "{inner}    {},",
+                    schema_ir_source(variant, depth + 2)?
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .join("\n");
+        return Ok(format!(
+            "SchemaIr::OneOf {{\n{inner}variants: vec![\n{variants}\n{inner}],\n{pad}}}"
+        ));
+    }
+    if let Some(id) = schema.get("$ref").and_then(Value::as_str) {
+        return Ok(format!(
+            "SchemaIr::Ref {{ id: {}.to_string() }}",
+            quoted(id)
+        ));
+    }
+    if schema.get("enum").is_some() {
+        bail!("`enum` schemas are not supported yet; add them to src/schema.rs by hand");
+    }
+    let ty = schema
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("schema needs a `type`, `oneOf` or `$ref`: {schema}"))?;
+    let bound = |key: &str| match schema.get(key) {
+        Some(value) if value.is_number() => format!("Some({value})"),
+        _ => "None".to_string(),
+    };
+    let text = |key: &str| match schema.get(key).and_then(Value::as_str) {
+        Some(value) => format!("Some({}.to_string())", quoted(value)),
+        None => "None".to_string(),
+    };
+    Ok(match ty {
+        "object" => {
+            let properties = match schema.get("properties").and_then(Value::as_object) {
+                Some(properties) if !properties.is_empty() => {
+                    let entries = properties
+                        .iter()
+                        .map(|(key, value)| {
+                            Ok(format!(
+                                "{inner}    ({}.to_string(), {}),",
+                                quoted(key),
+                                schema_ir_source(value, depth + 2)?
+                            ))
+                        })
+                        .collect::<Result<Vec<_>>>()?
+                        .join("\n");
+                    format!("BTreeMap::from([\n{entries}\n{inner}])")
+                }
+                _ => "BTreeMap::new()".to_string(),
+            };
+            let required = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(|name| format!("{}.to_string()", quoted(name)))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default();
+            let additional = match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => "AdditionalProperties::Forbid",
+                None | Some(Value::Bool(true)) => "AdditionalProperties::Allow",
+                Some(_) => bail!("schema-valued `additionalProperties` is not supported yet"),
+            };
+            format!(
+                "SchemaIr::Object {{\n{inner}properties: {properties},\n{inner}required: vec![{required}],\n{inner}additional: {additional},\n{pad}}}"
+            )
+        }
+        "array" => {
+            let items = schema
+                .get("items")
+                .ok_or_else(|| anyhow!("array schema needs `items`"))?;
+            format!(
+                "SchemaIr::Array {{\n{inner}items: Box::new({}),\n{inner}min_items: {},\n{inner}max_items: {},\n{pad}}}",
+                schema_ir_source(items, depth + 1)?,
+                bound("minItems"),
+                bound("maxItems")
+            )
+        }
+        "string" if schema.get("contentEncoding").and_then(Value::as_str) == Some("base64") => {
+            "SchemaIr::Bytes".to_string()
+        }
+        "string" => format!(
+            "SchemaIr::String {{\n{inner}min_len: {},\n{inner}max_len: {},\n{inner}regex: {},\n{inner}format: {},\n{pad}}}",
+            bound("minLength"),
+            bound("maxLength"),
+            text("pattern"),
+            text("format")
+        ),
+        "integer" => format!(
+            "SchemaIr::Int {{\n{inner}min: {},\n{inner}max: {},\n{pad}}}",
+            bound("minimum"),
+            bound("maximum")
+        ),
+        "number" => {
+            let float = |key: &str| match schema.get(key).and_then(Value::as_f64) {
+                Some(value) => format!("Some({value:?})"),
+                None => "None".to_string(),
+            };
+            format!(
+                "SchemaIr::Float {{\n{inner}min: {},\n{inner}max: {},\n{pad}}}",
+                float("minimum"),
+                float("maximum")
+            )
+        }
+        "boolean" => "SchemaIr::Bool".to_string(),
+        "null" => "SchemaIr::Null".to_string(),
+        other => bail!("unsupported schema type `{other}`"),
+    })
+}
+
+fn quoted(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
@@ -375,6 +375,7 @@ mod tests {
             template_tags: vec!["test".into()],
             path: project.clone(),
             created: vec!["README.md".into()],
+            hooks: Vec::new(),
         };
 
         let report = run_post_init(&outcome, false);
@@ -403,6 +404,7 @@ mod tests {
             template_tags: vec![],
             path: project.clone(),
             created: vec![],
+            hooks: Vec::new(),
         };
         let report = run_post_init(&outcome, false);
         assert!(matches!(
@@ -430,6 +432,7 @@ mod tests {
             template_tags: vec![],
             path: project.clone(),
             created: vec![],
+            hooks: Vec::new(),
         };
         let report = run_post_init(&outcome, true);
         assert_eq!(report.git.status, GitInitStatus::Skipped);
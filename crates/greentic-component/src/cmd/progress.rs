@@ -0,0 +1,87 @@
+//! NDJSON progress events for long-running commands (`--log-format json`).
+//!
+//! Each event is one JSON object per line on stderr:
+//!
+//! ```text
+//! {"command":"build","event":"start","phase":"compile","ts_ms":1760000000000}
+//! {"command":"build","duration_ms":5120,"event":"finish","outcome":"ok","phase":"compile","ts_ms":1760000005120}
+//! {"code":"W_MISSING_DESCRIPTION","command":"build","event":"diagnostic","message":"...","severity":"warning","ts_ms":...}
+//! ```
+//!
+//! A failed phase finishes with `"outcome":"error"` and an `error` message.
+//! Stdout is left alone, so `--json` reports still parse.
+
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde_json::{Value, json};
+
+static JSON_EVENTS: AtomicBool = AtomicBool::new(false);
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable messages only
+    #[default]
+    Text,
+    /// Human-readable messages plus NDJSON progress events on stderr
+    Json,
+}
+
+/// Selects the event format for the rest of the process.
+pub fn set_format(format: LogFormat) {
+    JSON_EVENTS.store(format == LogFormat::Json, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    JSON_EVENTS.load(Ordering::Relaxed)
+}
+
+/// Runs `run` as `phase` of `command`, bracketed by start/finish events.
+pub fn phase<T>(command: &str, phase: &str, run: impl FnOnce() -> Result<T>) -> Result<T> {
+    if !enabled() {
+        return run();
+    }
+    emit(command, "start", json!({ "phase": phase }));
+    let started = Instant::now();
+    let result = run();
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let finish = match &result {
+        Ok(_) => json!({ "phase": phase, "outcome": "ok", "duration_ms": duration_ms }),
+        Err(err) => json!({
+            "phase": phase,
+            "outcome": "error",
+            "duration_ms": duration_ms,
+            "error": format!("{err:#}"),
+        }),
+    };
+    emit(command, "finish", finish);
+    result
+}
+
+/// Reports a coded warning or error that does not end the phase.
+pub fn diagnostic(command: &str, severity: &str, code: &str, message: &str) {
+    if enabled() {
+        emit(
+            command,
+            "diagnostic",
+            json!({ "severity": severity, "code": code, "message": message }),
+        );
+    }
+}
+
+fn emit(command: &str, event: &str, fields: Value) {
+    let mut line = fields;
+    line["command"] = json!(command);
+    line["event"] = json!(event);
+    line["ts_ms"] = json!(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default()
+    );
+    let mut stderr = std::io::stderr().lock();
+    let _ = writeln!(stderr, "{line}");
+}
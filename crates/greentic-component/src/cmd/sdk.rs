@@ -0,0 +1,508 @@
+//! Generates a Rust helper module with typed wrappers over the host imports
+//! (state, secrets, HTTP, log) for a component project, so guest code does
+//! not call the raw bindings directly. The module is rendered per ABI and
+//! carries a marker line that `sdk check` compares against.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Subcommand};
+
+use super::wit::resolve_abi;
+
+/// First line of every generated module; `{abi}` is the component ABI.
+const MARKER: &str = "// @generated by `greentic-component sdk gen` for component@{abi}.";
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SdkCommand {
+    /// Write the host helper module into a component project
+    Gen(SdkGenArgs),
+    /// Fail when the helper module is missing, edited, or for another ABI
+    Check(SdkCheckArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SdkGenArgs {
+    /// ABI version to generate for (defaults to the project's
+    /// `[package.metadata.greentic] abi_version`)
+    #[arg(long, value_name = "VERSION")]
+    pub abi: Option<String>,
+    /// Component project directory
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub project: PathBuf,
+    /// Module path, relative to the project
+    #[arg(long, value_name = "PATH", default_value = "src/greentic_host.rs")]
+    pub out: PathBuf,
+    /// Overwrite a module that was edited locally
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SdkCheckArgs {
+    /// ABI version to check against (defaults to the project's abi_version)
+    #[arg(long, value_name = "VERSION")]
+    pub abi: Option<String>,
+    /// Component project directory
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub project: PathBuf,
+    /// Module path, relative to the project
+    #[arg(long, value_name = "PATH", default_value = "src/greentic_host.rs")]
+    pub out: PathBuf,
+}
+
+/// Crates the generated module uses, which the project must depend on.
+const REQUIRED_DEPS: &[&str] = &[
+    "greentic-interfaces-guest",
+    "serde",
+    "serde_json",
+    "wit-bindgen",
+];
+
+pub fn run(command: SdkCommand) -> Result<()> {
+    match command {
+        SdkCommand::Gen(args) => generate(&args).map(|_| ()),
+        SdkCommand::Check(args) => check(&args),
+    }
+}
+
+/// ABI versions the generator has a template for.
+pub fn supported_abis() -> &'static [&'static str] {
+    &["0.6.0"]
+}
+
+/// The helper module for `abi`.
+pub fn render(abi: &str) -> Result<String> {
+    let template = match abi {
+        "0.6.0" => HOST_V0_6,
+        other => bail!(
+            "no host helper template for ABI `{other}`; supported: {}",
+            supported_abis().join(", ")
+        ),
+    };
+    Ok(format!(
+        "{}\n{}",
+        MARKER.replace("{abi}", abi),
+        template.replace("{abi}", abi)
+    ))
+}
+
+pub fn generate(args: &SdkGenArgs) -> Result<PathBuf> {
+    let abi = resolve_abi(args.abi.as_deref(), &args.project)?;
+    let rendered = render(&abi)?;
+    let target = args.project.join(&args.out);
+    if !args.force
+        && let Ok(existing) = fs::read_to_string(&target)
+    {
+        match generated_abi(&existing) {
+            None => bail!(
+                "{} exists and was not generated by `sdk gen`; pass --force to overwrite",
+                target.display()
+            ),
+            Some(found) if found == abi && normalize(&existing) != normalize(&rendered) => bail!(
+                "{} was edited locally; pass --force to overwrite",
+                target.display()
+            ),
+            Some(_) => {}
+        }
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    fs::write(&target, &rendered).with_context(|| format!("write {}", target.display()))?;
+    println!(
+        "generated component@{abi} host helpers at {}",
+        target.display()
+    );
+    let missing = missing_deps(&args.project);
+    if !missing.is_empty() {
+        eprintln!(
+            "warning: Cargo.toml does not depend on {}; the helper module needs them",
+            missing.join(", ")
+        );
+    }
+    if let Some(module) = args.out.file_stem().and_then(|stem| stem.to_str()) {
+        println!("declare it with `mod {module};` in your crate root");
+    }
+    Ok(target)
+}
+
+pub fn check(args: &SdkCheckArgs) -> Result<()> {
+    let abi = resolve_abi(args.abi.as_deref(), &args.project)?;
+    let rendered = render(&abi)?;
+    let target = args.project.join(&args.out);
+    let existing = fs::read_to_string(&target).with_context(|| {
+        format!(
+            "read {}; run `greentic-component sdk gen` first",
+            target.display()
+        )
+    })?;
+    match generated_abi(&existing) {
+        None => bail!("{} was not generated by `sdk gen`", target.display()),
+        Some(found) if found != abi => bail!(
+            "{} targets component@{found} but the project uses {abi}; run `greentic-component sdk gen`",
+            target.display()
+        ),
+        Some(_) if normalize(&existing) != normalize(&rendered) => bail!(
+            "{} differs from the generated helpers for component@{abi}; run `greentic-component sdk gen --force`",
+            target.display()
+        ),
+        Some(_) => {
+            println!(
+                "{} matches the component@{abi} host helpers",
+                target.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// The ABI named by a generated module's marker line.
+fn generated_abi(text: &str) -> Option<&str> {
+    let (prefix, suffix) = MARKER.split_once("{abi}")?;
+    text.lines()
+        .next()?
+        .strip_prefix(prefix)?
+        .strip_suffix(suffix)
+}
+
+fn normalize(text: &str) -> String {
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
+
+fn missing_deps(project: &Path) -> Vec<&'static str> {
+    let Some(deps) = fs::read_to_string(project.join("Cargo.toml"))
+        .ok()
+        .and_then(|raw| toml::from_str::<toml::Value>(&raw).ok())
+        .and_then(|doc| doc.get("dependencies").cloned())
+    else {
+        return REQUIRED_DEPS.to_vec();
+    };
+    REQUIRED_DEPS
+        .iter()
+        .copied()
+        .filter(|dep| deps.get(*dep).is_none())
+        .collect()
+}
+
+const HOST_V0_6: &str = r####"// Regenerate with `greentic-component sdk gen --force` instead of editing.
+//
+// Typed wrappers over the component@{abi} host imports. Each call needs the
+// matching manifest capability (`host.state`, `host.secrets`,
+// `host.http.client`). Outside wasm the calls return
+// `HostError::Unavailable` and logs go to stderr.
+#![allow(dead_code)]
+
+use std::fmt;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Component ABI these helpers were generated for.
+pub const ABI_VERSION: &str = "{abi}";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostError {
+    /// The host refused or failed the call, e.g. `state.write.denied`.
+    Host { code: String, message: String },
+    /// A value did not encode to or decode from JSON.
+    Json(String),
+    /// Host imports only exist inside the wasm component.
+    Unavailable,
+}
+
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostError::Host { code, message } => write!(f, "{code}: {message}"),
+            HostError::Json(message) => write!(f, "invalid JSON: {message}"),
+            HostError::Unavailable => f.write_str("host imports are unavailable outside wasm"),
+        }
+    }
+}
+
+impl std::error::Error for HostError {}
+
+impl From<serde_json::Error> for HostError {
+    fn from(err: serde_json::Error) -> Self {
+        HostError::Json(err.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, HostError>;
+
+/// Key-value state scoped to the invocation's tenant.
+pub mod state {
+    use super::*;
+
+    /// `None` when the key was never written.
+    pub fn get_bytes(key: &str) -> Result<Option<Vec<u8>>> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            match greentic_interfaces_guest::state_store::read(key, None) {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(err) if err.code.ends_with(".miss") => Ok(None),
+                Err(err) => Err(HostError::Host {
+                    code: err.code,
+                    message: err.message,
+                }),
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = key;
+            Err(HostError::Unavailable)
+        }
+    }
+
+    pub fn get<T: DeserializeOwned>(key: &str) -> Result<Option<T>> {
+        match get_bytes(key)? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put_bytes(key: &str, bytes: &[u8]) -> Result<()> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            greentic_interfaces_guest::state_store::write(key, bytes, None)
+                .map(|_| ())
+                .map_err(|err| HostError::Host {
+                    code: err.code,
+                    message: err.message,
+                })
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = (key, bytes);
+            Err(HostError::Unavailable)
+        }
+    }
+
+    pub fn put<T: Serialize + ?Sized>(key: &str, value: &T) -> Result<()> {
+        put_bytes(key, &serde_json::to_vec(value)?)
+    }
+
+    pub fn delete(key: &str) -> Result<()> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            greentic_interfaces_guest::state_store::delete(key, None)
+                .map(|_| ())
+                .map_err(|err| HostError::Host {
+                    code: err.code,
+                    message: err.message,
+                })
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = key;
+            Err(HostError::Unavailable)
+        }
+    }
+}
+
+/// Secrets declared under `host.secrets.required`. Never log the values.
+pub mod secrets {
+    use super::*;
+
+    pub fn get(key: &str) -> Result<Option<Vec<u8>>> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            greentic_interfaces_guest::secrets_store::get(key).map_err(|err| HostError::Host {
+                code: "secrets.error".to_string(),
+                message: format!("{err:?}"),
+            })
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = key;
+            Err(HostError::Unavailable)
+        }
+    }
+
+    pub fn get_string(key: &str) -> Result<Option<String>> {
+        match get(key)? {
+            Some(bytes) => String::from_utf8(bytes)
+                .map(Some)
+                .map_err(|_| HostError::Host {
+                    code: "secrets.not_utf8".to_string(),
+                    message: format!("secret `{key}` is not UTF-8"),
+                }),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Outbound HTTP through the runner host (`host.http.client`).
+pub mod http {
+    use super::*;
+
+    /// Sends a request and returns the response body.
+    pub fn request(method: &str, url: &str, body: Option<&[u8]>) -> Result<Vec<u8>> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            greentic_interfaces_guest::runner_host::http_request(method, url, &[], body).map_err(
+                |err| HostError::Host {
+                    code: "http.error".to_string(),
+                    message: err.to_string(),
+                },
+            )
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = (method, url, body);
+            Err(HostError::Unavailable)
+        }
+    }
+
+    pub fn get(url: &str) -> Result<Vec<u8>> {
+        request("GET", url, None)
+    }
+
+    pub fn get_json<T: DeserializeOwned>(url: &str) -> Result<T> {
+        Ok(serde_json::from_slice(&get(url)?)?)
+    }
+
+    pub fn post_json<B: Serialize + ?Sized, T: DeserializeOwned>(url: &str, body: &B) -> Result<T> {
+        let body = serde_json::to_vec(body)?;
+        Ok(serde_json::from_slice(&request("POST", url, Some(&body))?)?)
+    }
+}
+
+/// Structured logs through the `greentic:component/log@{abi}` import; the
+/// host attaches the component and tenant.
+pub mod log {
+    #[cfg(target_arch = "wasm32")]
+    mod bindings {
+        wit_bindgen::generate!({
+            inline: r#"
+                package greentic:component@{abi};
+
+                interface log {
+                    enum level { trace, debug, info, warn, error }
+                    log: func(level: level, target: string, message: string, fields: list<tuple<string, string>>);
+                }
+
+                world host-log {
+                    import log;
+                }
+            "#,
+        });
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Level {
+        Trace,
+        Debug,
+        Info,
+        Warn,
+        Error,
+    }
+
+    pub fn log(level: Level, target: &str, message: &str, fields: &[(&str, &str)]) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            use bindings::greentic::component::log as host;
+
+            let level = match level {
+                Level::Trace => host::Level::Trace,
+                Level::Debug => host::Level::Debug,
+                Level::Info => host::Level::Info,
+                Level::Warn => host::Level::Warn,
+                Level::Error => host::Level::Error,
+            };
+            let fields = fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<Vec<_>>();
+            host::log(level, target, message, &fields);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = fields;
+            eprintln!("[{level:?}] {target}: {message}");
+        }
+    }
+
+    pub fn trace(message: &str) {
+        log(Level::Trace, module_path!(), message, &[]);
+    }
+
+    pub fn debug(message: &str) {
+        log(Level::Debug, module_path!(), message, &[]);
+    }
+
+    pub fn info(message: &str) {
+        log(Level::Info, module_path!(), message, &[]);
+    }
+
+    pub fn warn(message: &str) {
+        log(Level::Warn, module_path!(), message, &[]);
+    }
+
+    pub fn error(message: &str) {
+        log(Level::Error, module_path!(), message, &[]);
+    }
+}
+"####;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_module_tracks_the_project_abi() {
+        let project = tempfile::TempDir::new().unwrap();
+        let cargo = |abi: &str| {
+            fs::write(
+                project.path().join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[package.metadata.greentic]\nabi_version = \"{abi}\"\n\n[dependencies]\nserde = \"1\"\n"
+                ),
+            )
+            .unwrap();
+        };
+        cargo("0.6.0");
+        let gen_args = SdkGenArgs {
+            abi: None,
+            project: project.path().to_path_buf(),
+            out: "src/greentic_host.rs".into(),
+            force: false,
+        };
+        let check_args = SdkCheckArgs {
+            abi: None,
+            project: project.path().to_path_buf(),
+            out: "src/greentic_host.rs".into(),
+        };
+        let module = generate(&gen_args).unwrap();
+        let text = fs::read_to_string(&module).unwrap();
+        assert_eq!(generated_abi(&text), Some("0.6.0"));
+        assert!(text.contains("pub const ABI_VERSION: &str = \"0.6.0\";"));
+        assert!(text.contains("package greentic:component@0.6.0;"));
+        assert!(!text.contains("{abi}"));
+        check(&check_args).unwrap();
+        assert_eq!(
+            missing_deps(project.path()),
+            ["greentic-interfaces-guest", "serde_json", "wit-bindgen"]
+        );
+
+        fs::write(&module, format!("{text}\nfn local() {{}}\n")).unwrap();
+        assert!(check(&check_args).is_err());
+        assert!(generate(&gen_args).is_err());
+        generate(&SdkGenArgs {
+            force: true,
+            ..gen_args.clone()
+        })
+        .unwrap();
+
+        cargo("0.7.0");
+        assert!(render("0.7.0").is_err());
+        assert!(check(&check_args).is_err());
+    }
+}
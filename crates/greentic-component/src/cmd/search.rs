@@ -0,0 +1,315 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use greentic_component_runtime::{CATALOG_FILE, Catalog};
+use serde::Serialize;
+
+use super::wit::normalize_abi;
+use crate::store::{ComponentIndex, IndexEntry};
+
+#[derive(Args, Debug, Clone)]
+pub struct SearchArgs {
+    /// Text matched against component ids and names (case-insensitive);
+    /// omit to list everything
+    #[arg(value_name = "QUERY")]
+    pub query: Option<String>,
+    /// Only components declaring this capability or one beneath it
+    /// (`host.http`, `wasi.filesystem.mode=read_only`); repeatable
+    #[arg(long = "capability", value_name = "CAP")]
+    pub capabilities: Vec<String>,
+    /// Only components supporting this flow kind; repeatable
+    #[arg(long = "supports", value_name = "KIND")]
+    pub supports: Vec<String>,
+    /// Only components built against this ABI version (`0.6` or `0.6.0`)
+    #[arg(long, value_name = "VERSION")]
+    pub abi: Option<String>,
+    /// Extra registry index to search (path or http(s) URL); repeatable
+    #[arg(long = "index", value_name = "PATH|URL")]
+    pub indexes: Vec<String>,
+    /// Catalog listing components and registries (defaults to
+    /// ./components.toml when present)
+    #[arg(long, value_name = "PATH")]
+    pub catalog: Option<PathBuf>,
+    /// List every published version instead of the newest per component
+    #[arg(long)]
+    pub all_versions: bool,
+    /// Emit JSON instead of a table
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// One component version that matched.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchHit {
+    /// Registry name from the catalog, the index location for `--index`,
+    /// or `catalog` for a catalog entry.
+    pub registry: String,
+    pub id: String,
+    pub name: String,
+    /// Version, or the catalog's requirement for catalog entries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub abi: Option<String>,
+    pub supports: Vec<String>,
+    pub capabilities: Vec<String>,
+    pub locator: String,
+}
+
+pub fn run(args: SearchArgs) -> Result<()> {
+    let hits = search(&args)?;
+    if args.json {
+        let mut stdout = std::io::stdout();
+        serde_json::to_writer_pretty(&mut stdout, &hits)?;
+        stdout.write_all(b"\n").ok();
+    } else if hits.is_empty() {
+        println!("no components match");
+    } else {
+        print_table(&hits);
+    }
+    Ok(())
+}
+
+/// Searches every configured registry index and the catalog's own
+/// entries. A registry that cannot be read is reported on stderr and
+/// skipped, unless none could be read at all.
+pub fn search(args: &SearchArgs) -> Result<Vec<SearchHit>> {
+    let catalog = load_catalog(args.catalog.as_deref())?;
+    // Relative index paths in the catalog are relative to the catalog.
+    let catalog_dir = args
+        .catalog
+        .as_deref()
+        .and_then(Path::parent)
+        .unwrap_or_else(|| Path::new(""));
+    let mut registries: Vec<(String, String)> = catalog
+        .iter()
+        .flat_map(|catalog| catalog.registries())
+        .map(|(name, registry)| {
+            let index = if is_url(&registry.index) {
+                registry.index.clone()
+            } else {
+                catalog_dir.join(&registry.index).display().to_string()
+            };
+            (name.to_string(), index)
+        })
+        .collect();
+    registries.extend(
+        args.indexes
+            .iter()
+            .map(|index| (index.clone(), index.clone())),
+    );
+    if registries.is_empty() && catalog.is_none() {
+        bail!(
+            "nothing to search: add [registries.<name>] to {CATALOG_FILE} or pass --index <PATH|URL>"
+        );
+    }
+
+    let query = args.query.as_deref().map(str::to_lowercase);
+    let matches_query = |id: &str, name: &str| {
+        query.as_deref().is_none_or(|query| {
+            id.to_lowercase().contains(query) || name.to_lowercase().contains(query)
+        })
+    };
+    let abi = args.abi.as_deref().map(normalize_abi);
+
+    let mut hits = Vec::new();
+    let mut failures = 0;
+    for (registry, location) in &registries {
+        let index = match fetch_index(location) {
+            Ok(index) => index,
+            Err(err) => {
+                eprintln!("warning: skipping registry `{registry}`: {err:#}");
+                failures += 1;
+                continue;
+            }
+        };
+        let mut newest: BTreeMap<&str, &IndexEntry> = BTreeMap::new();
+        for entry in &index.components {
+            if !matches_query(&entry.id, &entry.name)
+                || !args
+                    .capabilities
+                    .iter()
+                    .all(|wanted| declares_capability(&entry.capabilities, wanted))
+                || !args
+                    .supports
+                    .iter()
+                    .all(|kind| entry.supports.contains(kind))
+                || abi.as_deref().is_some_and(|abi| {
+                    world_abi(&entry.world).map(normalize_abi).as_deref() != Some(abi)
+                })
+            {
+                continue;
+            }
+            if args.all_versions {
+                hits.push(index_hit(registry, location, entry));
+            } else {
+                // Entries are sorted by version, so the last one wins.
+                newest.insert(&entry.id, entry);
+            }
+        }
+        hits.extend(
+            newest
+                .into_values()
+                .map(|entry| index_hit(registry, location, entry)),
+        );
+    }
+    if failures > 0 && failures == registries.len() && catalog.is_none() {
+        bail!("no registry index could be read");
+    }
+
+    // Catalog entries carry no metadata, so they only match plain queries.
+    let filtered = !args.capabilities.is_empty() || !args.supports.is_empty() || abi.is_some();
+    if let Some(catalog) = &catalog
+        && !filtered
+    {
+        for name in catalog.names() {
+            let entry = catalog.get(name).expect("catalog name has an entry");
+            if matches_query(name, name) {
+                hits.push(SearchHit {
+                    registry: "catalog".into(),
+                    id: name.to_string(),
+                    name: name.to_string(),
+                    version: entry.version.clone(),
+                    abi: None,
+                    supports: Vec::new(),
+                    capabilities: Vec::new(),
+                    locator: entry.locator.clone(),
+                });
+            }
+        }
+    }
+
+    hits.sort_by(|a, b| a.id.cmp(&b.id).then_with(|| a.registry.cmp(&b.registry)));
+    Ok(hits)
+}
+
+fn load_catalog(path: Option<&Path>) -> Result<Option<Catalog>> {
+    match path {
+        Some(path) => Catalog::from_path(path)
+            .map(Some)
+            .with_context(|| format!("failed to load catalog {}", path.display())),
+        None => Catalog::discover(Path::new("."))
+            .with_context(|| format!("failed to load ./{CATALOG_FILE}")),
+    }
+}
+
+fn fetch_index(location: &str) -> Result<ComponentIndex> {
+    if is_url(location) {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        let bytes = client
+            .get(location)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.bytes())
+            .with_context(|| format!("fetch index {location}"))?;
+        ComponentIndex::from_slice(&bytes, location, None)
+    } else {
+        ComponentIndex::load(Path::new(location), None)
+    }
+}
+
+fn is_url(location: &str) -> bool {
+    location.starts_with("https://") || location.starts_with("http://")
+}
+
+fn index_hit(registry: &str, location: &str, entry: &IndexEntry) -> SearchHit {
+    SearchHit {
+        registry: registry.to_string(),
+        id: entry.id.clone(),
+        name: entry.name.clone(),
+        version: Some(entry.version.clone()),
+        abi: world_abi(&entry.world).map(str::to_string),
+        supports: entry.supports.clone(),
+        capabilities: entry.capabilities.clone(),
+        locator: locator(location, &entry.locator),
+    }
+}
+
+/// The entry's locator made absolute against where the index lives.
+fn locator(index_location: &str, locator: &str) -> String {
+    if locator.contains("://") {
+        return locator.to_string();
+    }
+    if is_url(index_location) {
+        let base = index_location
+            .rsplit_once('/')
+            .map_or(index_location, |(base, _)| base);
+        return format!("{base}/{locator}");
+    }
+    Path::new(index_location)
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(locator)
+        .display()
+        .to_string()
+}
+
+/// `greentic:component/component@0.6.0` -> `0.6.0`.
+fn world_abi(world: &str) -> Option<&str> {
+    world.rsplit_once('@').map(|(_, version)| version)
+}
+
+/// `wanted` names a declared capability or a parent of one.
+fn declares_capability(declared: &[String], wanted: &str) -> bool {
+    declared.iter().any(|capability| {
+        capability == wanted
+            || capability
+                .strip_prefix(wanted)
+                .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('='))
+    })
+}
+
+fn print_table(hits: &[SearchHit]) {
+    println!(
+        "{:<32} {:<10} {:<6} {:<24} REGISTRY",
+        "COMPONENT", "VERSION", "ABI", "SUPPORTS"
+    );
+    for hit in hits {
+        let supports = if hit.supports.is_empty() {
+            "-".to_string()
+        } else {
+            hit.supports.join(",")
+        };
+        println!(
+            "{:<32} {:<10} {:<6} {:<24} {}",
+            hit.id,
+            hit.version.as_deref().unwrap_or("-"),
+            hit.abi.as_deref().unwrap_or("-"),
+            supports,
+            hit.registry
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capability_filters_match_parents_and_values() {
+        let declared = vec![
+            "host.http.client".to_string(),
+            "wasi.filesystem.mode=read_only".to_string(),
+        ];
+        assert!(declares_capability(&declared, "host.http"));
+        assert!(declares_capability(&declared, "host.http.client"));
+        assert!(declares_capability(&declared, "wasi.filesystem.mode"));
+        assert!(!declares_capability(&declared, "host.ht"));
+        assert!(!declares_capability(&declared, "host.state"));
+
+        assert_eq!(
+            locator("https://acme.dev/registry/index.json", "echo/0.1.0/c.wasm"),
+            "https://acme.dev/registry/echo/0.1.0/c.wasm"
+        );
+        assert_eq!(
+            locator("index.json", "oci://ghcr.io/acme/echo:0.1.0"),
+            "oci://ghcr.io/acme/echo:0.1.0"
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{Args, Subcommand};
+use greentic_types::SecretKey;
+use serde_json::{Map, Value, json};
+
+use super::op::Source;
+use crate::config::resolve_manifest_path;
+use crate::manifest::{parse_manifest_value, resolve_schema_refs};
+
+/// Marker comments in wizard scaffolds that `secret add` inserts code above.
+const MARKER: &str = "// greentic-component secret add: ";
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SecretCommand {
+    /// Declare a secret requirement and wire it into the scaffold
+    Add(SecretAddArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SecretAddArgs {
+    /// Secret key, e.g. API_TOKEN
+    #[arg(value_name = "KEY")]
+    pub key: String,
+    /// Secret format recorded in the requirement
+    #[arg(long, default_value = "text")]
+    pub format: String,
+    /// Scope as `env,tenant[,team]`
+    #[arg(
+        long,
+        value_name = "ENV,TENANT[,TEAM]",
+        value_delimiter = ',',
+        default_value = "dev,default"
+    )]
+    pub scope: Vec<String>,
+    /// Mark the secret as optional
+    #[arg(long)]
+    pub optional: bool,
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+    /// Example secrets file to add the key to, relative to the manifest
+    #[arg(long, value_name = "PATH", default_value = "examples/secrets.env")]
+    pub secrets_file: PathBuf,
+}
+
+/// Files touched by `secret add`.
+#[derive(Debug, Default)]
+pub struct SecretAddReport {
+    pub updated: Vec<PathBuf>,
+    /// Scaffold files left alone because they lack the insertion markers.
+    pub skipped: Vec<PathBuf>,
+}
+
+pub fn run(command: SecretCommand) -> Result<()> {
+    match command {
+        SecretCommand::Add(args) => {
+            let report = add(&args)?;
+            for path in &report.updated {
+                println!("updated {}", path.display());
+            }
+            for path in &report.skipped {
+                eprintln!(
+                    "warning: {} has no `{MARKER}` marker; add the QA question by hand",
+                    path.display()
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn add(args: &SecretAddArgs) -> Result<SecretAddReport> {
+    let key = SecretKey::new(args.key.trim())
+        .map_err(|err| anyhow!("invalid secret key `{}`: {err}", args.key))?;
+    let key = key.as_str().to_string();
+    let requirement = requirement(&key, args)?;
+
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let root = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
+    let raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let mut manifest: Value = serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    let object = manifest
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("{} must contain a JSON object", manifest_path.display()))?;
+    let declared = array_entry(object, "secret_requirements")?;
+    if declared
+        .iter()
+        .any(|entry| entry.get("key").and_then(Value::as_str) == Some(key.as_str()))
+    {
+        bail!(
+            "secret `{key}` is already declared in {}",
+            manifest_path.display()
+        );
+    }
+    declared.push(requirement.clone());
+    let host_secrets = ["capabilities", "host", "secrets"]
+        .into_iter()
+        .try_fold(&mut *object, |parent, name| object_entry(parent, name))?;
+    let required = array_entry(host_secrets, "required")?;
+    if !required
+        .iter()
+        .any(|entry| entry.get("key").and_then(Value::as_str) == Some(key.as_str()))
+    {
+        required.push(requirement);
+    }
+    let mut check = manifest.clone();
+    resolve_schema_refs(&mut check, &root).context("resolve manifest schema references")?;
+    parse_manifest_value(check).context("manifest with the new secret failed validation")?;
+
+    let mut report = SecretAddReport::default();
+    let id = key.to_ascii_lowercase();
+    let label_key = format!("qa.setup.secret.{id}.label");
+    let help_key = format!("qa.setup.secret.{id}.help");
+    let qa_rs = Source::load(root.join("src/qa.rs"), MARKER).ok();
+    let i18n_rs = Source::load(root.join("src/i18n.rs"), MARKER).ok();
+    let bundle_path = root.join("assets/i18n/en.json");
+    let wired = matches!(
+        (&qa_rs, &i18n_rs),
+        (Some(qa), Some(i18n)) if qa.has_marker("questions") && i18n.has_marker("keys")
+    );
+    let mut bundle = None;
+    if wired {
+        let raw = fs::read_to_string(&bundle_path)
+            .with_context(|| format!("read {}", bundle_path.display()))?;
+        let mut entries: Map<String, Value> = serde_json::from_str(&raw)
+            .with_context(|| format!("{} must be a JSON object", bundle_path.display()))?;
+        entries.insert(label_key.clone(), Value::String(key.clone()));
+        entries.insert(
+            help_key.clone(),
+            Value::String(format!("Value for the {} secret `{key}`.", args.format)),
+        );
+        bundle = Some(entries);
+    } else {
+        report.skipped.push(root.join("src/qa.rs"));
+    }
+
+    fs::write(
+        &manifest_path,
+        serde_json::to_string_pretty(&manifest)? + "\n",
+    )
+    .with_context(|| format!("failed to write {}", manifest_path.display()))?;
+    report.updated.push(manifest_path);
+
+    if let (Some(mut qa), Some(mut i18n), Some(entries)) = (qa_rs, i18n_rs, bundle) {
+        qa.insert(
+            "questions",
+            &[format!(
+                "question_secret({}, {}, {}),",
+                quoted(&id),
+                quoted(&label_key),
+                quoted(&help_key)
+            )],
+        );
+        if !qa.text.contains("fn question_secret(") {
+            qa.append(QUESTION_SECRET_FN);
+        }
+        i18n.insert("keys", &[format!("{},", quoted(&label_key))]);
+        i18n.insert("keys", &[format!("{},", quoted(&help_key))]);
+        report.updated.push(qa.save()?);
+        report.updated.push(i18n.save()?);
+        fs::write(&bundle_path, serde_json::to_string_pretty(&entries)? + "\n")
+            .with_context(|| format!("failed to write {}", bundle_path.display()))?;
+        report.updated.push(bundle_path);
+    }
+
+    let secrets_path = root.join(&args.secrets_file);
+    if add_example_secret(&secrets_path, &key)? {
+        report.updated.push(secrets_path);
+    }
+    Ok(report)
+}
+
+fn requirement(key: &str, args: &SecretAddArgs) -> Result<Value> {
+    let scope = args
+        .scope
+        .iter()
+        .map(|part| part.trim())
+        .collect::<Vec<_>>();
+    let mut rendered = match scope.as_slice() {
+        [env, tenant] | [env, tenant, _] => json!({ "env": env, "tenant": tenant }),
+        _ => bail!("--scope must be `env,tenant` or `env,tenant,team`"),
+    };
+    if let [_, _, team] = scope.as_slice() {
+        rendered["team"] = json!(team);
+    }
+    Ok(json!({
+        "key": key,
+        "required": !args.optional,
+        "scope": rendered,
+        "format": args.format.trim(),
+    }))
+}
+
+fn object_entry<'a>(
+    parent: &'a mut Map<String, Value>,
+    name: &str,
+) -> Result<&'a mut Map<String, Value>> {
+    parent
+        .entry(name)
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("`{name}` must be a JSON object"))
+}
+
+fn array_entry<'a>(parent: &'a mut Map<String, Value>, name: &str) -> Result<&'a mut Vec<Value>> {
+    parent
+        .entry(name)
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or_else(|| anyhow!("`{name}` must be a JSON array"))
+}
+
+/// Adds a `KEY=` placeholder unless the file already mentions the key.
+fn add_example_secret(path: &Path, key: &str) -> Result<bool> {
+    let existing = match fs::read_to_string(path) {
+        Ok(existing) => existing,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            "# Example secrets for `greentic-component test --secrets`. Do not commit real values.\n"
+                .to_string()
+        }
+        Err(err) => return Err(err).with_context(|| format!("read {}", path.display())),
+    };
+    let prefix = format!("{key}=");
+    if existing
+        .lines()
+        .any(|line| line.trim_start().starts_with(&prefix))
+    {
+        return Ok(false);
+    }
+    let mut contents = existing;
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str(&prefix);
+    contents.push('\n');
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(true)
+}
+
+fn quoted(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+const QUESTION_SECRET_FN: &str = r#"fn question_secret(id: &str, label_key: &str, help_key: &str) -> Question {
+    Question {
+        id: id.to_string(),
+        label: I18nText::new(label_key, None),
+        help: Some(I18nText::new(help_key, None)),
+        error: None,
+        kind: QuestionKind::Text,
+        required: true,
+        default: None,
+    }
+}
+"#;
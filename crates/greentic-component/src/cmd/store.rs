@@ -1,10 +1,12 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result, anyhow, bail};
 use clap::{Args, Subcommand};
+use greentic_component_runtime::{CATALOG_FILE, Catalog, CatalogSignature};
 use serde_json::Value;
 
+use super::progress;
 use crate::path_safety::normalize_under_root;
 use greentic_distributor_client::{DistClient, DistOptions};
 
@@ -22,7 +24,12 @@ pub struct StoreFetchArgs {
     /// Optional cache directory for fetched components
     #[arg(long, value_name = "DIR")]
     pub cache_dir: Option<PathBuf>,
+    /// Component catalog mapping names to sources (defaults to
+    /// ./components.toml when present)
+    #[arg(long, value_name = "PATH")]
+    pub catalog: Option<PathBuf>,
     /// Source reference to resolve (file://, oci://, repo://, store://, etc.)
+    /// or a component name from the catalog
     #[arg(value_name = "SOURCE")]
     pub source: String,
 }
@@ -34,16 +41,45 @@ pub fn run(command: StoreCommand) -> Result<()> {
 }
 
 fn fetch(args: StoreFetchArgs) -> Result<()> {
-    let source = resolve_source(&args.source)?;
+    let catalog = load_catalog(args.catalog.as_deref())?;
+    let entry = catalog
+        .as_ref()
+        .and_then(|catalog| catalog.lookup(&args.source));
+    if let Some(entry) = entry
+        && entry.signature == CatalogSignature::Required
+    {
+        bail!(
+            "catalog entry `{}` requires a signature, which store fetch cannot verify",
+            args.source
+        );
+    }
+    let source = resolve_source(entry.map_or(args.source.as_str(), |entry| &entry.locator))?;
     let mut opts = DistOptions::default();
     if let Some(cache_dir) = &args.cache_dir {
         opts.cache_dir = cache_dir.clone();
     }
     let client = DistClient::new(opts);
-    let rt = tokio::runtime::Runtime::new().context("failed to create async runtime")?;
-    let resolved = rt
-        .block_on(async { client.ensure_cached(&source).await })
-        .context("store fetch failed")?;
+    let resolved = progress::phase("store.fetch", "download", || {
+        let rt = tokio::runtime::Runtime::new().context("failed to create async runtime")?;
+        rt.block_on(async { client.ensure_cached(&source).await })
+            .context("store fetch failed")
+    })?;
+    if let Some(expected) = entry.and_then(|entry| entry.digest_hex()) {
+        progress::phase("store.fetch", "verify", || {
+            let actual = resolved
+                .digest
+                .strip_prefix("sha256:")
+                .unwrap_or(&resolved.digest);
+            if !actual.eq_ignore_ascii_case(expected) {
+                bail!(
+                    "digest mismatch for `{}`: catalog pins sha256:{expected}, fetched {}",
+                    args.source,
+                    resolved.digest
+                );
+            }
+            Ok(())
+        })?;
+    }
     let cache_path = resolved
         .cache_path
         .ok_or_else(|| anyhow!("resolved source has no cached component path"))?;
@@ -92,12 +128,14 @@ fn fetch(args: StoreFetchArgs) -> Result<()> {
             }
         }
     }
-    fs::copy(&cache_path, &wasm_out_path).with_context(|| {
-        format!(
-            "failed to copy cached component {} to {}",
-            cache_path.display(),
-            wasm_out_path.display()
-        )
+    progress::phase("store.fetch", "write", || {
+        fs::copy(&cache_path, &wasm_out_path).with_context(|| {
+            format!(
+                "failed to copy cached component {} to {}",
+                cache_path.display(),
+                wasm_out_path.display()
+            )
+        })
     })?;
     println!(
         "Wrote {} (digest {}) for source {}",
@@ -111,6 +149,16 @@ fn fetch(args: StoreFetchArgs) -> Result<()> {
     Ok(())
 }
 
+fn load_catalog(path: Option<&Path>) -> Result<Option<Catalog>> {
+    match path {
+        Some(path) => Catalog::from_path(path)
+            .map(Some)
+            .with_context(|| format!("failed to load catalog {}", path.display())),
+        None => Catalog::discover(Path::new("."))
+            .with_context(|| format!("failed to load ./{CATALOG_FILE}")),
+    }
+}
+
 fn resolve_source(source: &str) -> Result<String> {
     let (prefix, path_str) = if let Some(rest) = source.strip_prefix("file://") {
         ("file://", rest)
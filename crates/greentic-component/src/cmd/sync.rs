@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use clap::Args;
+use serde_json::Value;
+
+use super::inspect::load_describe;
+use crate::config::resolve_manifest_path;
+use crate::manifest::{parse_manifest_value, resolve_schema_refs};
+use crate::manifest_sync::{SyncReport, apply_describe, check_manifest_sync};
+
+#[derive(Args, Debug, Clone)]
+pub struct SyncArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+    /// Component wasm or describe CBOR (defaults to the manifest's wasm artifact)
+    #[arg(long, value_name = "PATH")]
+    pub wasm: Option<PathBuf>,
+    /// Rewrite operations and config_schema from describe()
+    #[arg(long)]
+    pub write: bool,
+    /// Emit the drift report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(args: &SyncArgs) -> Result<SyncReport> {
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let mut manifest: Value = serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    let manifest_dir = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let mut resolved = manifest.clone();
+    resolve_schema_refs(&mut resolved, manifest_dir)
+        .context("resolve manifest schema references")?;
+
+    let describe_path = match &args.wasm {
+        Some(path) => path.clone(),
+        None => parse_manifest_value(resolved.clone())
+            .context("parse manifest")?
+            .wasm_artifact_path(manifest_dir),
+    };
+    let describe = load_describe(&describe_path).map_err(|err| anyhow!(err))?;
+    let report = check_manifest_sync(&resolved, &describe);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for drift in &report.drift {
+            eprintln!("drift: {}: {}", drift.path, drift.message);
+        }
+    }
+    if report.in_sync() {
+        if !args.json {
+            println!("{} matches describe()", manifest_path.display());
+        }
+        return Ok(report);
+    }
+    if !args.write {
+        bail!(
+            "{} drift(s) between {} and describe(); rerun with --write to update the manifest",
+            report.drift.len(),
+            manifest_path.display()
+        );
+    }
+
+    let changes = apply_describe(&mut manifest, &resolved, &describe);
+    let mut check = manifest.clone();
+    resolve_schema_refs(&mut check, manifest_dir).context("resolve manifest schema references")?;
+    parse_manifest_value(check).context("synchronized manifest failed validation")?;
+    let formatted = serde_json::to_string_pretty(&manifest)?;
+    fs::write(&manifest_path, formatted + "\n")
+        .with_context(|| format!("failed to write {}", manifest_path.display()))?;
+    if !args.json {
+        println!(
+            "Updated {} from describe() ({changes} change(s))",
+            manifest_path.display()
+        );
+    }
+    Ok(report)
+}
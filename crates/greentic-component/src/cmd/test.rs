@@ -1,6 +1,7 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 use anyhow::{Context, Result, bail};
@@ -10,24 +11,44 @@ use blake3::Hasher;
 use clap::{ArgAction, Args, ValueEnum};
 use serde::Serialize;
 use serde_json::{Map, Value};
+use tracing::level_filters::LevelFilter;
 use uuid::Uuid;
 
 use super::component_world::canonical_component_world;
-use crate::capabilities::FilesystemMode;
+use super::exit::ExitCode;
+use crate::capabilities::{Capabilities, FilesystemMode};
 use crate::manifest::ComponentManifest;
-use crate::manifest::parse_manifest;
+use crate::manifest::{apply_env_overlay, parse_manifest, resolve_schema_refs};
 use crate::test_harness::{
-    ComponentInvokeError, HarnessConfig, HarnessError, InvokeOutcome, TestHarness, WasiPreopen,
+    AuditEntry, AuditKind, AuditLog, ChaosConfig, ComponentInvokeError, ConcurrencyReport,
+    DEFAULT_MAX_OUTPUT_BYTES, EventRecorder, ExpectedStatus, FsQuotaExceeded, FsSandbox, GuestTrap,
+    HarnessConfig, HarnessError, InvokeOutcome, InvokeOverrides, IsolationProfile, StateDumpEntry,
+    StateDumpFilter, StepExpect, StepPlan, TestHarness, TestHarnessBuilder, TranscriptMessaging,
+    WasiRandom, blob_permission, events_host, messaging_host, progress_permission,
+    secret_permissions, sql_access, state_permissions, telemetry_host,
+};
+use greentic_component_runtime::{
+    GuestLogRecord, GuestLogSink, GuestLogger, PoolingConfig, Progress, ProgressCallback,
+    TelemetryRecord,
 };
 use greentic_types::{EnvId, TeamId, TenantCtx, TenantId, UserId};
 
-const MAX_OUTPUT_BYTES: usize = 2 * 1024 * 1024;
+const REDACTED: &str = "***REDACTED***";
 
 #[derive(Clone, Debug, ValueEnum)]
 pub enum StateMode {
     Inmem,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum RandomMode {
+    /// Fixed byte streams, identical on every run.
+    #[default]
+    Fixed,
+    /// The host's real randomness (soak tests).
+    Real,
+}
+
 #[derive(Args, Debug)]
 pub struct TestArgs {
     /// Path to the component wasm binary.
@@ -51,9 +72,13 @@ pub struct TestArgs {
     /// Write output JSON to a file.
     #[arg(long, value_name = "PATH")]
     pub output: Option<PathBuf>,
-    /// Optional component configuration JSON (file path or inline JSON).
-    #[arg(long, value_name = "PATH|JSON")]
+    /// Component configuration JSON (file path or inline JSON). Without it
+    /// or `--config-json`, the defaults from `config_schema` are used.
+    #[arg(long, value_name = "PATH|JSON", conflicts_with = "config_json")]
     pub config: Option<String>,
+    /// Inline component configuration JSON.
+    #[arg(long, value_name = "JSON")]
+    pub config_json: Option<String>,
     /// Write trace JSON output (overrides GREENTIC_TRACE_OUT).
     #[arg(long, value_name = "PATH")]
     pub trace_out: Option<PathBuf>,
@@ -69,27 +94,110 @@ pub struct TestArgs {
     /// Allow HTTP requests (ignored in dry-run).
     #[arg(long)]
     pub allow_http: bool,
+    /// Allow raw sockets to the hosts in `capabilities.net` (ignored in dry-run).
+    #[arg(long)]
+    pub allow_net: bool,
     /// Allow filesystem writes (ignored in dry-run).
     #[arg(long)]
     pub allow_fs_write: bool,
+    /// Back a manifest mount with a host directory as NAME=PATH (repeatable).
+    /// Unmapped mounts get an empty temp directory.
+    #[arg(long = "mount", value_name = "NAME=PATH")]
+    pub mount: Vec<String>,
+    /// Cap on bytes written to writable mounts (defaults to
+    /// limits.fs_quota_bytes).
+    #[arg(long, value_name = "MB")]
+    pub fs_quota_mb: Option<u64>,
+    /// Isolation profile selecting engine settings and host defaults.
+    #[arg(long, value_enum, default_value = "balanced")]
+    pub isolation: IsolationProfile,
     /// Timeout in milliseconds.
     #[arg(long, default_value_t = 2000, value_name = "MS")]
     pub timeout_ms: u64,
+    /// Most verbose guest `log` level to print (`off`, `error`, `warn`,
+    /// `info`, `debug`, `trace`).
+    #[arg(long, default_value = "info", value_name = "LEVEL")]
+    pub guest_log_level: LevelFilter,
     /// Max memory in megabytes.
     #[arg(long, default_value_t = 256, value_name = "MB")]
     pub max_memory_mb: u64,
+    /// Largest invoke output accepted (defaults to the manifest's
+    /// `limits.max_output_bytes`, else 2 MiB).
+    #[arg(long, value_name = "BYTES")]
+    pub max_output_bytes: Option<usize>,
     /// State backend (only inmem is supported).
     #[arg(long, value_enum, default_value = "inmem")]
     pub state: StateMode,
     /// Dump in-memory state after invocation.
     #[arg(long)]
     pub state_dump: bool,
+    /// Restrict --state-dump to one scope, as TENANT[/TEAM[/USER]].
+    #[arg(
+        long,
+        value_name = "TENANT[/TEAM[/USER]]",
+        value_parser = parse_state_dump_scope,
+        requires = "state_dump"
+    )]
+    pub state_dump_scope: Option<StateDumpFilter>,
     /// Seed in-memory state as KEY=BASE64 (repeatable).
     #[arg(long = "state-set", value_name = "KEY=BASE64")]
     pub state_set: Vec<String>,
+    /// Keep a step's state writes and deletes only if the step succeeds;
+    /// a trap or component error rolls them back.
+    #[arg(long)]
+    pub state_transactional: bool,
+    /// Run a SQL script against an in-memory SQLite connection before
+    /// invoking, as CONN=PATH (repeatable).
+    #[arg(long = "sql-init", value_name = "CONN=PATH")]
+    pub sql_init: Vec<String>,
+    /// Append every message the component sends or acks to this file as
+    /// JSON lines.
+    #[arg(long = "messaging-transcript", value_name = "PATH")]
+    pub messaging_transcript: Option<PathBuf>,
+    /// Write the events the component emitted to this file (an
+    /// `events.json` transcript).
+    #[arg(long = "events-out", value_name = "PATH")]
+    pub events_out: Option<PathBuf>,
+    /// Print the span events and attributes the component recorded.
+    #[arg(long)]
+    pub show_telemetry: bool,
+    /// Inject seeded latency, failures, truncated responses, and reordered
+    /// state writes into host imports, as configured in this YAML file.
+    #[arg(long, value_name = "PATH")]
+    pub chaos: Option<PathBuf>,
     /// Repeatable step marker for multi-step runs.
     #[arg(long, action = ArgAction::Count)]
     pub step: u8,
+    /// Step plan (YAML or JSON) listing each step's op, input, config,
+    /// secrets, and expected outcome; replaces --op/--input/--step.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["op", "input", "input_json", "step"])]
+    pub plan: Option<PathBuf>,
+    /// Invoke the operation from this many threads at once and report
+    /// failures, state conflicts and latency instead of the output.
+    #[arg(long, default_value_t = 1, value_name = "N", conflicts_with_all = ["plan", "step"])]
+    pub concurrency: usize,
+    /// Total invocations to spread over --concurrency threads.
+    #[arg(long, default_value_t = 1, value_name = "N", conflicts_with_all = ["plan", "step"])]
+    pub repeat: usize,
+    /// Replace the config for one step as IDX=PATH (1-based, repeatable).
+    #[arg(long = "step-config", value_name = "IDX=PATH")]
+    pub step_config: Vec<String>,
+    /// Add a secret for one step as IDX=KEY=VALUE (1-based, repeatable).
+    #[arg(long = "step-secret", value_name = "IDX=KEY=VALUE")]
+    pub step_secret: Vec<String>,
+    /// Expected output JSON; the (last) step's output must equal it.
+    #[arg(long, value_name = "PATH")]
+    pub expect_output: Option<PathBuf>,
+    /// Match --expect-output as a subset: objects may have extra keys and
+    /// keys starting with `/` are JSON pointers into the output.
+    #[arg(long, requires = "expect_output")]
+    pub expect_subset: bool,
+    /// Error code the (last) step must fail with.
+    #[arg(long, value_name = "CODE", conflicts_with = "expect_output")]
+    pub expect_error_code: Option<String>,
+    /// Whether the (last) step must succeed or fail.
+    #[arg(long, value_enum, value_name = "STATUS")]
+    pub expect_status: Option<ExpectedStatus>,
     /// Load secrets from a .env style file.
     #[arg(long, value_name = "PATH")]
     pub secrets: Option<PathBuf>,
@@ -99,7 +207,15 @@ pub struct TestArgs {
     /// Provide a secret inline as KEY=VALUE (repeatable).
     #[arg(long = "secret", value_name = "KEY=VALUE")]
     pub secret: Vec<String>,
-    /// Environment identifier for the exec context.
+    /// Set an environment variable for the component as KEY=VALUE (repeatable).
+    /// KEY must be listed in `capabilities.wasi.env.allow`.
+    #[arg(long = "env-var", value_name = "KEY=VALUE")]
+    pub env_var: Vec<String>,
+    /// Pass allow-listed variables through from the host environment.
+    #[arg(long)]
+    pub inherit_env: bool,
+    /// Environment identifier for the exec context; also selects
+    /// component.manifest.override.<env>.json when present.
     #[arg(long, default_value = "dev")]
     pub env: String,
     /// Tenant identifier for the exec context.
@@ -123,11 +239,38 @@ pub struct TestArgs {
     /// Emit extra diagnostic output (e.g. generated session id).
     #[arg(long)]
     pub verbose: bool,
+    /// Write a JSON audit log of every host-import call the component made.
+    #[arg(long, value_name = "PATH")]
+    pub audit_out: Option<PathBuf>,
+    /// Print outputs, traces, audit logs and state dumps without scrubbing
+    /// secrets (local debugging only).
+    #[arg(long)]
+    pub no_redact: bool,
+    /// Derive all WASI randomness from this seed; it is recorded in the
+    /// trace so a failing run can be replayed.
+    #[arg(long, value_name = "U64", conflicts_with = "random")]
+    pub random_seed: Option<u64>,
+    /// Source of WASI randomness when no --random-seed is given.
+    #[arg(long, value_enum, default_value = "fixed")]
+    pub random: RandomMode,
+    /// Manifest profile to run under; must be in `profiles.supported`
+    /// (defaults to `profiles.default`).
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+    /// Run everything once per profile in `profiles.supported`, stopping at
+    /// the first profile that fails. Traces get a `.<profile>` suffix.
+    #[arg(long, conflicts_with_all = ["profile", "output"])]
+    pub all_profiles: bool,
 }
 
 pub fn run(args: TestArgs) -> Result<()> {
     let trace_out = resolve_trace_out(&args)?;
-    match run_inner(&args, trace_out.as_deref()) {
+    let result = if args.all_profiles {
+        run_profiles(&args, trace_out.as_deref())
+    } else {
+        run_inner(&args, trace_out.as_deref(), args.profile.as_deref())
+    };
+    match result {
         Ok(()) => Ok(()),
         Err(err) => Err(TestCommandError::from_anyhow(
             err,
@@ -140,29 +283,73 @@ pub fn run(args: TestArgs) -> Result<()> {
     }
 }
 
-fn run_inner(args: &TestArgs, trace_out: Option<&Path>) -> Result<()> {
-    if args.world != canonical_component_world() {
-        return Err(anyhow::Error::new(UnsupportedWorldError {
-            world: args.world.clone(),
-        }));
+/// The matrix mode of `--all-profiles`.
+fn run_profiles(args: &TestArgs, trace_out: Option<&Path>) -> Result<()> {
+    let (_, manifest) = load_manifest(args)?;
+    if manifest.profiles.supported.is_empty() {
+        bail!("--all-profiles needs at least one entry in profiles.supported");
+    }
+    for profile in &manifest.profiles.supported {
+        eprintln!("profile: {profile}");
+        let trace_out = trace_out.map(|path| profile_trace_path(path, profile));
+        if let Err(err) = run_inner(args, trace_out.as_deref(), Some(profile)) {
+            eprintln!("profile {profile} failed");
+            return Err(err);
+        }
     }
+    Ok(())
+}
 
+/// Reads the manifest next to the wasm (or `--manifest`), resolving schema
+/// references and applying the `--env` overlay.
+fn load_manifest(args: &TestArgs) -> Result<(Value, ComponentManifest)> {
     let manifest_path = resolve_manifest_path(&args.wasm, args.manifest.as_deref())?;
     let manifest_raw = fs::read_to_string(&manifest_path)
         .with_context(|| format!("read manifest {}", manifest_path.display()))?;
-    let manifest_value: Value =
+    let mut manifest_value: Value =
         serde_json::from_str(&manifest_raw).context("manifest must be valid JSON")?;
-    let manifest = parse_manifest(&manifest_raw).context("parse manifest")?;
+    let manifest_dir = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    resolve_schema_refs(&mut manifest_value, manifest_dir)
+        .context("resolve manifest schema references")?;
+    apply_env_overlay(&mut manifest_value, &manifest_path, &args.env)
+        .context("apply manifest overlay")?;
+    let manifest =
+        parse_manifest(&serde_json::to_string(&manifest_value)?).context("parse manifest")?;
+    Ok((manifest_value, manifest))
+}
+
+fn run_inner(args: &TestArgs, trace_out: Option<&Path>, profile: Option<&str>) -> Result<()> {
+    if args.world != canonical_component_world() {
+        return Err(anyhow::Error::new(UnsupportedWorldError {
+            world: args.world.clone(),
+        }));
+    }
+
+    let (manifest_value, manifest) = load_manifest(args)?;
+    let profile = select_profile(&manifest, profile)?;
 
     let steps = collect_steps(args)?;
     let mut trace = TraceContext::new(trace_out, &manifest, &steps);
+    let random = wasi_random(args);
+    trace.random = random;
+    trace.profile = profile.clone();
     let start = Instant::now();
 
     let mut timing = TimingMs::default();
     let mut secret_values: Vec<String> = Vec::new();
+    let audit = AuditLog::default();
+    let redaction_pointers = if args.no_redact {
+        HashMap::new()
+    } else {
+        describe_redactions(&args.wasm)
+    };
 
+    let mut current_op: Option<&str> = None;
     let result = (|| -> Result<Vec<String>> {
-        for (op, _) in &steps {
+        for Step { op, .. } in &steps {
             if !manifest
                 .operations
                 .iter()
@@ -170,6 +357,9 @@ fn run_inner(args: &TestArgs, trace_out: Option<&Path>) -> Result<()> {
             {
                 bail!("operation `{op}` not declared in manifest");
             }
+            if let Some(deprecated) = manifest.operation_deprecation(op) {
+                eprintln!("warning: operation {}", deprecated.notice(op));
+            }
         }
         let wasm_bytes =
             fs::read(&args.wasm).with_context(|| format!("read wasm {}", args.wasm.display()))?;
@@ -182,30 +372,105 @@ fn run_inner(args: &TestArgs, trace_out: Option<&Path>) -> Result<()> {
         let (allow_state_read, allow_state_write, allow_state_delete) =
             state_permissions(&manifest_value, &manifest);
         if !args.state_set.is_empty() && !allow_state_write {
-            bail!("manifest does not declare host.state.write; add it to use --state-set");
+            return Err(CapabilityDeniedError::new(
+                "host.state.write",
+                "manifest does not declare host.state.write; add it to use --state-set",
+            )
+            .into());
         }
         let (allow_secrets, allowed_secrets) = secret_permissions(&manifest);
 
         let secrets = load_secrets(args)?;
-        if !allow_secrets && !secrets.is_empty() {
-            bail!(
-                "manifest does not declare host.secrets; add host.secrets to enable secrets access"
-            );
+        let step_secrets = steps.iter().flat_map(|step| &step.overrides.secrets);
+        if !allow_secrets && (!secrets.is_empty() || step_secrets.clone().next().is_some()) {
+            return Err(CapabilityDeniedError::new(
+                "host.secrets",
+                "manifest does not declare host.secrets; add host.secrets to enable secrets access",
+            )
+            .into());
+        }
+        if !args.no_redact {
+            secret_values = secrets
+                .values()
+                .chain(step_secrets.map(|(_, value)| value))
+                .filter(|value| !value.is_empty())
+                .cloned()
+                .collect();
         }
-        secret_values = secrets
-            .values()
-            .filter(|value| !value.is_empty())
-            .cloned()
-            .collect();
 
-        let config = load_config(args)?;
+        let config = resolve_config(args, &manifest_value)?;
+        if let Some(schema) = manifest_value.get("config_schema") {
+            for step in &steps {
+                if let Some(config) = &step.overrides.config {
+                    check_config(schema, config, ConfigOrigin::Provided)
+                        .with_context(|| format!("step {} ({}) config", step.index, step.op))?;
+                }
+            }
+        }
+        let env_vars = resolve_env_vars(&manifest, &args.env_var, args.inherit_env)?;
         let state_seeds = parse_state_seeds(args)?;
-        let allow_http = args.allow_http && !args.dry_run;
-        let allow_fs_write = args.allow_fs_write && !args.dry_run;
+        let isolation = args.isolation;
+        let allow_http = isolation.grants(args.allow_http) && !args.dry_run;
+        let allow_fs_write = isolation.grants(args.allow_fs_write) && !args.dry_run;
+        let allow_net = isolation.grants(args.allow_net) && !args.dry_run;
         let max_memory_bytes = parse_max_memory_bytes(args.max_memory_mb)?;
-        let wasi_preopens = resolve_wasi_preopens(&manifest, allow_fs_write, args.dry_run)?;
+        let quota_bytes = args
+            .fs_quota_mb
+            .map(|mb| mb.saturating_mul(1024 * 1024))
+            .or_else(|| {
+                manifest
+                    .limits
+                    .as_ref()
+                    .and_then(|limits| limits.fs_quota_bytes)
+            });
+        let sandbox = resolve_fs_sandbox(
+            &manifest,
+            &args.mount,
+            allow_fs_write,
+            args.dry_run,
+            quota_bytes,
+        )?;
         let prefix = state_prefix(args.flow.as_deref(), &session_id);
         let flow_id = args.flow.clone().unwrap_or_else(|| "test".to_string());
+        let on_progress = print_progress(secret_values.clone());
+        if !args.sql_init.is_empty() && manifest_value.pointer("/capabilities/host/sql").is_none() {
+            return Err(CapabilityDeniedError::new(
+                "host.sql",
+                "manifest does not declare host.sql; add it to use --sql-init",
+            )
+            .into());
+        }
+        let sql = sql_access(&manifest_value, &tenant_ctx, &args.sql_init)?;
+        let mut transcript = TranscriptMessaging::new();
+        if let Some(path) = &args.messaging_transcript {
+            transcript = transcript.with_file(path);
+        }
+        let transcript = Arc::new(transcript);
+        let messaging = messaging_host(&manifest, &tenant_ctx, transcript.clone());
+        let recorder = Arc::new(EventRecorder::new());
+        let events = events_host(&manifest, &tenant_ctx, recorder.clone());
+        let captured_telemetry = Arc::new(Mutex::new(Vec::new()));
+        let mut telemetry = telemetry_host(&manifest, &tenant_ctx);
+        if args.show_telemetry {
+            let captured = captured_telemetry.clone();
+            telemetry = telemetry.with_sink(Arc::new(move |record: &TelemetryRecord| {
+                captured
+                    .lock()
+                    .expect("telemetry mutex poisoned")
+                    .push(record.clone())
+            }));
+        }
+        let chaos = args
+            .chaos
+            .as_deref()
+            .map(ChaosConfig::from_path)
+            .transpose()?;
+        let guest_log = GuestLogger::new(
+            manifest.id.as_str(),
+            Some(&tenant_ctx),
+            args.guest_log_level,
+        )
+        .with_sink(print_guest_log(secret_values.clone(), trace.log_buffer()));
         let harness = TestHarness::new(HarnessConfig {
             wasm_bytes,
             tenant_ctx: tenant_ctx.clone(),
@@ -219,11 +484,47 @@ fn run_inner(args: &TestArgs, trace_out: Option<&Path>) -> Result<()> {
             allow_secrets,
             allowed_secrets,
             secrets,
-            wasi_preopens,
+            wasi_preopens: sandbox.preopens(),
+            fs_quota: sandbox.quota(),
+            env_vars,
             config,
             allow_http,
+            http_domains: manifest.http_domains.clone(),
             timeout_ms: args.timeout_ms,
             max_memory_bytes,
+            max_output_bytes: args
+                .max_output_bytes
+                .or_else(|| {
+                    manifest
+                        .limits
+                        .as_ref()
+                        .and_then(|limits| limits.max_output_bytes)
+                        .map(|bytes| usize::try_from(bytes).unwrap_or(usize::MAX))
+                })
+                .unwrap_or(DEFAULT_MAX_OUTPUT_BYTES),
+            isolation,
+            max_open_handles: isolation.max_open_handles(
+                manifest
+                    .limits
+                    .as_ref()
+                    .and_then(|limits| limits.files)
+                    .map(|files| files as usize),
+            ),
+            net: manifest.net.clone().filter(|_| allow_net),
+            audit: audit.clone(),
+            allow_progress: progress_permission(&manifest_value),
+            on_progress: Some(on_progress),
+            guest_log,
+            blobs: blob_permission(&manifest_value)?,
+            sql,
+            messaging,
+            events,
+            telemetry,
+            chaos,
+            pooling: None,
+            transactional_state: args.state_transactional,
+            random,
+            profile,
         })?;
 
         if steps.len() > 1 && args.output.is_some() {
@@ -231,29 +532,111 @@ fn run_inner(args: &TestArgs, trace_out: Option<&Path>) -> Result<()> {
         }
 
         let mut outputs = Vec::new();
-        for (op, input) in steps.iter() {
+        let mut chaos_seen = 0;
+        // Concurrent runs replace the step loop with one report, but still
+        // get the state dump and other post-run listings below.
+        let concurrent = args.concurrency > 1 || args.repeat > 1;
+        let mut concurrency_report = None;
+        if concurrent {
+            let step = &steps[0];
+            current_op = Some(step.op.as_str());
+            let report =
+                harness.invoke_concurrently(&step.op, &step.input, args.concurrency, args.repeat);
+            outputs.push(serde_json::to_string(&report)?);
+            concurrency_report = Some(report);
+        }
+        let sequential_steps: &[Step] = if concurrent { &[] } else { &steps };
+        for step in sequential_steps {
+            let op = step.op.as_str();
+            current_op = Some(op);
+            let audit_mark = audit.len();
+            let outcome = harness.invoke_with(op, &step.input, &step.overrides);
+            trace.record_step(
+                step,
+                &outcome,
+                audit.entries_since(audit_mark),
+                &secret_values,
+            );
+            let injected = harness.chaos_events();
+            if injected.len() > chaos_seen {
+                let mut listing = serde_json::to_value(&injected[chaos_seen..]).unwrap_or_default();
+                redact_value(&mut listing, &secret_values);
+                let listing =
+                    serde_json::to_string_pretty(&listing).unwrap_or_else(|_| "[]".into());
+                eprintln!("chaos ({op}):\n{listing}");
+                chaos_seen = injected.len();
+            }
+            let outcome = match (outcome, &step.expect) {
+                (Err(err), Some(expect)) => {
+                    let payload = error_payload_from_anyhow(&err);
+                    expect
+                        .check_error(&payload.code)
+                        .map_err(|message| step.expectation_failed(message))?;
+                    outputs.push(serde_json::to_string(
+                        &serde_json::json!({ "error": payload }),
+                    )?);
+                    continue;
+                }
+                (outcome, _) => outcome?,
+            };
             let InvokeOutcome {
                 output_json,
                 instantiate_ms,
                 run_ms,
-            } = harness.invoke(op, input)?;
-            if output_json.len() > MAX_OUTPUT_BYTES {
-                return Err(anyhow::Error::new(OutputLimitError {
-                    limit: MAX_OUTPUT_BYTES,
-                    actual: output_json.len(),
-                }));
+                ..
+            } = outcome;
+            if let Some(expect) = &step.expect {
+                let output: Value =
+                    serde_json::from_str(&output_json).context("output is not valid JSON")?;
+                expect
+                    .check_output(&output)
+                    .map_err(|message| step.expectation_failed(message))?;
             }
             timing.instantiate = timing.instantiate.saturating_add(instantiate_ms);
             timing.run = timing.run.saturating_add(run_ms);
             outputs.push(output_json);
+            sandbox.check_quota()?;
+        }
+
+        let fs_changes = sandbox.changes()?;
+        if !fs_changes.is_empty() {
+            let listing = serde_json::to_string_pretty(&fs_changes).unwrap_or_else(|_| "[]".into());
+            eprintln!("filesystem changes:\n{listing}");
+        }
+
+        let messages = transcript.transcript();
+        if !messages.is_empty() {
+            let mut listing = serde_json::to_value(&messages).unwrap_or_default();
+            redact_value(&mut listing, &secret_values);
+            let listing = serde_json::to_string_pretty(&listing).unwrap_or_else(|_| "[]".into());
+            eprintln!("messages:\n{listing}");
+        }
+        if let Some(path) = &args.events_out {
+            recorder.write_transcript(path)?;
+        }
+        if args.show_telemetry {
+            let captured = captured_telemetry.lock().expect("telemetry mutex poisoned");
+            let mut listing = serde_json::to_value(&*captured).unwrap_or_default();
+            redact_value(&mut listing, &secret_values);
+            let listing = serde_json::to_string_pretty(&listing).unwrap_or_else(|_| "[]".into());
+            eprintln!("telemetry:\n{listing}");
         }
 
         if args.state_dump {
-            let dump = harness.state_dump();
+            let mut dump = match &args.state_dump_scope {
+                Some(filter) => harness.state_dump_matching(filter),
+                None => harness.state_dump(),
+            };
+            redact_state_dump(&mut dump, &secret_values);
             let dump_json = serde_json::to_string_pretty(&dump).unwrap_or_else(|_| "{}".into());
             eprintln!("state dump:\n{dump_json}");
         }
 
+        if let Some(report) = concurrency_report
+            && !report.passed()
+        {
+            return Err(anyhow::Error::new(ConcurrencyFailure { report }));
+        }
         Ok(outputs)
     })();
 
@@ -265,9 +648,12 @@ fn run_inner(args: &TestArgs, trace_out: Option<&Path>) -> Result<()> {
             }
 
             let mut redacted_outputs = Vec::new();
-            for raw in &outputs {
+            for (raw, step) in outputs.iter().zip(&steps) {
                 let mut value: Value =
                     serde_json::from_str(raw).context("output is not valid JSON")?;
+                if let Some(pointers) = redaction_pointers.get(&step.op) {
+                    redact_pointers(&mut value, pointers);
+                }
                 redact_value(&mut value, &secret_values);
                 redacted_outputs.push(value);
             }
@@ -308,13 +694,30 @@ fn run_inner(args: &TestArgs, trace_out: Option<&Path>) -> Result<()> {
             }
 
             trace.write(timing.total, None)?;
+            if let Some(path) = &args.audit_out {
+                write_audit_report(path, &manifest, &audit, &secret_values)?;
+            }
             Ok(())
         }
         Err(err) => {
             let mut payload = error_payload_from_anyhow(&err);
             redact_error_payload(&mut payload, &secret_values);
+            let warnings = current_op
+                .zip(
+                    err.chain()
+                        .find_map(|source| source.downcast_ref::<ComponentInvokeError>()),
+                )
+                .and_then(|(op, component_err)| {
+                    undeclared_error_warning(&manifest, op, &component_err.code)
+                })
+                .into_iter()
+                .collect::<Vec<_>>();
+            for warning in &warnings {
+                eprintln!("warning: {}", warning.message);
+            }
             let failure = TestRunFailure {
                 payload: payload.clone(),
+                warnings,
                 world: args.world.clone(),
                 wasm: args.wasm.clone(),
                 timing_ms: timing,
@@ -325,6 +728,11 @@ fn run_inner(args: &TestArgs, trace_out: Option<&Path>) -> Result<()> {
             if let Some(path) = trace.out_path.as_deref() {
                 eprintln!("#TRY_SAVE_TRACE {}", path.display());
             }
+            if let Some(path) = &args.audit_out
+                && let Err(audit_err) = write_audit_report(path, &manifest, &audit, &secret_values)
+            {
+                eprintln!("failed to write audit log: {audit_err}");
+            }
             Err(anyhow::Error::new(failure))
         }
     }
@@ -347,7 +755,138 @@ fn resolve_manifest_path(wasm: &Path, manifest: Option<&Path>) -> Result<PathBuf
     }
 }
 
-fn collect_steps(args: &TestArgs) -> Result<Vec<(String, Value)>> {
+/// One invocation of a (possibly multi-step) run.
+struct Step {
+    /// 1-based position, as used by `--step-config`/`--step-secret`.
+    index: usize,
+    op: String,
+    input: Value,
+    overrides: InvokeOverrides,
+    expect: Option<StepExpect>,
+}
+
+impl Step {
+    fn expectation_failed(&self, message: String) -> anyhow::Error {
+        // The JSON envelope escapes newlines; print the diff as-is for humans.
+        eprintln!("step {} ({}): {message}", self.index, self.op);
+        anyhow::Error::new(StepExpectationError {
+            step: self.index,
+            op: self.op.clone(),
+            message,
+        })
+    }
+}
+
+fn collect_steps(args: &TestArgs) -> Result<Vec<Step>> {
+    let mut steps = match &args.plan {
+        Some(path) => StepPlan::from_path(path)?
+            .steps
+            .into_iter()
+            .map(|planned| Step {
+                index: 0,
+                op: planned.op,
+                input: planned.input.unwrap_or_default(),
+                overrides: InvokeOverrides {
+                    config: planned.config,
+                    secrets: planned.secrets.into_iter().collect(),
+                },
+                expect: planned.expect,
+            })
+            .collect(),
+        None => collect_cli_steps(args)?
+            .into_iter()
+            .map(|(op, input)| Step {
+                index: 0,
+                op,
+                input,
+                overrides: InvokeOverrides::default(),
+                expect: None,
+            })
+            .collect::<Vec<_>>(),
+    };
+    for (idx, step) in steps.iter_mut().enumerate() {
+        step.index = idx + 1;
+    }
+    for raw in &args.step_config {
+        let (step, path) = step_override(&steps, raw, "--step-config")?;
+        let contents = fs::read_to_string(path).with_context(|| format!("read config {path}"))?;
+        steps[step].overrides.config = Some(
+            serde_json::from_str(&contents)
+                .with_context(|| format!("step config {path} must be valid JSON"))?,
+        );
+    }
+    if let Some(expect) = cli_expectation(args)?
+        && let Some(last) = steps.last_mut()
+    {
+        last.expect = Some(expect);
+    }
+    for raw in &args.step_secret {
+        let (step, secret) = step_override(&steps, raw, "--step-secret")?;
+        let (key, value) = secret
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("--step-secret must be IDX=KEY=VALUE, got `{raw}`"))?;
+        steps[step]
+            .overrides
+            .secrets
+            .insert(key.to_string(), value.to_string());
+    }
+    Ok(steps)
+}
+
+/// The `--expect-*` flags as an expectation on the last step; they replace
+/// whatever a plan declared for it.
+fn cli_expectation(args: &TestArgs) -> Result<Option<StepExpect>> {
+    if args.expect_output.is_none()
+        && args.expect_error_code.is_none()
+        && args.expect_status.is_none()
+    {
+        return Ok(None);
+    }
+    let status = match (args.expect_status, &args.expect_error_code) {
+        (Some(ExpectedStatus::Ok), Some(_)) => {
+            bail!("--expect-error-code cannot be combined with --expect-status ok")
+        }
+        (Some(status), _) => status,
+        (None, Some(_)) => ExpectedStatus::Error,
+        (None, None) => ExpectedStatus::Ok,
+    };
+    let output = args
+        .expect_output
+        .as_ref()
+        .map(|path| -> Result<Value> {
+            let raw = fs::read_to_string(path)
+                .with_context(|| format!("read expected output {}", path.display()))?;
+            serde_json::from_str(&raw)
+                .with_context(|| format!("{} must be valid JSON", path.display()))
+        })
+        .transpose()?;
+    Ok(Some(StepExpect {
+        status,
+        code: args.expect_error_code.clone(),
+        output,
+        exact: !args.expect_subset,
+    }))
+}
+
+/// Splits `IDX=REST` and returns the zero-based step position with `REST`.
+fn step_override<'a>(steps: &[Step], raw: &'a str, flag: &str) -> Result<(usize, &'a str)> {
+    let (idx, rest) = raw
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("{flag} must start with a step number, got `{raw}`"))?;
+    let idx: usize = idx
+        .trim()
+        .parse()
+        .with_context(|| format!("{flag}: `{idx}` is not a step number"))?;
+    if idx == 0 || idx > steps.len() {
+        bail!(
+            "{flag}: step {idx} is out of range; the run has {} step(s)",
+            steps.len()
+        );
+    }
+    Ok((idx - 1, rest))
+}
+
+fn collect_cli_steps(args: &TestArgs) -> Result<Vec<(String, Value)>> {
     if args.op.is_empty() {
         bail!("--op is required");
     }
@@ -427,6 +966,75 @@ fn resolve_trace_out(args: &TestArgs) -> Result<Option<PathBuf>> {
         .map(PathBuf::from))
 }
 
+/// `requested` when the manifest supports it, otherwise the manifest's
+/// default profile.
+pub(crate) fn select_profile(
+    manifest: &ComponentManifest,
+    requested: Option<&str>,
+) -> Result<Option<String>> {
+    let profiles = &manifest.profiles;
+    match requested {
+        Some(name) if profiles.supported.iter().any(|supported| supported == name) => {
+            Ok(Some(name.to_string()))
+        }
+        Some(name) => bail!(
+            "profile `{name}` is not in profiles.supported ({})",
+            profiles.supported.join(", ")
+        ),
+        None => Ok(profiles.default.clone()),
+    }
+}
+
+/// `trace.json` becomes `trace.<profile>.json`.
+fn profile_trace_path(path: &Path, profile: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{stem}.{profile}.{}", ext.to_string_lossy()),
+        None => format!("{stem}.{profile}"),
+    };
+    path.with_file_name(name)
+}
+
+/// Builds a harness that grants exactly what `manifest` declares, using the
+/// `test` command's defaults (dry-run, `dev`/`default` tenant, no secrets)
+/// and the manifest's limits. `profile` replaces the manifest's default
+/// profile and must be one it supports.
+/// The returned sandbox backs the harness mounts and must outlive it.
+pub(crate) fn harness_for_manifest(
+    wasm_bytes: Vec<u8>,
+    manifest_value: &Value,
+    manifest: &ComponentManifest,
+    config: Option<Value>,
+    audit: AuditLog,
+    pooling: Option<PoolingConfig>,
+    profile: Option<&str>,
+) -> Result<(TestHarness, FsSandbox)> {
+    let (builder, sandbox) =
+        harness_builder_for_manifest(wasm_bytes, manifest_value, manifest, config, profile)?;
+    let harness = builder.audit(audit).pooling(pooling).build()?;
+    Ok((harness, sandbox))
+}
+
+/// The builder behind [`harness_for_manifest`], for callers that seed state
+/// or pin the state prefix before building.
+pub(crate) fn harness_builder_for_manifest(
+    wasm_bytes: Vec<u8>,
+    manifest_value: &Value,
+    manifest: &ComponentManifest,
+    config: Option<Value>,
+    profile: Option<&str>,
+) -> Result<(TestHarnessBuilder, FsSandbox)> {
+    let sandbox = resolve_fs_sandbox(manifest, &[], false, true, None)?;
+    let mut builder = TestHarness::builder(wasm_bytes).manifest(manifest_value, manifest)?;
+    builder.config_mut().config = config;
+    builder.config_mut().profile = select_profile(manifest, profile)?;
+    builder.config_mut().wasi_preopens = sandbox.preopens();
+    Ok((builder, sandbox))
+}
+
 fn state_prefix(flow: Option<&str>, session: &str) -> String {
     if let Some(flow) = flow {
         format!("flow/{flow}/{session}")
@@ -435,68 +1043,95 @@ fn state_prefix(flow: Option<&str>, session: &str) -> String {
     }
 }
 
-fn resolve_wasi_preopens(
+/// Gives every manifest mount its own host directory: the one mapped with
+/// `--mount NAME=PATH`, or an empty temp directory. Mounts are read-only
+/// unless the manifest allows writes and `--allow-fs-write` is set outside
+/// dry-run.
+fn resolve_fs_sandbox(
     manifest: &ComponentManifest,
+    mappings: &[String],
     allow_fs_write: bool,
     dry_run: bool,
-) -> Result<Vec<WasiPreopen>> {
-    let Some(fs) = manifest.capabilities.wasi.filesystem.as_ref() else {
-        return Ok(Vec::new());
-    };
-    if fs.mode == FilesystemMode::None {
-        return Ok(Vec::new());
-    }
-    let host_root =
-        std::env::current_dir().context("resolve current working directory for mounts")?;
-    let meta = fs::metadata(&host_root)
-        .with_context(|| format!("failed to stat preopen {}", host_root.display()))?;
-    if !meta.is_dir() {
-        bail!("preopen {} must be a directory", host_root.display());
-    }
-    let mut read_only = matches!(fs.mode, FilesystemMode::ReadOnly);
-    if dry_run || !allow_fs_write {
-        read_only = true;
+    quota_bytes: Option<u64>,
+) -> Result<FsSandbox> {
+    let mut sandbox = FsSandbox::new(quota_bytes);
+    let filesystem = manifest
+        .capabilities
+        .wasi
+        .filesystem
+        .as_ref()
+        .filter(|fs| fs.mode != FilesystemMode::None);
+    let mounts = filesystem
+        .map(|fs| fs.mounts.as_slice())
+        .unwrap_or_default();
+    let mut host_dirs = HashMap::new();
+    for entry in mappings {
+        let (name, path) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("invalid --mount `{entry}`; use NAME=PATH"))?;
+        if !mounts.iter().any(|mount| mount.name == name) {
+            bail!("--mount `{name}` does not match a wasi.filesystem mount in the manifest");
+        }
+        host_dirs.insert(name.to_string(), PathBuf::from(path));
     }
-    let mut preopens = Vec::new();
-    for mount in &fs.mounts {
-        preopens.push(WasiPreopen::new(&host_root, mount.guest_path.clone()).read_only(read_only));
+    let read_only = filesystem.is_some_and(|fs| fs.mode == FilesystemMode::ReadOnly)
+        || dry_run
+        || !allow_fs_write;
+    for mount in mounts {
+        match host_dirs.get(&mount.name) {
+            Some(path) => {
+                sandbox.add_host_mount(&mount.name, &mount.guest_path, path, read_only)?
+            }
+            None => sandbox.add_temp_mount(&mount.name, &mount.guest_path, read_only)?,
+        }
     }
-    Ok(preopens)
+    Ok(sandbox)
 }
 
-fn state_permissions(
-    manifest_value: &Value,
-    manifest: &crate::manifest::ComponentManifest,
-) -> (bool, bool, bool) {
-    let mut allow_state_read = false;
-    let mut allow_state_write = false;
-    if let Some(state) = manifest.capabilities.host.state.as_ref() {
-        allow_state_read = state.read;
-        allow_state_write = state.write;
-    }
-    let allow_state_delete = manifest_value
-        .get("capabilities")
-        .and_then(|caps| caps.get("host"))
-        .and_then(|host| host.get("state"))
-        .and_then(|state| state.get("delete"))
-        .and_then(|value| value.as_bool())
-        .unwrap_or(false);
-    if allow_state_delete && !allow_state_write {
-        allow_state_write = true;
-    }
-    (allow_state_read, allow_state_write, allow_state_delete)
-}
-
-fn secret_permissions(manifest: &crate::manifest::ComponentManifest) -> (bool, HashSet<String>) {
-    let Some(secrets) = manifest.capabilities.host.secrets.as_ref() else {
-        return (false, HashSet::new());
-    };
-    let allowed = secrets
-        .required
-        .iter()
-        .map(|req| req.key.as_str().to_string())
-        .collect::<HashSet<_>>();
-    (true, allowed)
+/// Prints each `report-progress` call to stderr as it arrives, redacting
+/// messages that contain a secret value.
+fn print_progress(secret_values: Vec<String>) -> ProgressCallback {
+    Arc::new(move |progress: &Progress| {
+        let message = if secret_values
+            .iter()
+            .any(|secret| progress.message.contains(secret.as_str()))
+        {
+            REDACTED
+        } else {
+            progress.message.as_str()
+        };
+        eprintln!("progress: {:>3}% {message}", progress.percent);
+    })
+}
+
+/// Prints guest log records to stderr, redacting any that mention a secret,
+/// and keeps them for the trace when one is being written.
+fn print_guest_log(
+    secret_values: Vec<String>,
+    captured: Option<Arc<Mutex<Vec<TraceLog>>>>,
+) -> GuestLogSink {
+    Arc::new(move |record: &GuestLogRecord| {
+        let line = record.to_string();
+        let line = if secret_values
+            .iter()
+            .any(|secret| line.contains(secret.as_str()))
+        {
+            REDACTED.to_string()
+        } else {
+            line
+        };
+        eprintln!("[guest {} {}] {line}", record.level.as_str(), record.target);
+        if let Some(captured) = &captured {
+            captured
+                .lock()
+                .expect("trace log mutex poisoned")
+                .push(TraceLog {
+                    level: record.level.as_str(),
+                    target: record.target.clone(),
+                    message: line,
+                });
+        }
+    })
 }
 
 fn load_secrets(args: &TestArgs) -> Result<HashMap<String, String>> {
@@ -518,6 +1153,54 @@ fn load_secrets(args: &TestArgs) -> Result<HashMap<String, String>> {
     Ok(secrets)
 }
 
+/// Collects the environment handed to the component: explicit `--env-var`
+/// pairs, then (with `--inherit-env`) host values for the remaining
+/// allow-listed names. Anything outside `capabilities.wasi.env.allow` is
+/// rejected rather than silently dropped.
+fn resolve_env_vars(
+    manifest: &ComponentManifest,
+    pairs: &[String],
+    inherit: bool,
+) -> Result<Vec<(String, String)>> {
+    let allow = manifest
+        .capabilities
+        .wasi
+        .env
+        .as_ref()
+        .map(|env| env.allow.as_slice())
+        .unwrap_or_default();
+    let mut vars = BTreeMap::new();
+    for entry in pairs {
+        let (key, value) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("invalid --env-var `{entry}`; use KEY=VALUE"))?;
+        if !allow.iter().any(|allowed| allowed == key) {
+            return Err(CapabilityDeniedError::new(
+                "wasi.env.allow",
+                format!(
+                    "environment variable `{key}` is not declared in capabilities.wasi.env.allow"
+                ),
+            )
+            .into());
+        }
+        vars.insert(key.to_string(), value.to_string());
+    }
+    if inherit {
+        for key in allow {
+            if !vars.contains_key(key)
+                && let Ok(value) = std::env::var(key)
+            {
+                vars.insert(key.clone(), value);
+            }
+        }
+    }
+    Ok(vars.into_iter().collect())
+}
+
+fn parse_state_dump_scope(raw: &str) -> Result<StateDumpFilter, String> {
+    StateDumpFilter::parse(raw).ok_or_else(|| format!("expected TENANT[/TEAM[/USER]], got `{raw}`"))
+}
+
 fn parse_state_seeds(args: &TestArgs) -> Result<Vec<(String, Vec<u8>)>> {
     let mut seeds = Vec::new();
     for entry in &args.state_set {
@@ -571,19 +1254,74 @@ fn parse_json_secrets(path: &Path) -> Result<HashMap<String, String>> {
 }
 
 fn load_config(args: &TestArgs) -> Result<Option<Value>> {
-    let Some(raw) = &args.config else {
-        return Ok(None);
-    };
-    let path = Path::new(raw);
-    let contents = if path.exists() {
-        fs::read_to_string(path).with_context(|| format!("read config {}", path.display()))?
-    } else {
-        raw.clone()
+    let contents = match (&args.config, &args.config_json) {
+        (Some(raw), _) if Path::new(raw).exists() => {
+            fs::read_to_string(raw).with_context(|| format!("read config {raw}"))?
+        }
+        (Some(raw), _) | (None, Some(raw)) => raw.clone(),
+        (None, None) => return Ok(None),
     };
     let value: Value = serde_json::from_str(&contents).context("config must be valid JSON")?;
     Ok(Some(value))
 }
 
+/// The config handed to the component: `--config`/`--config-json` when
+/// given, otherwise the defaults declared in `config_schema`. Either way it
+/// must validate against `config_schema` before anything is invoked.
+fn resolve_config(args: &TestArgs, manifest_value: &Value) -> Result<Option<Value>> {
+    let schema = manifest_value.get("config_schema");
+    let (config, origin) = match (load_config(args)?, schema) {
+        (Some(config), _) => (config, ConfigOrigin::Provided),
+        (None, Some(schema)) => (
+            schema_default(schema).unwrap_or_else(|| Value::Object(Map::new())),
+            ConfigOrigin::SchemaDefaults,
+        ),
+        (None, None) => return Ok(None),
+    };
+    if let Some(schema) = schema {
+        check_config(schema, &config, origin)?;
+    }
+    Ok(Some(config))
+}
+
+fn check_config(schema: &Value, config: &Value, origin: ConfigOrigin) -> Result<()> {
+    let validator = jsonschema::validator_for(schema).context("config_schema does not compile")?;
+    let violations = validator
+        .iter_errors(config)
+        .map(|err| {
+            let pointer = err.instance_path().to_string();
+            ConfigViolation {
+                pointer: if pointer.is_empty() {
+                    "/".to_string()
+                } else {
+                    pointer
+                },
+                message: err.to_string(),
+            }
+        })
+        .collect::<Vec<_>>();
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigSchemaError { origin, violations }.into())
+    }
+}
+
+/// The value a schema's `default` keywords produce: an explicit `default`
+/// wins, otherwise objects are assembled from their properties' defaults.
+fn schema_default(schema: &Value) -> Option<Value> {
+    if let Some(default) = schema.get("default") {
+        return Some(default.clone());
+    }
+    let properties = schema.get("properties")?.as_object()?;
+    let object = properties
+        .iter()
+        .filter_map(|(name, property)| Some((name.clone(), schema_default(property)?)))
+        .collect::<Map<_, _>>();
+    let is_object = schema.get("type").and_then(Value::as_str) == Some("object");
+    (is_object || !object.is_empty()).then_some(Value::Object(object))
+}
+
 fn parse_max_memory_bytes(max_memory_mb: u64) -> Result<usize> {
     let bytes = max_memory_mb
         .checked_mul(1024 * 1024)
@@ -648,6 +1386,9 @@ struct TestOutputEnvelope {
 #[derive(Debug)]
 struct TestRunFailure {
     payload: TestErrorPayload,
+    /// Non-fatal findings reported next to the error, such as an error code
+    /// missing from the manifest's catalog.
+    warnings: Vec<Diagnostic>,
     world: String,
     wasm: PathBuf,
     timing_ms: TimingMs,
@@ -663,6 +1404,7 @@ enum TestErrorOutput {
 pub struct TestCommandError {
     output: TestErrorOutput,
     pretty: bool,
+    exit_code: ExitCode,
 }
 
 impl TestCommandError {
@@ -674,10 +1416,12 @@ impl TestCommandError {
         wasm: &Path,
     ) -> Self {
         if let Some(failure) = err.downcast_ref::<TestRunFailure>() {
+            let exit_code = exit_code_for(&failure.payload.code);
             if raw_output {
                 return Self {
                     output: TestErrorOutput::Raw(failure.payload.clone()),
                     pretty,
+                    exit_code,
                 };
             }
             let envelope = TestOutputEnvelope {
@@ -685,20 +1429,29 @@ impl TestCommandError {
                 world: failure.world.clone(),
                 wasm: failure.wasm.display().to_string(),
                 result: None,
-                diagnostics: vec![diagnostic_from_payload(&failure.payload)],
+                diagnostics: std::iter::once(diagnostic_from_payload(&failure.payload))
+                    .chain(failure.warnings.iter().cloned())
+                    .collect(),
                 timing_ms: failure.timing_ms,
             };
             return Self {
                 output: TestErrorOutput::Envelope(envelope),
                 pretty,
+                exit_code,
             };
         }
 
         let payload = error_payload_from_anyhow(&err);
+        // `test.failure` covers manifest problems too; the chain tells them apart.
+        let exit_code = match exit_code_for(&payload.code) {
+            ExitCode::Failure => ExitCode::of(&err),
+            code => code,
+        };
         if raw_output {
             return Self {
                 output: TestErrorOutput::Raw(payload),
                 pretty,
+                exit_code,
             };
         }
         let envelope = TestOutputEnvelope {
@@ -712,26 +1465,48 @@ impl TestCommandError {
         Self {
             output: TestErrorOutput::Envelope(envelope),
             pretty,
+            exit_code,
         }
     }
 
+    /// The process exit code this failure maps to; also rendered as
+    /// `exit_code` in the JSON output.
+    pub fn exit_code(&self) -> ExitCode {
+        self.exit_code
+    }
+
     pub fn render_json(&self) -> String {
-        match &self.output {
-            TestErrorOutput::Raw(payload) => {
-                if self.pretty {
-                    serde_json::to_string_pretty(payload).unwrap_or_else(|_| "{}".to_string())
-                } else {
-                    serde_json::to_string(payload).unwrap_or_else(|_| "{}".to_string())
-                }
-            }
-            TestErrorOutput::Envelope(envelope) => {
-                if self.pretty {
-                    serde_json::to_string_pretty(envelope).unwrap_or_else(|_| "{}".to_string())
-                } else {
-                    serde_json::to_string(envelope).unwrap_or_else(|_| "{}".to_string())
-                }
-            }
+        let value = match &self.output {
+            TestErrorOutput::Raw(payload) => serde_json::to_value(payload),
+            TestErrorOutput::Envelope(envelope) => serde_json::to_value(envelope),
+        };
+        let Ok(mut value) = value else {
+            return "{}".to_string();
+        };
+        if let Some(object) = value.as_object_mut() {
+            object.insert("exit_code".into(), Value::from(self.exit_code.code()));
         }
+        if self.pretty {
+            serde_json::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            serde_json::to_string(&value).unwrap_or_else(|_| "{}".to_string())
+        }
+    }
+}
+
+/// Maps a test error code onto the CLI's exit code contract. Codes outside
+/// the `test.` namespace come from the component.
+fn exit_code_for(code: &str) -> ExitCode {
+    match code {
+        "test.timeout" => ExitCode::Timeout,
+        "test.memory_limit" | "test.open_handle_limit" | "test.fs_quota" | "test.output.limit" => {
+            ExitCode::Limit
+        }
+        "test.capability.denied" => ExitCode::CapabilityDenied,
+        "test.trap" => ExitCode::ComponentError,
+        "test.config.invalid" | "test.world.unsupported" => ExitCode::Usage,
+        code if code.starts_with("test.") => ExitCode::Failure,
+        _ => ExitCode::ComponentError,
     }
 }
 
@@ -760,6 +1535,80 @@ impl std::fmt::Display for TestRunFailure {
 
 impl std::error::Error for TestRunFailure {}
 
+#[derive(Debug, Clone, Copy)]
+enum ConfigOrigin {
+    Provided,
+    SchemaDefaults,
+}
+
+#[derive(Debug, Serialize)]
+struct ConfigViolation {
+    pointer: String,
+    message: String,
+}
+
+#[derive(Debug)]
+struct ConfigSchemaError {
+    origin: ConfigOrigin,
+    violations: Vec<ConfigViolation>,
+}
+
+impl std::fmt::Display for ConfigSchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.origin {
+            ConfigOrigin::Provided => write!(f, "config does not match config_schema")?,
+            ConfigOrigin::SchemaDefaults => write!(
+                f,
+                "config_schema defaults do not form a valid config; pass --config or --config-json"
+            )?,
+        }
+        for violation in &self.violations {
+            write!(f, "\n  {}: {}", violation.pointer, violation.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigSchemaError {}
+
+#[derive(Debug)]
+struct CapabilityDeniedError {
+    capability: &'static str,
+    message: String,
+}
+
+impl CapabilityDeniedError {
+    fn new(capability: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            capability,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for CapabilityDeniedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CapabilityDeniedError {}
+
+#[derive(Debug)]
+struct StepExpectationError {
+    step: usize,
+    op: String,
+    message: String,
+}
+
+impl std::fmt::Display for StepExpectationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "step {} ({}): {}", self.step, self.op, self.message)
+    }
+}
+
+impl std::error::Error for StepExpectationError {}
+
 #[derive(Debug)]
 struct UnsupportedWorldError {
     world: String,
@@ -779,22 +1628,29 @@ impl std::fmt::Display for UnsupportedWorldError {
 impl std::error::Error for UnsupportedWorldError {}
 
 #[derive(Debug)]
-struct OutputLimitError {
-    limit: usize,
-    actual: usize,
+struct ConcurrencyFailure {
+    report: ConcurrencyReport,
 }
 
-impl std::fmt::Display for OutputLimitError {
+impl std::fmt::Display for ConcurrencyFailure {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let report = &self.report;
         write!(
             f,
-            "output size {} bytes exceeds limit {} bytes",
-            self.actual, self.limit
-        )
+            "{} of {} concurrent `{}` invocations failed",
+            report.invocations - report.succeeded,
+            report.invocations,
+            report.operation
+        )?;
+        let conflicts = report.state_conflicts.values().sum::<u64>();
+        if conflicts > 0 {
+            write!(f, "; {conflicts} write(s) lost a concurrent update")?;
+        }
+        Ok(())
     }
 }
 
-impl std::error::Error for OutputLimitError {}
+impl std::error::Error for ConcurrencyFailure {}
 
 fn diagnostic_from_payload(payload: &TestErrorPayload) -> Diagnostic {
     Diagnostic {
@@ -803,10 +1659,35 @@ fn diagnostic_from_payload(payload: &TestErrorPayload) -> Diagnostic {
         message: payload.message.clone(),
         details: payload.details.clone(),
         path: None,
-        hint: None,
+        hint: payload
+            .details
+            .as_ref()
+            .and_then(|details| details.pointer("/trap/hint"))
+            .and_then(Value::as_str)
+            .map(str::to_string),
     }
 }
 
+/// Flags a component error code the manifest's `errors` catalog does not
+/// declare for `operation`. Manifests without a catalog are not checked.
+fn undeclared_error_warning(
+    manifest: &ComponentManifest,
+    operation: &str,
+    code: &str,
+) -> Option<Diagnostic> {
+    if manifest.errors.is_empty() || manifest.declared_error(operation, code).is_some() {
+        return None;
+    }
+    Some(Diagnostic {
+        severity: "warning".to_string(),
+        code: "test.error.undeclared".to_string(),
+        message: format!("operation `{operation}` returned undeclared error code `{code}`"),
+        details: None,
+        path: Some("errors".to_string()),
+        hint: Some("add the code to `errors` in component.manifest.json".to_string()),
+    })
+}
+
 fn redact_error_payload(payload: &mut TestErrorPayload, secrets: &[String]) {
     payload.message = redact_string(&payload.message, secrets);
     if let Some(details) = payload.details.as_mut() {
@@ -814,6 +1695,21 @@ fn redact_error_payload(payload: &mut TestErrorPayload, secrets: &[String]) {
     }
 }
 
+/// Drops dump values that contain a secret, keeping `value_base64` valid
+/// (empty) and flagging the entry as `redacted`.
+fn redact_state_dump(dump: &mut [StateDumpEntry], secrets: &[String]) {
+    for entry in dump {
+        let value = BASE64_STANDARD
+            .decode(&entry.value_base64)
+            .unwrap_or_default();
+        let value = String::from_utf8_lossy(&value);
+        if secrets.iter().any(|secret| value.contains(secret.as_str())) {
+            entry.value_base64.clear();
+            entry.redacted = true;
+        }
+    }
+}
+
 fn redact_value(value: &mut Value, secrets: &[String]) {
     match value {
         Value::String(text) => {
@@ -833,13 +1729,41 @@ fn redact_value(value: &mut Value, secrets: &[String]) {
     }
 }
 
+/// JSON pointers the describe payload marks for redaction, keyed by operation.
+/// Components without a readable describe payload simply have none.
+fn describe_redactions(wasm: &Path) -> HashMap<String, Vec<String>> {
+    let Ok(describe) = super::inspect::load_describe(wasm) else {
+        return HashMap::new();
+    };
+    describe
+        .operations
+        .into_iter()
+        .map(|operation| {
+            let pointers = operation
+                .redactions
+                .into_iter()
+                .map(|rule| rule.json_pointer)
+                .collect();
+            (operation.id, pointers)
+        })
+        .collect()
+}
+
+fn redact_pointers(value: &mut Value, pointers: &[String]) {
+    for pointer in pointers {
+        if let Some(target) = value.pointer_mut(pointer) {
+            *target = Value::String(REDACTED.to_string());
+        }
+    }
+}
+
 fn redact_string(value: &str, secrets: &[String]) -> String {
     let mut out = value.to_string();
     for secret in secrets {
         if secret.is_empty() {
             continue;
         }
-        out = out.replace(secret, "***REDACTED***");
+        out = out.replace(secret, REDACTED);
     }
     out
 }
@@ -872,11 +1796,31 @@ fn error_payload_from_anyhow(err: &anyhow::Error) -> TestErrorPayload {
         .chain()
         .find_map(|source| source.downcast_ref::<HarnessError>())
     {
-        let (code, message) = match harness_err {
-            HarnessError::Timeout { .. } => ("test.timeout", harness_err.to_string()),
-            HarnessError::MemoryLimit { .. } => ("test.memory_limit", harness_err.to_string()),
+        let (code, details) = match harness_err {
+            HarnessError::Timeout { .. } => ("test.timeout", None),
+            HarnessError::MemoryLimit { .. } => ("test.memory_limit", None),
+            HarnessError::OpenHandleLimit {
+                max_open_handles,
+                open_handles,
+            } => (
+                "test.open_handle_limit",
+                Some(serde_json::json!({
+                    "limit": max_open_handles,
+                    "open_handles": open_handles,
+                })),
+            ),
+            HarnessError::OutputTooLarge {
+                max_output_bytes,
+                size,
+            } => (
+                "test.output.limit",
+                Some(serde_json::json!({
+                    "limit": max_output_bytes,
+                    "actual": size,
+                })),
+            ),
         };
-        (code.to_string(), message, None)
+        (code.to_string(), harness_err.to_string(), details)
     } else if let Some(world_err) = err
         .chain()
         .find_map(|source| source.downcast_ref::<UnsupportedWorldError>())
@@ -886,16 +1830,56 @@ fn error_payload_from_anyhow(err: &anyhow::Error) -> TestErrorPayload {
             world_err.to_string(),
             None,
         )
-    } else if let Some(limit_err) = err
+    } else if let Some(step_err) = err
         .chain()
-        .find_map(|source| source.downcast_ref::<OutputLimitError>())
+        .find_map(|source| source.downcast_ref::<StepExpectationError>())
     {
         (
-            "test.output.limit".to_string(),
-            limit_err.to_string(),
+            "test.step.expectation".to_string(),
+            step_err.to_string(),
             Some(serde_json::json!({
-                "limit": limit_err.limit,
-                "actual": limit_err.actual,
+                "step": step_err.step,
+                "op": step_err.op,
+                "diff": step_err.message.lines().skip(1).map(str::trim).collect::<Vec<_>>(),
+            })),
+        )
+    } else if let Some(failure) = err
+        .chain()
+        .find_map(|source| source.downcast_ref::<ConcurrencyFailure>())
+    {
+        (
+            "test.concurrency".to_string(),
+            failure.to_string(),
+            serde_json::to_value(&failure.report).ok(),
+        )
+    } else if let Some(denied) = err
+        .chain()
+        .find_map(|source| source.downcast_ref::<CapabilityDeniedError>())
+    {
+        (
+            "test.capability.denied".to_string(),
+            denied.to_string(),
+            Some(serde_json::json!({ "capability": denied.capability })),
+        )
+    } else if let Some(config_err) = err
+        .chain()
+        .find_map(|source| source.downcast_ref::<ConfigSchemaError>())
+    {
+        (
+            "test.config.invalid".to_string(),
+            config_err.to_string(),
+            Some(serde_json::json!({ "violations": config_err.violations })),
+        )
+    } else if let Some(quota_err) = err
+        .chain()
+        .find_map(|source| source.downcast_ref::<FsQuotaExceeded>())
+    {
+        (
+            "test.fs_quota".to_string(),
+            quota_err.to_string(),
+            Some(serde_json::json!({
+                "limit": quota_err.quota_bytes,
+                "used": quota_err.used_bytes,
             })),
         )
     } else if let Some(component_err) = err
@@ -907,6 +1891,12 @@ fn error_payload_from_anyhow(err: &anyhow::Error) -> TestErrorPayload {
             component_err.message.clone(),
             component_error_details(component_err),
         )
+    } else if let Some(trap) = err.downcast_ref::<GuestTrap>() {
+        (
+            "test.trap".to_string(),
+            trap.to_string(),
+            Some(serde_json::json!({ "trap": trap })),
+        )
     } else {
         ("test.failure".to_string(), err.to_string(), None)
     };
@@ -937,6 +1927,11 @@ fn error_payload_from_anyhow(err: &anyhow::Error) -> TestErrorPayload {
     }
 }
 
+const TRACE_VERSION: u8 = 2;
+
+/// The `--trace-out` file, described by `schemas/v1/test-trace.schema.json`.
+/// The top-level operation and hashes describe the first step, as in
+/// version 1; `steps` has one record per step that ran.
 #[derive(Debug, Serialize)]
 struct TraceRecord {
     trace_version: u8,
@@ -945,26 +1940,149 @@ struct TraceRecord {
     input_hash: Option<String>,
     output_hash: Option<String>,
     duration_ms: u64,
+    /// `fixed`, `seeded` or `real`.
+    random: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    random_seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    steps: Vec<TraceStep>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<TestErrorPayload>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct TraceStep {
+    /// 1-based position in the plan or `--op` list.
+    index: usize,
+    operation: String,
+    input_hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_hash: Option<String>,
+    /// `ok` or `error`.
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_code: Option<String>,
+    /// Only known for steps that returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usage: Option<TraceUsage>,
+    host_calls: TraceHostCalls,
+    logs: Vec<TraceLog>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TraceUsage {
+    instantiate_ms: u64,
+    run_ms: u64,
+    peak_memory_bytes: usize,
+    table_growth: u64,
+    peak_open_handles: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fuel_consumed: Option<u64>,
+    host_call_count: u64,
+}
+
+/// Distinct targets touched during one step, by import family. Secret and
+/// state values are never included, only their keys.
+#[derive(Debug, Clone, Default, Serialize)]
+struct TraceHostCalls {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    http: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    secrets: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    state_read: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    state_write: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    state_delete: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    state_list: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fs: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    net: Vec<String>,
+}
+
+impl TraceHostCalls {
+    fn from_entries(entries: Vec<AuditEntry>, secrets: &[String]) -> Self {
+        let mut calls = Self::default();
+        for entry in entries {
+            let list = match entry.kind {
+                AuditKind::Http => &mut calls.http,
+                AuditKind::Secret => &mut calls.secrets,
+                AuditKind::StateRead => &mut calls.state_read,
+                AuditKind::StateWrite => &mut calls.state_write,
+                AuditKind::StateDelete => &mut calls.state_delete,
+                AuditKind::StateList => &mut calls.state_list,
+                AuditKind::Fs => &mut calls.fs,
+                AuditKind::Net => &mut calls.net,
+            };
+            let target = redact_string(&entry.target, secrets);
+            if !list.contains(&target) {
+                list.push(target);
+            }
+        }
+        calls
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TraceLog {
+    level: &'static str,
+    target: String,
+    message: String,
+}
+
+/// What the component declared next to what it actually did, for review.
+#[derive(Debug, Serialize)]
+struct AuditReport<'a> {
+    component_id: &'a str,
+    version: String,
+    declared: &'a Capabilities,
+    entries: Vec<AuditEntry>,
+}
+
+fn write_audit_report(
+    path: &Path,
+    manifest: &ComponentManifest,
+    audit: &AuditLog,
+    secrets: &[String],
+) -> Result<()> {
+    let mut entries = audit.entries();
+    for entry in &mut entries {
+        entry.target = redact_string(&entry.target, secrets);
+        if let Some(detail) = entry.detail.as_mut() {
+            *detail = redact_string(detail, secrets);
+        }
+    }
+    let report = AuditReport {
+        component_id: manifest.id.as_str(),
+        version: manifest.version.to_string(),
+        declared: &manifest.capabilities,
+        entries,
+    };
+    let json = serde_json::to_string_pretty(&report)?;
+    fs::write(path, json).with_context(|| format!("write audit log {}", path.display()))
+}
+
 struct TraceContext {
     out_path: Option<PathBuf>,
     component_id: String,
     operation: String,
     input_hash: Option<String>,
     output_hash: Option<String>,
+    random: WasiRandom,
+    profile: Option<String>,
+    steps: Vec<TraceStep>,
+    /// Guest logs since the last recorded step; `None` without a trace.
+    logs: Option<Arc<Mutex<Vec<TraceLog>>>>,
 }
 
 impl TraceContext {
-    fn new(
-        out_path: Option<&Path>,
-        manifest: &ComponentManifest,
-        steps: &[(String, Value)],
-    ) -> Self {
+    fn new(out_path: Option<&Path>, manifest: &ComponentManifest, steps: &[Step]) -> Self {
         let (operation, input_hash) = match steps.first() {
-            Some((op, input)) => (op.clone(), Some(hash_json_value(input))),
+            Some(step) => (step.op.clone(), Some(hash_json_value(&step.input))),
             None => ("unknown".to_string(), None),
         };
         Self {
@@ -973,25 +2091,98 @@ impl TraceContext {
             operation,
             input_hash,
             output_hash: None,
+            random: WasiRandom::default(),
+            profile: None,
+            steps: Vec::new(),
+            logs: out_path.map(|_| Arc::default()),
         }
     }
 
+    fn log_buffer(&self) -> Option<Arc<Mutex<Vec<TraceLog>>>> {
+        self.logs.clone()
+    }
+
+    /// Records one invoke together with the host calls and guest logs it
+    /// produced. A no-op when no trace is being written.
+    fn record_step(
+        &mut self,
+        step: &Step,
+        outcome: &Result<InvokeOutcome>,
+        host_calls: Vec<AuditEntry>,
+        secrets: &[String],
+    ) {
+        let Some(logs) = &self.logs else {
+            return;
+        };
+        let logs = std::mem::take(&mut *logs.lock().expect("trace log mutex poisoned"));
+        let (output_hash, error_code, usage) = match outcome {
+            Ok(outcome) => (
+                Some(hash_bytes(outcome.output_json.as_bytes())),
+                None,
+                Some(TraceUsage {
+                    instantiate_ms: outcome.instantiate_ms,
+                    run_ms: outcome.run_ms,
+                    peak_memory_bytes: outcome.peak_memory_bytes,
+                    table_growth: outcome.table_growth,
+                    peak_open_handles: outcome.peak_open_handles,
+                    fuel_consumed: outcome.fuel_consumed,
+                    host_call_count: outcome.host_call_count,
+                }),
+            ),
+            Err(err) => (None, Some(error_payload_from_anyhow(err).code), None),
+        };
+        self.steps.push(TraceStep {
+            index: step.index,
+            operation: step.op.clone(),
+            input_hash: hash_json_value(&step.input),
+            output_hash,
+            status: if error_code.is_none() { "ok" } else { "error" },
+            error_code,
+            usage,
+            host_calls: TraceHostCalls::from_entries(host_calls, secrets),
+            logs,
+        });
+    }
+
     fn write(&self, duration_ms: u64, error: Option<TestErrorPayload>) -> Result<()> {
         let Some(path) = self.out_path.as_deref() else {
             return Ok(());
         };
-        let record = TraceRecord {
-            trace_version: 1,
+        let json = serde_json::to_string_pretty(&self.record(duration_ms, error))
+            .context("serialize trace JSON")?;
+        fs::write(path, json).with_context(|| format!("write trace {}", path.display()))?;
+        Ok(())
+    }
+
+    fn record(&self, duration_ms: u64, error: Option<TestErrorPayload>) -> TraceRecord {
+        TraceRecord {
+            trace_version: TRACE_VERSION,
             component_id: self.component_id.clone(),
             operation: self.operation.clone(),
             input_hash: self.input_hash.clone(),
             output_hash: self.output_hash.clone(),
             duration_ms,
+            random: match self.random {
+                WasiRandom::Fixed => "fixed",
+                WasiRandom::Seed(_) => "seeded",
+                WasiRandom::Real => "real",
+            },
+            random_seed: match self.random {
+                WasiRandom::Seed(seed) => Some(seed),
+                _ => None,
+            },
+            profile: self.profile.clone(),
+            steps: self.steps.clone(),
             error,
-        };
-        let json = serde_json::to_string_pretty(&record).context("serialize trace JSON")?;
-        fs::write(path, json).with_context(|| format!("write trace {}", path.display()))?;
-        Ok(())
+        }
+    }
+}
+
+fn wasi_random(args: &TestArgs) -> WasiRandom {
+    match (args.random_seed, args.random) {
+        (Some(seed), _) => WasiRandom::Seed(seed),
+        (None, RandomMode::Real) => WasiRandom::Real,
+        (None, RandomMode::Fixed) => WasiRandom::Fixed,
     }
 }
 
@@ -1013,6 +2204,51 @@ fn duration_ms(duration: std::time::Duration) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_harness::AuditOutcome;
+    use serde_json::json;
+
+    #[test]
+    fn config_defaults_come_from_schema_and_are_validated() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "required": ["mode", "retry", "endpoint"],
+            "properties": {
+                "mode": { "type": "string", "default": "fast" },
+                "retry": {
+                    "type": "object",
+                    "properties": { "attempts": { "type": "integer", "default": 3 } }
+                },
+                "endpoint": { "type": "string" }
+            }
+        });
+        let defaults = schema_default(&schema).expect("object defaults");
+        assert_eq!(
+            defaults,
+            serde_json::json!({ "mode": "fast", "retry": { "attempts": 3 } })
+        );
+
+        let err = check_config(&schema, &defaults, ConfigOrigin::SchemaDefaults)
+            .expect_err("endpoint has no default");
+        let payload = error_payload_from_anyhow(&err);
+        assert_eq!(payload.code, "test.config.invalid");
+        let details = payload.details.expect("details");
+        let violations = &details["violations"];
+        assert_eq!(violations[0]["pointer"], "/");
+        assert!(
+            violations[0]["message"]
+                .as_str()
+                .unwrap()
+                .contains("endpoint")
+        );
+
+        let err = check_config(
+            &schema,
+            &serde_json::json!({ "mode": 1, "retry": {}, "endpoint": "x" }),
+            ConfigOrigin::Provided,
+        )
+        .expect_err("mode must be a string");
+        assert!(err.to_string().contains("/mode:"), "{err}");
+    }
 
     #[test]
     fn raw_output_preserves_legacy_error_shape() {
@@ -1023,6 +2259,7 @@ mod tests {
         };
         let failure = TestRunFailure {
             payload: payload.clone(),
+            warnings: Vec::new(),
             world: canonical_component_world().to_string(),
             wasm: PathBuf::from("component.wasm"),
             timing_ms: TimingMs::default(),
@@ -1046,6 +2283,7 @@ mod tests {
             error_payload_from_anyhow(&anyhow::Error::new(HarnessError::Timeout { timeout_ms: 1 }));
         let failure = TestRunFailure {
             payload,
+            warnings: Vec::new(),
             world: canonical_component_world().to_string(),
             wasm: PathBuf::from("component.wasm"),
             timing_ms: TimingMs::default(),
@@ -1061,6 +2299,7 @@ mod tests {
         let value: Value = serde_json::from_str(&rendered).expect("envelope json");
         assert_eq!(value["status"], "error");
         assert_eq!(value["diagnostics"][0]["code"], "test.timeout");
+        assert_eq!(value["exit_code"], 6);
     }
 
     #[test]
@@ -1070,6 +2309,7 @@ mod tests {
         }));
         let failure = TestRunFailure {
             payload,
+            warnings: Vec::new(),
             world: canonical_component_world().to_string(),
             wasm: PathBuf::from("component.wasm"),
             timing_ms: TimingMs::default(),
@@ -1084,6 +2324,136 @@ mod tests {
         .render_json();
         let value: Value = serde_json::from_str(&rendered).expect("envelope json");
         assert_eq!(value["diagnostics"][0]["code"], "test.memory_limit");
+        assert_eq!(value["exit_code"], 7);
+    }
+
+    #[test]
+    fn envelope_reports_open_handle_count() {
+        let payload =
+            error_payload_from_anyhow(&anyhow::Error::new(HarnessError::OpenHandleLimit {
+                max_open_handles: 16,
+                open_handles: 17,
+            }));
+        assert_eq!(payload.code, "test.open_handle_limit");
+        let details = payload.details.expect("details");
+        assert_eq!(details["limit"], 16);
+        assert_eq!(details["open_handles"], 17);
+    }
+
+    #[test]
+    fn envelope_reports_output_size() {
+        let payload =
+            error_payload_from_anyhow(&anyhow::Error::new(HarnessError::OutputTooLarge {
+                max_output_bytes: 1024,
+                size: 4096,
+            }));
+        assert_eq!(payload.code, "test.output.limit");
+        let details = payload.details.expect("details");
+        assert_eq!(details["limit"], 1024);
+        assert_eq!(details["actual"], 4096);
+    }
+
+    #[test]
+    fn env_vars_are_limited_to_the_allow_list() {
+        let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/manifests/valid.component.json");
+        let manifest_raw = fs::read_to_string(&manifest_path).expect("manifest");
+        let manifest = parse_manifest(&manifest_raw).expect("manifest parse");
+
+        let vars = resolve_env_vars(&manifest, &["RUST_LOG=debug".to_string()], false)
+            .expect("allow-listed var");
+        assert_eq!(vars, vec![("RUST_LOG".to_string(), "debug".to_string())]);
+
+        let err = resolve_env_vars(&manifest, &["HOME=/root".to_string()], true).unwrap_err();
+        assert!(err.to_string().contains("HOME"), "{err}");
+        let payload = error_payload_from_anyhow(&err);
+        assert_eq!(payload.code, "test.capability.denied");
+        assert_eq!(exit_code_for(&payload.code), ExitCode::CapabilityDenied);
+        assert!(resolve_env_vars(&manifest, &["RUST_LOG".to_string()], false).is_err());
+    }
+
+    #[test]
+    fn profiles_are_checked_against_the_manifest() {
+        let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/manifests/valid.component.json");
+        let manifest_raw = fs::read_to_string(&manifest_path).expect("manifest");
+        let manifest = parse_manifest(&manifest_raw).expect("manifest parse");
+
+        assert_eq!(
+            select_profile(&manifest, None).unwrap().as_deref(),
+            Some("stateless")
+        );
+        assert_eq!(
+            select_profile(&manifest, Some("cached"))
+                .unwrap()
+                .as_deref(),
+            Some("cached")
+        );
+        let err = select_profile(&manifest, Some("edge")).unwrap_err();
+        assert!(err.to_string().contains("stateless, cached"), "{err}");
+
+        assert_eq!(
+            profile_trace_path(Path::new("out/trace.json"), "cached"),
+            Path::new("out/trace.cached.json")
+        );
+        assert_eq!(
+            profile_trace_path(Path::new("trace"), "cached"),
+            Path::new("trace.cached")
+        );
+    }
+
+    #[test]
+    fn warns_on_error_codes_missing_from_the_catalog() {
+        let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/manifests/valid.component.json");
+        let mut value: Value =
+            serde_json::from_str(&fs::read_to_string(&manifest_path).expect("manifest"))
+                .expect("manifest json");
+        let manifest = parse_manifest(&value.to_string()).expect("manifest parse");
+        assert!(
+            undeclared_error_warning(&manifest, "handle_message", "upstream.timeout").is_none(),
+            "manifests without a catalog are not checked"
+        );
+
+        value["errors"] = serde_json::json!([
+            { "code": "upstream.timeout", "retryable": true, "operations": ["handle_message"] }
+        ]);
+        let manifest = parse_manifest(&value.to_string()).expect("manifest parse");
+        assert!(
+            undeclared_error_warning(&manifest, "handle_message", "upstream.timeout").is_none()
+        );
+        let warning = undeclared_error_warning(&manifest, "handle_message", "bad.input")
+            .expect("undeclared code");
+        assert_eq!(warning.severity, "warning");
+        assert_eq!(warning.code, "test.error.undeclared");
+    }
+
+    #[test]
+    fn mounts_map_to_host_dirs_or_temp_sandboxes() {
+        let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/manifests/valid.component.json");
+        let manifest_raw = fs::read_to_string(&manifest_path).expect("manifest");
+        let manifest = parse_manifest(&manifest_raw).expect("manifest parse");
+        let host = tempfile::TempDir::new().expect("tempdir");
+
+        let mapped = format!("assets={}", host.path().display());
+        let sandbox = resolve_fs_sandbox(&manifest, &[mapped], true, false, None).expect("sandbox");
+        let preopens = sandbox.preopens();
+        assert_eq!(preopens.len(), 1);
+        assert_eq!(preopens[0].host_path, host.path());
+        assert_eq!(preopens[0].guest_path, "/assets");
+        assert!(
+            preopens[0].read_only,
+            "read_only mode wins over --allow-fs-write"
+        );
+
+        let sandbox = resolve_fs_sandbox(&manifest, &[], false, true, None).expect("sandbox");
+        let cwd = std::env::current_dir().expect("cwd");
+        assert_ne!(sandbox.preopens()[0].host_path, cwd);
+
+        let err = resolve_fs_sandbox(&manifest, &["cache=/tmp".to_string()], false, true, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("cache"), "{err}");
     }
 
     #[test]
@@ -1096,19 +2466,25 @@ mod tests {
             fs_caps.mode = FilesystemMode::Sandbox;
         }
 
-        let preopens = resolve_wasi_preopens(&manifest, false, false).expect("preopens");
+        let preopens = resolve_fs_sandbox(&manifest, &[], false, false, None)
+            .expect("sandbox")
+            .preopens();
         assert!(
             preopens.iter().all(|preopen| preopen.read_only),
             "expected read-only preopens by default"
         );
 
-        let preopens = resolve_wasi_preopens(&manifest, true, false).expect("preopens");
+        let preopens = resolve_fs_sandbox(&manifest, &[], true, false, None)
+            .expect("sandbox")
+            .preopens();
         assert!(
             preopens.iter().all(|preopen| !preopen.read_only),
             "expected writable preopens when allowed"
         );
 
-        let preopens = resolve_wasi_preopens(&manifest, true, true).expect("preopens");
+        let preopens = resolve_fs_sandbox(&manifest, &[], true, true, None)
+            .expect("sandbox")
+            .preopens();
         assert!(
             preopens.iter().all(|preopen| preopen.read_only),
             "expected dry-run to force read-only preopens"
@@ -1135,4 +2511,125 @@ mod tests {
         assert!(!payload.message.contains("super-secret"));
         assert_eq!(payload.details.unwrap()["hint"], "***REDACTED***");
     }
+
+    #[test]
+    fn redacted_state_dump_entries_keep_valid_base64() {
+        let entry = |value: &[u8]| StateDumpEntry {
+            env: "dev".into(),
+            tenant: "tenant".into(),
+            team: None,
+            user_present: false,
+            prefix: "test".into(),
+            key: "token".into(),
+            value_base64: BASE64_STANDARD.encode(value),
+            redacted: false,
+        };
+        let mut dump = vec![entry(b"bearer super-secret"), entry(b"plain")];
+        redact_state_dump(&mut dump, &["super-secret".to_string()]);
+
+        assert!(dump[0].redacted);
+        assert!(BASE64_STANDARD.decode(&dump[0].value_base64).is_ok());
+        let json = serde_json::to_value(&dump).expect("serialize");
+        assert_eq!(json[0]["redacted"], true);
+        assert_eq!(json[0]["value_base64"], "");
+        assert!(json[1].get("redacted").is_none());
+        assert_eq!(json[1]["value_base64"], BASE64_STANDARD.encode(b"plain"));
+    }
+
+    #[test]
+    fn redacts_describe_pointers() {
+        let mut value = serde_json::json!({
+            "user": { "password": "hunter2", "name": "ada" },
+            "items": [{ "card": "4111" }]
+        });
+        redact_pointers(
+            &mut value,
+            &[
+                "/user/password".to_string(),
+                "/items/0/card".to_string(),
+                "/missing".to_string(),
+            ],
+        );
+        assert_eq!(value["user"]["password"], "***REDACTED***");
+        assert_eq!(value["user"]["name"], "ada");
+        assert_eq!(value["items"][0]["card"], "***REDACTED***");
+        assert!(value.get("missing").is_none());
+    }
+
+    #[test]
+    fn trace_records_steps_and_matches_shipped_schema() {
+        let schema: Value =
+            serde_json::from_str(include_str!("../../schemas/v1/test-trace.schema.json"))
+                .expect("trace schema JSON");
+        let validator = jsonschema::validator_for(&schema).expect("trace schema compiles");
+
+        let mut trace = TraceContext {
+            out_path: None,
+            component_id: "com.example.demo".into(),
+            operation: "get".into(),
+            input_hash: Some(hash_json_value(&json!({ "key": "a" }))),
+            output_hash: None,
+            random: WasiRandom::Seed(7),
+            profile: Some("cached".into()),
+            steps: Vec::new(),
+            logs: Some(Arc::default()),
+        };
+        trace.log_buffer().unwrap().lock().unwrap().push(TraceLog {
+            level: "info",
+            target: "demo".into(),
+            message: "token s3cret".into(),
+        });
+        let step = Step {
+            index: 1,
+            op: "get".into(),
+            input: json!({ "key": "a" }),
+            overrides: InvokeOverrides::default(),
+            expect: None,
+        };
+        let host_calls = [
+            (AuditKind::Http, "GET https://api.example.com/?t=s3cret"),
+            (AuditKind::StateRead, "a"),
+            (AuditKind::StateRead, "a"),
+        ]
+        .map(|(kind, target)| AuditEntry {
+            timestamp_ms: 0,
+            kind,
+            target: target.into(),
+            outcome: AuditOutcome::Ok,
+            detail: None,
+        })
+        .to_vec();
+        let outcome = Ok(InvokeOutcome {
+            output_json: "{}".into(),
+            table_growth: 12,
+            host_call_count: 3,
+            ..InvokeOutcome::default()
+        });
+        trace.record_step(&step, &outcome, host_calls, &["s3cret".to_string()]);
+        let failed = Err(anyhow::Error::new(ComponentInvokeError {
+            code: "not_found".into(),
+            message: "missing".into(),
+            retryable: false,
+            backoff_ms: None,
+            details: None,
+        }));
+        trace.record_step(&Step { index: 2, ..step }, &failed, Vec::new(), &[]);
+
+        let record = serde_json::to_value(trace.record(12, None)).unwrap();
+        assert!(validator.is_valid(&record), "{record:#}");
+        assert_eq!(record["trace_version"], 2);
+        assert_eq!(record["profile"], "cached");
+        let steps = record["steps"].as_array().unwrap();
+        assert_eq!(steps[0]["host_calls"]["state_read"], json!(["a"]));
+        assert_eq!(
+            steps[0]["host_calls"]["http"],
+            json!(["GET https://api.example.com/?t=***REDACTED***"])
+        );
+        assert_eq!(steps[0]["logs"][0]["message"], "token s3cret");
+        assert_eq!(steps[0]["usage"]["table_growth"], 12);
+        assert_eq!(steps[0]["usage"]["host_call_count"], 3);
+        assert_eq!(steps[1]["status"], "error");
+        assert_eq!(steps[1]["error_code"], "not_found");
+        assert!(steps[1]["logs"].as_array().unwrap().is_empty());
+    }
 }
@@ -0,0 +1,342 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use clap::Args;
+use greentic_types::schemas::component::v0_6_0::QaMode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::doctor::apply_answers;
+use super::inspect::load_describe;
+use super::test::harness_builder_for_manifest;
+use crate::compat::check_compat;
+use crate::config::resolve_manifest_path;
+use crate::manifest::{ComponentManifest, parse_manifest_value, resolve_schema_refs};
+use crate::test_harness::{StateDumpEntry, TestHarness};
+
+/// Both versions read and write state under this prefix, so whatever the
+/// old component stored is where the new one looks for it.
+const STATE_PREFIX: &str = "upgrade-test";
+
+#[derive(Args, Debug, Clone)]
+pub struct UpgradeTestArgs {
+    /// Currently deployed component wasm
+    #[arg(long, value_name = "PATH")]
+    pub from: PathBuf,
+    /// Candidate component wasm
+    #[arg(long, value_name = "PATH")]
+    pub to: PathBuf,
+    /// Upgrade scenario: config, answers, seed state and the steps to run
+    /// before and after the upgrade
+    #[arg(long, value_name = "PATH")]
+    pub state: PathBuf,
+    /// Manifest of the candidate component (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+    /// Manifest of the deployed component (defaults to --manifest)
+    #[arg(long, value_name = "PATH")]
+    pub from_manifest: Option<PathBuf>,
+    /// Emit the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// The `--state` file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpgradeScenario {
+    /// Config the deployed component runs with.
+    #[serde(default)]
+    pub config: Option<Value>,
+    /// Answers for the candidate's `update` QA.
+    #[serde(default)]
+    pub answers: Option<Value>,
+    /// State present before the first step, as KEY -> base64.
+    #[serde(default)]
+    pub seed: BTreeMap<String, String>,
+    /// Invocations against the deployed component.
+    #[serde(default)]
+    pub before: Vec<UpgradeStep>,
+    /// Invocations against the candidate once state and config are migrated.
+    pub after: Vec<UpgradeStep>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpgradeStep {
+    pub op: String,
+    #[serde(default = "empty_object")]
+    pub input: Value,
+    /// Output the step must return, when given.
+    #[serde(default)]
+    pub expect: Option<Value>,
+}
+
+fn empty_object() -> Value {
+    Value::Object(Default::default())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpgradeReport {
+    pub from_version: String,
+    pub to_version: String,
+    /// What `compat` predicts from the two describes.
+    pub compat_claims_compatible: bool,
+    pub before: Vec<StepResult>,
+    /// Config the candidate was given after `apply-answers(update)`.
+    pub migrated_config: Value,
+    /// Keys carried from the deployed component into the candidate.
+    pub state_keys: Vec<String>,
+    pub after: Vec<StepResult>,
+}
+
+impl UpgradeReport {
+    pub fn passed(&self) -> bool {
+        self.before.iter().chain(&self.after).all(|step| step.ok)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepResult {
+    pub op: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub fn run(args: &UpgradeTestArgs) -> Result<UpgradeReport> {
+    let raw = fs::read_to_string(&args.state)
+        .with_context(|| format!("read upgrade scenario {}", args.state.display()))?;
+    let scenario: UpgradeScenario = serde_json::from_str(&raw)
+        .with_context(|| format!("parse upgrade scenario {}", args.state.display()))?;
+    if scenario.after.is_empty() {
+        bail!("upgrade scenario needs at least one `after` step");
+    }
+    let to_manifest = load_manifest(&args.manifest)?;
+    let from_manifest = match &args.from_manifest {
+        Some(path) => load_manifest(path)?,
+        None => to_manifest.clone(),
+    };
+
+    let from_describe = load_describe(&args.from).map_err(|err| anyhow!(err))?;
+    let to_describe = load_describe(&args.to).map_err(|err| anyhow!(err))?;
+    let compat = check_compat(&from_describe, &to_describe);
+
+    let mut seeds = Vec::new();
+    for (key, value) in &scenario.seed {
+        let bytes = BASE64_STANDARD
+            .decode(value)
+            .with_context(|| format!("invalid base64 for seed key `{key}`"))?;
+        seeds.push((key.clone(), bytes));
+    }
+    let old = build_harness(&args.from, &from_manifest, scenario.config.clone(), seeds)?;
+    let before = run_steps(&old, &scenario.before);
+    let carried = scoped_state(old.state_dump())?;
+
+    let empty = empty_object();
+    let migrated_config = apply_answers(
+        &args.to,
+        QaMode::Update,
+        scenario.config.as_ref().unwrap_or(&empty),
+        scenario.answers.as_ref().unwrap_or(&empty),
+    )
+    .map_err(|err| anyhow!("apply-answers(update) on {}: {err}", args.to.display()))?;
+
+    let state_keys = carried.iter().map(|(key, _)| key.clone()).collect();
+    let new = build_harness(
+        &args.to,
+        &to_manifest,
+        Some(migrated_config.clone()),
+        carried,
+    )?;
+    let after = run_steps(&new, &scenario.after);
+
+    let report = UpgradeReport {
+        compat_claims_compatible: compat.is_compatible(),
+        from_version: compat.old_version,
+        to_version: compat.new_version,
+        before,
+        migrated_config,
+        state_keys,
+        after,
+    };
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        emit_human(&report);
+    }
+    if !report.passed() {
+        let claim = if report.compat_claims_compatible {
+            " although compat reports the versions as drop-in compatible"
+        } else {
+            ""
+        };
+        bail!(
+            "upgrade-test: {} -> {} failed{claim}",
+            report.from_version,
+            report.to_version
+        );
+    }
+    Ok(report)
+}
+
+#[derive(Clone)]
+struct LoadedManifest {
+    value: Value,
+    manifest: ComponentManifest,
+}
+
+fn load_manifest(path: &Path) -> Result<LoadedManifest> {
+    let manifest_path = resolve_manifest_path(path);
+    let raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let mut value: Value = serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    let manifest_dir = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    resolve_schema_refs(&mut value, manifest_dir).context("resolve manifest schema references")?;
+    let manifest = parse_manifest_value(value.clone())
+        .with_context(|| format!("parse manifest {}", manifest_path.display()))?;
+    Ok(LoadedManifest { value, manifest })
+}
+
+fn build_harness(
+    wasm: &Path,
+    manifest: &LoadedManifest,
+    config: Option<Value>,
+    seeds: Vec<(String, Vec<u8>)>,
+) -> Result<TestHarness> {
+    let wasm_bytes = fs::read(wasm).with_context(|| format!("read wasm {}", wasm.display()))?;
+    let (mut builder, _sandbox) = harness_builder_for_manifest(
+        wasm_bytes,
+        &manifest.value,
+        &manifest.manifest,
+        config,
+        None,
+    )?;
+    builder = builder.state_prefix(STATE_PREFIX);
+    builder.config_mut().state_seeds = seeds;
+    builder.build()
+}
+
+fn run_steps(harness: &TestHarness, steps: &[UpgradeStep]) -> Vec<StepResult> {
+    steps
+        .iter()
+        .map(|step| {
+            let outcome = harness
+                .invoke(&step.op, &step.input)
+                .map_err(|err| format!("{err:#}"))
+                .and_then(|outcome| {
+                    serde_json::from_str::<Value>(&outcome.output_json)
+                        .map_err(|err| format!("output is not JSON: {err}"))
+                });
+            match outcome {
+                Ok(output) => {
+                    let error = step
+                        .expect
+                        .as_ref()
+                        .filter(|expected| **expected != output)
+                        .map(|expected| format!("expected {expected}, got {output}"));
+                    StepResult {
+                        op: step.op.clone(),
+                        ok: error.is_none(),
+                        output: Some(output),
+                        error,
+                    }
+                }
+                Err(error) => StepResult {
+                    op: step.op.clone(),
+                    ok: false,
+                    output: None,
+                    error: Some(error),
+                },
+            }
+        })
+        .collect()
+}
+
+/// The deployed component's state under [`STATE_PREFIX`], decoded for
+/// seeding the candidate.
+fn scoped_state(dump: Vec<StateDumpEntry>) -> Result<Vec<(String, Vec<u8>)>> {
+    dump.into_iter()
+        .filter(|entry| entry.prefix == STATE_PREFIX)
+        .map(|entry| {
+            let bytes = BASE64_STANDARD
+                .decode(&entry.value_base64)
+                .with_context(|| format!("decode state `{}`", entry.key))?;
+            Ok((entry.key, bytes))
+        })
+        .collect()
+}
+
+fn emit_human(report: &UpgradeReport) {
+    println!(
+        "upgrade-test: {} -> {} (compat: {})",
+        report.from_version,
+        report.to_version,
+        if report.compat_claims_compatible {
+            "drop-in compatible"
+        } else {
+            "incompatible"
+        }
+    );
+    for (phase, steps) in [("before", &report.before), ("after", &report.after)] {
+        for step in steps {
+            match &step.error {
+                None => println!("  {phase:<6} ok     {}", step.op),
+                Some(error) => println!("  {phase:<6} FAILED {}: {error}", step.op),
+            }
+        }
+        if phase == "before" {
+            println!(
+                "  migrated config: {}; carried {} state key(s)",
+                report.migrated_config,
+                report.state_keys.len()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn scenarios_parse_and_only_the_shared_prefix_is_carried() {
+        let scenario: UpgradeScenario = serde_json::from_value(json!({
+            "config": { "greeting": "hi" },
+            "seed": { "counter": "AQ==" },
+            "before": [{ "op": "increment" }],
+            "after": [{ "op": "read", "expect": { "count": 2 } }]
+        }))
+        .expect("scenario");
+        assert_eq!(scenario.before[0].input, json!({}));
+        assert_eq!(scenario.after[0].expect, Some(json!({ "count": 2 })));
+        assert!(serde_json::from_value::<UpgradeScenario>(json!({ "steps": [] })).is_err());
+
+        let entry = |prefix: &str, key: &str| StateDumpEntry {
+            env: "dev".into(),
+            tenant: "tenant".into(),
+            team: None,
+            user_present: false,
+            prefix: prefix.into(),
+            key: key.into(),
+            value_base64: "AQ==".into(),
+            redacted: false,
+        };
+        let carried = scoped_state(vec![
+            entry(STATE_PREFIX, "counter"),
+            entry("test/other", "stray"),
+        ])
+        .expect("decode");
+        assert_eq!(carried, vec![("counter".to_string(), vec![1_u8])]);
+    }
+}
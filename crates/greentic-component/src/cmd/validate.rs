@@ -0,0 +1,393 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use component_manifest::diagnostic::escape_pointer_token;
+use greentic_component_runtime::{CATALOG_FILE, Catalog};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::cmd::flow::{
+    FlowIssue, validate_component_refs, validate_configurator_flows, validate_dev_flows,
+};
+use crate::config::resolve_manifest_path;
+use crate::manifest::{
+    ComponentManifest, ManifestDiagnostic, ManifestError, parse_manifest_value, resolve_schema_refs,
+};
+use crate::schema_quality::{SchemaQualityMode, validate_operation_schemas};
+use crate::signing::compute_wasm_hash;
+
+#[derive(Args, Debug, Clone)]
+pub struct ValidateArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+    /// Emit diagnostics as JSON, with pointers and line/column positions
+    #[arg(long)]
+    pub json: bool,
+    /// Allow empty operation schemas (warnings only)
+    #[arg(long)]
+    pub permissive: bool,
+    /// Verify `hashes.component_wasm` against this wasm instead of
+    /// `artifacts.component_wasm`
+    #[arg(long, value_name = "PATH")]
+    pub wasm: Option<PathBuf>,
+    /// Catalog used to resolve component-name dependencies (defaults to the
+    /// one next to the manifest)
+    #[arg(long, value_name = "PATH")]
+    pub catalog: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidateDiagnostic {
+    pub severity: Severity,
+    #[serde(flatten)]
+    pub diagnostic: ManifestDiagnostic,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidateReport {
+    pub manifest: PathBuf,
+    pub valid: bool,
+    /// Outcome of each pass: `manifest` (schema and capabilities),
+    /// `schema_quality`, `hash` and `flows`.
+    pub checks: BTreeMap<&'static str, CheckStatus>,
+    pub diagnostics: Vec<ValidateDiagnostic>,
+}
+
+/// Returned when a pass after parsing fails, so the command still exits
+/// with `manifest_invalid`.
+#[derive(Debug, thiserror::Error)]
+#[error("{manifest} failed validation with {errors} error(s)")]
+pub struct ValidationFailed {
+    pub manifest: String,
+    pub errors: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ValidateOptions {
+    pub permissive: bool,
+    pub wasm: Option<PathBuf>,
+    pub catalog: Option<PathBuf>,
+}
+
+pub fn run(args: ValidateArgs) -> Result<()> {
+    let options = ValidateOptions {
+        permissive: args.permissive,
+        wasm: args.wasm,
+        catalog: args.catalog,
+    };
+    let (report, error) = validate(&args.manifest, &options)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for diagnostic in &report.diagnostics {
+            print_diagnostic(&report.manifest, diagnostic);
+        }
+        if report.valid {
+            println!("{}: ok", report.manifest.display());
+        }
+    }
+    if let Some(err) = error {
+        return Err(
+            anyhow::Error::new(err).context(format!("{} is invalid", report.manifest.display()))
+        );
+    }
+    if !report.valid {
+        let errors = report
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .count();
+        return Err(ValidationFailed {
+            manifest: report.manifest.display().to_string(),
+            errors,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Runs every check `build` would, without building, and locates each
+/// problem in the manifest text. A parse error stops the later passes and is
+/// returned alongside the report so callers keep its type.
+pub fn validate(
+    path: &Path,
+    options: &ValidateOptions,
+) -> Result<(ValidateReport, Option<ManifestError>)> {
+    let manifest_path = resolve_manifest_path(path);
+    let text = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    validate_source(manifest_path, &text, options)
+}
+
+/// [`validate`] for manifest text that may not be saved yet (an editor
+/// buffer); `$ref`s, the wasm and the catalog still resolve next to
+/// `manifest_path`.
+pub fn validate_source(
+    manifest_path: PathBuf,
+    text: &str,
+    options: &ValidateOptions,
+) -> Result<(ValidateReport, Option<ManifestError>)> {
+    let root = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
+    let mut checks = BTreeMap::new();
+    let mut diagnostics = Vec::new();
+    let (value, parsed) = match serde_json::from_str::<Value>(text) {
+        Ok(value) => {
+            let mut resolved = value.clone();
+            let parsed = resolve_schema_refs(&mut resolved, &root)
+                .and_then(|_| parse_manifest_value(resolved.clone()))
+                .map(|manifest| (manifest, resolved));
+            (Some(value), parsed)
+        }
+        Err(err) => (None, Err(ManifestError::from(err))),
+    };
+    let (manifest, resolved) = match parsed {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            checks.insert("manifest", CheckStatus::Failed);
+            for check in ["schema_quality", "hash", "flows"] {
+                checks.insert(check, CheckStatus::Skipped);
+            }
+            diagnostics.extend(
+                err.diagnostics(value.as_ref())
+                    .into_iter()
+                    .map(|diagnostic| error(diagnostic, text)),
+            );
+            let report = ValidateReport {
+                manifest: manifest_path,
+                valid: false,
+                checks,
+                diagnostics,
+            };
+            return Ok((report, Some(err)));
+        }
+    };
+    checks.insert("manifest", CheckStatus::Passed);
+
+    let before = diagnostics.len();
+    check_schema_quality(&manifest, options.permissive, text, &mut diagnostics);
+    checks.insert("schema_quality", status(&diagnostics[before..]));
+
+    let wasm_path = match &options.wasm {
+        Some(path) => path.clone(),
+        None => manifest.wasm_artifact_path(&root),
+    };
+    if wasm_path.is_file() {
+        let before = diagnostics.len();
+        check_hash(&manifest, &wasm_path, text, &mut diagnostics)?;
+        checks.insert("hash", status(&diagnostics[before..]));
+    } else {
+        checks.insert("hash", CheckStatus::Skipped);
+    }
+
+    let catalog = match &options.catalog {
+        Some(path) => Some(
+            Catalog::from_path(path)
+                .with_context(|| format!("failed to load catalog {}", path.display()))?,
+        ),
+        None => Catalog::discover(&root)
+            .with_context(|| format!("failed to load {}", root.join(CATALOG_FILE).display()))?,
+    };
+    let before = diagnostics.len();
+    let mut issues = match validate_dev_flows(&resolved) {
+        Ok(issues) => issues,
+        Err(err) => {
+            diagnostics.push(error(
+                ManifestDiagnostic::new(
+                    "manifest.dev_flows.invalid",
+                    "/dev_flows",
+                    err.to_string(),
+                ),
+                text,
+            ));
+            Vec::new()
+        }
+    };
+    issues.extend(validate_configurator_flows(&resolved, &root));
+    issues.extend(validate_component_refs(&resolved, catalog.as_ref()));
+    diagnostics.extend(
+        issues
+            .iter()
+            .map(|issue| error(flow_diagnostic(issue), text)),
+    );
+    checks.insert("flows", status(&diagnostics[before..]));
+
+    let valid = diagnostics
+        .iter()
+        .all(|diagnostic| diagnostic.severity == Severity::Warning);
+    Ok((
+        ValidateReport {
+            manifest: manifest_path,
+            valid,
+            checks,
+            diagnostics,
+        },
+        None,
+    ))
+}
+
+fn check_schema_quality(
+    manifest: &ComponentManifest,
+    permissive: bool,
+    text: &str,
+    diagnostics: &mut Vec<ValidateDiagnostic>,
+) {
+    // Permissive mode collects every empty schema; strict mode would stop at
+    // the first one.
+    let Ok(warnings) = validate_operation_schemas(manifest, SchemaQualityMode::Permissive) else {
+        return;
+    };
+    let severity = if permissive {
+        Severity::Warning
+    } else {
+        Severity::Error
+    };
+    for warning in warnings {
+        let index = manifest
+            .operations
+            .iter()
+            .position(|operation| operation.name == warning.operation)
+            .unwrap_or_default();
+        let diagnostic = ManifestDiagnostic::new(
+            "manifest.operation.schema_empty",
+            format!("/operations/{index}/{}_schema", warning.direction),
+            format!(
+                "operation `{}` has an empty {} schema",
+                warning.operation, warning.direction
+            ),
+        )
+        .with_suggestion("describe the payload with real JSON Schema, or pass --permissive");
+        diagnostics.push(ValidateDiagnostic {
+            severity,
+            diagnostic: diagnostic.locate(text),
+        });
+    }
+}
+
+fn check_hash(
+    manifest: &ComponentManifest,
+    wasm_path: &Path,
+    text: &str,
+    diagnostics: &mut Vec<ValidateDiagnostic>,
+) -> Result<()> {
+    let actual = compute_wasm_hash(wasm_path)
+        .with_context(|| format!("failed to hash {}", wasm_path.display()))?;
+    let expected = manifest.hashes.component_wasm.as_str();
+    if actual != expected {
+        let diagnostic = ManifestDiagnostic::new(
+            "manifest.hashes.mismatch",
+            "/hashes/component_wasm",
+            format!(
+                "hash {expected} does not match {} ({actual})",
+                wasm_path.display()
+            ),
+        )
+        .with_suggestion("run `greentic-component hash` after rebuilding the wasm");
+        diagnostics.push(error(diagnostic, text));
+    }
+    Ok(())
+}
+
+/// Turns a flow issue's dotted path (`graph.edges[0].from`) into a pointer
+/// under its `dev_flows` entry, or under `dependencies` for catalog refs.
+fn flow_diagnostic(issue: &FlowIssue) -> ManifestDiagnostic {
+    let (code, prefix) = match issue.flow.as_str() {
+        "dependencies" => ("manifest.dependencies.unresolved", String::new()),
+        "configurators" => (
+            "manifest.configurators.unresolved",
+            "/configurators".to_string(),
+        ),
+        flow => (
+            "manifest.dev_flows.invalid",
+            format!("/dev_flows/{}", escape_pointer_token(flow)),
+        ),
+    };
+    ManifestDiagnostic::new(
+        code,
+        prefix + &flow_path_pointer(&issue.path),
+        issue.message.clone(),
+    )
+}
+
+/// `edges[0].from` as `/edges/0/from`.
+pub(crate) fn flow_path_pointer(path: &str) -> String {
+    let mut pointer = String::new();
+    for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+        let mut parts = segment.split('[');
+        if let Some(key) = parts.next() {
+            pointer.push('/');
+            pointer.push_str(&escape_pointer_token(key));
+        }
+        for index in parts {
+            pointer.push('/');
+            pointer.push_str(index.trim_end_matches(']'));
+        }
+    }
+    pointer
+}
+
+fn error(diagnostic: ManifestDiagnostic, text: &str) -> ValidateDiagnostic {
+    let diagnostic = match diagnostic.line {
+        Some(_) => diagnostic,
+        None => diagnostic.locate(text),
+    };
+    ValidateDiagnostic {
+        severity: Severity::Error,
+        diagnostic,
+    }
+}
+
+fn status(diagnostics: &[ValidateDiagnostic]) -> CheckStatus {
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        CheckStatus::Failed
+    } else {
+        CheckStatus::Passed
+    }
+}
+
+fn print_diagnostic(path: &Path, entry: &ValidateDiagnostic) {
+    let diagnostic = &entry.diagnostic;
+    let location = match (diagnostic.line, diagnostic.column) {
+        (Some(line), Some(column)) => format!("{}:{line}:{column}", path.display()),
+        _ => path.display().to_string(),
+    };
+    let severity = match entry.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+    println!(
+        "{location}: {severity}[{}]: {}",
+        diagnostic.code, diagnostic.message
+    );
+    if !diagnostic.pointer.is_empty() {
+        println!("  at {}", diagnostic.pointer);
+    }
+    if let Some(suggestion) = &diagnostic.suggestion {
+        println!("  help: {suggestion}");
+    }
+}
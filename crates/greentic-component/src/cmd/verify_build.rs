@@ -0,0 +1,475 @@
+#![cfg(feature = "cli")]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, anyhow, bail};
+use clap::Args;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+
+use super::build::{
+    BuildBackend, command_stdout, resolved_wasm_rustflags, sanitize_wasm_rustflags, wasm_build_args,
+};
+use crate::config::resolve_manifest_path;
+
+/// Mount point of the clean checkout inside `--container` builds.
+const CONTAINER_ROOT: &str = "/work";
+
+#[derive(Args, Debug, Clone)]
+pub struct VerifyBuildArgs {
+    /// Path to component.manifest.json (or directory containing it)
+    #[arg(long, value_name = "PATH", default_value = "component.manifest.json")]
+    pub manifest: PathBuf,
+    /// Path to the cargo binary (fallback: $CARGO, then `cargo` on PATH)
+    #[arg(long = "cargo", value_name = "PATH")]
+    pub cargo_bin: Option<PathBuf>,
+    /// Published wasm to compare with the rebuild (defaults to the manifest's wasm artifact)
+    #[arg(long, value_name = "PATH")]
+    pub artifact: Option<PathBuf>,
+    /// Build inside this container image instead of on the host
+    #[arg(long, value_name = "IMAGE")]
+    pub container: Option<String>,
+    /// Container engine used with --container
+    #[arg(
+        long,
+        value_name = "BIN",
+        default_value = "docker",
+        requires = "container"
+    )]
+    pub engine: String,
+    /// Keep the temporary checkout for inspection
+    #[arg(long)]
+    pub keep: bool,
+    /// Emit the verification report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Outcome of rebuilding a component from a clean checkout.
+#[derive(Debug, Serialize)]
+pub struct VerifyBuildReport {
+    pub commit: String,
+    /// Where the rebuild ran: `host` or `container:<image>`.
+    pub builder: String,
+    pub rebuilt_blake3: String,
+    /// Matches the subject digest of `build --attestation` statements.
+    pub rebuilt_sha256: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest_hash: Option<String>,
+    pub matches_manifest: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifact_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches_artifact: Option<bool>,
+    pub nondeterminism: Vec<NondeterminismSource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkout: Option<PathBuf>,
+}
+
+impl VerifyBuildReport {
+    pub fn reproducible(&self) -> bool {
+        self.matches_manifest && self.matches_artifact != Some(false)
+    }
+}
+
+/// Something that can make two builds of the same commit differ.
+#[derive(Debug, Serialize)]
+pub struct NondeterminismSource {
+    pub kind: &'static str,
+    pub message: String,
+}
+
+impl NondeterminismSource {
+    fn new(kind: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+pub fn run(args: &VerifyBuildArgs) -> Result<VerifyBuildReport> {
+    let report = verify(args)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Rebuilt commit {} ({})", report.commit, report.builder);
+        println!("  rebuilt   {}", report.rebuilt_blake3);
+        match &report.manifest_hash {
+            Some(hash) => println!(
+                "  manifest  {hash} ({})",
+                if report.matches_manifest {
+                    "match"
+                } else {
+                    "MISMATCH"
+                }
+            ),
+            None => println!("  manifest  <no hashes.component_wasm> (MISMATCH)"),
+        }
+        if let (Some(path), Some(hash), Some(matches)) = (
+            &report.artifact,
+            &report.artifact_hash,
+            report.matches_artifact,
+        ) {
+            println!(
+                "  artifact  {hash} ({}, {})",
+                if matches { "match" } else { "MISMATCH" },
+                path.display()
+            );
+        }
+        for source in &report.nondeterminism {
+            eprintln!("warning[{}]: {}", source.kind, source.message);
+        }
+        if let Some(checkout) = &report.checkout {
+            println!("Kept checkout at {}", checkout.display());
+        }
+    }
+    if !report.reproducible() {
+        bail!(
+            "rebuild of {} is not bit-for-bit identical{}",
+            report.commit,
+            if report.nondeterminism.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "; check the {} nondeterminism source(s) reported above",
+                    report.nondeterminism.len()
+                )
+            }
+        );
+    }
+    if !args.json {
+        println!("Build is reproducible");
+    }
+    Ok(report)
+}
+
+/// Rebuilds the manifest's component from a fresh clone of its git commit
+/// and compares the result with the recorded hash and published artifact.
+pub fn verify(args: &VerifyBuildArgs) -> Result<VerifyBuildReport> {
+    let manifest_path = resolve_manifest_path(&args.manifest);
+    let raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let manifest: JsonValue = serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+    let manifest_dir = manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+        .canonicalize()
+        .with_context(|| format!("failed to canonicalize {}", manifest_path.display()))?;
+    let artifact_rel = manifest
+        .get("artifacts")
+        .and_then(|artifacts| artifacts.get("component_wasm"))
+        .and_then(JsonValue::as_str)
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow!("manifest has no artifacts.component_wasm"))?;
+    let wasm_name = artifact_rel
+        .file_name()
+        .ok_or_else(|| anyhow!("artifacts.component_wasm must name a file"))?
+        .to_os_string();
+
+    let repo_root = command_stdout(
+        Command::new("git")
+            .args(["rev-parse", "--show-toplevel"])
+            .current_dir(&manifest_dir),
+    )
+    .map(PathBuf::from)
+    .ok_or_else(|| {
+        anyhow!(
+            "{} is not inside a git checkout; verify-build rebuilds from a commit",
+            manifest_dir.display()
+        )
+    })?
+    .canonicalize()
+    .context("failed to canonicalize the git root")?;
+    let component_rel = manifest_dir
+        .strip_prefix(&repo_root)
+        .unwrap_or(Path::new(""))
+        .to_path_buf();
+
+    let mut nondeterminism = Vec::new();
+    let head = command_stdout(
+        Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(&repo_root),
+    )
+    .ok_or_else(|| anyhow!("git checkout at {} has no commits", repo_root.display()))?;
+    if command_stdout(
+        Command::new("git")
+            .args(["status", "--porcelain"])
+            .current_dir(&repo_root),
+    )
+    .is_some_and(|status| !status.is_empty())
+    {
+        nondeterminism.push(NondeterminismSource::new(
+            "dirty-tree",
+            "the working tree has uncommitted changes that the clean checkout does not include",
+        ));
+    }
+    let provenance = manifest.get("provenance");
+    let commit = match provenance
+        .and_then(|provenance| provenance.get("git_commit"))
+        .and_then(JsonValue::as_str)
+    {
+        Some(commit) => commit.to_string(),
+        None => {
+            nondeterminism.push(NondeterminismSource::new(
+                "no-provenance",
+                format!("manifest records no provenance.git_commit; rebuilt HEAD ({head})"),
+            ));
+            head.clone()
+        }
+    };
+    if provenance
+        .and_then(|provenance| provenance.get("git_dirty"))
+        .and_then(JsonValue::as_bool)
+        == Some(true)
+    {
+        nondeterminism.push(NondeterminismSource::new(
+            "dirty-build",
+            "the recorded build ran on a working tree with uncommitted changes",
+        ));
+    }
+
+    let temp = TempDir::new().context("failed to create a temporary directory")?;
+    let checkout = temp.path().join("src");
+    git(
+        &repo_root,
+        &[
+            "clone",
+            "--quiet",
+            "--no-checkout",
+            "--no-hardlinks",
+            &repo_root.to_string_lossy(),
+            &checkout.to_string_lossy(),
+        ],
+    )?;
+    git(&checkout, &["checkout", "--quiet", "--detach", &commit]).with_context(|| {
+        format!(
+            "commit {commit} is not available in {}",
+            repo_root.display()
+        )
+    })?;
+    // Best effort: components without submodules simply have nothing to fetch.
+    let _ = git(&checkout, &["submodule", "update", "--init", "--recursive"]);
+    let build_dir = checkout.join(&component_rel);
+    nondeterminism.extend(checkout_findings(&checkout, &build_dir));
+
+    let cargo_bin = args
+        .cargo_bin
+        .clone()
+        .or_else(|| env::var_os("CARGO").map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("cargo"));
+    let backend = BuildBackend::resolve(&build_dir, &manifest, &cargo_bin)?;
+    let target_dir = build_dir.join("target");
+    let builder = match &args.container {
+        Some(image) => {
+            let work_dir = Path::new(CONTAINER_ROOT).join(&component_rel);
+            let mut cmd = Command::new(&args.engine);
+            cmd.args(["run", "--rm", "-v"])
+                .arg(format!("{}:{CONTAINER_ROOT}", checkout.display()))
+                .arg("-w")
+                .arg(&work_dir);
+            match &backend {
+                BuildBackend::Cargo(_) => {
+                    cmd.arg("-e").arg(format!(
+                        "CARGO_TARGET_DIR={}",
+                        work_dir.join("target").display()
+                    ));
+                    if let Some(flags) = resolved_wasm_rustflags() {
+                        cmd.arg("-e")
+                            .arg(format!("RUSTFLAGS={}", sanitize_wasm_rustflags(&flags)));
+                    }
+                    cmd.arg(image).arg("cargo").args(wasm_build_args(&manifest));
+                }
+                BuildBackend::Custom(spec) => {
+                    for (key, value) in &spec.env {
+                        cmd.arg("-e").arg(format!("{key}={value}"));
+                    }
+                    cmd.arg(image).args(&spec.run);
+                }
+            }
+            run_build(&mut cmd, &format!("{} run {image}", args.engine))?;
+            format!("container:{image}")
+        }
+        None => {
+            let mut cmd = backend.command(&build_dir, &manifest)?;
+            if let BuildBackend::Cargo(_) = backend {
+                cmd.env("CARGO_TARGET_DIR", &target_dir);
+            }
+            let invocation = backend.invocation(&cmd);
+            run_build(&mut cmd, &invocation)?;
+            nondeterminism.extend(toolchain_findings(provenance, &build_dir));
+            "host".to_string()
+        }
+    };
+
+    let rebuilt_path = match backend {
+        BuildBackend::Cargo(_) => target_dir.join("wasm32-wasip2/release").join(&wasm_name),
+        BuildBackend::Custom(_) => build_dir.join(&artifact_rel),
+    };
+    let rebuilt = fs::read(&rebuilt_path).with_context(|| {
+        format!(
+            "the rebuild did not produce {}",
+            rebuilt_path
+                .strip_prefix(&checkout)
+                .unwrap_or(&rebuilt_path)
+                .display()
+        )
+    })?;
+    let rebuilt_blake3 = format!("blake3:{}", blake3::hash(&rebuilt).to_hex());
+    nondeterminism.extend(embedded_path_findings(&rebuilt, &checkout));
+
+    let manifest_hash = manifest
+        .get("hashes")
+        .and_then(|hashes| hashes.get("component_wasm"))
+        .and_then(JsonValue::as_str)
+        .map(str::to_string);
+    let artifact = args
+        .artifact
+        .clone()
+        .or_else(|| Some(manifest_dir.join(&artifact_rel)).filter(|path| path.is_file()));
+    let artifact_hash = artifact
+        .as_ref()
+        .map(|path| {
+            fs::read(path)
+                .map(|bytes| format!("blake3:{}", blake3::hash(&bytes).to_hex()))
+                .with_context(|| format!("failed to read artifact {}", path.display()))
+        })
+        .transpose()?;
+
+    Ok(VerifyBuildReport {
+        commit,
+        builder,
+        rebuilt_sha256: hex::encode(Sha256::digest(&rebuilt)),
+        matches_manifest: manifest_hash.as_deref() == Some(rebuilt_blake3.as_str()),
+        manifest_hash,
+        matches_artifact: artifact_hash.as_ref().map(|hash| *hash == rebuilt_blake3),
+        artifact,
+        artifact_hash,
+        rebuilt_blake3,
+        nondeterminism,
+        checkout: args.keep.then(|| temp.keep()),
+    })
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+fn run_build(cmd: &mut Command, invocation: &str) -> Result<()> {
+    let output = cmd
+        .output()
+        .with_context(|| format!("failed to run {invocation}"))?;
+    if !output.status.success() {
+        bail!(
+            "{invocation} failed with status {}:\n{}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Inputs the clean checkout leaves unpinned.
+fn checkout_findings(checkout: &Path, build_dir: &Path) -> Vec<NondeterminismSource> {
+    let mut findings = Vec::new();
+    let has_lockfile = build_dir
+        .ancestors()
+        .take_while(|dir| dir.starts_with(checkout))
+        .any(|dir| dir.join("Cargo.lock").is_file());
+    if !has_lockfile {
+        findings.push(NondeterminismSource::new(
+            "missing-lockfile",
+            "no Cargo.lock is committed, so dependency versions are resolved at build time",
+        ));
+    }
+    let pins_toolchain = build_dir
+        .ancestors()
+        .take_while(|dir| dir.starts_with(checkout))
+        .any(|dir| {
+            dir.join("rust-toolchain.toml").is_file() || dir.join("rust-toolchain").is_file()
+        });
+    if !pins_toolchain {
+        findings.push(NondeterminismSource::new(
+            "unpinned-toolchain",
+            "no rust-toolchain.toml is committed, so the compiler version depends on the builder",
+        ));
+    }
+    findings
+}
+
+/// Compares the host toolchain with the one recorded in the manifest.
+fn toolchain_findings(
+    provenance: Option<&JsonValue>,
+    build_dir: &Path,
+) -> Vec<NondeterminismSource> {
+    let recorded = provenance
+        .and_then(|provenance| provenance.get("toolchain"))
+        .and_then(JsonValue::as_str);
+    let rustc = env::var_os("RUSTC")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("rustc"));
+    let current = command_stdout(Command::new(rustc).arg("--version").current_dir(build_dir));
+    let mut findings = Vec::new();
+    if let (Some(recorded), Some(current)) = (recorded, current)
+        && recorded != current
+    {
+        findings.push(NondeterminismSource::new(
+            "toolchain-mismatch",
+            format!("recorded build used `{recorded}`, the rebuild used `{current}`"),
+        ));
+    }
+    if resolved_wasm_rustflags().is_some() {
+        findings.push(NondeterminismSource::new(
+            "host-rustflags",
+            "WASM_RUSTFLAGS/RUSTFLAGS from the environment were applied and are not recorded in the repository",
+        ));
+    }
+    findings
+}
+
+/// Absolute build paths baked into the wasm differ between machines.
+fn embedded_path_findings(wasm: &[u8], checkout: &Path) -> Vec<NondeterminismSource> {
+    let mut prefixes = vec![checkout.to_string_lossy().into_owned()];
+    if let Some(home) = env::var_os("HOME") {
+        prefixes.push(home.to_string_lossy().into_owned());
+    }
+    prefixes
+        .into_iter()
+        .filter(|prefix| prefix.len() > 1 && contains(wasm, prefix.as_bytes()))
+        .map(|prefix| {
+            NondeterminismSource::new(
+                "embedded-path",
+                format!(
+                    "the wasm embeds the absolute path `{prefix}`; add `--remap-path-prefix` to RUSTFLAGS"
+                ),
+            )
+        })
+        .collect()
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
@@ -0,0 +1,353 @@
+//! Canonical WIT packages per component ABI, shipped in `assets/wit/<abi>/`
+//! with their dependencies under `deps/`, as `wit-parser` expects them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Subcommand};
+use include_dir::{Dir, DirEntry, include_dir};
+use serde::Serialize;
+
+use super::build::read_abi_version;
+
+static CANONICAL_WIT: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/assets/wit");
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum WitCommand {
+    /// Copy the canonical WIT packages for an ABI into a project's wit/
+    Vendor(WitVendorArgs),
+    /// Compare a project's wit/ with the canonical packages for its abi_version
+    Check(WitCheckArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct WitVendorArgs {
+    /// ABI version to vendor, e.g. `0.6.0` (defaults to the project's
+    /// `[package.metadata.greentic] abi_version`)
+    #[arg(long, value_name = "VERSION")]
+    pub abi: Option<String>,
+    /// Component project directory
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub project: PathBuf,
+    /// Overwrite vendored files that were edited locally
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct WitCheckArgs {
+    /// ABI version to check against (defaults to the project's abi_version)
+    #[arg(long, value_name = "VERSION")]
+    pub abi: Option<String>,
+    /// Component project directory
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub project: PathBuf,
+    /// Emit the drift report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// How a vendored file differs from the canonical text. Files the project
+/// added itself are not reported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WitDrift {
+    /// Relative to the project's `wit/`.
+    pub path: String,
+    /// `missing` or `modified`.
+    pub status: &'static str,
+}
+
+pub fn run(command: WitCommand) -> Result<()> {
+    match command {
+        WitCommand::Vendor(args) => vendor(&args).map(|_| ()),
+        WitCommand::Check(args) => check(&args).map(|_| ()),
+    }
+}
+
+/// ABI versions with canonical WIT, oldest first.
+pub fn available_abis() -> Vec<&'static str> {
+    let mut versions = CANONICAL_WIT
+        .dirs()
+        .filter_map(|dir| dir.path().to_str())
+        .collect::<Vec<_>>();
+    versions.sort_by_key(|version| {
+        version
+            .split('.')
+            .map(|part| part.parse::<u64>().unwrap_or_default())
+            .collect::<Vec<_>>()
+    });
+    versions
+}
+
+/// Canonical files for `abi` as `(path relative to wit/, contents)`.
+pub fn canonical_files(abi: &str) -> Result<Vec<(String, &'static str)>> {
+    let abi = normalize_abi(abi);
+    let Some(root) = CANONICAL_WIT.get_dir(abi.as_str()) else {
+        bail!(
+            "no canonical WIT for ABI `{abi}`; available: {}",
+            available_abis().join(", ")
+        );
+    };
+    let mut files = Vec::new();
+    collect_files(root, root.path(), &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+pub fn vendor(args: &WitVendorArgs) -> Result<Vec<PathBuf>> {
+    let abi = resolve_abi(args.abi.as_deref(), &args.project)?;
+    let files = canonical_files(&abi)?;
+    let wit_dir = args.project.join("wit");
+    if !args.force {
+        let edited = drift(&wit_dir, &files)
+            .into_iter()
+            .filter(|drift| drift.status == "modified")
+            .map(|drift| drift.path)
+            .collect::<Vec<_>>();
+        if !edited.is_empty() {
+            bail!(
+                "wit/ has local edits to {}; pass --force to overwrite",
+                edited.join(", ")
+            );
+        }
+    }
+    let mut written = Vec::new();
+    for (relative, contents) in files {
+        let target = wit_dir.join(&relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+        }
+        fs::write(&target, contents).with_context(|| format!("write {}", target.display()))?;
+        written.push(target);
+    }
+    println!(
+        "vendored component@{abi} WIT ({} file(s)) into {}",
+        written.len(),
+        wit_dir.display()
+    );
+    Ok(written)
+}
+
+pub fn check(args: &WitCheckArgs) -> Result<Vec<WitDrift>> {
+    let abi = resolve_abi(args.abi.as_deref(), &args.project)?;
+    let files = canonical_files(&abi)?;
+    let drift = drift(&args.project.join("wit"), &files);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&drift)?);
+    } else if drift.is_empty() {
+        println!("wit/ matches the canonical component@{abi} packages");
+    } else {
+        for entry in &drift {
+            println!("{:<8} wit/{}", entry.status, entry.path);
+        }
+    }
+    if !drift.is_empty() {
+        bail!(
+            "wit/ differs from the canonical component@{abi} packages in {} file(s); run `greentic-component wit vendor --abi {abi} --force`",
+            drift.len()
+        );
+    }
+    Ok(drift)
+}
+
+fn drift(wit_dir: &Path, files: &[(String, &str)]) -> Vec<WitDrift> {
+    files
+        .iter()
+        .filter_map(|(relative, canonical)| {
+            let status = match fs::read_to_string(wit_dir.join(relative)) {
+                Err(_) => "missing",
+                Ok(local) if normalize(&local) != normalize(canonical) => "modified",
+                Ok(_) => return None,
+            };
+            Some(WitDrift {
+                path: relative.clone(),
+                status,
+            })
+        })
+        .collect()
+}
+
+/// Ignores line endings and trailing whitespace, which editors change
+/// without touching the package.
+fn normalize(text: &str) -> String {
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
+
+/// `explicit` when given, else the project's `[package.metadata.greentic]
+/// abi_version`.
+pub(crate) fn resolve_abi(explicit: Option<&str>, project: &Path) -> Result<String> {
+    match explicit {
+        Some(abi) => Ok(normalize_abi(abi)),
+        None => read_abi_version(project)
+            .map(|abi| normalize_abi(&abi))
+            .with_context(|| {
+                format!(
+                    "{} declares no [package.metadata.greentic] abi_version; pass --abi",
+                    project.join("Cargo.toml").display()
+                )
+            }),
+    }
+}
+
+/// `0.6` and `0.6.0` name the same ABI.
+pub(crate) fn normalize_abi(abi: &str) -> String {
+    let abi = abi.trim().trim_start_matches('v');
+    match abi.split('.').count() {
+        2 => format!("{abi}.0"),
+        _ => abi.to_string(),
+    }
+}
+
+fn collect_files(
+    dir: &'static Dir<'static>,
+    root: &Path,
+    out: &mut Vec<(String, &'static str)>,
+) -> Result<()> {
+    for entry in dir.entries() {
+        match entry {
+            DirEntry::Dir(child) => collect_files(child, root, out)?,
+            DirEntry::File(file) => {
+                let relative = file
+                    .path()
+                    .strip_prefix(root)
+                    .context("canonical WIT path outside its ABI directory")?;
+                let contents = file
+                    .contents_utf8()
+                    .with_context(|| format!("{} is not UTF-8", relative.display()))?;
+                out.push((relative.to_string_lossy().replace('\\', "/"), contents));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_packages_parse_and_round_trip_through_vendor() {
+        assert!(available_abis().contains(&"0.6.0"));
+        let project = tempfile::TempDir::new().unwrap();
+        fs::write(
+            project.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[package.metadata.greentic]\nabi_version = \"0.6.0\"\n",
+        )
+        .unwrap();
+        let vendor_args = WitVendorArgs {
+            abi: None,
+            project: project.path().to_path_buf(),
+            force: false,
+        };
+        vendor(&vendor_args).unwrap();
+
+        let mut resolve = wit_parser::Resolve::default();
+        let (pkg, _) = resolve
+            .push_dir(project.path().join("wit"))
+            .expect("vendored WIT parses");
+        resolve
+            .select_world(&[pkg], Some("component-v0-v6-v0"))
+            .expect("component world");
+
+        let check_args = WitCheckArgs {
+            abi: Some("0.6".into()),
+            project: project.path().to_path_buf(),
+            json: false,
+        };
+        assert!(check(&check_args).unwrap().is_empty());
+
+        let component = project.path().join("wit/component.wit");
+        let edited = fs::read_to_string(&component)
+            .unwrap()
+            .replace("ok: bool", "ok: u8");
+        fs::write(&component, format!("{edited}\n// local\n")).unwrap();
+        fs::remove_file(project.path().join("wit/deps/types-core/core.wit")).unwrap();
+        assert_eq!(
+            drift(
+                &project.path().join("wit"),
+                &canonical_files("0.6.0").unwrap()
+            ),
+            vec![
+                WitDrift {
+                    path: "component.wit".into(),
+                    status: "modified",
+                },
+                WitDrift {
+                    path: "deps/types-core/core.wit".into(),
+                    status: "missing",
+                },
+            ]
+        );
+        assert!(vendor(&vendor_args).is_err());
+        vendor(&WitVendorArgs {
+            force: true,
+            ..vendor_args
+        })
+        .unwrap();
+    }
+
+    /// Every host interface the runtime links, as one store's data. Never
+    /// built: the test only type-checks imports.
+    #[allow(dead_code)]
+    struct HostImports {
+        progress: greentic_component_runtime::ProgressReporter,
+        log: greentic_component_runtime::GuestLogger,
+        blob: greentic_component_runtime::BlobHost,
+        sql: greentic_component_runtime::SqlHost,
+        messaging: greentic_component_runtime::MessagingHost,
+        events: greentic_component_runtime::EventsHost,
+        tools: greentic_component_runtime::ToolsHost,
+        telemetry: greentic_component_runtime::TelemetryHost,
+    }
+
+    #[test]
+    fn host_imports_world_matches_the_runtime_linker() {
+        use greentic_component_runtime as runtime;
+
+        let mut resolve = wit_parser::Resolve::default();
+        let (pkg, _) = resolve
+            .push_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/wit/0.6.0"))
+            .expect("canonical WIT parses");
+        let world = resolve
+            .select_world(&[pkg], Some("host-imports-v0-v6-v0"))
+            .expect("host imports world");
+        let mut module =
+            wit_component::dummy_module(&resolve, world, wit_parser::ManglingAndAbi::Standard32);
+        wit_component::embed_component_metadata(
+            &mut module,
+            &resolve,
+            world,
+            wit_component::StringEncoding::UTF8,
+        )
+        .unwrap();
+        let bytes = wit_component::ComponentEncoder::default()
+            .module(&module)
+            .unwrap()
+            .validate(true)
+            .encode()
+            .unwrap();
+
+        let engine = wasmtime::Engine::default();
+        let component = wasmtime::component::Component::from_binary(&engine, &bytes).unwrap();
+        let mut linker = wasmtime::component::Linker::<HostImports>::new(&engine);
+        runtime::add_progress_to_linker(&mut linker, |host| &mut host.progress).unwrap();
+        runtime::add_log_to_linker(&mut linker, |host| &mut host.log).unwrap();
+        runtime::add_blob_to_linker(&mut linker, |host| &mut host.blob).unwrap();
+        runtime::add_sql_to_linker(&mut linker, |host| &mut host.sql).unwrap();
+        runtime::add_messaging_to_linker(&mut linker, |host| &mut host.messaging).unwrap();
+        runtime::add_events_to_linker(&mut linker, |host| &mut host.events).unwrap();
+        runtime::add_tools_to_linker(&mut linker, |host| &mut host.tools).unwrap();
+        runtime::add_telemetry_to_linker(&mut linker, |host| &mut host.telemetry).unwrap();
+        // Type-checks every import of the world against the linked host
+        // functions, names and signatures alike.
+        linker
+            .instantiate_pre(&component)
+            .expect("runtime links every interface in the WIT");
+    }
+}
@@ -9,6 +9,8 @@ use clap::{ArgAction, Args, Subcommand, ValueEnum};
 use greentic_types::cbor::canonical;
 use serde_json::Value as JsonValue;
 
+use crate::cmd::new::print_hook_summary;
+use crate::scaffold::hooks::{self, HookApproval, PostGenHook};
 use crate::scaffold::validate::{
     ComponentName, ValidationError, ensure_path_available, normalize_version,
 };
@@ -50,6 +52,30 @@ pub struct WizardNewArgs {
         action = ArgAction::Append
     )]
     pub provided_capabilities: Vec<String>,
+    /// Command to run in the generated directory afterwards, e.g. "cargo fmt"; quoted
+    /// arguments are kept together like in a shell (repeatable)
+    #[arg(long = "hook", value_name = "command", action = ArgAction::Append)]
+    pub hooks: Vec<String>,
+    #[command(flatten)]
+    pub capabilities: WizardCapabilityArgs,
+}
+
+/// Host capabilities to pre-wire: manifest blocks, guest imports and example
+/// calls in `src/runtime.rs`.
+#[derive(Args, Debug, Clone, Default)]
+pub struct WizardCapabilityArgs {
+    /// Allow outbound HTTP and add an example request
+    #[arg(long = "with-http")]
+    pub http: bool,
+    /// Allow state-store reads/writes and add an example counter
+    #[arg(long = "with-state")]
+    pub state: bool,
+    /// Declare required secrets and add an example lookup
+    #[arg(long = "with-secrets", value_name = "KEY,...", value_delimiter = ',')]
+    pub secrets: Vec<String>,
+    /// Mount a host directory class at a guest path (repeatable)
+    #[arg(long = "with-fs", value_name = "mount=path", action = ArgAction::Append)]
+    pub fs: Vec<String>,
 }
 
 #[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
@@ -84,19 +110,37 @@ fn run_new(args: WizardNewArgs) -> Result<()> {
         None => None,
     };
 
+    let features = HostFeatures::from_args(&args.capabilities)?;
     let context = WizardContext {
         name: name.into_string(),
         abi_version,
         prefill_mode: args.mode,
         prefill_answers_cbor: answers.as_ref().map(|payload| payload.cbor.clone()),
         prefill_answers_json: answers.map(|payload| payload.json),
-        required_capabilities: normalize_capabilities(args.required_capabilities)?,
+        required_capabilities: normalize_capabilities(
+            args.required_capabilities
+                .into_iter()
+                .chain(features.required_capabilities())
+                .collect(),
+        )?,
         provided_capabilities: normalize_capabilities(args.provided_capabilities)?,
+        features,
     };
 
     write_template(&target, &context)?;
 
     println!("wizard: created {}", target.display());
+    // Hooks given on the command line are already confirmed by the caller.
+    let declared = args
+        .hooks
+        .iter()
+        .map(|line| PostGenHook::from_command_line(line))
+        .collect::<Result<Vec<_>>>()?;
+    let reports = hooks::run_hooks(&target, &declared, &HookApproval::Approved);
+    print_hook_summary(&reports);
+    if let Some(failed) = reports.iter().find(|report| report.is_blocking()) {
+        bail!("wizard: hook `{}` failed", failed.command);
+    }
     Ok(())
 }
 
@@ -142,6 +186,104 @@ struct WizardContext {
     prefill_answers_json: Option<String>,
     required_capabilities: Vec<String>,
     provided_capabilities: Vec<String>,
+    features: HostFeatures,
+}
+
+#[derive(Debug, Clone, Default)]
+struct HostFeatures {
+    http: bool,
+    state: bool,
+    secrets: Vec<String>,
+    mounts: Vec<FsMount>,
+}
+
+#[derive(Debug, Clone)]
+struct FsMount {
+    name: String,
+    guest_path: String,
+}
+
+impl HostFeatures {
+    fn from_args(args: &WizardCapabilityArgs) -> Result<Self> {
+        let mut secrets = Vec::new();
+        for key in &args.secrets {
+            let key = key.trim();
+            let valid = !key.is_empty()
+                && !key.starts_with('/')
+                && !key.contains("..")
+                && key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '/'));
+            if !valid {
+                bail!("wizard: invalid secret key `{key}`");
+            }
+            if !secrets.iter().any(|existing| existing == key) {
+                secrets.push(key.to_string());
+            }
+        }
+        let mut mounts: Vec<FsMount> = Vec::new();
+        for spec in &args.fs {
+            let Some((name, guest_path)) = spec.split_once('=') else {
+                bail!("wizard: --with-fs expects mount=path, got `{spec}`");
+            };
+            let (name, guest_path) = (name.trim(), guest_path.trim());
+            if name.is_empty() || !guest_path.starts_with('/') {
+                bail!(
+                    "wizard: --with-fs needs a mount name and an absolute guest path, got `{spec}`"
+                );
+            }
+            if mounts.iter().any(|mount| mount.name == name) {
+                bail!("wizard: mount `{name}` given more than once");
+            }
+            mounts.push(FsMount {
+                name: name.to_string(),
+                guest_path: guest_path.to_string(),
+            });
+        }
+        Ok(Self {
+            http: args.http,
+            state: args.state,
+            secrets,
+            mounts,
+        })
+    }
+
+    fn any(&self) -> bool {
+        self.http || self.state || !self.secrets.is_empty() || !self.mounts.is_empty()
+    }
+
+    /// Entries for `describe()`'s required capabilities.
+    fn required_capabilities(&self) -> Vec<String> {
+        let mut capabilities = Vec::new();
+        if self.http {
+            capabilities.push("host.http.client".to_string());
+        }
+        if self.state {
+            capabilities.push("host.state.read".to_string());
+            capabilities.push("host.state.write".to_string());
+        }
+        if !self.secrets.is_empty() {
+            capabilities.push("host.secrets.required".to_string());
+        }
+        if !self.mounts.is_empty() {
+            capabilities.push("wasi.filesystem".to_string());
+        }
+        capabilities
+    }
+
+    fn guest_features(&self) -> Vec<&'static str> {
+        let mut features = vec!["component-v0-6"];
+        if self.http {
+            features.push("runner-host-v1");
+        }
+        if self.state {
+            features.push("state-store-v1");
+        }
+        if !self.secrets.is_empty() {
+            features.push("secrets-store-v1");
+        }
+        features
+    }
 }
 
 fn normalize_capabilities(capabilities: Vec<String>) -> Result<Vec<String>> {
@@ -189,7 +331,7 @@ fn build_files(context: &WizardContext) -> Result<Vec<GeneratedFile>> {
         text_file("src/lib.rs", render_lib_rs()),
         text_file("src/descriptor.rs", render_descriptor_rs(context)),
         text_file("src/schema.rs", render_schema_rs()),
-        text_file("src/runtime.rs", render_runtime_rs()),
+        text_file("src/runtime.rs", render_runtime_rs(&context.features)),
         text_file("src/qa.rs", render_qa_rs(context)),
         text_file("src/i18n.rs", render_i18n_rs()),
         text_file("assets/i18n/en.json", render_i18n_bundle()),
@@ -243,6 +385,13 @@ fn binary_file(path: &str, contents: Vec<u8>) -> GeneratedFile {
 }
 
 fn render_cargo_toml(context: &WizardContext) -> String {
+    let guest_features = context
+        .features
+        .guest_features()
+        .iter()
+        .map(|feature| format!("\"{feature}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
     format!(
         r#"[package]
 name = "{name}"
@@ -266,12 +415,13 @@ world = "greentic:component/component-v0-v6-v0@0.6.0"
 
 [dependencies]
 greentic-types = "0.4"
-greentic-interfaces-guest = {{ version = "0.4", default-features = false, features = ["component-v0-6"] }}
+greentic-interfaces-guest = {{ version = "0.4", default-features = false, features = [{guest_features}] }}
 serde = {{ version = "1", features = ["derive"] }}
 serde_json = "1"
 "#,
         name = context.name,
-        abi_version = context.abi_version
+        abi_version = context.abi_version,
+        guest_features = guest_features
     )
 }
 
@@ -373,6 +523,9 @@ fn render_manifest_json(context: &WizardContext) -> String {
         "properties": {{
           "message": {{
             "type": "string",
+            "description": "Text to process.",
+            "minLength": 1,
+            "maxLength": 1024,
             "default": "hello"
           }}
         }},
@@ -385,7 +538,10 @@ fn render_manifest_json(context: &WizardContext) -> String {
         "required": ["result"],
         "properties": {{
           "result": {{
-            "type": "string"
+            "type": "string",
+            "description": "Processed text.",
+            "minLength": 1,
+            "maxLength": 1024
           }}
         }},
         "additionalProperties": false
@@ -398,7 +554,8 @@ fn render_manifest_json(context: &WizardContext) -> String {
     "required": ["enabled"],
     "properties": {{
       "enabled": {{
-        "type": "boolean"
+        "type": "boolean",
+        "description": "Whether the component handles messages."
       }}
     }},
     "additionalProperties": false
@@ -408,13 +565,10 @@ fn render_manifest_json(context: &WizardContext) -> String {
     "default": "stateless",
     "supported": ["stateless"]
   }},
-  "secret_requirements": [],
+  "secret_requirements": {secret_requirements},
   "capabilities": {{
     "wasi": {{
-      "filesystem": {{
-        "mode": "none",
-        "mounts": []
-      }},
+      "filesystem": {filesystem},
       "random": true,
       "clocks": true
     }},
@@ -425,9 +579,9 @@ fn render_manifest_json(context: &WizardContext) -> String {
       }},
       "telemetry": {{
         "scope": "node"
-      }},
+      }},{host_extras}
       "secrets": {{
-        "required": []
+        "required": {host_secrets}
       }}
     }}
   }},
@@ -458,10 +612,77 @@ fn render_manifest_json(context: &WizardContext) -> String {
 }}
 "#,
         name = context.name,
-        name_snake = name_snake
+        name_snake = name_snake,
+        secret_requirements = render_secret_requirements(&context.features.secrets, 2),
+        filesystem = render_filesystem(&context.features.mounts),
+        host_extras = render_host_extras(&context.features),
+        host_secrets = render_secret_requirements(&context.features.secrets, 8),
+    )
+}
+
+/// A JSON array of text secrets, its items indented by `indent + 2` spaces.
+fn render_secret_requirements(keys: &[String], indent: usize) -> String {
+    if keys.is_empty() {
+        return "[]".to_string();
+    }
+    let pad = " ".repeat(indent);
+    let items = keys
+        .iter()
+        .map(|key| {
+            format!(
+                r#"{pad}  {{
+{pad}    "key": {key},
+{pad}    "required": true,
+{pad}    "scope": {{ "env": "dev", "tenant": "default" }},
+{pad}    "format": "text"
+{pad}  }}"#,
+                key = json_string(key)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+    format!("[\n{items}\n{pad}]")
+}
+
+fn render_filesystem(mounts: &[FsMount]) -> String {
+    if mounts.is_empty() {
+        return "{\n        \"mode\": \"none\",\n        \"mounts\": []\n      }".to_string();
+    }
+    let items = mounts
+        .iter()
+        .map(|mount| {
+            format!(
+                "          {{ \"name\": {name}, \"host_class\": {name}, \"guest_path\": {path} }}",
+                name = json_string(&mount.name),
+                path = json_string(&mount.guest_path)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+    format!(
+        "{{\n        \"mode\": \"sandbox\",\n        \"mounts\": [\n{items}\n        ]\n      }}"
     )
 }
 
+fn render_host_extras(features: &HostFeatures) -> String {
+    let mut extras = String::new();
+    if features.http {
+        extras.push_str(
+            "\n      \"http\": {\n        \"client\": true,\n        \"server\": false\n      },",
+        );
+    }
+    if features.state {
+        extras.push_str(
+            "\n      \"state\": {\n        \"read\": true,\n        \"write\": true,\n        \"delete\": false\n      },",
+        );
+    }
+    extras
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
 fn render_lib_rs() -> String {
     r#"use greentic_interfaces_guest::component_v0_6::node;
 
@@ -486,21 +707,24 @@ impl node::Guest for Component {
             version: info.version,
             summary: Some("Generated by greentic-component wizard".to_string()),
             capabilities: Vec::new(),
-            ops: vec![node::Op {
-                name: "run".to_string(),
-                summary: Some("Run the component with CBOR payload".to_string()),
-                input: node::IoSchema {
-                    schema: node::SchemaSource::InlineCbor(schema::input_schema_cbor()),
-                    content_type: "application/cbor".to_string(),
-                    schema_version: None,
+            ops: vec![
+                node::Op {
+                    name: "run".to_string(),
+                    summary: Some("Run the component with CBOR payload".to_string()),
+                    input: node::IoSchema {
+                        schema: node::SchemaSource::InlineCbor(schema::input_schema_cbor()),
+                        content_type: "application/cbor".to_string(),
+                        schema_version: None,
+                    },
+                    output: node::IoSchema {
+                        schema: node::SchemaSource::InlineCbor(schema::output_schema_cbor()),
+                        content_type: "application/cbor".to_string(),
+                        schema_version: None,
+                    },
+                    examples: Vec::new(),
                 },
-                output: node::IoSchema {
-                    schema: node::SchemaSource::InlineCbor(schema::output_schema_cbor()),
-                    content_type: "application/cbor".to_string(),
-                    schema_version: None,
-                },
-                examples: Vec::new(),
-            }],
+                // greentic-component op add: ops
+            ],
             schemas: Vec::new(),
             setup: None,
         }
@@ -510,18 +734,17 @@ impl node::Guest for Component {
         operation: String,
         envelope: node::InvocationEnvelope,
     ) -> Result<node::InvocationResult, node::NodeError> {
-        let (output, _new_state) = runtime::run(envelope.payload_cbor, Vec::new());
-        let output = if operation == "run" {
-            output
-        } else {
-            runtime::run(
+        let output = match operation.as_str() {
+            "run" => runtime::run(envelope.payload_cbor, Vec::new()).0,
+            // greentic-component op add: dispatch
+            _ => runtime::run(
                 greentic_types::cbor::canonical::to_canonical_cbor_allow_floats(&serde_json::json!({
                     "message": format!("unsupported operation: {operation}")
                 }))
                 .unwrap_or_default(),
                 Vec::new(),
             )
-            .0
+            .0,
         };
         Ok(node::InvocationResult {
             ok: true,
@@ -636,7 +859,10 @@ fn qa_spec(mode: Mode) -> ComponentQaSpec {
         Mode::Setup => (
             "qa.setup.title",
             Some("qa.setup.description"),
-            vec![question_enabled("qa.setup.enabled.label", "qa.setup.enabled.help")],
+            vec![
+                question_enabled("qa.setup.enabled.label", "qa.setup.enabled.help"),
+                // greentic-component secret add: questions
+            ],
         ),
         Mode::Update => ("qa.update.title", None, Vec::new()),
         Mode::Remove => ("qa.remove.title", None, Vec::new()),
@@ -738,7 +964,10 @@ pub fn describe() -> ComponentDescribe {
         provided_capabilities: provided_capabilities(),
         required_capabilities: required_capabilities(),
         metadata: BTreeMap::new(),
-        operations: vec![operation],
+        operations: vec![
+            operation,
+            // greentic-component op add: operations
+        ],
         config_schema,
     }
 }
@@ -844,8 +1073,8 @@ fn object_schema(props: Vec<(&str, SchemaIr)>) -> SchemaIr {
     .to_string()
 }
 
-fn render_runtime_rs() -> String {
-    r#"use std::collections::BTreeMap;
+fn render_runtime_rs(features: &HostFeatures) -> String {
+    let base = r#"use std::collections::BTreeMap;
 
 use greentic_types::cbor::canonical;
 use serde_json::Value as JsonValue;
@@ -859,7 +1088,7 @@ pub fn run(input: Vec<u8>, state: Vec<u8>) -> (Vec<u8>, Vec<u8>) {
     let mut output = BTreeMap::new();
     output.insert(
         "result".to_string(),
-        JsonValue::String(format!("processed: {message}")),
+        JsonValue::String(__RESULT__),
     );
     let output_cbor = canonical::to_canonical_cbor_allow_floats(&output).unwrap_or_default();
     let state_cbor = canonicalize_or_empty(&state);
@@ -894,8 +1123,127 @@ fn decode_map(bytes: &[u8]) -> BTreeMap<String, JsonValue> {
     };
     map.into_iter().collect()
 }
+"#;
+    if !features.any() {
+        return base.replace("__RESULT__", r#"format!("processed: {message}")"#);
+    }
+    let mut runtime = base.replace(
+        "__RESULT__",
+        r#"format!("processed: {message} [{}]", host_calls::summary())"#,
+    );
+    runtime.push_str(&render_host_calls(features));
+    runtime
+}
+
+/// Example usage of each capability selected with `--with-*`. The calls only
+/// exist in wasm builds; native builds (unit tests) report a placeholder.
+fn render_host_calls(features: &HostFeatures) -> String {
+    let mut calls = Vec::new();
+    let mut functions = String::new();
+    if features.state {
+        calls.push("visits()".to_string());
+        functions.push_str(
+            r#"
+    /// Counts invocations in the state store (`host.state.read` + `write`).
+    #[cfg(target_arch = "wasm32")]
+    fn visits() -> String {
+        use greentic_interfaces_guest::state_store;
+
+        let count = state_store::read("visits", None)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .and_then(|text| text.parse::<u64>().ok())
+            .unwrap_or(0)
+            + 1;
+        match state_store::write("visits", count.to_string().as_bytes(), None) {
+            Ok(_) => format!("visits: {count}"),
+            Err(err) => format!("state write failed: {}", err.message),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn visits() -> String {
+        "visits: n/a outside wasm".to_string()
+    }
+"#,
+        );
+    }
+    if !features.secrets.is_empty() {
+        for key in &features.secrets {
+            calls.push(format!("secret_status({})", json_string(key)));
+        }
+        functions.push_str(
+            r#"
+    /// Looks up a declared secret (`host.secrets.required`). Never log the value.
+    #[cfg(target_arch = "wasm32")]
+    fn secret_status(key: &str) -> String {
+        match greentic_interfaces_guest::secrets_store::get(key) {
+            Ok(Some(_)) => format!("{key}: present"),
+            Ok(None) => format!("{key}: missing"),
+            Err(err) => format!("{key}: {err:?}"),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn secret_status(key: &str) -> String {
+        format!("{key}: n/a outside wasm")
+    }
+"#,
+        );
+    }
+    if features.http {
+        calls.push("http_status()".to_string());
+        functions.push_str(
+            r#"
+    /// Calls out over HTTP (`host.http.client`).
+    #[cfg(target_arch = "wasm32")]
+    fn http_status() -> String {
+        let url = "https://example.com/".to_string();
+        match greentic_interfaces_guest::runner_host::http_request("GET", &url, &[], None) {
+            Ok(body) => format!("GET {url}: {} bytes", body.len()),
+            Err(err) => format!("GET {url} failed: {err}"),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn http_status() -> String {
+        "http: n/a outside wasm".to_string()
+    }
+"#,
+        );
+    }
+    if !features.mounts.is_empty() {
+        for mount in &features.mounts {
+            calls.push(format!("mount_entries({})", json_string(&mount.guest_path)));
+        }
+        functions.push_str(
+            r#"
+    /// Lists a mounted directory through WASI (`wasi.filesystem`).
+    fn mount_entries(path: &str) -> String {
+        match std::fs::read_dir(path) {
+            Ok(entries) => format!("{path}: {} entries", entries.count()),
+            Err(err) => format!("{path}: {err}"),
+        }
+    }
+"#,
+        );
+    }
+    let calls = calls
+        .iter()
+        .map(|call| format!("            {call},\n"))
+        .collect::<String>();
+    format!(
+        r#"
+/// Example host calls generated by `greentic-component wizard new --with-*`.
+mod host_calls {{
+    pub fn summary() -> String {{
+        [
+{calls}        ]
+        .join("; ")
+    }}
+{functions}}}
 "#
-    .to_string()
+    )
 }
 
 fn render_i18n_rs() -> String {
@@ -910,6 +1258,7 @@ fn render_i18n_rs() -> String {
     "qa.setup.enabled.help",
     "qa.update.title",
     "qa.remove.title",
+    // greentic-component secret add: keys
 ];
 
 pub fn all_keys() -> Vec<String> {
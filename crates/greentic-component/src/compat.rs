@@ -0,0 +1,501 @@
+//! Drop-in compatibility checks between two versions of a component.
+//!
+//! The check compares `describe` payloads: removed operations and new required
+//! capabilities are breaking, and schemas are compared by structural subtyping.
+//! Inputs and config are contravariant (everything the old schema accepted
+//! must still be accepted), outputs are covariant (everything the new schema
+//! can produce must have been valid before).
+
+use std::collections::BTreeSet;
+
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use greentic_types::schemas::component::v0_6_0::ComponentDescribe;
+use semver::Version;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// Additive or otherwise safe for existing callers.
+    Compatible,
+    /// Existing callers, configs, or hosts may stop working.
+    Breaking,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CompatChange {
+    pub kind: ChangeKind,
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionBump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CompatReport {
+    pub old_version: String,
+    pub new_version: String,
+    pub changes: Vec<CompatChange>,
+    /// Smallest semver bump that honestly describes the changes.
+    pub required_bump: VersionBump,
+    /// Whether the new version number carries at least `required_bump`.
+    pub version_ok: bool,
+}
+
+impl CompatReport {
+    /// `true` when the new component can replace the old one without
+    /// breaking existing flows, configs, or host grants.
+    pub fn is_compatible(&self) -> bool {
+        self.breaking().next().is_none()
+    }
+
+    pub fn breaking(&self) -> impl Iterator<Item = &CompatChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.kind == ChangeKind::Breaking)
+    }
+}
+
+/// Diffs two describe payloads.
+pub fn check_compat(old: &ComponentDescribe, new: &ComponentDescribe) -> CompatReport {
+    let mut diff = Diff::default();
+
+    for old_op in &old.operations {
+        let path = format!("operations.{}", old_op.id);
+        match new.operations.iter().find(|op| op.id == old_op.id) {
+            None => diff.breaking(&path, "operation removed"),
+            Some(new_op) => {
+                subschema(
+                    &old_op.input.schema,
+                    &new_op.input.schema,
+                    &format!("{path}.input"),
+                    Variance::Contra,
+                    &mut diff,
+                );
+                subschema(
+                    &new_op.output.schema,
+                    &old_op.output.schema,
+                    &format!("{path}.output"),
+                    Variance::Co,
+                    &mut diff,
+                );
+            }
+        }
+    }
+    for new_op in &new.operations {
+        if !old.operations.iter().any(|op| op.id == new_op.id) {
+            diff.compatible(&format!("operations.{}", new_op.id), "operation added");
+        }
+    }
+
+    subschema(
+        &old.config_schema,
+        &new.config_schema,
+        "config",
+        Variance::Contra,
+        &mut diff,
+    );
+
+    let old_required = capability_labels(&old.required_capabilities);
+    let new_required = capability_labels(&new.required_capabilities);
+    for cap in new_required.difference(&old_required) {
+        diff.breaking(
+            &format!("required_capabilities.{cap}"),
+            "new capability requirement needs a host grant",
+        );
+    }
+    for cap in old_required.difference(&new_required) {
+        diff.compatible(
+            &format!("required_capabilities.{cap}"),
+            "capability no longer required",
+        );
+    }
+    let old_provided = capability_labels(&old.provided_capabilities);
+    let new_provided = capability_labels(&new.provided_capabilities);
+    for cap in old_provided.difference(&new_provided) {
+        diff.breaking(
+            &format!("provided_capabilities.{cap}"),
+            "capability no longer provided",
+        );
+    }
+    for cap in new_provided.difference(&old_provided) {
+        diff.compatible(
+            &format!("provided_capabilities.{cap}"),
+            "capability now provided",
+        );
+    }
+
+    let required_bump = if diff.has(ChangeKind::Breaking) {
+        VersionBump::Major
+    } else if diff.has(ChangeKind::Compatible) {
+        VersionBump::Minor
+    } else {
+        VersionBump::None
+    };
+    let version_ok = match (
+        Version::parse(&old.info.version),
+        Version::parse(&new.info.version),
+    ) {
+        (Ok(old_v), Ok(new_v)) => actual_bump(&old_v, &new_v) >= required_bump,
+        _ => false,
+    };
+
+    CompatReport {
+        old_version: old.info.version.clone(),
+        new_version: new.info.version.clone(),
+        changes: diff.changes,
+        required_bump,
+        version_ok,
+    }
+}
+
+/// Classifies a version change. On `0.x` every component shifts down one
+/// place (cargo semantics): a minor bump is breaking, a patch bump additive.
+fn actual_bump(old: &Version, new: &Version) -> VersionBump {
+    if new <= old {
+        VersionBump::None
+    } else if new.major > old.major || (old.major == 0 && new.minor > old.minor) {
+        VersionBump::Major
+    } else if new.minor > old.minor || (old.major == 0 && new.patch > old.patch) {
+        VersionBump::Minor
+    } else {
+        VersionBump::Patch
+    }
+}
+
+/// Which side of a comparison is the old schema, used to word changes.
+#[derive(Clone, Copy)]
+enum Variance {
+    /// `sub` is the old schema (inputs, config).
+    Contra,
+    /// `sub` is the new schema (outputs).
+    Co,
+}
+
+impl Variance {
+    fn pick(self, contra: &'static str, co: &'static str) -> &'static str {
+        match self {
+            Variance::Contra => contra,
+            Variance::Co => co,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Diff {
+    changes: Vec<CompatChange>,
+}
+
+impl Diff {
+    fn breaking(&mut self, path: &str, message: impl Into<String>) {
+        self.push(ChangeKind::Breaking, path, message);
+    }
+
+    fn compatible(&mut self, path: &str, message: impl Into<String>) {
+        self.push(ChangeKind::Compatible, path, message);
+    }
+
+    fn push(&mut self, kind: ChangeKind, path: &str, message: impl Into<String>) {
+        self.changes.push(CompatChange {
+            kind,
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn has(&self, kind: ChangeKind) -> bool {
+        self.changes.iter().any(|change| change.kind == kind)
+    }
+}
+
+/// Records why some value valid under `sub` might be rejected by `sup`.
+fn subschema(sub: &SchemaIr, sup: &SchemaIr, path: &str, variance: Variance, diff: &mut Diff) {
+    match (sub, sup) {
+        (
+            SchemaIr::Object {
+                properties: sub_props,
+                required: sub_required,
+                additional: sub_additional,
+            },
+            SchemaIr::Object {
+                properties: sup_props,
+                required: sup_required,
+                additional: sup_additional,
+            },
+        ) => {
+            for field in sup_required {
+                if !sub_required.contains(field) {
+                    diff.breaking(
+                        &format!("{path}.{field}"),
+                        variance.pick("field became required", "field is no longer guaranteed"),
+                    );
+                }
+            }
+            for (field, sup_field) in sup_props {
+                let field_path = format!("{path}.{field}");
+                match sub_props.get(field) {
+                    Some(sub_field) => subschema(sub_field, sup_field, &field_path, variance, diff),
+                    // Already reported as a required-field change above.
+                    None if sup_required.contains(field) => {}
+                    None => match sub_additional {
+                        AdditionalProperties::Forbid => diff.compatible(
+                            &field_path,
+                            variance.pick("optional field added", "field no longer emitted"),
+                        ),
+                        AdditionalProperties::Allow => diff.breaking(
+                            &field_path,
+                            variance.pick(
+                                "previously free-form field is now constrained",
+                                "field emitted without the previous constraints",
+                            ),
+                        ),
+                        AdditionalProperties::Schema(extra) => {
+                            subschema(extra, sup_field, &field_path, variance, diff)
+                        }
+                    },
+                }
+            }
+            for (field, sub_field) in sub_props {
+                if sup_props.contains_key(field) {
+                    continue;
+                }
+                let field_path = format!("{path}.{field}");
+                match sup_additional {
+                    AdditionalProperties::Allow => {}
+                    AdditionalProperties::Forbid => diff.breaking(
+                        &field_path,
+                        variance.pick("field removed", "new field not allowed previously"),
+                    ),
+                    AdditionalProperties::Schema(extra) => {
+                        subschema(sub_field, extra, &field_path, variance, diff)
+                    }
+                }
+            }
+            match (sub_additional, sup_additional) {
+                (AdditionalProperties::Forbid, _)
+                | (AdditionalProperties::Allow, AdditionalProperties::Allow) => {}
+                (
+                    AdditionalProperties::Schema(sub_extra),
+                    AdditionalProperties::Schema(sup_extra),
+                ) => subschema(
+                    sub_extra,
+                    sup_extra,
+                    &format!("{path}.additional"),
+                    variance,
+                    diff,
+                ),
+                (AdditionalProperties::Schema(_), AdditionalProperties::Allow) => {}
+                _ => diff.breaking(
+                    &format!("{path}.additional"),
+                    "additional properties are more restricted",
+                ),
+            }
+        }
+        (
+            SchemaIr::Array {
+                items: sub_items,
+                min_items: sub_min,
+                max_items: sub_max,
+            },
+            SchemaIr::Array {
+                items: sup_items,
+                min_items: sup_min,
+                max_items: sup_max,
+            },
+        ) => {
+            if tighter_min(*sub_min, *sup_min) || tighter_max(*sub_max, *sup_max) {
+                diff.breaking(
+                    path,
+                    variance.pick(
+                        "array length bounds tightened",
+                        "array length bounds loosened",
+                    ),
+                );
+            }
+            subschema(
+                sub_items,
+                sup_items,
+                &format!("{path}.items"),
+                variance,
+                diff,
+            );
+        }
+        (
+            SchemaIr::String {
+                min_len: sub_min,
+                max_len: sub_max,
+                regex: sub_regex,
+                format: sub_format,
+            },
+            SchemaIr::String {
+                min_len: sup_min,
+                max_len: sup_max,
+                regex: sup_regex,
+                format: sup_format,
+            },
+        ) => {
+            if tighter_min(*sub_min, *sup_min) || tighter_max(*sub_max, *sup_max) {
+                diff.breaking(
+                    path,
+                    variance.pick(
+                        "string length bounds tightened",
+                        "string length bounds loosened",
+                    ),
+                );
+            }
+            if sup_regex.is_some() && sup_regex != sub_regex {
+                diff.breaking(path, "string pattern changed");
+            }
+            if sup_format.is_some() && sup_format != sub_format {
+                diff.breaking(path, "string format changed");
+            }
+        }
+        (
+            SchemaIr::Int {
+                min: sub_min,
+                max: sub_max,
+            },
+            SchemaIr::Int {
+                min: sup_min,
+                max: sup_max,
+            },
+        ) => {
+            if tighter_min(*sub_min, *sup_min) || tighter_max(*sub_max, *sup_max) {
+                diff.breaking(
+                    path,
+                    variance.pick("integer bounds tightened", "integer bounds loosened"),
+                );
+            }
+        }
+        (
+            SchemaIr::Float {
+                min: sub_min,
+                max: sub_max,
+            },
+            SchemaIr::Float {
+                min: sup_min,
+                max: sup_max,
+            },
+        ) => {
+            if tighter_min(*sub_min, *sup_min) || tighter_max(*sub_max, *sup_max) {
+                diff.breaking(
+                    path,
+                    variance.pick("number bounds tightened", "number bounds loosened"),
+                );
+            }
+        }
+        (
+            SchemaIr::Int { .. },
+            SchemaIr::Float {
+                min: None,
+                max: None,
+            },
+        ) => diff.compatible(
+            path,
+            variance.pick("integer widened to number", "number narrowed to integer"),
+        ),
+        (SchemaIr::Enum { values: sub_values }, SchemaIr::Enum { values: sup_values }) => {
+            if sub_values.iter().any(|value| !sup_values.contains(value)) {
+                diff.breaking(
+                    path,
+                    variance.pick("enum values removed", "enum values added"),
+                );
+            } else if sup_values.len() > sub_values.len() {
+                diff.compatible(
+                    path,
+                    variance.pick("enum values added", "enum values removed"),
+                );
+            }
+        }
+        (SchemaIr::OneOf { variants }, _) => {
+            for (idx, variant) in variants.iter().enumerate() {
+                subschema(
+                    variant,
+                    sup,
+                    &format!("{path}.variants[{idx}]"),
+                    variance,
+                    diff,
+                );
+            }
+        }
+        (_, SchemaIr::OneOf { variants }) => {
+            let fits = variants.iter().any(|variant| {
+                let mut probe = Diff::default();
+                subschema(sub, variant, path, variance, &mut probe);
+                !probe.has(ChangeKind::Breaking)
+            });
+            if !fits {
+                diff.breaking(path, "no oneOf variant covers the other schema");
+            }
+        }
+        (SchemaIr::Ref { id: sub_id }, SchemaIr::Ref { id: sup_id }) => {
+            if sub_id != sup_id {
+                diff.breaking(path, "schema ref changed");
+            }
+        }
+        (SchemaIr::Bool, SchemaIr::Bool)
+        | (SchemaIr::Null, SchemaIr::Null)
+        | (SchemaIr::Bytes, SchemaIr::Bytes) => {}
+        _ => {
+            let (old, new) = match variance {
+                Variance::Contra => (sub, sup),
+                Variance::Co => (sup, sub),
+            };
+            diff.breaking(
+                path,
+                format!("type changed from {} to {}", kind_name(old), kind_name(new)),
+            )
+        }
+    }
+}
+
+/// `true` when `sup` demands a larger minimum than `sub` guaranteed.
+fn tighter_min<T: PartialOrd>(sub: Option<T>, sup: Option<T>) -> bool {
+    match (sub, sup) {
+        (_, None) => false,
+        (None, Some(_)) => true,
+        (Some(sub), Some(sup)) => sup > sub,
+    }
+}
+
+/// `true` when `sup` demands a smaller maximum than `sub` guaranteed.
+fn tighter_max<T: PartialOrd>(sub: Option<T>, sup: Option<T>) -> bool {
+    match (sub, sup) {
+        (_, None) => false,
+        (None, Some(_)) => true,
+        (Some(sub), Some(sup)) => sup < sub,
+    }
+}
+
+fn kind_name(schema: &SchemaIr) -> &'static str {
+    match schema {
+        SchemaIr::Object { .. } => "object",
+        SchemaIr::Array { .. } => "array",
+        SchemaIr::String { .. } => "string",
+        SchemaIr::Int { .. } => "int",
+        SchemaIr::Float { .. } => "float",
+        SchemaIr::Enum { .. } => "enum",
+        SchemaIr::OneOf { .. } => "oneof",
+        SchemaIr::Bool => "bool",
+        SchemaIr::Null => "null",
+        SchemaIr::Bytes => "bytes",
+        SchemaIr::Ref { .. } => "ref",
+    }
+}
+
+fn capability_labels<T: Serialize>(caps: &[T]) -> BTreeSet<String> {
+    caps.iter()
+        .filter_map(|cap| serde_json::to_value(cap).ok())
+        .map(|value| match value {
+            serde_json::Value::String(label) => label,
+            other => other.to_string(),
+        })
+        .collect()
+}
@@ -3,6 +3,8 @@
 #[cfg(feature = "abi")]
 pub mod abi;
 pub mod capabilities;
+pub mod client_gen;
+pub mod compat;
 #[cfg(feature = "cli")]
 pub mod config;
 #[cfg(feature = "describe")]
@@ -13,6 +15,7 @@ pub mod limits;
 #[cfg(feature = "loader")]
 pub mod loader;
 pub mod manifest;
+pub mod manifest_sync;
 pub mod path_safety;
 #[cfg(feature = "prepare")]
 pub mod prepare;
@@ -33,6 +36,8 @@ pub mod cmd;
 pub mod scaffold;
 #[cfg(feature = "cli")]
 pub mod test_harness;
+#[cfg(feature = "cli")]
+pub mod testing;
 #[cfg(any(
     feature = "abi",
     feature = "describe",
@@ -40,10 +45,14 @@ pub mod test_harness;
     feature = "cli"
 ))]
 pub mod wasm;
+#[cfg(feature = "abi")]
+pub mod wit_config;
 
 #[cfg(feature = "abi")]
 pub use abi::{AbiError, check_world, has_lifecycle};
 pub use capabilities::{Capabilities, CapabilityError};
+pub use client_gen::{ClientLang, generate_client};
+pub use compat::{CompatReport, check_compat};
 #[cfg(feature = "describe")]
 pub use describe::{
     DescribeError, DescribePayload, DescribeVersion, from_embedded, from_exported_func,
@@ -53,11 +62,15 @@ pub use error::ComponentError;
 pub use lifecycle::Lifecycle;
 pub use limits::{LimitError, LimitOverrides, Limits, defaults_dev, merge};
 #[cfg(feature = "loader")]
-pub use loader::{ComponentHandle, LoadError, discover};
+pub use loader::{ComponentHandle, LoadError, discover, discover_for_env};
 pub use manifest::{
-    Artifacts, ComponentManifest, DescribeExport, DescribeKind, Hashes, ManifestError, ManifestId,
-    WasmHash, World, parse_manifest, schema as manifest_schema, validate_manifest,
+    Artifacts, BuildCommand, BuildProfile, BuildSpec, ComponentManifest, ConfiguratorMode,
+    Configurators, DeclaredError, DescribeExport, DescribeKind, Hashes, ManifestError, ManifestId,
+    SchemaDraft, ValidationOptions, WasmHash, World, apply_env_overlay, parse_manifest,
+    parse_manifest_for_profile, parse_manifest_value, parse_manifest_value_with,
+    parse_manifest_with_overlay, resolve_schema_refs, schema as manifest_schema, validate_manifest,
 };
+pub use manifest_sync::{SyncReport, check_manifest_sync, verify_schema_hashes};
 #[cfg(feature = "prepare")]
 pub use prepare::{
     PackEntry, PreparedComponent, RunnerConfig, clear_cache_for, prepare_component,
@@ -75,7 +88,11 @@ pub use signing::{
     verify_wasm_hash,
 };
 pub use store::{
-    CompatError, CompatPolicy, ComponentBytes, ComponentId, ComponentLocator, ComponentStore,
-    MetaInfo, SourceId,
+    Cache, CacheCheck, CacheEncryptionKey, CacheEntry, CacheKey, CacheStatus, CacheTampered,
+    CompatError, CompatPolicy, ComponentBytes, ComponentId, ComponentIndex, ComponentLocator,
+    ComponentStore, DigestPin, IndexEntry, IndexKey, MetaInfo, PinAlgorithm, ProvenanceRequirement,
+    Registry, Resolution, ResolvedDependency, Resolver, SYSTEM_CACHE_ENV, SourceId, TamperPolicy,
 };
 pub use telemetry::{TelemetrySpec, span_name};
+#[cfg(feature = "abi")]
+pub use wit_config::{WitConfig, WitConfigError, WitConfigField, infer_config_from_wit};
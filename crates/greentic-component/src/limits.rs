@@ -10,6 +10,12 @@ pub struct Limits {
     pub fuel: Option<u64>,
     #[serde(default)]
     pub files: Option<u32>,
+    /// Largest invoke output a host accepts, in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<u64>,
+    /// Bytes a component may add to its writable mounts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fs_quota_bytes: Option<u64>,
 }
 
 impl Limits {
@@ -26,6 +32,18 @@ impl Limits {
                 value: self.wall_time_ms as u128,
             });
         }
+        if self.max_output_bytes == Some(0) {
+            return Err(LimitError::NonZero {
+                field: "max_output_bytes",
+                value: 0,
+            });
+        }
+        if self.fs_quota_bytes == Some(0) {
+            return Err(LimitError::NonZero {
+                field: "fs_quota_bytes",
+                value: 0,
+            });
+        }
         Ok(())
     }
 }
@@ -40,6 +58,10 @@ pub struct LimitOverrides {
     pub fuel: Option<Option<u64>>,
     #[serde(default, with = "double_option")]
     pub files: Option<Option<u32>>,
+    #[serde(default, with = "double_option")]
+    pub max_output_bytes: Option<Option<u64>>,
+    #[serde(default, with = "double_option")]
+    pub fs_quota_bytes: Option<Option<u64>>,
 }
 
 pub fn defaults_dev() -> Limits {
@@ -48,6 +70,8 @@ pub fn defaults_dev() -> Limits {
         wall_time_ms: 30_000,
         fuel: Some(50_000),
         files: Some(128),
+        max_output_bytes: None,
+        fs_quota_bytes: None,
     }
 }
 
@@ -66,6 +90,12 @@ pub fn merge(user: Option<&LimitOverrides>, defaults: &Limits) -> Limits {
         if let Some(files) = overrides.files {
             merged.files = files;
         }
+        if let Some(max_output_bytes) = overrides.max_output_bytes {
+            merged.max_output_bytes = max_output_bytes;
+        }
+        if let Some(fs_quota_bytes) = overrides.fs_quota_bytes {
+            merged.fs_quota_bytes = fs_quota_bytes;
+        }
     }
     merged
 }
@@ -4,9 +4,12 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use directories::BaseDirs;
+use serde_json::Value;
 use thiserror::Error;
 
-use crate::manifest::{ComponentManifest, parse_manifest};
+use crate::manifest::{
+    ComponentManifest, ManifestError, apply_env_overlay, parse_manifest_value, resolve_schema_refs,
+};
 use crate::signing::{SigningError, verify_manifest_hash};
 
 const MANIFEST_NAME: &str = "component.manifest.json";
@@ -50,25 +53,36 @@ pub fn discover(path_or_id: &str) -> Result<ComponentHandle, LoadError> {
 pub fn discover_with_manifest(
     path_or_id: &str,
     manifest_override: Option<&Path>,
+) -> Result<ComponentHandle, LoadError> {
+    discover_for_env(path_or_id, manifest_override, None)
+}
+
+/// Like [`discover_with_manifest`], but deep-merges
+/// `component.manifest.override.<env>.json` over the manifest when `env` is
+/// set and the overlay exists next to it.
+pub fn discover_for_env(
+    path_or_id: &str,
+    manifest_override: Option<&Path>,
+    env: Option<&str>,
 ) -> Result<ComponentHandle, LoadError> {
     if let Some(manifest_path) = manifest_override {
-        return load_from_manifest(manifest_path);
+        return load_from_manifest(manifest_path, env);
     }
     let normalized = normalize_path_or_id(path_or_id);
     let normalized_str = normalized.as_ref();
-    if let Some(handle) = try_explicit(normalized_str)? {
+    if let Some(handle) = try_explicit(normalized_str, env)? {
         return Ok(handle);
     }
-    if let Some(handle) = try_workspace(normalized_str)? {
+    if let Some(handle) = try_workspace(normalized_str, env)? {
         return Ok(handle);
     }
-    if let Some(handle) = try_registry(path_or_id)? {
+    if let Some(handle) = try_registry(path_or_id, env)? {
         return Ok(handle);
     }
     Err(LoadError::NotFound(path_or_id.to_string()))
 }
 
-fn try_explicit(arg: &str) -> Result<Option<ComponentHandle>, LoadError> {
+fn try_explicit(arg: &str, env: Option<&str>) -> Result<Option<ComponentHandle>, LoadError> {
     let path = Path::new(arg);
     if !path.exists() {
         return Ok(None);
@@ -87,13 +101,13 @@ fn try_explicit(arg: &str) -> Result<Option<ComponentHandle>, LoadError> {
     };
 
     if target.exists() {
-        return load_from_manifest(&target).map(Some);
+        return load_from_manifest(&target, env).map(Some);
     }
 
     Ok(None)
 }
 
-fn try_workspace(id: &str) -> Result<Option<ComponentHandle>, LoadError> {
+fn try_workspace(id: &str, env: Option<&str>) -> Result<Option<ComponentHandle>, LoadError> {
     let cwd = std::env::current_dir().map_err(|e| LoadError::Io {
         path: PathBuf::from("."),
         source: e,
@@ -109,7 +123,7 @@ fn try_workspace(id: &str) -> Result<Option<ComponentHandle>, LoadError> {
                 .map(|dir| dir.join(MANIFEST_NAME))
                 .unwrap_or_else(|| candidate.with_extension("manifest.json"));
             if manifest_path.exists() {
-                return load_from_manifest(&manifest_path).map(Some);
+                return load_from_manifest(&manifest_path, env).map(Some);
             }
         }
     }
@@ -117,7 +131,7 @@ fn try_workspace(id: &str) -> Result<Option<ComponentHandle>, LoadError> {
     Ok(None)
 }
 
-fn try_registry(id: &str) -> Result<Option<ComponentHandle>, LoadError> {
+fn try_registry(id: &str, env: Option<&str>) -> Result<Option<ComponentHandle>, LoadError> {
     let Some(base) = BaseDirs::new() else {
         return Ok(None);
     };
@@ -148,26 +162,38 @@ fn try_registry(id: &str) -> Result<Option<ComponentHandle>, LoadError> {
     for dir in candidates {
         let manifest_path = dir.join(MANIFEST_NAME);
         if manifest_path.exists() {
-            return load_from_manifest(&manifest_path).map(Some);
+            return load_from_manifest(&manifest_path, env).map(Some);
         }
     }
 
     Ok(None)
 }
 
-fn load_from_manifest(path: &Path) -> Result<ComponentHandle, LoadError> {
+fn load_from_manifest(path: &Path, env: Option<&str>) -> Result<ComponentHandle, LoadError> {
     let contents = fs::read_to_string(path).map_err(|source| LoadError::Io {
         path: path.to_path_buf(),
         source,
     })?;
-    let manifest = parse_manifest(&contents).map_err(|source| LoadError::Manifest {
-        path: path.to_path_buf(),
-        source,
-    })?;
     let root = path
         .parent()
         .map(|p| p.to_path_buf())
         .unwrap_or_else(|| PathBuf::from("."));
+    let manifest_err = |path: &Path, source: ManifestError| LoadError::Manifest {
+        path: path.to_path_buf(),
+        source,
+    };
+    let mut value: Value = serde_json::from_str(&contents)
+        .map_err(|err| manifest_err(path, ManifestError::Json(err)))?;
+    let schema_root = if root.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        root.as_path()
+    };
+    resolve_schema_refs(&mut value, schema_root).map_err(|err| manifest_err(path, err))?;
+    if let Some(env) = env {
+        apply_env_overlay(&mut value, path, env).map_err(|err| manifest_err(path, err))?;
+    }
+    let manifest = parse_manifest_value(value).map_err(|err| manifest_err(path, err))?;
     let wasm_path = root.join(manifest.artifacts.component_wasm());
     if !wasm_path.exists() {
         return Err(LoadError::MissingArtifact { path: wasm_path });
@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+
+use component_manifest::overlay::merge_patch;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::ManifestError;
+
+/// The `build` section of a manifest.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BuildSpec {
+    /// Compile step to run instead of cargo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<BuildCommand>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, BuildProfile>,
+}
+
+/// A project's own compile step (componentize-js, componentize-py, `make`,
+/// ...). It runs from the manifest directory and must leave the component
+/// at `artifacts.component_wasm`; `build` then hashes and packages it as
+/// it would a cargo build.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BuildCommand {
+    /// Program and arguments, run without a shell.
+    pub run: Vec<String>,
+    /// Environment variables added for the command.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Per-environment patch (dev/staging/prod, ...) applied over the base manifest.
+///
+/// Each section uses JSON merge-patch semantics: objects merge key by key and a
+/// `null` value removes the corresponding entry from the base manifest.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BuildProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<Value>,
+}
+
+impl BuildProfile {
+    fn sections(&self) -> [(&'static str, Option<&Value>); 3] {
+        [
+            ("limits", self.limits.as_ref()),
+            ("capabilities", self.capabilities.as_ref()),
+            ("telemetry", self.telemetry.as_ref()),
+        ]
+    }
+}
+
+/// Returns the build profile names declared in a raw manifest value.
+pub fn build_profile_names(manifest: &Value) -> Vec<String> {
+    manifest
+        .get("build")
+        .and_then(|build| build.get("profiles"))
+        .and_then(Value::as_object)
+        .map(|profiles| profiles.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Returns `build.command` from a raw manifest value.
+pub fn build_command(manifest: &Value) -> Result<Option<BuildCommand>, ManifestError> {
+    Ok(manifest
+        .get("build")
+        .and_then(|build| build.get("command"))
+        .cloned()
+        .map(serde_json::from_value)
+        .transpose()?)
+}
+
+/// Applies `build.profiles.<profile>` over the base manifest in place. The
+/// `build` section itself is kept so the result can be re-validated.
+pub fn apply_build_profile(manifest: &mut Value, profile: &str) -> Result<(), ManifestError> {
+    let spec: Option<BuildSpec> = manifest
+        .get("build")
+        .cloned()
+        .map(serde_json::from_value)
+        .transpose()?;
+    let selected = spec
+        .and_then(|mut spec| spec.profiles.remove(profile))
+        .ok_or_else(|| ManifestError::UnknownBuildProfile {
+            profile: profile.to_string(),
+        })?;
+    let Some(root) = manifest.as_object_mut() else {
+        return Ok(());
+    };
+    for (section, patch) in selected.sections() {
+        let Some(patch) = patch else {
+            continue;
+        };
+        match root.get_mut(section) {
+            Some(base) => merge_patch(base, patch),
+            None => {
+                let mut base = Value::Object(Default::default());
+                merge_patch(&mut base, patch);
+                root.insert(section.to_string(), base);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::ManifestError;
+
+/// Lifecycle step a configurator flow runs for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfiguratorMode {
+    /// First-time configuration when the component is added.
+    Setup,
+    /// Reconfiguration when a new version replaces an installed one.
+    Upgrade,
+    /// Cleanup before the component is removed.
+    Remove,
+}
+
+impl ConfiguratorMode {
+    pub const ALL: [ConfiguratorMode; 3] = [
+        ConfiguratorMode::Setup,
+        ConfiguratorMode::Upgrade,
+        ConfiguratorMode::Remove,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ConfiguratorMode::Setup => "setup",
+            ConfiguratorMode::Upgrade => "upgrade",
+            ConfiguratorMode::Remove => "remove",
+        }
+    }
+}
+
+impl fmt::Display for ConfiguratorMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ConfiguratorMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == value)
+            .ok_or_else(|| format!("unknown configurator mode `{value}`"))
+    }
+}
+
+/// The manifest's `configurators`: ids of the flows that configure the
+/// component, each found in `dev_flows` or as `flows/<id>.ygtc`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Configurators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setup: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upgrade: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remove: Option<String>,
+    /// Pre-lifecycle names. They are checked like the others but no mode
+    /// runs them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub basic: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full: Option<String>,
+}
+
+impl Configurators {
+    pub fn flow(&self, mode: ConfiguratorMode) -> Option<&str> {
+        match mode {
+            ConfiguratorMode::Setup => self.setup.as_deref(),
+            ConfiguratorMode::Upgrade => self.upgrade.as_deref(),
+            ConfiguratorMode::Remove => self.remove.as_deref(),
+        }
+    }
+
+    /// Every referenced flow as `(field, flow id)`, in declaration order.
+    pub fn flows(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("setup", &self.setup),
+            ("upgrade", &self.upgrade),
+            ("remove", &self.remove),
+            ("basic", &self.basic),
+            ("full", &self.full),
+        ]
+        .into_iter()
+        .filter_map(|(field, flow)| flow.as_deref().map(|flow| (field, flow)))
+    }
+
+    /// Flow ids double as file names under `flows/`, so each must stay
+    /// within one path segment.
+    pub fn validate(&self) -> Result<(), ManifestError> {
+        for (field, flow) in self.flows() {
+            let valid = !flow.is_empty()
+                && !flow.starts_with('.')
+                && flow
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'));
+            if !valid {
+                return Err(ManifestError::InvalidConfigurator {
+                    field,
+                    flow: flow.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+use std::fmt;
+
+use component_manifest::ManifestDiagnostic;
+use component_manifest::diagnostic::{escape_pointer_token, index_pointer, reason_pointer};
+use serde_json::Value;
+
+use super::ManifestError;
+
+/// One place where the manifest breaks the published JSON Schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON pointer of the offending value; empty for the document.
+    pub pointer: String,
+    pub message: String,
+}
+
+/// Every schema violation found in one pass, in document order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolations(pub Vec<SchemaViolation>);
+
+impl fmt::Display for SchemaViolations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages = self
+            .0
+            .iter()
+            .map(|violation| violation.message.as_str())
+            .collect::<Vec<_>>();
+        f.write_str(&messages.join(", "))
+    }
+}
+
+impl ManifestError {
+    /// Stable code for this error, independent of its message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ManifestError::Json(_) => "manifest.json",
+            ManifestError::Schema(_) => "manifest.schema",
+            ManifestError::InvalidSchema { .. } => "manifest.schema.invalid",
+            ManifestError::InvalidWorld { .. } => "manifest.world.invalid",
+            ManifestError::EmptyField(_) => "manifest.field.empty",
+            ManifestError::MissingOperations => "manifest.operations.missing",
+            ManifestError::InvalidOperation { .. } => "manifest.operation.invalid",
+            ManifestError::DuplicateOperation(_) => "manifest.operation.duplicate",
+            ManifestError::InvalidDeprecation { .. } => "manifest.deprecation.invalid",
+            ManifestError::DuplicateErrorCode(_) => "manifest.errors.duplicate",
+            ManifestError::InvalidErrorCode { .. } => "manifest.errors.invalid",
+            ManifestError::InvalidDefaultOperation { .. } => "manifest.default_operation.unknown",
+            ManifestError::InvalidRole(_) => "manifest.role.invalid",
+            ManifestError::MissingSupports => "manifest.supports.missing",
+            ManifestError::MissingProfiles => "manifest.profiles.missing",
+            ManifestError::InvalidProfileDefault { .. } => "manifest.profiles.default_unknown",
+            ManifestError::InvalidConfigurator { .. } => "manifest.configurators.invalid",
+            ManifestError::InvalidVersion { .. } => "manifest.version.invalid",
+            ManifestError::InvalidDescribeExport { .. } => "manifest.describe_export.invalid",
+            ManifestError::InvalidArtifactPath { .. } => "manifest.artifacts.path",
+            ManifestError::InvalidHashFormat { .. } => "manifest.hashes.format",
+            ManifestError::Capability(_) => "manifest.capabilities.invalid",
+            ManifestError::UnknownCapabilityPreset(_) => "manifest.capabilities.preset.unknown",
+            ManifestError::DuplicateSecretRequirement(_) => "manifest.secret.duplicate",
+            ManifestError::InvalidSecretRequirement { .. } => "manifest.secret.invalid",
+            ManifestError::Limits(_) => "manifest.limits.invalid",
+            ManifestError::Dependency(_) => "manifest.dependencies.invalid",
+            ManifestError::Provenance(_) => "manifest.provenance.invalid",
+            ManifestError::SchemaRef { .. } => "manifest.schema_ref.unresolved",
+            ManifestError::SchemaRefCycle(_) => "manifest.schema_ref.cycle",
+            ManifestError::NonOverridableField(_) => "manifest.overlay.non_overridable",
+            ManifestError::InvalidOverlay(_) => "manifest.overlay.invalid",
+            ManifestError::InvalidEnvironment(_) => "manifest.overlay.env_invalid",
+            ManifestError::OverlayIo { .. } => "manifest.overlay.io",
+            ManifestError::UnknownBuildProfile { .. } => "manifest.build.profile_unknown",
+            ManifestError::BuildProfile { source, .. } => source.code(),
+        }
+    }
+
+    /// The error as diagnostics: one per schema violation, one otherwise.
+    /// Named entries (operations, error codes, secrets) are resolved to
+    /// their index in `manifest` when it is given.
+    pub fn diagnostics(&self, manifest: Option<&Value>) -> Vec<ManifestDiagnostic> {
+        if let ManifestError::Schema(violations) = self {
+            return violations
+                .0
+                .iter()
+                .map(|violation| {
+                    let diagnostic = ManifestDiagnostic::new(
+                        self.code(),
+                        violation.pointer.clone(),
+                        violation.message.clone(),
+                    );
+                    match schema_suggestion(&violation.message) {
+                        Some(suggestion) => diagnostic.with_suggestion(suggestion),
+                        None => diagnostic,
+                    }
+                })
+                .collect();
+        }
+        let named = |array: &str, key: &str, name: &str| match manifest {
+            Some(manifest) => index_pointer(manifest, array, key, name),
+            None => format!("/{array}"),
+        };
+        let (pointer, suggestion): (String, Option<&str>) = match self {
+            ManifestError::Json(_) | ManifestError::Schema(_) => (String::new(), None),
+            ManifestError::InvalidSchema { field, reason } => (
+                format!(
+                    "{}{}",
+                    field_pointer(manifest, field),
+                    reason_pointer(reason).unwrap_or_default()
+                ),
+                None,
+            ),
+            ManifestError::InvalidWorld { .. } => (
+                "/world".into(),
+                Some("use `namespace:package/world@version`"),
+            ),
+            ManifestError::EmptyField(field) => (format!("/{field}"), None),
+            ManifestError::MissingOperations => {
+                ("/operations".into(), Some("declare at least one operation"))
+            }
+            ManifestError::InvalidOperation { operation } => (
+                format!("{}/name", named("operations", "name", operation)),
+                Some("operation names match ^[a-z][a-z0-9_.:-]*$"),
+            ),
+            ManifestError::DuplicateOperation(name) => (
+                format!("{}/name", last_named(manifest, "operations", "name", name)),
+                Some("rename or remove the repeated operation"),
+            ),
+            ManifestError::InvalidDeprecation { subject, .. } => (
+                format!("{}/deprecated", named("operations", "name", subject)),
+                None,
+            ),
+            ManifestError::DuplicateErrorCode(code) => (
+                format!("{}/code", last_named(manifest, "errors", "code", code)),
+                Some("remove the repeated error code"),
+            ),
+            ManifestError::InvalidErrorCode { code, .. } => {
+                (format!("{}/code", named("errors", "code", code)), None)
+            }
+            ManifestError::InvalidDefaultOperation { .. } => (
+                "/default_operation".into(),
+                Some("set it to the name of a declared operation"),
+            ),
+            ManifestError::InvalidRole(_) => {
+                ("/role".into(), Some("use `tool`, `adapter` or `process`"))
+            }
+            ManifestError::MissingSupports => (
+                "/supports".into(),
+                Some("list the flow kinds the component handles, e.g. [\"messaging\"]"),
+            ),
+            ManifestError::MissingProfiles => ("/profiles/supported".into(), None),
+            ManifestError::InvalidProfileDefault { .. } => (
+                "/profiles/default".into(),
+                Some("add it to profiles.supported or pick one of those"),
+            ),
+            ManifestError::InvalidConfigurator { field, .. } => (
+                format!("/configurators/{field}"),
+                Some("use a flow id of letters, digits, `-`, `_` and `.`"),
+            ),
+            ManifestError::InvalidVersion { .. } => (
+                "/version".into(),
+                Some("use a semantic version such as 0.1.0"),
+            ),
+            ManifestError::InvalidDescribeExport { .. } => ("/describe_export".into(), None),
+            ManifestError::InvalidArtifactPath { .. } => (
+                "/artifacts/component_wasm".into(),
+                Some("use a path relative to the manifest"),
+            ),
+            ManifestError::InvalidHashFormat { .. } => (
+                "/hashes/component_wasm".into(),
+                Some("run `greentic-component hash` to recompute it"),
+            ),
+            ManifestError::Capability(_) => ("/capabilities".into(), None),
+            ManifestError::UnknownCapabilityPreset(_) => (
+                "/capabilities/preset".into(),
+                Some("use http-tool, stateless-transform or stateful-messaging"),
+            ),
+            ManifestError::DuplicateSecretRequirement(key) => (
+                last_named(manifest, "secret_requirements", "key", key),
+                Some("remove the repeated requirement"),
+            ),
+            ManifestError::InvalidSecretRequirement { key, .. } => {
+                (named("secret_requirements", "key", key), None)
+            }
+            ManifestError::Limits(_) => ("/limits".into(), None),
+            ManifestError::Dependency(_) => ("/dependencies".into(), None),
+            ManifestError::Provenance(_) => ("/provenance".into(), None),
+            ManifestError::SchemaRef { reference, .. } => (
+                manifest
+                    .and_then(|manifest| ref_pointer(manifest, reference, &mut String::new()))
+                    .unwrap_or_default(),
+                Some("check the path is relative to the manifest and the file is valid JSON"),
+            ),
+            ManifestError::SchemaRefCycle(_) => (String::new(), None),
+            ManifestError::NonOverridableField(field) => (format!("/{field}"), None),
+            ManifestError::InvalidOverlay(_)
+            | ManifestError::InvalidEnvironment(_)
+            | ManifestError::OverlayIo { .. } => (String::new(), None),
+            ManifestError::UnknownBuildProfile { .. } => (
+                "/build/profiles".into(),
+                Some("declare the profile under build.profiles"),
+            ),
+            ManifestError::BuildProfile { profile, .. } => (
+                format!("/build/profiles/{}", escape_pointer_token(profile)),
+                None,
+            ),
+        };
+        let mut diagnostic = ManifestDiagnostic::new(self.code(), pointer, self.to_string());
+        diagnostic.suggestion = suggestion.map(str::to_string);
+        if let ManifestError::Json(err) = self
+            && err.line() > 0
+        {
+            diagnostic.line = Some(err.line());
+            diagnostic.column = Some(err.column());
+        }
+        vec![diagnostic]
+    }
+}
+
+/// `operations.<name>.input_schema` as a pointer, with the operation
+/// resolved to its index.
+fn field_pointer(manifest: Option<&Value>, field: &str) -> String {
+    match field
+        .strip_prefix("operations.")
+        .and_then(|rest| rest.rsplit_once('.'))
+    {
+        Some((name, schema)) => {
+            let operation = match manifest {
+                Some(manifest) => index_pointer(manifest, "operations", "name", name),
+                None => "/operations".into(),
+            };
+            format!("{operation}/{schema}")
+        }
+        None => format!("/{}", field.replace('.', "/")),
+    }
+}
+
+fn last_named(manifest: Option<&Value>, array: &str, key: &str, name: &str) -> String {
+    manifest
+        .and_then(|manifest| manifest.get(array))
+        .and_then(Value::as_array)
+        .and_then(|entries| {
+            entries
+                .iter()
+                .rposition(|entry| entry.get(key).and_then(Value::as_str) == Some(name))
+        })
+        .map(|index| format!("/{array}/{index}"))
+        .unwrap_or_else(|| format!("/{array}"))
+}
+
+/// Pointer to the `$ref` whose value is `reference`.
+fn ref_pointer(value: &Value, reference: &str, pointer: &mut String) -> Option<String> {
+    match value {
+        Value::Object(map) => {
+            if map.get("$ref").and_then(Value::as_str) == Some(reference) {
+                return Some(format!("{pointer}/$ref"));
+            }
+            for (key, child) in map {
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&escape_pointer_token(key));
+                let found = ref_pointer(child, reference, pointer);
+                pointer.truncate(len);
+                if found.is_some() {
+                    return found;
+                }
+            }
+            None
+        }
+        Value::Array(items) => items.iter().enumerate().find_map(|(index, child)| {
+            let len = pointer.len();
+            pointer.push_str(&format!("/{index}"));
+            let found = ref_pointer(child, reference, pointer);
+            pointer.truncate(len);
+            found
+        }),
+        _ => None,
+    }
+}
+
+fn schema_suggestion(message: &str) -> Option<&'static str> {
+    if message.contains("Additional properties are not allowed") {
+        Some("remove the field or check its spelling against the manifest schema")
+    } else if message.contains("is a required property") {
+        Some("add the missing field")
+    } else if message.contains("is not one of") {
+        Some("use one of the values the schema allows")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::{ManifestError, OPERATION_PATTERN};
+
+/// An error code the component may return from `invoke`, as declared under
+/// the manifest's `errors` catalog. Flow authors route on these codes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeclaredError {
+    pub code: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub retryable: bool,
+    /// Operations that can return the code; empty means every operation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub operations: Vec<String>,
+}
+
+impl DeclaredError {
+    pub fn applies_to(&self, operation: &str) -> bool {
+        self.operations.is_empty() || self.operations.iter().any(|op| op == operation)
+    }
+}
+
+pub(super) fn validate_error_catalog(
+    errors: &[DeclaredError],
+    operations: &HashSet<&String>,
+) -> Result<(), ManifestError> {
+    let mut seen = HashSet::new();
+    for error in errors {
+        if !seen.insert(error.code.as_str()) {
+            return Err(ManifestError::DuplicateErrorCode(error.code.clone()));
+        }
+        if !OPERATION_PATTERN.is_match(&error.code) {
+            return Err(ManifestError::InvalidErrorCode {
+                code: error.code.clone(),
+                reason: "codes must be lowercase and start with a letter".into(),
+            });
+        }
+        if let Some(unknown) = error
+            .operations
+            .iter()
+            .find(|operation| !operations.contains(operation))
+        {
+            return Err(ManifestError::InvalidErrorCode {
+                code: error.code.clone(),
+                reason: format!("operation `{unknown}` is not declared"),
+            });
+        }
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::{Component, Path, PathBuf};
 
 use jsonschema::{Validator, validator_for};
@@ -10,15 +10,37 @@ use serde_json::Value;
 use thiserror::Error;
 
 use crate::capabilities::{
-    Capabilities, ComponentConfigurators, ComponentProfiles, validate_capabilities,
+    Capabilities, ComponentProfiles, NetCaps, ToolsCaps, validate_capabilities,
 };
 use crate::limits::Limits;
 use crate::provenance::Provenance;
 use crate::telemetry::TelemetrySpec;
+pub use component_manifest::ComponentRole;
+use component_manifest::{ComponentDependency, ManifestValidator, deprecated_properties};
+pub use component_manifest::{SchemaDraft, ValidationOptions};
 use greentic_types::component::ComponentOperation;
 use greentic_types::flow::FlowKind;
 use greentic_types::{SecretKey, SecretRequirement};
 
+mod build_profiles;
+mod configurators;
+mod diagnostics;
+mod error_catalog;
+mod overlay;
+mod schema_refs;
+
+pub use build_profiles::{
+    BuildCommand, BuildProfile, BuildSpec, apply_build_profile, build_command, build_profile_names,
+};
+pub use component_manifest::{Deprecation, ManifestDiagnostic, locate_pointer};
+pub use configurators::{ConfiguratorMode, Configurators};
+pub use diagnostics::{SchemaViolation, SchemaViolations};
+pub use error_catalog::DeclaredError;
+pub use overlay::{
+    OVERRIDABLE_FIELDS, apply_env_overlay, apply_overlay, overlay_path, parse_manifest_with_overlay,
+};
+pub use schema_refs::{has_external_schema_refs, inline_local_refs, resolve_schema_refs};
+
 static RAW_SCHEMA: &str = include_str!("../../schemas/v1/component.manifest.schema.json");
 
 static COMPILED_SCHEMA: Lazy<Validator> = Lazy::new(|| {
@@ -35,26 +57,50 @@ pub struct ComponentManifest {
     pub id: ManifestId,
     pub name: String,
     pub version: Version,
+    /// `tool` when the manifest does not say.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<ComponentRole>,
     #[serde(default)]
     pub supports: Vec<FlowKind>,
     pub world: World,
     #[serde(default)]
     pub capabilities: Capabilities,
+    /// Raw socket access from `capabilities.net`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub net: Option<NetCaps>,
+    /// Components this one may call through the host, from `capabilities.tools`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<ToolsCaps>,
+    /// Hosts the HTTP client may reach, from `capabilities.host.http.domains`;
+    /// empty allows any host.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub http_domains: Vec<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub secret_requirements: Vec<SecretRequirement>,
     pub profiles: ComponentProfiles,
     #[serde(default)]
-    pub configurators: Option<ComponentConfigurators>,
+    pub configurators: Option<Configurators>,
     #[serde(default)]
     pub limits: Option<Limits>,
     #[serde(default)]
     pub telemetry: Option<TelemetrySpec>,
     pub describe_export: DescribeExport,
     pub operations: Vec<ComponentOperation>,
+    /// `deprecated` notices declared on operations, keyed by operation name.
+    /// Derived from `operations[].deprecated`, so never serialized on its own.
+    #[serde(skip_serializing)]
+    pub deprecated_operations: BTreeMap<String, Deprecation>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub default_operation: Option<String>,
+    /// Error codes `invoke` may return.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<DeclaredError>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<ComponentDependency>,
     #[serde(default)]
     pub provenance: Option<Provenance>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<BuildSpec>,
     pub artifacts: Artifacts,
     pub hashes: Hashes,
 }
@@ -63,6 +109,17 @@ impl ComponentManifest {
     pub fn wasm_artifact_path(&self, root: &Path) -> PathBuf {
         root.join(&self.artifacts.component_wasm)
     }
+
+    pub fn operation_deprecation(&self, operation: &str) -> Option<&Deprecation> {
+        self.deprecated_operations.get(operation)
+    }
+
+    /// Looks up `code` in the error catalog for `operation`.
+    pub fn declared_error(&self, operation: &str, code: &str) -> Option<&DeclaredError> {
+        self.errors
+            .iter()
+            .find(|error| error.code == code && error.applies_to(operation))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
@@ -196,27 +253,139 @@ pub fn schema() -> &'static str {
 }
 
 pub fn parse_manifest(raw: &str) -> Result<ComponentManifest, ManifestError> {
+    parse_manifest_value(serde_json::from_str(raw)?)
+}
+
+/// Parses a manifest that was already loaded as JSON, e.g. after
+/// [`resolve_schema_refs`] or [`apply_overlay`] ran over it.
+pub fn parse_manifest_value(value: Value) -> Result<ComponentManifest, ManifestError> {
+    let profiles = build_profile_names(&value);
+    if profiles.is_empty() {
+        return parse_value(value);
+    }
+    let manifest = parse_value(value.clone())?;
+    for profile in profiles {
+        let mut effective = value.clone();
+        apply_build_profile(&mut effective, &profile)?;
+        parse_value(effective).map_err(|source| ManifestError::BuildProfile {
+            profile,
+            source: Box::new(source),
+        })?;
+    }
+    Ok(manifest)
+}
+
+/// Like [`parse_manifest_value`], and additionally compiles `config_schema`
+/// and every operation schema with `options`, e.g. to pin the JSON Schema
+/// draft or assert `format`.
+pub fn parse_manifest_value_with(
+    value: Value,
+    options: &ValidationOptions,
+) -> Result<ComponentManifest, ManifestError> {
+    let config_schema = value.get("config_schema").cloned();
+    let manifest = parse_manifest_value(value)?;
+    let compile = |field: String, schema: &Value| {
+        options
+            .compile(schema)
+            .map(|_| ())
+            .map_err(|reason| ManifestError::InvalidSchema { field, reason })
+    };
+    if let Some(schema) = &config_schema {
+        compile("config_schema".into(), schema)?;
+    }
+    for operation in &manifest.operations {
+        compile(
+            format!("operations.{}.input_schema", operation.name),
+            &operation.input_schema,
+        )?;
+        compile(
+            format!("operations.{}.output_schema", operation.name),
+            &operation.output_schema,
+        )?;
+    }
+    Ok(manifest)
+}
+
+/// Parses the manifest with `build.profiles.<profile>` applied over the base.
+pub fn parse_manifest_for_profile(
+    raw: &str,
+    profile: &str,
+) -> Result<ComponentManifest, ManifestError> {
     let mut value: Value = serde_json::from_str(raw)?;
+    validate_value(&value)?;
+    apply_build_profile(&mut value, profile)?;
+    parse_value(value).map_err(|source| ManifestError::BuildProfile {
+        profile: profile.to_string(),
+        source: Box::new(source),
+    })
+}
+
+fn parse_value(mut value: Value) -> Result<ComponentManifest, ManifestError> {
+    expand_capability_preset(&mut value)?;
     normalize_state_delete(&mut value);
     validate_value(&value)?;
-    let raw_manifest: RawManifest = serde_json::from_value(value)?;
+    // `capabilities.net` and `capabilities.tools` are not part of the shared
+    // capability types yet, so they are read from the raw block alongside them.
+    let (net, tools) = match value.get("capabilities") {
+        Some(capabilities) => (
+            NetCaps::from_capabilities(capabilities)
+                .map_err(|err| ManifestError::Capability(err.to_string()))?,
+            ToolsCaps::from_capabilities(capabilities)
+                .map_err(|err| ManifestError::Capability(err.to_string()))?,
+        ),
+        None => (None, None),
+    };
+    let http_domains = take_http_domains(&mut value);
+    let mut raw_manifest: RawManifest = serde_json::from_value(value)?;
+    raw_manifest.net = net;
+    raw_manifest.tools = tools;
+    raw_manifest.http_domains = http_domains;
     raw_manifest.try_into()
 }
 
+/// `capabilities.host.http.domains` is not part of the shared capability
+/// types either, so it is taken out of the block before they are read.
+fn take_http_domains(value: &mut Value) -> Vec<String> {
+    value
+        .pointer_mut("/capabilities/host/http")
+        .and_then(Value::as_object_mut)
+        .and_then(|http| http.remove("domains"))
+        .and_then(|domains| serde_json::from_value(domains).ok())
+        .unwrap_or_default()
+}
+
 pub fn validate_manifest(raw: &str) -> Result<(), ManifestError> {
-    let value: Value = serde_json::from_str(raw)?;
+    let mut value: Value = serde_json::from_str(raw)?;
+    expand_capability_preset(&mut value)?;
     validate_value(&value)
 }
 
+/// Replaces `capabilities.preset` with the vetted block it names (see
+/// [`component_manifest::presets`]), keeping explicit entries as overrides.
+fn expand_capability_preset(value: &mut Value) -> Result<(), ManifestError> {
+    let Some(capabilities) = value.get_mut("capabilities") else {
+        return Ok(());
+    };
+    component_manifest::expand_capability_preset(capabilities).map_err(|err| match err {
+        component_manifest::ManifestError::UnknownCapabilityPreset(preset) => {
+            ManifestError::UnknownCapabilityPreset(preset)
+        }
+        other => ManifestError::Capability(other.to_string()),
+    })
+}
+
 fn validate_value(value: &Value) -> Result<(), ManifestError> {
-    let errors: Vec<String> = COMPILED_SCHEMA
+    let violations: Vec<SchemaViolation> = COMPILED_SCHEMA
         .iter_errors(value)
-        .map(|err| err.to_string())
+        .map(|err| SchemaViolation {
+            pointer: err.instance_path().to_string(),
+            message: err.to_string(),
+        })
         .collect();
-    if errors.is_empty() {
+    if violations.is_empty() {
         Ok(())
     } else {
-        Err(ManifestError::Schema(errors.join(", ")))
+        Err(ManifestError::Schema(SchemaViolations(violations)))
     }
 }
 
@@ -243,7 +412,9 @@ pub enum ManifestError {
     #[error("manifest json parse failed: {0}")]
     Json(#[from] serde_json::Error),
     #[error("manifest schema validation failed: {0}")]
-    Schema(String),
+    Schema(SchemaViolations),
+    #[error("schema `{field}` is invalid {reason}")]
+    InvalidSchema { field: String, reason: String },
     #[error("world identifier is invalid: `{world}`")]
     InvalidWorld { world: String },
     #[error("manifest field `{0}` cannot be empty")]
@@ -254,14 +425,24 @@ pub enum ManifestError {
     InvalidOperation { operation: String },
     #[error("duplicate operation `{0}` detected")]
     DuplicateOperation(String),
+    #[error("deprecation of `{subject}` is invalid: {reason}")]
+    InvalidDeprecation { subject: String, reason: String },
+    #[error("duplicate error code `{0}` detected")]
+    DuplicateErrorCode(String),
+    #[error("error code `{code}` is invalid: {reason}")]
+    InvalidErrorCode { code: String, reason: String },
     #[error("default_operation `{operation}` must match one of the declared operations")]
     InvalidDefaultOperation { operation: String },
+    #[error("role `{0}` is not one of tool, adapter, process")]
+    InvalidRole(String),
     #[error("component must support at least one flow kind")]
     MissingSupports,
     #[error("profiles.supported must include at least one profile identifier")]
     MissingProfiles,
     #[error("profiles.default `{default}` must be one of the supported profiles")]
     InvalidProfileDefault { default: String },
+    #[error("configurators.{field} `{flow}` is not a valid flow id")]
+    InvalidConfigurator { field: &'static str, flow: String },
     #[error("invalid semantic version `{version}`: {source}")]
     InvalidVersion {
         version: String,
@@ -276,14 +457,42 @@ pub enum ManifestError {
     InvalidHashFormat { hash: String },
     #[error("capability validation failed: {0}")]
     Capability(String),
+    #[error("unknown capability preset `{0}`")]
+    UnknownCapabilityPreset(String),
     #[error("duplicate secret requirement `{0}` detected")]
     DuplicateSecretRequirement(String),
     #[error("secret requirement `{key}` is invalid: {reason}")]
     InvalidSecretRequirement { key: String, reason: String },
     #[error("limits invalid: {0}")]
     Limits(String),
+    #[error("dependencies invalid: {0}")]
+    Dependency(String),
     #[error("provenance invalid: {0}")]
     Provenance(String),
+    #[error("schema reference `{reference}` could not be resolved: {reason}")]
+    SchemaRef { reference: String, reason: String },
+    #[error("schema references form a cycle: {0}")]
+    SchemaRefCycle(String),
+    #[error("field `{0}` cannot be overridden by an environment overlay")]
+    NonOverridableField(String),
+    #[error("environment overlay is invalid: {0}")]
+    InvalidOverlay(String),
+    #[error("environment name `{0}` must only contain ASCII letters, digits, `-` and `_`")]
+    InvalidEnvironment(String),
+    #[error("failed to read environment overlay {}: {source}", path.display())]
+    OverlayIo {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("build profile `{profile}` is not declared in build.profiles")]
+    UnknownBuildProfile { profile: String },
+    #[error("build profile `{profile}` is invalid: {source}")]
+    BuildProfile {
+        profile: String,
+        #[source]
+        source: Box<ManifestError>,
+    },
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -293,25 +502,41 @@ struct RawManifest {
     version: String,
     world: String,
     #[serde(default)]
+    role: Option<String>,
+    #[serde(default)]
     supports: Vec<FlowKind>,
     #[serde(default)]
     capabilities: Capabilities,
+    #[serde(skip)]
+    net: Option<NetCaps>,
+    #[serde(skip)]
+    tools: Option<ToolsCaps>,
+    #[serde(skip)]
+    http_domains: Vec<String>,
     #[serde(default)]
     secret_requirements: Vec<SecretRequirement>,
     #[serde(default)]
     profiles: ComponentProfiles,
     #[serde(default)]
-    configurators: Option<ComponentConfigurators>,
+    configurators: Option<Configurators>,
     #[serde(default)]
     limits: Option<Limits>,
     #[serde(default)]
     telemetry: Option<TelemetrySpec>,
     describe_export: String,
-    operations: Vec<ComponentOperation>,
+    #[serde(default)]
+    config_schema: Option<Value>,
+    operations: Vec<RawOperation>,
     #[serde(default)]
     default_operation: Option<String>,
     #[serde(default)]
+    errors: Vec<DeclaredError>,
+    #[serde(default)]
+    dependencies: Vec<ComponentDependency>,
+    #[serde(default)]
     provenance: Option<Provenance>,
+    #[serde(default)]
+    build: Option<BuildSpec>,
     artifacts: RawArtifacts,
     hashes: RawHashes,
 }
@@ -332,6 +557,13 @@ impl TryFrom<RawManifest> for ComponentManifest {
                 source,
             })?;
         let describe_export = DescribeExport::parse(raw.describe_export)?;
+        let role = raw
+            .role
+            .map(|role| {
+                role.parse::<ComponentRole>()
+                    .map_err(|_| ManifestError::InvalidRole(role))
+            })
+            .transpose()?;
         let artifacts = Artifacts::try_from(raw.artifacts)?;
         let hashes = Hashes::try_from(raw.hashes)?;
 
@@ -342,7 +574,7 @@ impl TryFrom<RawManifest> for ComponentManifest {
         validate_profiles(&raw.profiles)?;
 
         if let Some(configurators) = &raw.configurators {
-            validate_configurators(configurators)?;
+            configurators.validate()?;
         }
 
         validate_capabilities(&raw.capabilities)
@@ -356,6 +588,10 @@ impl TryFrom<RawManifest> for ComponentManifest {
                 .map_err(|err| ManifestError::Limits(err.to_string()))?;
         }
 
+        ManifestValidator::new()
+            .validate_dependencies(&raw.dependencies)
+            .map_err(|err| ManifestError::Dependency(err.to_string()))?;
+
         if let Some(provenance) = &raw.provenance {
             provenance
                 .validate()
@@ -366,7 +602,7 @@ impl TryFrom<RawManifest> for ComponentManifest {
             return Err(ManifestError::MissingOperations);
         }
         let mut seen_operations = HashSet::new();
-        for operation in &raw.operations {
+        for RawOperation { operation, .. } in &raw.operations {
             if !seen_operations.insert(&operation.name) {
                 return Err(ManifestError::DuplicateOperation(operation.name.clone()));
             }
@@ -376,11 +612,37 @@ impl TryFrom<RawManifest> for ComponentManifest {
                 });
             }
         }
+        let mut deprecated_operations = BTreeMap::new();
+        for RawOperation {
+            operation,
+            deprecated,
+        } in &raw.operations
+        {
+            let Some(deprecated) = deprecated else {
+                continue;
+            };
+            validate_deprecation(&operation.name, deprecated)?;
+            if let Some(replacement) = &deprecated.replacement
+                && !seen_operations.contains(replacement)
+            {
+                return Err(ManifestError::InvalidDeprecation {
+                    subject: operation.name.clone(),
+                    reason: format!("replacement `{replacement}` is not a declared operation"),
+                });
+            }
+            deprecated_operations.insert(operation.name.clone(), deprecated.clone());
+        }
+        error_catalog::validate_error_catalog(&raw.errors, &seen_operations)?;
+        if let Some(schema) = &raw.config_schema {
+            for (property, deprecated) in deprecated_properties(schema) {
+                validate_deprecation(&property, &deprecated)?;
+            }
+        }
         if let Some(default_operation) = &raw.default_operation
             && !raw
                 .operations
                 .iter()
-                .any(|op| op.name == *default_operation)
+                .any(|op| op.operation.name == *default_operation)
         {
             return Err(ManifestError::InvalidDefaultOperation {
                 operation: default_operation.clone(),
@@ -391,24 +653,54 @@ impl TryFrom<RawManifest> for ComponentManifest {
             id,
             name: raw.name,
             version,
+            role,
             world,
             supports: raw.supports,
             capabilities: raw.capabilities,
+            net: raw.net,
+            tools: raw.tools,
+            http_domains: raw.http_domains,
             secret_requirements: raw.secret_requirements,
             profiles: raw.profiles,
             configurators: raw.configurators,
             limits: raw.limits,
             telemetry: raw.telemetry,
             describe_export,
-            operations: raw.operations,
+            operations: raw.operations.into_iter().map(|op| op.operation).collect(),
+            deprecated_operations,
             default_operation: raw.default_operation,
+            errors: raw.errors,
+            dependencies: raw.dependencies,
             provenance: raw.provenance,
+            build: raw.build,
             artifacts,
             hashes,
         })
     }
 }
 
+/// Operation entry as written in the manifest: the shared operation type plus
+/// the manifest-only `deprecated` notice.
+#[derive(Debug, serde::Deserialize)]
+struct RawOperation {
+    #[serde(default)]
+    deprecated: Option<Deprecation>,
+    #[serde(flatten)]
+    operation: ComponentOperation,
+}
+
+fn validate_deprecation(subject: &str, deprecated: &Deprecation) -> Result<(), ManifestError> {
+    deprecated.validate(subject).map_err(|err| match err {
+        component_manifest::ManifestError::InvalidDeprecation { subject, reason } => {
+            ManifestError::InvalidDeprecation { subject, reason }
+        }
+        other => ManifestError::InvalidDeprecation {
+            subject: subject.to_string(),
+            reason: other.to_string(),
+        },
+    })
+}
+
 #[derive(Debug, serde::Deserialize)]
 struct RawArtifacts {
     component_wasm: String,
@@ -532,8 +824,3 @@ fn validate_profiles(profiles: &ComponentProfiles) -> Result<(), ManifestError>
     }
     Ok(())
 }
-
-fn validate_configurators(_configurators: &ComponentConfigurators) -> Result<(), ManifestError> {
-    // Flow identifiers are validated by greentic-types, so no additional checks are required.
-    Ok(())
-}
@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+
+pub use component_manifest::OVERRIDABLE_FIELDS;
+use serde_json::Value;
+
+use super::ManifestError;
+
+/// Location of the `<env>` overlay for a manifest, e.g.
+/// `component.manifest.json` -> `component.manifest.override.prod.json`.
+/// Environment names other than ASCII letters, digits, `-` and `_` are
+/// rejected.
+pub fn overlay_path(manifest_path: &Path, env: &str) -> Result<PathBuf, ManifestError> {
+    component_manifest::overlay_path(manifest_path, env).map_err(from_shared)
+}
+
+/// Deep-merges an environment overlay over the base manifest in place.
+///
+/// Only [`OVERRIDABLE_FIELDS`] may appear in the overlay; `null` removes an
+/// entry (JSON merge-patch semantics). The merged value still needs schema
+/// validation, which [`parse_manifest_with_overlay`] performs.
+pub fn apply_overlay(manifest: &mut Value, overlay: &Value) -> Result<(), ManifestError> {
+    component_manifest::apply_overlay(manifest, overlay).map_err(from_shared)
+}
+
+/// Applies the `<env>` overlay next to `manifest_path` when it exists and
+/// returns its path.
+pub fn apply_env_overlay(
+    manifest: &mut Value,
+    manifest_path: &Path,
+    env: &str,
+) -> Result<Option<PathBuf>, ManifestError> {
+    component_manifest::apply_env_overlay(manifest, manifest_path, env).map_err(from_shared)
+}
+
+/// Parses a manifest with an environment overlay deep-merged over it.
+pub fn parse_manifest_with_overlay(
+    raw: &str,
+    overlay_raw: &str,
+) -> Result<super::ComponentManifest, ManifestError> {
+    let mut value: Value = serde_json::from_str(raw)?;
+    let overlay: Value = serde_json::from_str(overlay_raw)?;
+    apply_overlay(&mut value, &overlay)?;
+    super::parse_manifest_value(value)
+}
+
+fn from_shared(err: component_manifest::ManifestError) -> ManifestError {
+    use component_manifest::ManifestError as Shared;
+    match err {
+        Shared::Json(err) => ManifestError::Json(err),
+        Shared::NonOverridableField(field) => ManifestError::NonOverridableField(field),
+        Shared::InvalidOverlay(reason) => ManifestError::InvalidOverlay(reason),
+        Shared::InvalidEnvironment(env) => ManifestError::InvalidEnvironment(env),
+        Shared::OverlayIo { path, source } => ManifestError::OverlayIo { path, source },
+        other => ManifestError::InvalidOverlay(other.to_string()),
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use component_manifest::diagnostic::escape_pointer_token;
+use serde_json::{Map, Value};
+
+use super::ManifestError;
+
+/// Inlines external `$ref`s in operation and config schemas.
+///
+/// References such as `{"$ref": "schemas/io/input.schema.json"}` (optionally
+/// with a `#/json/pointer` fragment) are resolved relative to the file that
+/// contains them and must stay inside `manifest_dir`. URLs are left untouched.
+/// A `$ref` with sibling keywords is rewritten to `allOf: [<resolved>]` so the
+/// siblings keep applying.
+///
+/// Local references (`#/...`) inside an external file point into that file,
+/// so they are inlined along with it. Recursive ones cannot be inlined; the
+/// file is bundled under `$defs/<path relative to manifest_dir>` of the
+/// operation or config schema and the references are rebased onto it.
+pub fn resolve_schema_refs(manifest: &mut Value, manifest_dir: &Path) -> Result<(), ManifestError> {
+    let root = manifest_dir
+        .canonicalize()
+        .map_err(|err| ManifestError::SchemaRef {
+            reference: manifest_dir.display().to_string(),
+            reason: err.to_string(),
+        })?;
+    let mut resolver = Resolver {
+        root: &root,
+        stack: Vec::new(),
+        bundles: BTreeMap::new(),
+    };
+    if let Some(operations) = manifest.get_mut("operations").and_then(Value::as_array_mut) {
+        for operation in operations {
+            for field in ["input_schema", "output_schema"] {
+                if let Some(schema) = operation.get_mut(field) {
+                    resolver.resolve_root(schema)?;
+                }
+            }
+        }
+    }
+    if let Some(schema) = manifest.get_mut("config_schema") {
+        resolver.resolve_root(schema)?;
+    }
+    Ok(())
+}
+
+/// The canonical form of a schema for hashing: local references are inlined
+/// and `$defs`/`definitions` that nothing points at any more are dropped, so
+/// a schema hashes the same whether it was written inline, with `$defs`, or
+/// spread over referenced files. Recursive references are kept.
+pub fn inline_local_refs(schema: &Value) -> Value {
+    let mut canonical = schema.clone();
+    inline_local(&mut canonical, schema, &mut Vec::new(), &mut |reference| {
+        reference.to_string()
+    });
+    drop_unused_defs(&mut canonical);
+    canonical
+}
+
+/// Returns whether the manifest still carries external schema references.
+pub fn has_external_schema_refs(manifest: &Value) -> bool {
+    match manifest {
+        Value::Object(map) => map.iter().any(|(key, value)| {
+            (key == "$ref" && value.as_str().is_some_and(is_external_ref))
+                || has_external_schema_refs(value)
+        }),
+        Value::Array(items) => items.iter().any(has_external_schema_refs),
+        _ => false,
+    }
+}
+
+fn is_external_ref(reference: &str) -> bool {
+    !reference.starts_with('#') && !reference.contains("://")
+}
+
+/// Replaces local references in `value` with what they point at in
+/// `document`. A reference already being expanded is recursive; `recursive`
+/// decides what it becomes.
+fn inline_local(
+    value: &mut Value,
+    document: &Value,
+    stack: &mut Vec<String>,
+    recursive: &mut dyn FnMut(&str) -> String,
+) {
+    match value {
+        Value::Object(map) => {
+            let local = map
+                .get("$ref")
+                .and_then(Value::as_str)
+                .filter(|reference| reference.starts_with('#'))
+                .map(str::to_string);
+            if let Some(reference) = local {
+                if stack.contains(&reference) {
+                    map.insert("$ref".into(), Value::String(recursive(&reference)));
+                } else if let Some(target) = document.pointer(&reference[1..]) {
+                    let mut target = target.clone();
+                    stack.push(reference);
+                    inline_local(&mut target, document, stack, recursive);
+                    stack.pop();
+                    map.remove("$ref");
+                    for child in map.values_mut() {
+                        inline_local(child, document, stack, recursive);
+                    }
+                    merge_resolved(value, target);
+                    return;
+                }
+            }
+            for child in map.values_mut() {
+                inline_local(child, document, stack, recursive);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                inline_local(item, document, stack, recursive);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Puts `resolved` where a `$ref` was removed from `value`: in its place when
+/// nothing else is left, otherwise as an extra `allOf` entry.
+fn merge_resolved(value: &mut Value, resolved: Value) {
+    let Some(map) = value.as_object_mut() else {
+        return;
+    };
+    if map.is_empty() {
+        *value = resolved;
+        return;
+    }
+    match map.get_mut("allOf").and_then(Value::as_array_mut) {
+        Some(all_of) => all_of.push(resolved),
+        None => {
+            map.insert("allOf".into(), Value::Array(vec![resolved]));
+        }
+    }
+}
+
+fn has_local_refs(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => map.iter().any(|(key, value)| {
+            (key == "$ref" && value.as_str().is_some_and(|r| r.starts_with('#')))
+                || has_local_refs(value)
+        }),
+        Value::Array(items) => items.iter().any(has_local_refs),
+        _ => false,
+    }
+}
+
+fn drop_unused_defs(schema: &mut Value) {
+    if has_local_refs(schema) {
+        return;
+    }
+    if let Some(map) = schema.as_object_mut() {
+        map.remove("$defs");
+        map.remove("definitions");
+    }
+}
+
+struct Resolver<'a> {
+    root: &'a Path,
+    stack: Vec<String>,
+    /// Files with recursive local references, keyed by their `$defs` entry.
+    /// `None` while the file itself is being resolved.
+    bundles: BTreeMap<String, Option<Value>>,
+}
+
+impl Resolver<'_> {
+    fn resolve_root(&mut self, schema: &mut Value) -> Result<(), ManifestError> {
+        let root = self.root;
+        self.resolve(schema, root)?;
+        let bundles = std::mem::take(&mut self.bundles);
+        if bundles.is_empty() {
+            return Ok(());
+        }
+        if !schema.is_object() {
+            *schema = Value::Object(Map::from_iter([(
+                "allOf".to_string(),
+                Value::Array(vec![schema.take()]),
+            )]));
+        }
+        let map = schema
+            .as_object_mut()
+            .expect("schema coerced to object above");
+        let defs = map
+            .entry("$defs")
+            .or_insert_with(|| Value::Object(Map::new()));
+        let Some(defs) = defs.as_object_mut() else {
+            return Err(ManifestError::SchemaRef {
+                reference: "$defs".into(),
+                reason: "cannot bundle referenced files: `$defs` is not an object".into(),
+            });
+        };
+        for (name, bundle) in bundles {
+            if defs.contains_key(&name) {
+                return Err(ManifestError::SchemaRef {
+                    reference: name.clone(),
+                    reason: "`$defs` already has an entry with this name".into(),
+                });
+            }
+            defs.insert(name, bundle.expect("bundles are complete once resolved"));
+        }
+        Ok(())
+    }
+
+    fn resolve(&mut self, value: &mut Value, base_dir: &Path) -> Result<(), ManifestError> {
+        match value {
+            Value::Object(map) => {
+                let external = map
+                    .get("$ref")
+                    .and_then(Value::as_str)
+                    .filter(|reference| is_external_ref(reference))
+                    .map(str::to_string);
+                if let Some(reference) = external {
+                    let resolved = self.load(&reference, base_dir)?;
+                    map.remove("$ref");
+                    for child in map.values_mut() {
+                        self.resolve(child, base_dir)?;
+                    }
+                    merge_resolved(value, resolved);
+                    return Ok(());
+                }
+                for child in map.values_mut() {
+                    self.resolve(child, base_dir)?;
+                }
+                Ok(())
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.resolve(item, base_dir)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn load(&mut self, reference: &str, base_dir: &Path) -> Result<Value, ManifestError> {
+        let (file, pointer) = match reference.split_once('#') {
+            Some((file, pointer)) => (file, Some(pointer)),
+            None => (reference, None),
+        };
+        let err = |reason: String| ManifestError::SchemaRef {
+            reference: reference.to_string(),
+            reason,
+        };
+        let path = base_dir
+            .join(file)
+            .canonicalize()
+            .map_err(|io| err(io.to_string()))?;
+        if !path.starts_with(self.root) {
+            return Err(err("reference escapes the manifest directory".into()));
+        }
+        let key = format!("{}#{}", path.display(), pointer.unwrap_or(""));
+        if self.stack.contains(&key) {
+            let mut chain = self.stack.clone();
+            chain.push(key);
+            return Err(ManifestError::SchemaRefCycle(chain.join(" -> ")));
+        }
+
+        let contents = fs::read_to_string(&path).map_err(|io| err(io.to_string()))?;
+        let document: Value =
+            serde_json::from_str(&contents).map_err(|json| err(json.to_string()))?;
+        let pointer = pointer.unwrap_or("");
+        let mut resolved = document
+            .pointer(pointer)
+            .cloned()
+            .ok_or_else(|| err(format!("pointer `{pointer}` not found")))?;
+
+        // Local references point into this file; inline them before the
+        // fragment moves into another document.
+        let bundle = path
+            .strip_prefix(self.root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let mut recursive = false;
+        inline_local(
+            &mut resolved,
+            &document,
+            &mut vec![format!("#{pointer}")],
+            &mut |local| {
+                recursive = true;
+                rebase(&bundle, local)
+            },
+        );
+        if pointer.is_empty() {
+            drop_unused_defs(&mut resolved);
+        }
+
+        self.stack.push(key);
+        let file_dir = path.parent().map(PathBuf::from).unwrap_or_default();
+        let mut outcome = self.resolve(&mut resolved, &file_dir);
+        if outcome.is_ok() && recursive && !self.bundles.contains_key(&bundle) {
+            outcome = self.bundle(&bundle, document, &file_dir);
+        }
+        self.stack.pop();
+        outcome?;
+        Ok(resolved)
+    }
+
+    /// Adds the whole file to the bundles, with its local references rebased
+    /// onto `$defs/<bundle>`.
+    fn bundle(
+        &mut self,
+        bundle: &str,
+        mut document: Value,
+        file_dir: &Path,
+    ) -> Result<(), ManifestError> {
+        self.bundles.insert(bundle.to_string(), None);
+        rebase_all(&mut document, bundle);
+        self.resolve(&mut document, file_dir)?;
+        self.bundles.insert(bundle.to_string(), Some(document));
+        Ok(())
+    }
+}
+
+/// `#/pointer` in a bundled file, as seen from the schema it is bundled into.
+fn rebase(bundle: &str, local: &str) -> String {
+    format!("#/$defs/{}{}", escape_pointer_token(bundle), &local[1..])
+}
+
+fn rebase_all(value: &mut Value, bundle: &str) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                match child {
+                    Value::String(reference) if key == "$ref" && reference.starts_with('#') => {
+                        *reference = rebase(bundle, reference);
+                    }
+                    _ => rebase_all(child, bundle),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                rebase_all(item, bundle);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,574 @@
+//! Drift detection between `component.manifest.json` and a component's
+//! `describe()` payload.
+//!
+//! `describe()` is generated from the component's own types, so it is treated
+//! as the source of truth. Schemas are compared after dropping annotations
+//! (titles, descriptions, defaults, `x-` extensions) that describe does not
+//! carry, so only structural differences are reported.
+
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use greentic_types::schemas::component::v0_6_0::{ComponentDescribe, schema_hash};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+use crate::manifest::inline_local_refs;
+
+/// Schema keywords that carry no structure and are ignored when comparing.
+const ANNOTATION_KEYS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    /// `describe()` exports an operation the manifest does not declare.
+    MissingInManifest,
+    /// The manifest declares an operation `describe()` does not export.
+    MissingInDescribe,
+    /// Both sides have the schema but it differs structurally.
+    SchemaMismatch,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SyncDrift {
+    pub kind: DriftKind,
+    /// Manifest location, e.g. `operations.run.input_schema`.
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    pub drift: Vec<SyncDrift>,
+}
+
+impl SyncReport {
+    pub fn in_sync(&self) -> bool {
+        self.drift.is_empty()
+    }
+}
+
+/// Compares manifest operations and `config_schema` with `describe`.
+///
+/// `manifest` must have its schema `$ref`s resolved (see
+/// [`resolve_schema_refs`](crate::resolve_schema_refs)).
+pub fn check_manifest_sync(manifest: &Value, describe: &ComponentDescribe) -> SyncReport {
+    let mut report = SyncReport::default();
+    let declared = manifest_operations(manifest);
+
+    for operation in &describe.operations {
+        let id = operation.id.as_str();
+        let Some(entry) = declared
+            .iter()
+            .find(|(name, _)| *name == id)
+            .map(|(_, e)| *e)
+        else {
+            report.drift.push(SyncDrift {
+                kind: DriftKind::MissingInManifest,
+                path: format!("operations.{id}"),
+                message: format!("describe() exports `{id}` but the manifest does not declare it"),
+            });
+            continue;
+        };
+        for (field, schema) in [
+            ("input_schema", &operation.input.schema),
+            ("output_schema", &operation.output.schema),
+        ] {
+            compare_schema(
+                &mut report,
+                &format!("operations.{id}.{field}"),
+                entry.get(field),
+                schema,
+            );
+        }
+    }
+    for (name, _) in &declared {
+        if !describe.operations.iter().any(|op| op.id == *name) {
+            report.drift.push(SyncDrift {
+                kind: DriftKind::MissingInDescribe,
+                path: format!("operations.{name}"),
+                message: format!("manifest declares `{name}` but describe() does not export it"),
+            });
+        }
+    }
+    compare_schema(
+        &mut report,
+        "config_schema",
+        manifest.get("config_schema"),
+        &describe.config_schema,
+    );
+    report
+}
+
+/// Rewrites `manifest` so its operations and `config_schema` match
+/// `describe`. Schemas that already agree are left untouched, so file
+/// references and annotations survive; operations describe does not export
+/// are dropped and new ones are appended. Returns the number of changes.
+///
+/// `resolved` is the same manifest with schema references resolved and is
+/// only used for the comparison.
+pub fn apply_describe(
+    manifest: &mut Value,
+    resolved: &Value,
+    describe: &ComponentDescribe,
+) -> usize {
+    let resolved_ops = manifest_operations(resolved);
+    let mut changes = 0;
+    let existing = manifest
+        .get("operations")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    let mut operations = Vec::new();
+    for operation in &describe.operations {
+        let id = operation.id.as_str();
+        let position = existing
+            .iter()
+            .position(|entry| entry.get("name").and_then(Value::as_str) == Some(id));
+        let mut entry = match position {
+            Some(index) => existing[index].clone(),
+            None => {
+                changes += 1;
+                json!({ "name": id })
+            }
+        };
+        let current = resolved_ops
+            .iter()
+            .find(|(name, _)| *name == id)
+            .map(|(_, entry)| *entry);
+        for (field, schema) in [
+            ("input_schema", &operation.input.schema),
+            ("output_schema", &operation.output.schema),
+        ] {
+            let expected = schema_ir_to_json_schema(schema);
+            let actual = current.and_then(|entry| entry.get(field));
+            if actual.is_none_or(|actual| normalize(actual) != normalize(&expected)) {
+                entry[field] = expected;
+                if position.is_some() {
+                    changes += 1;
+                }
+            }
+        }
+        operations.push(entry);
+    }
+    changes += existing.len().saturating_sub(
+        existing
+            .iter()
+            .filter(|entry| {
+                let name = entry.get("name").and_then(Value::as_str);
+                describe
+                    .operations
+                    .iter()
+                    .any(|op| Some(op.id.as_str()) == name)
+            })
+            .count(),
+    );
+    manifest["operations"] = Value::Array(operations);
+
+    let expected = schema_ir_to_json_schema(&describe.config_schema);
+    let actual = resolved.get("config_schema");
+    if actual.is_none_or(|actual| normalize(actual) != normalize(&expected)) {
+        manifest["config_schema"] = expected;
+        changes += 1;
+    }
+    changes
+}
+
+/// An operation whose `schema_hash` does not match its schemas.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SchemaHashMismatch {
+    pub operation: String,
+    /// Where the mismatch was found, e.g. `describe.operations[0].schema_hash`
+    /// or `operations.run.input_schema`.
+    pub path: String,
+    pub message: String,
+}
+
+/// Recomputes each describe operation's `schema_hash` twice: from the
+/// SchemaIR describe reports, and from the manifest's JSON Schemas converted
+/// to SchemaIR. Either disagreeing with the reported hash is a mismatch.
+///
+/// Manifest schemas that use keywords SchemaIR cannot express are skipped
+/// here; [`check_manifest_sync`] still reports them as drift. `manifest`
+/// must have its schema `$ref`s resolved; local references are inlined with
+/// [`inline_local_refs`] before hashing.
+pub fn verify_schema_hashes(
+    manifest: &Value,
+    describe: &ComponentDescribe,
+) -> Vec<SchemaHashMismatch> {
+    let declared = manifest_operations(manifest);
+    let canonical_ir = |schema: &Value| json_schema_to_schema_ir(&inline_local_refs(schema));
+    let config_schema = manifest.get("config_schema").map(canonical_ir).transpose();
+    let mut mismatches = Vec::new();
+    for (idx, operation) in describe.operations.iter().enumerate() {
+        let id = operation.id.as_str();
+        let mut mismatch = |path: String, message: String| {
+            mismatches.push(SchemaHashMismatch {
+                operation: id.to_string(),
+                path,
+                message,
+            })
+        };
+        match schema_hash(
+            &operation.input.schema,
+            &operation.output.schema,
+            &describe.config_schema,
+        ) {
+            Ok(expected) if expected == operation.schema_hash => {}
+            Ok(expected) => mismatch(
+                format!("describe.operations[{idx}].schema_hash"),
+                format!(
+                    "describe() reports {} but its schemas hash to {expected}",
+                    operation.schema_hash
+                ),
+            ),
+            Err(err) => mismatch(
+                format!("describe.operations[{idx}].schema_hash"),
+                format!("schema_hash computation failed: {err}"),
+            ),
+        }
+
+        let Some(entry) = declared
+            .iter()
+            .find(|(name, _)| *name == id)
+            .map(|(_, e)| *e)
+        else {
+            continue;
+        };
+        let converted = (
+            entry.get("input_schema").map(canonical_ir),
+            entry.get("output_schema").map(canonical_ir),
+            &config_schema,
+        );
+        let (Some(Ok(input)), Some(Ok(output)), Ok(config)) = converted else {
+            continue;
+        };
+        let config = config.as_ref().unwrap_or(&describe.config_schema);
+        let Ok(actual) = schema_hash(&input, &output, config) else {
+            continue;
+        };
+        if actual == operation.schema_hash {
+            continue;
+        }
+        let differs = [
+            ("input_schema", &input, &operation.input.schema),
+            ("output_schema", &output, &operation.output.schema),
+            ("config_schema", config, &describe.config_schema),
+        ]
+        .into_iter()
+        .filter(|(_, manifest, describe)| {
+            normalize(&schema_ir_to_json_schema(manifest))
+                != normalize(&schema_ir_to_json_schema(describe))
+        })
+        .map(|(field, _, _)| field)
+        .collect::<Vec<_>>();
+        let path = match differs.as_slice() {
+            ["config_schema"] => "config_schema".to_string(),
+            [field, ..] if *field != "config_schema" => format!("operations.{id}.{field}"),
+            _ => format!("operations.{id}"),
+        };
+        mismatch(
+            path,
+            format!(
+                "manifest schemas hash to {actual} but describe() reports {}",
+                operation.schema_hash
+            ),
+        );
+    }
+    mismatches
+}
+
+/// Converts a manifest JSON Schema to SchemaIR, the inverse of
+/// [`schema_ir_to_json_schema`]. Annotations are ignored; keywords SchemaIR
+/// has no equivalent for are an error.
+pub fn json_schema_to_schema_ir(schema: &Value) -> Result<SchemaIr, String> {
+    let Some(map) = schema.as_object() else {
+        return Err("schema must be an object".into());
+    };
+    if let Some(id) = map.get("$ref").and_then(Value::as_str) {
+        return Ok(SchemaIr::Ref { id: id.to_string() });
+    }
+    if let Some(values) = map.get("enum") {
+        return Ok(SchemaIr::Enum {
+            values: serde_json::from_value(values.clone())
+                .map_err(|err| format!("invalid `enum`: {err}"))?,
+        });
+    }
+    if let Some(variants) = map.get("oneOf").and_then(Value::as_array) {
+        return Ok(SchemaIr::OneOf {
+            variants: variants
+                .iter()
+                .map(json_schema_to_schema_ir)
+                .collect::<Result<_, _>>()?,
+        });
+    }
+    match map.get("type").and_then(Value::as_str) {
+        Some("object") => {
+            let properties = match map.get("properties") {
+                None => Default::default(),
+                Some(Value::Object(properties)) => properties
+                    .iter()
+                    .map(|(name, schema)| Ok((name.clone(), json_schema_to_schema_ir(schema)?)))
+                    .collect::<Result<_, String>>()?,
+                Some(_) => return Err("`properties` must be an object".into()),
+            };
+            let required = map
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            let additional = match map.get("additionalProperties") {
+                None | Some(Value::Bool(true)) => AdditionalProperties::Allow,
+                Some(Value::Bool(false)) => AdditionalProperties::Forbid,
+                Some(extra) => {
+                    AdditionalProperties::Schema(Box::new(json_schema_to_schema_ir(extra)?))
+                }
+            };
+            Ok(SchemaIr::Object {
+                properties,
+                required,
+                additional,
+            })
+        }
+        Some("array") => Ok(SchemaIr::Array {
+            items: Box::new(json_schema_to_schema_ir(
+                map.get("items").ok_or("array schema needs `items`")?,
+            )?),
+            min_items: bound(map, "minItems")?,
+            max_items: bound(map, "maxItems")?,
+        }),
+        Some("string") if map.get("contentEncoding").and_then(Value::as_str) == Some("base64") => {
+            Ok(SchemaIr::Bytes)
+        }
+        Some("string") => Ok(SchemaIr::String {
+            min_len: bound(map, "minLength")?,
+            max_len: bound(map, "maxLength")?,
+            regex: bound(map, "pattern")?,
+            format: bound(map, "format")?,
+        }),
+        Some("integer") => Ok(SchemaIr::Int {
+            min: bound(map, "minimum")?,
+            max: bound(map, "maximum")?,
+        }),
+        Some("number") => Ok(SchemaIr::Float {
+            min: bound(map, "minimum")?,
+            max: bound(map, "maximum")?,
+        }),
+        Some("boolean") => Ok(SchemaIr::Bool),
+        Some("null") => Ok(SchemaIr::Null),
+        Some(other) => Err(format!("unsupported type `{other}`")),
+        None => Err("schema has no `type`, `enum`, `oneOf` or `$ref`".into()),
+    }
+}
+
+/// Converts a describe schema to the JSON Schema the manifest uses.
+pub fn schema_ir_to_json_schema(schema: &SchemaIr) -> Value {
+    match schema {
+        SchemaIr::Object {
+            properties,
+            required,
+            additional,
+        } => {
+            let mut out = Map::new();
+            out.insert("type".into(), json!("object"));
+            out.insert(
+                "properties".into(),
+                Value::Object(
+                    properties
+                        .iter()
+                        .map(|(name, schema)| (name.clone(), schema_ir_to_json_schema(schema)))
+                        .collect(),
+                ),
+            );
+            if !required.is_empty() {
+                out.insert("required".into(), json!(required));
+            }
+            let additional = match additional {
+                AdditionalProperties::Allow => Value::Bool(true),
+                AdditionalProperties::Forbid => Value::Bool(false),
+                AdditionalProperties::Schema(extra) => schema_ir_to_json_schema(extra),
+            };
+            out.insert("additionalProperties".into(), additional);
+            Value::Object(out)
+        }
+        SchemaIr::Array {
+            items,
+            min_items,
+            max_items,
+        } => with_bounds(
+            json!({ "type": "array", "items": schema_ir_to_json_schema(items) }),
+            [
+                ("minItems", json!(min_items)),
+                ("maxItems", json!(max_items)),
+            ],
+        ),
+        SchemaIr::String {
+            min_len,
+            max_len,
+            regex,
+            format,
+        } => with_bounds(
+            json!({ "type": "string" }),
+            [
+                ("minLength", json!(min_len)),
+                ("maxLength", json!(max_len)),
+                ("pattern", json!(regex)),
+                ("format", json!(format)),
+            ],
+        ),
+        SchemaIr::Int { min, max } => with_bounds(
+            json!({ "type": "integer" }),
+            [("minimum", json!(min)), ("maximum", json!(max))],
+        ),
+        SchemaIr::Float { min, max } => with_bounds(
+            json!({ "type": "number" }),
+            [("minimum", json!(min)), ("maximum", json!(max))],
+        ),
+        SchemaIr::Enum { values } => json!({ "enum": values }),
+        SchemaIr::OneOf { variants } => json!({
+            "oneOf": variants.iter().map(schema_ir_to_json_schema).collect::<Vec<_>>()
+        }),
+        SchemaIr::Bool => json!({ "type": "boolean" }),
+        SchemaIr::Null => json!({ "type": "null" }),
+        SchemaIr::Bytes => json!({ "type": "string", "contentEncoding": "base64" }),
+        SchemaIr::Ref { id } => json!({ "$ref": id }),
+    }
+}
+
+/// Reads an optional keyword into whatever type the SchemaIR field uses.
+fn bound<T: DeserializeOwned>(map: &Map<String, Value>, key: &str) -> Result<Option<T>, String> {
+    map.get(key)
+        .map(|value| serde_json::from_value(value.clone()))
+        .transpose()
+        .map_err(|err| format!("invalid `{key}`: {err}"))
+}
+
+fn with_bounds<const N: usize>(mut schema: Value, bounds: [(&str, Value); N]) -> Value {
+    for (key, value) in bounds {
+        if !value.is_null() {
+            schema[key] = value;
+        }
+    }
+    schema
+}
+
+fn manifest_operations(manifest: &Value) -> Vec<(&str, &Value)> {
+    manifest
+        .get("operations")
+        .and_then(Value::as_array)
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|entry| Some((entry.get("name")?.as_str()?, entry)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn compare_schema(
+    report: &mut SyncReport,
+    path: &str,
+    actual: Option<&Value>,
+    expected: &SchemaIr,
+) {
+    let expected = normalize(&schema_ir_to_json_schema(expected));
+    let message = match actual {
+        None => "missing from the manifest".to_string(),
+        Some(actual) => match first_difference(&normalize(actual), &expected, "") {
+            None => return,
+            Some(at) if at.is_empty() => "differs from describe()".to_string(),
+            Some(at) => format!("differs from describe() at {at}"),
+        },
+    };
+    report.drift.push(SyncDrift {
+        kind: DriftKind::SchemaMismatch,
+        path: path.to_string(),
+        message,
+    });
+}
+
+/// Strips annotations and defaults that do not change what a schema accepts.
+fn normalize(schema: &Value) -> Value {
+    match schema {
+        Value::Object(map) => {
+            let mut out = Map::new();
+            for (key, value) in map {
+                if ANNOTATION_KEYS.contains(&key.as_str())
+                    || key.starts_with("x-")
+                    || key.starts_with("x_")
+                {
+                    continue;
+                }
+                match (key.as_str(), value) {
+                    ("required", Value::Array(items)) if items.is_empty() => continue,
+                    ("additionalProperties", Value::Bool(true)) => continue,
+                    ("required", Value::Array(items)) => {
+                        let mut sorted = items.clone();
+                        sorted.sort_by_key(|item| item.to_string());
+                        out.insert(key.clone(), Value::Array(sorted));
+                    }
+                    // Property names are data, not keywords, so only their
+                    // schemas are normalized.
+                    ("properties", Value::Object(props)) => {
+                        out.insert(
+                            key.clone(),
+                            Value::Object(
+                                props
+                                    .iter()
+                                    .map(|(name, schema)| (name.clone(), normalize(schema)))
+                                    .collect(),
+                            ),
+                        );
+                    }
+                    _ => {
+                        out.insert(key.clone(), normalize(value));
+                    }
+                }
+            }
+            // An object schema without `properties` is the same as an empty one.
+            if out.get("type").and_then(Value::as_str) == Some("object") {
+                out.entry("properties")
+                    .or_insert_with(|| Value::Object(Map::new()));
+            }
+            Value::Object(out)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(normalize).collect()),
+        other => other.clone(),
+    }
+}
+
+fn first_difference(actual: &Value, expected: &Value, path: &str) -> Option<String> {
+    match (actual, expected) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys = a.keys().chain(b.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+            keys.into_iter().find_map(|key| {
+                let child = format!("{path}/{key}");
+                match (a.get(key), b.get(key)) {
+                    (Some(left), Some(right)) => first_difference(left, right, &child),
+                    _ => Some(child),
+                }
+            })
+        }
+        _ if actual == expected => None,
+        _ => Some(path.to_string()),
+    }
+}
@@ -15,7 +15,7 @@ use crate::error::ComponentError;
 use crate::lifecycle::Lifecycle;
 use crate::limits::Limits;
 use crate::loader;
-use crate::manifest::ComponentManifest;
+use crate::manifest::{ComponentManifest, has_external_schema_refs, resolve_schema_refs};
 use crate::schema::{self, JsonPath};
 use crate::signing::{SigningError, compute_wasm_hash};
 use crate::telemetry::TelemetrySpec;
@@ -219,7 +219,15 @@ impl PreparedComponent {
     }
 
     pub fn to_pack_entry(&self) -> Result<PackEntry, ComponentError> {
-        let manifest_json = fs::read_to_string(&self.manifest_path)?;
+        let mut manifest_json = fs::read_to_string(&self.manifest_path)?;
+        let mut manifest_value: serde_json::Value =
+            serde_json::from_str(&manifest_json).map_err(crate::manifest::ManifestError::from)?;
+        if has_external_schema_refs(&manifest_value) {
+            // Packs are self-contained: inline the referenced schema files.
+            resolve_schema_refs(&mut manifest_value, &self.root)?;
+            manifest_json = serde_json::to_string_pretty(&manifest_value)
+                .map_err(crate::manifest::ManifestError::from)?;
+        }
         let describe_schema = self.describe.versions.first().map(|version| {
             serde_json::to_string(&version.schema).expect("describe schema serialization")
         });
@@ -1,14 +1,27 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
 use thiserror::Error;
 use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// in-toto statement type used for build attestations.
+pub const IN_TOTO_STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+/// SLSA provenance predicate type used for build attestations.
+pub const SLSA_PROVENANCE_PREDICATE_TYPE: &str = "https://slsa.dev/provenance/v1";
+/// Build type recorded in SLSA attestations emitted by `greentic-component build`.
+pub const SLSA_BUILD_TYPE: &str = "https://greentic.ai/component/build/v1";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Provenance {
     pub builder: String,
     pub git_commit: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_dirty: Option<bool>,
     pub toolchain: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cargo_component: Option<String>,
     #[serde(with = "time::serde::rfc3339")]
     pub built_at_utc: OffsetDateTime,
 }
@@ -21,11 +34,65 @@ impl Provenance {
         if self.toolchain.trim().is_empty() {
             return Err(ProvenanceError::EmptyField("toolchain"));
         }
+        if let Some(cargo_component) = &self.cargo_component
+            && cargo_component.trim().is_empty()
+        {
+            return Err(ProvenanceError::EmptyField("cargo_component"));
+        }
         if !GIT_COMMIT_RE.is_match(&self.git_commit) {
             return Err(ProvenanceError::InvalidGit(self.git_commit.clone()));
         }
         Ok(())
     }
+
+    /// Renders an in-toto v1 statement carrying a SLSA v1 provenance predicate
+    /// for the given artifact. `sha256` is the lowercase hex digest of the artifact;
+    /// `source_remote` is the repository URL the commit was built from, if known.
+    pub fn slsa_statement(
+        &self,
+        subject_name: &str,
+        sha256: &str,
+        source_remote: Option<&str>,
+        external_parameters: Value,
+    ) -> Value {
+        let built_at = self
+            .built_at_utc
+            .format(&Rfc3339)
+            .expect("rfc3339 formatting of a valid timestamp should never fail");
+        let mut toolchain = json!({ "rustc": self.toolchain });
+        if let Some(cargo_component) = &self.cargo_component {
+            toolchain["cargo_component"] = Value::String(cargo_component.clone());
+        }
+        let mut source = json!({
+            "digest": { "gitCommit": self.git_commit },
+            "annotations": { "dirty": self.git_dirty.unwrap_or(false) }
+        });
+        if let Some(remote) = source_remote {
+            source["uri"] = Value::String(format!("git+{remote}@{}", self.git_commit));
+        }
+        json!({
+            "_type": IN_TOTO_STATEMENT_TYPE,
+            "subject": [
+                {
+                    "name": subject_name,
+                    "digest": { "sha256": sha256 }
+                }
+            ],
+            "predicateType": SLSA_PROVENANCE_PREDICATE_TYPE,
+            "predicate": {
+                "buildDefinition": {
+                    "buildType": SLSA_BUILD_TYPE,
+                    "externalParameters": external_parameters,
+                    "internalParameters": { "toolchain": toolchain },
+                    "resolvedDependencies": [source]
+                },
+                "runDetails": {
+                    "builder": { "id": self.builder },
+                    "metadata": { "startedOn": built_at }
+                }
+            }
+        })
+    }
 }
 
 #[derive(Debug, Error)]
@@ -18,6 +18,7 @@ use time::OffsetDateTime;
 use walkdir::WalkDir;
 
 use super::deps::{self, DependencyMode};
+use super::hooks::PostGenHook;
 use super::validate::{self, ValidationError};
 use super::write::{GeneratedFile, WriteError, Writer};
 
@@ -71,6 +72,7 @@ impl ScaffoldEngine {
             template_tags: descriptor.tags.clone(),
             path: request.path,
             created,
+            hooks: package.metadata.hooks.clone(),
         })
     }
 
@@ -293,6 +295,9 @@ pub struct ScaffoldOutcome {
     #[serde(serialize_with = "serialize_path")]
     pub path: PathBuf,
     pub created: Vec<String>,
+    /// Post-generation hooks declared by the template; not run yet.
+    #[serde(skip)]
+    pub hooks: Vec<PostGenHook>,
 }
 
 impl ScaffoldOutcome {
@@ -431,6 +436,7 @@ struct ResolvedTemplateMetadata {
     description: Option<String>,
     tags: Vec<String>,
     executables: Vec<String>,
+    hooks: Vec<PostGenHook>,
 }
 
 impl ResolvedTemplateMetadata {
@@ -440,6 +446,7 @@ impl ResolvedTemplateMetadata {
             description: None,
             tags: Vec::new(),
             executables: Vec::new(),
+            hooks: Vec::new(),
         }
     }
 }
@@ -452,6 +459,8 @@ struct TemplateMetadataFile {
     tags: Vec<String>,
     #[serde(default)]
     executables: Vec<String>,
+    #[serde(default)]
+    hooks: Vec<PostGenHook>,
 }
 
 fn embedded_metadata(
@@ -507,6 +516,7 @@ fn resolve_metadata(
             description: file.description,
             tags: file.tags,
             executables: file.executables,
+            hooks: file.hooks,
         },
         None => ResolvedTemplateMetadata::fallback(fallback_id.to_string()),
     }
@@ -0,0 +1,280 @@
+#![cfg(feature = "cli")]
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::process::Command;
+use std::time::Instant;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+/// A command a template asks to run inside the freshly generated directory,
+/// e.g. `cargo fmt` or `./scripts/bootstrap.sh`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostGenHook {
+    /// Label used in reports; defaults to the program name.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Program and arguments, run without a shell.
+    pub run: Vec<String>,
+    /// Fail `new` when this hook fails instead of only reporting it.
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl PostGenHook {
+    /// Parses a command line as passed to `--hook`, splitting it into words
+    /// the way a POSIX shell would: single quotes keep everything literal,
+    /// double quotes allow `\"` and `\\` escapes, and a backslash outside
+    /// quotes escapes the next character. Nothing is expanded.
+    pub fn from_command_line(line: &str) -> Result<Self> {
+        Ok(Self {
+            name: None,
+            run: split_command_line(line)?,
+            required: true,
+        })
+    }
+
+    pub fn label(&self) -> &str {
+        self.name
+            .as_deref()
+            .or_else(|| self.run.first().map(String::as_str))
+            .unwrap_or("hook")
+    }
+
+    pub fn display_command(&self) -> String {
+        self.run.join(" ")
+    }
+}
+
+fn split_command_line(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => bail!("unterminated single quote in hook `{line}`"),
+                    }
+                }
+            }
+            '"' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => bail!("unterminated double quote in hook `{line}`"),
+                        },
+                        Some(c) => current.push(c),
+                        None => bail!("unterminated double quote in hook `{line}`"),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => bail!("hook `{line}` ends with a lone backslash"),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookStatus {
+    Ok,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HookReport {
+    pub name: String,
+    pub command: String,
+    pub status: HookStatus,
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl HookReport {
+    fn new(hook: &PostGenHook, status: HookStatus, message: Option<String>) -> Self {
+        Self {
+            name: hook.label().to_string(),
+            command: hook.display_command(),
+            status,
+            required: hook.required,
+            exit_code: None,
+            duration_ms: None,
+            message,
+        }
+    }
+
+    /// A required hook that did not succeed.
+    pub fn is_blocking(&self) -> bool {
+        self.required && self.status == HookStatus::Failed
+    }
+}
+
+/// Whether declared hooks may run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookApproval {
+    Approved,
+    Declined(String),
+}
+
+/// Decides whether to run `hooks`: `--no-hooks` wins, `--run-hooks`
+/// approves, and otherwise the user is asked on a terminal. Under
+/// `--non-interactive`, or without a terminal to ask on, hooks are skipped
+/// rather than run unattended.
+pub fn approve(
+    hooks: &[PostGenHook],
+    no_hooks: bool,
+    run_hooks: bool,
+    non_interactive: bool,
+) -> HookApproval {
+    if no_hooks {
+        return HookApproval::Declined("disabled via --no-hooks".into());
+    }
+    if hooks.is_empty() || run_hooks {
+        return HookApproval::Approved;
+    }
+    if non_interactive || !io::stdin().is_terminal() {
+        return HookApproval::Declined(
+            "not confirmed; pass --run-hooks to run hooks without a prompt".into(),
+        );
+    }
+    let mut stderr = io::stderr();
+    let _ = writeln!(stderr, "The template declares post-generation hooks:");
+    for hook in hooks {
+        let _ = writeln!(stderr, "  $ {}", hook.display_command());
+    }
+    let _ = write!(stderr, "Run them now? [y/N] ");
+    let _ = stderr.flush();
+    let mut reply = String::new();
+    if io::stdin().lock().read_line(&mut reply).is_err() {
+        return HookApproval::Declined("could not read confirmation".into());
+    }
+    match reply.trim().to_ascii_lowercase().as_str() {
+        "y" | "yes" => HookApproval::Approved,
+        _ => HookApproval::Declined("declined at prompt".into()),
+    }
+}
+
+/// Runs `hooks` in order inside `dir`. Every hook is attempted; a failure
+/// is recorded and does not stop the ones after it.
+pub fn run_hooks(dir: &Path, hooks: &[PostGenHook], approval: &HookApproval) -> Vec<HookReport> {
+    hooks
+        .iter()
+        .map(|hook| match approval {
+            HookApproval::Approved => run_hook(dir, hook),
+            HookApproval::Declined(reason) => {
+                HookReport::new(hook, HookStatus::Skipped, Some(reason.clone()))
+            }
+        })
+        .collect()
+}
+
+fn run_hook(dir: &Path, hook: &PostGenHook) -> HookReport {
+    let Some((program, args)) = hook.run.split_first() else {
+        return HookReport::new(hook, HookStatus::Failed, Some("hook has no command".into()));
+    };
+    let started = Instant::now();
+    let output = Command::new(program).args(args).current_dir(dir).output();
+    let duration_ms = Some(started.elapsed().as_millis());
+    match output {
+        Ok(output) if output.status.success() => HookReport {
+            exit_code: output.status.code(),
+            duration_ms,
+            ..HookReport::new(hook, HookStatus::Ok, None)
+        },
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            HookReport {
+                exit_code: output.status.code(),
+                duration_ms,
+                ..HookReport::new(
+                    hook,
+                    HookStatus::Failed,
+                    (!stderr.is_empty()).then_some(stderr),
+                )
+            }
+        }
+        Err(err) => HookReport::new(
+            hook,
+            HookStatus::Failed,
+            Some(format!("failed to run `{program}`: {err}")),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declined_hooks_are_reported_as_skipped() {
+        let hook = PostGenHook::from_command_line("cargo fmt").unwrap();
+        let reports = run_hooks(
+            Path::new("."),
+            std::slice::from_ref(&hook),
+            &approve(std::slice::from_ref(&hook), true, true, true),
+        );
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].status, HookStatus::Skipped);
+        assert_eq!(reports[0].name, "cargo");
+        assert_eq!(reports[0].command, "cargo fmt");
+    }
+
+    #[test]
+    fn non_interactive_alone_does_not_approve_hooks() {
+        let hooks = [PostGenHook::from_command_line("cargo fmt").unwrap()];
+        assert!(matches!(
+            approve(&hooks, false, false, true),
+            HookApproval::Declined(reason) if reason.contains("--run-hooks")
+        ));
+        assert_eq!(approve(&hooks, false, true, true), HookApproval::Approved);
+    }
+
+    #[test]
+    fn command_lines_keep_quoted_arguments_together() {
+        let hook = PostGenHook::from_command_line(r#"sh -c 'echo "hi there"' "two words" a\ b ''"#)
+            .unwrap();
+        assert_eq!(
+            hook.run,
+            ["sh", "-c", r#"echo "hi there""#, "two words", "a b", ""]
+        );
+        assert!(PostGenHook::from_command_line("echo 'unterminated").is_err());
+    }
+
+    #[test]
+    fn failing_required_hook_blocks() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let hook = PostGenHook {
+            name: Some("missing".into()),
+            run: vec!["greentic-definitely-not-a-binary".into()],
+            required: true,
+        };
+        let reports = run_hooks(temp.path(), &[hook], &HookApproval::Approved);
+        assert_eq!(reports[0].status, HookStatus::Failed);
+        assert!(reports[0].is_blocking());
+    }
+}
@@ -1,4 +1,5 @@
 pub mod deps;
 pub mod engine;
+pub mod hooks;
 pub mod validate;
 pub mod write;
@@ -1,18 +1,266 @@
+use std::collections::BTreeMap;
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use anyhow::{Context, Result};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{Context, Result, anyhow, bail};
 use bytes::Bytes;
 use directories::BaseDirs;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use tokio::fs as tfs;
 use tokio::io::AsyncWriteExt;
+use tracing::warn;
 
-use super::{ComponentBytes, ComponentId, ComponentLocator, meta};
+use super::{ComponentBytes, ComponentId, ComponentLocator, MetaInfo, meta};
 use crate::path_safety::normalize_under_root;
 
+const INDEX_FILE: &str = "index.json";
+const NONCE_LEN: usize = 12;
+
 #[derive(Clone, Debug)]
 pub struct Cache {
     root: PathBuf,
+    /// Read-only tier consulted when `root` misses.
+    system: Option<PathBuf>,
+    /// Set on the system tier, which is never written to.
+    read_only: bool,
+    signing: Option<CacheSigning>,
+    encryption: Option<CacheEncryptionKey>,
+}
+
+/// Host secret used to HMAC index entries. Keep it outside the cache root:
+/// anyone who can read it can re-sign tampered entries.
+#[derive(Clone)]
+pub struct CacheKey(Arc<[u8]>);
+
+impl CacheKey {
+    pub fn new(bytes: impl Into<Vec<u8>>) -> Self {
+        Self(bytes.into().into())
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("read cache key {}", path.display()))?;
+        if bytes.is_empty() {
+            bail!("cache key {} is empty", path.display());
+        }
+        Ok(Self::new(bytes))
+    }
+
+    fn mac(&self) -> Hmac<Sha256> {
+        Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts keys of any length")
+    }
+}
+
+impl fmt::Debug for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CacheKey(..)")
+    }
+}
+
+/// AES-256-GCM key that seals cached artifact bytes at rest. As with
+/// [`CacheKey`], it must not live in the cache root.
+#[derive(Clone)]
+pub struct CacheEncryptionKey(Key<Aes256Gcm>);
+
+impl CacheEncryptionKey {
+    /// Environment variable [`load`](Self::load) reads: 64 hex characters.
+    pub const ENV_VAR: &'static str = "GREENTIC_CACHE_ENCRYPTION_KEY";
+    /// Keyring service and account [`load`](Self::load) falls back to.
+    pub const KEYRING_SERVICE: &'static str = "greentic-component";
+    pub const KEYRING_ACCOUNT: &'static str = "cache-encryption-key";
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes.into())
+    }
+
+    pub fn generate() -> Self {
+        Self(Aes256Gcm::generate_key(&mut OsRng))
+    }
+
+    pub fn from_hex(raw: &str) -> Result<Self> {
+        let bytes = hex::decode(raw.trim()).context("cache encryption key must be hex")?;
+        let bytes: [u8; 32] = bytes.try_into().map_err(|bytes: Vec<u8>| {
+            anyhow!("cache encryption key must be 32 bytes, got {}", bytes.len())
+        })?;
+        Ok(Self::from_bytes(bytes))
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// The key from [`ENV_VAR`](Self::ENV_VAR), else, with the `keyring`
+    /// feature, from the OS keyring. `None` when neither holds one; a
+    /// keyring that cannot be reached (no secret service on a headless box)
+    /// is logged and counts as holding none.
+    pub fn load() -> Result<Option<Self>> {
+        match std::env::var(Self::ENV_VAR) {
+            Ok(raw) => return Self::from_hex(&raw).map(Some).context(Self::ENV_VAR),
+            Err(std::env::VarError::NotPresent) => {}
+            Err(err) => return Err(err).context(Self::ENV_VAR),
+        }
+        #[cfg(feature = "keyring")]
+        {
+            let (service, account) = (Self::KEYRING_SERVICE, Self::KEYRING_ACCOUNT);
+            match keyring_password(service, account) {
+                Ok(Some(raw)) => Self::from_hex(&raw)
+                    .map(Some)
+                    .with_context(|| format!("keyring entry {service}/{account}")),
+                Ok(None) => Ok(None),
+                Err(err) => {
+                    warn!(
+                        "cache encryption disabled: keyring entry {service}/{account} unavailable: {err}"
+                    );
+                    Ok(None)
+                }
+            }
+        }
+        #[cfg(not(feature = "keyring"))]
+        {
+            Ok(None)
+        }
+    }
+
+    /// Reads a hex key stored as the password of `service`/`account`.
+    #[cfg(feature = "keyring")]
+    pub fn from_keyring(service: &str, account: &str) -> Result<Option<Self>> {
+        keyring_password(service, account)
+            .with_context(|| format!("read keyring entry {service}/{account}"))?
+            .map(|raw| Self::from_hex(&raw))
+            .transpose()
+    }
+
+    fn seal(&self, id: &ComponentId, plaintext: &[u8]) -> Result<(Vec<u8>, String)> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(&self.0)
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad: id.0.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("encrypting {} for the cache failed", id.0))?;
+        Ok((ciphertext, hex::encode(nonce)))
+    }
+
+    /// The artifact `id` sealed with `nonce`; the error says why it did not
+    /// open.
+    fn open(&self, id: &ComponentId, nonce: &str, ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+        let nonce = hex::decode(nonce)
+            .ok()
+            .filter(|nonce| nonce.len() == NONCE_LEN)
+            .ok_or_else(|| "index entry has a malformed nonce".to_string())?;
+        Aes256Gcm::new(&self.0)
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: id.0.as_bytes(),
+                },
+            )
+            .map_err(|_| "bytes do not decrypt (wrong key or modified ciphertext)".to_string())
+    }
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_password(service: &str, account: &str) -> keyring::Result<Option<String>> {
+    match keyring::Entry::new(service, account)?.get_password() {
+        Ok(raw) => Ok(Some(raw)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+impl fmt::Debug for CacheEncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CacheEncryptionKey(..)")
+    }
+}
+
+/// What a signed cache does when an entry fails its integrity check.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TamperPolicy {
+    /// Log a warning and treat the entry as a miss so it is fetched again.
+    #[default]
+    Refetch,
+    /// Fail the load with [`CacheTampered`].
+    Fail,
+}
+
+#[derive(Clone, Debug)]
+struct CacheSigning {
+    key: CacheKey,
+    on_tamper: TamperPolicy,
+}
+
+/// A signed cache entry whose bytes, index record or locator hint changed
+/// since it was stored.
+#[derive(Debug, thiserror::Error)]
+#[error("cache entry for {locator} was modified on disk: {reason}")]
+pub struct CacheTampered {
+    pub locator: String,
+    pub reason: String,
+}
+
+/// What the cache index records about one stored artifact.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub id: ComponentId,
+    pub size: u64,
+    /// Locators whose fetches resolved to this artifact.
+    pub locators: Vec<ComponentLocator>,
+    /// Unix seconds of the most recent store.
+    pub stored_at: u64,
+    pub meta: MetaInfo,
+    /// Hex HMAC-SHA256 over the id, size, nonce and locators, present when
+    /// the cache was written with a [`CacheKey`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Hex AES-GCM nonce the bytes on disk were sealed with; `None` for
+    /// entries stored in plaintext.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CacheStatus {
+    Ok,
+    /// Indexed but the bytes are gone.
+    Missing,
+    /// The bytes on disk no longer hash to the indexed digest.
+    Corrupt {
+        actual: ComponentId,
+    },
+    /// The bytes are intact but the index record is unsigned or its
+    /// signature does not match the configured key.
+    Unsigned,
+    /// The bytes are encrypted and do not open: no key was given, the key is
+    /// wrong, or the ciphertext changed.
+    Unreadable,
+    /// The bytes are intact but stored in plaintext, although the cache now
+    /// encrypts.
+    Unencrypted,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CacheCheck {
+    pub id: ComponentId,
+    #[serde(flatten)]
+    pub status: CacheStatus,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheIndex {
+    #[serde(default)]
+    entries: BTreeMap<String, CacheEntry>,
 }
 
 impl Default for Cache {
@@ -28,7 +276,62 @@ impl Cache {
                 .map(|dirs| dirs.home_dir().join(".greentic").join("components"))
                 .unwrap_or_else(|| PathBuf::from(".greentic").join("components"))
         });
-        Cache { root }
+        Cache {
+            root,
+            system: None,
+            read_only: false,
+            signing: None,
+            encryption: None,
+        }
+    }
+
+    /// Looks artifacts up in `dir` when the cache root misses. The directory
+    /// is only read, so it can be provisioned into an immutable image by
+    /// running a store against it ahead of time; new fetches are written to
+    /// the cache root. Listing, verifying and purging see the cache root
+    /// only.
+    pub fn with_system_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.system = Some(dir.into());
+        self
+    }
+
+    /// The read-only cache consulted when the cache root misses, if any.
+    pub fn system_cache(&self) -> Option<&Path> {
+        self.system.as_deref()
+    }
+
+    /// The system cache as a cache of its own, checked with the same keys.
+    fn system_tier(&self) -> Option<Cache> {
+        self.system.as_ref().map(|root| Cache {
+            root: root.clone(),
+            system: None,
+            read_only: true,
+            signing: self.signing.clone(),
+            encryption: self.encryption.clone(),
+        })
+    }
+
+    /// Signs index entries with `key` and checks them on every load.
+    ///
+    /// Entries written without a key count as tampered once one is set, so
+    /// switching an existing cache over refetches (or rejects) what it holds.
+    pub fn with_signing_key(mut self, key: CacheKey, on_tamper: TamperPolicy) -> Self {
+        self.signing = Some(CacheSigning { key, on_tamper });
+        self
+    }
+
+    /// Stores artifact bytes encrypted with `key`, under a fresh nonce per
+    /// store, and decrypts them on load.
+    ///
+    /// Plaintext entries left from before are treated as misses and stored
+    /// again encrypted. Entries this cache cannot decrypt fail their load.
+    pub fn with_encryption_key(mut self, key: CacheEncryptionKey) -> Self {
+        self.encryption = Some(key);
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
     }
 
     async fn entry_path_for_id(&self, id: &ComponentId) -> Result<PathBuf> {
@@ -38,8 +341,8 @@ impl Cache {
 
     fn key_for_locator(loc: &ComponentLocator) -> String {
         match loc {
-            ComponentLocator::Fs { path } => format!("fs:{}", path.to_string_lossy()),
-            ComponentLocator::Oci { reference } => format!("oci:{reference}"),
+            ComponentLocator::Fs { path, .. } => format!("fs:{}", path.to_string_lossy()),
+            ComponentLocator::Oci { reference, .. } => format!("oci:{reference}"),
         }
     }
 
@@ -52,13 +355,27 @@ impl Cache {
     }
 
     async fn normalize_in_root(&self, candidate: &Path) -> Result<PathBuf> {
-        tfs::create_dir_all(&self.root)
-            .await
-            .with_context(|| format!("unable to create cache root at {}", self.root.display()))?;
+        if !self.read_only {
+            tfs::create_dir_all(&self.root).await.with_context(|| {
+                format!("unable to create cache root at {}", self.root.display())
+            })?;
+        }
         normalize_under_root(&self.root, candidate)
     }
 
+    /// The cached artifact for `loc`, from the cache root or else the
+    /// [system cache](Self::with_system_cache).
     pub async fn try_load(&self, loc: &ComponentLocator) -> Result<Option<ComponentBytes>> {
+        if let Some(hit) = self.try_load_here(loc).await? {
+            return Ok(Some(hit));
+        }
+        match self.system_tier() {
+            Some(system) if path_exists(&system.root).await => system.try_load_here(loc).await,
+            _ => Ok(None),
+        }
+    }
+
+    async fn try_load_here(&self, loc: &ComponentLocator) -> Result<Option<ComponentBytes>> {
         let hint_path = self.hint_path_for_locator(loc).await?;
         if !path_exists(&hint_path).await {
             return Ok(None);
@@ -71,12 +388,66 @@ impl Cache {
             return Ok(None);
         }
 
-        let bytes_vec = tfs::read(&data_path).await?;
+        let stored = tfs::read(&data_path).await?;
+        let nonce = self
+            .read_index()
+            .await?
+            .entries
+            .remove(&id.0)
+            .and_then(|entry| entry.nonce);
+        let bytes_vec = match (&self.encryption, nonce) {
+            (None, None) => stored,
+            (None, Some(_)) => {
+                bail!("cached {loc} is encrypted; configure the cache encryption key to read it")
+            }
+            (Some(_), None) => {
+                warn!("cached {loc} is not encrypted; fetching it again");
+                return Ok(None);
+            }
+            (Some(key), Some(nonce)) => match key.open(&id, &nonce, &stored) {
+                Ok(plaintext) => plaintext,
+                Err(reason) => {
+                    if self
+                        .signing
+                        .as_ref()
+                        .is_some_and(|signing| signing.on_tamper == TamperPolicy::Fail)
+                    {
+                        return Err(CacheTampered {
+                            locator: loc.to_string(),
+                            reason,
+                        }
+                        .into());
+                    }
+                    warn!("ignoring cached {loc}: {reason}; fetching it again");
+                    return Ok(None);
+                }
+            },
+        };
         let (computed_id, meta) = meta::compute_id_and_meta(&bytes_vec).await?;
         let bytes = Bytes::from(bytes_vec);
 
+        if let Some(signing) = &self.signing
+            && let Some(reason) = self
+                .tamper_reason(&signing.key, loc, &id, &computed_id)
+                .await?
+        {
+            match signing.on_tamper {
+                TamperPolicy::Refetch => {
+                    warn!("ignoring cached {loc}: {reason}; fetching it again");
+                    return Ok(None);
+                }
+                TamperPolicy::Fail => {
+                    return Err(CacheTampered {
+                        locator: loc.to_string(),
+                        reason,
+                    }
+                    .into());
+                }
+            }
+        }
+
         // Update hint if the stored id mismatched (e.g., manual tampering).
-        if computed_id != id {
+        if computed_id != id && !self.read_only {
             self.write_hint(loc, &computed_id).await?;
         }
 
@@ -96,12 +467,238 @@ impl Cache {
         if let Some(parent) = path.parent() {
             tfs::create_dir_all(parent).await?;
         }
+        let (stored, nonce) = match &self.encryption {
+            Some(key) => {
+                let (ciphertext, nonce) = key.seal(&cb.id, cb.bytes.as_ref())?;
+                (Bytes::from(ciphertext), Some(nonce))
+            }
+            None => (cb.bytes.clone(), None),
+        };
         let mut file = tfs::File::create(&path).await?;
-        file.write_all(cb.bytes.as_ref()).await?;
+        file.write_all(stored.as_ref()).await?;
         file.flush().await?;
 
         self.write_hint(loc, &cb.id).await?;
-        Ok(())
+
+        let mut index = self.read_index().await?;
+        let entry = index
+            .entries
+            .entry(cb.id.0.clone())
+            .or_insert_with(|| CacheEntry {
+                id: cb.id.clone(),
+                size: cb.meta.size,
+                locators: Vec::new(),
+                stored_at: 0,
+                meta: cb.meta.clone(),
+                signature: None,
+                nonce: None,
+            });
+        if !entry.locators.contains(loc) {
+            entry.locators.push(loc.clone());
+        }
+        entry.meta = cb.meta.clone();
+        entry.nonce = nonce;
+        entry.stored_at = unix_now();
+        // The bytes were just written, so this entry is trustworthy again.
+        self.sign(entry);
+        // A locator now resolving to new bytes no longer points at its old entry.
+        for (id, other) in index.entries.iter_mut() {
+            if *id != cb.id.0 && other.locators.contains(loc) {
+                self.resign_with(other, |other| {
+                    other.locators.retain(|candidate| candidate != loc)
+                });
+            }
+        }
+        self.write_index(&index).await
+    }
+
+    /// Indexed artifacts, most recently stored first.
+    pub async fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries = self
+            .read_index()
+            .await?
+            .entries
+            .into_values()
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| b.stored_at.cmp(&a.stored_at).then(a.id.0.cmp(&b.id.0)));
+        Ok(entries)
+    }
+
+    /// Looks up an entry by `sha256:<hex>`, bare hex, or an unambiguous hex
+    /// prefix.
+    pub async fn entry(&self, digest: &str) -> Result<Option<CacheEntry>> {
+        let wanted = digest.strip_prefix("sha256:").unwrap_or(digest);
+        let index = self.read_index().await?;
+        let mut matches = index.entries.into_values().filter(|entry| {
+            entry
+                .id
+                .0
+                .strip_prefix("sha256:")
+                .is_some_and(|hex| hex.starts_with(wanted))
+        });
+        let found = matches.next();
+        if found.is_some() && matches.next().is_some() {
+            bail!("digest prefix `{digest}` matches more than one cache entry");
+        }
+        Ok(found)
+    }
+
+    /// Re-hashes every indexed artifact and reports those whose bytes are
+    /// missing or no longer match their digest, and, with a signing key,
+    /// those whose index record fails its signature. Encrypted entries are
+    /// decrypted first; with an encryption key, plaintext ones are flagged.
+    pub async fn verify(&self) -> Result<Vec<CacheCheck>> {
+        let index = self.read_index().await?;
+        let mut checks = Vec::with_capacity(index.entries.len());
+        for entry in index.entries.into_values() {
+            let id = entry.id.clone();
+            let path = self.entry_path_for_id(&id).await?;
+            let status = match tfs::read(&path).await {
+                Ok(stored) => {
+                    let bytes = match (&self.encryption, entry.nonce.as_deref()) {
+                        (_, None) => Some(stored),
+                        (Some(key), Some(nonce)) => key.open(&id, nonce, &stored).ok(),
+                        (None, Some(_)) => None,
+                    };
+                    let Some(bytes) = bytes else {
+                        checks.push(CacheCheck {
+                            id,
+                            status: CacheStatus::Unreadable,
+                        });
+                        continue;
+                    };
+                    let actual =
+                        ComponentId(format!("sha256:{}", hex::encode(Sha256::digest(&bytes))));
+                    if actual != id {
+                        CacheStatus::Corrupt { actual }
+                    } else if self
+                        .signing
+                        .as_ref()
+                        .is_some_and(|signing| !signature_matches(&signing.key, &entry))
+                    {
+                        CacheStatus::Unsigned
+                    } else if self.encryption.is_some() && entry.nonce.is_none() {
+                        CacheStatus::Unencrypted
+                    } else {
+                        CacheStatus::Ok
+                    }
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => CacheStatus::Missing,
+                Err(err) => {
+                    return Err(err).with_context(|| format!("read {}", path.display()));
+                }
+            };
+            checks.push(CacheCheck { id, status });
+        }
+        Ok(checks)
+    }
+
+    /// Forgets `loc`; the artifact's bytes are deleted once no other locator
+    /// refers to them. Returns the artifact the locator pointed at.
+    pub async fn purge(&self, loc: &ComponentLocator) -> Result<Option<ComponentId>> {
+        let hint_path = self.hint_path_for_locator(loc).await?;
+        let hinted = match tfs::read_to_string(&hint_path).await {
+            Ok(id) => Some(ComponentId(id.trim().to_owned())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err).with_context(|| format!("read {}", hint_path.display())),
+        };
+        if hinted.is_some() {
+            tfs::remove_file(&hint_path).await?;
+        }
+
+        let mut index = self.read_index().await?;
+        let indexed = index
+            .entries
+            .values()
+            .find(|entry| entry.locators.contains(loc))
+            .map(|entry| entry.id.clone());
+        let Some(id) = indexed.or(hinted) else {
+            return Ok(None);
+        };
+        let orphaned = match index.entries.get_mut(&id.0) {
+            Some(entry) => {
+                self.resign_with(entry, |entry| {
+                    entry.locators.retain(|candidate| candidate != loc)
+                });
+                entry.locators.is_empty()
+            }
+            None => true,
+        };
+        if orphaned {
+            index.entries.remove(&id.0);
+            let path = self.entry_path_for_id(&id).await?;
+            match tfs::remove_file(&path).await {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err).with_context(|| format!("remove {}", path.display())),
+            }
+        }
+        self.write_index(&index).await?;
+        Ok(Some(id))
+    }
+
+    /// Why the entry `loc` resolved to can no longer be trusted, if it can't.
+    async fn tamper_reason(
+        &self,
+        key: &CacheKey,
+        loc: &ComponentLocator,
+        hinted: &ComponentId,
+        actual: &ComponentId,
+    ) -> Result<Option<String>> {
+        if actual != hinted {
+            return Ok(Some(format!(
+                "bytes hash to {} instead of {}",
+                actual.0, hinted.0
+            )));
+        }
+        let index = self.read_index().await?;
+        let reason = match index.entries.get(&actual.0) {
+            None => "artifact is missing from the cache index",
+            Some(entry) if entry.signature.is_none() => "index entry is unsigned",
+            Some(entry) if !signature_matches(key, entry) => "index entry signature mismatch",
+            Some(entry) if !entry.locators.contains(loc) => "locator was never stored here",
+            Some(_) => return Ok(None),
+        };
+        Ok(Some(reason.to_string()))
+    }
+
+    fn sign(&self, entry: &mut CacheEntry) {
+        entry.signature = self
+            .signing
+            .as_ref()
+            .map(|signing| hex::encode(entry_mac(&signing.key, entry).finalize().into_bytes()));
+    }
+
+    /// Applies `change` and re-signs the entry only if it verified before, so
+    /// unrelated bookkeeping never launders a tampered record.
+    fn resign_with(&self, entry: &mut CacheEntry, change: impl FnOnce(&mut CacheEntry)) {
+        let trusted = match &self.signing {
+            Some(signing) => signature_matches(&signing.key, entry),
+            None => true,
+        };
+        change(entry);
+        if trusted {
+            self.sign(entry);
+        }
+    }
+
+    async fn read_index(&self) -> Result<CacheIndex> {
+        let path = self.root.join(INDEX_FILE);
+        match tfs::read(&path).await {
+            Ok(raw) => serde_json::from_slice(&raw)
+                .map_err(|err| anyhow!("corrupt cache index {}: {err}", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(CacheIndex::default()),
+            Err(err) => Err(err).with_context(|| format!("read {}", path.display())),
+        }
+    }
+
+    async fn write_index(&self, index: &CacheIndex) -> Result<()> {
+        let path = self.normalize_in_root(Path::new(INDEX_FILE)).await?;
+        let tmp = path.with_extension("json.tmp");
+        tfs::write(&tmp, serde_json::to_vec_pretty(index)?).await?;
+        tfs::rename(&tmp, &path)
+            .await
+            .with_context(|| format!("write {}", path.display()))
     }
 
     async fn write_hint(&self, loc: &ComponentLocator, id: &ComponentId) -> Result<()> {
@@ -114,6 +711,44 @@ impl Cache {
     }
 }
 
+fn entry_mac(key: &CacheKey, entry: &CacheEntry) -> Hmac<Sha256> {
+    let mut locators = entry
+        .locators
+        .iter()
+        .map(Cache::key_for_locator)
+        .collect::<Vec<_>>();
+    locators.sort();
+    let mut mac = key.mac();
+    mac.update(entry.id.0.as_bytes());
+    mac.update(format!("\n{}", entry.size).as_bytes());
+    if let Some(nonce) = &entry.nonce {
+        mac.update(format!("\nnonce:{nonce}").as_bytes());
+    }
+    for locator in locators {
+        mac.update(b"\n");
+        mac.update(locator.as_bytes());
+    }
+    mac
+}
+
+fn signature_matches(key: &CacheKey, entry: &CacheEntry) -> bool {
+    let Some(signature) = entry
+        .signature
+        .as_deref()
+        .and_then(|sig| hex::decode(sig).ok())
+    else {
+        return false;
+    };
+    entry_mac(key, entry).verify_slice(&signature).is_ok()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
 async fn path_exists(path: &Path) -> bool {
     tfs::try_exists(path).await.unwrap_or(false)
 }
@@ -1,18 +1,90 @@
 use super::MetaInfo;
+use semver::Version;
 use thiserror::Error;
 
+/// Rules a fetched artifact must satisfy before the store hands it out or
+/// caches it. Empty fields impose nothing.
 #[derive(Clone, Debug, Default)]
 pub struct CompatPolicy {
     pub required_abi_prefix: String,
     pub required_capabilities: Vec<String>,
+    /// Exact `abi_version` values accepted (e.g. `greentic-abi-0`).
+    pub allowed_abi_versions: Vec<String>,
+    /// Worlds the artifact may target, as `ns:pkg/world` (any version) or
+    /// `ns:pkg/world@version`.
+    pub required_worlds: Vec<String>,
+    /// Largest artifact accepted, in bytes.
+    pub max_size: Option<u64>,
+    pub provenance: Option<ProvenanceRequirement>,
+}
+
+/// Minimum provenance an artifact must record.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProvenanceRequirement {
+    /// Required `provider_name`; `None` accepts any recorded provider.
+    pub provider: Option<String>,
+    /// Oldest acceptable `provider_version`.
+    pub min_version: Option<Version>,
+}
+
+impl CompatPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_abi_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.required_abi_prefix = prefix.into();
+        self
+    }
+
+    pub fn allow_abi_version(mut self, version: impl Into<String>) -> Self {
+        self.allowed_abi_versions.push(version.into());
+        self
+    }
+
+    pub fn require_capability(mut self, capability: impl Into<String>) -> Self {
+        self.required_capabilities.push(capability.into());
+        self
+    }
+
+    /// Accepts artifacts targeting `world`; repeat to accept several.
+    pub fn require_world(mut self, world: impl Into<String>) -> Self {
+        self.required_worlds.push(world.into());
+        self
+    }
+
+    pub fn with_max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    pub fn with_provenance(mut self, requirement: ProvenanceRequirement) -> Self {
+        self.provenance = Some(requirement);
+        self
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum CompatError {
     #[error("ABI mismatch: required prefix '{required}', got '{got}'")]
     AbiMismatch { required: String, got: String },
+    #[error("ABI '{got}' is not allowed (allowed: {allowed:?})")]
+    AbiNotAllowed { got: String, allowed: Vec<String> },
+    #[error("world {got:?} is not one of the required worlds {required:?}")]
+    WorldMismatch {
+        required: Vec<String>,
+        got: Option<String>,
+    },
+    #[error("artifact is {size} bytes, larger than the {max} byte limit")]
+    TooLarge { size: u64, max: u64 },
     #[error("Missing capabilities: {0:?}")]
     MissingCapabilities(Vec<String>),
+    #[error("artifact records no provenance (provider name and version)")]
+    MissingProvenance,
+    #[error("provider '{got}' does not match required provider '{required}'")]
+    ProviderMismatch { required: String, got: String },
+    #[error("provider version '{got}' is older than the required {required}")]
+    ProviderTooOld { required: Version, got: String },
 }
 
 pub fn check(policy: &CompatPolicy, meta: &MetaInfo) -> Result<(), CompatError> {
@@ -25,6 +97,41 @@ pub fn check(policy: &CompatPolicy, meta: &MetaInfo) -> Result<(), CompatError>
         });
     }
 
+    if !policy.allowed_abi_versions.is_empty()
+        && !policy.allowed_abi_versions.contains(&meta.abi_version)
+    {
+        return Err(CompatError::AbiNotAllowed {
+            got: meta.abi_version.clone(),
+            allowed: policy.allowed_abi_versions.clone(),
+        });
+    }
+
+    if !policy.required_worlds.is_empty() {
+        let matches = meta.world.as_deref().is_some_and(|world| {
+            policy.required_worlds.iter().any(|required| {
+                world == required
+                    || world
+                        .strip_prefix(required.as_str())
+                        .is_some_and(|rest| rest.starts_with('@'))
+            })
+        });
+        if !matches {
+            return Err(CompatError::WorldMismatch {
+                required: policy.required_worlds.clone(),
+                got: meta.world.clone(),
+            });
+        }
+    }
+
+    if let Some(max) = policy.max_size
+        && meta.size > max
+    {
+        return Err(CompatError::TooLarge {
+            size: meta.size,
+            max,
+        });
+    }
+
     if !policy.required_capabilities.is_empty() {
         let mut missing = Vec::new();
         for capability in &policy.required_capabilities {
@@ -37,5 +144,37 @@ pub fn check(policy: &CompatPolicy, meta: &MetaInfo) -> Result<(), CompatError>
         }
     }
 
+    if let Some(requirement) = &policy.provenance {
+        check_provenance(requirement, meta)?;
+    }
+
+    Ok(())
+}
+
+fn check_provenance(
+    requirement: &ProvenanceRequirement,
+    meta: &MetaInfo,
+) -> Result<(), CompatError> {
+    let (Some(provider), Some(version)) = (&meta.provider_name, &meta.provider_version) else {
+        return Err(CompatError::MissingProvenance);
+    };
+    if let Some(required) = &requirement.provider
+        && required != provider
+    {
+        return Err(CompatError::ProviderMismatch {
+            required: required.clone(),
+            got: provider.clone(),
+        });
+    }
+    if let Some(min) = &requirement.min_version
+        && Version::parse(version)
+            .ok()
+            .is_none_or(|version| version < *min)
+    {
+        return Err(CompatError::ProviderTooOld {
+            required: min.clone(),
+            got: version.clone(),
+        });
+    }
     Ok(())
 }
@@ -0,0 +1,267 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow, bail};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::Sha256;
+
+use super::ComponentLocator;
+
+/// Conventional file name of a registry catalog index.
+pub const INDEX_FILE: &str = "index.json";
+
+/// Layout version written to [`ComponentIndex::format`].
+pub const INDEX_FORMAT: u32 = 1;
+
+const SIGNATURE_ALGORITHM: &str = "hmac-sha256";
+
+/// Registry secret used to sign and verify a [`ComponentIndex`]. It is
+/// shared between the publisher and every consumer that checks the index.
+#[derive(Clone)]
+pub struct IndexKey(Arc<[u8]>);
+
+impl IndexKey {
+    pub fn new(bytes: impl Into<Vec<u8>>) -> Self {
+        Self(bytes.into().into())
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("read index key {}", path.display()))?;
+        if bytes.is_empty() {
+            bail!("index key {} is empty", path.display());
+        }
+        Ok(Self::new(bytes))
+    }
+
+    fn mac(&self) -> Hmac<Sha256> {
+        Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts keys of any length")
+    }
+}
+
+impl fmt::Debug for IndexKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("IndexKey(..)")
+    }
+}
+
+/// Catalog of every component version a registry publishes, written by
+/// `greentic-component index build` and read by [`Resolver::add_index`]
+/// and by portals listing what a registry offers.
+///
+/// [`Resolver::add_index`]: super::Resolver::add_index
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ComponentIndex {
+    pub format: u32,
+    /// Sorted by id, then version.
+    pub components: Vec<IndexEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<IndexSignature>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub world: String,
+    /// Artifact location: a path relative to the index file, or an
+    /// `oci://`/`file://` locator.
+    pub locator: String,
+    /// `sha256:<hex>` of the wasm, as the store computes it.
+    pub digest: String,
+    /// `hashes.component_wasm` from the manifest.
+    pub wasm_hash: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub supports: Vec<String>,
+    /// Declared capabilities as dotted paths (`wasi.clocks`,
+    /// `host.http.client`, ...).
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
+    pub schemas: IndexSchemas,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IndexSchemas {
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub config: Value,
+    #[serde(default)]
+    pub operations: Vec<IndexOperation>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexOperation {
+    pub name: String,
+    pub input: Value,
+    pub output: Value,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexSignature {
+    pub algorithm: String,
+    /// Hex HMAC over the canonical JSON of `format` and `components`.
+    pub value: String,
+}
+
+impl ComponentIndex {
+    pub fn new(mut components: Vec<IndexEntry>) -> Self {
+        components.sort_by(|a, b| {
+            a.id.cmp(&b.id).then_with(|| {
+                match (
+                    semver::Version::parse(&a.version),
+                    semver::Version::parse(&b.version),
+                ) {
+                    (Ok(a), Ok(b)) => a.cmp(&b),
+                    _ => a.version.cmp(&b.version),
+                }
+            })
+        });
+        Self {
+            format: INDEX_FORMAT,
+            components,
+            signature: None,
+        }
+    }
+
+    /// Reads an index, checking its signature when `key` is given.
+    pub fn load(path: &Path, key: Option<&IndexKey>) -> Result<Self> {
+        let raw = std::fs::read(path).with_context(|| format!("read index {}", path.display()))?;
+        Self::from_slice(&raw, &path.display().to_string(), key)
+    }
+
+    /// Parses index bytes fetched from `origin`, which only names the
+    /// index in errors.
+    pub fn from_slice(raw: &[u8], origin: &str, key: Option<&IndexKey>) -> Result<Self> {
+        let index: ComponentIndex =
+            serde_json::from_slice(raw).with_context(|| format!("parse index {origin}"))?;
+        if index.format != INDEX_FORMAT {
+            bail!(
+                "index {origin} has format {}, expected {INDEX_FORMAT}",
+                index.format
+            );
+        }
+        if let Some(key) = key {
+            index
+                .verify(key)
+                .with_context(|| format!("verify index {origin}"))?;
+        }
+        Ok(index)
+    }
+
+    pub fn sign(&mut self, key: &IndexKey) {
+        self.signature = Some(IndexSignature {
+            algorithm: SIGNATURE_ALGORITHM.into(),
+            value: hex::encode(self.mac(key).finalize().into_bytes()),
+        });
+    }
+
+    pub fn verify(&self, key: &IndexKey) -> Result<()> {
+        let signature = self
+            .signature
+            .as_ref()
+            .ok_or_else(|| anyhow!("index is not signed"))?;
+        if signature.algorithm != SIGNATURE_ALGORITHM {
+            bail!(
+                "unsupported index signature algorithm `{}`",
+                signature.algorithm
+            );
+        }
+        let value = hex::decode(&signature.value).context("index signature is not hex")?;
+        self.mac(key)
+            .verify_slice(&value)
+            .map_err(|_| anyhow!("index signature does not match"))
+    }
+
+    /// Every entry for `id`, with its locator resolved against the
+    /// directory holding the index.
+    pub fn versions<'a>(
+        &'a self,
+        id: &'a str,
+        index_dir: &'a Path,
+    ) -> impl Iterator<Item = (&'a IndexEntry, Result<ComponentLocator>)> + 'a {
+        self.components
+            .iter()
+            .filter(move |entry| entry.id == id)
+            .map(move |entry| (entry, entry.resolve_locator(index_dir)))
+    }
+
+    fn mac(&self, key: &IndexKey) -> Hmac<Sha256> {
+        let mut mac = key.mac();
+        // `serde_json` maps are sorted, so this encoding is stable.
+        let payload = json!({ "format": self.format, "components": self.components });
+        mac.update(payload.to_string().as_bytes());
+        mac
+    }
+}
+
+impl IndexEntry {
+    pub fn resolve_locator(&self, index_dir: &Path) -> Result<ComponentLocator> {
+        if self.locator.contains("://") {
+            ComponentLocator::parse(&self.locator)
+        } else {
+            Ok(ComponentLocator::Fs {
+                path: index_dir.join(PathBuf::from(&self.locator)),
+                pin: None,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, version: &str) -> IndexEntry {
+        IndexEntry {
+            id: id.into(),
+            name: id.into(),
+            version: version.into(),
+            world: "greentic:component/component@0.6.0".into(),
+            locator: format!("{id}/{version}/component.wasm"),
+            digest: "sha256:00".into(),
+            wasm_hash: "blake3:00".into(),
+            size: 8,
+            supports: vec!["messaging".into()],
+            capabilities: vec!["host.state.read".into()],
+            secrets: Vec::new(),
+            schemas: IndexSchemas::default(),
+        }
+    }
+
+    #[test]
+    fn signed_index_round_trips_and_detects_edits() {
+        let key = IndexKey::new(b"registry-secret".to_vec());
+        let mut index = ComponentIndex::new(vec![
+            entry("acme.echo", "0.10.0"),
+            entry("acme.echo", "0.9.1"),
+        ]);
+        assert_eq!(index.components[0].version, "0.9.1");
+        index.sign(&key);
+        index.verify(&key).unwrap();
+
+        let text = serde_json::to_string(&index).unwrap();
+        let reloaded: ComponentIndex = serde_json::from_str(&text).unwrap();
+        reloaded.verify(&key).unwrap();
+
+        let mut tampered = reloaded.clone();
+        tampered.components[1].digest = "sha256:ff".into();
+        assert!(tampered.verify(&key).is_err());
+        assert!(reloaded.verify(&IndexKey::new(b"other".to_vec())).is_err());
+
+        let dir = Path::new("/srv/registry");
+        let (_, locator) = reloaded.versions("acme.echo", dir).next().unwrap();
+        assert_eq!(
+            locator.unwrap(),
+            ComponentLocator::Fs {
+                path: dir.join("acme.echo/0.9.1/component.wasm"),
+                pin: None,
+            }
+        );
+    }
+}
@@ -18,6 +18,9 @@ pub struct MetaInfo {
     pub provider_name: Option<String>,
     pub provider_version: Option<String>,
     pub capabilities: Vec<String>,
+    /// Targeted world as `ns:pkg/world@version`, when the artifact embeds WIT.
+    #[serde(default)]
+    pub world: Option<String>,
 }
 
 pub async fn compute_id_and_meta(bytes: &[u8]) -> Result<(ComponentId, MetaInfo)> {
@@ -32,6 +35,7 @@ pub async fn compute_id_and_meta(bytes: &[u8]) -> Result<(ComponentId, MetaInfo)
     let mut provider_name = None;
     let mut provider_version = None;
     let mut capabilities = Vec::new();
+    let mut world = None;
 
     #[cfg(feature = "abi")]
     if let Some(extracted) = extract_from_wit_metadata(bytes) {
@@ -39,6 +43,7 @@ pub async fn compute_id_and_meta(bytes: &[u8]) -> Result<(ComponentId, MetaInfo)
         provider_name = extracted.provider_name;
         provider_version = extracted.provider_version;
         capabilities = extracted.capabilities;
+        world = extracted.world;
     }
 
     // Fall back to producers metadata to at least capture greentic-interfaces provenance.
@@ -59,6 +64,7 @@ pub async fn compute_id_and_meta(bytes: &[u8]) -> Result<(ComponentId, MetaInfo)
         provider_name,
         provider_version,
         capabilities,
+        world,
     };
 
     Ok((id, meta))
@@ -69,6 +75,7 @@ struct ExtractedMeta {
     provider_name: Option<String>,
     provider_version: Option<String>,
     capabilities: Vec<String>,
+    world: Option<String>,
 }
 
 #[cfg(feature = "abi")]
@@ -81,9 +88,11 @@ fn extract_from_wit_metadata(bytes: &[u8]) -> Option<ExtractedMeta> {
     let mut abi_version = "greentic-abi-0".to_string();
     let mut provider_name = None;
     let mut provider_version = None;
+    let mut world_name = Some(world.name.clone());
 
     if let Some(pkg_id) = world.package {
         let pkg = &resolve.packages[pkg_id];
+        world_name = Some(format_package(&pkg.name, Some(&world.name)));
         provider_name = Some(pkg.name.name.clone());
         if let Some(version) = &pkg.name.version {
             provider_version = Some(version.to_string());
@@ -98,6 +107,7 @@ fn extract_from_wit_metadata(bytes: &[u8]) -> Option<ExtractedMeta> {
         provider_name,
         provider_version,
         capabilities,
+        world: world_name,
     })
 }
 
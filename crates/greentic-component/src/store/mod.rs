@@ -1,23 +1,200 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::path::{Path, PathBuf};
 
-#[cfg(not(feature = "oci"))]
-use anyhow::bail;
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result, anyhow, bail};
 use bytes::Bytes;
+use component_manifest::ComponentDependency;
 use serde::{Deserialize, Serialize};
-use tracing::instrument;
+use sha2::{Digest as _, Sha256};
+use tracing::{instrument, warn};
 
-use self::cache::Cache;
 use crate::path_safety::normalize_under_root;
 
+/// Environment variable [`ComponentStore::from_env`] reads the read-only
+/// system cache directory from; the runtime's store reads the same one.
+pub const SYSTEM_CACHE_ENV: &str = "GREENTIC_COMPONENT_SYSTEM_CACHE";
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ComponentId(pub String);
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ComponentLocator {
-    Fs { path: PathBuf },
-    Oci { reference: String },
+    Fs {
+        path: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pin: Option<DigestPin>,
+    },
+    Oci {
+        reference: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pin: Option<DigestPin>,
+    },
+}
+
+impl ComponentLocator {
+    /// Parses `oci://<reference>`, `file://<path>` or a plain path. Any other
+    /// scheme (`https://`, `warg://`, ...) is rejected instead of being read
+    /// as a relative path.
+    ///
+    /// A digest can be pinned inline with an OCI reference ending in
+    /// `@sha256:<hex>` or a `#sha256=<hex>` / `#blake3=<hex>` fragment on
+    /// either URL form. Fetches then fail unless the bytes match it.
+    pub fn parse(locator: &str) -> Result<Self> {
+        if let Some(reference) = locator.strip_prefix("oci://") {
+            let (reference, fragment) = split_fragment(reference);
+            let inline = reference
+                .rsplit('/')
+                .next()
+                .and_then(|name| name.split_once('@'))
+                .map(|(_, digest)| {
+                    let (algorithm, hex) = digest.split_once(':').unwrap_or(("", digest));
+                    DigestPin::parse(algorithm, hex)
+                })
+                .transpose()
+                .with_context(|| format!("locator `{locator}`"))?;
+            let pin = match (inline, fragment_pin(locator, fragment)?) {
+                (Some(_), Some(_)) => {
+                    bail!("locator `{locator}` pins its digest twice")
+                }
+                (inline, fragment) => inline.or(fragment),
+            };
+            return Ok(ComponentLocator::Oci {
+                reference: reference.to_string(),
+                pin,
+            });
+        }
+        if let Some(path) = locator.strip_prefix("file://") {
+            let (path, fragment) = split_fragment(path);
+            return Ok(ComponentLocator::Fs {
+                path: PathBuf::from(path),
+                pin: fragment_pin(locator, fragment)?,
+            });
+        }
+        if let Some((scheme, _)) = locator.split_once("://")
+            && !scheme.is_empty()
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        {
+            bail!(
+                "unsupported locator scheme `{scheme}://` in `{locator}` \
+                 (expected oci://, file:// or a path)"
+            );
+        }
+        Ok(ComponentLocator::Fs {
+            path: PathBuf::from(locator),
+            pin: None,
+        })
+    }
+
+    /// The digest the fetched bytes must match, if the locator pins one.
+    pub fn pin(&self) -> Option<&DigestPin> {
+        match self {
+            ComponentLocator::Fs { pin, .. } | ComponentLocator::Oci { pin, .. } => pin.as_ref(),
+        }
+    }
+}
+
+impl fmt::Display for ComponentLocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentLocator::Fs { path, pin } => {
+                write!(f, "{}", path.display())?;
+                match pin {
+                    Some(pin) => write!(f, "#{}={}", pin.algorithm, pin.hex),
+                    None => Ok(()),
+                }
+            }
+            ComponentLocator::Oci { reference, pin } => {
+                write!(f, "oci://{reference}")?;
+                match pin {
+                    Some(pin) if !reference.ends_with(&format!("@{pin}")) => {
+                        write!(f, "#{}={}", pin.algorithm, pin.hex)
+                    }
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+fn split_fragment(locator: &str) -> (&str, Option<&str>) {
+    match locator.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (locator, None),
+    }
+}
+
+fn fragment_pin(locator: &str, fragment: Option<&str>) -> Result<Option<DigestPin>> {
+    let Some(fragment) = fragment.filter(|fragment| !fragment.is_empty()) else {
+        return Ok(None);
+    };
+    let (algorithm, hex) = fragment.split_once('=').ok_or_else(|| {
+        anyhow!("locator `{locator}`: pin `#{fragment}` must be `#<algorithm>=<hex>`")
+    })?;
+    DigestPin::parse(algorithm, hex)
+        .map(Some)
+        .with_context(|| format!("locator `{locator}`"))
+}
+
+/// A digest pinned inside a locator; see [`ComponentLocator::parse`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DigestPin {
+    pub algorithm: PinAlgorithm,
+    /// Lowercase hex.
+    pub hex: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PinAlgorithm {
+    Sha256,
+    Blake3,
+}
+
+impl fmt::Display for PinAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PinAlgorithm::Sha256 => "sha256",
+            PinAlgorithm::Blake3 => "blake3",
+        })
+    }
+}
+
+impl DigestPin {
+    fn parse(algorithm: &str, hex: &str) -> Result<Self> {
+        let algorithm = match algorithm.to_ascii_lowercase().as_str() {
+            "sha256" => PinAlgorithm::Sha256,
+            "blake3" => PinAlgorithm::Blake3,
+            other => bail!("unsupported pin algorithm `{other}` (expected sha256 or blake3)"),
+        };
+        if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("pinned {algorithm} digest must be 64 hex characters");
+        }
+        Ok(Self {
+            algorithm,
+            hex: hex.to_ascii_lowercase(),
+        })
+    }
+
+    /// The digest of `bytes` under the pinned algorithm, as lowercase hex.
+    pub fn compute(&self, bytes: &[u8]) -> String {
+        match self.algorithm {
+            PinAlgorithm::Sha256 => hex::encode(Sha256::digest(bytes)),
+            PinAlgorithm::Blake3 => blake3::hash(bytes).to_hex().to_string(),
+        }
+    }
+
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        self.compute(bytes) == self.hex
+    }
+}
+
+impl fmt::Display for DigestPin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm, self.hex)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -29,6 +206,13 @@ pub struct ComponentBytes {
 
 pub type SourceId = String;
 
+/// A manifest dependency together with the component bytes it resolved to.
+#[derive(Clone, Debug)]
+pub struct ResolvedDependency {
+    pub dependency: ComponentDependency,
+    pub component: ComponentBytes,
+}
+
 #[derive(Clone, Debug)]
 pub struct ComponentStore {
     sources: HashMap<SourceId, ComponentLocator>,
@@ -51,9 +235,50 @@ impl ComponentStore {
         }
     }
 
+    /// The store the CLI works with: [`ComponentStore::with_cache_dir`],
+    /// encrypting the cache when [`CacheEncryptionKey::load`] finds a key and
+    /// falling back to the system cache named by [`SYSTEM_CACHE_ENV`].
+    pub fn from_env(cache_dir: Option<PathBuf>, compat: CompatPolicy) -> Result<Self> {
+        let mut store = Self::with_cache_dir(cache_dir, compat);
+        if let Some(key) = CacheEncryptionKey::load()? {
+            store = store.with_cache_encryption(key);
+        }
+        if let Some(dir) = std::env::var_os(SYSTEM_CACHE_ENV).filter(|dir| !dir.is_empty()) {
+            store = store.with_system_cache(dir);
+        }
+        Ok(store)
+    }
+
+    /// Reads from `dir` when the cache misses; see [`Cache::with_system_cache`].
+    pub fn with_system_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache = self.cache.with_system_cache(dir);
+        self
+    }
+
+    /// Signs cache entries with `key`; see [`Cache::with_signing_key`].
+    pub fn with_cache_key(mut self, key: CacheKey, on_tamper: TamperPolicy) -> Self {
+        self.cache = self.cache.with_signing_key(key, on_tamper);
+        self
+    }
+
+    /// Encrypts cached artifacts with `key`; see [`Cache::with_encryption_key`].
+    pub fn with_cache_encryption(mut self, key: CacheEncryptionKey) -> Self {
+        self.cache = self.cache.with_encryption_key(key);
+        self
+    }
+
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
     pub fn add_fs(&mut self, id: impl Into<SourceId>, path: impl Into<PathBuf>) -> &mut Self {
-        self.sources
-            .insert(id.into(), ComponentLocator::Fs { path: path.into() });
+        self.sources.insert(
+            id.into(),
+            ComponentLocator::Fs {
+                path: path.into(),
+                pin: None,
+            },
+        );
         self
     }
 
@@ -62,6 +287,7 @@ impl ComponentStore {
             id.into(),
             ComponentLocator::Oci {
                 reference: reference.into(),
+                pin: None,
             },
         );
         self
@@ -73,18 +299,62 @@ impl ComponentStore {
             .sources
             .get(source_id)
             .ok_or_else(|| anyhow!("unknown source id: {source_id}"))?;
+        self.fetch(loc).await
+    }
 
+    /// Fetches every manifest dependency. A source registered under the
+    /// dependency id wins over the `locator` declared in the manifest; version
+    /// ranges are checked against `describe` by the runtime once loaded.
+    pub async fn resolve_dependencies(
+        &self,
+        dependencies: &[ComponentDependency],
+    ) -> Result<Vec<ResolvedDependency>> {
+        let mut resolved = Vec::with_capacity(dependencies.len());
+        for dependency in dependencies {
+            let locator = match self.sources.get(&dependency.id) {
+                Some(loc) => loc.clone(),
+                None => {
+                    let locator = dependency.locator.as_deref().ok_or_else(|| {
+                        anyhow!(
+                            "dependency `{}` has no registered source or locator",
+                            dependency.id
+                        )
+                    })?;
+                    ComponentLocator::parse(locator)
+                        .with_context(|| format!("dependency `{}`", dependency.id))?
+                }
+            };
+            let component = self
+                .fetch(&locator)
+                .await
+                .with_context(|| format!("failed to resolve dependency `{}`", dependency.id))?;
+            resolved.push(ResolvedDependency {
+                dependency: dependency.clone(),
+                component,
+            });
+        }
+        Ok(resolved)
+    }
+
+    async fn fetch(&self, loc: &ComponentLocator) -> Result<ComponentBytes> {
         if let Some(hit) = self.cache.try_load(loc).await? {
-            compat::check(&self.compat, &hit.meta).map_err(anyhow::Error::new)?;
-            return Ok(hit);
+            match loc.pin() {
+                Some(pin) if !pin.matches(&hit.bytes) => {
+                    warn!("cached {loc} does not match its pinned digest {pin}; fetching it again");
+                }
+                _ => {
+                    compat::check(&self.compat, &hit.meta).map_err(anyhow::Error::new)?;
+                    return Ok(hit);
+                }
+            }
         }
 
         let bytes = match loc {
-            ComponentLocator::Fs { path } => {
+            ComponentLocator::Fs { path, .. } => {
                 let (fs_root, candidate) = filesystem_root_and_path(path.as_path())?;
                 fs_source::fetch(&fs_root, &candidate).await?
             }
-            ComponentLocator::Oci { reference } => {
+            ComponentLocator::Oci { reference, .. } => {
                 #[cfg(feature = "oci")]
                 {
                     oci_source::fetch(reference).await?
@@ -96,6 +366,16 @@ impl ComponentStore {
             }
         };
 
+        if let Some(pin) = loc.pin()
+            && !pin.matches(&bytes)
+        {
+            bail!(
+                "{loc} does not match its pinned digest: expected {pin}, got {}:{}",
+                pin.algorithm,
+                pin.compute(&bytes)
+            );
+        }
+
         let (id, meta) = meta::compute_id_and_meta(bytes.as_ref()).await?;
         let cb = ComponentBytes { id, bytes, meta };
 
@@ -130,9 +410,20 @@ fn filesystem_root_and_path(path: &Path) -> Result<(PathBuf, PathBuf)> {
 mod cache;
 mod compat;
 mod fs_source;
+mod index;
 mod meta;
 #[cfg(feature = "oci")]
 mod oci_source;
+mod resolver;
 
-pub use compat::{CompatError, CompatPolicy};
+pub use cache::{
+    Cache, CacheCheck, CacheEncryptionKey, CacheEntry, CacheKey, CacheStatus, CacheTampered,
+    TamperPolicy,
+};
+pub use compat::{CompatError, CompatPolicy, ProvenanceRequirement};
+pub use index::{
+    ComponentIndex, INDEX_FILE, INDEX_FORMAT, IndexEntry, IndexKey, IndexOperation, IndexSchemas,
+    IndexSignature,
+};
 pub use meta::MetaInfo;
+pub use resolver::{Registry, Resolution, Resolver};
@@ -32,3 +32,32 @@ pub async fn fetch(reference: &str) -> Result<Bytes> {
         selected.ok_or_else(|| anyhow!("no suitable component layer found for {reference}"))?;
     Ok(Bytes::from(bytes))
 }
+
+/// Tags requested per page of the tag list; registries may cap it lower.
+const TAG_PAGE_SIZE: usize = 100;
+
+/// Lists every tag of `repository` (a reference without a tag), following
+/// the registry's pagination: each page is requested with `n` and `last`
+/// set to the final tag of the previous one, until a page brings nothing new.
+pub async fn list_tags(repository: &str) -> Result<Vec<String>> {
+    let reference: Reference = repository
+        .parse()
+        .map_err(|err| anyhow!("invalid OCI repository '{repository}': {err}"))?;
+    let client = Client::new(ClientConfig::default());
+    let mut tags: Vec<String> = Vec::new();
+    loop {
+        let page = client
+            .list_tags(
+                &reference,
+                &RegistryAuth::Anonymous,
+                Some(TAG_PAGE_SIZE),
+                tags.last().map(String::as_str),
+            )
+            .await?;
+        // Registries that ignore `last` hand back the first page again.
+        if page.tags.is_empty() || page.tags.last() == tags.last() {
+            return Ok(tags);
+        }
+        tags.extend(page.tags);
+    }
+}
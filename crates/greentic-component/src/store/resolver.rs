@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use component_manifest::{ComponentDependency, WitCompat};
+use semver::{Version, VersionReq};
+use tracing::instrument;
+
+use super::index::{ComponentIndex, IndexKey};
+use super::{CompatError, ComponentLocator, ComponentStore, MetaInfo};
+
+/// Where the published versions of one component are listed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Registry {
+    /// An OCI repository whose tags are versions (`1.2.0` or `v1.2.0`).
+    Oci { repository: String },
+    /// A directory holding one `<version>.wasm` file per version.
+    Dir { path: PathBuf },
+    /// A catalog written by `greentic-component index build`; artifacts must
+    /// match the digest it records.
+    Index { path: PathBuf },
+    /// A package in a warg registry, e.g. `acme:translate` on
+    /// `https://registry.example`. The store cannot fetch from warg yet, so
+    /// resolving against one fails with that reason rather than with "no
+    /// registry configured".
+    Warg { registry: String, package: String },
+}
+
+/// The version a [`Resolver`] picked for a component.
+#[derive(Clone, Debug)]
+pub struct Resolution {
+    pub id: String,
+    pub version: Version,
+    pub locator: ComponentLocator,
+    /// `sha256:<hex>` of the selected artifact.
+    pub digest: String,
+    pub meta: MetaInfo,
+}
+
+/// Picks the newest published version of a component that satisfies a semver
+/// requirement, passes the store's [`CompatPolicy`](super::CompatPolicy) and,
+/// when set, falls inside a WIT compatibility range.
+///
+/// Candidates are fetched newest first through the store, so the chosen
+/// artifact is cached once resolution returns.
+#[derive(Clone, Debug)]
+pub struct Resolver {
+    store: ComponentStore,
+    registries: HashMap<String, Registry>,
+    wit_compat: Option<WitCompat>,
+    index_key: Option<IndexKey>,
+}
+
+impl Resolver {
+    pub fn new(store: ComponentStore) -> Self {
+        Self {
+            store,
+            registries: HashMap::new(),
+            wit_compat: None,
+            index_key: None,
+        }
+    }
+
+    /// Only accept artifacts whose world package version lies within
+    /// `wit_compat.min..=wit_compat.max`. Artifacts without a detectable
+    /// version are skipped.
+    pub fn with_wit_compat(mut self, wit_compat: WitCompat) -> Self {
+        self.wit_compat = Some(wit_compat);
+        self
+    }
+
+    /// Require catalog indexes to carry a valid signature for `key`.
+    pub fn with_index_key(mut self, key: IndexKey) -> Self {
+        self.index_key = Some(key);
+        self
+    }
+
+    pub fn add_oci(&mut self, id: impl Into<String>, repository: impl Into<String>) -> &mut Self {
+        self.registries.insert(
+            id.into(),
+            Registry::Oci {
+                repository: repository.into(),
+            },
+        );
+        self
+    }
+
+    pub fn add_dir(&mut self, id: impl Into<String>, path: impl Into<PathBuf>) -> &mut Self {
+        self.registries
+            .insert(id.into(), Registry::Dir { path: path.into() });
+        self
+    }
+
+    pub fn add_warg(
+        &mut self,
+        id: impl Into<String>,
+        registry: impl Into<String>,
+        package: impl Into<String>,
+    ) -> &mut Self {
+        self.registries.insert(
+            id.into(),
+            Registry::Warg {
+                registry: registry.into(),
+                package: package.into(),
+            },
+        );
+        self
+    }
+
+    /// Registers every component listed in the catalog index at `path`.
+    /// The index is read again at resolve time, so it can be republished
+    /// while the resolver is alive.
+    pub fn add_index(&mut self, path: impl Into<PathBuf>) -> Result<&mut Self> {
+        let path = path.into();
+        let index = ComponentIndex::load(&path, self.index_key.as_ref())?;
+        for entry in &index.components {
+            self.registries
+                .insert(entry.id.clone(), Registry::Index { path: path.clone() });
+        }
+        Ok(self)
+    }
+
+    /// Resolves a manifest dependency against its declared version range.
+    pub async fn resolve_dependency(&self, dependency: &ComponentDependency) -> Result<Resolution> {
+        let req = dependency.version_req()?;
+        self.resolve(&dependency.id, &req).await
+    }
+
+    #[instrument(level = "debug", skip(self, req), fields(req = %req))]
+    pub async fn resolve(&self, id: &str, req: &VersionReq) -> Result<Resolution> {
+        let registry = self
+            .registries
+            .get(id)
+            .ok_or_else(|| anyhow!("no registry configured for `{id}`"))?;
+        let mut candidates = list_versions(id, registry, self.index_key.as_ref())
+            .await
+            .with_context(|| format!("list versions of `{id}`"))?
+            .into_iter()
+            .filter(|candidate| req.matches(&candidate.version))
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| b.version.cmp(&a.version));
+
+        let mut rejected = String::new();
+        for Candidate {
+            version,
+            locator,
+            digest,
+        } in candidates
+        {
+            let component = match self.store.fetch(&locator).await {
+                Ok(component) => component,
+                Err(err) if err.downcast_ref::<CompatError>().is_some() => {
+                    let _ = write!(rejected, "\n  {version}: {err}");
+                    continue;
+                }
+                Err(err) => {
+                    return Err(err.context(format!("fetch `{id}` {version}")));
+                }
+            };
+            if let Some(expected) = &digest
+                && component.id.0 != *expected
+            {
+                let _ = write!(
+                    rejected,
+                    "\n  {version}: digest {} does not match the index ({expected})",
+                    component.id.0
+                );
+                continue;
+            }
+            if let Some(wit_compat) = &self.wit_compat
+                && let Err(reason) = check_wit_compat(wit_compat, &component.meta)
+            {
+                let _ = write!(rejected, "\n  {version}: {reason}");
+                continue;
+            }
+            return Ok(Resolution {
+                id: id.to_string(),
+                version,
+                locator,
+                digest: component.id.0.clone(),
+                meta: component.meta,
+            });
+        }
+        if rejected.is_empty() {
+            Err(anyhow!("no published version of `{id}` matches `{req}`"))
+        } else {
+            Err(anyhow!(
+                "no compatible version of `{id}` matches `{req}`; rejected:{rejected}"
+            ))
+        }
+    }
+}
+
+struct Candidate {
+    version: Version,
+    locator: ComponentLocator,
+    /// Digest the artifact must have, when the registry records one.
+    digest: Option<String>,
+}
+
+impl Candidate {
+    fn new(version: Version, locator: ComponentLocator) -> Self {
+        Self {
+            version,
+            locator,
+            digest: None,
+        }
+    }
+}
+
+async fn list_versions(
+    id: &str,
+    registry: &Registry,
+    index_key: Option<&IndexKey>,
+) -> Result<Vec<Candidate>> {
+    match registry {
+        Registry::Oci { repository } => {
+            #[cfg(feature = "oci")]
+            {
+                let tags = super::oci_source::list_tags(repository).await?;
+                Ok(tags
+                    .into_iter()
+                    .filter_map(|tag| {
+                        let version = parse_version(&tag)?;
+                        let reference = format!("{repository}:{tag}");
+                        Some(Candidate::new(
+                            version,
+                            ComponentLocator::Oci {
+                                reference,
+                                pin: None,
+                            },
+                        ))
+                    })
+                    .collect())
+            }
+            #[cfg(not(feature = "oci"))]
+            {
+                bail!("OCI support disabled: enable the `oci` feature to list {repository}");
+            }
+        }
+        Registry::Dir { path } => {
+            let mut entries = tokio::fs::read_dir(path)
+                .await
+                .with_context(|| format!("read registry directory {}", path.display()))?;
+            let mut versions = Vec::new();
+            while let Some(entry) = entries.next_entry().await? {
+                let file = entry.path();
+                if file.extension().is_none_or(|ext| ext != "wasm") {
+                    continue;
+                }
+                if let Some(version) = file
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(parse_version)
+                {
+                    versions.push(Candidate::new(
+                        version,
+                        ComponentLocator::Fs {
+                            path: file,
+                            pin: None,
+                        },
+                    ));
+                }
+            }
+            Ok(versions)
+        }
+        Registry::Index { path } => {
+            let index = ComponentIndex::load(path, index_key)?;
+            let dir = path.parent().unwrap_or_else(|| Path::new("."));
+            let mut versions = Vec::new();
+            for (entry, locator) in index.versions(id, dir) {
+                if let Some(version) = parse_version(&entry.version) {
+                    versions.push(Candidate {
+                        version,
+                        locator: locator.with_context(|| {
+                            format!("index entry {id}@{} in {}", entry.version, path.display())
+                        })?,
+                        digest: Some(entry.digest.clone()),
+                    });
+                }
+            }
+            Ok(versions)
+        }
+        Registry::Warg { registry, package } => {
+            bail!(
+                "cannot list `{package}` on warg registry {registry}: warg fetches are not supported yet"
+            )
+        }
+    }
+}
+
+/// Tags like `latest` or `main` are not versions and are ignored.
+fn parse_version(tag: &str) -> Option<Version> {
+    Version::parse(tag.strip_prefix('v').unwrap_or(tag)).ok()
+}
+
+fn check_wit_compat(wit_compat: &WitCompat, meta: &MetaInfo) -> Result<(), String> {
+    let package = wit_compat
+        .package
+        .rsplit_once(':')
+        .map_or(wit_compat.package.as_str(), |(_, name)| name);
+    let found = meta
+        .provider_name
+        .as_deref()
+        .zip(meta.provider_version.as_deref())
+        .filter(|(name, _)| *name == package)
+        .and_then(|(_, version)| Version::parse(version).ok())
+        .ok_or_else(|| format!("no `{}` WIT version found", wit_compat.package))?;
+    let min = Version::parse(&wit_compat.min)
+        .map_err(|err| format!("wit_compat.min `{}`: {err}", wit_compat.min))?;
+    if found < min {
+        return Err(format!("WIT {found} is older than {min}"));
+    }
+    if let Some(max) = &wit_compat.max {
+        let max = Version::parse(max).map_err(|err| format!("wit_compat.max `{max}`: {err}"))?;
+        if found > max {
+            return Err(format!("WIT {found} is newer than {max}"));
+        }
+    }
+    Ok(())
+}
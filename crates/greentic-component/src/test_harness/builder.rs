@@ -0,0 +1,491 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use greentic_component_runtime::{
+    BlobLimits, EventSink, EventsHost, GuestLogger, MessagingHost, PoolingConfig, ProgressCallback,
+    SqlAccess, TelemetryHost, events_scope, messaging_scope, sql_scope,
+};
+use greentic_types::{EnvId, TenantCtx, TenantId};
+use serde_json::Value;
+use tracing::level_filters::LevelFilter;
+use uuid::Uuid;
+
+use super::{
+    AuditLog, ChaosConfig, EventRecorder, HarnessConfig, IsolationProfile, SqliteSql, TestHarness,
+    TranscriptMessaging, WasiPreopen, WasiRandom,
+};
+use crate::capabilities::NetCaps;
+use crate::manifest::ComponentManifest;
+
+/// Used when neither the manifest nor the caller sets a timeout.
+const DEFAULT_TIMEOUT_MS: u64 = 2_000;
+/// Used when neither the manifest nor the caller sets a memory limit.
+const DEFAULT_MAX_MEMORY_MB: u64 = 256;
+/// Used when neither the manifest nor the caller caps output size.
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 2 * 1024 * 1024;
+
+/// Fluent construction of a [`TestHarness`], from [`TestHarness::builder`].
+///
+/// Without further calls the harness grants nothing: no state, secrets,
+/// HTTP, sockets, blobs, SQL, messaging or events, for the `dev`/`default`
+/// tenant. [`manifest`](Self::manifest) grants what a manifest declares and
+/// takes its `limits`; the remaining methods override single settings.
+pub struct TestHarnessBuilder {
+    config: HarnessConfig,
+}
+
+impl TestHarness {
+    pub fn builder(wasm_bytes: Vec<u8>) -> TestHarnessBuilder {
+        TestHarnessBuilder::new(wasm_bytes)
+    }
+}
+
+impl TestHarnessBuilder {
+    fn new(wasm_bytes: Vec<u8>) -> Self {
+        let tenant_ctx = TenantCtx::new(
+            EnvId::try_from("dev".to_string()).expect("static env id"),
+            TenantId::try_from("default".to_string()).expect("static tenant id"),
+        );
+        Self {
+            config: HarnessConfig {
+                wasm_bytes,
+                guest_log: GuestLogger::new("component", Some(&tenant_ctx), LevelFilter::WARN),
+                tenant_ctx,
+                flow_id: "test".to_string(),
+                node_id: None,
+                state_prefix: format!("test/{}", Uuid::new_v4()),
+                state_seeds: Vec::new(),
+                allow_state_read: false,
+                allow_state_write: false,
+                allow_state_delete: false,
+                allow_secrets: false,
+                allowed_secrets: HashSet::new(),
+                secrets: HashMap::new(),
+                wasi_preopens: Vec::new(),
+                fs_quota: None,
+                env_vars: Vec::new(),
+                config: None,
+                allow_http: false,
+                http_domains: Vec::new(),
+                timeout_ms: DEFAULT_TIMEOUT_MS,
+                max_memory_bytes: (DEFAULT_MAX_MEMORY_MB * 1024 * 1024) as usize,
+                max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+                isolation: IsolationProfile::default(),
+                max_open_handles: None,
+                net: None,
+                audit: AuditLog::default(),
+                allow_progress: false,
+                on_progress: None,
+                blobs: None,
+                sql: None,
+                messaging: MessagingHost::default(),
+                events: EventsHost::default(),
+                telemetry: TelemetryHost::default(),
+                chaos: None,
+                pooling: None,
+                transactional_state: false,
+                random: WasiRandom::Fixed,
+                profile: None,
+            },
+        }
+    }
+
+    /// Grants what the manifest declares (state, secrets, HTTP client and its
+    /// domains, progress, blobs, SQL, messaging, events, telemetry) and
+    /// applies its `limits`: the
+    /// wall-time becomes the timeout, `memory_mb` the memory cap, `files`
+    /// the open-handle cap and `max_output_bytes` the output cap. The
+    /// default profile is selected. Call [`tenant`](Self::tenant) first when
+    /// the tenant matters, since hosts are scoped to it here.
+    pub fn manifest(
+        mut self,
+        manifest_value: &Value,
+        manifest: &ComponentManifest,
+    ) -> Result<Self> {
+        let tenant_ctx = self.config.tenant_ctx.clone();
+        let config = &mut self.config;
+        (
+            config.allow_state_read,
+            config.allow_state_write,
+            config.allow_state_delete,
+        ) = state_permissions(manifest_value, manifest);
+        (config.allow_secrets, config.allowed_secrets) = secret_permissions(manifest);
+        config.allow_http = http_permission(manifest);
+        config.http_domains = manifest.http_domains.clone();
+        config.allow_progress = progress_permission(manifest_value);
+        config.blobs = blob_permission(manifest_value)?;
+        config.sql = sql_access(manifest_value, &tenant_ctx, &[])?;
+        config.messaging =
+            messaging_host(manifest, &tenant_ctx, Arc::new(TranscriptMessaging::new()));
+        config.events = events_host(manifest, &tenant_ctx, Arc::new(EventRecorder::new()));
+        config.telemetry = telemetry_host(manifest, &tenant_ctx);
+        config.guest_log =
+            GuestLogger::new(manifest.id.as_str(), Some(&tenant_ctx), LevelFilter::WARN);
+        config.profile = manifest.profiles.default.clone();
+        if let Some(limits) = &manifest.limits {
+            config.timeout_ms = limits.wall_time_ms;
+            config.max_memory_bytes = usize::try_from(u64::from(limits.memory_mb) * 1024 * 1024)
+                .context("limits.memory_mb is too large for this platform")?;
+            config.max_open_handles = limits.files.map(|files| files as usize);
+            if let Some(max_output_bytes) = limits.max_output_bytes {
+                config.max_output_bytes = usize::try_from(max_output_bytes).unwrap_or(usize::MAX);
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn tenant(mut self, tenant_ctx: TenantCtx) -> Self {
+        self.config.tenant_ctx = tenant_ctx;
+        self
+    }
+
+    pub fn flow(mut self, flow_id: impl Into<String>, node_id: Option<String>) -> Self {
+        self.config.flow_id = flow_id.into();
+        self.config.node_id = node_id;
+        self
+    }
+
+    pub fn state_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.config.state_prefix = prefix.into();
+        self
+    }
+
+    pub fn state_seed(mut self, key: impl Into<String>, value: Vec<u8>) -> Self {
+        self.config.state_seeds.push((key.into(), value));
+        self
+    }
+
+    /// Adds a secret; it is only readable when secrets are granted.
+    pub fn secret(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.secrets.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn config(mut self, config: Value) -> Self {
+        self.config.config = Some(config);
+        self
+    }
+
+    pub fn preopen(mut self, preopen: WasiPreopen) -> Self {
+        self.config.wasi_preopens.push(preopen);
+        self
+    }
+
+    pub fn env_var(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.env_vars.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn allow_http(mut self, allow: bool) -> Self {
+        self.config.allow_http = allow;
+        self
+    }
+
+    pub fn net(mut self, net: Option<NetCaps>) -> Self {
+        self.config.net = net;
+        self
+    }
+
+    pub fn timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.config.timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn max_memory_bytes(mut self, bytes: usize) -> Self {
+        self.config.max_memory_bytes = bytes;
+        self
+    }
+
+    pub fn max_output_bytes(mut self, bytes: usize) -> Self {
+        self.config.max_output_bytes = bytes;
+        self
+    }
+
+    pub fn max_open_handles(mut self, handles: Option<usize>) -> Self {
+        self.config.max_open_handles = handles;
+        self
+    }
+
+    pub fn isolation(mut self, isolation: IsolationProfile) -> Self {
+        self.config.isolation = isolation;
+        self
+    }
+
+    pub fn audit(mut self, audit: AuditLog) -> Self {
+        self.config.audit = audit;
+        self
+    }
+
+    pub fn on_progress(mut self, callback: ProgressCallback) -> Self {
+        self.config.on_progress = Some(callback);
+        self
+    }
+
+    pub fn chaos(mut self, chaos: ChaosConfig) -> Self {
+        self.config.chaos = Some(chaos);
+        self
+    }
+
+    pub fn pooling(mut self, pooling: Option<PoolingConfig>) -> Self {
+        self.config.pooling = pooling;
+        self
+    }
+
+    /// Commit each invocation's state changes only when it succeeds.
+    pub fn transactional_state(mut self, enabled: bool) -> Self {
+        self.config.transactional_state = enabled;
+        self
+    }
+
+    pub fn random(mut self, random: WasiRandom) -> Self {
+        self.config.random = random;
+        self
+    }
+
+    /// Runs under `profile` instead of the manifest default.
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.config.profile = Some(profile.into());
+        self
+    }
+
+    /// Direct access for settings without a dedicated method.
+    pub fn config_mut(&mut self) -> &mut HarnessConfig {
+        &mut self.config
+    }
+
+    pub fn into_config(self) -> HarnessConfig {
+        self.config
+    }
+
+    pub fn build(self) -> Result<TestHarness> {
+        TestHarness::new(self.config)
+    }
+}
+
+pub(crate) fn state_permissions(
+    manifest_value: &Value,
+    manifest: &ComponentManifest,
+) -> (bool, bool, bool) {
+    let mut allow_state_read = false;
+    let mut allow_state_write = false;
+    if let Some(state) = manifest.capabilities.host.state.as_ref() {
+        allow_state_read = state.read;
+        allow_state_write = state.write;
+    }
+    let allow_state_delete = manifest_value
+        .get("capabilities")
+        .and_then(|caps| caps.get("host"))
+        .and_then(|host| host.get("state"))
+        .and_then(|state| state.get("delete"))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    if allow_state_delete && !allow_state_write {
+        allow_state_write = true;
+    }
+    (allow_state_read, allow_state_write, allow_state_delete)
+}
+
+pub(crate) fn progress_permission(manifest_value: &Value) -> bool {
+    manifest_value
+        .pointer("/capabilities/host/progress")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Reads `capabilities.host.blob`: `true` grants the default limits, an
+/// object may override `max_blob_bytes` and `max_written_bytes`.
+pub(crate) fn blob_permission(manifest_value: &Value) -> Result<Option<BlobLimits>> {
+    let Some(blob) = manifest_value.pointer("/capabilities/host/blob") else {
+        return Ok(None);
+    };
+    let mut limits = BlobLimits::default();
+    match blob {
+        Value::Bool(granted) => return Ok(granted.then_some(limits)),
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                let value = value.as_u64().with_context(|| {
+                    format!("capabilities.host.blob.{key} must be a non-negative integer")
+                })?;
+                match key.as_str() {
+                    "max_blob_bytes" => limits.max_blob_bytes = value,
+                    "max_written_bytes" => limits.max_written_bytes = value,
+                    other => bail!("unknown capabilities.host.blob field `{other}`"),
+                }
+            }
+        }
+        _ => bail!("capabilities.host.blob must be a boolean or an object"),
+    }
+    Ok(Some(limits))
+}
+
+/// Grants send/ack according to `capabilities.host.messaging.outbound` and
+/// `.inbound`, delivering to `transcript`.
+pub(crate) fn messaging_host(
+    manifest: &ComponentManifest,
+    tenant_ctx: &TenantCtx,
+    transcript: Arc<TranscriptMessaging>,
+) -> MessagingHost {
+    let (inbound, outbound) = manifest
+        .capabilities
+        .host
+        .messaging
+        .as_ref()
+        .map(|messaging| (messaging.inbound, messaging.outbound))
+        .unwrap_or_default();
+    MessagingHost::new(
+        messaging_scope(tenant_ctx),
+        Some(transcript),
+        outbound,
+        inbound,
+    )
+}
+
+/// Lets the component emit into `sink` when it declares
+/// `capabilities.host.events.outbound`.
+pub(crate) fn events_host(
+    manifest: &ComponentManifest,
+    tenant_ctx: &TenantCtx,
+    sink: Arc<dyn EventSink>,
+) -> EventsHost {
+    let outbound = manifest
+        .capabilities
+        .host
+        .events
+        .as_ref()
+        .is_some_and(|events| events.outbound);
+    EventsHost::new(events_scope(tenant_ctx), outbound.then_some(sink))
+}
+
+/// Keeps guest telemetry only when `capabilities.host.telemetry` is declared,
+/// limited to its scope.
+pub(crate) fn telemetry_host(
+    manifest: &ComponentManifest,
+    tenant_ctx: &TenantCtx,
+) -> TelemetryHost {
+    let scope = manifest
+        .capabilities
+        .host
+        .telemetry
+        .as_ref()
+        .map(|telemetry| telemetry.scope.clone());
+    TelemetryHost::new(manifest.id.as_str(), Some(tenant_ctx), scope)
+}
+
+/// Reads `capabilities.host.sql` (`connections` and named `statements`) and
+/// backs the declared connections with in-memory SQLite, running each
+/// `CONN=PATH` init script first.
+pub(crate) fn sql_access(
+    manifest_value: &Value,
+    tenant_ctx: &TenantCtx,
+    init: &[String],
+) -> Result<Option<SqlAccess>> {
+    let Some(sql) = manifest_value.pointer("/capabilities/host/sql") else {
+        return Ok(None);
+    };
+    let connections = sql
+        .get("connections")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|name| {
+            name.as_str()
+                .map(str::to_string)
+                .context("capabilities.host.sql.connections must be strings")
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let statements = sql
+        .get("statements")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(name, statement)| {
+            let statement = statement.as_str().with_context(|| {
+                format!("capabilities.host.sql.statements.{name} must be a string")
+            })?;
+            Ok((name.clone(), statement.to_string()))
+        })
+        .collect::<Result<BTreeMap<_, _>>>()?;
+
+    let backend = SqliteSql::new(connections);
+    let scope = sql_scope(tenant_ctx);
+    for entry in init {
+        let (connection, path) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("invalid --sql-init `{entry}`; use CONN=PATH"))?;
+        let script = fs::read_to_string(path).with_context(|| format!("read sql script {path}"))?;
+        backend
+            .execute_batch(&scope, connection, &script)
+            .with_context(|| format!("run sql script {path}"))?;
+    }
+    Ok(Some(SqlAccess::new(Arc::new(backend), statements)))
+}
+
+pub(crate) fn http_permission(manifest: &ComponentManifest) -> bool {
+    manifest
+        .capabilities
+        .host
+        .http
+        .as_ref()
+        .is_some_and(|http| http.client)
+}
+
+pub(crate) fn secret_permissions(manifest: &ComponentManifest) -> (bool, HashSet<String>) {
+    let Some(secrets) = manifest.capabilities.host.secrets.as_ref() else {
+        return (false, HashSet::new());
+    };
+    let allowed = secrets
+        .required
+        .iter()
+        .map(|req| req.key.as_str().to_string())
+        .collect::<HashSet<_>>();
+    (true, allowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::{parse_manifest, parse_manifest_value};
+
+    #[test]
+    fn manifest_grants_and_limits_become_defaults() {
+        let denied = TestHarness::builder(Vec::new()).into_config();
+        assert!(!denied.allow_state_read && !denied.allow_secrets);
+        assert_eq!(denied.timeout_ms, DEFAULT_TIMEOUT_MS);
+
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/manifests/valid.component.json");
+        let raw = fs::read_to_string(path).expect("manifest");
+        let value: Value = serde_json::from_str(&raw).expect("manifest json");
+        let manifest = parse_manifest(&raw).expect("manifest parse");
+        let config = TestHarness::builder(Vec::new())
+            .manifest(&value, &manifest)
+            .expect("manifest defaults")
+            .timeout_ms(500)
+            .into_config();
+        assert!(config.allow_state_read && !config.allow_state_write);
+        assert!(config.allowed_secrets.contains("KV_API_TOKEN"));
+        assert_eq!(config.max_memory_bytes, 128 * 1024 * 1024);
+        assert_eq!(config.max_open_handles, Some(16));
+        assert_eq!(config.timeout_ms, 500, "explicit overrides win");
+        assert_eq!(config.profile.as_deref(), Some("stateless"));
+        assert!(config.allow_http && config.http_domains.is_empty());
+
+        let mut scoped = value.clone();
+        scoped["capabilities"]["host"]["http"]["domains"] = serde_json::json!(["api.example.com"]);
+        let scoped_manifest = parse_manifest_value(scoped.clone()).expect("domains accepted");
+        let config = TestHarness::builder(Vec::new())
+            .manifest(&scoped, &scoped_manifest)
+            .expect("manifest defaults")
+            .into_config();
+        assert_eq!(config.http_domains, vec!["api.example.com".to_string()]);
+
+        let cached = TestHarness::builder(Vec::new())
+            .manifest(&value, &manifest)
+            .expect("manifest defaults")
+            .profile("cached")
+            .into_config();
+        assert_eq!(cached.profile.as_deref(), Some("cached"));
+    }
+}
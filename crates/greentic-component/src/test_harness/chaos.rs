@@ -0,0 +1,244 @@
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+/// Host imports chaos can target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChaosImport {
+    State,
+    Secrets,
+    Http,
+}
+
+/// A fault that fires with `probability` (0.0 to 1.0) on each call.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChaosFault {
+    pub probability: f64,
+}
+
+/// Added delay, fired like [`ChaosFault`].
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChaosLatency {
+    pub probability: f64,
+    pub ms: u64,
+}
+
+/// Fault injection settings, usually read from `--chaos chaos.yaml`:
+///
+/// ```yaml
+/// seed: 42
+/// imports: [state, http]
+/// latency: { probability: 0.2, ms: 150 }
+/// failure: { probability: 0.1 }
+/// truncate: { probability: 0.1 }
+/// reorder_writes: { probability: 0.3 }
+/// ```
+///
+/// The same seed replays the same faults for the same sequence of calls.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChaosConfig {
+    #[serde(default)]
+    pub seed: u64,
+    /// Imports faults apply to; empty means all of them.
+    #[serde(default)]
+    pub imports: Vec<ChaosImport>,
+    pub latency: Option<ChaosLatency>,
+    /// Fails state and HTTP calls as if the backend were unavailable.
+    pub failure: Option<ChaosFault>,
+    /// Cuts state reads and HTTP bodies to half their length.
+    pub truncate: Option<ChaosFault>,
+    /// Acks a state write but applies it only after the next write (or when
+    /// the invocation ends), so reads in between see the old value.
+    pub reorder_writes: Option<ChaosFault>,
+}
+
+impl ChaosConfig {
+    /// Reads a YAML (or JSON) chaos file.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("read chaos config {}", path.display()))?;
+        let config: ChaosConfig = serde_norway::from_str(&raw)
+            .with_context(|| format!("parse chaos config {}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let probabilities = [
+            ("latency", self.latency.map(|fault| fault.probability)),
+            ("failure", self.failure.map(|fault| fault.probability)),
+            ("truncate", self.truncate.map(|fault| fault.probability)),
+            (
+                "reorder_writes",
+                self.reorder_writes.map(|fault| fault.probability),
+            ),
+        ];
+        for (name, probability) in probabilities {
+            if let Some(probability) = probability
+                && !(0.0..=1.0).contains(&probability)
+            {
+                bail!("chaos {name}.probability must be between 0 and 1, got {probability}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What was injected, in call order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChaosEvent {
+    pub import: ChaosImport,
+    pub fault: &'static str,
+    /// State key or `METHOD url`.
+    pub target: String,
+}
+
+/// Shared fault source for every host import of a harness. Clones share the
+/// random stream, so faults depend only on the seed and the call order.
+#[derive(Debug, Clone)]
+pub struct Chaos {
+    config: Arc<ChaosConfig>,
+    state: Arc<Mutex<ChaosState>>,
+}
+
+#[derive(Debug)]
+struct ChaosState {
+    rng: u64,
+    events: Vec<ChaosEvent>,
+}
+
+impl Chaos {
+    pub fn new(config: ChaosConfig) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ChaosState {
+                rng: config.seed,
+                events: Vec::new(),
+            })),
+            config: Arc::new(config),
+        }
+    }
+
+    pub fn events(&self) -> Vec<ChaosEvent> {
+        self.lock().events.clone()
+    }
+
+    /// Sleeps when the latency fault fires.
+    pub fn delay(&self, import: ChaosImport, target: &str) {
+        if let Some(latency) = self.config.latency
+            && self.fires(import, latency.probability, "latency", target)
+        {
+            std::thread::sleep(Duration::from_millis(latency.ms));
+        }
+    }
+
+    pub fn fail(&self, import: ChaosImport, target: &str) -> bool {
+        self.config
+            .failure
+            .is_some_and(|fault| self.fires(import, fault.probability, "failure", target))
+    }
+
+    pub fn truncate(&self, import: ChaosImport, target: &str, mut bytes: Vec<u8>) -> Vec<u8> {
+        if self
+            .config
+            .truncate
+            .is_some_and(|fault| self.fires(import, fault.probability, "truncate", target))
+        {
+            bytes.truncate(bytes.len() / 2);
+        }
+        bytes
+    }
+
+    pub fn reorder_write(&self, target: &str) -> bool {
+        self.config.reorder_writes.is_some_and(|fault| {
+            self.fires(
+                ChaosImport::State,
+                fault.probability,
+                "reorder_write",
+                target,
+            )
+        })
+    }
+
+    fn fires(
+        &self,
+        import: ChaosImport,
+        probability: f64,
+        fault: &'static str,
+        target: &str,
+    ) -> bool {
+        if !self.config.imports.is_empty() && !self.config.imports.contains(&import) {
+            return false;
+        }
+        let mut state = self.lock();
+        let roll = next_unit(&mut state.rng);
+        if roll >= probability {
+            return false;
+        }
+        state.events.push(ChaosEvent {
+            import,
+            fault,
+            target: target.to_string(),
+        });
+        true
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ChaosState> {
+        self.state.lock().expect("chaos mutex poisoned")
+    }
+}
+
+/// SplitMix64, mapped to `[0, 1)`.
+fn next_unit(state: &mut u64) -> f64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(raw: &str) -> ChaosConfig {
+        let config: ChaosConfig = serde_norway::from_str(raw).unwrap();
+        config.validate().unwrap();
+        config
+    }
+
+    #[test]
+    fn same_seed_replays_the_same_faults() {
+        let raw = "seed: 7\nimports: [http]\nfailure: { probability: 0.5 }\n";
+        let run = |chaos: Chaos| {
+            (0..32)
+                .map(|index| chaos.fail(ChaosImport::Http, &format!("GET /{index}")))
+                .collect::<Vec<_>>()
+        };
+        let first = run(Chaos::new(config(raw)));
+        assert_eq!(first, run(Chaos::new(config(raw))));
+        assert!(first.contains(&true) && first.contains(&false));
+
+        let chaos = Chaos::new(config(raw));
+        assert!(!(0..32).any(|_| chaos.fail(ChaosImport::State, "key")));
+        assert!(chaos.events().is_empty());
+
+        let always = Chaos::new(config("truncate: { probability: 1.0 }\n"));
+        assert_eq!(
+            always.truncate(ChaosImport::State, "key", b"abcd".to_vec()),
+            b"ab"
+        );
+        assert_eq!(always.events()[0].fault, "truncate");
+
+        let bad: ChaosConfig = serde_norway::from_str("failure: { probability: 2 }\n").unwrap();
+        assert!(bad.validate().is_err());
+    }
+}
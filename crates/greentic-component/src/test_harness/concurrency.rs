@@ -0,0 +1,157 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Barrier, Mutex};
+use std::time::Instant;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{TestHarness, invoke_error_code};
+
+/// What happened when one operation was invoked from many threads at once.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConcurrencyReport {
+    pub operation: String,
+    pub concurrency: usize,
+    pub invocations: usize,
+    pub succeeded: usize,
+    /// Failed invocations by error code (`timeout`, `trap`, or the
+    /// component's own code).
+    pub failures: BTreeMap<String, usize>,
+    /// Distinct outputs among the successful invocations. Every call gets
+    /// the same input, so more than one hints at shared mutable state unless
+    /// the operation is meant to count or timestamp.
+    pub distinct_outputs: usize,
+    /// Lost updates by state key: writes that replaced a value the writer
+    /// had not read.
+    pub state_conflicts: BTreeMap<String, u64>,
+    pub latency: LatencyStats,
+    pub wall_ms: u64,
+}
+
+/// Per-invocation wall time (instantiate + run), in microseconds.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencyStats {
+    pub min_us: u64,
+    pub mean_us: u64,
+    pub p50_us: u64,
+    pub p95_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+impl ConcurrencyReport {
+    /// No invocation failed and no write lost another's update.
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty() && self.state_conflicts.is_empty()
+    }
+}
+
+impl TestHarness {
+    /// Invokes `operation` `repeat` times from `concurrency` threads, each
+    /// call in its own store but sharing the harness state. Threads start
+    /// together so the first wave overlaps. Each call has its own deadline,
+    /// so a slow call times out without interrupting the others.
+    pub fn invoke_concurrently(
+        &self,
+        operation: &str,
+        input: &Value,
+        concurrency: usize,
+        repeat: usize,
+    ) -> ConcurrencyReport {
+        let concurrency = concurrency.clamp(1, repeat.max(1));
+        let conflicts_before = self.state_store.conflicts();
+        let next = AtomicUsize::new(0);
+        let start = Barrier::new(concurrency);
+        let samples = Mutex::new(Vec::with_capacity(repeat));
+        let started = Instant::now();
+        std::thread::scope(|scope| {
+            for _ in 0..concurrency {
+                scope.spawn(|| {
+                    start.wait();
+                    while next.fetch_add(1, Ordering::Relaxed) < repeat {
+                        let call_started = Instant::now();
+                        let outcome = self
+                            .invoke(operation, input)
+                            .map(|outcome| outcome.output_json)
+                            .map_err(|err| invoke_error_code(&err).to_string());
+                        let elapsed_us = call_started.elapsed().as_micros() as u64;
+                        samples
+                            .lock()
+                            .expect("concurrency samples mutex poisoned")
+                            .push((elapsed_us, outcome));
+                    }
+                });
+            }
+        });
+        let wall_ms = started.elapsed().as_millis() as u64;
+
+        let samples = samples
+            .into_inner()
+            .expect("concurrency samples mutex poisoned");
+        let mut failures = BTreeMap::new();
+        let mut outputs = HashSet::new();
+        let mut latencies = Vec::with_capacity(samples.len());
+        for (elapsed_us, outcome) in samples {
+            latencies.push(elapsed_us);
+            match outcome {
+                Ok(output) => {
+                    outputs.insert(output);
+                }
+                Err(code) => *failures.entry(code).or_default() += 1,
+            }
+        }
+        let state_conflicts = self
+            .state_store
+            .conflicts()
+            .into_iter()
+            .filter_map(|(key, count)| {
+                let fresh = count - conflicts_before.get(&key).copied().unwrap_or_default();
+                (fresh > 0).then_some((key, fresh))
+            })
+            .collect();
+        let invocations = latencies.len();
+        ConcurrencyReport {
+            operation: operation.to_string(),
+            concurrency,
+            invocations,
+            succeeded: invocations - failures.values().sum::<usize>(),
+            failures,
+            distinct_outputs: outputs.len(),
+            state_conflicts,
+            latency: latency_stats(latencies),
+            wall_ms,
+        }
+    }
+}
+
+fn latency_stats(mut samples: Vec<u64>) -> LatencyStats {
+    if samples.is_empty() {
+        return LatencyStats::default();
+    }
+    samples.sort_unstable();
+    let percentile = |pct: usize| samples[(samples.len() - 1) * pct / 100];
+    LatencyStats {
+        min_us: samples[0],
+        mean_us: samples.iter().sum::<u64>() / samples.len() as u64,
+        p50_us: percentile(50),
+        p95_us: percentile(95),
+        p99_us: percentile(99),
+        max_us: percentile(100),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latency_percentiles_use_sorted_samples() {
+        let stats = latency_stats((1..=100).rev().collect());
+        assert_eq!(stats.min_us, 1);
+        assert_eq!(stats.p50_us, 50);
+        assert_eq!(stats.p95_us, 95);
+        assert_eq!(stats.max_us, 100);
+        assert_eq!(latency_stats(Vec::new()).max_us, 0);
+    }
+}
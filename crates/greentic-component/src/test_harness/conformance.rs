@@ -0,0 +1,745 @@
+//! Host-import conformance suite.
+//!
+//! Encodes the state, secrets, HTTP and events semantics of this crate's test harness
+//! as a list of cases so other runtimes can check that their host
+//! implementations behave the same way. A host is driven through
+//! [`HostUnderTest`]; [`CommandHost`] adapts an external process speaking
+//! JSON lines (one [`HostCall`] per line on stdin, one [`HostReply`] per line
+//! on stdout) and [`ReferenceHost`] runs the harness's own implementation.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use greentic_component_runtime::{CompError, EventSink, EventsHost};
+use greentic_interfaces::runner_host_v1::RunnerHost;
+use greentic_interfaces_wasmtime::host_helpers::v1::secrets_store::{
+    SecretsError, SecretsStoreHost,
+};
+use greentic_interfaces_wasmtime::host_helpers::v1::state_store::StateStoreHost;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::test_harness::AuditLog;
+use crate::test_harness::events::EventRecorder;
+use crate::test_harness::linker::{RunnerHostImpl, SecretsStoreHostImpl, StateStoreHostImpl};
+use crate::test_harness::secrets::InMemorySecretsStore;
+use crate::test_harness::state::{InMemoryStateStore, StateScope};
+
+/// Capabilities granted to the host for one case. Sent with
+/// [`HostCall::Reset`], which must also drop all stored state.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostGrants {
+    #[serde(default)]
+    pub state_read: bool,
+    #[serde(default)]
+    pub state_write: bool,
+    #[serde(default)]
+    pub state_delete: bool,
+    #[serde(default)]
+    pub secrets: bool,
+    /// Secret keys the component declared; other keys are rejected.
+    #[serde(default)]
+    pub allowed_secrets: Vec<String>,
+    /// Secret values the host has available.
+    #[serde(default)]
+    pub secret_values: BTreeMap<String, String>,
+    #[serde(default)]
+    pub http: bool,
+    /// `events.outbound`: the component may emit events.
+    #[serde(default)]
+    pub events: bool,
+}
+
+/// One host-import call, as seen by the host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "call", rename_all = "snake_case")]
+pub enum HostCall {
+    Reset {
+        grants: HostGrants,
+    },
+    StateRead {
+        tenant: String,
+        key: String,
+    },
+    StateWrite {
+        tenant: String,
+        key: String,
+        value_base64: String,
+    },
+    StateDelete {
+        tenant: String,
+        key: String,
+    },
+    SecretGet {
+        key: String,
+    },
+    HttpRequest {
+        method: String,
+        url: String,
+    },
+    EmitEvent {
+        topic: String,
+        payload: String,
+    },
+    /// Asks for every event accepted since the last reset, as a JSON array of
+    /// `{"topic", "payload"}` objects in emission order.
+    EventsEmitted,
+}
+
+impl HostCall {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HostCall::Reset { .. } => "reset",
+            HostCall::StateRead { .. } => "state_read",
+            HostCall::StateWrite { .. } => "state_write",
+            HostCall::StateDelete { .. } => "state_delete",
+            HostCall::SecretGet { .. } => "secret_get",
+            HostCall::HttpRequest { .. } => "http_request",
+            HostCall::EmitEvent { .. } => "emit_event",
+            HostCall::EventsEmitted => "events_emitted",
+        }
+    }
+}
+
+/// The host's answer to a [`HostCall`]. Failed calls carry a stable error
+/// code such as `state.read.miss` or `secrets.denied`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostReply {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_base64: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl HostReply {
+    pub fn ok() -> Self {
+        Self {
+            ok: true,
+            value_base64: None,
+            code: None,
+        }
+    }
+
+    pub fn value(bytes: &[u8]) -> Self {
+        Self {
+            ok: true,
+            value_base64: Some(BASE64_STANDARD.encode(bytes)),
+            code: None,
+        }
+    }
+
+    pub fn error(code: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            value_base64: None,
+            code: Some(code.into()),
+        }
+    }
+}
+
+/// A host implementation the suite can drive.
+pub trait HostUnderTest {
+    /// Performs `call`. Errors are transport failures, not host-level
+    /// denials, and abort the run.
+    fn call(&mut self, call: &HostCall) -> Result<HostReply>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expectation {
+    /// The call succeeds; any returned value is ignored.
+    Ok,
+    /// The call succeeds and returns exactly these bytes.
+    Value(Vec<u8>),
+    /// The call succeeds and returns JSON equal to this value; key order and
+    /// whitespace do not matter.
+    Json(Value),
+    /// The call fails with this error code.
+    Error(&'static str),
+}
+
+#[derive(Debug, Clone)]
+pub struct ConformanceCase {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub grants: HostGrants,
+    pub steps: Vec<(HostCall, Expectation)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConformanceReport {
+    pub cases: Vec<CaseResult>,
+}
+
+impl ConformanceReport {
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(|case| case.passed)
+    }
+
+    pub fn failed(&self) -> usize {
+        self.cases.iter().filter(|case| !case.passed).count()
+    }
+}
+
+fn read(tenant: &str, key: &str) -> HostCall {
+    HostCall::StateRead {
+        tenant: tenant.into(),
+        key: key.into(),
+    }
+}
+
+fn write(tenant: &str, key: &str, value: &[u8]) -> HostCall {
+    HostCall::StateWrite {
+        tenant: tenant.into(),
+        key: key.into(),
+        value_base64: BASE64_STANDARD.encode(value),
+    }
+}
+
+fn delete(tenant: &str, key: &str) -> HostCall {
+    HostCall::StateDelete {
+        tenant: tenant.into(),
+        key: key.into(),
+    }
+}
+
+fn secret(key: &str) -> HostCall {
+    HostCall::SecretGet { key: key.into() }
+}
+
+fn emit(topic: &str, payload: &str) -> HostCall {
+    HostCall::EmitEvent {
+        topic: topic.into(),
+        payload: payload.into(),
+    }
+}
+
+/// Every case in the suite, in the order they run.
+pub fn cases() -> Vec<ConformanceCase> {
+    let state_all = HostGrants {
+        state_read: true,
+        state_write: true,
+        state_delete: true,
+        ..HostGrants::default()
+    };
+    let api_key_only = HostGrants {
+        secrets: true,
+        allowed_secrets: vec!["API_KEY".into()],
+        ..HostGrants::default()
+    };
+    vec![
+        ConformanceCase {
+            id: "state-roundtrip",
+            description: "written state reads back and is gone after delete",
+            grants: state_all.clone(),
+            steps: vec![
+                (write("acme", "greeting", b"hello"), Expectation::Ok),
+                (
+                    read("acme", "greeting"),
+                    Expectation::Value(b"hello".to_vec()),
+                ),
+                (delete("acme", "greeting"), Expectation::Ok),
+                (
+                    read("acme", "greeting"),
+                    Expectation::Error("state.read.miss"),
+                ),
+            ],
+        },
+        ConformanceCase {
+            id: "state-overwrite",
+            description: "a second write replaces the stored value",
+            grants: state_all.clone(),
+            steps: vec![
+                (write("acme", "counter", b"1"), Expectation::Ok),
+                (write("acme", "counter", b"2"), Expectation::Ok),
+                (read("acme", "counter"), Expectation::Value(b"2".to_vec())),
+            ],
+        },
+        ConformanceCase {
+            id: "state-delete-missing",
+            description: "deleting a key that was never written succeeds",
+            grants: state_all.clone(),
+            steps: vec![(delete("acme", "never-written"), Expectation::Ok)],
+        },
+        ConformanceCase {
+            id: "state-tenant-isolation",
+            description: "state written for one tenant is invisible to another",
+            grants: state_all,
+            steps: vec![
+                (write("acme", "shared", b"a"), Expectation::Ok),
+                (
+                    read("globex", "shared"),
+                    Expectation::Error("state.read.miss"),
+                ),
+                (write("globex", "shared", b"b"), Expectation::Ok),
+                (read("acme", "shared"), Expectation::Value(b"a".to_vec())),
+            ],
+        },
+        ConformanceCase {
+            id: "state-denied-without-capability",
+            description: "state calls are denied when no state capability is granted",
+            grants: HostGrants::default(),
+            steps: vec![
+                (read("acme", "key"), Expectation::Error("state.read.denied")),
+                (
+                    write("acme", "key", b"value"),
+                    Expectation::Error("state.write.denied"),
+                ),
+                (
+                    delete("acme", "key"),
+                    Expectation::Error("state.delete.denied"),
+                ),
+            ],
+        },
+        ConformanceCase {
+            id: "state-delete-needs-its-own-grant",
+            description: "read and write access do not imply delete",
+            grants: HostGrants {
+                state_read: true,
+                state_write: true,
+                ..HostGrants::default()
+            },
+            steps: vec![
+                (write("acme", "key", b"value"), Expectation::Ok),
+                (
+                    delete("acme", "key"),
+                    Expectation::Error("state.delete.denied"),
+                ),
+                (read("acme", "key"), Expectation::Value(b"value".to_vec())),
+            ],
+        },
+        ConformanceCase {
+            id: "secrets-denied-without-capability",
+            description: "secret reads are denied when secrets are not granted",
+            grants: HostGrants {
+                secret_values: BTreeMap::from([("API_KEY".into(), "s3cr3t".into())]),
+                allowed_secrets: vec!["API_KEY".into()],
+                ..HostGrants::default()
+            },
+            steps: vec![(secret("API_KEY"), Expectation::Error("secrets.denied"))],
+        },
+        ConformanceCase {
+            id: "secrets-undeclared-key",
+            description: "only declared secret keys can be read",
+            grants: HostGrants {
+                secret_values: BTreeMap::from([
+                    ("API_KEY".into(), "s3cr3t".into()),
+                    ("OTHER".into(), "other".into()),
+                ]),
+                ..api_key_only.clone()
+            },
+            steps: vec![
+                (secret("OTHER"), Expectation::Error("secrets.invalid_key")),
+                (secret("API_KEY"), Expectation::Value(b"s3cr3t".to_vec())),
+            ],
+        },
+        ConformanceCase {
+            id: "secrets-missing-value",
+            description: "a declared secret without a value reports not found",
+            grants: api_key_only,
+            steps: vec![(secret("API_KEY"), Expectation::Error("secrets.not_found"))],
+        },
+        ConformanceCase {
+            id: "http-denied-without-capability",
+            description: "outbound HTTP is denied when it is not granted",
+            grants: HostGrants::default(),
+            steps: vec![(
+                HostCall::HttpRequest {
+                    method: "GET".into(),
+                    url: "http://127.0.0.1:9/".into(),
+                },
+                Expectation::Error("http.denied"),
+            )],
+        },
+        ConformanceCase {
+            id: "events-recorded-in-order",
+            description: "emitted events are recorded with their topic and parsed payload",
+            grants: HostGrants {
+                events: true,
+                ..HostGrants::default()
+            },
+            steps: vec![
+                (emit("orders.created", r#"{"id": 7}"#), Expectation::Ok),
+                (
+                    emit("orders.shipped", r#"{"id": 7, "carrier": "ups"}"#),
+                    Expectation::Ok,
+                ),
+                (
+                    HostCall::EventsEmitted,
+                    Expectation::Json(json!([
+                        {"topic": "orders.created", "payload": {"id": 7}},
+                        {"topic": "orders.shipped", "payload": {"carrier": "ups", "id": 7}},
+                    ])),
+                ),
+            ],
+        },
+        ConformanceCase {
+            id: "events-invalid-payload",
+            description: "payloads that are not JSON and empty topics are rejected",
+            grants: HostGrants {
+                events: true,
+                ..HostGrants::default()
+            },
+            steps: vec![
+                (
+                    emit("orders.created", "id=7"),
+                    Expectation::Error("events.invalid"),
+                ),
+                (emit("", "{}"), Expectation::Error("events.invalid")),
+                (HostCall::EventsEmitted, Expectation::Json(json!([]))),
+            ],
+        },
+        ConformanceCase {
+            id: "events-denied-without-capability",
+            description: "emitting is denied when events.outbound is not granted",
+            grants: HostGrants::default(),
+            steps: vec![
+                (
+                    emit("orders.created", "{}"),
+                    Expectation::Error("events.denied"),
+                ),
+                (HostCall::EventsEmitted, Expectation::Json(json!([]))),
+            ],
+        },
+    ]
+}
+
+/// Runs the suite (or only the case named `only`) against `host`.
+pub fn run_conformance(
+    host: &mut dyn HostUnderTest,
+    only: Option<&str>,
+) -> Result<ConformanceReport> {
+    let selected = cases()
+        .into_iter()
+        .filter(|case| only.is_none_or(|id| case.id == id))
+        .collect::<Vec<_>>();
+    if let Some(id) = only
+        && selected.is_empty()
+    {
+        bail!("unknown conformance case `{id}`");
+    }
+
+    let mut report = ConformanceReport::default();
+    for case in selected {
+        let mut failures = Vec::new();
+        let reset = HostCall::Reset {
+            grants: case.grants.clone(),
+        };
+        let reply = host
+            .call(&reset)
+            .with_context(|| format!("{}: reset failed", case.id))?;
+        if !reply.ok {
+            failures.push(format!("reset was rejected: {}", describe_reply(&reply)));
+        } else {
+            for (index, (call, expected)) in case.steps.iter().enumerate() {
+                let reply = host
+                    .call(call)
+                    .with_context(|| format!("{}: step {} failed", case.id, index + 1))?;
+                if let Some(mismatch) = check_reply(expected, &reply) {
+                    failures.push(format!("step {} ({}): {mismatch}", index + 1, call.name()));
+                }
+            }
+        }
+        report.cases.push(CaseResult {
+            id: case.id,
+            description: case.description,
+            passed: failures.is_empty(),
+            failures,
+        });
+    }
+    Ok(report)
+}
+
+fn check_reply(expected: &Expectation, reply: &HostReply) -> Option<String> {
+    let matches = match expected {
+        Expectation::Ok => reply.ok,
+        Expectation::Value(bytes) => {
+            reply.ok
+                && reply
+                    .value_base64
+                    .as_deref()
+                    .and_then(|value| BASE64_STANDARD.decode(value).ok())
+                    .is_some_and(|value| value == *bytes)
+        }
+        Expectation::Json(expected) => {
+            reply.ok
+                && reply
+                    .value_base64
+                    .as_deref()
+                    .and_then(|value| BASE64_STANDARD.decode(value).ok())
+                    .and_then(|value| serde_json::from_slice::<Value>(&value).ok())
+                    .is_some_and(|value| value == *expected)
+        }
+        Expectation::Error(code) => !reply.ok && reply.code.as_deref() == Some(*code),
+    };
+    if matches {
+        return None;
+    }
+    let wanted = match expected {
+        Expectation::Ok => "success".to_string(),
+        Expectation::Value(bytes) => format!("value `{}`", String::from_utf8_lossy(bytes)),
+        Expectation::Json(value) => format!("value `{value}`"),
+        Expectation::Error(code) => format!("error `{code}`"),
+    };
+    Some(format!("expected {wanted}, got {}", describe_reply(reply)))
+}
+
+fn describe_reply(reply: &HostReply) -> String {
+    match (reply.ok, &reply.value_base64, &reply.code) {
+        (true, Some(value), _) => {
+            let bytes = BASE64_STANDARD.decode(value).unwrap_or_default();
+            format!("value `{}`", String::from_utf8_lossy(&bytes))
+        }
+        (true, None, _) => "success".to_string(),
+        (false, _, Some(code)) => format!("error `{code}`"),
+        (false, _, None) => "error without a code".to_string(),
+    }
+}
+
+/// The test harness's own host implementation, used as the reference.
+pub struct ReferenceHost {
+    grants: HostGrants,
+    store: Arc<InMemoryStateStore>,
+    tenants: HashMap<String, StateStoreHostImpl>,
+    secrets: SecretsStoreHostImpl,
+    runner: RunnerHostImpl,
+    recorder: Arc<EventRecorder>,
+    events: EventsHost,
+}
+
+impl Default for ReferenceHost {
+    fn default() -> Self {
+        Self::new(HostGrants::default())
+    }
+}
+
+impl ReferenceHost {
+    fn new(grants: HostGrants) -> Self {
+        let allowed = grants
+            .allowed_secrets
+            .iter()
+            .cloned()
+            .collect::<HashSet<_>>();
+        let values = grants
+            .secret_values
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        let secrets = InMemorySecretsStore::new(grants.secrets, allowed).with_secrets(values);
+        let recorder = Arc::new(EventRecorder::new());
+        let sink = grants
+            .events
+            .then(|| recorder.clone() as Arc<dyn EventSink>);
+        Self {
+            secrets: SecretsStoreHostImpl::new(Arc::new(secrets), AuditLog::default()),
+            runner: RunnerHostImpl::new(grants.http, None, AuditLog::default()),
+            store: Arc::new(InMemoryStateStore::new()),
+            tenants: HashMap::new(),
+            events: EventsHost::new("conformance", sink),
+            recorder,
+            grants,
+        }
+    }
+
+    fn state(&mut self, tenant: &str) -> &mut StateStoreHostImpl {
+        let grants = &self.grants;
+        let store = &self.store;
+        self.tenants.entry(tenant.to_string()).or_insert_with(|| {
+            let scope = StateScope {
+                env: "dev".into(),
+                tenant: tenant.to_string(),
+                team: None,
+                user: None,
+                prefix: "conformance".into(),
+            };
+            StateStoreHostImpl::new(
+                scope,
+                store.clone(),
+                grants.state_read,
+                grants.state_write,
+                grants.state_delete,
+                AuditLog::default(),
+            )
+        })
+    }
+}
+
+impl HostUnderTest for ReferenceHost {
+    fn call(&mut self, call: &HostCall) -> Result<HostReply> {
+        let reply = match call {
+            HostCall::Reset { grants } => {
+                *self = Self::new(grants.clone());
+                HostReply::ok()
+            }
+            HostCall::StateRead { tenant, key } => {
+                match self.state(tenant).read(key.clone(), None) {
+                    Ok(bytes) => HostReply::value(&bytes),
+                    Err(err) => HostReply::error(err.code),
+                }
+            }
+            HostCall::StateWrite {
+                tenant,
+                key,
+                value_base64,
+            } => {
+                let bytes = BASE64_STANDARD
+                    .decode(value_base64)
+                    .context("state_write value must be base64")?;
+                match self.state(tenant).write(key.clone(), bytes, None) {
+                    Ok(_) => HostReply::ok(),
+                    Err(err) => HostReply::error(err.code),
+                }
+            }
+            HostCall::StateDelete { tenant, key } => {
+                match self.state(tenant).delete(key.clone(), None) {
+                    Ok(_) => HostReply::ok(),
+                    Err(err) => HostReply::error(err.code),
+                }
+            }
+            HostCall::SecretGet { key } => match self.secrets.get(key.clone()) {
+                Ok(Some(bytes)) => HostReply::value(&bytes),
+                Ok(None) | Err(SecretsError::NotFound) => HostReply::error("secrets.not_found"),
+                Err(SecretsError::Denied) => HostReply::error("secrets.denied"),
+                Err(SecretsError::InvalidKey) => HostReply::error("secrets.invalid_key"),
+                Err(_) => HostReply::error("secrets.error"),
+            },
+            HostCall::HttpRequest { method, url } => {
+                match self
+                    .runner
+                    .http_request(method.clone(), url.clone(), Vec::new(), None)?
+                {
+                    Ok(bytes) => HostReply::value(&bytes),
+                    Err(_) if !self.grants.http => HostReply::error("http.denied"),
+                    Err(_) => HostReply::error("http.error"),
+                }
+            }
+            HostCall::EmitEvent { topic, payload } => match self.events.emit(topic, payload) {
+                Ok(()) => HostReply::ok(),
+                Err(CompError::HostFeatureDenied(_)) => HostReply::error("events.denied"),
+                Err(_) => HostReply::error("events.invalid"),
+            },
+            HostCall::EventsEmitted => {
+                HostReply::value(serde_json::to_string(&self.recorder.events())?.as_bytes())
+            }
+        };
+        Ok(reply)
+    }
+}
+
+/// Drives an external host adapter over stdin/stdout JSON lines.
+pub struct CommandHost {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl CommandHost {
+    pub fn spawn(program: &str, args: &[String]) -> Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| format!("failed to start host command `{program}`"))?;
+        let stdin = child.stdin.take().context("host command stdin")?;
+        let stdout = BufReader::new(child.stdout.take().context("host command stdout")?);
+        Ok(Self {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+}
+
+impl HostUnderTest for CommandHost {
+    fn call(&mut self, call: &HostCall) -> Result<HostReply> {
+        let line = serde_json::to_string(call)?;
+        writeln!(self.stdin, "{line}").context("write to host command")?;
+        self.stdin.flush().context("flush host command stdin")?;
+        let mut reply = String::new();
+        if self
+            .stdout
+            .read_line(&mut reply)
+            .context("read from host command")?
+            == 0
+        {
+            bail!("host command exited before answering `{}`", call.name());
+        }
+        serde_json::from_str(reply.trim())
+            .with_context(|| format!("host command sent an invalid reply: {}", reply.trim()))
+    }
+}
+
+impl Drop for CommandHost {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mimics a host that forgets to enforce the delete grant.
+    struct LenientDeleteHost(ReferenceHost);
+
+    impl HostUnderTest for LenientDeleteHost {
+        fn call(&mut self, call: &HostCall) -> Result<HostReply> {
+            match self.0.call(call)? {
+                reply if reply.code.as_deref() == Some("state.delete.denied") => {
+                    Ok(HostReply::ok())
+                }
+                reply => Ok(reply),
+            }
+        }
+    }
+
+    #[test]
+    fn reference_host_passes_and_lenient_host_fails() {
+        let report = run_conformance(&mut ReferenceHost::default(), None).expect("run");
+        let failed = report
+            .cases
+            .iter()
+            .filter(|case| !case.passed)
+            .map(|case| (case.id, case.failures.clone()))
+            .collect::<Vec<_>>();
+        assert!(report.passed(), "{failed:?}");
+        assert_eq!(report.cases.len(), cases().len());
+
+        let report =
+            run_conformance(&mut LenientDeleteHost(ReferenceHost::default()), None).expect("run");
+        let failed = report
+            .cases
+            .iter()
+            .filter(|case| !case.passed)
+            .map(|case| case.id)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            failed,
+            vec![
+                "state-denied-without-capability",
+                "state-delete-needs-its-own-grant"
+            ]
+        );
+
+        let call = serde_json::to_value(secret("API_KEY")).expect("call json");
+        assert_eq!(call["call"], "secret_get");
+        assert!(run_conformance(&mut ReferenceHost::default(), Some("nope")).is_err());
+    }
+}
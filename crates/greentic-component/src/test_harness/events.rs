@@ -0,0 +1,42 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use greentic_component_runtime::{CompError, EmittedEvent, EventSink};
+
+/// Keeps every emitted event in memory so a run can be inspected or written
+/// out as an `events.json` transcript afterwards.
+#[derive(Debug, Default)]
+pub struct EventRecorder {
+    events: Mutex<Vec<EmittedEvent>>,
+}
+
+impl EventRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> Vec<EmittedEvent> {
+        self.events
+            .lock()
+            .expect("event recorder mutex poisoned")
+            .clone()
+    }
+
+    /// Writes the recorded events as a pretty-printed JSON array.
+    pub fn write_transcript(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.events())?;
+        fs::write(path, json).with_context(|| format!("write events to {}", path.display()))
+    }
+}
+
+impl EventSink for EventRecorder {
+    fn emit(&self, _scope: &str, event: &EmittedEvent) -> Result<(), CompError> {
+        self.events
+            .lock()
+            .expect("event recorder mutex poisoned")
+            .push(event.clone());
+        Ok(())
+    }
+}
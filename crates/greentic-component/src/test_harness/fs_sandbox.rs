@@ -0,0 +1,330 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use tempfile::TempDir;
+use walkdir::WalkDir;
+
+use crate::test_harness::WasiPreopen;
+
+/// Host directories backing a component's `wasi.filesystem` mounts for one
+/// test run.
+///
+/// Each mount gets its own empty temp directory unless the caller maps it to
+/// an existing host directory. Files present when the mount was added form
+/// the baseline that [`FsSandbox::changes`] and the byte quota are measured
+/// against.
+#[derive(Debug)]
+pub struct FsSandbox {
+    mounts: Vec<SandboxMount>,
+    quota_bytes: Option<u64>,
+}
+
+#[derive(Debug)]
+struct SandboxMount {
+    name: String,
+    guest_path: String,
+    host_path: PathBuf,
+    read_only: bool,
+    baseline: BTreeMap<PathBuf, FileStamp>,
+    _temp: Option<TempDir>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// A file the component created, modified or removed under a mount.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FsChange {
+    pub mount: String,
+    /// Path as the component sees it.
+    pub path: String,
+    pub change: FsChangeKind,
+    /// Size after the run (0 for deleted files).
+    pub bytes: u64,
+}
+
+/// Writable mounts grew past the sandbox quota.
+#[derive(Debug, Clone, Copy)]
+pub struct FsQuotaExceeded {
+    pub quota_bytes: u64,
+    pub used_bytes: u64,
+}
+
+impl fmt::Display for FsQuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "filesystem quota exceeded ({} bytes written, limit {} bytes)",
+            self.used_bytes, self.quota_bytes
+        )
+    }
+}
+
+impl std::error::Error for FsQuotaExceeded {}
+
+/// The quota over a sandbox's writable mounts, shared with the harness so it
+/// can be checked after every host call. WASI writes are host calls, so a
+/// write that crosses the quota fails the invocation that made it.
+#[derive(Debug, Clone)]
+pub struct FsQuota {
+    quota_bytes: u64,
+    /// Host directory and its size when it was added to the sandbox.
+    mounts: Arc<Vec<(PathBuf, u64)>>,
+}
+
+impl FsQuota {
+    pub fn quota_bytes(&self) -> u64 {
+        self.quota_bytes
+    }
+
+    /// Bytes added to the writable mounts since they were added.
+    pub fn used_bytes(&self) -> Result<u64> {
+        let mut used = 0u64;
+        for (host_path, before) in self.mounts.iter() {
+            let after: u64 = snapshot(host_path)?.values().map(|stamp| stamp.len).sum();
+            used = used.saturating_add(after.saturating_sub(*before));
+        }
+        Ok(used)
+    }
+
+    /// Fails with [`FsQuotaExceeded`] once the mounts grew past the quota.
+    pub fn check(&self) -> Result<()> {
+        let used_bytes = self.used_bytes()?;
+        if used_bytes > self.quota_bytes {
+            return Err(anyhow::Error::new(FsQuotaExceeded {
+                quota_bytes: self.quota_bytes,
+                used_bytes,
+            }));
+        }
+        Ok(())
+    }
+}
+
+impl FsSandbox {
+    pub fn new(quota_bytes: Option<u64>) -> Self {
+        Self {
+            mounts: Vec::new(),
+            quota_bytes,
+        }
+    }
+
+    /// Backs `name` with a fresh, empty temp directory.
+    pub fn add_temp_mount(&mut self, name: &str, guest_path: &str, read_only: bool) -> Result<()> {
+        let temp = tempfile::Builder::new()
+            .prefix(&format!("greentic-mount-{name}-"))
+            .tempdir()
+            .with_context(|| format!("create sandbox for mount `{name}`"))?;
+        let host_path = temp.path().to_path_buf();
+        self.push(name, guest_path, host_path, read_only, Some(temp))
+    }
+
+    /// Backs `name` with an existing host directory.
+    pub fn add_host_mount(
+        &mut self,
+        name: &str,
+        guest_path: &str,
+        host_path: &Path,
+        read_only: bool,
+    ) -> Result<()> {
+        let meta = std::fs::metadata(host_path)
+            .with_context(|| format!("failed to stat mount {}", host_path.display()))?;
+        if !meta.is_dir() {
+            bail!("mount {} must be a directory", host_path.display());
+        }
+        self.push(name, guest_path, host_path.to_path_buf(), read_only, None)
+    }
+
+    fn push(
+        &mut self,
+        name: &str,
+        guest_path: &str,
+        host_path: PathBuf,
+        read_only: bool,
+        temp: Option<TempDir>,
+    ) -> Result<()> {
+        let baseline = snapshot(&host_path)?;
+        self.mounts.push(SandboxMount {
+            name: name.to_string(),
+            guest_path: guest_path.to_string(),
+            host_path,
+            read_only,
+            baseline,
+            _temp: temp,
+        });
+        Ok(())
+    }
+
+    pub fn preopens(&self) -> Vec<WasiPreopen> {
+        self.mounts
+            .iter()
+            .map(|mount| {
+                WasiPreopen::new(&mount.host_path, mount.guest_path.clone())
+                    .read_only(mount.read_only)
+            })
+            .collect()
+    }
+
+    pub fn quota_bytes(&self) -> Option<u64> {
+        self.quota_bytes
+    }
+
+    /// The quota over the writable mounts, for
+    /// [`HarnessConfig::fs_quota`](crate::test_harness::HarnessConfig::fs_quota).
+    pub fn quota(&self) -> Option<FsQuota> {
+        let quota_bytes = self.quota_bytes?;
+        let mounts = self
+            .mounts
+            .iter()
+            .filter(|mount| !mount.read_only)
+            .map(|mount| {
+                let before = mount.baseline.values().map(|stamp| stamp.len).sum();
+                (mount.host_path.clone(), before)
+            })
+            .collect();
+        Some(FsQuota {
+            quota_bytes,
+            mounts: Arc::new(mounts),
+        })
+    }
+
+    /// Fails once writable mounts have grown past the quota.
+    pub fn check_quota(&self) -> Result<()> {
+        match self.quota() {
+            Some(quota) => quota.check(),
+            None => Ok(()),
+        }
+    }
+
+    /// Files created, modified or deleted under each mount, sorted by mount
+    /// then path.
+    pub fn changes(&self) -> Result<Vec<FsChange>> {
+        let mut changes = Vec::new();
+        for mount in &self.mounts {
+            let current = snapshot(&mount.host_path)?;
+            for (rel, stamp) in &current {
+                let change = match mount.baseline.get(rel) {
+                    None => FsChangeKind::Created,
+                    Some(before) if before != stamp => FsChangeKind::Modified,
+                    Some(_) => continue,
+                };
+                changes.push(mount.change(rel, change, stamp.len));
+            }
+            for rel in mount.baseline.keys() {
+                if !current.contains_key(rel) {
+                    changes.push(mount.change(rel, FsChangeKind::Deleted, 0));
+                }
+            }
+        }
+        changes.sort_by(|a, b| a.mount.cmp(&b.mount).then_with(|| a.path.cmp(&b.path)));
+        Ok(changes)
+    }
+}
+
+impl SandboxMount {
+    fn change(&self, rel: &Path, change: FsChangeKind, bytes: u64) -> FsChange {
+        let rel = rel
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        FsChange {
+            mount: self.name.clone(),
+            path: format!("{}/{rel}", self.guest_path.trim_end_matches('/')),
+            change,
+            bytes,
+        }
+    }
+}
+
+fn snapshot(root: &Path) -> Result<BTreeMap<PathBuf, FileStamp>> {
+    let mut files = BTreeMap::new();
+    for entry in WalkDir::new(root).follow_links(false) {
+        let entry = entry.with_context(|| format!("scan mount {}", root.display()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let meta = entry
+            .metadata()
+            .with_context(|| format!("stat {}", entry.path().display()))?;
+        let rel = entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .to_path_buf();
+        files.insert(
+            rel,
+            FileStamp {
+                len: meta.len(),
+                modified: meta.modified().ok(),
+            },
+        );
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_changes_and_quota_per_mount() {
+        let host = TempDir::new().expect("tempdir");
+        std::fs::write(host.path().join("keep.txt"), b"keep").unwrap();
+        std::fs::write(host.path().join("gone.txt"), b"gone").unwrap();
+
+        let mut sandbox = FsSandbox::new(Some(8));
+        sandbox
+            .add_host_mount("data", "/data", host.path(), false)
+            .unwrap();
+        sandbox
+            .add_temp_mount("scratch", "/scratch/", false)
+            .unwrap();
+        let scratch = sandbox.preopens()[1].host_path.clone();
+
+        std::fs::remove_file(host.path().join("gone.txt")).unwrap();
+        std::fs::create_dir(scratch.join("out")).unwrap();
+        std::fs::write(scratch.join("out/report.json"), b"{}").unwrap();
+        sandbox.check_quota().expect("2 bytes is within quota");
+
+        let changes = sandbox.changes().unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                FsChange {
+                    mount: "data".into(),
+                    path: "/data/gone.txt".into(),
+                    change: FsChangeKind::Deleted,
+                    bytes: 0,
+                },
+                FsChange {
+                    mount: "scratch".into(),
+                    path: "/scratch/out/report.json".into(),
+                    change: FsChangeKind::Created,
+                    bytes: 2,
+                },
+            ]
+        );
+
+        std::fs::write(scratch.join("big.bin"), [0u8; 16]).unwrap();
+        let err = sandbox.check_quota().unwrap_err();
+        let quota = err.downcast_ref::<FsQuotaExceeded>().expect("quota error");
+        assert_eq!(quota.used_bytes, 18);
+        assert_eq!(sandbox.quota().expect("quota").used_bytes().unwrap(), 18);
+    }
+}
@@ -0,0 +1,83 @@
+use clap::ValueEnum;
+use wasmtime::{Config, InstanceAllocationStrategy, PoolingAllocationConfig};
+
+/// Engine settings and host defaults for a test run, mirroring the runtime's
+/// isolation profiles so a component can be exercised under the same knobs it
+/// will be deployed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum IsolationProfile {
+    /// Pooled instances, small stacks, canonical NaNs, no SIMD or threads;
+    /// HTTP, sockets and filesystem writes are denied whatever the flags say.
+    Strict,
+    /// Wasmtime defaults; host access follows the command-line flags.
+    #[default]
+    Balanced,
+    /// Large stacks, SIMD and threads; declared HTTP, socket and filesystem
+    /// access is granted without the `--allow-*` flags (still not in dry-run).
+    Trusted,
+}
+
+impl IsolationProfile {
+    pub(crate) fn configure(self, config: &mut Config) {
+        let (max_wasm_stack, simd, threads) = match self {
+            IsolationProfile::Strict => (256 * 1024, false, false),
+            IsolationProfile::Balanced => (512 * 1024, true, false),
+            IsolationProfile::Trusted => (2 * 1024 * 1024, true, true),
+        };
+        if self == IsolationProfile::Strict {
+            let mut pooling = PoolingAllocationConfig::default();
+            pooling.total_component_instances(16);
+            pooling.total_core_instances(128);
+            pooling.total_memories(32);
+            pooling.total_tables(64);
+            config.allocation_strategy(InstanceAllocationStrategy::Pooling(pooling));
+            config.cranelift_nan_canonicalization(true);
+            config.wasm_relaxed_simd(false);
+        }
+        config.max_wasm_stack(max_wasm_stack);
+        config.wasm_simd(simd);
+        config.wasm_threads(threads);
+    }
+
+    /// Resolves an `--allow-*` flag under this profile.
+    pub fn grants(self, requested: bool) -> bool {
+        match self {
+            IsolationProfile::Strict => false,
+            IsolationProfile::Balanced => requested,
+            IsolationProfile::Trusted => true,
+        }
+    }
+
+    /// Open WASI handle cap, tightened to 32 under `Strict`.
+    pub fn max_open_handles(self, declared: Option<usize>) -> Option<usize> {
+        match self {
+            IsolationProfile::Strict => Some(declared.map_or(32, |files| files.min(32))),
+            _ => declared,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_build_engines_and_resolve_flags() {
+        for profile in IsolationProfile::value_variants() {
+            let mut config = Config::new();
+            config.wasm_component_model(true);
+            config.epoch_interruption(true);
+            profile.configure(&mut config);
+            wasmtime::Engine::new(&config)
+                .unwrap_or_else(|err| panic!("{profile:?} engine: {err}"));
+        }
+        assert!(!IsolationProfile::Strict.grants(true));
+        assert!(IsolationProfile::Balanced.grants(true));
+        assert!(IsolationProfile::Trusted.grants(false));
+        assert_eq!(
+            IsolationProfile::Strict.max_open_handles(Some(64)),
+            Some(32)
+        );
+        assert_eq!(IsolationProfile::Balanced.max_open_handles(None), None);
+    }
+}
@@ -1,8 +1,16 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use anyhow::{Context, Result, anyhow};
+use greentic_component_runtime::{
+    AuditKind, AuditLog, AuditOutcome, BlobHost, EventsHost, GuestLogger, MessagingHost,
+    OpenHandles, ProgressReporter, SqlHost, TelemetryHost, ToolsHost, add_blob_to_linker,
+    add_events_to_linker, add_log_to_linker, add_messaging_to_linker, add_progress_to_linker,
+    add_sql_to_linker, add_telemetry_to_linker, add_tools_to_linker,
+};
 use greentic_interfaces::runner_host_v1::{self, RunnerHost};
 use greentic_interfaces_host::component::v0_5::{self, ControlHost};
 use greentic_interfaces_wasmtime::host_helpers::v1::secrets_store::{
@@ -13,27 +21,45 @@ use greentic_interfaces_wasmtime::host_helpers::v1::state_store::{
 };
 use reqwest::blocking::Client as HttpClient;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use wasmtime::StoreContextMut;
 use wasmtime::component::Linker;
 use wasmtime::{Engine, ResourceLimiter};
 use wasmtime_wasi::clocks::{HostMonotonicClock, HostWallClock};
 use wasmtime_wasi::random::Deterministic;
+use wasmtime_wasi::sockets::SocketAddrUse;
 use wasmtime_wasi::{
     DirPerms, FilePerms, ResourceTable, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView,
 };
 
-use crate::test_harness::WasiPreopen;
+use crate::capabilities::NetCaps;
+use crate::test_harness::chaos::{Chaos, ChaosImport};
+use crate::test_harness::fs_sandbox::{FsQuota, FsQuotaExceeded};
 use crate::test_harness::secrets::InMemorySecretsStore;
-use crate::test_harness::state::{InMemoryStateStore, StateScope};
+use crate::test_harness::state::{InMemoryStateStore, StateScope, StateTransaction};
+use crate::test_harness::{WasiPreopen, WasiRandom};
 
 pub struct HostState {
     control: ControlHostImpl,
     runner: RunnerHostImpl,
     state: StateStoreHostImpl,
     secrets: SecretsStoreHostImpl,
+    progress: ProgressReporter,
+    guest_log: GuestLogger,
+    blobs: BlobHost,
+    sql: SqlHost,
+    messaging: MessagingHost,
+    events: EventsHost,
+    /// The harness runs a single component, so tool calls always trap.
+    tools: ToolsHost,
+    telemetry: TelemetryHost,
     wasi_ctx: WasiCtx,
     wasi_table: ResourceTable,
     limits: HostLimits,
     memory_limit_hit: Arc<AtomicBool>,
+    handles: HandleLimits,
+    fs_quota: Option<FsQuota>,
+    fs_quota_hit: Option<FsQuotaExceeded>,
+    host_call_count: u64,
 }
 
 pub struct HostStateConfig {
@@ -44,19 +70,54 @@ pub struct HostStateConfig {
     pub allow_state_write: bool,
     pub allow_state_delete: bool,
     pub wasi_preopens: Vec<WasiPreopen>,
+    pub fs_quota: Option<FsQuota>,
+    pub env_vars: Vec<(String, String)>,
     pub allow_http: bool,
+    /// Hosts HTTP requests may reach; empty allows any host.
+    pub http_domains: Vec<String>,
     pub config_json: Option<String>,
     pub max_memory_bytes: usize,
+    pub max_open_handles: Option<usize>,
+    /// Socket access granted from `capabilities.net`; `None` keeps sockets
+    /// closed.
+    pub net: Option<NetCaps>,
+    pub audit: AuditLog,
+    pub progress: ProgressReporter,
+    pub guest_log: GuestLogger,
+    pub blobs: BlobHost,
+    pub sql: SqlHost,
+    pub messaging: MessagingHost,
+    pub events: EventsHost,
+    pub telemetry: TelemetryHost,
+    /// Fault injection for the state, secrets, and HTTP imports.
+    pub chaos: Option<Chaos>,
+    /// Buffer state changes until [`HostState::commit_state`].
+    pub transactional_state: bool,
+    pub random: WasiRandom,
 }
 
 impl HostState {
     pub fn new(config: HostStateConfig) -> Result<Self> {
         let mut wasi_builder = WasiCtxBuilder::new();
-        wasi_builder.secure_random(Deterministic::new(vec![0, 1, 2, 3]));
-        wasi_builder.insecure_random(Deterministic::new(vec![4, 5, 6, 7]));
-        wasi_builder.insecure_random_seed(0);
+        match config.random {
+            WasiRandom::Fixed => {
+                wasi_builder.secure_random(Deterministic::new(vec![0, 1, 2, 3]));
+                wasi_builder.insecure_random(Deterministic::new(vec![4, 5, 6, 7]));
+                wasi_builder.insecure_random_seed(0);
+            }
+            WasiRandom::Seed(seed) => {
+                wasi_builder.secure_random(Deterministic::new(seeded_bytes(seed, 0)));
+                wasi_builder.insecure_random(Deterministic::new(seeded_bytes(seed, 1)));
+                wasi_builder.insecure_random_seed(u128::from(seed));
+            }
+            // Leave wasmtime's OS-backed defaults in place.
+            WasiRandom::Real => {}
+        }
         wasi_builder.wall_clock(FixedWallClock::new());
         wasi_builder.monotonic_clock(FixedMonotonicClock::new());
+        for (key, value) in &config.env_vars {
+            wasi_builder.env(key, value);
+        }
         for preopen in &config.wasi_preopens {
             let (dir_perms, file_perms) = if preopen.read_only {
                 (DirPerms::READ, FilePerms::READ)
@@ -79,34 +140,191 @@ impl HostState {
                 })?;
         }
 
+        configure_sockets(&mut wasi_builder, config.net.as_ref(), &config.audit);
+
         let memory_limit_hit = Arc::new(AtomicBool::new(false));
         let limits = HostLimits::new(config.max_memory_bytes, memory_limit_hit.clone());
 
         Ok(Self {
             control: ControlHostImpl,
-            runner: RunnerHostImpl::new(config.allow_http, config.config_json),
+            runner: RunnerHostImpl::new(
+                config.allow_http,
+                config.config_json,
+                config.audit.clone(),
+            )
+            .with_chaos(config.chaos.clone())
+            .with_domains(config.http_domains),
             state: StateStoreHostImpl::new(
                 config.base_scope,
                 config.state_store,
                 config.allow_state_read,
                 config.allow_state_write,
                 config.allow_state_delete,
-            ),
-            secrets: SecretsStoreHostImpl::new(config.secrets),
+                config.audit.clone(),
+            )
+            .with_chaos(config.chaos.clone())
+            .transactional(config.transactional_state),
+            secrets: SecretsStoreHostImpl::new(config.secrets, config.audit.clone())
+                .with_chaos(config.chaos),
+            progress: config.progress,
+            guest_log: config.guest_log,
+            blobs: config.blobs,
+            sql: config.sql,
+            messaging: config.messaging,
+            events: config.events,
+            tools: ToolsHost::default(),
+            telemetry: config.telemetry,
             wasi_ctx: wasi_builder.build(),
             wasi_table: ResourceTable::new(),
             limits,
             memory_limit_hit,
+            handles: HandleLimits {
+                max_open: config.max_open_handles,
+                open: OpenHandles::default(),
+                audit: config.audit,
+                hit: false,
+            },
+            fs_quota: config.fs_quota,
+            fs_quota_hit: None,
+            host_call_count: 0,
         })
     }
 
+    /// Applies state changes buffered in transactional mode. Call only once
+    /// the invocation succeeded; dropping the state rolls them back.
+    pub fn commit_state(&mut self) {
+        self.state.commit();
+    }
+
     pub fn memory_limit_hit(&self) -> bool {
         self.memory_limit_hit.load(Ordering::Relaxed)
     }
 
+    /// Largest linear memory size granted so far.
+    pub fn peak_memory_bytes(&self) -> usize {
+        self.limits.peak
+    }
+
+    /// Table elements allocated so far, initial table sizes included.
+    pub fn table_growth(&self) -> u64 {
+        self.limits.table_growth
+    }
+
+    /// Counts a guest call into a host import; called from the store's
+    /// call hook.
+    pub fn count_host_call(&mut self) {
+        self.host_call_count += 1;
+    }
+
+    pub fn host_call_count(&self) -> u64 {
+        self.host_call_count
+    }
+
     pub fn limits_mut(&mut self) -> &mut dyn ResourceLimiter {
         &mut self.limits
     }
+
+    /// Records how many WASI handles (files, directories, streams) are live,
+    /// audits the ones opened or closed by the call, and fails once the
+    /// manifest's `limits.files` cap is exceeded. Called every time a host
+    /// call returns to the guest.
+    pub fn check_open_handles(&mut self) -> Result<()> {
+        let open = self.handles.open.update(&mut self.wasi_table);
+        self.handles.open.audit(&self.handles.audit);
+        if let Some(max_open) = self.handles.max_open
+            && open > max_open
+        {
+            self.handles.hit = true;
+            return Err(anyhow!(
+                "open handle limit exceeded ({open} open, max {max_open})"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fails once the writable preopens grew past the quota. Called every
+    /// time a host call returns, so the WASI write that crossed it fails.
+    pub fn check_fs_quota(&mut self) -> Result<()> {
+        let Some(quota) = &self.fs_quota else {
+            return Ok(());
+        };
+        let err = match quota.check() {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+        if let Some(exceeded) = err.downcast_ref::<FsQuotaExceeded>() {
+            self.fs_quota_hit = Some(*exceeded);
+        }
+        Err(err)
+    }
+
+    pub fn fs_quota_hit(&self) -> Option<FsQuotaExceeded> {
+        self.fs_quota_hit
+    }
+
+    pub fn open_handle_limit_hit(&self) -> bool {
+        self.handles.hit
+    }
+
+    pub fn max_open_handles(&self) -> Option<usize> {
+        self.handles.max_open
+    }
+
+    /// Highest number of simultaneously open WASI handles seen so far.
+    pub fn peak_open_handles(&self) -> usize {
+        self.handles.open.peak()
+    }
+}
+
+/// Opens WASI sockets according to `net`, allowing connects only to the
+/// addresses its hosts resolve to. Binds are limited to UDP, which needs a
+/// local socket before it can send.
+fn configure_sockets(builder: &mut WasiCtxBuilder, net: Option<&NetCaps>, audit: &AuditLog) {
+    let Some(net) = net.filter(|net| net.is_enabled()) else {
+        builder.allow_tcp(false);
+        builder.allow_udp(false);
+        return;
+    };
+    builder.allow_tcp(net.tcp);
+    builder.allow_udp(net.udp);
+    builder.allow_ip_name_lookup(true);
+    let allowed = Arc::new(net.resolve_hosts());
+    let audit = audit.clone();
+    builder.socket_addr_check(move |addr: SocketAddr, addr_use: SocketAddrUse| {
+        let permitted = match addr_use {
+            SocketAddrUse::TcpBind => false,
+            SocketAddrUse::UdpBind => true,
+            SocketAddrUse::TcpConnect
+            | SocketAddrUse::UdpConnect
+            | SocketAddrUse::UdpOutgoingDatagram => allowed.contains(&addr.ip()),
+        };
+        audit.record(
+            AuditKind::Net,
+            addr.to_string(),
+            if permitted {
+                AuditOutcome::Ok
+            } else {
+                AuditOutcome::Denied
+            },
+            Some(format!("{addr_use:?}")),
+        );
+        Box::pin(async move { permitted })
+    });
+}
+
+/// Bytes cycled by a [`Deterministic`] generator: 4 KiB of splitmix64 output
+/// for `seed`, with `stream` keeping the secure and insecure sources apart.
+fn seeded_bytes(seed: u64, stream: u64) -> Vec<u8> {
+    let mut state = seed ^ stream.wrapping_mul(0xD1B5_4A32_D192_ED03);
+    let mut bytes = Vec::with_capacity(4096);
+    while bytes.len() < 4096 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        bytes.extend_from_slice(&(z ^ (z >> 31)).to_le_bytes());
+    }
+    bytes
 }
 
 pub fn build_linker(engine: &Engine) -> Result<Linker<HostState>> {
@@ -114,7 +332,16 @@ pub fn build_linker(engine: &Engine) -> Result<Linker<HostState>> {
     runner_host_v1::add_to_linker(&mut linker, |state: &mut HostState| &mut state.runner)?;
     v0_5::add_control_to_linker(&mut linker, |state: &mut HostState| &mut state.control)?;
     add_state_store_to_linker(&mut linker, |state: &mut HostState| &mut state.state)?;
+    add_state_store_extensions(&mut linker)?;
     add_secrets_store_to_linker(&mut linker, |state: &mut HostState| &mut state.secrets)?;
+    add_progress_to_linker(&mut linker, |state: &mut HostState| &mut state.progress)?;
+    add_log_to_linker(&mut linker, |state: &mut HostState| &mut state.guest_log)?;
+    add_blob_to_linker(&mut linker, |state: &mut HostState| &mut state.blobs)?;
+    add_sql_to_linker(&mut linker, |state: &mut HostState| &mut state.sql)?;
+    add_messaging_to_linker(&mut linker, |state: &mut HostState| &mut state.messaging)?;
+    add_events_to_linker(&mut linker, |state: &mut HostState| &mut state.events)?;
+    add_tools_to_linker(&mut linker, |state: &mut HostState| &mut state.tools)?;
+    add_telemetry_to_linker(&mut linker, |state: &mut HostState| &mut state.telemetry)?;
     wasmtime_wasi::p2::add_to_linker_sync(&mut linker)?;
     Ok(linker)
 }
@@ -131,18 +358,41 @@ impl ControlHost for ControlHostImpl {
 
 pub struct RunnerHostImpl {
     allow_http: bool,
+    http_domains: Vec<String>,
     config_json: Option<String>,
     http_client: HttpClient,
+    audit: AuditLog,
+    chaos: Option<Chaos>,
 }
 
 impl RunnerHostImpl {
-    fn new(allow_http: bool, config_json: Option<String>) -> Self {
+    pub(super) fn new(allow_http: bool, config_json: Option<String>, audit: AuditLog) -> Self {
         Self {
             allow_http,
+            http_domains: Vec::new(),
             config_json,
             http_client: HttpClient::new(),
+            audit,
+            chaos: None,
         }
     }
+
+    pub(super) fn with_chaos(mut self, chaos: Option<Chaos>) -> Self {
+        self.chaos = chaos;
+        self
+    }
+
+    pub(super) fn with_domains(mut self, domains: Vec<String>) -> Self {
+        self.http_domains = domains;
+        self
+    }
+
+    fn domain_allowed(&self, url: &reqwest::Url) -> bool {
+        self.http_domains.is_empty()
+            || url
+                .host_str()
+                .is_some_and(|host| self.http_domains.iter().any(|domain| domain == host))
+    }
 }
 
 impl RunnerHost for RunnerHostImpl {
@@ -152,6 +402,51 @@ impl RunnerHost for RunnerHostImpl {
         url: String,
         headers: Vec<String>,
         body: Option<Vec<u8>>,
+    ) -> wasmtime::Result<Result<Vec<u8>, String>> {
+        let target = format!("{method} {url}");
+        let domain_denied = url
+            .parse::<reqwest::Url>()
+            .is_ok_and(|url| !self.domain_allowed(&url));
+        let result = match self.chaos.clone() {
+            Some(chaos) if self.allow_http => {
+                chaos.delay(ChaosImport::Http, &target);
+                if chaos.fail(ChaosImport::Http, &target) {
+                    Err("chaos: injected http failure".to_string())
+                } else {
+                    self.send_http(method, url, headers, body)?
+                        .map(|bytes| chaos.truncate(ChaosImport::Http, &target, bytes))
+                }
+            }
+            _ => self.send_http(method, url, headers, body)?,
+        };
+        let (outcome, detail) = match &result {
+            Ok(_) => (AuditOutcome::Ok, None),
+            Err(_) if !self.allow_http || domain_denied => (AuditOutcome::Denied, None),
+            Err(err) => (AuditOutcome::Error, Some(err.clone())),
+        };
+        self.audit.record(AuditKind::Http, target, outcome, detail);
+        Ok(result)
+    }
+
+    fn kv_get(&mut self, _ns: String, _key: String) -> wasmtime::Result<Option<String>> {
+        if _ns == "config" && _key == "json" {
+            return Ok(self.config_json.clone());
+        }
+        Ok(None)
+    }
+
+    fn kv_put(&mut self, _ns: String, _key: String, _val: String) -> wasmtime::Result<()> {
+        Ok(())
+    }
+}
+
+impl RunnerHostImpl {
+    fn send_http(
+        &mut self,
+        method: String,
+        url: String,
+        headers: Vec<String>,
+        body: Option<Vec<u8>>,
     ) -> wasmtime::Result<Result<Vec<u8>, String>> {
         if !self.allow_http {
             return Ok(Err(
@@ -167,6 +462,12 @@ impl RunnerHost for RunnerHostImpl {
             Ok(url) => url,
             Err(err) => return Ok(Err(format!("invalid http url: {err}"))),
         };
+        if !self.domain_allowed(&url) {
+            return Ok(Err(format!(
+                "http host `{}` is not in capabilities.host.http.domains",
+                url.host_str().unwrap_or_default()
+            )));
+        }
 
         let mut builder = self.http_client.request(method, url);
 
@@ -207,34 +508,37 @@ impl RunnerHost for RunnerHostImpl {
             Ok(Err(format!("http request failed with status {status}")))
         }
     }
-
-    fn kv_get(&mut self, _ns: String, _key: String) -> wasmtime::Result<Option<String>> {
-        if _ns == "config" && _key == "json" {
-            return Ok(self.config_json.clone());
-        }
-        Ok(None)
-    }
-
-    fn kv_put(&mut self, _ns: String, _key: String, _val: String) -> wasmtime::Result<()> {
-        Ok(())
-    }
 }
 
+const STATE_STORE_INTERFACE: &str = "greentic:state/store@1.0.0";
+
 pub struct StateStoreHostImpl {
     base_scope: StateScope,
     state_store: Arc<InMemoryStateStore>,
     allow_state_read: bool,
     allow_state_write: bool,
     allow_state_delete: bool,
+    audit: AuditLog,
+    chaos: Option<Chaos>,
+    /// A write acked but held back by chaos `reorder_writes`; it lands after
+    /// the next write, or when the invocation ends.
+    deferred_write: Option<(StateScope, String, Vec<u8>)>,
+    /// Set in transactional mode; buffers every change until the invocation
+    /// succeeds.
+    transaction: Option<StateTransaction>,
+    /// Version of each key as this invocation last read it; a write that
+    /// replaces a different version lost someone else's update.
+    read_versions: HashMap<(StateScope, String), u64>,
 }
 
 impl StateStoreHostImpl {
-    fn new(
+    pub(super) fn new(
         base_scope: StateScope,
         state_store: Arc<InMemoryStateStore>,
         allow_state_read: bool,
         allow_state_write: bool,
         allow_state_delete: bool,
+        audit: AuditLog,
     ) -> Self {
         Self {
             base_scope,
@@ -242,6 +546,99 @@ impl StateStoreHostImpl {
             allow_state_read,
             allow_state_write,
             allow_state_delete,
+            audit,
+            chaos: None,
+            deferred_write: None,
+            transaction: None,
+            read_versions: HashMap::new(),
+        }
+    }
+
+    pub(super) fn transactional(mut self, enabled: bool) -> Self {
+        self.transaction = enabled.then(StateTransaction::default);
+        self
+    }
+
+    /// Applies the buffered changes; a no-op outside transactional mode.
+    pub(super) fn commit(&mut self) {
+        self.flush_deferred_write();
+        if let Some(transaction) = self.transaction.take() {
+            transaction.commit(&self.state_store);
+        }
+    }
+
+    fn store_read(&mut self, scope: &StateScope, key: &str) -> Option<Vec<u8>> {
+        if let Some(transaction) = &self.transaction {
+            return transaction.read(&self.state_store, scope, key);
+        }
+        let value = self.state_store.read_versioned(scope, key);
+        let version = value.as_ref().map_or(0, |(_, version)| *version);
+        self.read_versions
+            .insert((scope.clone(), key.to_string()), version);
+        value.map(|(bytes, _)| bytes)
+    }
+
+    fn store_write(
+        &mut self,
+        scope: &StateScope,
+        key: &str,
+        bytes: Vec<u8>,
+        ttl: Option<Duration>,
+    ) {
+        if let Some(transaction) = &mut self.transaction {
+            transaction.write(&self.state_store, scope, key, bytes, ttl);
+            return;
+        }
+        let replaced = self.state_store.write_with_ttl(scope, key, bytes, ttl);
+        if let Some(seen) = self.read_versions.remove(&(scope.clone(), key.to_string()))
+            && seen != replaced
+        {
+            self.state_store.record_conflict(key);
+        }
+    }
+
+    fn store_delete(&mut self, scope: &StateScope, key: &str) {
+        match &mut self.transaction {
+            Some(transaction) => transaction.delete(&self.state_store, scope, key),
+            None => {
+                self.state_store.delete(scope, key);
+            }
+        }
+    }
+
+    fn store_list(&self, scope: &StateScope, prefix: &str) -> Vec<String> {
+        match &self.transaction {
+            Some(transaction) => transaction.list(&self.state_store, scope, prefix),
+            None => self.state_store.list(scope, prefix),
+        }
+    }
+
+    pub(super) fn with_chaos(mut self, chaos: Option<Chaos>) -> Self {
+        self.chaos = chaos;
+        self
+    }
+
+    fn injected_failure(&self, kind: AuditKind, key: &str) -> Option<StateStoreError> {
+        let chaos = self.chaos.as_ref()?;
+        chaos.delay(ChaosImport::State, key);
+        if !chaos.fail(ChaosImport::State, key) {
+            return None;
+        }
+        self.audit.record(
+            kind,
+            key.to_string(),
+            AuditOutcome::Error,
+            Some("chaos: injected failure".into()),
+        );
+        Some(StateStoreError {
+            code: "state.unavailable".into(),
+            message: "state store temporarily unavailable (chaos)".into(),
+        })
+    }
+
+    fn flush_deferred_write(&mut self) {
+        if let Some((scope, key, bytes)) = self.deferred_write.take() {
+            self.store_write(&scope, &key, bytes, None);
         }
     }
 
@@ -272,18 +669,33 @@ impl StateStoreHost for StateStoreHostImpl {
         ctx: Option<WitTenantCtx>,
     ) -> std::result::Result<Vec<u8>, StateStoreError> {
         if !self.allow_state_read {
+            self.audit
+                .record(AuditKind::StateRead, key, AuditOutcome::Denied, None);
             return Err(StateStoreError {
                 code: "state.read.denied".into(),
                 message: "state store reads are disabled by manifest capability".into(),
             });
         }
+        if let Some(err) = self.injected_failure(AuditKind::StateRead, &key) {
+            return Err(err);
+        }
         let scope = self.scope_for_ctx(ctx.as_ref());
-        self.state_store
-            .read(&scope, &key)
-            .ok_or_else(|| StateStoreError {
-                code: "state.read.miss".into(),
-                message: format!("state key `{key}` not found"),
-            })
+        let value = self.store_read(&scope, &key);
+        let value = match &self.chaos {
+            Some(chaos) => value.map(|bytes| chaos.truncate(ChaosImport::State, &key, bytes)),
+            None => value,
+        };
+        let outcome = if value.is_some() {
+            AuditOutcome::Ok
+        } else {
+            AuditOutcome::Miss
+        };
+        self.audit
+            .record(AuditKind::StateRead, key.clone(), outcome, None);
+        value.ok_or_else(|| StateStoreError {
+            code: "state.read.miss".into(),
+            message: format!("state key `{key}` not found"),
+        })
     }
 
     fn write(
@@ -293,13 +705,30 @@ impl StateStoreHost for StateStoreHostImpl {
         ctx: Option<WitTenantCtx>,
     ) -> std::result::Result<OpAck, StateStoreError> {
         if !self.allow_state_write {
+            self.audit
+                .record(AuditKind::StateWrite, key, AuditOutcome::Denied, None);
             return Err(StateStoreError {
                 code: "state.write.denied".into(),
                 message: "state store writes are disabled by manifest capability".into(),
             });
         }
+        if let Some(err) = self.injected_failure(AuditKind::StateWrite, &key) {
+            return Err(err);
+        }
         let scope = self.scope_for_ctx(ctx.as_ref());
-        self.state_store.write(&scope, &key, bytes);
+        let defer = self.deferred_write.is_none()
+            && self
+                .chaos
+                .as_ref()
+                .is_some_and(|chaos| chaos.reorder_write(&key));
+        if defer {
+            self.deferred_write = Some((scope, key.clone(), bytes));
+        } else {
+            self.store_write(&scope, &key, bytes, None);
+            self.flush_deferred_write();
+        }
+        self.audit
+            .record(AuditKind::StateWrite, key, AuditOutcome::Ok, None);
         Ok(OpAck::Ok)
     }
 
@@ -309,24 +738,138 @@ impl StateStoreHost for StateStoreHostImpl {
         ctx: Option<WitTenantCtx>,
     ) -> std::result::Result<OpAck, StateStoreError> {
         if !self.allow_state_delete {
+            self.audit
+                .record(AuditKind::StateDelete, key, AuditOutcome::Denied, None);
             return Err(StateStoreError {
                 code: "state.delete.denied".into(),
                 message: "state store deletes are disabled by manifest capability".into(),
             });
         }
+        if let Some(err) = self.injected_failure(AuditKind::StateDelete, &key) {
+            return Err(err);
+        }
+        let scope = self.scope_for_ctx(ctx.as_ref());
+        self.store_delete(&scope, &key);
+        self.audit
+            .record(AuditKind::StateDelete, key, AuditOutcome::Ok, None);
+        Ok(OpAck::Ok)
+    }
+}
+
+impl StateStoreHostImpl {
+    /// Host side of the harness-only `list` import: live keys in the scope
+    /// starting with `prefix`. Listing needs `host.state.read`.
+    pub fn list(
+        &mut self,
+        prefix: String,
+        ctx: Option<WitTenantCtx>,
+    ) -> std::result::Result<Vec<String>, StateStoreError> {
+        if !self.allow_state_read {
+            self.audit
+                .record(AuditKind::StateList, prefix, AuditOutcome::Denied, None);
+            return Err(StateStoreError {
+                code: "state.list.denied".into(),
+                message: "state store reads are disabled by manifest capability".into(),
+            });
+        }
+        if let Some(err) = self.injected_failure(AuditKind::StateList, &prefix) {
+            return Err(err);
+        }
+        let scope = self.scope_for_ctx(ctx.as_ref());
+        let keys = self.store_list(&scope, &prefix);
+        self.audit.record(
+            AuditKind::StateList,
+            prefix,
+            AuditOutcome::Ok,
+            Some(format!("{} key(s)", keys.len())),
+        );
+        Ok(keys)
+    }
+
+    /// Host side of the harness-only `write-with-ttl` import. A TTL of zero
+    /// stores nothing observable, which matches an immediate expiry.
+    pub fn write_with_ttl(
+        &mut self,
+        key: String,
+        bytes: Vec<u8>,
+        ttl_ms: u64,
+        ctx: Option<WitTenantCtx>,
+    ) -> std::result::Result<OpAck, StateStoreError> {
+        if !self.allow_state_write {
+            self.audit
+                .record(AuditKind::StateWrite, key, AuditOutcome::Denied, None);
+            return Err(StateStoreError {
+                code: "state.write.denied".into(),
+                message: "state store writes are disabled by manifest capability".into(),
+            });
+        }
+        if let Some(err) = self.injected_failure(AuditKind::StateWrite, &key) {
+            return Err(err);
+        }
         let scope = self.scope_for_ctx(ctx.as_ref());
-        self.state_store.delete(&scope, &key);
+        self.flush_deferred_write();
+        self.store_write(&scope, &key, bytes, Some(Duration::from_millis(ttl_ms)));
+        self.audit.record(
+            AuditKind::StateWrite,
+            key,
+            AuditOutcome::Ok,
+            Some(format!("ttl {ttl_ms}ms")),
+        );
         Ok(OpAck::Ok)
     }
 }
 
+/// `greentic:state/store@1.0.0` only defines read/write/delete; the harness
+/// adds `list` and `write-with-ttl` to the same instance so components built
+/// against an extended store WIT can be exercised before hosts ship them.
+fn add_state_store_extensions(linker: &mut Linker<HostState>) -> Result<()> {
+    let mut inst = linker.instance(STATE_STORE_INTERFACE)?;
+    inst.func_wrap(
+        "list",
+        |mut caller: StoreContextMut<'_, HostState>,
+         (prefix, ctx): (String, Option<WitTenantCtx>)| {
+            Ok((caller.data_mut().state.list(prefix, ctx),))
+        },
+    )?;
+    inst.func_wrap(
+        "write-with-ttl",
+        |mut caller: StoreContextMut<'_, HostState>,
+         (key, bytes, ttl_ms, ctx): (String, Vec<u8>, u64, Option<WitTenantCtx>)| {
+            Ok((caller
+                .data_mut()
+                .state
+                .write_with_ttl(key, bytes, ttl_ms, ctx),))
+        },
+    )?;
+    Ok(())
+}
+
+impl Drop for StateStoreHostImpl {
+    fn drop(&mut self) {
+        self.flush_deferred_write();
+    }
+}
+
 pub struct SecretsStoreHostImpl {
     secrets: Arc<InMemorySecretsStore>,
+    audit: AuditLog,
+    chaos: Option<Chaos>,
 }
 
 impl SecretsStoreHostImpl {
-    fn new(secrets: Arc<InMemorySecretsStore>) -> Self {
-        Self { secrets }
+    pub(super) fn new(secrets: Arc<InMemorySecretsStore>, audit: AuditLog) -> Self {
+        Self {
+            secrets,
+            audit,
+            chaos: None,
+        }
+    }
+
+    /// Secrets only see chaos latency; a flaky secret lookup is not something
+    /// components are expected to retry.
+    pub(super) fn with_chaos(mut self, chaos: Option<Chaos>) -> Self {
+        self.chaos = chaos;
+        self
     }
 }
 
@@ -335,7 +878,20 @@ impl SecretsStoreHost for SecretsStoreHostImpl {
         &mut self,
         key: wasmtime::component::__internal::String,
     ) -> std::result::Result<Option<wasmtime::component::__internal::Vec<u8>>, SecretsError> {
-        self.secrets.get(&key)
+        if let Some(chaos) = &self.chaos {
+            chaos.delay(ChaosImport::Secrets, &key);
+        }
+        let result = self.secrets.get(&key);
+        let outcome = match &result {
+            Ok(_) => AuditOutcome::Ok,
+            Err(SecretsError::NotFound) => AuditOutcome::Miss,
+            Err(err) if matches!(err, SecretsError::Denied | SecretsError::InvalidKey) => {
+                AuditOutcome::Denied
+            }
+            Err(_) => AuditOutcome::Error,
+        };
+        self.audit.record(AuditKind::Secret, key, outcome, None);
+        result
     }
 }
 
@@ -351,6 +907,8 @@ impl WasiView for HostState {
 struct HostLimits {
     max_memory_bytes: usize,
     hit: Arc<AtomicBool>,
+    peak: usize,
+    table_growth: u64,
 }
 
 impl HostLimits {
@@ -358,6 +916,8 @@ impl HostLimits {
         Self {
             max_memory_bytes,
             hit,
+            peak: 0,
+            table_growth: 0,
         }
     }
 }
@@ -365,10 +925,11 @@ impl HostLimits {
 impl ResourceLimiter for HostLimits {
     fn memory_growing(
         &mut self,
-        _current: usize,
+        current: usize,
         desired: usize,
         _maximum: Option<usize>,
     ) -> Result<bool> {
+        self.peak = self.peak.max(current);
         if desired > self.max_memory_bytes {
             self.hit.store(true, Ordering::Relaxed);
             return Err(anyhow!(
@@ -376,19 +937,29 @@ impl ResourceLimiter for HostLimits {
                 self.max_memory_bytes
             ));
         }
+        self.peak = self.peak.max(desired);
         Ok(true)
     }
 
     fn table_growing(
         &mut self,
-        _current: usize,
-        _desired: usize,
+        current: usize,
+        desired: usize,
         _maximum: Option<usize>,
     ) -> Result<bool> {
+        self.table_growth += desired.saturating_sub(current) as u64;
         Ok(true)
     }
 }
 
+struct HandleLimits {
+    max_open: Option<usize>,
+    open: OpenHandles,
+    hit: bool,
+    /// Receives an `fs` entry for every handle opened or closed.
+    audit: AuditLog,
+}
+
 #[derive(Clone)]
 struct FixedWallClock {
     now: Duration,
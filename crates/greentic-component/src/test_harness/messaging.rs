@@ -0,0 +1,122 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use greentic_component_runtime::{CompError, MessagingBackend, OutboundMessage};
+use serde::Serialize;
+
+/// One recorded messaging call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranscriptEntry {
+    Sent {
+        id: String,
+        scope: String,
+        #[serde(flatten)]
+        message: OutboundMessage,
+    },
+    Acked {
+        id: String,
+        scope: String,
+    },
+}
+
+/// Messaging backend for local runs: nothing leaves the process, every send
+/// and ack is recorded in order and, when a file is set, appended to it as a
+/// JSON line.
+#[derive(Debug, Default)]
+pub struct TranscriptMessaging {
+    entries: Mutex<Vec<TranscriptEntry>>,
+    file: Option<PathBuf>,
+}
+
+impl TranscriptMessaging {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    pub fn transcript(&self) -> Vec<TranscriptEntry> {
+        self.entries
+            .lock()
+            .expect("transcript mutex poisoned")
+            .clone()
+    }
+
+    fn record(&self, entry: TranscriptEntry) -> Result<(), CompError> {
+        if let Some(path) = &self.file {
+            let line = serde_json::to_string(&entry)?;
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{line}"))
+                .map_err(|err| {
+                    CompError::Messaging(format!("write transcript {}: {err}", path.display()))
+                })?;
+        }
+        self.entries
+            .lock()
+            .expect("transcript mutex poisoned")
+            .push(entry);
+        Ok(())
+    }
+}
+
+impl MessagingBackend for TranscriptMessaging {
+    fn send(&self, scope: &str, message: &OutboundMessage) -> Result<String, CompError> {
+        let sent = self
+            .transcript()
+            .iter()
+            .filter(|entry| matches!(entry, TranscriptEntry::Sent { .. }))
+            .count();
+        let id = format!("msg-{}", sent + 1);
+        self.record(TranscriptEntry::Sent {
+            id: id.clone(),
+            scope: scope.to_string(),
+            message: message.clone(),
+        })?;
+        Ok(id)
+    }
+
+    fn ack(&self, scope: &str, message_id: &str) -> Result<(), CompError> {
+        self.record(TranscriptEntry::Acked {
+            id: message_id.to_string(),
+            scope: scope.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_sends_and_acks_as_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("messages.jsonl");
+        let messaging = TranscriptMessaging::new().with_file(&path);
+        let message = OutboundMessage {
+            channel: "email".into(),
+            body: "welcome".into(),
+            metadata: vec![("to".into(), "ada@example.com".into())],
+        };
+
+        assert_eq!(messaging.send("dev::acme", &message).unwrap(), "msg-1");
+        messaging.ack("dev::acme", "in-7").unwrap();
+        assert_eq!(messaging.send("dev::acme", &message).unwrap(), "msg-2");
+
+        assert_eq!(messaging.transcript().len(), 3);
+        let lines = std::fs::read_to_string(&path).unwrap();
+        let first: serde_json::Value = serde_json::from_str(lines.lines().next().unwrap()).unwrap();
+        assert_eq!(first["kind"], "sent");
+        assert_eq!(first["channel"], "email");
+        assert_eq!(first["metadata"][0][1], "ada@example.com");
+        assert!(lines.lines().nth(1).unwrap().contains("\"acked\""));
+    }
+}
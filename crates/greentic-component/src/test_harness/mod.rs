@@ -1,27 +1,61 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use blake3::Hasher;
+use greentic_component_runtime::{
+    BlobHost, BlobLimits, DirBlobs, EpochTicker, ErrorDetails, EventsHost, GuestLogger,
+    MessagingHost, MetricsSink, NoopMetrics, PoolingConfig, ProgressCallback, ProgressReporter,
+    SqlAccess, SqlHost, TelemetryHost, blob_scope, metric, sql_scope,
+};
 use greentic_interfaces_host::component::v0_5::exports::greentic::component::node;
 use greentic_interfaces_host::component::v0_5::exports::greentic::component::node::GuestIndices;
 use greentic_interfaces_host::component_v0_6;
 use greentic_types::TenantCtx;
 use greentic_types::cbor::canonical;
 use serde_json::Value;
+use tempfile::TempDir;
 use wasmtime::component::{Component, InstancePre, Linker};
-use wasmtime::{Config, Engine, Store};
+use wasmtime::{CallHook, Config, Engine, Store};
 
+use crate::capabilities::NetCaps;
 use crate::test_harness::linker::{HostState, HostStateConfig, build_linker};
 use crate::test_harness::secrets::InMemorySecretsStore;
-use crate::test_harness::state::{InMemoryStateStore, StateDumpEntry, StateScope};
-
+use crate::test_harness::state::{InMemoryStateStore, StateScope};
+
+mod builder;
+mod chaos;
+mod concurrency;
+pub mod conformance;
+mod events;
+mod fs_sandbox;
+mod isolation;
 mod linker;
+mod messaging;
+mod plan;
 mod secrets;
+mod sql;
 mod state;
+mod trap;
+
+pub use builder::{DEFAULT_MAX_OUTPUT_BYTES, TestHarnessBuilder};
+pub(crate) use builder::{
+    blob_permission, events_host, messaging_host, progress_permission, secret_permissions,
+    sql_access, state_permissions, telemetry_host,
+};
+pub use chaos::{Chaos, ChaosConfig, ChaosEvent, ChaosFault, ChaosImport, ChaosLatency};
+pub use concurrency::{ConcurrencyReport, LatencyStats};
+pub use events::EventRecorder;
+pub use fs_sandbox::{FsChange, FsChangeKind, FsQuota, FsQuotaExceeded, FsSandbox};
+pub use greentic_component_runtime::{AuditEntry, AuditKind, AuditLog, AuditOutcome};
+pub use isolation::IsolationProfile;
+pub use messaging::{TranscriptEntry, TranscriptMessaging};
+pub use plan::{ExpectedStatus, PlannedStep, StepExpect, StepPlan};
+pub use sql::SqliteSql;
+pub use state::{StateDumpEntry, StateDumpFilter};
+pub use trap::{GuestTrap, TrapFrame};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ComponentAbi {
@@ -46,10 +80,43 @@ impl std::fmt::Display for ComponentInvokeError {
 
 impl std::error::Error for ComponentInvokeError {}
 
+impl ComponentInvokeError {
+    /// `details` parsed into the documented error-details shape, when they
+    /// follow it.
+    pub fn error_details(&self) -> Option<ErrorDetails> {
+        self.details.as_deref().and_then(ErrorDetails::from_json)
+    }
+
+    /// How long to wait before retrying, or `None` for permanent errors;
+    /// the same rule as
+    /// [`CompError::retry_after`](greentic_component_runtime::CompError::retry_after).
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.retryable.then(|| {
+            let backoff_ms = self
+                .backoff_ms
+                .or_else(|| self.error_details()?.retry_after_ms);
+            Duration::from_millis(backoff_ms.unwrap_or(0))
+        })
+    }
+}
+
 #[derive(Debug)]
 pub enum HarnessError {
-    Timeout { timeout_ms: u64 },
-    MemoryLimit { max_memory_bytes: usize },
+    Timeout {
+        timeout_ms: u64,
+    },
+    MemoryLimit {
+        max_memory_bytes: usize,
+    },
+    OpenHandleLimit {
+        max_open_handles: usize,
+        open_handles: usize,
+    },
+    /// Checked before the output is decoded, and again on its JSON form.
+    OutputTooLarge {
+        max_output_bytes: usize,
+        size: usize,
+    },
 }
 
 impl std::fmt::Display for HarnessError {
@@ -64,6 +131,24 @@ impl std::fmt::Display for HarnessError {
                     "execution exceeded memory limit of {max_memory_bytes} bytes"
                 )
             }
+            HarnessError::OpenHandleLimit {
+                max_open_handles,
+                open_handles,
+            } => {
+                write!(
+                    f,
+                    "execution exceeded open handle limit of {max_open_handles} ({open_handles} open)"
+                )
+            }
+            HarnessError::OutputTooLarge {
+                max_output_bytes,
+                size,
+            } => {
+                write!(
+                    f,
+                    "output of {size} bytes exceeds the {max_output_bytes}-byte limit"
+                )
+            }
         }
     }
 }
@@ -84,10 +169,79 @@ pub struct HarnessConfig {
     pub allowed_secrets: HashSet<String>,
     pub secrets: HashMap<String, String>,
     pub wasi_preopens: Vec<WasiPreopen>,
+    /// Byte quota on the writable preopens, checked after every host call
+    /// (each check walks the mounts). Build it with [`FsSandbox::quota`].
+    pub fs_quota: Option<FsQuota>,
+    /// Variables exposed through WASI `environment`; callers filter these
+    /// against `capabilities.wasi.env.allow`.
+    pub env_vars: Vec<(String, String)>,
     pub config: Option<Value>,
     pub allow_http: bool,
+    /// Hosts HTTP requests may reach (`capabilities.host.http.domains`);
+    /// empty allows any host.
+    pub http_domains: Vec<String>,
     pub timeout_ms: u64,
     pub max_memory_bytes: usize,
+    /// Largest output accepted from an invocation (manifest
+    /// `limits.max_output_bytes`).
+    pub max_output_bytes: usize,
+    /// Engine knobs; host defaults are resolved by the caller.
+    pub isolation: IsolationProfile,
+    /// Cap on simultaneously open WASI handles (manifest `limits.files`).
+    pub max_open_handles: Option<usize>,
+    /// Raw socket access (manifest `capabilities.net`); `None` denies sockets.
+    pub net: Option<NetCaps>,
+    /// Receives every host-import call made during invocations.
+    pub audit: AuditLog,
+    /// Manifest `capabilities.host.progress`; without it `report-progress`
+    /// traps.
+    pub allow_progress: bool,
+    /// Called for each `report-progress` while an invocation runs.
+    pub on_progress: Option<ProgressCallback>,
+    /// Filters and forwards guest `log` calls.
+    pub guest_log: GuestLogger,
+    /// Manifest `capabilities.host.blob`; blobs live in a temp directory for
+    /// the harness lifetime. `None` makes blob calls trap.
+    pub blobs: Option<BlobLimits>,
+    /// Manifest `capabilities.host.sql` wired to a backend, usually
+    /// [`SqliteSql`]. `None` makes SQL calls trap.
+    pub sql: Option<SqlAccess>,
+    /// Manifest `capabilities.host.messaging` grants and backend, usually a
+    /// [`TranscriptMessaging`]. The default host denies both directions.
+    pub messaging: MessagingHost,
+    /// `emit-event` target, granted from `capabilities.host.events.outbound`
+    /// and usually backed by an [`EventRecorder`].
+    pub events: EventsHost,
+    /// Span events and attributes from the guest, scoped by
+    /// `capabilities.host.telemetry`.
+    pub telemetry: TelemetryHost,
+    /// Seeded fault injection for the state, secrets, and HTTP imports.
+    /// One random stream spans every invocation of the harness.
+    pub chaos: Option<ChaosConfig>,
+    /// Pooling allocator sizing; replaces the isolation profile's pool, so
+    /// it also turns pooling on outside `Strict`.
+    pub pooling: Option<PoolingConfig>,
+    /// Buffer each invocation's state writes and deletes, committing them
+    /// only when it succeeds; traps and component errors roll them back.
+    pub transactional_state: bool,
+    pub random: WasiRandom,
+    /// Manifest profile the run exercises. v0.6 guests receive it as
+    /// `{"profile": name}` in the invocation metadata; the v0.5 ABI has no
+    /// slot for it.
+    pub profile: Option<String>,
+}
+
+/// Where the guest's `wasi:random` bytes come from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WasiRandom {
+    /// The same short fixed byte streams on every run.
+    #[default]
+    Fixed,
+    /// Streams derived from this seed; the same seed replays the same
+    /// values, so a failure can be reproduced from its trace.
+    Seed(u64),
+    /// The host's real randomness, for soak tests.
+    Real,
 }
 
 #[derive(Clone, Debug)]
@@ -114,6 +268,9 @@ impl WasiPreopen {
 
 pub struct TestHarness {
     engine: Engine,
+    /// Advances `engine`'s epoch while invocations run; each store gets its
+    /// own deadline from it.
+    epoch: EpochTicker,
     component: Component,
     linker: Linker<HostState>,
     instance_pre: InstancePre<HostState>,
@@ -128,17 +285,58 @@ pub struct TestHarness {
     tenant_ctx: TenantCtx,
     exec_ctx: node::ExecCtx,
     wasi_preopens: Vec<WasiPreopen>,
+    fs_quota: Option<FsQuota>,
+    env_vars: Vec<(String, String)>,
     config_json: Option<String>,
     allow_http: bool,
+    http_domains: Vec<String>,
     timeout_ms: u64,
     max_memory_bytes: usize,
+    max_output_bytes: usize,
+    max_open_handles: Option<usize>,
+    net: Option<NetCaps>,
+    audit: AuditLog,
+    progress: ProgressReporter,
+    guest_log: GuestLogger,
+    blobs: BlobHost,
+    _blob_dir: Option<TempDir>,
+    sql: SqlHost,
+    messaging: MessagingHost,
+    events: EventsHost,
+    telemetry: TelemetryHost,
+    chaos: Option<Chaos>,
+    transactional_state: bool,
+    random: WasiRandom,
+    metadata_cbor: Option<Vec<u8>>,
+    profile: Option<String>,
     wasm_bytes_metadata: String,
+    metrics: Arc<dyn MetricsSink>,
+    metrics_component: String,
+}
+
+/// Config and secrets for a single invocation that differ from the
+/// harness-wide ones. State and every other host resource stay shared.
+#[derive(Debug, Clone, Default)]
+pub struct InvokeOverrides {
+    /// Replaces the harness config.
+    pub config: Option<Value>,
+    /// Added to the harness secrets, replacing keys that already exist.
+    pub secrets: HashMap<String, String>,
 }
 
+#[derive(Debug, Clone, Default)]
 pub struct InvokeOutcome {
     pub output_json: String,
     pub instantiate_ms: u64,
     pub run_ms: u64,
+    pub peak_memory_bytes: usize,
+    /// Table elements allocated, initial table sizes included.
+    pub table_growth: u64,
+    pub peak_open_handles: usize,
+    /// `None` unless the engine meters fuel.
+    pub fuel_consumed: Option<u64>,
+    /// Calls from the guest into host imports.
+    pub host_call_count: u64,
 }
 
 impl TestHarness {
@@ -147,6 +345,10 @@ impl TestHarness {
         wasmtime_config.wasm_component_model(true);
         wasmtime_config.wasm_backtrace_details(wasmtime::WasmBacktraceDetails::Enable);
         wasmtime_config.epoch_interruption(true);
+        config.isolation.configure(&mut wasmtime_config);
+        if let Some(pooling) = &config.pooling {
+            pooling.configure(&mut wasmtime_config);
+        }
         let engine = Engine::new(&wasmtime_config).context("create wasmtime engine")?;
 
         let component =
@@ -209,12 +411,41 @@ impl TestHarness {
             node_id: config.node_id,
         };
 
+        let (blobs, blob_dir) = match config.blobs {
+            Some(limits) => {
+                let dir = tempfile::Builder::new()
+                    .prefix("greentic-blobs-")
+                    .tempdir()
+                    .context("create blob directory")?;
+                let backend = DirBlobs::new(dir.path())?;
+                let scope = blob_scope(&config.tenant_ctx);
+                (
+                    BlobHost::new(true, scope, Arc::new(backend), limits),
+                    Some(dir),
+                )
+            }
+            None => (BlobHost::denied(), None),
+        };
+
+        let sql = SqlHost::new(sql_scope(&config.tenant_ctx), config.sql);
+
         let config_json = match config.config {
             Some(value) => Some(serde_json::to_string(&value).context("serialize config json")?),
             None => None,
         };
 
+        let metadata_cbor = match &config.profile {
+            Some(profile) => Some(
+                canonical::to_canonical_cbor_allow_floats(
+                    &serde_json::json!({ "profile": profile }),
+                )
+                .context("encode invocation metadata")?,
+            ),
+            None => None,
+        };
+
         Ok(Self {
+            epoch: EpochTicker::new(engine.clone(), EpochTicker::DEFAULT_RESOLUTION),
             engine,
             component,
             linker,
@@ -230,43 +461,166 @@ impl TestHarness {
             tenant_ctx: config.tenant_ctx,
             exec_ctx,
             wasi_preopens: config.wasi_preopens,
+            fs_quota: config.fs_quota,
+            env_vars: config.env_vars,
             config_json,
             allow_http: config.allow_http,
+            http_domains: config.http_domains,
             timeout_ms: config.timeout_ms,
             max_memory_bytes: config.max_memory_bytes,
+            max_output_bytes: config.max_output_bytes,
+            max_open_handles: config.max_open_handles,
+            net: config.net,
+            audit: config.audit,
+            progress: ProgressReporter::new(config.allow_progress, config.on_progress),
+            guest_log: config.guest_log,
+            blobs,
+            _blob_dir: blob_dir,
+            sql,
+            messaging: config.messaging,
+            events: config.events,
+            telemetry: config.telemetry,
+            chaos: config.chaos.map(Chaos::new),
+            transactional_state: config.transactional_state,
+            random: config.random,
+            metadata_cbor,
+            profile: config.profile,
             wasm_bytes_metadata,
+            metrics: Arc::new(NoopMetrics),
+            metrics_component: "component".to_string(),
         })
     }
 
     pub fn invoke(&self, operation: &str, input_json: &Value) -> Result<InvokeOutcome> {
+        self.invoke_with(operation, input_json, &InvokeOverrides::default())
+    }
+
+    pub fn invoke_with(
+        &self,
+        operation: &str,
+        input_json: &Value,
+        overrides: &InvokeOverrides,
+    ) -> Result<InvokeOutcome> {
+        let config_json = match &overrides.config {
+            Some(value) => Some(serde_json::to_string(value).context("serialize config json")?),
+            None => self.config_json.clone(),
+        };
+        let secrets = if overrides.secrets.is_empty() {
+            self.secrets_store.clone()
+        } else {
+            Arc::new(self.secrets_store.with_overrides(&overrides.secrets))
+        };
         let host_state = HostState::new(HostStateConfig {
             base_scope: self.state_scope.clone(),
             state_store: self.state_store.clone(),
-            secrets: self.secrets_store.clone(),
+            secrets,
             allow_state_read: self.allow_state_read,
             allow_state_write: self.allow_state_write,
             allow_state_delete: self.allow_state_delete,
             wasi_preopens: self.wasi_preopens.clone(),
+            fs_quota: self.fs_quota.clone(),
+            env_vars: self.env_vars.clone(),
             allow_http: self.allow_http,
-            config_json: self.config_json.clone(),
+            http_domains: self.http_domains.clone(),
+            config_json,
             max_memory_bytes: self.max_memory_bytes,
+            max_open_handles: self.max_open_handles,
+            net: self.net.clone(),
+            audit: self.audit.clone(),
+            progress: self.progress.clone(),
+            guest_log: self.guest_log.clone(),
+            blobs: self.blobs.clone(),
+            sql: self.sql.clone(),
+            messaging: self.messaging.clone(),
+            events: self.events.clone(),
+            telemetry: self.telemetry.clone(),
+            chaos: self.chaos.clone(),
+            transactional_state: self.transactional_state,
+            random: self.random,
         })
         .context("build WASI context")?;
         let mut store = Store::new(&self.engine, host_state);
         store.limiter(|state| state.limits_mut());
-        store.set_epoch_deadline(1);
-
-        let done = Arc::new(AtomicBool::new(false));
-        let _timeout_guard = TimeoutGuard::new(done.clone());
-        let engine = self.engine.clone();
-        let timeout_ms = self.timeout_ms;
-        std::thread::spawn(move || {
-            std::thread::sleep(Duration::from_millis(timeout_ms));
-            if !done.load(Ordering::Relaxed) {
-                engine.increment_epoch();
+        store.call_hook(|mut store, hook| {
+            match hook {
+                CallHook::CallingHost => store.data_mut().count_host_call(),
+                CallHook::ReturningFromHost => {
+                    store.data_mut().check_open_handles()?;
+                    store.data_mut().check_fs_quota()?;
+                }
+                _ => {}
             }
+            Ok(())
         });
+        let _deadline = self
+            .epoch
+            .deadline(&mut store, Duration::from_millis(self.timeout_ms));
+
+        let fuel_before = store.get_fuel().ok();
+        let started = Instant::now();
+        let mut result = self
+            .run(&mut store, operation, input_json)
+            .map_err(|err| self.decode_trap(err, operation));
+        if let Ok(outcome) = &mut result {
+            store.data_mut().commit_state();
+            outcome.peak_memory_bytes = store.data().peak_memory_bytes();
+            outcome.table_growth = store.data().table_growth();
+            outcome.peak_open_handles = store.data().peak_open_handles();
+            outcome.host_call_count = store.data().host_call_count();
+            outcome.fuel_consumed = fuel_before
+                .zip(store.get_fuel().ok())
+                .map(|(before, after)| before.saturating_sub(after));
+        }
+        self.record_metrics(operation, &result, started.elapsed(), &store, fuel_before);
+        result
+    }
+
+    /// Wraps a raw trap in a [`GuestTrap`] context so callers get the guest
+    /// location and a hint instead of wasmtime's backtrace text. Timeouts,
+    /// limits and component errors are already typed and pass through.
+    fn decode_trap(&self, err: anyhow::Error, operation: &str) -> anyhow::Error {
+        if err.downcast_ref::<HarnessError>().is_some()
+            || err.downcast_ref::<ComponentInvokeError>().is_some()
+        {
+            return err;
+        }
+        let export = match self.abi {
+            ComponentAbi::V0_5 => "greentic:component/node@0.5.0#invoke",
+            ComponentAbi::V0_6 => "greentic:component/node@0.6.0#invoke",
+        };
+        match GuestTrap::from_error(&err, export, operation) {
+            Some(trap) => err.context(trap),
+            None => err,
+        }
+    }
+
+    fn check_output_size(&self, size: usize) -> Result<()> {
+        if size > self.max_output_bytes {
+            return Err(anyhow::Error::new(HarnessError::OutputTooLarge {
+                max_output_bytes: self.max_output_bytes,
+                size,
+            }));
+        }
+        Ok(())
+    }
 
+    /// Attaches a metrics sink; invocations are labelled with `component`.
+    pub fn with_metrics(
+        mut self,
+        component: impl Into<String>,
+        metrics: Arc<dyn MetricsSink>,
+    ) -> Self {
+        self.metrics_component = component.into();
+        self.metrics = metrics;
+        self
+    }
+
+    fn run(
+        &self,
+        store: &mut Store<HostState>,
+        operation: &str,
+        input_json: &Value,
+    ) -> Result<InvokeOutcome> {
         let instantiate_start = Instant::now();
         match self.abi {
             ComponentAbi::V0_5 => {
@@ -276,11 +630,11 @@ impl TestHarness {
                     .context("missing v0.5 guest indices")?;
                 let instance = self
                     .instance_pre
-                    .instantiate(&mut store)
+                    .instantiate(&mut *store)
                     .context("instantiate component")
                     .and_then(|instance| {
                         guest_indices
-                            .load(&mut store, &instance)
+                            .load(&mut *store, &instance)
                             .context("load component exports")
                             .map(|exports| (instance, exports))
                     })
@@ -296,7 +650,7 @@ impl TestHarness {
                     Err(err) => {
                         return map_invoke_error(
                             err,
-                            &store,
+                            store,
                             self.timeout_ms,
                             self.max_memory_bytes,
                         );
@@ -307,7 +661,7 @@ impl TestHarness {
                 let input = serde_json::to_string(input_json).context("serialize input json")?;
                 let run_start = Instant::now();
                 let result = exports
-                    .call_invoke(&mut store, &self.exec_ctx, operation, &input)
+                    .call_invoke(&mut *store, &self.exec_ctx, operation, &input)
                     .context("invoke component");
 
                 use greentic_interfaces_host::component::v0_5::exports::greentic::component::node::InvokeResult;
@@ -317,7 +671,7 @@ impl TestHarness {
                     Err(err) => {
                         return map_invoke_error(
                             err,
-                            &store,
+                            store,
                             self.timeout_ms,
                             self.max_memory_bytes,
                         );
@@ -326,11 +680,15 @@ impl TestHarness {
                 let run_ms = duration_ms(run_start.elapsed());
 
                 match result {
-                    InvokeResult::Ok(output_json) => Ok(InvokeOutcome {
-                        output_json,
-                        instantiate_ms,
-                        run_ms,
-                    }),
+                    InvokeResult::Ok(output_json) => {
+                        self.check_output_size(output_json.len())?;
+                        Ok(InvokeOutcome {
+                            output_json,
+                            instantiate_ms,
+                            run_ms,
+                            ..InvokeOutcome::default()
+                        })
+                    }
                     InvokeResult::Err(err) => Err(anyhow::Error::new(ComponentInvokeError {
                         code: err.code,
                         message: err.message,
@@ -342,7 +700,7 @@ impl TestHarness {
             }
             ComponentAbi::V0_6 => {
                 let exports = component_v0_6::ComponentV0V6V0::instantiate(
-                    &mut store,
+                    &mut *store,
                     &self.component,
                     &self.linker,
                 )
@@ -358,7 +716,7 @@ impl TestHarness {
                     Err(err) => {
                         return map_invoke_error(
                             err,
-                            &store,
+                            store,
                             self.timeout_ms,
                             self.max_memory_bytes,
                         );
@@ -397,20 +755,20 @@ impl TestHarness {
                             .unwrap_or_else(|| "component".to_string()),
                         attempt: self.tenant_ctx.attempt,
                         payload_cbor: input,
-                        metadata_cbor: None,
+                        metadata_cbor: self.metadata_cbor.clone(),
                     };
 
                 let run_start = Instant::now();
                 let result = exports
                     .greentic_component_node()
-                    .call_invoke(&mut store, operation, &invoke_envelope)
+                    .call_invoke(&mut *store, operation, &invoke_envelope)
                     .context("invoke component");
                 let result = match result {
                     Ok(value) => value,
                     Err(err) => {
                         return map_invoke_error(
                             err,
-                            &store,
+                            store,
                             self.timeout_ms,
                             self.max_memory_bytes,
                         );
@@ -419,14 +777,17 @@ impl TestHarness {
                 let run_ms = duration_ms(run_start.elapsed());
                 match result {
                     Ok(result) => {
+                        self.check_output_size(result.output_cbor.len())?;
                         let output_value: Value = canonical::from_cbor(&result.output_cbor)
                             .context("decode invoke output cbor")?;
                         let output_json = serde_json::to_string(&output_value)
                             .context("serialize invoke output json")?;
+                        self.check_output_size(output_json.len())?;
                         Ok(InvokeOutcome {
                             output_json,
                             instantiate_ms,
                             run_ms,
+                            ..InvokeOutcome::default()
                         })
                     }
                     Err(err) => Err(anyhow::Error::new(ComponentInvokeError {
@@ -444,9 +805,74 @@ impl TestHarness {
         }
     }
 
+    fn record_metrics(
+        &self,
+        operation: &str,
+        result: &Result<InvokeOutcome>,
+        elapsed: Duration,
+        store: &Store<HostState>,
+        fuel_before: Option<u64>,
+    ) {
+        let component = self.metrics_component.as_str();
+        let labels = [("component", component), ("operation", operation)];
+        let outcome = if result.is_ok() { "ok" } else { "error" };
+        self.metrics.counter(
+            metric::INVOCATIONS,
+            1,
+            &[
+                ("component", component),
+                ("operation", operation),
+                ("outcome", outcome),
+            ],
+        );
+        self.metrics
+            .histogram(metric::INVOKE_DURATION, elapsed.as_secs_f64(), &labels);
+        if let Err(err) = result {
+            self.metrics.counter(
+                metric::ERRORS,
+                1,
+                &[
+                    ("component", component),
+                    ("operation", operation),
+                    ("category", invoke_error_category(err)),
+                ],
+            );
+        }
+        self.metrics.gauge(
+            metric::MEMORY_PEAK,
+            store.data().peak_memory_bytes() as f64,
+            &labels,
+        );
+        if let (Some(before), Ok(after)) = (fuel_before, store.get_fuel()) {
+            self.metrics
+                .histogram(metric::FUEL, before.saturating_sub(after) as f64, &labels);
+        }
+    }
+
+    /// The profile this harness runs under, if any.
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
     pub fn state_dump(&self) -> Vec<StateDumpEntry> {
         self.state_store.dump()
     }
+
+    /// Like [`state_dump`](Self::state_dump), restricted to one
+    /// tenant/team/user scope.
+    pub fn state_dump_matching(&self, filter: &StateDumpFilter) -> Vec<StateDumpEntry> {
+        self.state_store.dump_matching(filter)
+    }
+
+    /// Lost updates detected so far, by state key.
+    pub fn state_conflicts(&self) -> BTreeMap<String, u64> {
+        self.state_store.conflicts()
+    }
+
+    /// Faults injected so far, in call order; empty without a chaos config.
+    pub fn chaos_events(&self) -> Vec<ChaosEvent> {
+        self.chaos.as_ref().map(Chaos::events).unwrap_or_default()
+    }
 }
 
 fn make_component_tenant_ctx(tenant: &TenantCtx) -> node::TenantCtx {
@@ -491,28 +917,29 @@ fn make_component_tenant_ctx_v0_6(
     }
 }
 
-struct TimeoutGuard {
-    done: Arc<AtomicBool>,
-}
-
-impl TimeoutGuard {
-    fn new(done: Arc<AtomicBool>) -> Self {
-        Self { done }
-    }
-}
-
-impl Drop for TimeoutGuard {
-    fn drop(&mut self) {
-        self.done.store(true, Ordering::Relaxed);
-    }
-}
-
 fn is_timeout_error(err: &anyhow::Error) -> bool {
     err.chain()
         .find_map(|source| source.downcast_ref::<wasmtime::Trap>())
         .is_some_and(|trap| matches!(trap, wasmtime::Trap::Interrupt))
 }
 
+/// Metric label for a failed invocation: `component` when the component
+/// returned an error (its codes are unbounded), otherwise the harness
+/// failure kind.
+fn invoke_error_category(err: &anyhow::Error) -> &'static str {
+    if err.downcast_ref::<ComponentInvokeError>().is_some() {
+        return "component";
+    }
+    match err.downcast_ref::<HarnessError>() {
+        Some(HarnessError::Timeout { .. }) => "timeout",
+        Some(HarnessError::MemoryLimit { .. }) => "memory_limit",
+        Some(HarnessError::OpenHandleLimit { .. }) => "open_handle_limit",
+        Some(HarnessError::OutputTooLarge { .. }) => "output_too_large",
+        None if err.downcast_ref::<FsQuotaExceeded>().is_some() => "fs_quota",
+        None => "trap",
+    }
+}
+
 fn duration_ms(duration: Duration) -> u64 {
     duration.as_millis().try_into().unwrap_or(u64::MAX)
 }
@@ -531,6 +958,17 @@ fn map_invoke_error(
             max_memory_bytes,
         }));
     }
+    if store.data().open_handle_limit_hit()
+        && let Some(max_open_handles) = store.data().max_open_handles()
+    {
+        return Err(anyhow::Error::new(HarnessError::OpenHandleLimit {
+            max_open_handles,
+            open_handles: store.data().peak_open_handles(),
+        }));
+    }
+    if let Some(exceeded) = store.data().fs_quota_hit() {
+        return Err(anyhow::Error::new(exceeded));
+    }
     Err(err)
 }
 
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// A multi-step run, usually read from `--plan steps.yaml`:
+///
+/// ```yaml
+/// steps:
+///   - op: put
+///     input: { key: greeting, value: hello }
+///   - op: get
+///     input_file: get.json
+///     config: { mode: strict }
+///     secrets: { API_TOKEN: test-token }
+///     expect:
+///       output: { value: hello }
+///   - op: get
+///     input: { key: missing }
+///     expect: { status: error, code: not_found }
+/// ```
+///
+/// `input_file` and `config_file` are resolved relative to the plan file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepPlan {
+    pub steps: Vec<PlannedStep>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlannedStep {
+    pub op: String,
+    pub input: Option<Value>,
+    pub input_file: Option<PathBuf>,
+    /// Replaces the run's config for this step only.
+    pub config: Option<Value>,
+    pub config_file: Option<PathBuf>,
+    /// Added to the run's secrets for this step only.
+    #[serde(default)]
+    pub secrets: BTreeMap<String, String>,
+    pub expect: Option<StepExpect>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpectedStatus {
+    #[default]
+    Ok,
+    Error,
+}
+
+/// The outcome a step must produce for the run to pass.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepExpect {
+    #[serde(default)]
+    pub status: ExpectedStatus,
+    /// Error code the step must fail with (implies `status: error`).
+    pub code: Option<String>,
+    /// Fields the output must contain. Objects match as subsets, and keys
+    /// starting with `/` are JSON pointers into the output.
+    pub output: Option<Value>,
+    /// Require `output` to equal the whole output instead.
+    #[serde(default)]
+    pub exact: bool,
+}
+
+impl StepPlan {
+    /// Reads a YAML (or JSON) plan and inlines its `input_file` and
+    /// `config_file` references.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("read step plan {}", path.display()))?;
+        let mut plan: StepPlan = serde_norway::from_str(&raw)
+            .with_context(|| format!("parse step plan {}", path.display()))?;
+        if plan.steps.is_empty() {
+            bail!("step plan {} has no steps", path.display());
+        }
+        let base = path.parent().unwrap_or_else(|| Path::new("."));
+        for (idx, step) in plan.steps.iter_mut().enumerate() {
+            let label = format!("step {} ({})", idx + 1, step.op);
+            step.input = Some(match (step.input.take(), step.input_file.take()) {
+                (Some(input), None) => input,
+                (None, Some(file)) => read_json(&base.join(file))?,
+                (None, None) => Value::Object(Default::default()),
+                (Some(_), Some(_)) => bail!("{label}: use either input or input_file"),
+            });
+            step.config = match (step.config.take(), step.config_file.take()) {
+                (config, None) => config,
+                (None, Some(file)) => Some(read_json(&base.join(file))?),
+                (Some(_), Some(_)) => bail!("{label}: use either config or config_file"),
+            };
+        }
+        Ok(plan)
+    }
+}
+
+impl StepExpect {
+    /// Checks a successful step's output.
+    pub fn check_output(&self, output: &Value) -> Result<(), String> {
+        if self.status == ExpectedStatus::Error || self.code.is_some() {
+            return Err("expected an error, but the step succeeded".to_string());
+        }
+        let Some(expected) = &self.output else {
+            return Ok(());
+        };
+        let mut differences = Vec::new();
+        diff(output, expected, "", self.exact, &mut differences);
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "output does not match the expectation:\n  {}",
+                differences.join("\n  ")
+            ))
+        }
+    }
+
+    /// Checks a failed step's error code; `Ok` means the failure was expected.
+    pub fn check_error(&self, code: &str) -> Result<(), String> {
+        if self.status == ExpectedStatus::Ok && self.code.is_none() {
+            return Err(format!(
+                "expected success, but the step failed with `{code}`"
+            ));
+        }
+        match &self.code {
+            Some(expected) if expected != code => {
+                Err(format!("expected error `{expected}`, got `{code}`"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn read_json(path: &Path) -> Result<Value> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("{} must be valid JSON", path.display()))
+}
+
+/// Records every difference between `actual` and `expected` by JSON
+/// pointer. Outside `exact` mode objects match as subsets and `/`-prefixed
+/// keys address the output by pointer.
+fn diff(actual: &Value, expected: &Value, pointer: &str, exact: bool, out: &mut Vec<String>) {
+    let at = if pointer.is_empty() { "/" } else { pointer };
+    match (actual, expected) {
+        (Value::Object(actual_map), Value::Object(expected_map)) => {
+            for (key, expected) in expected_map {
+                if !exact && key.starts_with('/') {
+                    let child = format!("{pointer}{key}");
+                    match actual.pointer(key) {
+                        Some(actual) => diff(actual, expected, &child, exact, out),
+                        None => out.push(format!("{child}: missing, expected {expected}")),
+                    }
+                    continue;
+                }
+                let child = format!("{pointer}/{}", escape_pointer(key));
+                match actual_map.get(key) {
+                    Some(actual) => diff(actual, expected, &child, exact, out),
+                    None => out.push(format!("{child}: missing, expected {expected}")),
+                }
+            }
+            if exact {
+                for (key, actual) in actual_map {
+                    if !expected_map.contains_key(key) {
+                        out.push(format!(
+                            "{pointer}/{}: unexpected {actual}",
+                            escape_pointer(key)
+                        ));
+                    }
+                }
+            }
+        }
+        (Value::Array(actual_items), Value::Array(expected_items)) => {
+            for (idx, expected) in expected_items.iter().enumerate() {
+                let child = format!("{pointer}/{idx}");
+                match actual_items.get(idx) {
+                    Some(actual) => diff(actual, expected, &child, exact, out),
+                    None => out.push(format!("{child}: missing, expected {expected}")),
+                }
+            }
+            if actual_items.len() > expected_items.len() {
+                out.push(format!(
+                    "{at}: {} extra item(s) beyond the expected {}",
+                    actual_items.len() - expected_items.len(),
+                    expected_items.len()
+                ));
+            }
+        }
+        _ if actual == expected => {}
+        _ => out.push(format!("{at}: expected {expected}, got {actual}")),
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_inlines_files_and_checks_expectations() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::write(dir.path().join("cfg.json"), r#"{"mode":"strict"}"#).unwrap();
+        let plan_path = dir.path().join("plan.yaml");
+        fs::write(
+            &plan_path,
+            "steps:\n  - op: put\n    input: { key: a }\n  - op: get\n    config_file: cfg.json\n    expect: { output: { value: 1 } }\n",
+        )
+        .unwrap();
+        let plan = StepPlan::from_path(&plan_path).expect("plan");
+        assert_eq!(plan.steps[1].input, Some(serde_json::json!({})));
+        assert_eq!(
+            plan.steps[1].config,
+            Some(serde_json::json!({ "mode": "strict" }))
+        );
+
+        let expect = plan.steps[1].expect.as_ref().unwrap();
+        assert!(
+            expect
+                .check_output(&serde_json::json!({ "value": 1, "extra": true }))
+                .is_ok()
+        );
+        assert_eq!(
+            expect.check_output(&serde_json::json!({ "value": 2 })),
+            Err("output does not match the expectation:\n  /value: expected 1, got 2".to_string())
+        );
+        assert!(expect.check_error("boom").is_err());
+    }
+
+    #[test]
+    fn exact_and_pointer_expectations_report_each_difference() {
+        let output = serde_json::json!({ "items": [{ "id": "a" }, { "id": "b" }], "extra": 1 });
+        let exact = StepExpect {
+            output: Some(serde_json::json!({ "items": [{ "id": "a" }] })),
+            exact: true,
+            ..StepExpect::default()
+        };
+        let message = exact.check_output(&output).unwrap_err();
+        assert!(message.contains("/items: 1 extra item(s)"), "{message}");
+        assert!(message.contains("/extra: unexpected 1"), "{message}");
+
+        let pointers = StepExpect {
+            output: Some(serde_json::json!({ "/items/1/id": "b", "/items/2": null })),
+            ..StepExpect::default()
+        };
+        let message = pointers.check_output(&output).unwrap_err();
+        assert_eq!(
+            message,
+            "output does not match the expectation:\n  /items/2: missing, expected null"
+        );
+    }
+}
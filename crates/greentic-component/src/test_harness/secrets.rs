@@ -26,6 +26,17 @@ impl InMemorySecretsStore {
         self
     }
 
+    /// A copy with `overrides` layered over the current secrets.
+    pub fn with_overrides(&self, overrides: &HashMap<String, String>) -> Self {
+        let mut store = self.clone();
+        for (key, value) in overrides {
+            store
+                .secrets
+                .insert(key.clone(), value.clone().into_bytes());
+        }
+        store
+    }
+
     pub fn get(
         &self,
         key: &str,
@@ -0,0 +1,174 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use greentic_component_runtime::{CompError, SqlBackend, SqlResult};
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{Connection, params_from_iter};
+use serde_json::{Map, Value};
+
+/// In-memory SQLite databases for local runs, one per tenant scope and
+/// connection name. Only connections declared in the manifest are opened.
+#[derive(Debug)]
+pub struct SqliteSql {
+    connections: BTreeSet<String>,
+    open: Mutex<HashMap<(String, String), Connection>>,
+}
+
+impl SqliteSql {
+    pub fn new(connections: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            connections: connections.into_iter().collect(),
+            open: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Runs a setup script (schema, fixtures) against one connection.
+    pub fn execute_batch(&self, scope: &str, connection: &str, sql: &str) -> Result<(), CompError> {
+        self.with_connection(scope, connection, |conn| {
+            conn.execute_batch(sql).map_err(sql_error)
+        })
+    }
+
+    fn with_connection<R>(
+        &self,
+        scope: &str,
+        connection: &str,
+        run: impl FnOnce(&Connection) -> Result<R, CompError>,
+    ) -> Result<R, CompError> {
+        if !self.connections.contains(connection) {
+            return Err(CompError::Sql(format!(
+                "connection `{connection}` is not declared"
+            )));
+        }
+        let mut open = self.open.lock().expect("sqlite mutex poisoned");
+        let key = (scope.to_string(), connection.to_string());
+        if !open.contains_key(&key) {
+            open.insert(
+                key.clone(),
+                Connection::open_in_memory().map_err(sql_error)?,
+            );
+        }
+        run(&open[&key])
+    }
+}
+
+impl SqlBackend for SqliteSql {
+    fn query(
+        &self,
+        scope: &str,
+        connection: &str,
+        sql: &str,
+        params: &[Value],
+    ) -> Result<SqlResult, CompError> {
+        self.with_connection(scope, connection, |conn| {
+            let mut stmt = conn.prepare(sql).map_err(sql_error)?;
+            let params = params.iter().map(to_sql_value);
+            if stmt.column_count() == 0 {
+                let affected = stmt.execute(params_from_iter(params)).map_err(sql_error)?;
+                return Ok(SqlResult {
+                    rows: Vec::new(),
+                    rows_affected: affected as u64,
+                });
+            }
+            let columns = stmt
+                .column_names()
+                .into_iter()
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            let mut rows = stmt.query(params_from_iter(params)).map_err(sql_error)?;
+            let mut out = Vec::new();
+            while let Some(row) = rows.next().map_err(sql_error)? {
+                let mut record = Map::new();
+                for (index, column) in columns.iter().enumerate() {
+                    let value = row.get_ref(index).map_err(sql_error)?;
+                    record.insert(column.clone(), from_sql_value(value));
+                }
+                out.push(record);
+            }
+            Ok(SqlResult {
+                rows: out,
+                rows_affected: 0,
+            })
+        })
+    }
+}
+
+fn sql_error(err: rusqlite::Error) -> CompError {
+    CompError::Sql(err.to_string())
+}
+
+fn to_sql_value(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(flag) => SqlValue::Integer(i64::from(*flag)),
+        Value::Number(number) => match number.as_i64() {
+            Some(int) => SqlValue::Integer(int),
+            None => SqlValue::Real(number.as_f64().unwrap_or_default()),
+        },
+        Value::String(text) => SqlValue::Text(text.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+/// Blobs come back base64-encoded, since JSON has no byte type.
+fn from_sql_value(value: ValueRef<'_>) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(int) => Value::from(int),
+        ValueRef::Real(real) => Value::from(real),
+        ValueRef::Text(text) => Value::String(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(bytes) => Value::String(BASE64_STANDARD.encode(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn runs_parameterized_statements_per_scope() {
+        let sql = SqliteSql::new(["main".to_string()]);
+        sql.execute_batch(
+            "dev::acme",
+            "main",
+            "CREATE TABLE users (id INTEGER, name TEXT); INSERT INTO users VALUES (1, 'ada');",
+        )
+        .unwrap();
+
+        let inserted = sql
+            .query(
+                "dev::acme",
+                "main",
+                "INSERT INTO users VALUES (?1, ?2)",
+                &[json!(2), json!("grace")],
+            )
+            .unwrap();
+        assert_eq!(inserted.rows_affected, 1);
+
+        let found = sql
+            .query(
+                "dev::acme",
+                "main",
+                "SELECT name FROM users WHERE id = ?1",
+                &[json!(2)],
+            )
+            .unwrap();
+        assert_eq!(
+            found.rows,
+            vec![json!({"name": "grace"}).as_object().unwrap().clone()]
+        );
+
+        // Another tenant gets its own empty database.
+        assert!(
+            sql.query("dev::other", "main", "SELECT * FROM users", &[])
+                .is_err()
+        );
+        assert!(
+            sql.query("dev::acme", "analytics", "SELECT 1", &[])
+                .is_err()
+        );
+    }
+}
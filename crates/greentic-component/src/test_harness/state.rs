@@ -1,5 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
 use base64::Engine as _;
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
@@ -16,7 +18,17 @@ struct ScopedKey {
     key: String,
 }
 
-#[derive(Clone, Debug)]
+impl ScopedKey {
+    fn in_scope(&self, scope: &StateScope) -> bool {
+        self.env == scope.env
+            && self.tenant == scope.tenant
+            && self.team == scope.team
+            && self.user == scope.user
+            && self.prefix == scope.prefix
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct StateScope {
     pub env: String,
     pub tenant: String,
@@ -45,40 +57,185 @@ pub struct StateDumpEntry {
     pub user_present: bool,
     pub prefix: String,
     pub key: String,
+    /// Empty when the value was redacted.
     pub value_base64: String,
+    /// Set when the value held a secret and was dropped from the dump.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub redacted: bool,
+}
+
+/// Narrows a state dump to one tenant, optionally one team and user within
+/// it. Unset fields match everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateDumpFilter {
+    pub tenant: Option<String>,
+    pub team: Option<String>,
+    pub user: Option<String>,
+}
+
+impl StateDumpFilter {
+    /// Parses `TENANT[/TEAM[/USER]]`.
+    pub fn parse(raw: &str) -> Option<Self> {
+        let mut parts = raw.split('/').map(str::trim);
+        let tenant = parts.next().filter(|part| !part.is_empty())?;
+        let team = parts.next().filter(|part| !part.is_empty());
+        let user = parts.next().filter(|part| !part.is_empty());
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            tenant: Some(tenant.to_string()),
+            team: team.map(str::to_string),
+            user: user.map(str::to_string),
+        })
+    }
+
+    fn matches(&self, key: &ScopedKey) -> bool {
+        let field = |wanted: &Option<String>, actual: Option<&String>| {
+            wanted.as_ref().is_none_or(|wanted| actual == Some(wanted))
+        };
+        field(&self.tenant, Some(&key.tenant))
+            && field(&self.team, key.team.as_ref())
+            && field(&self.user, key.user.as_ref())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct StoredValue {
+    bytes: Vec<u8>,
+    expires_at: Option<Instant>,
+    /// Store-wide write counter at the time of the write, for spotting lost
+    /// updates between concurrent invocations.
+    version: u64,
+}
+
+impl StoredValue {
+    fn live(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
 }
 
+/// Keys written with a TTL behave as missing once it elapses, for reads,
+/// listings and dumps alike.
 #[derive(Debug)]
 pub struct InMemoryStateStore {
-    entries: Mutex<HashMap<ScopedKey, Vec<u8>>>,
+    entries: Mutex<HashMap<ScopedKey, StoredValue>>,
+    clock: AtomicU64,
+    conflicts: Mutex<BTreeMap<String, u64>>,
 }
 
 impl InMemoryStateStore {
     pub fn new() -> Self {
         Self {
             entries: Mutex::new(HashMap::new()),
+            clock: AtomicU64::new(0),
+            conflicts: Mutex::new(BTreeMap::new()),
         }
     }
 
     pub fn read(&self, scope: &StateScope, key: &str) -> Option<Vec<u8>> {
-        let guard = self.entries.lock().expect("state store mutex poisoned");
-        guard.get(&self.scoped_key(scope, key)).cloned()
+        self.read_versioned(scope, key).map(|(bytes, _)| bytes)
+    }
+
+    /// Reads `key` together with the version of the write that produced it.
+    pub fn read_versioned(&self, scope: &StateScope, key: &str) -> Option<(Vec<u8>, u64)> {
+        let mut guard = self.entries.lock().expect("state store mutex poisoned");
+        let scoped = self.scoped_key(scope, key);
+        match guard.get(&scoped) {
+            Some(value) if value.live(Instant::now()) => Some((value.bytes.clone(), value.version)),
+            Some(_) => {
+                guard.remove(&scoped);
+                None
+            }
+            None => None,
+        }
     }
 
     pub fn write(&self, scope: &StateScope, key: &str, bytes: Vec<u8>) {
+        self.write_with_ttl(scope, key, bytes, None);
+    }
+
+    /// Writes `bytes`, expiring them after `ttl` when one is given. Returns
+    /// the version it replaced, `0` when the key was missing.
+    pub fn write_with_ttl(
+        &self,
+        scope: &StateScope,
+        key: &str,
+        bytes: Vec<u8>,
+        ttl: Option<Duration>,
+    ) -> u64 {
+        let value = self.stored_value(bytes, ttl);
         let mut guard = self.entries.lock().expect("state store mutex poisoned");
-        guard.insert(self.scoped_key(scope, key), bytes);
+        let now = Instant::now();
+        guard
+            .insert(self.scoped_key(scope, key), value)
+            .filter(|previous| previous.live(now))
+            .map_or(0, |previous| previous.version)
+    }
+
+    /// Notes that a write to `key` replaced a value its writer never read.
+    pub fn record_conflict(&self, key: &str) {
+        let mut guard = self.conflicts.lock().expect("state store mutex poisoned");
+        *guard.entry(key.to_string()).or_default() += 1;
+    }
+
+    /// Lost-update counts by key, as reported through
+    /// [`record_conflict`](Self::record_conflict).
+    pub fn conflicts(&self) -> BTreeMap<String, u64> {
+        self.conflicts
+            .lock()
+            .expect("state store mutex poisoned")
+            .clone()
+    }
+
+    fn stored_value(&self, bytes: Vec<u8>, ttl: Option<Duration>) -> StoredValue {
+        StoredValue {
+            bytes,
+            expires_at: ttl.map(|ttl| Instant::now() + ttl),
+            version: self.clock.fetch_add(1, Ordering::Relaxed) + 1,
+        }
     }
 
     pub fn delete(&self, scope: &StateScope, key: &str) -> bool {
         let mut guard = self.entries.lock().expect("state store mutex poisoned");
-        guard.remove(&self.scoped_key(scope, key)).is_some()
+        guard
+            .remove(&self.scoped_key(scope, key))
+            .is_some_and(|value| value.live(Instant::now()))
+    }
+
+    /// Live keys in `scope` that start with `prefix`, sorted.
+    pub fn list(&self, scope: &StateScope, prefix: &str) -> Vec<String> {
+        let guard = self.entries.lock().expect("state store mutex poisoned");
+        let now = Instant::now();
+        let mut keys = guard
+            .iter()
+            .filter(|(key, value)| {
+                value.live(now) && key.in_scope(scope) && key.key.starts_with(prefix)
+            })
+            .map(|(key, _)| key.key.clone())
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
     }
 
     pub fn dump(&self) -> Vec<StateDumpEntry> {
+        self.dump_matching(&StateDumpFilter::default())
+    }
+
+    /// Live entries whose scope matches `filter`, sorted by scope and key.
+    pub fn dump_matching(&self, filter: &StateDumpFilter) -> Vec<StateDumpEntry> {
         let guard = self.entries.lock().expect("state store mutex poisoned");
-        guard
+        let now = Instant::now();
+        let mut entries = guard
             .iter()
+            .filter(|(key, value)| value.live(now) && filter.matches(key))
+            .collect::<Vec<_>>();
+        entries.sort_by(|(a, _), (b, _)| {
+            (&a.env, &a.tenant, &a.team, &a.user, &a.prefix, &a.key)
+                .cmp(&(&b.env, &b.tenant, &b.team, &b.user, &b.prefix, &b.key))
+        });
+        entries
+            .into_iter()
             .map(|(key, value)| StateDumpEntry {
                 env: key.env.clone(),
                 tenant: key.tenant.clone(),
@@ -86,7 +243,8 @@ impl InMemoryStateStore {
                 user_present: key.user.is_some(),
                 prefix: key.prefix.clone(),
                 key: key.key.clone(),
-                value_base64: BASE64_STANDARD.encode(value),
+                value_base64: BASE64_STANDARD.encode(&value.bytes),
+                redacted: false,
             })
             .collect()
     }
@@ -103,6 +261,82 @@ impl InMemoryStateStore {
     }
 }
 
+/// State changes buffered for one invocation. Reads through the transaction
+/// see its own writes; [`commit`](Self::commit) applies them to the store,
+/// and dropping it discards them.
+#[derive(Debug, Default)]
+pub struct StateTransaction {
+    // `None` marks a buffered delete.
+    pending: HashMap<ScopedKey, Option<StoredValue>>,
+}
+
+impl StateTransaction {
+    pub fn read(
+        &self,
+        store: &InMemoryStateStore,
+        scope: &StateScope,
+        key: &str,
+    ) -> Option<Vec<u8>> {
+        match self.pending.get(&store.scoped_key(scope, key)) {
+            Some(Some(value)) if value.live(Instant::now()) => Some(value.bytes.clone()),
+            Some(_) => None,
+            None => store.read(scope, key),
+        }
+    }
+
+    pub fn write(
+        &mut self,
+        store: &InMemoryStateStore,
+        scope: &StateScope,
+        key: &str,
+        bytes: Vec<u8>,
+        ttl: Option<Duration>,
+    ) {
+        self.pending.insert(
+            store.scoped_key(scope, key),
+            Some(store.stored_value(bytes, ttl)),
+        );
+    }
+
+    pub fn delete(&mut self, store: &InMemoryStateStore, scope: &StateScope, key: &str) {
+        self.pending.insert(store.scoped_key(scope, key), None);
+    }
+
+    pub fn list(
+        &self,
+        store: &InMemoryStateStore,
+        scope: &StateScope,
+        prefix: &str,
+    ) -> Vec<String> {
+        let now = Instant::now();
+        let mut keys = store.list(scope, prefix);
+        for (key, value) in &self.pending {
+            if !key.in_scope(scope) || !key.key.starts_with(prefix) {
+                continue;
+            }
+            let live = value.as_ref().is_some_and(|value| value.live(now));
+            match keys.binary_search(&key.key) {
+                Ok(idx) if !live => {
+                    keys.remove(idx);
+                }
+                Err(idx) if live => keys.insert(idx, key.key.clone()),
+                _ => {}
+            }
+        }
+        keys
+    }
+
+    pub fn commit(self, store: &InMemoryStateStore) {
+        let mut guard = store.entries.lock().expect("state store mutex poisoned");
+        for (key, value) in self.pending {
+            match value {
+                Some(value) => guard.insert(key, value),
+                None => guard.remove(&key),
+            };
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -168,4 +402,59 @@ mod tests {
         assert_eq!(store.read(&scope_a, "alpha").unwrap(), b"one");
         assert_eq!(store.read(&scope_b, "alpha").unwrap(), b"two");
     }
+
+    #[test]
+    fn ttl_listing_and_scoped_dumps() {
+        let store = InMemoryStateStore::new();
+        let alice = StateScope::from_tenant_ctx(
+            &tenant_ctx("dev", "acme", Some("ops"), Some("alice")),
+            "test/1".into(),
+        );
+        let other =
+            StateScope::from_tenant_ctx(&tenant_ctx("dev", "globex", None, None), "test/1".into());
+
+        store.write(&alice, "cart/2", b"b".to_vec());
+        store.write(&alice, "cart/1", b"a".to_vec());
+        store.write(&alice, "profile", b"p".to_vec());
+        store.write_with_ttl(&alice, "cart/expired", b"x".to_vec(), Some(Duration::ZERO));
+        store.write(&other, "cart/9", b"z".to_vec());
+
+        assert!(store.read(&alice, "cart/expired").is_none());
+        assert_eq!(store.list(&alice, "cart/"), vec!["cart/1", "cart/2"]);
+        assert_eq!(store.list(&other, ""), vec!["cart/9"]);
+
+        let filter = StateDumpFilter::parse("acme/ops").expect("filter");
+        let dump = store.dump_matching(&filter);
+        assert_eq!(
+            dump.iter()
+                .map(|entry| entry.key.as_str())
+                .collect::<Vec<_>>(),
+            vec!["cart/1", "cart/2", "profile"]
+        );
+        assert!(StateDumpFilter::parse("acme/ops/alice/extra").is_none());
+        assert_eq!(store.dump().len(), 4);
+    }
+
+    #[test]
+    fn transactions_apply_only_on_commit() {
+        let store = InMemoryStateStore::new();
+        let scope =
+            StateScope::from_tenant_ctx(&tenant_ctx("dev", "tenant", None, None), "test/1".into());
+        store.write(&scope, "kept", b"old".to_vec());
+
+        let mut txn = StateTransaction::default();
+        txn.write(&store, &scope, "added", b"new".to_vec(), None);
+        txn.delete(&store, &scope, "kept");
+        assert_eq!(txn.read(&store, &scope, "added"), Some(b"new".to_vec()));
+        assert!(txn.read(&store, &scope, "kept").is_none());
+        assert_eq!(txn.list(&store, &scope, ""), vec!["added"]);
+        drop(txn);
+        assert_eq!(store.list(&scope, ""), vec!["kept"]);
+
+        let mut txn = StateTransaction::default();
+        txn.write(&store, &scope, "added", b"new".to_vec(), None);
+        txn.delete(&store, &scope, "kept");
+        txn.commit(&store);
+        assert_eq!(store.list(&scope, ""), vec!["added"]);
+    }
 }
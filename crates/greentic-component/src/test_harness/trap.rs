@@ -0,0 +1,245 @@
+use serde::Serialize;
+use wasmtime::{Trap, WasmBacktrace};
+
+/// A guest trap decoded from wasmtime's backtrace: where it happened in the
+/// component's own code and, when the frames match a known panic path, what
+/// probably caused it.
+#[derive(Debug, Clone, Serialize)]
+pub struct GuestTrap {
+    /// Component export that was running, e.g.
+    /// `greentic:component/node@0.6.0#invoke`.
+    pub export: String,
+    pub operation: String,
+    /// wasmtime's description of the trap, e.g. an `unreachable` executed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trap: Option<String>,
+    /// Innermost frame outside the Rust panic and allocator machinery.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<TrapFrame>,
+    /// Innermost first, symbolicated from DWARF when the wasm carries it and
+    /// from the name section otherwise.
+    pub frames: Vec<TrapFrame>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrapFrame {
+    pub function: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+}
+
+impl std::fmt::Display for GuestTrap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "component trapped in `{}` (operation `{}`)",
+            self.export, self.operation
+        )?;
+        if let Some(trap) = &self.trap {
+            write!(f, ": {trap}")?;
+        }
+        if let Some(location) = &self.location {
+            write!(f, " at {location}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for GuestTrap {}
+
+impl std::fmt::Display for TrapFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.function)?;
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, " ({file}:{line})"),
+            (Some(file), None) => write!(f, " ({file})"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl GuestTrap {
+    /// Decodes `err` when it carries a wasm backtrace or trap; other errors
+    /// yield `None`.
+    pub fn from_error(err: &anyhow::Error, export: &str, operation: &str) -> Option<Self> {
+        let backtrace = err.downcast_ref::<WasmBacktrace>();
+        let trap = err.downcast_ref::<Trap>();
+        if backtrace.is_none() && trap.is_none() {
+            return None;
+        }
+        let frames = backtrace
+            .map(|backtrace| backtrace.frames().iter().flat_map(frames_of).collect())
+            .unwrap_or_default();
+        let location = frames
+            .iter()
+            .find(|frame| !is_runtime_frame(&frame.function))
+            .cloned();
+        Some(Self {
+            export: export.to_string(),
+            operation: operation.to_string(),
+            hint: hint(trap, &frames),
+            trap: trap.map(ToString::to_string),
+            location,
+            frames,
+        })
+    }
+}
+
+/// One entry per inlined DWARF symbol, or a single name-section entry.
+fn frames_of(frame: &wasmtime::FrameInfo) -> Vec<TrapFrame> {
+    let symbols = frame.symbols();
+    if symbols.is_empty() {
+        let function = frame
+            .func_name()
+            .map(demangle)
+            .unwrap_or_else(|| format!("<wasm function {}>", frame.func_index()));
+        return vec![TrapFrame {
+            function,
+            file: None,
+            line: None,
+        }];
+    }
+    symbols
+        .iter()
+        .map(|symbol| TrapFrame {
+            function: symbol
+                .name()
+                .or(frame.func_name())
+                .map(demangle)
+                .unwrap_or_else(|| format!("<wasm function {}>", frame.func_index())),
+            file: symbol.file().map(str::to_string),
+            line: symbol.line(),
+        })
+        .collect()
+}
+
+const RUNTIME_PREFIXES: &[&str] = &[
+    "core::panicking",
+    "core::option::",
+    "core::result::",
+    "core::cell::panic_",
+    "core::slice::index",
+    "std::panicking",
+    "std::sys",
+    "std::alloc",
+    "std::process::abort",
+    "alloc::alloc",
+    "alloc::raw_vec",
+    "__rust",
+    "rust_panic",
+    "abort",
+    "<wasm function",
+];
+
+fn is_runtime_frame(function: &str) -> bool {
+    RUNTIME_PREFIXES
+        .iter()
+        .any(|prefix| function.starts_with(prefix))
+}
+
+/// Common panic sources, recognised by the runtime frames they leave behind.
+fn hint(trap: Option<&Trap>, frames: &[TrapFrame]) -> Option<String> {
+    let has = |needle: &str| frames.iter().any(|frame| frame.function.contains(needle));
+    let message = if has("option::unwrap_failed") || has("option::expect_failed") {
+        "`unwrap()`/`expect()` on a `None`, often a missing config key or optional input field"
+    } else if has("result::unwrap_failed") {
+        "`unwrap()`/`expect()` on an `Err`; return the error as a component error instead"
+    } else if has("panic_bounds_check") || has("slice_index_fail") || has("slice_end_index") {
+        "index out of bounds; check the length of input arrays before indexing"
+    } else if has("div_by_zero") || matches!(trap, Some(Trap::IntegerDivisionByZero)) {
+        "division by zero"
+    } else if has("capacity_overflow") || has("handle_alloc_error") || has("alloc_error") {
+        "allocation failed; the component ran out of memory or asked for an absurd size"
+    } else if matches!(trap, Some(Trap::StackOverflow)) {
+        "stack overflow, usually unbounded recursion"
+    } else if has("serde_json") && has("panicking") {
+        "a panic while handling JSON; validate the input or config shape before unwrapping"
+    } else if has("panicking") {
+        "the component panicked; its panic message is on stderr"
+    } else {
+        return None;
+    };
+    Some(message.to_string())
+}
+
+/// Best-effort demangling of legacy Rust symbols
+/// (`_ZN4core6option13unwrap_failed17h0123456789abcdefE`); other names are
+/// returned unchanged.
+fn demangle(name: &str) -> String {
+    let Some(mut rest) = name
+        .strip_prefix("_ZN")
+        .or_else(|| name.strip_prefix("__ZN"))
+    else {
+        return name.to_string();
+    };
+    let mut parts = Vec::new();
+    while !rest.starts_with('E') {
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let Some(len) = rest[..digits].parse::<usize>().ok() else {
+            return name.to_string();
+        };
+        let Some(part) = rest.get(digits..digits + len) else {
+            return name.to_string();
+        };
+        parts.push(part);
+        rest = &rest[digits + len..];
+    }
+    if parts
+        .last()
+        .is_some_and(|last| last.len() == 17 && last.starts_with('h'))
+    {
+        parts.pop();
+    }
+    parts
+        .iter()
+        .map(|part| {
+            part.replace("..", "::")
+                .replace("$LT$", "<")
+                .replace("$GT$", ">")
+                .replace("$RF$", "&")
+                .replace("$C$", ",")
+                .replace("$u20$", " ")
+                .replace("$u7b$", "{")
+                .replace("$u7d$", "}")
+        })
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn demangles_legacy_symbols_and_hints_at_unwrap() {
+        assert_eq!(
+            demangle("_ZN4core6option13unwrap_failed17h0123456789abcdefE"),
+            "core::option::unwrap_failed"
+        );
+        assert_eq!(
+            demangle("_ZN12my_component6render17h0123456789abcdefE"),
+            "my_component::render"
+        );
+        assert_eq!(demangle("cabi_realloc"), "cabi_realloc");
+
+        let frames = ["core::option::unwrap_failed", "my_component::render"]
+            .map(|function| TrapFrame {
+                function: function.to_string(),
+                file: None,
+                line: None,
+            })
+            .to_vec();
+        assert!(hint(None, &frames).unwrap().contains("missing config key"));
+        assert_eq!(
+            frames
+                .iter()
+                .find(|frame| !is_runtime_frame(&frame.function))
+                .map(|frame| frame.function.as_str()),
+            Some("my_component::render")
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! Run the `greentic-component test` harness from a crate's own `#[test]`s,
+//! without shelling out to the CLI.
+//!
+//! ```no_run
+//! use greentic_component::testing::{CaseRunner, HarnessBuilder, assert_output_matches};
+//! use greentic_component::test_harness::StepPlan;
+//! use serde_json::json;
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let harness = HarnessBuilder::from_manifest("component.manifest.json")
+//!     .config(json!({ "mode": "strict" }))
+//!     .secret("API_TOKEN", "test-token")
+//!     .build()?;
+//! let output = harness.invoke("get", &json!({ "key": "greeting" }))?;
+//! assert_output_matches(&output, &json!({ "value": "hello" }));
+//!
+//! let plan = StepPlan::from_path("tests/steps.yaml".as_ref())?;
+//! CaseRunner::new(&harness).assert_plan(&plan);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The harness grants exactly what the manifest declares and runs with the
+//! `test` command's defaults: dry-run, the `dev`/`default` tenant and
+//! in-memory state that lives as long as the [`Harness`].
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::cmd::test::harness_for_manifest;
+use crate::manifest::{ComponentManifest, parse_manifest_value, resolve_schema_refs};
+use crate::test_harness::{
+    AuditLog, ComponentInvokeError, FsSandbox, InvokeOverrides, StepExpect, StepPlan, TestHarness,
+};
+
+/// Configures a [`Harness`] for one component.
+#[derive(Debug, Clone)]
+pub struct HarnessBuilder {
+    manifest: PathBuf,
+    wasm: Option<PathBuf>,
+    config: Option<Value>,
+    profile: Option<String>,
+    secrets: HashMap<String, String>,
+}
+
+impl HarnessBuilder {
+    /// Starts from a `component.manifest.json`; the wasm defaults to its
+    /// `artifacts.component_wasm`.
+    pub fn from_manifest(path: impl Into<PathBuf>) -> Self {
+        Self {
+            manifest: path.into(),
+            wasm: None,
+            config: None,
+            profile: None,
+            secrets: HashMap::new(),
+        }
+    }
+
+    /// Uses this wasm instead of the manifest's artifact.
+    pub fn wasm(mut self, path: impl Into<PathBuf>) -> Self {
+        self.wasm = Some(path.into());
+        self
+    }
+
+    pub fn config(mut self, config: Value) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Runs under one of the manifest's `profiles.supported` instead of its
+    /// default profile.
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Adds a secret; the manifest must declare `host.secrets` for the
+    /// component to read it.
+    pub fn secret(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.secrets.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Harness> {
+        let raw = fs::read_to_string(&self.manifest)
+            .with_context(|| format!("read manifest {}", self.manifest.display()))?;
+        let mut manifest_value: Value =
+            serde_json::from_str(&raw).context("manifest must be valid JSON")?;
+        let manifest_dir = self
+            .manifest
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        resolve_schema_refs(&mut manifest_value, manifest_dir)
+            .context("resolve manifest schema references")?;
+        let manifest = parse_manifest_value(manifest_value.clone()).context("parse manifest")?;
+        let wasm = self
+            .wasm
+            .unwrap_or_else(|| manifest.wasm_artifact_path(manifest_dir));
+        let wasm_bytes =
+            fs::read(&wasm).with_context(|| format!("read wasm {}", wasm.display()))?;
+        let (inner, sandbox) = harness_for_manifest(
+            wasm_bytes,
+            &manifest_value,
+            &manifest,
+            self.config,
+            AuditLog::default(),
+            None,
+            self.profile.as_deref(),
+        )?;
+        Ok(Harness {
+            inner,
+            _sandbox: sandbox,
+            manifest,
+            secrets: self.secrets,
+        })
+    }
+}
+
+/// A loaded component plus its in-memory host.
+pub struct Harness {
+    inner: TestHarness,
+    // Backs the harness mounts; declared after `inner` so it is dropped last.
+    _sandbox: FsSandbox,
+    manifest: ComponentManifest,
+    secrets: HashMap<String, String>,
+}
+
+impl Harness {
+    /// Invokes `operation` and parses its JSON output.
+    pub fn invoke(&self, operation: &str, input: &Value) -> Result<Value> {
+        self.invoke_with(operation, input, &InvokeOverrides::default())
+    }
+
+    /// Invokes `operation` with per-call config or secrets; the secrets are
+    /// layered over the builder's.
+    pub fn invoke_with(
+        &self,
+        operation: &str,
+        input: &Value,
+        overrides: &InvokeOverrides,
+    ) -> Result<Value> {
+        let mut merged = InvokeOverrides {
+            config: overrides.config.clone(),
+            secrets: self.secrets.clone(),
+        };
+        merged.secrets.extend(overrides.secrets.clone());
+        let outcome = self.inner.invoke_with(operation, input, &merged)?;
+        serde_json::from_str(&outcome.output_json)
+            .with_context(|| format!("`{operation}` returned invalid JSON"))
+    }
+
+    pub fn manifest(&self) -> &ComponentManifest {
+        &self.manifest
+    }
+
+    /// The underlying harness, for state dumps and chaos events.
+    pub fn inner(&self) -> &TestHarness {
+        &self.inner
+    }
+}
+
+/// The code of the component error behind `err`, if the component itself
+/// failed rather than the harness.
+pub fn error_code(err: &anyhow::Error) -> Option<&str> {
+    err.chain()
+        .find_map(|source| source.downcast_ref::<ComponentInvokeError>())
+        .map(|component_err| component_err.code.as_str())
+}
+
+/// Outcome of one plan step.
+#[derive(Debug, Clone)]
+pub struct CaseReport {
+    pub step: usize,
+    pub op: String,
+    pub output: Option<Value>,
+    pub error_code: Option<String>,
+    /// Why the step did not meet its expectation; `None` means it passed.
+    pub failure: Option<String>,
+}
+
+/// Runs [`StepPlan`]s against a [`Harness`], checking each step's `expect`.
+pub struct CaseRunner<'a> {
+    harness: &'a Harness,
+}
+
+impl<'a> CaseRunner<'a> {
+    pub fn new(harness: &'a Harness) -> Self {
+        Self { harness }
+    }
+
+    /// Runs every step, sharing state between them, and reports each one.
+    pub fn run(&self, plan: &StepPlan) -> Vec<CaseReport> {
+        let default_expect = StepExpect::default();
+        plan.steps
+            .iter()
+            .enumerate()
+            .map(|(idx, step)| {
+                let input = step
+                    .input
+                    .clone()
+                    .unwrap_or_else(|| Value::Object(Default::default()));
+                let overrides = InvokeOverrides {
+                    config: step.config.clone(),
+                    secrets: step.secrets.clone().into_iter().collect(),
+                };
+                let expect = step.expect.as_ref().unwrap_or(&default_expect);
+                let mut report = CaseReport {
+                    step: idx + 1,
+                    op: step.op.clone(),
+                    output: None,
+                    error_code: None,
+                    failure: None,
+                };
+                match self.harness.invoke_with(&step.op, &input, &overrides) {
+                    Ok(output) => {
+                        report.failure = expect.check_output(&output).err();
+                        report.output = Some(output);
+                    }
+                    Err(err) => match error_code(&err) {
+                        Some(code) => {
+                            report.failure = expect.check_error(code).err();
+                            report.error_code = Some(code.to_string());
+                        }
+                        None => report.failure = Some(format!("{err:#}")),
+                    },
+                }
+                report
+            })
+            .collect()
+    }
+
+    /// Runs the plan and panics listing every step that failed; returns the
+    /// reports otherwise.
+    pub fn assert_plan(&self, plan: &StepPlan) -> Vec<CaseReport> {
+        let reports = self.run(plan);
+        let failures = reports
+            .iter()
+            .filter_map(|report| {
+                report
+                    .failure
+                    .as_ref()
+                    .map(|failure| format!("step {} ({}): {failure}", report.step, report.op))
+            })
+            .collect::<Vec<_>>();
+        if !failures.is_empty() {
+            panic!(
+                "{} step(s) failed:\n{}",
+                failures.len(),
+                failures.join("\n")
+            );
+        }
+        reports
+    }
+}
+
+/// Panics unless `actual` contains `expected`: objects match as subsets and
+/// `/`-prefixed keys are JSON pointers, as in plan expectations.
+#[track_caller]
+pub fn assert_output_matches(actual: &Value, expected: &Value) {
+    let expect = StepExpect {
+        output: Some(expected.clone()),
+        ..StepExpect::default()
+    };
+    if let Err(message) = expect.check_output(actual) {
+        panic!("{message}");
+    }
+}
+
+/// Panics unless `actual` equals `expected`, listing every difference.
+#[track_caller]
+pub fn assert_output_eq(actual: &Value, expected: &Value) {
+    let expect = StepExpect {
+        output: Some(expected.clone()),
+        exact: true,
+        ..StepExpect::default()
+    };
+    if let Err(message) = expect.check_output(actual) {
+        panic!("{message}");
+    }
+}
+
+/// Panics unless `result` is a component error with `code`.
+#[track_caller]
+pub fn assert_error_code(result: &Result<Value>, code: &str) {
+    match result {
+        Ok(output) => panic!("expected error `{code}`, got output {output}"),
+        Err(err) => match error_code(err) {
+            Some(actual) if actual == code => {}
+            Some(actual) => panic!("expected error `{code}`, got `{actual}`"),
+            None => panic!("expected error `{code}`, got harness error: {err:#}"),
+        },
+    }
+}
@@ -0,0 +1,410 @@
+//! Config schema inference from a component's WIT.
+//!
+//! The component's world (or an interface it imports or exports) declares a
+//! `config` record. Each field becomes a property; `option<T>` fields are
+//! optional and everything else is required. Field doc comments provide the
+//! description plus two directives:
+//!
+//! ```wit
+//! record config {
+//!     /// Greeting shown to users
+//!     /// @default("Hello")
+//!     title: string,
+//!     /// @flow:hidden
+//!     internal-id: option<string>,
+//! }
+//! ```
+//!
+//! `@default(<json>)` sets the default (bare text is taken as a string) and
+//! `@flow:hidden` keeps the field out of generated config flows.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use serde::Serialize;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use thiserror::Error;
+use wit_parser::{Resolve, Type, TypeDefKind, TypeOwner, WorldId, WorldItem};
+
+#[derive(Debug, Error)]
+pub enum WitConfigError {
+    #[error("failed to parse WIT in {path}: {source:#}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: anyhow::Error,
+    },
+    #[error("no world found in WIT package `{0}`")]
+    NoWorld(String),
+    #[error("no `config` record found in WIT world `{0}`")]
+    MissingConfig(String),
+    #[error("config type must be a record")]
+    NotRecord,
+}
+
+/// Config schema inferred from a WIT `config` record.
+#[derive(Debug, Clone, Serialize)]
+pub struct WitConfig {
+    /// Name of the world the record was found in.
+    pub world: String,
+    /// JSON Schema as written to `config_schema`, including descriptions,
+    /// defaults and `x_flow_hidden` markers.
+    pub schema: JsonValue,
+    /// The same structure as used by `describe()` payloads.
+    #[serde(skip)]
+    pub schema_ir: SchemaIr,
+    /// Per-field docs and directives, in declaration order.
+    pub fields: Vec<WitConfigField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WitConfigField {
+    pub name: String,
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<JsonValue>,
+    pub hidden: bool,
+}
+
+/// Parses the WIT package in `wit_dir` and infers the config schema of
+/// `world`.
+///
+/// `world` may be a full reference (`greentic:component/node@0.6.0`) or a
+/// bare world name; when it is `None` or names no world in the package, the
+/// package's first world is used.
+pub fn infer_config_from_wit(
+    wit_dir: &Path,
+    world: Option<&str>,
+) -> Result<WitConfig, WitConfigError> {
+    let mut resolve = Resolve::default();
+    let (pkg, _) = resolve
+        .push_dir(wit_dir)
+        .map_err(|source| WitConfigError::Parse {
+            path: wit_dir.to_path_buf(),
+            source,
+        })?;
+
+    let world_id = select_world(&resolve, pkg, world)?;
+    let world_name = resolve.worlds[world_id].name.clone();
+    let config_id = find_config_type(&resolve, world_id)
+        .ok_or_else(|| WitConfigError::MissingConfig(world_name.clone()))?;
+    let (schema, schema_ir, fields) = schema_from_record(&resolve, config_id)?;
+    Ok(WitConfig {
+        world: world_name,
+        schema,
+        schema_ir,
+        fields,
+    })
+}
+
+fn select_world(
+    resolve: &Resolve,
+    pkg: wit_parser::PackageId,
+    world: Option<&str>,
+) -> Result<WorldId, WitConfigError> {
+    if let Some(target_name) = world.map(parse_world_name)
+        && let Some((id, _)) = resolve
+            .worlds
+            .iter()
+            .find(|(_, world)| world.package == Some(pkg) && world.name == target_name)
+    {
+        return Ok(id);
+    }
+
+    resolve
+        .worlds
+        .iter()
+        .find(|(_, world)| world.package == Some(pkg))
+        .map(|(id, _)| id)
+        .ok_or_else(|| WitConfigError::NoWorld(resolve.packages[pkg].name.name.clone()))
+}
+
+fn parse_world_name(raw: &str) -> &str {
+    let after_slash = raw.split_once('/').map_or(raw, |(_, world)| world);
+    after_slash.split('@').next().unwrap_or(after_slash)
+}
+
+fn find_config_type(resolve: &Resolve, world_id: WorldId) -> Option<wit_parser::TypeId> {
+    let interfaces = interfaces_in_world(resolve, world_id);
+    resolve.types.iter().find_map(|(id, ty)| {
+        let owned_here = match ty.owner {
+            TypeOwner::World(w) => w == world_id,
+            TypeOwner::Interface(i) => interfaces.contains(&i),
+            TypeOwner::None => false,
+        };
+        (owned_here && ty.name.as_deref() == Some("config")).then_some(id)
+    })
+}
+
+fn interfaces_in_world(resolve: &Resolve, world_id: WorldId) -> HashSet<wit_parser::InterfaceId> {
+    let mut ids = HashSet::new();
+    let world = &resolve.worlds[world_id];
+    for item in world.imports.values().chain(world.exports.values()) {
+        if let WorldItem::Interface { id, .. } = item {
+            ids.insert(*id);
+        }
+    }
+    ids
+}
+
+fn schema_from_record(
+    resolve: &Resolve,
+    type_id: wit_parser::TypeId,
+) -> Result<(JsonValue, SchemaIr, Vec<WitConfigField>), WitConfigError> {
+    let type_def = &resolve.types[type_id];
+    let record = match &type_def.kind {
+        TypeDefKind::Record(record) => record,
+        TypeDefKind::Type(inner) => {
+            let shape = map_type(resolve, inner);
+            return Ok((shape.schema, shape.ir, Vec::new()));
+        }
+        _ => return Err(WitConfigError::NotRecord),
+    };
+
+    let mut properties = JsonMap::new();
+    let mut ir_properties = Vec::new();
+    let mut required = Vec::new();
+    let mut fields = Vec::new();
+
+    for field in &record.fields {
+        let directives = DocDirectives::from_docs(&field.docs);
+        let shape = map_type(resolve, &field.ty);
+
+        let mut prop = shape.schema;
+        if let Some(desc) = &directives.description {
+            prop["description"] = JsonValue::String(desc.clone());
+        }
+        if let Some(default) = &directives.default {
+            prop["default"] = default.clone();
+        }
+        if directives.hidden {
+            prop["x_flow_hidden"] = JsonValue::Bool(true);
+        }
+
+        properties.insert(field.name.clone(), prop);
+        ir_properties.push((field.name.clone(), shape.ir));
+        if !shape.optional {
+            required.push(field.name.clone());
+        }
+        fields.push(WitConfigField {
+            name: field.name.clone(),
+            required: !shape.optional,
+            description: directives.description,
+            default: directives.default,
+            hidden: directives.hidden,
+        });
+    }
+
+    let mut schema = JsonMap::new();
+    schema.insert("type".into(), JsonValue::String("object".into()));
+    schema.insert("additionalProperties".into(), JsonValue::Bool(false));
+    schema.insert("properties".into(), JsonValue::Object(properties));
+    if !required.is_empty() {
+        schema.insert(
+            "required".into(),
+            JsonValue::Array(required.iter().cloned().map(JsonValue::String).collect()),
+        );
+    }
+    let ir = SchemaIr::Object {
+        properties: ir_properties.into_iter().collect(),
+        required,
+        additional: AdditionalProperties::Forbid,
+    };
+
+    Ok((JsonValue::Object(schema), ir, fields))
+}
+
+struct TypeShape {
+    schema: JsonValue,
+    ir: SchemaIr,
+    optional: bool,
+}
+
+impl TypeShape {
+    fn required(schema: JsonValue, ir: SchemaIr) -> Self {
+        Self {
+            schema,
+            ir,
+            optional: false,
+        }
+    }
+}
+
+fn string_ir() -> SchemaIr {
+    SchemaIr::String {
+        min_len: None,
+        max_len: None,
+        regex: None,
+        format: None,
+    }
+}
+
+fn map_type(resolve: &Resolve, ty: &Type) -> TypeShape {
+    match ty {
+        Type::Bool => TypeShape::required(json_type("boolean"), SchemaIr::Bool),
+        Type::String | Type::Char => TypeShape::required(json_type("string"), string_ir()),
+        Type::U8
+        | Type::U16
+        | Type::U32
+        | Type::U64
+        | Type::S8
+        | Type::S16
+        | Type::S32
+        | Type::S64 => TypeShape::required(
+            json_type("integer"),
+            SchemaIr::Int {
+                min: None,
+                max: None,
+            },
+        ),
+        Type::F32 | Type::F64 => TypeShape::required(
+            json_type("number"),
+            SchemaIr::Float {
+                min: None,
+                max: None,
+            },
+        ),
+        Type::Id(id) => match &resolve.types[*id].kind {
+            TypeDefKind::Type(inner) => map_type(resolve, inner),
+            TypeDefKind::Option(inner) => TypeShape {
+                optional: true,
+                ..map_type(resolve, inner)
+            },
+            TypeDefKind::Enum(e) => {
+                let values = e
+                    .cases
+                    .iter()
+                    .map(|case| JsonValue::String(case.name.clone()))
+                    .collect();
+                TypeShape::required(
+                    JsonValue::Object(
+                        [
+                            ("type".into(), JsonValue::String("string".into())),
+                            ("enum".into(), JsonValue::Array(values)),
+                        ]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    SchemaIr::Enum {
+                        values: e
+                            .cases
+                            .iter()
+                            .map(|case| ciborium::Value::Text(case.name.clone()))
+                            .collect(),
+                    },
+                )
+            }
+            TypeDefKind::List(inner) => {
+                let mapped = map_type(resolve, inner);
+                TypeShape::required(
+                    JsonValue::Object(
+                        [
+                            ("type".into(), JsonValue::String("array".into())),
+                            ("items".into(), mapped.schema),
+                        ]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    SchemaIr::Array {
+                        items: Box::new(mapped.ir),
+                        min_items: None,
+                        max_items: None,
+                    },
+                )
+            }
+            TypeDefKind::Record(record) => {
+                let mut properties = JsonMap::new();
+                let mut ir_properties = Vec::new();
+                let mut required = Vec::new();
+                for field in &record.fields {
+                    let shape = map_type(resolve, &field.ty);
+                    properties.insert(field.name.clone(), shape.schema);
+                    ir_properties.push((field.name.clone(), shape.ir));
+                    if !shape.optional {
+                        required.push(field.name.clone());
+                    }
+                }
+                let mut schema = JsonMap::new();
+                schema.insert("type".into(), JsonValue::String("object".into()));
+                schema.insert("properties".into(), JsonValue::Object(properties));
+                if !required.is_empty() {
+                    schema.insert(
+                        "required".into(),
+                        JsonValue::Array(required.iter().cloned().map(JsonValue::String).collect()),
+                    );
+                }
+                TypeShape::required(
+                    JsonValue::Object(schema),
+                    SchemaIr::Object {
+                        properties: ir_properties.into_iter().collect(),
+                        required,
+                        additional: AdditionalProperties::Allow,
+                    },
+                )
+            }
+            _ => TypeShape::required(json_type("string"), string_ir()),
+        },
+        _ => TypeShape::required(json_type("string"), string_ir()),
+    }
+}
+
+fn json_type(kind: &str) -> JsonValue {
+    JsonValue::Object(
+        [("type".into(), JsonValue::String(kind.to_string()))]
+            .into_iter()
+            .collect(),
+    )
+}
+
+#[derive(Debug, Default)]
+struct DocDirectives {
+    description: Option<String>,
+    default: Option<JsonValue>,
+    hidden: bool,
+}
+
+impl DocDirectives {
+    fn from_docs(docs: &wit_parser::Docs) -> Self {
+        let Some(raw) = docs.contents.as_deref() else {
+            return Self::default();
+        };
+        let default = extract_default(raw);
+        let hidden = raw.contains("@flow:hidden");
+        let description = render_description(raw);
+        Self {
+            description,
+            default,
+            hidden,
+        }
+    }
+}
+
+fn extract_default(raw: &str) -> Option<JsonValue> {
+    let marker = "@default(";
+    let start = raw.find(marker)?;
+    let after = &raw[start + marker.len()..];
+    let end = after.find(')')?;
+    let body = after[..end].trim();
+    if body.is_empty() {
+        return None;
+    }
+    serde_json::from_str(body)
+        .ok()
+        .or_else(|| Some(JsonValue::String(body.to_string())))
+}
+
+fn render_description(raw: &str) -> Option<String> {
+    let lines = raw
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('@'))
+        .map(str::trim_end)
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
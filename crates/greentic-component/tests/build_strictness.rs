@@ -198,3 +198,243 @@ fn build_fails_without_operations() {
         "manifest schema validation failed: [] has less than 1 item",
     ));
 }
+
+#[test]
+fn invalid_manifest_exits_with_manifest_code_and_json_payload() {
+    let temp = TempDir::new().expect("tempdir");
+    let mut manifest = minimal_manifest();
+    manifest["operations"] = serde_json::json!([]);
+    fs::write(
+        temp.path().join("component.manifest.json"),
+        serde_json::to_string_pretty(&manifest).unwrap(),
+    )
+    .expect("write manifest");
+    let fake_cargo = write_fake_cargo(temp.path());
+
+    let output = cargo_bin_cmd!("greentic-component")
+        .current_dir(temp.path())
+        .env("CARGO", &fake_cargo)
+        .env("GREENTIC_SKIP_NODE_EXPORT_CHECK", "1")
+        .args(["build", "--json"])
+        .assert()
+        .code(3)
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8(output).expect("utf8 stderr");
+    let last_line = stderr.lines().last().expect("stderr output");
+    let payload: JsonValue = serde_json::from_str(last_line).expect("json error payload");
+    assert_eq!(payload["status"], "error");
+    assert_eq!(payload["code"], "manifest_invalid");
+    assert_eq!(payload["exit_code"], 3);
+}
+
+#[test]
+fn build_reuses_cached_describe_for_unchanged_wasm() {
+    use greentic_types::cbor::canonical;
+    use greentic_types::schemas::common::schema_ir::SchemaIr;
+    use greentic_types::schemas::component::v0_6_0::{ComponentDescribe, ComponentInfo};
+
+    let temp = TempDir::new().expect("tempdir");
+    let manifest_path = temp.path().join("component.manifest.json");
+    fs::write(
+        &manifest_path,
+        serde_json::to_string_pretty(&minimal_manifest()).unwrap(),
+    )
+    .expect("write manifest");
+    write_component_wasm(temp.path(), "component.wasm");
+    let fake_cargo = write_fake_cargo(temp.path());
+
+    // The test wasm has no describe export, so a describe payload in the
+    // output can only have come from the cache.
+    let wasm = fs::read(temp.path().join("component.wasm")).expect("read wasm");
+    let hex = blake3::hash(&wasm).to_hex().to_string();
+    let describe = ComponentDescribe {
+        info: ComponentInfo {
+            id: "ai.greentic.cached".to_string(),
+            version: "0.1.0".to_string(),
+            role: "tool".to_string(),
+            display_name: None,
+        },
+        provided_capabilities: Vec::new(),
+        required_capabilities: Vec::new(),
+        metadata: Default::default(),
+        operations: Vec::new(),
+        config_schema: SchemaIr::Bool,
+    };
+    let cache_dir = temp.path().join("target/greentic/describe");
+    fs::create_dir_all(&cache_dir).expect("cache dir");
+    fs::write(
+        cache_dir.join(format!("{hex}.cbor")),
+        canonical::to_canonical_cbor_allow_floats(&describe).expect("encode describe"),
+    )
+    .expect("write cached describe");
+
+    let mut cmd = cargo_bin_cmd!("greentic-component");
+    cmd.current_dir(temp.path())
+        .env("CARGO", &fake_cargo)
+        .env("CARGO_TARGET_DIR", temp.path().join("target"))
+        .env("GREENTIC_SKIP_NODE_EXPORT_CHECK", "1")
+        .arg("build");
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("skipping describe artifacts").not());
+
+    let emitted = fs::read_to_string(temp.path().join("dist/example__0_6_0.describe.json"))
+        .expect("describe json emitted");
+    let emitted: JsonValue = serde_json::from_str(&emitted).expect("describe json");
+    assert_eq!(emitted["info"]["id"], "ai.greentic.cached");
+}
+
+#[test]
+fn strict_build_escalates_warnings_to_errors() {
+    let temp = TempDir::new().expect("tempdir");
+    let manifest_path = temp.path().join("component.manifest.json");
+    let original = serde_json::to_string_pretty(&minimal_manifest()).unwrap();
+    fs::write(&manifest_path, &original).expect("write manifest");
+    write_component_wasm(temp.path(), "component.wasm");
+    let fake_cargo = write_fake_cargo(temp.path());
+
+    let mut cmd = cargo_bin_cmd!("greentic-component");
+    cmd.current_dir(temp.path())
+        .env("CARGO", &fake_cargo)
+        .env("GREENTIC_SKIP_NODE_EXPORT_CHECK", "1")
+        .args(["build", "--no-flow", "--strict"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error[E_MISSING_DESCRIPTION]: operations.handle_message.input_schema",
+        ))
+        .stderr(predicate::str::contains("--strict:"));
+    assert_eq!(
+        fs::read_to_string(&manifest_path).expect("read manifest"),
+        original,
+        "a failed strict build must not rewrite the manifest"
+    );
+
+    let mut cmd = cargo_bin_cmd!("greentic-component");
+    cmd.current_dir(temp.path())
+        .env("CARGO", &fake_cargo)
+        .env("GREENTIC_SKIP_NODE_EXPORT_CHECK", "1")
+        .args(["build", "--no-flow"]);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("warning[W_MISSING_DESCRIPTION]"));
+}
+
+#[test]
+fn build_command_replaces_cargo() {
+    let temp = TempDir::new().expect("tempdir");
+    let manifest_path = temp.path().join("component.manifest.json");
+    let mut manifest = minimal_manifest();
+    manifest["artifacts"]["component_wasm"] = "out/component.wasm".into();
+    manifest["build"] = serde_json::json!({
+        "command": {
+            "run": ["sh", "-c", "mkdir -p \"$OUT\" && cp prebuilt.wasm \"$OUT/component.wasm\""],
+            "env": { "OUT": "out" }
+        }
+    });
+    fs::write(
+        &manifest_path,
+        serde_json::to_string_pretty(&manifest).unwrap(),
+    )
+    .expect("write manifest");
+    write_component_wasm(temp.path(), "prebuilt.wasm");
+
+    let mut cmd = cargo_bin_cmd!("greentic-component");
+    cmd.current_dir(temp.path())
+        .env("GREENTIC_SKIP_NODE_EXPORT_CHECK", "1")
+        .args(["build", "--no-flow", "--cargo", "/nonexistent/cargo"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Running build command `sh -c"));
+
+    let written: JsonValue =
+        serde_json::from_str(&fs::read_to_string(&manifest_path).expect("read manifest"))
+            .expect("manifest json");
+    let prebuilt = fs::read(temp.path().join("prebuilt.wasm")).expect("read prebuilt");
+    assert_eq!(
+        written["hashes"]["component_wasm"],
+        format!("blake3:{}", blake3::hash(&prebuilt).to_hex())
+    );
+    assert_eq!(written["build"], manifest["build"]);
+}
+
+#[test]
+fn package_json_build_command_replaces_cargo() {
+    let temp = TempDir::new().expect("tempdir");
+    fs::write(
+        temp.path().join("component.manifest.json"),
+        serde_json::to_string_pretty(&minimal_manifest()).unwrap(),
+    )
+    .expect("write manifest");
+    fs::write(
+        temp.path().join("package.json"),
+        serde_json::json!({
+            "name": "example",
+            "greentic": { "build": { "run": ["sh", "-c", "cp prebuilt.wasm component.wasm"] } }
+        })
+        .to_string(),
+    )
+    .expect("write package.json");
+    write_component_wasm(temp.path(), "prebuilt.wasm");
+
+    cargo_bin_cmd!("greentic-component")
+        .current_dir(temp.path())
+        .env("GREENTIC_SKIP_NODE_EXPORT_CHECK", "1")
+        .args(["build", "--no-flow", "--cargo", "/nonexistent/cargo"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Running build command `sh -c cp prebuilt.wasm component.wasm`",
+        ));
+    assert!(temp.path().join("component.wasm").is_file());
+}
+
+#[test]
+fn json_log_format_streams_phase_events_on_stderr() {
+    let temp = TempDir::new().expect("tempdir");
+    let mut manifest = minimal_manifest();
+    manifest["build"] = serde_json::json!({
+        "command": { "run": ["sh", "-c", "cp prebuilt.wasm component.wasm"] }
+    });
+    fs::write(
+        temp.path().join("component.manifest.json"),
+        serde_json::to_string_pretty(&manifest).unwrap(),
+    )
+    .expect("write manifest");
+    write_component_wasm(temp.path(), "prebuilt.wasm");
+
+    let output = cargo_bin_cmd!("greentic-component")
+        .current_dir(temp.path())
+        .env("GREENTIC_SKIP_NODE_EXPORT_CHECK", "1")
+        .args(["build", "--no-flow", "--log-format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stderr
+        .clone();
+    let events = String::from_utf8(output)
+        .expect("utf8 stderr")
+        .lines()
+        .filter_map(|line| serde_json::from_str::<JsonValue>(line).ok())
+        .filter(|event| event["command"] == "build")
+        .collect::<Vec<_>>();
+    let phases = |kind: &str| {
+        events
+            .iter()
+            .filter(|event| event["event"] == kind)
+            .map(|event| event["phase"].as_str().unwrap_or_default().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(phases("start"), phases("finish"));
+    for phase in ["manifest", "compile", "describe", "write"] {
+        assert!(phases("finish").contains(&phase.to_string()), "{events:?}");
+    }
+    let compile = events
+        .iter()
+        .find(|event| event["event"] == "finish" && event["phase"] == "compile")
+        .expect("compile finish event");
+    assert_eq!(compile["outcome"], "ok");
+    assert!(compile["duration_ms"].is_u64());
+}
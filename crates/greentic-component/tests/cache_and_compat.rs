@@ -1,4 +1,7 @@
-use greentic_component::{CompatPolicy, ComponentStore};
+use greentic_component::{
+    CacheEncryptionKey, CacheKey, CacheStatus, CacheTampered, CompatPolicy, ComponentLocator,
+    ComponentStore, ProvenanceRequirement, TamperPolicy,
+};
 use tempfile::tempdir;
 use tokio::runtime::Runtime;
 
@@ -15,6 +18,7 @@ fn cache_hit_and_compat_fail() {
             CompatPolicy {
                 required_abi_prefix: "greentic-abi-0".to_string(),
                 required_capabilities: Vec::new(),
+                ..CompatPolicy::default()
             },
         );
 
@@ -34,6 +38,7 @@ fn cache_hit_and_compat_fail() {
             CompatPolicy {
                 required_abi_prefix: "greentic-abi-0".to_string(),
                 required_capabilities: vec!["needs:x".to_string()],
+                ..CompatPolicy::default()
             },
         );
         stricter.add_fs("fake", &component_path);
@@ -45,3 +50,278 @@ fn cache_hit_and_compat_fail() {
         );
     });
 }
+
+#[test]
+fn compat_rules_reject_with_specific_errors() {
+    Runtime::new().unwrap().block_on(async {
+        let td = tempdir().expect("tempdir");
+        let component_path = td.path().join("comp.wasm");
+        std::fs::write(&component_path, b"FAKE_WASM_BYTES").expect("write component");
+
+        let cache_dir = td.path().join("cache");
+        let rejects = |policy: CompatPolicy| {
+            let component_path = component_path.clone();
+            let cache_dir = cache_dir.clone();
+            async move {
+                let mut store = ComponentStore::with_cache_dir(Some(cache_dir), policy);
+                store.add_fs("fake", &component_path);
+                store
+                    .get("fake")
+                    .await
+                    .expect_err("policy should reject")
+                    .to_string()
+            }
+        };
+
+        let err = rejects(CompatPolicy::new().allow_abi_version("greentic-abi-1")).await;
+        assert!(err.contains("ABI 'greentic-abi-0' is not allowed"), "{err}");
+        let err = rejects(CompatPolicy::new().with_max_size(8)).await;
+        assert!(err.contains("larger than the 8 byte limit"), "{err}");
+        let err = rejects(CompatPolicy::new().require_world("greentic:component/component")).await;
+        assert!(err.contains("required worlds"), "{err}");
+        let err =
+            rejects(CompatPolicy::new().with_provenance(ProvenanceRequirement::default())).await;
+        assert!(err.contains("no provenance"), "{err}");
+
+        let mut lenient = ComponentStore::with_cache_dir(
+            Some(cache_dir.clone()),
+            CompatPolicy::new()
+                .with_abi_prefix("greentic-abi-")
+                .allow_abi_version("greentic-abi-0")
+                .with_max_size(1024),
+        );
+        lenient.add_fs("fake", &component_path);
+        lenient
+            .get("fake")
+            .await
+            .expect("policy accepts the artifact");
+    });
+}
+
+#[test]
+fn cache_index_lists_verifies_and_purges() {
+    Runtime::new().unwrap().block_on(async {
+        let td = tempdir().expect("tempdir");
+        let first_path = td.path().join("a.wasm");
+        let second_path = td.path().join("b.wasm");
+        std::fs::write(&first_path, b"FAKE_WASM_A").expect("write a");
+        std::fs::write(&second_path, b"FAKE_WASM_A").expect("write b");
+
+        let cache_dir = td.path().join("cache");
+        let mut store =
+            ComponentStore::with_cache_dir(Some(cache_dir.clone()), CompatPolicy::default());
+        store.add_fs("a", &first_path).add_fs("b", &second_path);
+        let fetched = store.get("a").await.expect("fetch a");
+        store.get("b").await.expect("fetch b");
+
+        let cache = store.cache();
+        let entries = cache.entries().await.expect("entries");
+        assert_eq!(entries.len(), 1, "identical bytes share one entry");
+        assert_eq!(entries[0].id, fetched.id);
+        assert_eq!(entries[0].locators.len(), 2);
+        let hex = fetched.id.0.strip_prefix("sha256:").unwrap();
+        let shown = cache
+            .entry(&hex[..8])
+            .await
+            .expect("lookup")
+            .expect("entry");
+        assert_eq!(shown.size, 11);
+
+        let checks = cache.verify().await.expect("verify");
+        assert_eq!(checks[0].status, CacheStatus::Ok);
+        std::fs::write(cache_dir.join(fetched.id.0.replace(':', "_")), b"tampered")
+            .expect("tamper");
+        let checks = cache.verify().await.expect("verify");
+        assert!(matches!(checks[0].status, CacheStatus::Corrupt { .. }));
+
+        let a = ComponentLocator::Fs {
+            path: first_path.clone(),
+            pin: None,
+        };
+        assert_eq!(
+            cache.purge(&a).await.expect("purge a"),
+            Some(fetched.id.clone())
+        );
+        assert!(cache_dir.join(fetched.id.0.replace(':', "_")).exists());
+        let b = ComponentLocator::Fs {
+            path: second_path,
+            pin: None,
+        };
+        cache.purge(&b).await.expect("purge b");
+        assert!(!cache_dir.join(fetched.id.0.replace(':', "_")).exists());
+        assert!(cache.entries().await.expect("entries").is_empty());
+        assert_eq!(cache.purge(&a).await.expect("purge again"), None);
+    });
+}
+
+#[test]
+fn signed_cache_detects_index_tampering() {
+    Runtime::new().unwrap().block_on(async {
+        let td = tempdir().expect("tempdir");
+        let component_path = td.path().join("comp.wasm");
+        std::fs::write(&component_path, b"FAKE_WASM_BYTES").expect("write component");
+        let cache_dir = td.path().join("cache");
+        let key = CacheKey::new(b"host-secret".to_vec());
+        let signed = |on_tamper| {
+            let mut store =
+                ComponentStore::with_cache_dir(Some(cache_dir.clone()), CompatPolicy::default())
+                    .with_cache_key(key.clone(), on_tamper);
+            store.add_fs("fake", &component_path);
+            store
+        };
+
+        let strict = signed(TamperPolicy::Fail);
+        strict.get("fake").await.expect("first fetch");
+        strict.get("fake").await.expect("signed cache hit");
+        let checks = strict.cache().verify().await.expect("verify");
+        assert_eq!(checks[0].status, CacheStatus::Ok);
+
+        let index_path = cache_dir.join("index.json");
+        let mut index: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&index_path).unwrap()).unwrap();
+        for entry in index["entries"].as_object_mut().unwrap().values_mut() {
+            entry["size"] = serde_json::json!(1);
+        }
+        std::fs::write(&index_path, serde_json::to_vec(&index).unwrap()).unwrap();
+
+        let checks = strict.cache().verify().await.expect("verify");
+        assert_eq!(checks[0].status, CacheStatus::Unsigned);
+        let err = strict.get("fake").await.expect_err("tampered index");
+        assert!(err.downcast_ref::<CacheTampered>().is_some(), "{err:#}");
+
+        // Refetching rewrites and re-signs the entry.
+        signed(TamperPolicy::Refetch)
+            .get("fake")
+            .await
+            .expect("refetch");
+        strict.get("fake").await.expect("re-signed cache hit");
+    });
+}
+
+#[test]
+fn encrypted_cache_seals_bytes_and_reads_them_back() {
+    Runtime::new().unwrap().block_on(async {
+        let td = tempdir().expect("tempdir");
+        let component_path = td.path().join("comp.wasm");
+        std::fs::write(&component_path, b"FAKE_WASM_BYTES").expect("write component");
+        let cache_dir = td.path().join("cache");
+        let key = CacheEncryptionKey::generate();
+        let store = |key: Option<CacheEncryptionKey>| {
+            let mut store =
+                ComponentStore::with_cache_dir(Some(cache_dir.clone()), CompatPolicy::default());
+            if let Some(key) = key {
+                store = store.with_cache_encryption(key);
+            }
+            store.add_fs("fake", &component_path);
+            store
+        };
+
+        // Plaintext left from before encryption is refetched and sealed.
+        let plain = store(None);
+        let fetched = plain.get("fake").await.expect("plaintext fetch");
+        let encrypted = store(Some(key.clone()));
+        assert_eq!(
+            encrypted.cache().verify().await.expect("verify")[0].status,
+            CacheStatus::Unencrypted
+        );
+        encrypted.get("fake").await.expect("refetch");
+
+        let data_path = cache_dir.join(fetched.id.0.replace(':', "_"));
+        let on_disk = std::fs::read(&data_path).expect("cached bytes");
+        assert_ne!(on_disk, b"FAKE_WASM_BYTES");
+        let entry = encrypted
+            .cache()
+            .entry(&fetched.id.0)
+            .await
+            .expect("entry")
+            .expect("indexed");
+        let nonce = entry.nonce.expect("nonce recorded");
+
+        let hit = encrypted.get("fake").await.expect("decrypted cache hit");
+        assert_eq!(hit.bytes.as_ref(), b"FAKE_WASM_BYTES");
+        assert_eq!(hit.id, fetched.id);
+        assert_eq!(
+            encrypted.cache().verify().await.expect("verify")[0].status,
+            CacheStatus::Ok
+        );
+
+        // Each store seals under a fresh nonce.
+        std::fs::remove_file(&data_path).unwrap();
+        encrypted.get("fake").await.expect("refetch after removal");
+        let entry = encrypted
+            .cache()
+            .entry(&fetched.id.0)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(entry.nonce.as_deref(), Some(nonce.as_str()));
+
+        let err = store(None).get("fake").await.expect_err("no key");
+        assert!(err.to_string().contains("encrypted"), "{err:#}");
+        let wrong = store(Some(CacheEncryptionKey::generate()));
+        assert_eq!(
+            wrong.cache().verify().await.expect("verify")[0].status,
+            CacheStatus::Unreadable
+        );
+        let refetched = wrong.get("fake").await.expect("wrong key refetches");
+        assert_eq!(refetched.bytes.as_ref(), b"FAKE_WASM_BYTES");
+    });
+}
+
+#[test]
+fn system_cache_is_read_but_never_written() {
+    Runtime::new().unwrap().block_on(async {
+        let td = tempdir().expect("tempdir");
+        let baked = td.path().join("baked.wasm");
+        let fresh = td.path().join("fresh.wasm");
+        std::fs::write(&baked, b"BAKED_WASM_BYTES").expect("write component");
+        std::fs::write(&fresh, b"FRESH_WASM_BYTES").expect("write component");
+        let system_dir = td.path().join("system");
+        let user_dir = td.path().join("user");
+
+        // The operator provisions the system cache ahead of time.
+        let mut provisioning =
+            ComponentStore::with_cache_dir(Some(system_dir.clone()), CompatPolicy::default());
+        provisioning.add_fs("baked", &baked);
+        let provisioned = provisioning.get("baked").await.expect("provision");
+        std::fs::remove_file(&baked).expect("remove source");
+        let listing = |dir: &std::path::Path| {
+            let mut names = walk(dir);
+            names.sort();
+            names
+        };
+        let before = listing(&system_dir);
+
+        let mut store =
+            ComponentStore::with_cache_dir(Some(user_dir.clone()), CompatPolicy::default())
+                .with_system_cache(&system_dir);
+        store.add_fs("baked", &baked).add_fs("fresh", &fresh);
+        let hit = store
+            .get("baked")
+            .await
+            .expect("served from the system cache");
+        assert_eq!(hit.id, provisioned.id);
+        assert_eq!(hit.bytes.as_ref(), b"BAKED_WASM_BYTES");
+        assert!(store.cache().entries().await.expect("entries").is_empty());
+
+        let fetched = store.get("fresh").await.expect("fetch into the user cache");
+        let entries = store.cache().entries().await.expect("entries");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, fetched.id);
+        assert_eq!(listing(&system_dir), before);
+    });
+}
+
+fn walk(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    std::fs::read_dir(dir)
+        .expect("read dir")
+        .flat_map(|entry| {
+            let path = entry.expect("entry").path();
+            if path.is_dir() {
+                walk(&path)
+            } else {
+                vec![path]
+            }
+        })
+        .collect()
+}
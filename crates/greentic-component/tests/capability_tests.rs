@@ -1,6 +1,7 @@
 use std::fs;
 use std::path::Path;
 
+use greentic_component::capabilities::validate_capabilities;
 use greentic_component::manifest::parse_manifest;
 use greentic_component::security::{Profile, enforce_capabilities};
 
@@ -28,3 +29,12 @@ fn profile_allows_whitelisted_capabilities() {
     let profile = Profile::new(manifest.capabilities.clone());
     enforce_capabilities(&manifest, profile).expect("profile should allow matching capabilities");
 }
+
+#[test]
+fn events_capability_must_enable_a_direction() {
+    let mut caps = manifest().capabilities;
+    let events = caps.host.events.as_mut().expect("fixture declares events");
+    events.outbound = false;
+    let err = validate_capabilities(&caps).expect_err("events without a direction");
+    assert_eq!(err.path, "host.events");
+}
@@ -0,0 +1,141 @@
+#![cfg(feature = "cli")]
+
+use greentic_component::cmd::caps::suggest_capabilities;
+use greentic_component::test_harness::{AuditEntry, AuditKind, AuditOutcome};
+use serde_json::{Value, json};
+
+fn entry(kind: AuditKind, target: &str, outcome: AuditOutcome) -> AuditEntry {
+    AuditEntry {
+        timestamp_ms: 0,
+        kind,
+        target: target.to_string(),
+        outcome,
+        detail: None,
+    }
+}
+
+fn declared() -> Value {
+    let raw = include_str!("fixtures/manifests/valid.component.json");
+    let manifest: Value = serde_json::from_str(raw).expect("fixture json");
+    manifest["capabilities"].clone()
+}
+
+#[test]
+fn narrows_capabilities_to_observed_usage() {
+    let entries = vec![
+        entry(
+            AuditKind::Http,
+            "GET https://api.example.com/v1/items",
+            AuditOutcome::Ok,
+        ),
+        entry(
+            AuditKind::Http,
+            "POST https://api.example.com/v1/items",
+            AuditOutcome::Ok,
+        ),
+        entry(AuditKind::Secret, "KV_API_TOKEN", AuditOutcome::Ok),
+        entry(AuditKind::StateRead, "cursor", AuditOutcome::Miss),
+    ];
+
+    let suggestion = suggest_capabilities(&declared(), &entries).expect("suggest");
+
+    assert_eq!(suggestion.http_domains, vec!["api.example.com".to_string()]);
+    assert!(suggestion.unused.is_empty(), "{:?}", suggestion.unused);
+    assert!(suggestion.undeclared.is_empty());
+    let host = &suggestion.capabilities["host"];
+    assert_eq!(
+        host["http"],
+        json!({"client": true, "server": false, "domains": ["api.example.com"]})
+    );
+    assert_eq!(host["state"], json!({"read": true, "write": false}));
+    assert_eq!(host["secrets"]["required"][0]["key"], "KV_API_TOKEN");
+    // Not observable through the audit log, so left as declared.
+    assert_eq!(
+        host["messaging"],
+        json!({"inbound": true, "outbound": true})
+    );
+}
+
+#[test]
+fn flags_unused_and_undeclared_capabilities() {
+    let entries = vec![
+        entry(AuditKind::StateWrite, "cursor", AuditOutcome::Denied),
+        entry(AuditKind::Secret, "OTHER_TOKEN", AuditOutcome::Denied),
+    ];
+
+    let suggestion = suggest_capabilities(&declared(), &entries).expect("suggest");
+
+    assert!(suggestion.http_domains.is_empty());
+    assert_eq!(
+        suggestion.unused,
+        vec![
+            "host.http.client".to_string(),
+            "host.secrets.required[KV_API_TOKEN]".to_string(),
+            "host.state.read".to_string(),
+        ]
+    );
+    assert_eq!(
+        suggestion.undeclared,
+        vec![
+            "host.secrets.required[OTHER_TOKEN]".to_string(),
+            "host.state.write".to_string(),
+        ]
+    );
+    let host = &suggestion.capabilities["host"];
+    assert!(host.get("http").is_none());
+    assert!(host.get("secrets").is_none());
+    assert_eq!(host["state"], json!({"read": false, "write": true}));
+}
+
+#[test]
+fn generated_fixtures_run_as_cases() {
+    let fixture = std::path::Path::new("tests/contract/fixtures/component_v0_6_0");
+    let temp = tempfile::TempDir::new().expect("temp dir");
+    for file in ["component.manifest.json", "component.wasm"] {
+        std::fs::copy(fixture.join(file), temp.path().join(file)).expect("copy fixture");
+    }
+
+    assert_cmd::cargo::cargo_bin_cmd!("greentic-component")
+        .args(["fixtures", "gen", "--manifest"])
+        .arg(temp.path())
+        .assert()
+        .success();
+    let cases = temp.path().join("tests/cases");
+    assert!(cases.join("handle-message/valid-minimal.json").is_file());
+    assert!(
+        cases
+            .join("handle-message/invalid-input-missing.json")
+            .is_file()
+    );
+
+    assert_cmd::cargo::cargo_bin_cmd!("greentic-component")
+        .args(["caps", "suggest", "--json", "--manifest"])
+        .arg(temp.path())
+        .arg("--cases")
+        .arg(&cases)
+        .assert()
+        .success();
+
+    std::fs::write(
+        cases.join("handle-message/wrong-expectation.json"),
+        json!({ "op": "handle_message", "input": { "input": "hi" }, "expect": "error" })
+            .to_string(),
+    )
+    .expect("write case");
+    let output = assert_cmd::cargo::cargo_bin_cmd!("greentic-component")
+        .args(["caps", "suggest", "--manifest"])
+        .arg(temp.path())
+        .arg("--cases")
+        .arg(&cases)
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8_lossy(&output);
+    assert!(
+        stderr.contains("1 case(s) did not end as expected"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("wrong-expectation.json"), "{stderr}");
+}
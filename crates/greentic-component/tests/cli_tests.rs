@@ -331,6 +331,54 @@ fn store_fetch_accepts_directory_source() {
     assert_eq!(fetched, b"fake-wasm");
 }
 
+#[test]
+fn store_fetch_resolves_catalog_names() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let source_path = temp.path().join("component.wasm");
+    fs::write(&source_path, b"fake-wasm").unwrap();
+    let catalog_path = temp.path().join("components.toml");
+    fs::write(
+        &catalog_path,
+        format!(
+            "[components.fake]\nlocator = \"file://{}\"\n\n[components.pinned]\nlocator = \"file://{}\"\ndigest = \"sha256:00\"\n",
+            source_path.display(),
+            source_path.display()
+        ),
+    )
+    .unwrap();
+
+    let out_dir = temp.path().join("out");
+    let cache_dir = temp.path().join("cache");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("greentic-component");
+    cmd.arg("store")
+        .arg("fetch")
+        .arg("--out")
+        .arg(&out_dir)
+        .arg("--cache-dir")
+        .arg(&cache_dir)
+        .arg("--catalog")
+        .arg(&catalog_path)
+        .arg("fake")
+        .assert()
+        .success();
+    let fetched = fs::read(out_dir.join("component.wasm")).expect("fetched component");
+    assert_eq!(fetched, b"fake-wasm");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("greentic-component");
+    cmd.arg("store")
+        .arg("fetch")
+        .arg("--out")
+        .arg(temp.path().join("pinned"))
+        .arg("--cache-dir")
+        .arg(&cache_dir)
+        .arg("--catalog")
+        .arg(&catalog_path)
+        .arg("pinned")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("digest mismatch for `pinned`"));
+}
+
 #[test]
 fn test_command_writes_trace_on_failure() {
     let temp = tempfile::TempDir::new().unwrap();
@@ -360,10 +408,80 @@ fn test_command_writes_trace_on_failure() {
 
     let trace = fs::read_to_string(&trace_path).expect("trace should be written");
     let value: Value = serde_json::from_str(&trace).expect("trace JSON");
-    assert_eq!(value["trace_version"].as_u64(), Some(1));
+    assert_eq!(value["trace_version"].as_u64(), Some(2));
+    assert!(value["steps"].as_array().is_some());
+    assert_eq!(value["random"], "fixed");
     assert!(value["error"]["code"].as_str().is_some());
 }
 
+#[test]
+fn errors_list_prints_the_catalog() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let manifest_path = temp.path().join("component.manifest.json");
+    let fixture =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/manifests/valid.component.json");
+    let mut manifest: Value = serde_json::from_str(&fs::read_to_string(fixture).unwrap()).unwrap();
+    manifest["errors"] = serde_json::json!([
+        { "code": "upstream.timeout", "retryable": true, "description": "Upstream did not answer" }
+    ]);
+    fs::write(&manifest_path, manifest.to_string()).unwrap();
+
+    let output = assert_cmd::cargo::cargo_bin_cmd!("greentic-component")
+        .arg("errors")
+        .arg("list")
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--operation")
+        .arg("handle_message")
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let listed: Value = serde_json::from_slice(&output).expect("errors JSON");
+    assert_eq!(listed[0]["code"], "upstream.timeout");
+    assert_eq!(listed[0]["retryable"], true);
+}
+
+#[test]
+fn client_gen_writes_a_typescript_client() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let out = temp.path().join("client/echo.ts");
+    let fixture =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/manifests/valid.component.json");
+
+    assert_cmd::cargo::cargo_bin_cmd!("greentic-component")
+        .arg("client")
+        .arg("gen")
+        .arg("--manifest")
+        .arg(&fixture)
+        .arg("--lang")
+        .arg("ts")
+        .arg("--out")
+        .arg(&out)
+        .assert()
+        .success();
+    let source = fs::read_to_string(&out).expect("client written");
+    assert!(source.contains("async handleMessage(input: HandleMessageInput)"));
+}
+
+#[test]
+fn conformance_passes_against_the_reference_host() {
+    let output = assert_cmd::cargo::cargo_bin_cmd!("greentic-component")
+        .arg("conformance")
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: Value = serde_json::from_slice(&output).expect("report JSON");
+    let cases = report["cases"].as_array().expect("cases");
+    assert!(!cases.is_empty());
+    assert!(cases.iter().all(|case| case["passed"] == true));
+}
+
 #[test]
 fn build_fails_on_empty_operation_schemas() {
     let component = TestComponent::new(TEST_WIT, &["describe"]);
@@ -379,6 +497,10 @@ fn build_fails_on_empty_operation_schemas() {
         no_validate: true,
         json: false,
         permissive: false,
+        strict: false,
+        profile: None,
+        provenance: false,
+        attestation: false,
     };
 
     let err = build::run(args).expect_err("build should fail when schemas are empty");
@@ -403,11 +525,97 @@ fn build_permissive_allows_empty_operation_schemas() {
         no_validate: true,
         json: false,
         permissive: true,
+        strict: false,
+        profile: None,
+        provenance: false,
+        attestation: false,
     };
 
     build::run(args).expect("permissive build should succeed");
 }
 
+#[test]
+fn build_records_provenance_and_attestation() {
+    let component = TestComponent::new(TEST_WIT, &["describe"]);
+    let root = component.dir.path();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args([
+                "-c",
+                "user.name=greentic",
+                "-c",
+                "user.email=dev@greentic.ai",
+            ])
+            .args(args)
+            .current_dir(root)
+            .status()
+            .expect("git should run");
+        assert!(status.success(), "git {args:?} failed");
+    };
+    git(&["init", "--quiet"]);
+    git(&["add", "."]);
+    git(&["commit", "--quiet", "-m", "init"]);
+    git(&[
+        "remote",
+        "add",
+        "origin",
+        "https://ci-token@github.com/greentic-ai/demo.git",
+    ]);
+
+    let args = BuildArgs {
+        manifest: component.manifest_path.clone(),
+        cargo_bin: Some(true_bin()),
+        no_flow: true,
+        no_infer_config: true,
+        no_write_schema: true,
+        force_write_schema: false,
+        no_validate: true,
+        json: false,
+        permissive: false,
+        strict: false,
+        profile: None,
+        provenance: true,
+        attestation: true,
+    };
+    build::run(args).expect("build should succeed");
+
+    let manifest: Value =
+        serde_json::from_str(&fs::read_to_string(&component.manifest_path).unwrap()).unwrap();
+    let provenance = &manifest["provenance"];
+    assert_eq!(provenance["git_commit"].as_str().unwrap().len(), 40);
+    assert_eq!(provenance["git_dirty"], Value::Bool(false));
+    assert!(
+        provenance["builder"]
+            .as_str()
+            .unwrap()
+            .starts_with("greentic-component@")
+    );
+
+    let attestation = fs::read_dir(root.join("dist"))
+        .unwrap()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| path.to_string_lossy().ends_with(".intoto.json"))
+        .expect("attestation should be written");
+    let statement: Value = serde_json::from_str(&fs::read_to_string(attestation).unwrap()).unwrap();
+    assert_eq!(statement["predicateType"], "https://slsa.dev/provenance/v1");
+    assert_eq!(
+        statement["subject"][0]["digest"]["sha256"]
+            .as_str()
+            .unwrap()
+            .len(),
+        64
+    );
+    let source = &statement["predicate"]["buildDefinition"]["resolvedDependencies"][0];
+    assert_eq!(
+        source["uri"],
+        format!(
+            "git+https://github.com/greentic-ai/demo.git@{}",
+            provenance["git_commit"].as_str().unwrap()
+        )
+    );
+}
+
 fn true_bin() -> std::path::PathBuf {
     if let Some(path) = std::env::var_os("TRUE_BIN") {
         return std::path::PathBuf::from(path);
@@ -441,3 +649,306 @@ fn rewrite_operation_schemas_to_empty(manifest_path: &Path) {
     )
     .unwrap();
 }
+
+#[test]
+fn validate_reports_located_diagnostics_in_json() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let raw = fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/manifests/valid.component.json"),
+    )
+    .unwrap();
+    let mut manifest: Value = serde_json::from_str(&raw).unwrap();
+    manifest["default_operation"] = json!("missing_op");
+    let text = serde_json::to_string_pretty(&manifest).unwrap();
+    let manifest_path = temp.path().join("component.manifest.json");
+    fs::write(&manifest_path, &text).unwrap();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("greentic-component");
+    let assert = cmd
+        .arg("validate")
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--json")
+        .assert()
+        .code(3);
+    let output = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8 stdout");
+    let report: Value = serde_json::from_str(&output).expect("json");
+    assert_eq!(report["valid"], false);
+    let diagnostic = &report["diagnostics"][0];
+    assert_eq!(diagnostic["code"], "manifest.default_operation.unknown");
+    assert_eq!(diagnostic["pointer"], "/default_operation");
+    assert!(diagnostic["suggestion"].is_string());
+    let line = diagnostic["line"].as_u64().expect("line") as usize;
+    assert!(
+        text.lines()
+            .nth(line - 1)
+            .unwrap()
+            .contains("\"default_operation\"")
+    );
+}
+
+#[test]
+fn index_build_writes_a_signed_catalog_of_packs() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let registry = temp.path().join("registry");
+    let pack = registry.join("echo");
+    fs::create_dir_all(pack.join("bin")).unwrap();
+    let wasm = b"\0asm\x01\0\0\0";
+    fs::write(pack.join("bin/component.wasm"), wasm).unwrap();
+    let raw = fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/manifests/valid.component.json"),
+    )
+    .unwrap();
+    let mut manifest: Value = serde_json::from_str(&raw).unwrap();
+    manifest["hashes"]["component_wasm"] = json!(format!("blake3:{}", blake3::hash(wasm).to_hex()));
+    fs::write(
+        pack.join("component.manifest.json"),
+        serde_json::to_string_pretty(&manifest).unwrap(),
+    )
+    .unwrap();
+    let key = temp.path().join("registry.key");
+    fs::write(&key, b"registry-secret").unwrap();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("greentic-component");
+    cmd.arg("index")
+        .arg("build")
+        .arg(&registry)
+        .arg("--key-file")
+        .arg(&key)
+        .assert()
+        .success();
+    let index: Value =
+        serde_json::from_str(&fs::read_to_string(registry.join("index.json")).unwrap()).unwrap();
+    let entry = &index["components"][0];
+    assert_eq!(entry["id"], manifest["id"]);
+    assert_eq!(entry["locator"], "echo/bin/component.wasm");
+    assert!(entry["digest"].as_str().unwrap().starts_with("sha256:"));
+    assert!(
+        entry["capabilities"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("host.http.client"))
+    );
+    assert_eq!(entry["schemas"]["operations"][0]["name"], "handle_message");
+    assert_eq!(index["signature"]["algorithm"], "hmac-sha256");
+
+    let verify = |expect_ok: bool| {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("greentic-component");
+        let assert = cmd
+            .arg("index")
+            .arg("verify")
+            .arg(registry.join("index.json"))
+            .arg("--key-file")
+            .arg(&key)
+            .assert();
+        if expect_ok {
+            assert.success();
+        } else {
+            assert.failure();
+        }
+    };
+    verify(true);
+    fs::write(pack.join("bin/component.wasm"), b"\0asm\x01\0\0\0\0").unwrap();
+    verify(false);
+}
+
+#[test]
+fn search_filters_registry_indexes_by_capability_and_abi() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let entry = |id: &str, version: &str, abi: &str, capabilities: Value| {
+        json!({
+            "id": id,
+            "name": id,
+            "version": version,
+            "world": format!("greentic:component/component@{abi}"),
+            "locator": format!("{id}/{version}/component.wasm"),
+            "digest": "sha256:00",
+            "wasm_hash": "blake3:00",
+            "size": 8,
+            "supports": ["messaging"],
+            "capabilities": capabilities,
+            "schemas": {}
+        })
+    };
+    let index = json!({
+        "format": 1,
+        "components": [
+            entry("acme.fetch", "0.1.0", "0.6.0", json!(["host.http.client"])),
+            entry("acme.fetch", "0.2.0", "0.6.0", json!(["host.http.client"])),
+            entry("acme.legacy-fetch", "1.0.0", "0.5.0", json!(["host.http.client"])),
+            entry("acme.echo", "0.1.0", "0.6.0", json!([])),
+        ]
+    });
+    fs::create_dir_all(temp.path().join("registry")).unwrap();
+    fs::write(temp.path().join("registry/index.json"), index.to_string()).unwrap();
+    let catalog = temp.path().join("components.toml");
+    fs::write(
+        &catalog,
+        "[registries.acme]\nindex = \"registry/index.json\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("greentic-component");
+    let output = cmd
+        .arg("search")
+        .arg("fetch")
+        .arg("--capability")
+        .arg("host.http")
+        .arg("--abi")
+        .arg("0.6")
+        .arg("--catalog")
+        .arg(&catalog)
+        .arg("--json")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let hits: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let hits = hits.as_array().unwrap();
+    assert_eq!(hits.len(), 1, "{hits:?}");
+    assert_eq!(hits[0]["id"], "acme.fetch");
+    assert_eq!(hits[0]["version"], "0.2.0");
+    assert_eq!(hits[0]["registry"], "acme");
+    assert!(
+        hits[0]["locator"]
+            .as_str()
+            .unwrap()
+            .ends_with("acme.fetch/0.2.0/component.wasm")
+    );
+}
+
+#[test]
+fn graph_renders_dependencies_and_fails_on_cycles() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let raw = fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/manifests/valid.component.json"),
+    )
+    .unwrap();
+    let echo: Value = serde_json::from_str(&raw).unwrap();
+    let mut app = echo.clone();
+    app["id"] = json!("acme.app");
+    app["dependencies"] = json!([{
+        "id": "com.greentic.demo.echo",
+        "version": "^0.3",
+        "locator": "../echo",
+        "exports": ["handle_message"]
+    }]);
+    let write = |dir: &str, manifest: &Value| {
+        fs::create_dir_all(temp.path().join(dir)).unwrap();
+        fs::write(
+            temp.path().join(dir).join("component.manifest.json"),
+            serde_json::to_string_pretty(manifest).unwrap(),
+        )
+        .unwrap();
+    };
+    write("app", &app);
+    write("echo", &echo);
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("greentic-component");
+    let output = cmd
+        .arg("graph")
+        .arg("--manifest")
+        .arg(temp.path().join("app"))
+        .arg("--format")
+        .arg("mermaid")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("n0 -->|\"^0.3\"| n1"), "{stdout}");
+    assert!(
+        stdout.contains("com.greentic.demo.echo<br/>0.3.0"),
+        "{stdout}"
+    );
+
+    let mut echo = echo;
+    echo["dependencies"] = json!([{ "id": "acme.app", "version": "*", "locator": "../app" }]);
+    write("echo", &echo);
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("greentic-component");
+    let output = cmd
+        .arg("graph")
+        .arg("--manifest")
+        .arg(temp.path().join("app"))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("dependency cycle: acme.app -> com.greentic.demo.echo -> acme.app"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn validate_checks_schema_quality_and_wasm_hash() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let raw = fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/manifests/valid.component.json"),
+    )
+    .unwrap();
+    let mut manifest: Value = serde_json::from_str(&raw).unwrap();
+    manifest.as_object_mut().unwrap().remove("dev_flows");
+    manifest["operations"][0]["output_schema"] = json!({});
+    let manifest_path = temp.path().join("component.manifest.json");
+    fs::write(
+        &manifest_path,
+        serde_json::to_string_pretty(&manifest).unwrap(),
+    )
+    .unwrap();
+    let wasm = b"\0asm\x01\0\0\0";
+    fs::create_dir_all(temp.path().join("bin")).unwrap();
+    fs::write(temp.path().join("bin/component.wasm"), wasm).unwrap();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("greentic-component");
+    let assert = cmd
+        .arg("validate")
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--json")
+        .assert()
+        .code(3);
+    let report: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    assert_eq!(report["checks"]["manifest"], "passed");
+    assert_eq!(report["checks"]["schema_quality"], "failed");
+    assert_eq!(report["checks"]["hash"], "failed");
+    let codes = report["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["code"].as_str().unwrap(),
+                diagnostic["pointer"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        codes,
+        vec![
+            (
+                "manifest.operation.schema_empty",
+                "/operations/0/output_schema"
+            ),
+            ("manifest.hashes.mismatch", "/hashes/component_wasm"),
+        ]
+    );
+
+    manifest["hashes"]["component_wasm"] = json!(format!("blake3:{}", blake3::hash(wasm).to_hex()));
+    fs::write(
+        &manifest_path,
+        serde_json::to_string_pretty(&manifest).unwrap(),
+    )
+    .unwrap();
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("greentic-component");
+    let assert = cmd
+        .arg("validate")
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--permissive")
+        .arg("--json")
+        .assert()
+        .success();
+    let report: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    assert_eq!(report["valid"], true);
+    assert_eq!(report["checks"]["hash"], "passed");
+    assert_eq!(report["diagnostics"][0]["severity"], "warning");
+}
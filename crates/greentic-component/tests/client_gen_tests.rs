@@ -0,0 +1,67 @@
+use std::fs;
+use std::path::Path;
+
+use greentic_component::{ClientLang, generate_client, parse_manifest_value};
+use serde_json::{Value, json};
+
+fn manifest() -> Value {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/manifests/valid.component.json");
+    let mut manifest: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    manifest["operations"][0]["input_schema"] = json!({
+        "type": "object",
+        "required": ["message", "mode"],
+        "properties": {
+            "message": { "type": "string", "description": "Text to echo" },
+            "mode": { "enum": ["plain", "shout-loud"] },
+            "repeat": { "type": "integer" },
+            "tags": { "type": "array", "items": { "type": "string" } },
+            "meta": {
+                "type": "object",
+                "properties": { "trace-id": { "type": ["string", "null"] } }
+            },
+            "type": { "type": "boolean" }
+        }
+    });
+    manifest
+}
+
+#[test]
+fn rust_client_has_typed_structs_and_one_method_per_operation() {
+    let manifest = parse_manifest_value(manifest()).expect("manifest");
+    let source = generate_client(&manifest, ClientLang::Rust);
+
+    assert!(source.contains("pub struct HandleMessageInput {"));
+    assert!(source.contains("    pub message: String,"));
+    assert!(source.contains("    pub mode: HandleMessageInputMode,"));
+    assert!(source.contains("    pub repeat: Option<i64>,"));
+    assert!(source.contains("    pub tags: Option<Vec<String>>,"));
+    assert!(source.contains("    pub r#type: Option<bool>,"));
+    assert!(source.contains("pub struct HandleMessageInputMeta {"));
+    assert!(source.contains("    #[serde(rename = \"trace-id\")]\n"));
+    assert!(source.contains("    pub trace_id: Option<Option<String>>,"));
+    assert!(source.contains("    #[serde(rename = \"shout-loud\")]\n    ShoutLoud,"));
+    assert!(source.contains("pub struct HandleMessageOutput {"));
+    assert!(source.contains("pub struct EchoComponentClient<'a> {"));
+    assert!(source.contains(
+        "pub fn handle_message(&self, input: &HandleMessageInput) -> Result<HandleMessageOutput, CompError>"
+    ));
+    assert!(source.contains("greentic_component_runtime::invoke(self.handle, \"handle_message\""));
+}
+
+#[test]
+fn ts_client_has_interfaces_and_async_methods() {
+    let manifest = parse_manifest_value(manifest()).expect("manifest");
+    let source = generate_client(&manifest, ClientLang::Ts);
+
+    assert!(source.contains("export interface HandleMessageInput {"));
+    assert!(source.contains("  /** Text to echo */\n  message: string;"));
+    assert!(source.contains("  repeat?: number;"));
+    assert!(source.contains("  tags?: string[];"));
+    assert!(source.contains("  \"trace-id\"?: string | null;"));
+    assert!(source.contains("export type HandleMessageInputMode = \"plain\" | \"shout-loud\";"));
+    assert!(source.contains("export class EchoComponentClient {"));
+    assert!(source.contains(
+        "async handleMessage(input: HandleMessageInput): Promise<HandleMessageOutput> {"
+    ));
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use greentic_component::compat::{ChangeKind, VersionBump, check_compat};
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use greentic_types::schemas::component::v0_6_0::{
+    ComponentDescribe, ComponentInfo, ComponentOperation, ComponentRunInput, ComponentRunOutput,
+    schema_hash,
+};
+
+fn string() -> SchemaIr {
+    SchemaIr::String {
+        min_len: None,
+        max_len: None,
+        regex: None,
+        format: None,
+    }
+}
+
+fn object(props: Vec<(&str, SchemaIr)>, required: &[&str]) -> SchemaIr {
+    SchemaIr::Object {
+        properties: props
+            .into_iter()
+            .map(|(name, schema)| (name.to_string(), schema))
+            .collect(),
+        required: required.iter().map(|name| name.to_string()).collect(),
+        additional: AdditionalProperties::Forbid,
+    }
+}
+
+fn operation(id: &str, input: SchemaIr, output: SchemaIr, config: &SchemaIr) -> ComponentOperation {
+    ComponentOperation {
+        id: id.to_string(),
+        display_name: None,
+        schema_hash: schema_hash(&input, &output, config).expect("schema hash"),
+        input: ComponentRunInput { schema: input },
+        output: ComponentRunOutput { schema: output },
+        defaults: BTreeMap::new(),
+        redactions: Vec::new(),
+        constraints: BTreeMap::new(),
+    }
+}
+
+fn describe(version: &str, operations: Vec<ComponentOperation>) -> ComponentDescribe {
+    ComponentDescribe {
+        info: ComponentInfo {
+            id: "com.greentic.demo".to_string(),
+            version: version.to_string(),
+            role: "tool".to_string(),
+            display_name: None,
+        },
+        provided_capabilities: Vec::new(),
+        required_capabilities: Vec::new(),
+        metadata: BTreeMap::new(),
+        operations,
+        config_schema: config(),
+    }
+}
+
+fn config() -> SchemaIr {
+    object(vec![("enabled", SchemaIr::Bool)], &["enabled"])
+}
+
+fn baseline(version: &str) -> ComponentDescribe {
+    describe(
+        version,
+        vec![operation(
+            "run",
+            object(vec![("name", string())], &["name"]),
+            object(vec![("ok", SchemaIr::Bool)], &["ok"]),
+            &config(),
+        )],
+    )
+}
+
+#[test]
+fn identical_describes_are_compatible() {
+    let report = check_compat(&baseline("1.0.0"), &baseline("1.0.1"));
+    assert!(report.is_compatible());
+    assert!(report.changes.is_empty());
+    assert_eq!(report.required_bump, VersionBump::None);
+    assert!(report.version_ok);
+}
+
+#[test]
+fn additive_changes_need_a_minor_bump() {
+    let new = describe(
+        "0.1.1",
+        vec![
+            operation(
+                "run",
+                object(vec![("name", string()), ("locale", string())], &["name"]),
+                object(vec![("ok", SchemaIr::Bool)], &["ok"]),
+                &config(),
+            ),
+            operation(
+                "preview",
+                object(vec![], &[]),
+                object(vec![], &[]),
+                &config(),
+            ),
+        ],
+    );
+    let report = check_compat(&baseline("0.1.0"), &new);
+    assert!(report.is_compatible(), "{:?}", report.changes);
+    assert_eq!(report.required_bump, VersionBump::Minor);
+    assert!(report.version_ok, "0.x patch bumps count as additive");
+    assert!(
+        report
+            .changes
+            .iter()
+            .any(|change| change.path == "operations.preview"
+                && change.kind == ChangeKind::Compatible)
+    );
+}
+
+#[test]
+fn breaking_changes_are_reported_with_paths() {
+    let old = describe(
+        "1.2.0",
+        vec![
+            operation(
+                "run",
+                object(vec![("name", string())], &["name"]),
+                object(vec![("ok", SchemaIr::Bool)], &["ok"]),
+                &config(),
+            ),
+            operation(
+                "legacy",
+                object(vec![], &[]),
+                object(vec![], &[]),
+                &config(),
+            ),
+        ],
+    );
+    let new = describe(
+        "1.3.0",
+        vec![operation(
+            "run",
+            object(
+                vec![("name", string()), ("tenant", string())],
+                &["name", "tenant"],
+            ),
+            object(vec![("ok", string())], &["ok"]),
+            &config(),
+        )],
+    );
+    let report = check_compat(&old, &new);
+    assert!(!report.is_compatible());
+    assert_eq!(report.required_bump, VersionBump::Major);
+    assert!(!report.version_ok);
+    let breaking: Vec<_> = report
+        .breaking()
+        .map(|change| change.path.as_str())
+        .collect();
+    assert!(breaking.contains(&"operations.legacy"), "{breaking:?}");
+    assert!(
+        breaking.contains(&"operations.run.input.tenant"),
+        "{breaking:?}"
+    );
+    assert!(
+        breaking.contains(&"operations.run.output.ok"),
+        "{breaking:?}"
+    );
+}
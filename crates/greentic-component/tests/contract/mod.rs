@@ -3,23 +3,71 @@ use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use assert_cmd::Command;
-use greentic_component::cmd::component_world::canonical_component_world;
 use serde_json::Value;
 
 const ARTIFACT_ROOT: &str = "target/contract-artifacts";
+const FIXTURE_ROOT: &str = "tests/contract/fixtures";
+const DEFAULT_OPERATION: &str = "handle_message";
 
 pub struct WorldContract {
-    pub id: &'static str,
+    pub id: String,
     pub fixture_dir: PathBuf,
-    pub operation: &'static str,
+    pub operation: String,
 }
 
+/// One contract per `component_v<major>_<minor>_<patch>` fixture directory,
+/// oldest ABI first, so a fixture generated for a new world version is
+/// covered without touching this file.
 pub fn registry() -> Vec<WorldContract> {
-    vec![WorldContract {
-        id: canonical_component_world(),
-        fixture_dir: PathBuf::from("tests/contract/fixtures/component_v0_6_0"),
-        operation: "handle_message",
-    }]
+    let Ok(entries) = fs::read_dir(FIXTURE_ROOT) else {
+        return Vec::new();
+    };
+    let mut worlds = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            let version = fixture_abi_version(&name)?;
+            let fixture_dir = entry.path();
+            let operation = fixture_operation(&fixture_dir);
+            Some((
+                version,
+                WorldContract {
+                    id: format!(
+                        "greentic:component/component@{}.{}.{}",
+                        version.0, version.1, version.2
+                    ),
+                    fixture_dir,
+                    operation,
+                },
+            ))
+        })
+        .collect::<Vec<_>>();
+    worlds.sort_by_key(|(version, _)| *version);
+    worlds.into_iter().map(|(_, world)| world).collect()
+}
+
+fn fixture_abi_version(dir_name: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = dir_name.strip_prefix("component_v")?.split('_');
+    let version = (
+        parts.next()?.parse().ok()?,
+        parts.next()?.parse().ok()?,
+        parts.next()?.parse().ok()?,
+    );
+    parts.next().is_none().then_some(version)
+}
+
+/// The manifest's `default_operation`, when the fixture has been built.
+fn fixture_operation(fixture_dir: &Path) -> String {
+    fs::read_to_string(fixture_dir.join("component.manifest.json"))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+        .and_then(|manifest| {
+            manifest
+                .get("default_operation")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| DEFAULT_OPERATION.to_string())
 }
 
 pub fn run_contract_suite(world: &WorldContract) {
@@ -109,7 +157,7 @@ pub fn run_harness_once(world: &WorldContract, input: &Value) -> Value {
         .arg("--manifest")
         .arg(&manifest_path)
         .arg("--op")
-        .arg(world.operation)
+        .arg(&world.operation)
         .arg("--input")
         .arg(&input_path);
 
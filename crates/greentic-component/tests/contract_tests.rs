@@ -1,5 +1,21 @@
 mod contract;
 
+use greentic_component::cmd::component_world::canonical_component_world;
+
+#[test]
+fn registry_discovers_every_fixture_abi() {
+    let worlds = contract::registry()
+        .into_iter()
+        .map(|world| world.id)
+        .collect::<Vec<_>>();
+    assert!(
+        worlds.iter().any(|id| id == canonical_component_world()),
+        "no contract fixture for {}: {worlds:?}",
+        canonical_component_world()
+    );
+    assert!(worlds.iter().any(|id| id.ends_with("@0.5.0")), "{worlds:?}");
+}
+
 #[test]
 fn contract_suite_runs_for_component_world() {
     for world in contract::registry() {
@@ -19,6 +35,7 @@ fn contract_suite_runs_for_component_world() {
 #[cfg(feature = "fuzz")]
 mod fuzz {
     use super::contract;
+    use greentic_component::cmd::component_world::canonical_component_world;
     use proptest::prelude::*;
     use serde_json::{Number, Value};
 
@@ -44,7 +61,10 @@ mod fuzz {
             eprintln!("GREENTIC_FUZZ not set; skipping fuzz suite");
             return;
         }
-        let world = match contract::registry().into_iter().next() {
+        let world = match contract::registry()
+            .into_iter()
+            .find(|world| world.id == canonical_component_world())
+        {
             Some(world) => world,
             None => return,
         };
@@ -149,3 +149,329 @@ fn flow_update_errors_when_operation_ambiguous() {
         .failure()
         .stderr(predicates::str::contains("declares multiple operations"));
 }
+
+#[test]
+fn flow_validate_accepts_generated_flows_and_reports_broken_ones() {
+    let temp = TempDir::new().expect("tempdir");
+    write_stub_manifest(&temp, true);
+    write_input_schema(&temp, Some("hello"));
+
+    let mut update = cargo_bin_cmd!("greentic-component");
+    update.current_dir(temp.path()).arg("flow").arg("update");
+    update.assert().success();
+    let mut validate = cargo_bin_cmd!("greentic-component");
+    validate
+        .current_dir(temp.path())
+        .arg("flow")
+        .arg("validate");
+    validate.assert().success();
+
+    let manifest_path = temp.path().join("component.manifest.json");
+    let mut json: JsonValue =
+        serde_json::from_str(&fs::read_to_string(&manifest_path).expect("manifest")).expect("json");
+    let nodes = &mut json["dev_flows"]["custom"]["graph"]["nodes"];
+    nodes["ask_config"]["routing"][0]["to"] = JsonValue::from("missing_node");
+    nodes["emit_config"]["template"] =
+        JsonValue::from(r#"{ "node_id": "example", "node": { "handle_event": { "input": {} } } }"#);
+    json["dev_flows"]["default"]["graph"]["nodes"]["emit_config"]["template"] =
+        JsonValue::from("{ \"node_id\": ");
+    fs::write(&manifest_path, serde_json::to_string_pretty(&json).unwrap()).expect("write");
+
+    let mut validate = cargo_bin_cmd!("greentic-component");
+    validate
+        .current_dir(temp.path())
+        .arg("flow")
+        .arg("validate");
+    validate
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "custom: graph.nodes.ask_config.routing: references unknown node `missing_node`",
+        ))
+        .stderr(predicates::str::contains(
+            "operation `handle_event` is not declared by the manifest",
+        ))
+        .stderr(predicates::str::contains(
+            "default: graph.nodes.emit_config.template: template is not valid JSON",
+        ));
+}
+
+#[test]
+fn flow_preview_renders_graph_and_localized_questions() {
+    let temp = TempDir::new().expect("tempdir");
+    write_stub_manifest(&temp, true);
+    write_input_schema(&temp, Some("hello"));
+    let i18n_dir = temp.path().join("assets/i18n");
+    fs::create_dir_all(&i18n_dir).expect("i18n dir");
+    fs::write(i18n_dir.join("fr.json"), r#"{ "Input:": "Entrée" }"#).expect("write bundle");
+
+    let mut cmd = cargo_bin_cmd!("greentic-component");
+    cmd.current_dir(temp.path())
+        .args(["flow", "preview", "--flow", "custom", "--format", "mermaid"])
+        .args(["--locale", "fr"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("flowchart TD"))
+        .stdout(predicates::str::contains("ask_config --> emit_config"))
+        .stdout(predicates::str::contains(
+            "emit_config --> next_step((next step))",
+        ))
+        .stdout(predicates::str::contains(
+            r#"1. Entrée [input: string; default "hello"]"#,
+        ));
+
+    let manifest = fs::read_to_string(temp.path().join("component.manifest.json")).unwrap();
+    assert!(
+        !manifest.contains("emit_config"),
+        "preview must not write the manifest"
+    );
+}
+
+#[test]
+fn flow_update_honors_only_templates_and_preserved_nodes() {
+    let temp = TempDir::new().expect("tempdir");
+    write_stub_manifest(&temp, true);
+    write_input_schema(&temp, Some("hello"));
+    let run_update = |extra: &[&str]| {
+        let mut cmd = cargo_bin_cmd!("greentic-component");
+        cmd.current_dir(temp.path())
+            .args(["flow", "update"])
+            .args(extra);
+        cmd.assert().success();
+        let text = fs::read_to_string(temp.path().join("component.manifest.json")).unwrap();
+        serde_json::from_str::<JsonValue>(&text).expect("json")
+    };
+    let mut json = run_update(&[]);
+
+    let custom_nodes = &mut json["dev_flows"]["custom"]["graph"]["nodes"];
+    custom_nodes["ask_config"]["x_flow_preserve"] = JsonValue::Bool(true);
+    custom_nodes["ask_config"]["questions"]["fields"][0]["prompt"] = "Hand-written prompt".into();
+    custom_nodes["review"] = serde_json::json!({
+        "x_flow_preserve": true,
+        "routing": [{ "to": "emit_config" }]
+    });
+    json["dev_flows"]["default"]["graph"]["nodes"]["emit_config"]["template"] = "{}".into();
+    fs::write(
+        temp.path().join("component.manifest.json"),
+        serde_json::to_string_pretty(&json).unwrap(),
+    )
+    .unwrap();
+    let templates = temp.path().join("flows/templates");
+    fs::create_dir_all(&templates).unwrap();
+    fs::write(
+        templates.join("default.json.hbs"),
+        r#"{
+  "id": "{{component_id}}.default",
+  "kind": "{{kind}}",
+  "nodes": {
+    "announce": { "routing": [{ "to": "emit_config" }] },
+    "emit_config": { "template": {{emit_template_json}} }
+  }
+}"#,
+    )
+    .unwrap();
+
+    let json = run_update(&["--only", "custom"]);
+    let custom_nodes = &json["dev_flows"]["custom"]["graph"]["nodes"];
+    assert_eq!(
+        custom_nodes["ask_config"]["questions"]["fields"][0]["prompt"],
+        "Hand-written prompt"
+    );
+    assert!(
+        custom_nodes["review"].is_object(),
+        "marked node must survive"
+    );
+    assert_eq!(
+        json["dev_flows"]["default"]["graph"]["nodes"]["emit_config"]["template"], "{}",
+        "--only custom must leave the default flow alone"
+    );
+
+    let json = run_update(&["--only", "default"]);
+    let default_graph = &json["dev_flows"]["default"]["graph"];
+    assert_eq!(default_graph["id"], "ai.greentic.example.default");
+    assert!(default_graph["nodes"]["announce"].is_object());
+    let template = default_graph["nodes"]["emit_config"]["template"]
+        .as_str()
+        .expect("template");
+    assert!(template.contains("\"handle_message\""));
+}
+
+#[test]
+fn flow_test_answers_questions_and_emits_node() {
+    let temp = TempDir::new().expect("tempdir");
+    write_stub_manifest(&temp, true);
+    write_input_schema(&temp, Some("hello"));
+    let mut update = cargo_bin_cmd!("greentic-component");
+    update.current_dir(temp.path()).args(["flow", "update"]);
+    update.assert().success();
+
+    fs::write(
+        temp.path().join("answers.json"),
+        r#"{ "input": "say \"hi\"" }"#,
+    )
+    .unwrap();
+    let mut cmd = cargo_bin_cmd!("greentic-component");
+    cmd.current_dir(temp.path()).args([
+        "flow",
+        "test",
+        "--answers",
+        "answers.json",
+        "--no-invoke",
+        "--json",
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let report: JsonValue = serde_json::from_slice(&output).expect("json report");
+    assert_eq!(
+        report["path"],
+        serde_json::json!(["ask_config", "emit_config"])
+    );
+    assert_eq!(report["operation"], "handle_message");
+    assert_eq!(report["input"]["input"], "say \"hi\"");
+    assert_eq!(report["emitted"]["node_id"], "example");
+
+    fs::write(temp.path().join("answers.json"), r#"{ "input": 5 }"#).unwrap();
+    let mut cmd = cargo_bin_cmd!("greentic-component");
+    cmd.current_dir(temp.path())
+        .args(["flow", "test", "--answers", "answers.json", "--no-invoke"]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "answer for `input` must be a string",
+    ));
+}
+
+#[test]
+fn flow_test_invokes_the_component_with_the_emitted_node() {
+    let fixture = std::path::Path::new("tests/contract/fixtures/component_v0_6_0");
+    let temp = TempDir::new().expect("tempdir");
+    fs::copy(
+        fixture.join("component.wasm"),
+        temp.path().join("component.wasm"),
+    )
+    .expect("copy wasm");
+    let raw = fs::read_to_string(fixture.join("component.manifest.json")).expect("manifest");
+    let mut manifest: JsonValue = serde_json::from_str(&raw).expect("manifest json");
+    // The emitted input doubles as config when the component has no
+    // `component-qa`, so the fixture's closed config schema is opened up.
+    manifest["config_schema"] = serde_json::json!({
+        "type": "object",
+        "properties": {},
+        "required": []
+    });
+    fs::write(
+        temp.path().join("component.manifest.json"),
+        serde_json::to_string_pretty(&manifest).unwrap(),
+    )
+    .expect("write manifest");
+    let mut update = cargo_bin_cmd!("greentic-component");
+    update.current_dir(temp.path()).args(["flow", "update"]);
+    update.assert().success();
+
+    fs::write(temp.path().join("answers.json"), r#"{ "input": "hello" }"#).unwrap();
+    let mut cmd = cargo_bin_cmd!("greentic-component");
+    cmd.current_dir(temp.path())
+        .args(["flow", "test", "--answers", "answers.json", "--json"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let report: JsonValue = serde_json::from_slice(&output).expect("json report");
+    assert_eq!(report["operation"], "handle_message");
+    assert_eq!(report["input"]["input"], "hello");
+    assert!(
+        report.get("config").is_none(),
+        "fixture has no component-qa"
+    );
+    assert!(
+        !report["output"].is_null(),
+        "expected the component's output, got {report}"
+    );
+}
+
+#[test]
+fn flow_validate_checks_named_dependencies_against_catalog() {
+    let temp = TempDir::new().expect("tempdir");
+    write_stub_manifest(&temp, true);
+    let manifest_path = temp.path().join("component.manifest.json");
+    let mut json: JsonValue =
+        serde_json::from_str(&fs::read_to_string(&manifest_path).expect("manifest")).expect("json");
+    json["dependencies"] = serde_json::json!([
+        { "id": "acme.slack", "version": "^1.4", "locator": "slack-adapter" },
+        { "id": "acme.local", "version": "^0.1", "locator": "./deps/local.wasm" }
+    ]);
+    fs::write(&manifest_path, serde_json::to_string_pretty(&json).unwrap()).expect("write");
+
+    let mut validate = cargo_bin_cmd!("greentic-component");
+    validate.current_dir(temp.path()).args(["flow", "validate"]);
+    validate.assert().failure().stderr(predicates::str::contains(
+        "dependencies: dependencies[0].locator: component `slack-adapter` is a catalog name but no components.toml was found",
+    ));
+
+    fs::write(
+        temp.path().join("components.toml"),
+        "[components.slack-adapter]\nlocator = \"oci://ghcr.io/greentic/slack-adapter:1.4.2\"\n",
+    )
+    .expect("write catalog");
+    let mut validate = cargo_bin_cmd!("greentic-component");
+    validate.current_dir(temp.path()).args(["flow", "validate"]);
+    validate.assert().success();
+}
+
+#[test]
+fn configurators_resolve_flows_and_run_them() {
+    let temp = TempDir::new().expect("tempdir");
+    write_stub_manifest(&temp, true);
+    write_input_schema(&temp, Some("hello"));
+    let mut update = cargo_bin_cmd!("greentic-component");
+    update.current_dir(temp.path()).args(["flow", "update"]);
+    update.assert().success();
+
+    let manifest_path = temp.path().join("component.manifest.json");
+    let mut json: JsonValue =
+        serde_json::from_str(&fs::read_to_string(&manifest_path).expect("manifest")).expect("json");
+    json["configurators"] =
+        serde_json::json!({ "setup": "custom", "upgrade": "upgrade", "remove": "teardown" });
+    let upgrade_graph = json["dev_flows"]["custom"]["graph"].clone();
+    fs::write(&manifest_path, serde_json::to_string_pretty(&json).unwrap()).expect("write");
+    fs::create_dir_all(temp.path().join("flows")).expect("flows dir");
+    // JSON is valid YAML, so the FlowIR graph doubles as a `.ygtc` file.
+    fs::write(
+        temp.path().join("flows/upgrade.ygtc"),
+        serde_json::to_string_pretty(&upgrade_graph).unwrap(),
+    )
+    .expect("write flow");
+
+    let mut validate = cargo_bin_cmd!("greentic-component");
+    validate.current_dir(temp.path()).args(["flow", "validate"]);
+    validate
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "configurators: remove: flow `teardown` is not in dev_flows or flows/teardown.ygtc",
+        ));
+
+    for (mode, flow) in [("setup", "custom"), ("upgrade", "upgrade")] {
+        let mut run = cargo_bin_cmd!("greentic-component");
+        run.current_dir(temp.path())
+            .args(["configurator", "run", mode, "--no-invoke", "--json"]);
+        let output = run.assert().success().get_output().stdout.clone();
+        let report: JsonValue = serde_json::from_slice(&output).expect("json report");
+        assert_eq!(report["flow"], flow);
+        assert_eq!(report["operation"], "handle_message");
+        assert_eq!(report["input"]["input"], "hello");
+    }
+
+    let mut run = cargo_bin_cmd!("greentic-component");
+    run.current_dir(temp.path())
+        .args(["configurator", "run", "remove", "--no-invoke"]);
+    run.assert().failure().stderr(predicates::str::contains(
+        "flow `teardown` is neither in dev_flows",
+    ));
+
+    json["configurators"] = serde_json::json!({ "setup": "custom" });
+    fs::write(&manifest_path, serde_json::to_string_pretty(&json).unwrap()).expect("write");
+    let mut validate = cargo_bin_cmd!("greentic-component");
+    validate.current_dir(temp.path()).args(["flow", "validate"]);
+    validate.assert().success();
+    let mut run = cargo_bin_cmd!("greentic-component");
+    run.current_dir(temp.path())
+        .args(["configurator", "run", "upgrade", "--no-invoke"]);
+    run.assert().failure().stderr(predicates::str::contains(
+        "manifest declares no configurators.upgrade flow",
+    ));
+}
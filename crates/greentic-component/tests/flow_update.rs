@@ -177,6 +177,91 @@ fn flow_update_is_idempotent() {
     assert_eq!(initial, after, "running update twice should be stable");
 }
 
+#[test]
+fn node_kind_follows_component_role() {
+    let temp = TempDir::new().expect("tempdir");
+    let manifest = r#"{"id":"component-demo","name":"component-demo","role":"adapter","operations":[{"name":"handle_message","input_schema":{},"output_schema":{}}],"config_schema":{"type":"object","properties":{},"required":[]}}"#;
+    fs::write(temp.path().join("component.manifest.json"), manifest).expect("write manifest");
+    let schema_dir = temp.path().join("schemas/io");
+    fs::create_dir_all(&schema_dir).expect("schema dir");
+    fs::write(
+        schema_dir.join("input.schema.json"),
+        r#"{ "type": "object", "properties": { "input": { "type": "string", "default": "hi" } } }"#,
+    )
+    .expect("write input schema");
+
+    let mut cmd = cargo_bin_cmd!("greentic-component");
+    cmd.current_dir(temp.path()).arg("flow").arg("update");
+    cmd.assert().success();
+
+    let value: JsonValue = serde_json::from_str(
+        &fs::read_to_string(temp.path().join("component.manifest.json")).unwrap(),
+    )
+    .unwrap();
+    for flow in ["default", "custom"] {
+        let template = value["dev_flows"][flow]["graph"]["nodes"]["emit_config"]["template"]
+            .as_str()
+            .expect("template");
+        assert!(
+            template.contains(r#""node_kind": "component.adapter""#),
+            "{flow} template should carry the adapter node kind"
+        );
+    }
+
+    let mut manifest: JsonValue = serde_json::from_str(manifest).unwrap();
+    manifest["role"] = "daemon".into();
+    fs::write(
+        temp.path().join("component.manifest.json"),
+        manifest.to_string(),
+    )
+    .expect("write manifest");
+    let mut cmd = cargo_bin_cmd!("greentic-component");
+    cmd.current_dir(temp.path()).arg("flow").arg("update");
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("unknown component role `daemon`"));
+}
+
+#[test]
+fn deprecated_fields_are_not_scaffolded() {
+    let temp = TempDir::new().expect("tempdir");
+    let manifest = r#"{"id":"component-demo","name":"component-demo","operations":[{"name":"handle_message","input_schema":{},"output_schema":{}}],"config_schema":{"type":"object","properties":{},"required":[]}}"#;
+    fs::write(temp.path().join("component.manifest.json"), manifest).expect("write manifest");
+    let schema_dir = temp.path().join("schemas/io");
+    fs::create_dir_all(&schema_dir).expect("schema dir");
+    fs::write(
+        schema_dir.join("input.schema.json"),
+        r#"{
+  "type": "object",
+  "properties": {
+    "title": { "type": "string", "default": "hi" },
+    "legacy_title": { "type": "string", "deprecated": { "since": "0.2.0", "replacement": "title" } },
+    "old_flag": { "type": "boolean", "deprecated": true }
+  },
+  "required": ["title"]
+}"#,
+    )
+    .expect("write input schema");
+
+    let mut cmd = cargo_bin_cmd!("greentic-component");
+    cmd.current_dir(temp.path()).arg("flow").arg("update");
+    cmd.assert().success();
+
+    let value: JsonValue = serde_json::from_str(
+        &fs::read_to_string(temp.path().join("component.manifest.json")).unwrap(),
+    )
+    .unwrap();
+    let fields =
+        value["dev_flows"]["custom"]["graph"]["nodes"]["ask_config"]["questions"]["fields"]
+            .as_array()
+            .expect("question fields");
+    let field_ids: Vec<&str> = fields
+        .iter()
+        .filter_map(|entry| entry["id"].as_str())
+        .collect();
+    assert_eq!(field_ids, vec!["title"]);
+}
+
 #[test]
 fn infers_schema_from_wit_when_missing() {
     let temp = TempDir::new().expect("tempdir");
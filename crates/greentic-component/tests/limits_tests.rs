@@ -8,6 +8,8 @@ fn merge_respects_overrides() {
         wall_time_ms: None,
         fuel: Some(Some(0)),
         files: Some(None),
+        max_output_bytes: Some(Some(4096)),
+        fs_quota_bytes: Some(Some(1 << 20)),
     };
 
     let merged = merge(Some(&overrides), &defaults);
@@ -15,6 +17,8 @@ fn merge_respects_overrides() {
     assert_eq!(merged.wall_time_ms, defaults.wall_time_ms);
     assert_eq!(merged.fuel, Some(0));
     assert!(merged.files.is_none());
+    assert_eq!(merged.max_output_bytes, Some(4096));
+    assert_eq!(merged.fs_quota_bytes, Some(1 << 20));
 }
 
 #[test]
@@ -24,6 +28,8 @@ fn validate_rejects_zero_limits() {
         wall_time_ms: 10,
         fuel: None,
         files: None,
+        max_output_bytes: None,
+        fs_quota_bytes: None,
     };
     match limits.validate() {
         Err(LimitError::NonZero { field, .. }) => assert_eq!(field, "memory_mb"),
@@ -3,7 +3,7 @@
 #[path = "support/mod.rs"]
 mod support;
 
-use greentic_component::{LoadError, discover};
+use greentic_component::{LoadError, discover, discover_for_env};
 use support::TestComponent;
 
 const TEST_WIT: &str = r#"
@@ -38,3 +38,23 @@ fn discovers_from_file_scheme_manifest_path() {
     assert_eq!(handle.manifest.id.as_str(), "com.greentic.test.component");
     assert_eq!(handle.wasm_path, component.wasm_path);
 }
+
+#[test]
+fn discover_for_env_applies_manifest_overlay() {
+    let component = TestComponent::new(TEST_WIT, &["describe"]);
+    std::fs::write(
+        component
+            .dir
+            .path()
+            .join("component.manifest.override.prod.json"),
+        r#"{"limits":{"memory_mb":32,"wall_time_ms":500}}"#,
+    )
+    .unwrap();
+    let source = component.manifest_path.to_str().unwrap();
+
+    let prod = discover_for_env(source, None, Some("prod")).unwrap();
+    assert_eq!(prod.manifest.limits.as_ref().unwrap().memory_mb, 32);
+
+    let dev = discover_for_env(source, None, Some("dev")).unwrap();
+    assert_eq!(dev.manifest.limits, component.manifest.limits);
+}
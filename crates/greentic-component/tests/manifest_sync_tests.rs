@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+use greentic_component::manifest_sync::{
+    DriftKind, apply_describe, check_manifest_sync, json_schema_to_schema_ir,
+    schema_ir_to_json_schema, verify_schema_hashes,
+};
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use greentic_types::schemas::component::v0_6_0::{
+    ComponentDescribe, ComponentInfo, ComponentOperation, ComponentRunInput, ComponentRunOutput,
+    schema_hash,
+};
+use serde_json::json;
+
+fn object(props: Vec<(&str, SchemaIr)>, required: &[&str]) -> SchemaIr {
+    SchemaIr::Object {
+        properties: props
+            .into_iter()
+            .map(|(name, schema)| (name.to_string(), schema))
+            .collect(),
+        required: required.iter().map(|name| name.to_string()).collect(),
+        additional: AdditionalProperties::Forbid,
+    }
+}
+
+fn operation(id: &str, input: SchemaIr, output: SchemaIr, config: &SchemaIr) -> ComponentOperation {
+    ComponentOperation {
+        id: id.to_string(),
+        display_name: None,
+        schema_hash: schema_hash(&input, &output, config).expect("schema hash"),
+        input: ComponentRunInput { schema: input },
+        output: ComponentRunOutput { schema: output },
+        defaults: BTreeMap::new(),
+        redactions: Vec::new(),
+        constraints: BTreeMap::new(),
+    }
+}
+
+fn describe() -> ComponentDescribe {
+    let config = object(vec![("enabled", SchemaIr::Bool)], &["enabled"]);
+    let name = SchemaIr::String {
+        min_len: Some(1),
+        max_len: None,
+        regex: None,
+        format: None,
+    };
+    ComponentDescribe {
+        info: ComponentInfo {
+            id: "com.greentic.demo".to_string(),
+            version: "0.1.0".to_string(),
+            role: "tool".to_string(),
+            display_name: None,
+        },
+        provided_capabilities: Vec::new(),
+        required_capabilities: Vec::new(),
+        metadata: BTreeMap::new(),
+        operations: vec![
+            operation(
+                "run",
+                object(vec![("name", name)], &["name"]),
+                object(vec![("ok", SchemaIr::Bool)], &["ok"]),
+                &config,
+            ),
+            operation(
+                "count",
+                object(vec![], &[]),
+                SchemaIr::Int {
+                    min: Some(0),
+                    max: None,
+                },
+                &config,
+            ),
+        ],
+        config_schema: config,
+    }
+}
+
+#[test]
+fn annotations_do_not_count_as_drift() {
+    let describe = describe();
+    let mut manifest = json!({
+        "operations": describe.operations.iter().map(|op| json!({
+            "name": op.id,
+            "input_schema": schema_ir_to_json_schema(&op.input.schema),
+            "output_schema": schema_ir_to_json_schema(&op.output.schema),
+        })).collect::<Vec<_>>(),
+        "config_schema": schema_ir_to_json_schema(&describe.config_schema),
+    });
+    manifest["operations"][0]["input_schema"]["properties"]["name"]["description"] =
+        json!("Who to greet");
+    manifest["config_schema"]["title"] = json!("Config");
+
+    let report = check_manifest_sync(&manifest, &describe);
+    assert!(report.in_sync(), "unexpected drift: {:?}", report.drift);
+}
+
+#[test]
+fn reports_and_repairs_drift() {
+    let describe = describe();
+    let mut manifest = json!({
+        "operations": [
+            {
+                "name": "run",
+                "input_schema": {
+                    "type": "object",
+                    "properties": { "name": { "type": "string", "description": "kept" } },
+                    "required": ["name"],
+                    "additionalProperties": false
+                },
+                "output_schema": {
+                    "type": "object",
+                    "properties": { "ok": { "type": "boolean" } },
+                    "required": ["ok"],
+                    "additionalProperties": false
+                },
+                "deprecated": { "since": "0.1.0" }
+            },
+            { "name": "legacy", "input_schema": {}, "output_schema": {} }
+        ],
+        "config_schema": schema_ir_to_json_schema(&describe().config_schema)
+    });
+
+    let report = check_manifest_sync(&manifest, &describe);
+    let drift = report
+        .drift
+        .iter()
+        .map(|drift| (drift.kind, drift.path.as_str(), drift.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        drift,
+        vec![
+            (
+                DriftKind::SchemaMismatch,
+                "operations.run.input_schema",
+                "differs from describe() at /properties/name/minLength",
+            ),
+            (
+                DriftKind::MissingInManifest,
+                "operations.count",
+                "describe() exports `count` but the manifest does not declare it",
+            ),
+            (
+                DriftKind::MissingInDescribe,
+                "operations.legacy",
+                "manifest declares `legacy` but describe() does not export it",
+            ),
+        ]
+    );
+
+    let resolved = manifest.clone();
+    let changes = apply_describe(&mut manifest, &resolved, &describe);
+    assert_eq!(changes, 3);
+    assert!(check_manifest_sync(&manifest, &describe).in_sync());
+    let names = manifest["operations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|op| op["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["run", "count"]);
+    assert_eq!(manifest["operations"][0]["deprecated"]["since"], "0.1.0");
+    assert_eq!(manifest["operations"][1]["output_schema"]["minimum"], 0);
+}
+
+#[test]
+fn schema_hashes_verify_against_manifest_and_describe() {
+    let mut describe = describe();
+    let mut manifest = json!({
+        "operations": describe.operations.iter().map(|op| json!({
+            "name": op.id,
+            "input_schema": schema_ir_to_json_schema(&op.input.schema),
+            "output_schema": schema_ir_to_json_schema(&op.output.schema),
+        })).collect::<Vec<_>>(),
+        "config_schema": schema_ir_to_json_schema(&describe.config_schema),
+    });
+    assert!(verify_schema_hashes(&manifest, &describe).is_empty());
+    let converted =
+        json_schema_to_schema_ir(&manifest["operations"][0]["input_schema"]).expect("convertible");
+    assert_eq!(
+        schema_ir_to_json_schema(&converted),
+        schema_ir_to_json_schema(&describe.operations[0].input.schema)
+    );
+
+    manifest["operations"][0]["input_schema"]["properties"]["name"]["maxLength"] = json!(8);
+    let mismatches = verify_schema_hashes(&manifest, &describe);
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].operation, "run");
+    assert_eq!(mismatches[0].path, "operations.run.input_schema");
+
+    describe.operations[1].schema_hash = "0".repeat(64);
+    let mismatches = verify_schema_hashes(&manifest, &describe);
+    assert!(
+        mismatches
+            .iter()
+            .any(|m| m.operation == "count" && m.path == "describe.operations[1].schema_hash"),
+        "{mismatches:?}"
+    );
+}
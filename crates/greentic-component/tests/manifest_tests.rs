@@ -2,7 +2,10 @@ use std::fs;
 use std::path::Path;
 
 use greentic_component::manifest::{
-    DescribeKind, ManifestError, parse_manifest, validate_manifest,
+    ComponentRole, ConfiguratorMode, DescribeKind, ManifestError, SchemaDraft, ValidationOptions,
+    inline_local_refs, overlay_path, parse_manifest, parse_manifest_for_profile,
+    parse_manifest_value, parse_manifest_value_with, parse_manifest_with_overlay,
+    resolve_schema_refs, validate_manifest,
 };
 use greentic_types::flow::FlowKind;
 use serde_json::Value;
@@ -129,3 +132,397 @@ fn manifest_preserves_dev_flows() {
     parse_manifest(&serialized).expect("manifest with dev_flows parses");
     validate_manifest(&serialized).expect("schema-valid manifest with dev_flows");
 }
+
+fn manifest_with_build_profiles() -> Value {
+    let mut value: Value = serde_json::from_str(&fixture("valid.component.json")).unwrap();
+    value["build"] = serde_json::json!({
+        "profiles": {
+            "dev": {
+                "limits": { "memory_mb": 512, "fuel": null }
+            },
+            "prod": {
+                "capabilities": { "host": { "http": null } },
+                "telemetry": { "emit_node_spans": false }
+            }
+        }
+    });
+    value
+}
+
+#[test]
+fn build_profile_overrides_are_applied() {
+    let raw = serde_json::to_string(&manifest_with_build_profiles()).unwrap();
+    let base = parse_manifest(&raw).expect("base manifest parses");
+    assert_eq!(base.build.as_ref().unwrap().profiles.len(), 2);
+    assert!(base.capabilities.host.http.is_some());
+
+    let dev = parse_manifest_for_profile(&raw, "dev").expect("dev profile parses");
+    let limits = dev.limits.expect("limits");
+    assert_eq!(limits.memory_mb, 512);
+    assert_eq!(limits.wall_time_ms, 60000);
+    assert!(limits.fuel.is_none());
+
+    let prod = parse_manifest_for_profile(&raw, "prod").expect("prod profile parses");
+    assert!(prod.capabilities.host.http.is_none());
+    let telemetry = prod.telemetry.expect("telemetry");
+    assert_eq!(telemetry.span_prefix, "component.echo");
+    assert!(!telemetry.emit_node_spans);
+}
+
+#[test]
+fn unknown_build_profile_is_rejected() {
+    let raw = serde_json::to_string(&manifest_with_build_profiles()).unwrap();
+    match parse_manifest_for_profile(&raw, "staging").unwrap_err() {
+        ManifestError::UnknownBuildProfile { profile } => assert_eq!(profile, "staging"),
+        err => panic!("expected UnknownBuildProfile, got {err:?}"),
+    }
+}
+
+#[test]
+fn invalid_build_profile_fails_base_parse() {
+    let mut value = manifest_with_build_profiles();
+    value["build"]["profiles"]["prod"]["capabilities"]["wasi"] =
+        serde_json::json!({ "filesystem": { "mode": "everything" } });
+    let raw = serde_json::to_string(&value).unwrap();
+    match parse_manifest(&raw).unwrap_err() {
+        ManifestError::BuildProfile { profile, .. } => assert_eq!(profile, "prod"),
+        err => panic!("expected BuildProfile error, got {err:?}"),
+    }
+}
+
+#[test]
+fn manifest_dependencies_are_validated() {
+    let mut value: Value = serde_json::from_str(&fixture("valid.component.json")).unwrap();
+    value["dependencies"] = serde_json::json!([
+        { "id": "com.greentic.demo.store", "version": "^1.2", "exports": ["get"] }
+    ]);
+    let manifest = parse_manifest(&serde_json::to_string(&value).unwrap()).unwrap();
+    assert_eq!(manifest.dependencies.len(), 1);
+    assert!(manifest.dependencies[0].accepts("1.4.0").unwrap());
+
+    value["dependencies"][0]["version"] = Value::String("not-a-range".into());
+    match parse_manifest(&serde_json::to_string(&value).unwrap()).unwrap_err() {
+        ManifestError::Dependency(_) => {}
+        err => panic!("expected Dependency error, got {err:?}"),
+    }
+}
+
+#[test]
+fn error_catalog_is_validated() {
+    let mut value: Value = serde_json::from_str(&fixture("valid.component.json")).unwrap();
+    value["errors"] = serde_json::json!([
+        { "code": "upstream.timeout", "retryable": true },
+        { "code": "bad_input", "description": "Message was empty", "operations": ["handle_message"] }
+    ]);
+    let manifest = parse_manifest(&serde_json::to_string(&value).unwrap()).unwrap();
+    assert_eq!(manifest.errors.len(), 2);
+    assert!(
+        manifest
+            .declared_error("handle_message", "upstream.timeout")
+            .is_some_and(|error| error.retryable)
+    );
+
+    value["errors"][1]["operations"] = serde_json::json!(["missing_op"]);
+    match parse_manifest(&serde_json::to_string(&value).unwrap()).unwrap_err() {
+        ManifestError::InvalidErrorCode { code, .. } => assert_eq!(code, "bad_input"),
+        err => panic!("expected InvalidErrorCode error, got {err:?}"),
+    }
+
+    value["errors"][1] = serde_json::json!({ "code": "upstream.timeout" });
+    match parse_manifest(&serde_json::to_string(&value).unwrap()).unwrap_err() {
+        ManifestError::DuplicateErrorCode(code) => assert_eq!(code, "upstream.timeout"),
+        err => panic!("expected DuplicateErrorCode error, got {err:?}"),
+    }
+}
+
+#[test]
+fn role_is_parsed_and_restricted_to_known_values() {
+    let mut value: Value = serde_json::from_str(&fixture("valid.component.json")).unwrap();
+    assert_eq!(
+        parse_manifest(&fixture("valid.component.json"))
+            .unwrap()
+            .role,
+        None
+    );
+
+    value["role"] = serde_json::json!("adapter");
+    let manifest = parse_manifest(&serde_json::to_string(&value).unwrap()).unwrap();
+    assert_eq!(manifest.role, Some(ComponentRole::Adapter));
+
+    value["role"] = serde_json::json!("daemon");
+    assert!(parse_manifest(&serde_json::to_string(&value).unwrap()).is_err());
+}
+
+#[test]
+fn configurators_name_one_flow_per_lifecycle_step() {
+    let mut value: Value = serde_json::from_str(&fixture("valid.component.json")).unwrap();
+    value["configurators"] = serde_json::json!({ "setup": "custom", "remove": "teardown" });
+    let manifest = parse_manifest(&serde_json::to_string(&value).unwrap()).unwrap();
+    let configurators = manifest.configurators.expect("configurators");
+    assert_eq!(configurators.flow(ConfiguratorMode::Setup), Some("custom"));
+    assert_eq!(configurators.flow(ConfiguratorMode::Upgrade), None);
+    assert_eq!(
+        configurators.flows().collect::<Vec<_>>(),
+        [("setup", "custom"), ("remove", "teardown")]
+    );
+
+    for bad in [
+        serde_json::json!({ "install": "custom" }),
+        serde_json::json!({ "setup": "../custom" }),
+    ] {
+        value["configurators"] = bad;
+        assert!(parse_manifest(&serde_json::to_string(&value).unwrap()).is_err());
+    }
+}
+
+#[test]
+fn env_overlay_deep_merges_overridable_fields() {
+    let raw = fixture("valid.component.json");
+    let overlay = serde_json::json!({
+        "limits": { "memory_mb": 64 },
+        "capabilities": { "host": { "http": null } },
+        "telemetry": { "attributes": { "env": "prod" } }
+    });
+    let manifest = parse_manifest_with_overlay(&raw, &overlay.to_string()).unwrap();
+    assert_eq!(manifest.limits.as_ref().unwrap().memory_mb, 64);
+    assert_eq!(manifest.limits.as_ref().unwrap().files, Some(16));
+    assert!(manifest.capabilities.host.http.is_none());
+    let attributes = &manifest.telemetry.as_ref().unwrap().attributes;
+    assert_eq!(
+        attributes.get("component").map(String::as_str),
+        Some("echo")
+    );
+    assert_eq!(attributes.get("env").map(String::as_str), Some("prod"));
+}
+
+#[test]
+fn env_overlay_rejects_non_overridable_fields() {
+    let raw = fixture("valid.component.json");
+    match parse_manifest_with_overlay(&raw, r#"{"id":"com.other"}"#).unwrap_err() {
+        ManifestError::NonOverridableField(field) => assert_eq!(field, "id"),
+        err => panic!("expected NonOverridableField, got {err:?}"),
+    }
+    match parse_manifest_with_overlay(&raw, r#"{"capabilities":{"extra":{}}}"#).unwrap_err() {
+        ManifestError::NonOverridableField(field) => assert_eq!(field, "capabilities.extra"),
+        err => panic!("expected NonOverridableField, got {err:?}"),
+    }
+}
+
+#[test]
+fn overlay_path_follows_manifest_name() {
+    let path = overlay_path(Path::new("demo/component.manifest.json"), "prod").unwrap();
+    assert_eq!(
+        path,
+        Path::new("demo/component.manifest.override.prod.json")
+    );
+    for env in ["../x", "prod/../../etc", "", "a b"] {
+        match overlay_path(Path::new("demo/component.manifest.json"), env).unwrap_err() {
+            ManifestError::InvalidEnvironment(name) => assert_eq!(name, env),
+            err => panic!("expected InvalidEnvironment, got {err:?}"),
+        }
+    }
+}
+
+fn manifest_with_input_ref(dir: &Path, reference: &str) -> Value {
+    let mut value: Value = serde_json::from_str(&fixture("valid.component.json")).unwrap();
+    value["operations"][0]["input_schema"] = serde_json::json!({ "$ref": reference });
+    fs::create_dir_all(dir.join("schemas/io")).unwrap();
+    value
+}
+
+#[test]
+fn external_schema_refs_are_inlined() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut value = manifest_with_input_ref(dir.path(), "schemas/io/input.schema.json");
+    fs::write(
+        dir.path().join("schemas/io/input.schema.json"),
+        r#"{"type":"object","properties":{"message":{"$ref":"defs.json#/$defs/message"}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("schemas/io/defs.json"),
+        r#"{"$defs":{"message":{"type":"string"}}}"#,
+    )
+    .unwrap();
+
+    resolve_schema_refs(&mut value, dir.path()).expect("refs resolve");
+    assert_eq!(
+        value["operations"][0]["input_schema"],
+        serde_json::json!({
+            "type": "object",
+            "properties": { "message": { "type": "string" } }
+        })
+    );
+    parse_manifest_value(value).expect("resolved manifest parses");
+}
+
+#[test]
+fn local_refs_inside_external_files_are_inlined_or_bundled() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut value = manifest_with_input_ref(dir.path(), "schemas/io/input.schema.json");
+    value["operations"][0]["output_schema"] = serde_json::json!({ "$ref": "schemas/io/tree.json" });
+    fs::write(
+        dir.path().join("schemas/io/input.schema.json"),
+        r##"{"type":"object","properties":{"message":{"$ref":"#/$defs/x"}},"$defs":{"x":{"type":"string"}}}"##,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("schemas/io/tree.json"),
+        r##"{"type":"object","properties":{"children":{"type":"array","items":{"$ref":"#"}}}}"##,
+    )
+    .unwrap();
+
+    resolve_schema_refs(&mut value, dir.path()).expect("refs resolve");
+    let inline = serde_json::json!({
+        "type": "object",
+        "properties": { "message": { "type": "string" } }
+    });
+    assert_eq!(value["operations"][0]["input_schema"], inline);
+
+    let output = &value["operations"][0]["output_schema"];
+    assert_eq!(
+        output["properties"]["children"]["items"]["$ref"],
+        "#/$defs/schemas~1io~1tree.json"
+    );
+    assert_eq!(
+        output["$defs"]["schemas/io/tree.json"]["properties"]["children"]["items"]["$ref"],
+        "#/$defs/schemas~1io~1tree.json"
+    );
+    let validator = jsonschema::validator_for(output).expect("bundled schema compiles");
+    assert!(validator.is_valid(&serde_json::json!({ "children": [{ "children": [] }] })));
+    assert!(!validator.is_valid(&serde_json::json!({ "children": [{ "children": 1 }] })));
+
+    // Hashes see the same schema however it was written.
+    let with_defs = serde_json::json!({
+        "type": "object",
+        "properties": { "message": { "$ref": "#/$defs/x" } },
+        "$defs": { "x": { "type": "string" } }
+    });
+    assert_eq!(inline_local_refs(&with_defs), inline);
+    parse_manifest_value(value).expect("resolved manifest parses");
+}
+
+#[test]
+fn external_schema_ref_cycles_are_detected() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut value = manifest_with_input_ref(dir.path(), "schemas/io/a.json");
+    fs::write(
+        dir.path().join("schemas/io/a.json"),
+        r#"{"type":"object","properties":{"b":{"$ref":"b.json"}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("schemas/io/b.json"),
+        r#"{"type":"object","properties":{"a":{"$ref":"a.json"}}}"#,
+    )
+    .unwrap();
+
+    match resolve_schema_refs(&mut value, dir.path()).unwrap_err() {
+        ManifestError::SchemaRefCycle(chain) => assert!(chain.contains("a.json"), "{chain}"),
+        err => panic!("expected SchemaRefCycle, got {err:?}"),
+    }
+}
+
+#[test]
+fn external_schema_refs_must_stay_in_manifest_dir() {
+    let outer = tempfile::tempdir().unwrap();
+    let dir = outer.path().join("component");
+    fs::write(outer.path().join("secret.json"), r#"{"type":"object"}"#).unwrap();
+    let mut value = manifest_with_input_ref(&dir, "../secret.json");
+
+    match resolve_schema_refs(&mut value, &dir).unwrap_err() {
+        ManifestError::SchemaRef { reference, .. } => assert_eq!(reference, "../secret.json"),
+        err => panic!("expected SchemaRef error, got {err:?}"),
+    }
+}
+
+#[test]
+fn operation_deprecations_are_parsed_and_validated() {
+    let mut value: Value = serde_json::from_str(&fixture("valid.component.json")).unwrap();
+    value["operations"][0]["deprecated"] =
+        serde_json::json!({ "since": "0.3.0", "message": "use the v2 endpoint" });
+    let manifest = parse_manifest_value(value.clone()).expect("deprecation accepted");
+    let operation = &manifest.operations[0].name;
+    let deprecated = manifest
+        .operation_deprecation(operation)
+        .expect("deprecation recorded");
+    assert_eq!(deprecated.since.as_deref(), Some("0.3.0"));
+
+    value["operations"][0]["deprecated"]["replacement"] = Value::String("missing_op".into());
+    match parse_manifest_value(value).unwrap_err() {
+        ManifestError::InvalidDeprecation { subject, reason } => {
+            assert_eq!(&subject, operation);
+            assert!(reason.contains("missing_op"), "{reason}");
+        }
+        err => panic!("expected InvalidDeprecation, got {err:?}"),
+    }
+}
+
+#[test]
+fn capability_presets_expand_with_overrides() {
+    let mut value: Value = serde_json::from_str(&fixture("valid.component.json")).unwrap();
+    value["capabilities"] = serde_json::json!({
+        "preset": "stateful-messaging",
+        "host": { "messaging": { "outbound": false }, "telemetry": null }
+    });
+    let manifest = parse_manifest_value(value.clone()).expect("preset expands");
+    let host = &manifest.capabilities.host;
+    let state = host.state.as_ref().expect("preset grants state");
+    assert!(state.read && state.write);
+    let messaging = host.messaging.as_ref().expect("preset grants messaging");
+    assert!(messaging.inbound && !messaging.outbound);
+    assert!(host.telemetry.is_none());
+    assert!(manifest.capabilities.wasi.clocks);
+
+    value["capabilities"] = serde_json::json!({ "preset": "root-everything" });
+    assert!(parse_manifest_value(value).is_err());
+}
+
+#[test]
+fn net_capabilities_are_parsed_and_validated() {
+    let mut value: Value = serde_json::from_str(&fixture("valid.component.json")).unwrap();
+    assert!(parse_manifest_value(value.clone()).unwrap().net.is_none());
+
+    value["capabilities"]["net"] = serde_json::json!({
+        "tcp": true,
+        "hosts": ["smtp.example.com"]
+    });
+    let manifest = parse_manifest_value(value.clone()).expect("net accepted");
+    let net = manifest.net.expect("net parsed");
+    assert!(net.tcp && !net.udp);
+    assert_eq!(net.hosts, vec!["smtp.example.com".to_string()]);
+
+    value["capabilities"]["net"] = serde_json::json!({ "udp": true, "hosts": [] });
+    match parse_manifest_value(value).unwrap_err() {
+        ManifestError::Capability(reason) => assert!(reason.contains("net.hosts"), "{reason}"),
+        err => panic!("expected Capability error, got {err:?}"),
+    }
+}
+
+#[test]
+fn validation_options_compile_embedded_schemas() {
+    let mut value: Value = serde_json::from_str(&fixture("valid.component.json")).unwrap();
+    value["operations"][0]["input_schema"]["properties"]["callback"] =
+        serde_json::json!({ "type": "string", "format": "uri", "default": "not a uri" });
+
+    parse_manifest_value_with(value.clone(), &ValidationOptions::default())
+        .expect("format is an annotation by default");
+    let err = parse_manifest_value_with(
+        value.clone(),
+        &ValidationOptions::default().assert_formats(true),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(&err, ManifestError::InvalidSchema { field, reason }
+            if field == "operations.handle_message.input_schema"
+                && reason.contains("/properties/callback/default")),
+        "{err}"
+    );
+
+    let err = parse_manifest_value_with(
+        value,
+        &ValidationOptions::default().with_draft(SchemaDraft::Draft7),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("pinned to draft-07"), "{err}");
+}
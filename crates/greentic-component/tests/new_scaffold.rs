@@ -325,3 +325,51 @@ REAL_CARGO="$(command -v cargo)"
             .or(predicates::str::contains("failed to load component")),
     );
 }
+
+#[cfg(unix)]
+#[test]
+fn new_runs_template_post_generation_hooks() {
+    let temp = TempDir::new().expect("temp dir");
+    let template_root = temp.path().join("templates");
+    let template = template_root.join("hooked");
+    fs::create_dir_all(&template).expect("template dir");
+    fs::write(template.join("README.md.hbs"), "# {{name}}\n").expect("readme");
+    fs::write(
+        template.join("template.json"),
+        r#"{
+            "id": "hooked",
+            "hooks": [{ "name": "marker", "run": ["sh", "-c", "echo done > hooked.txt"] }]
+        }"#,
+    )
+    .expect("template json");
+
+    let scaffold = |name: &str, extra: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("greentic-component"));
+        cmd.arg("new")
+            .arg("--name")
+            .arg(name)
+            .arg("--template")
+            .arg("hooked")
+            .arg("--path")
+            .arg(temp.path().join(name))
+            .args(["--no-check", "--no-git", "--json"])
+            .args(extra)
+            .env("HOME", temp.path())
+            .env("GREENTIC_TEMPLATE_ROOT", &template_root);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        serde_json::from_slice::<JsonValue>(&output).expect("json output")
+    };
+
+    let unconfirmed = scaffold("unconfirmed-hooks", &["--non-interactive"]);
+    assert_eq!(unconfirmed["hooks"][0]["status"], "skipped");
+    assert!(!temp.path().join("unconfirmed-hooks/hooked.txt").exists());
+
+    let ran = scaffold("with-hooks", &["--non-interactive", "--run-hooks"]);
+    assert_eq!(ran["hooks"][0]["name"], "marker");
+    assert_eq!(ran["hooks"][0]["status"], "ok");
+    assert!(temp.path().join("with-hooks/hooked.txt").exists());
+
+    let skipped = scaffold("without-hooks", &["--non-interactive", "--no-hooks"]);
+    assert_eq!(skipped["hooks"][0]["status"], "skipped");
+    assert!(!temp.path().join("without-hooks/hooked.txt").exists());
+}
@@ -0,0 +1,103 @@
+use component_manifest::ComponentDependency;
+use greentic_component::{CompatPolicy, ComponentLocator, ComponentStore, PinAlgorithm};
+use tempfile::tempdir;
+use tokio::runtime::Runtime;
+
+fn dependency(id: &str, locator: Option<String>) -> ComponentDependency {
+    ComponentDependency {
+        id: id.to_string(),
+        version: "^0.1".to_string(),
+        locator,
+        exports: Vec::new(),
+    }
+}
+
+#[test]
+fn resolves_dependencies_from_sources_and_locators() {
+    Runtime::new().unwrap().block_on(async {
+        let td = tempdir().expect("tempdir");
+        let registered = td.path().join("registered.wasm");
+        let declared = td.path().join("declared.wasm");
+        std::fs::write(&registered, b"REGISTERED").unwrap();
+        std::fs::write(&declared, b"DECLARED").unwrap();
+
+        let mut store =
+            ComponentStore::with_cache_dir(Some(td.path().join("cache")), CompatPolicy::default());
+        store.add_fs("dep.registered", &registered);
+
+        let deps = vec![
+            dependency("dep.registered", Some("file:///does/not/exist.wasm".into())),
+            dependency(
+                "dep.declared",
+                Some(format!("file://{}", declared.display())),
+            ),
+        ];
+        let resolved = store.resolve_dependencies(&deps).await.expect("resolve");
+        assert_eq!(resolved[0].component.bytes.as_ref(), b"REGISTERED");
+        assert_eq!(resolved[1].component.bytes.as_ref(), b"DECLARED");
+
+        let err = store
+            .resolve_dependencies(&[dependency("dep.missing", None)])
+            .await
+            .expect_err("missing locator should fail");
+        assert!(err.to_string().contains("dep.missing"));
+
+        for locator in ["https://example.com/dep.wasm", "warg://acme/dep"] {
+            let err = store
+                .resolve_dependencies(&[dependency("dep.remote", Some(locator.into()))])
+                .await
+                .expect_err("unsupported schemes must not be read as paths");
+            assert!(
+                format!("{err:#}").contains("unsupported locator scheme"),
+                "{err:#}"
+            );
+        }
+    });
+}
+
+#[test]
+fn digests_pinned_in_locators_are_enforced() {
+    Runtime::new().unwrap().block_on(async {
+        let td = tempdir().expect("tempdir");
+        let component = td.path().join("pinned.wasm");
+        std::fs::write(&component, b"PINNED").unwrap();
+        let blake3 = blake3::hash(b"PINNED").to_hex().to_string();
+        let store =
+            ComponentStore::with_cache_dir(Some(td.path().join("cache")), CompatPolicy::default());
+
+        let pinned = format!("file://{}#blake3={blake3}", component.display());
+        let locator = ComponentLocator::parse(&pinned).expect("parse");
+        let pin = locator.pin().expect("pin parsed");
+        assert_eq!(pin.algorithm, PinAlgorithm::Blake3);
+        assert_eq!(locator.to_string(), pinned.trim_start_matches("file://"));
+        let resolved = store
+            .resolve_dependencies(&[dependency("dep.pinned", Some(pinned))])
+            .await
+            .expect("pinned fetch");
+        assert_eq!(resolved[0].component.bytes.as_ref(), b"PINNED");
+
+        let wrong = format!("file://{}#sha256={}", component.display(), "0".repeat(64));
+        let err = store
+            .resolve_dependencies(&[dependency("dep.wrong", Some(wrong))])
+            .await
+            .expect_err("mismatched pin");
+        assert!(
+            format!("{err:#}").contains("does not match its pinned digest"),
+            "{err:#}"
+        );
+
+        let oci = ComponentLocator::parse(&format!("oci://ghcr.io/org/comp@sha256:{blake3}"))
+            .expect("oci pin");
+        assert_eq!(
+            oci.pin().map(|pin| pin.algorithm),
+            Some(PinAlgorithm::Sha256)
+        );
+        for malformed in [
+            format!("file://{}#md5=00", component.display()),
+            format!("file://{}#sha256", component.display()),
+            "oci://ghcr.io/org/comp@sha256:nothex".to_string(),
+        ] {
+            assert!(ComponentLocator::parse(&malformed).is_err(), "{malformed}");
+        }
+    });
+}
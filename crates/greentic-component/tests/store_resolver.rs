@@ -0,0 +1,73 @@
+use component_manifest::WitCompat;
+use greentic_component::{CompatPolicy, ComponentLocator, ComponentStore, Resolver};
+use semver::VersionReq;
+use tempfile::tempdir;
+use tokio::runtime::Runtime;
+
+#[test]
+fn resolves_newest_compatible_version_from_directory() {
+    Runtime::new().unwrap().block_on(async {
+        let td = tempdir().expect("tempdir");
+        let registry = td.path().join("registry");
+        std::fs::create_dir(&registry).unwrap();
+        for name in ["0.1.0", "0.1.4", "v0.2.0", "0.3.0-rc.1", "1.0.0", "latest"] {
+            std::fs::write(registry.join(format!("{name}.wasm")), name.as_bytes()).unwrap();
+        }
+
+        let store =
+            ComponentStore::with_cache_dir(Some(td.path().join("cache")), CompatPolicy::default());
+        let mut resolver = Resolver::new(store);
+        resolver.add_dir("acme.translate", &registry);
+
+        let req = VersionReq::parse(">=0.1, <1").unwrap();
+        let resolved = resolver
+            .resolve("acme.translate", &req)
+            .await
+            .expect("resolve");
+        assert_eq!(resolved.version.to_string(), "0.2.0");
+        assert_eq!(
+            resolved.locator,
+            ComponentLocator::Fs {
+                path: registry.join("v0.2.0.wasm"),
+                pin: None,
+            }
+        );
+        assert!(resolved.digest.starts_with("sha256:"));
+
+        let err = resolver
+            .resolve("acme.translate", &VersionReq::parse("^2").unwrap())
+            .await
+            .expect_err("nothing matches ^2");
+        assert!(err.to_string().contains("no published version"), "{err}");
+
+        let strict = ComponentStore::with_cache_dir(
+            Some(td.path().join("cache")),
+            CompatPolicy {
+                required_abi_prefix: "greentic-abi-0".to_string(),
+                required_capabilities: vec!["needs:x".to_string()],
+                ..CompatPolicy::default()
+            },
+        );
+        let mut strict_resolver = Resolver::new(strict);
+        strict_resolver.add_dir("acme.translate", &registry);
+        let err = strict_resolver
+            .resolve("acme.translate", &req)
+            .await
+            .expect_err("compat policy rejects every candidate");
+        assert!(
+            err.to_string().contains("0.1.4: Missing capabilities"),
+            "{err}"
+        );
+
+        let wit_resolver = resolver.clone().with_wit_compat(WitCompat {
+            package: "greentic:component".to_string(),
+            min: "0.6.0".to_string(),
+            max: None,
+        });
+        let err = wit_resolver
+            .resolve("acme.translate", &req)
+            .await
+            .expect_err("fake artifacts carry no WIT version");
+        assert!(err.to_string().contains("WIT version found"), "{err}");
+    });
+}
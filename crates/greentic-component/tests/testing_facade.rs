@@ -0,0 +1,60 @@
+#![cfg(feature = "cli")]
+
+use greentic_component::test_harness::{PlannedStep, StepExpect, StepPlan};
+use greentic_component::testing::{
+    CaseRunner, HarnessBuilder, assert_output_eq, assert_output_matches,
+};
+use serde_json::json;
+
+const FIXTURE: &str = "tests/contract/fixtures/component_v0_6_0/component.manifest.json";
+
+#[test]
+fn harness_invokes_fixture_and_runs_plans() {
+    let harness = HarnessBuilder::from_manifest(FIXTURE)
+        .build()
+        .expect("harness for the contract fixture");
+    assert_eq!(
+        harness.manifest().default_operation.as_deref(),
+        Some("handle_message")
+    );
+
+    let output = harness
+        .invoke("handle_message", &json!({ "input": "hello" }))
+        .expect("invoke handle_message");
+    assert_output_matches(
+        &json!({ "echo": output, "ok": true }),
+        &json!({ "ok": true }),
+    );
+
+    let plan = StepPlan {
+        steps: vec![
+            PlannedStep {
+                op: "handle_message".to_string(),
+                input: Some(json!({ "input": "hello" })),
+                ..PlannedStep::default()
+            },
+            PlannedStep {
+                op: "handle_message".to_string(),
+                input: Some(json!({ "input": "hello" })),
+                expect: Some(StepExpect {
+                    code: Some("never.raised".to_string()),
+                    ..StepExpect::default()
+                }),
+                ..PlannedStep::default()
+            },
+        ],
+    };
+    let reports = CaseRunner::new(&harness).run(&plan);
+    assert_eq!(reports.len(), 2);
+    assert!(reports[0].failure.is_none(), "{:?}", reports[0]);
+    assert_eq!(
+        reports[1].failure.as_deref(),
+        Some("expected an error, but the step succeeded")
+    );
+}
+
+#[test]
+#[should_panic(expected = "/value: expected 1, got 2")]
+fn exact_assertion_reports_differences() {
+    assert_output_eq(&json!({ "value": 2 }), &json!({ "value": 1 }));
+}
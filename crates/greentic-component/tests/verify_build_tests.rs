@@ -0,0 +1,101 @@
+#![cfg(all(feature = "cli", unix))]
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+const WASM_BYTES: &[u8] = b"\0asm\x0d\0\x01\0";
+
+/// Stands in for cargo: writes the same bytes to the target dir on every run.
+fn write_fake_cargo(dir: &Path) -> std::path::PathBuf {
+    let script = "#!/bin/sh\nset -e\nout=\"$CARGO_TARGET_DIR/wasm32-wasip2/release\"\nmkdir -p \"$out\"\nprintf '\\000asm\\015\\000\\001\\000' > \"$out/example.wasm\"\n";
+    let path = dir.join("fake_cargo.sh");
+    fs::write(&path, script).expect("write fake cargo");
+    let mut perms = fs::metadata(&path).expect("metadata").permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(&path, perms).expect("chmod");
+    path
+}
+
+fn git(root: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=greentic",
+            "-c",
+            "user.email=dev@greentic.ai",
+        ])
+        .args(args)
+        .current_dir(root)
+        .status()
+        .expect("git should run");
+    assert!(status.success(), "git {args:?} failed");
+}
+
+fn write_manifest(root: &Path, hash: &str) {
+    let manifest = json!({
+        "id": "ai.greentic.example",
+        "name": "example",
+        "version": "0.1.0",
+        "world": "greentic:component/node@0.5.0",
+        "artifacts": { "component_wasm": "target/wasm32-wasip2/release/example.wasm" },
+        "hashes": { "component_wasm": hash }
+    });
+    fs::write(
+        root.join("component.manifest.json"),
+        serde_json::to_string_pretty(&manifest).unwrap(),
+    )
+    .expect("write manifest");
+}
+
+#[test]
+fn verify_build_compares_clean_rebuild_with_manifest_hash() {
+    let temp = TempDir::new().expect("tempdir");
+    let root = temp.path();
+    let tools = TempDir::new().expect("tools dir");
+    let fake_cargo = write_fake_cargo(tools.path());
+    let expected = format!("blake3:{}", blake3::hash(WASM_BYTES).to_hex());
+    write_manifest(root, &expected);
+    fs::write(root.join("Cargo.lock"), "version = 4\n").expect("write lockfile");
+    git(root, &["init", "--quiet"]);
+    git(root, &["add", "."]);
+    git(root, &["commit", "--quiet", "-m", "init"]);
+
+    let output = cargo_bin_cmd!("greentic-component")
+        .current_dir(root)
+        .args(["verify-build", "--json", "--cargo"])
+        .arg(&fake_cargo)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: Value = serde_json::from_slice(&output).expect("json report");
+    assert_eq!(report["rebuilt_blake3"], expected.as_str());
+    assert_eq!(report["matches_manifest"], true);
+    let kinds = report["nondeterminism"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|source| source["kind"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert!(kinds.contains(&"no-provenance"), "{kinds:?}");
+    assert!(kinds.contains(&"unpinned-toolchain"), "{kinds:?}");
+    assert!(!kinds.contains(&"missing-lockfile"), "{kinds:?}");
+
+    write_manifest(root, &format!("blake3:{}", "0".repeat(64)));
+    git(root, &["commit", "--quiet", "-am", "stale hash"]);
+    cargo_bin_cmd!("greentic-component")
+        .current_dir(root)
+        .args(["verify-build", "--cargo"])
+        .arg(&fake_cargo)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("MISMATCH"))
+        .stderr(predicate::str::contains("not bit-for-bit identical"));
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "abi")]
+
+use std::fs;
+
+use assert_fs::TempDir;
+use greentic_component::{WitConfigError, infer_config_from_wit};
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use serde_json::json;
+
+const WORLD_WIT: &str = r#"
+package demo:component;
+
+world component {
+    import settings: interface {
+        enum mode { fast, safe }
+
+        record config {
+            /// Greeting shown to users
+            /// @default("Hello")
+            title: string,
+            /// @default(5)
+            max-items: u32,
+            mode: mode,
+            /// @flow:hidden
+            internal-id: option<string>,
+        }
+    }
+}
+"#;
+
+fn wit_dir(source: &str) -> TempDir {
+    let temp = TempDir::new().expect("tempdir");
+    fs::write(temp.path().join("world.wit"), source).expect("write wit");
+    temp
+}
+
+#[test]
+fn infers_schema_defaults_and_docs_from_config_record() {
+    let temp = wit_dir(WORLD_WIT);
+    let inferred = infer_config_from_wit(temp.path(), Some("demo:component/component@0.1.0"))
+        .expect("infer config");
+
+    assert_eq!(inferred.world, "component");
+    assert_eq!(inferred.schema["additionalProperties"], json!(false));
+    assert_eq!(
+        inferred.schema["required"],
+        json!(["title", "max-items", "mode"])
+    );
+    let title = &inferred.schema["properties"]["title"];
+    assert_eq!(title["description"], "Greeting shown to users");
+    assert_eq!(title["default"], "Hello");
+    assert_eq!(inferred.schema["properties"]["max-items"]["default"], 5);
+    assert_eq!(
+        inferred.schema["properties"]["mode"]["enum"],
+        json!(["fast", "safe"])
+    );
+    assert_eq!(
+        inferred.schema["properties"]["internal-id"]["x_flow_hidden"],
+        true
+    );
+
+    let hidden = inferred
+        .fields
+        .iter()
+        .find(|field| field.name == "internal-id")
+        .expect("internal-id field");
+    assert!(hidden.hidden && !hidden.required);
+    assert_eq!(hidden.description, None);
+
+    let SchemaIr::Object {
+        properties,
+        required,
+        additional,
+    } = &inferred.schema_ir
+    else {
+        panic!("config should infer an object schema");
+    };
+    assert!(matches!(additional, AdditionalProperties::Forbid));
+    assert_eq!(required.len(), 3);
+    assert!(matches!(
+        properties.get("max-items"),
+        Some(SchemaIr::Int { .. })
+    ));
+    assert!(matches!(
+        properties.get("mode"),
+        Some(SchemaIr::Enum { values }) if values.len() == 2
+    ));
+}
+
+#[test]
+fn reports_missing_config_record() {
+    let temp = wit_dir(
+        r#"
+package demo:component;
+
+world component {
+    export run: func();
+}
+"#,
+    );
+    let err = infer_config_from_wit(temp.path(), None).expect_err("no config record");
+    assert!(matches!(err, WitConfigError::MissingConfig(world) if world == "component"));
+}
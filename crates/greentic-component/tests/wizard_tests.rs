@@ -1,6 +1,10 @@
 #![cfg(feature = "cli")]
 
-use greentic_component::cmd::wizard::{WizardCommand, WizardMode, WizardNewArgs, run};
+use greentic_component::cmd::op::{self, OpAddArgs};
+use greentic_component::cmd::secret::{self, SecretAddArgs};
+use greentic_component::cmd::wizard::{
+    WizardCapabilityArgs, WizardCommand, WizardMode, WizardNewArgs, run,
+};
 use std::fs;
 
 #[test]
@@ -14,6 +18,8 @@ fn wizard_new_creates_template_files() {
         out: Some(temp.path().to_path_buf()),
         required_capabilities: Vec::new(),
         provided_capabilities: Vec::new(),
+        hooks: Vec::new(),
+        capabilities: Default::default(),
     };
 
     run(WizardCommand::New(args)).expect("wizard new should succeed");
@@ -55,6 +61,8 @@ fn wizard_new_writes_answers_files_when_provided() {
         out: Some(temp.path().to_path_buf()),
         required_capabilities: Vec::new(),
         provided_capabilities: Vec::new(),
+        hooks: Vec::new(),
+        capabilities: Default::default(),
     };
 
     run(WizardCommand::New(args)).expect("wizard new should succeed");
@@ -84,6 +92,8 @@ fn wizard_new_embeds_declared_capabilities_in_descriptor() {
             "host.http.client".into(),
         ],
         provided_capabilities: vec!["telemetry.emit".into()],
+        hooks: Vec::new(),
+        capabilities: Default::default(),
     };
 
     run(WizardCommand::New(args)).expect("wizard new should succeed");
@@ -107,6 +117,8 @@ fn wizard_new_qa_apply_answers_enforces_mode_contracts() {
         out: Some(temp.path().to_path_buf()),
         required_capabilities: Vec::new(),
         provided_capabilities: Vec::new(),
+        hooks: Vec::new(),
+        capabilities: Default::default(),
     };
 
     run(WizardCommand::New(args)).expect("wizard new should succeed");
@@ -120,3 +132,205 @@ fn wizard_new_qa_apply_answers_enforces_mode_contracts() {
     assert!(qa_rs.contains("Mode::Remove => {"));
     assert!(qa_rs.contains("config.insert(\"enabled\".to_string(), JsonValue::Bool(false));"));
 }
+
+#[test]
+fn wizard_new_wires_selected_host_capabilities() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let args = WizardNewArgs {
+        name: "host-component".into(),
+        abi_version: "0.6.0".into(),
+        mode: WizardMode::Default,
+        answers: None,
+        out: Some(temp.path().to_path_buf()),
+        required_capabilities: Vec::new(),
+        provided_capabilities: Vec::new(),
+        hooks: Vec::new(),
+        capabilities: WizardCapabilityArgs {
+            http: true,
+            state: true,
+            secrets: vec!["API_TOKEN".into()],
+            fs: vec!["assets=/assets".into()],
+        },
+    };
+
+    run(WizardCommand::New(args)).expect("wizard new should succeed");
+
+    let root = temp.path().join("host-component");
+    let manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(root.join("component.manifest.json")).unwrap())
+            .expect("manifest stays valid JSON");
+    let capabilities = &manifest["capabilities"];
+    assert_eq!(capabilities["host"]["http"]["client"], true);
+    assert_eq!(capabilities["host"]["state"]["write"], true);
+    assert_eq!(
+        capabilities["host"]["secrets"]["required"][0]["key"],
+        "API_TOKEN"
+    );
+    assert_eq!(manifest["secret_requirements"][0]["key"], "API_TOKEN");
+    assert_eq!(capabilities["wasi"]["filesystem"]["mode"], "sandbox");
+    assert_eq!(
+        capabilities["wasi"]["filesystem"]["mounts"][0]["guest_path"],
+        "/assets"
+    );
+
+    let cargo_toml = fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(cargo_toml.contains(
+        r#"features = ["component-v0-6", "runner-host-v1", "state-store-v1", "secrets-store-v1"]"#
+    ));
+    let runtime = fs::read_to_string(root.join("src/runtime.rs")).unwrap();
+    for call in [
+        "visits()",
+        "secret_status(\"API_TOKEN\")",
+        "http_status()",
+        "mount_entries(\"/assets\")",
+    ] {
+        assert!(runtime.contains(call), "missing {call}");
+    }
+    let descriptor = fs::read_to_string(root.join("src/descriptor.rs")).unwrap();
+    assert!(descriptor.contains("\"host.state.write\""));
+}
+
+#[test]
+fn wizard_new_rejects_malformed_fs_mount() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let args = WizardNewArgs {
+        name: "bad-mount".into(),
+        abi_version: "0.6.0".into(),
+        mode: WizardMode::Default,
+        answers: None,
+        out: Some(temp.path().to_path_buf()),
+        required_capabilities: Vec::new(),
+        provided_capabilities: Vec::new(),
+        hooks: Vec::new(),
+        capabilities: WizardCapabilityArgs {
+            fs: vec!["assets".into()],
+            ..Default::default()
+        },
+    };
+
+    let err = run(WizardCommand::New(args)).expect_err("mount without path");
+    assert!(err.to_string().contains("mount=path"), "{err}");
+}
+
+#[test]
+fn op_add_extends_wizard_scaffold() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let args = WizardNewArgs {
+        name: "ops-component".into(),
+        abi_version: "0.6.0".into(),
+        mode: WizardMode::Default,
+        answers: None,
+        out: Some(temp.path().to_path_buf()),
+        required_capabilities: Vec::new(),
+        provided_capabilities: Vec::new(),
+        hooks: Vec::new(),
+        capabilities: Default::default(),
+    };
+    run(WizardCommand::New(args)).expect("wizard new should succeed");
+
+    let root = temp.path().join("ops-component");
+    let input = temp.path().join("lookup.input.json");
+    let output = temp.path().join("lookup.output.json");
+    fs::write(
+        &input,
+        r#"{"type":"object","required":["key"],"properties":{"key":{"type":"string","minLength":1}},"additionalProperties":false}"#,
+    )
+    .unwrap();
+    fs::write(
+        &output,
+        r#"{"type":"object","properties":{"hits":{"type":"array","items":{"type":"integer","minimum":0}}}}"#,
+    )
+    .unwrap();
+    let add = OpAddArgs {
+        name: "lookup".into(),
+        input,
+        output,
+        manifest: root.join("component.manifest.json"),
+    };
+    op::add(&add).expect("op add should succeed");
+
+    let manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(root.join("component.manifest.json")).unwrap())
+            .unwrap();
+    let operations = manifest["operations"].as_array().unwrap();
+    assert_eq!(operations.len(), 2);
+    assert_eq!(operations[1]["name"], "lookup");
+    assert_eq!(operations[1]["input_schema"]["required"][0], "key");
+
+    let schema = fs::read_to_string(root.join("src/schema.rs")).unwrap();
+    assert!(schema.contains("pub fn lookup_input_schema() -> SchemaIr"));
+    assert!(schema.contains("min_len: Some(1)"));
+    assert!(schema.contains("items: Box::new(SchemaIr::Int"));
+    let descriptor = fs::read_to_string(root.join("src/descriptor.rs")).unwrap();
+    assert!(descriptor.contains("lookup_operation(&config_schema),"));
+    assert!(descriptor.contains("id: \"lookup\".to_string()"));
+    let lib = fs::read_to_string(root.join("src/lib.rs")).unwrap();
+    assert!(lib.contains("\"lookup\" => runtime::lookup(envelope.payload_cbor, Vec::new()).0,"));
+    assert!(lib.contains("schema::lookup_input_schema_cbor()"));
+    let runtime = fs::read_to_string(root.join("src/runtime.rs")).unwrap();
+    assert!(runtime.contains("pub fn lookup(input: Vec<u8>, state: Vec<u8>)"));
+    assert!(runtime.contains("fn lookup_returns_a_cbor_map()"));
+
+    let err = op::add(&add).expect_err("duplicate operation is rejected");
+    assert!(err.to_string().contains("already exists"), "{err}");
+}
+
+#[test]
+fn secret_add_declares_and_wires_secret() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let args = WizardNewArgs {
+        name: "secret-component".into(),
+        abi_version: "0.6.0".into(),
+        mode: WizardMode::Default,
+        answers: None,
+        out: Some(temp.path().to_path_buf()),
+        required_capabilities: Vec::new(),
+        provided_capabilities: Vec::new(),
+        hooks: Vec::new(),
+        capabilities: Default::default(),
+    };
+    run(WizardCommand::New(args)).expect("wizard new should succeed");
+
+    let root = temp.path().join("secret-component");
+    let add = SecretAddArgs {
+        key: "API_TOKEN".into(),
+        format: "text".into(),
+        scope: vec!["prod".into(), "acme".into()],
+        optional: false,
+        manifest: root.join("component.manifest.json"),
+        secrets_file: "examples/secrets.env".into(),
+    };
+    let report = secret::add(&add).expect("secret add should succeed");
+    assert!(report.skipped.is_empty(), "{:?}", report.skipped);
+
+    let manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(root.join("component.manifest.json")).unwrap())
+            .unwrap();
+    assert_eq!(manifest["secret_requirements"][0]["key"], "API_TOKEN");
+    assert_eq!(manifest["secret_requirements"][0]["scope"]["env"], "prod");
+    assert_eq!(
+        manifest["secret_requirements"][0]["scope"]["tenant"],
+        "acme"
+    );
+    assert_eq!(
+        manifest["capabilities"]["host"]["secrets"]["required"][0]["key"],
+        "API_TOKEN"
+    );
+
+    let qa = fs::read_to_string(root.join("src/qa.rs")).unwrap();
+    assert!(qa.contains(
+        r#"question_secret("api_token", "qa.setup.secret.api_token.label", "qa.setup.secret.api_token.help"),"#
+    ));
+    assert!(qa.contains("fn question_secret("));
+    let i18n = fs::read_to_string(root.join("src/i18n.rs")).unwrap();
+    assert!(i18n.contains("\"qa.setup.secret.api_token.label\","));
+    let bundle: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(root.join("assets/i18n/en.json")).unwrap())
+            .unwrap();
+    assert_eq!(bundle["qa.setup.secret.api_token.label"], "API_TOKEN");
+    let secrets = fs::read_to_string(root.join("examples/secrets.env")).unwrap();
+    assert!(secrets.lines().any(|line| line == "API_TOKEN="));
+
+    let err = secret::add(&add).expect_err("duplicate secret is rejected");
+    assert!(err.to_string().contains("already declared"), "{err}");
+}